@@ -0,0 +1,158 @@
+// Run with `cargo bench --bench lookup`. To compare a change against the
+// tree before it, run `cargo bench --bench lookup -- --save-baseline before`
+// there, then `cargo bench --bench lookup -- --baseline before` with it.
+
+use std::convert::Infallible;
+use std::hint::black_box;
+use std::time::Duration;
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use futures::FutureExt;
+use futures::stream;
+use wiremock::matchers::method;
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use cybersec_wow::common;
+use cybersec_wow::metrics::Metrics;
+use cybersec_wow::pwned::{
+    InFlight, ProxySettings, RangeClient, Settings, SocksSettings, build_client, parse_endpoint,
+};
+use cybersec_wow::range::{find_count, find_suffix, parse_range};
+use cybersec_wow::rate_limit::RateLimiter;
+use cybersec_wow::{HashMode, Normalization, hash_password, hash_suffix_of};
+
+/// A real-sized range body for prefix `F3BBB`: a thousand `SUFFIX:COUNT`
+/// lines, about 40KB, CRLF-terminated as the API sends them.
+const RANGE: &str = include_str!("../tests/fixtures/range-F3BBB.txt");
+
+/// Listed in the fixture.
+const FOUND: &str = "hunter2";
+/// Shares the fixture's prefix but isn't listed in it.
+const NOT_FOUND: &str = "F3BBB00000000000000000000000000000000000";
+
+fn hashing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hash_password");
+    let passwords = [
+        ("short", "hunter2".to_owned()),
+        ("long", "correct horse battery staple ".repeat(8)),
+        ("unicode", "Ｐässwörd—ñ🔑".repeat(4)),
+    ];
+    for (name, password) in &passwords {
+        for (id, mode, normalization) in [
+            ("sha1", HashMode::Sha1, Normalization::Off),
+            ("sha1_nfkc", HashMode::Sha1, Normalization::Nfkc),
+            ("ntlm", HashMode::Ntlm, Normalization::Off),
+        ] {
+            group.bench_with_input(BenchmarkId::new(id, name), password, |b, password| {
+                b.iter(|| hash_password(black_box(password), mode, normalization))
+            });
+        }
+    }
+    group.finish();
+}
+
+fn parsing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("range");
+    group.throughput(Throughput::Bytes(RANGE.len() as u64));
+    group.bench_function("parse_range", |b| {
+        b.iter(|| parse_range(black_box(RANGE)).unwrap())
+    });
+
+    let found = hash_password(FOUND, HashMode::Sha1, Normalization::Off);
+    for (name, hash) in [("found", found.as_str()), ("not_found", NOT_FOUND)] {
+        let suffix = hash_suffix_of(hash);
+        // Timing the wrong case would hide a regression in the other one.
+        assert_eq!(
+            find_count(RANGE, suffix).unwrap().is_some(),
+            name == "found",
+            "{name}"
+        );
+        group.bench_with_input(
+            BenchmarkId::new("parse_and_get", name),
+            suffix,
+            |b, suffix| b.iter(|| parse_range(black_box(RANGE)).unwrap().get(suffix)),
+        );
+        // What `find_count` replaces for a body already in memory.
+        group.bench_with_input(BenchmarkId::new("find_count", name), suffix, |b, suffix| {
+            b.iter(|| find_count(black_box(RANGE), suffix))
+        });
+        group.bench_with_input(
+            BenchmarkId::new("find_suffix", name),
+            suffix,
+            |b, suffix| {
+                b.iter(|| {
+                    let chunks = stream::iter([Ok::<_, Infallible>(black_box(RANGE))]);
+                    find_suffix(chunks, suffix).now_or_never().unwrap()
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+/// The per-keystroke check against the bundled list, once it's loaded.
+fn common_rank(c: &mut Criterion) {
+    let mut group = c.benchmark_group("common");
+    common::rank("");
+    for (name, password) in [
+        ("listed", FOUND),
+        ("unlisted", "correct horse battery staple"),
+        ("long", &"x".repeat(256)),
+    ] {
+        group.bench_with_input(BenchmarkId::new("rank", name), password, |b, password| {
+            b.iter(|| common::rank(black_box(password)))
+        });
+    }
+    group.finish();
+}
+
+/// Back-to-back lookups against a local server, with one client kept
+/// across them as the app does, and with a new one (and so a new
+/// connection) for each.
+fn clients(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let server = runtime.block_on(async {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(RANGE))
+            .mount(&server)
+            .await;
+        server
+    });
+    let settings = Settings {
+        endpoint: parse_endpoint(&server.uri()).unwrap(),
+        padding: false,
+        decoys: false,
+        decoy_count: 0,
+        timeout: Duration::from_secs(10),
+        proxy: ProxySettings::default(),
+        socks: SocksSettings::default(),
+        rate_limit: u32::MAX,
+    };
+    let range_client = |http| {
+        RangeClient::new(
+            http,
+            settings.clone(),
+            InFlight::default(),
+            Metrics::default(),
+            RateLimiter::default(),
+        )
+    };
+    let hash = hash_password(FOUND, HashMode::Sha1, Normalization::Off);
+
+    let mut group = c.benchmark_group("search");
+    let shared = range_client(build_client(&settings).unwrap());
+    group.bench_function("shared_client", |b| {
+        b.iter(|| runtime.block_on(shared.clone().search(hash.clone(), HashMode::Sha1)))
+    });
+    group.bench_function("client_per_search", |b| {
+        b.iter(|| {
+            let client = range_client(build_client(&settings).unwrap());
+            runtime.block_on(client.search(hash.clone(), HashMode::Sha1))
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, hashing, parsing, common_rank, clients);
+criterion_main!(benches);
@@ -0,0 +1,44 @@
+#![no_main]
+
+use std::convert::Infallible;
+
+use futures::{FutureExt, stream};
+use libfuzzer_sys::fuzz_target;
+
+use cybersec_wow::BreachResult;
+use cybersec_wow::range::{find_suffix, parse_range};
+
+// Run with `cargo +nightly fuzz run range`, seeding the corpus with
+// tests/fixtures/range-F3BBB.txt.
+//
+// Whatever comes back from the network, the parser must not panic, and the
+// streaming lookup must agree with the parsed range on every entry in it.
+fuzz_target!(|data: &[u8]| {
+    let Some((&chunk_len, body)) = data.split_first() else {
+        return;
+    };
+    let chunks = || {
+        let chunks: Vec<_> = body
+            .chunks(usize::from(chunk_len).max(1))
+            .map(Ok::<_, Infallible>)
+            .collect();
+        stream::iter(chunks)
+    };
+    let text = String::from_utf8_lossy(body);
+
+    let Ok(range) = parse_range(&text) else {
+        let _ = find_suffix(chunks(), "0018A45C4D1DEF81644B54AB7F969B88D65").now_or_never();
+        return;
+    };
+    // Any string may be handed over as a hash, however short.
+    let start: String = text.chars().take(40).collect();
+    for hash in [&start, "", "F3BB"] {
+        let _ = BreachResult::from_range(&range, hash);
+    }
+    if std::str::from_utf8(body).is_ok() {
+        for (suffix, count) in range.iter().take(16) {
+            let found = find_suffix(chunks(), suffix).now_or_never().unwrap();
+            assert_eq!(found, Ok(Ok(Some(count))), "{suffix}");
+        }
+    }
+});
@@ -0,0 +1,17 @@
+#!/bin/sh
+# Checks that the library builds and passes its tests without the app, that
+# either TLS backend builds, and that nothing of the GUI stack comes along
+# when it's left out. Run from anywhere in the repository.
+set -eu
+cd "$(dirname "$0")/.."
+
+cargo check --no-default-features --all-targets
+cargo check --no-default-features --features rustls,socks --lib
+cargo test --no-default-features --lib
+
+for crate in iced wgpu winit; do
+    if cargo tree --no-default-features -e normal -i "$crate" >/dev/null 2>&1; then
+        echo "$crate is built without the gui feature" >&2
+        exit 1
+    fi
+done
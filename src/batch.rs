@@ -0,0 +1,1247 @@
+use std::cmp::Ordering;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::mem;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use futures::future;
+use futures::stream::{self, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+
+use crate::bloom;
+use crate::cache::DiskCache;
+use crate::hash::normalize_hash;
+use crate::import::Malformed;
+use crate::pwned::RangeClient;
+use crate::secret::SecretString;
+use crate::severity::{Severity, Thresholds};
+use crate::{HashMode, Normalization, hash_password, hash_prefix_of, hash_suffix_of};
+
+/// Most lines an imported file may have, so a wrong pick can't tie the app up.
+pub const MAX_IMPORT_LINES: usize = 1_000_000;
+
+/// Largest file accepted for import, checked before it's read.
+pub const MAX_IMPORT_BYTES: u64 = 64 * 1024 * 1024;
+
+/// A non-empty line of a pasted batch.
+#[derive(Clone, Debug)]
+pub struct Line {
+    /// 1-based, counting the empty lines that were skipped.
+    pub number: usize,
+    /// What an imported password manager entry is called; empty for a
+    /// plain list.
+    pub label: String,
+    /// The imported entry's username, for a policy that forbids it; empty
+    /// when there's none.
+    pub username: String,
+    pub preview: String,
+    /// Index of the line's password in [`Lines::passwords`].
+    pub password: usize,
+}
+
+/// A pasted batch with empty lines dropped and repeated passwords folded
+/// together, so each is only checked once.
+#[derive(Default)]
+pub struct Lines {
+    /// Wiped as each is dropped, so none outlives the batch it was read for.
+    pub passwords: Vec<SecretString>,
+    pub lines: Vec<Line>,
+    /// Rows of a password manager export, or lines of a hash list, that
+    /// couldn't be read.
+    pub malformed: Vec<Malformed>,
+    /// Set for a hash list: `passwords` are then uppercase hashes of this
+    /// kind, to be looked up as they are.
+    pub hashed: Option<HashMode>,
+    /// Set for a directory dump, whose lines are user accounts rather than
+    /// sites.
+    pub accounts: bool,
+}
+
+impl fmt::Debug for Lines {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Lines")
+            .field("passwords", &self.passwords.len())
+            .field("lines", &self.lines)
+            .field("malformed", &self.malformed)
+            .field("hashed", &self.hashed)
+            .field("accounts", &self.accounts)
+            .finish()
+    }
+}
+
+/// Reads an imported password list: UTF-8, one password per line, with an
+/// optional byte order mark.
+pub fn read_list(bytes: &[u8]) -> Result<Lines, String> {
+    Ok(split_lines(list_text(bytes)?))
+}
+
+/// The text of an imported list, checked against [`MAX_IMPORT_LINES`].
+pub(crate) fn list_text(bytes: &[u8]) -> Result<&str, String> {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    let text = std::str::from_utf8(bytes).map_err(|error| {
+        format!(
+            "the file isn't UTF-8 text (invalid byte at offset {})",
+            error.valid_up_to()
+        )
+    })?;
+    let lines = text.lines().count();
+    if lines > MAX_IMPORT_LINES {
+        return Err(format!(
+            "the file has {lines} lines, more than the {MAX_IMPORT_LINES} a batch allows"
+        ));
+    }
+    Ok(text)
+}
+
+/// Reads a list of `mode` hashes, one per line in either case, each
+/// optionally followed by `:label`. Blank lines and those starting with `#`
+/// are skipped, and a line that isn't a hash of the right length is left
+/// in [`Lines::malformed`] rather than failing the whole list. A hash
+/// listed twice is looked up once, as a repeated password is.
+pub fn read_hashes(bytes: &[u8], mode: HashMode) -> Result<Lines, String> {
+    let entries = text_lines(list_text(bytes)?).filter_map(|(number, line)| {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let (hash, label) = line.split_once(':').unwrap_or((line, ""));
+        Some((number, Ok((hash, label.trim().to_owned()))))
+    });
+    Ok(hash_lines(mode, entries))
+}
+
+/// The lines of `text`, numbered from 1, ended by `\n`, `\r\n` or a lone
+/// `\r`, so a file whose line endings were mangled along the way reads the
+/// same as one that wasn't.
+pub(crate) fn text_lines(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    (1..).zip(text.lines().flat_map(|line| line.split('\r')))
+}
+
+/// A hash list of `entries`: the number of each line read, with its hash in
+/// `mode` and label, or why it couldn't be read.
+pub(crate) fn hash_lines<'a>(
+    mode: HashMode,
+    entries: impl IntoIterator<Item = (usize, Result<(&'a str, String), String>)>,
+) -> Lines {
+    let mut seen = HashMap::new();
+    let mut batch = Lines {
+        hashed: Some(mode),
+        ..Lines::default()
+    };
+    for (number, entry) in entries {
+        let entry = entry.and_then(|(hash, label)| Ok((normalize_hash(hash, mode)?, label)));
+        let (hash, label) = match entry {
+            Ok(entry) => entry,
+            Err(reason) => {
+                batch.malformed.push(Malformed {
+                    line: number,
+                    reason,
+                });
+                continue;
+            }
+        };
+        let preview = format!("{mode} {}…", hash_prefix_of(&hash));
+        let password = *seen.entry(hash).or_insert_with_key(|hash| {
+            batch.passwords.push(hash.clone().into());
+            batch.passwords.len() - 1
+        });
+        batch.lines.push(Line {
+            number,
+            label,
+            username: String::new(),
+            preview,
+            password,
+        });
+    }
+    batch
+}
+
+pub fn split_lines(text: &str) -> Lines {
+    let mut seen = HashMap::new();
+    let mut batch = Lines::default();
+    for (number, line) in (1..).zip(text.lines()) {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if line.is_empty() {
+            continue;
+        }
+        let password = *seen.entry(line).or_insert_with(|| {
+            batch.passwords.push(line.to_owned().into());
+            batch.passwords.len() - 1
+        });
+        batch.lines.push(Line {
+            number,
+            label: String::new(),
+            username: String::new(),
+            preview: mask(line),
+            password,
+        });
+    }
+    batch
+}
+
+/// Folds together the passwords of `batch` that hash the same, as ones
+/// `normalization` makes equal do, so each hash is looked up once and
+/// every line with it counts as reuse. The first of each is kept, and the
+/// others are wiped. A hash list is already folded, and left as it is.
+pub fn fold_by_hash(batch: &mut Lines, mode: HashMode, normalization: Normalization) {
+    if batch.hashed.is_some() {
+        return;
+    }
+    let mut by_hash = HashMap::new();
+    let mut kept = Vec::new();
+    let mut remap = Vec::with_capacity(batch.passwords.len());
+    for password in mem::take(&mut batch.passwords) {
+        let hash = hash_password(password.expose(), mode, normalization);
+        // A repeat isn't kept, so it's dropped, and wiped, here.
+        let index = match by_hash.entry(hash) {
+            Entry::Occupied(entry) => *entry.get(),
+            Entry::Vacant(entry) => {
+                kept.push(password);
+                *entry.insert(kept.len() - 1)
+            }
+        };
+        remap.push(index);
+    }
+    batch.passwords = kept;
+    for line in &mut batch.lines {
+        line.password = remap[line.password];
+    }
+}
+
+/// The first character followed by an asterisk for each one after it.
+pub fn mask(password: &str) -> String {
+    let mut chars = password.chars();
+    chars.next().into_iter().chain(chars.map(|_| '*')).collect()
+}
+
+/// The outcome for the password at `index` of a batch.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BatchItem {
+    pub index: usize,
+    /// The hash prefix that was looked up, as sent to the API.
+    pub prefix: String,
+    pub checked_at: DateTime<Utc>,
+    pub outcome: Result<Option<u64>, String>,
+}
+
+/// Running totals for a batch, over its distinct passwords.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BatchStats {
+    pub total: usize,
+    pub done: usize,
+    pub breached: usize,
+    pub clean: usize,
+    pub errored: usize,
+    /// Distinct prefixes to fetch, a request each, and how many have been.
+    /// Items resumed from a checkpoint need none.
+    pub ranges: usize,
+    pub ranges_done: usize,
+    pub elapsed: Duration,
+}
+
+impl BatchStats {
+    fn record(&mut self, outcome: &Result<Option<u64>, String>, elapsed: Duration) {
+        self.done += 1;
+        match outcome {
+            Ok(Some(_)) => self.breached += 1,
+            Ok(None) => self.clean += 1,
+            Err(_) => self.errored += 1,
+        }
+        self.elapsed = elapsed;
+    }
+
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            return 1.;
+        }
+        self.done as f32 / self.total as f32
+    }
+
+    /// Extrapolated from the average time per fetched range, as it's the
+    /// requests that take the time; `None` until one has been fetched.
+    pub fn eta(&self) -> Option<Duration> {
+        if self.ranges_done == 0 {
+            return None;
+        }
+        let remaining = self.ranges.saturating_sub(self.ranges_done);
+        Some(
+            self.elapsed
+                .div_f64(self.ranges_done as f64)
+                .mul_f64(remaining as f64),
+        )
+    }
+}
+
+/// Which lines the batch results table shows.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Filter {
+    /// Matched against the label or the masked preview, ignoring case, or
+    /// the line number.
+    pub text: String,
+    pub breached_only: bool,
+    pub errors_only: bool,
+    /// Hides passwords seen fewer times than this; 0 for no minimum.
+    pub min_count: u64,
+}
+
+impl Filter {
+    pub fn is_active(&self) -> bool {
+        !self.text.trim().is_empty() || self.breached_only || self.errors_only || self.min_count > 0
+    }
+
+    /// Whether `line`, with `item` once its password is checked, is shown.
+    /// With both "breached only" and "errors only" on, either will do.
+    pub fn matches(&self, line: &Line, item: Option<&BatchItem>) -> bool {
+        let text = self.text.trim().to_lowercase();
+        if !text.is_empty()
+            && !line.label.to_lowercase().contains(&text)
+            && !line.preview.to_lowercase().contains(&text)
+            && line.number.to_string() != text
+        {
+            return false;
+        }
+        let outcome = item.map(|item| &item.outcome);
+        let breached = matches!(outcome, Some(Ok(Some(_))));
+        let errored = matches!(outcome, Some(Err(_)));
+        if (self.breached_only || self.errors_only)
+            && !(self.breached_only && breached || self.errors_only && errored)
+        {
+            return false;
+        }
+        self.min_count == 0 || matches!(outcome, Some(Ok(Some(count))) if *count >= self.min_count)
+    }
+}
+
+/// A column the batch results table can be sorted by.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortColumn {
+    /// The order the lines were pasted or imported in.
+    #[default]
+    Line,
+    Count,
+    Severity,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Sort {
+    pub column: SortColumn,
+    pub descending: bool,
+}
+
+impl Sort {
+    /// The sort after clicking `column`'s header: the other way round if the
+    /// table is already sorted by it, otherwise by it with the most exposed
+    /// passwords first.
+    pub fn toggled(self, column: SortColumn) -> Self {
+        if self.column == column {
+            return Sort {
+                descending: !self.descending,
+                ..self
+            };
+        }
+        Sort {
+            column,
+            descending: column != SortColumn::Line,
+        }
+    }
+}
+
+/// The order to list `lines` in, as indexes into it; the lines themselves
+/// stay in input order, and so do exports.
+///
+/// The sort is stable, so ties keep their input order and a row only moves
+/// when its own result arrives. Lines still being checked, and those that
+/// failed, come last whichever way the table is sorted.
+pub fn sorted_lines(
+    lines: &[Line],
+    results: &[Option<BatchItem>],
+    sort: Sort,
+    thresholds: &Thresholds,
+) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..lines.len()).collect();
+    if sort.column == SortColumn::Line {
+        if sort.descending {
+            order.reverse();
+        }
+        return order;
+    }
+    let key = |index: usize| {
+        let item = results.get(lines[index].password)?.as_ref()?;
+        let count = item.outcome.as_ref().ok()?.unwrap_or(0);
+        Some(match sort.column {
+            // Not found sorts below every tier.
+            SortColumn::Severity if count == 0 => 0,
+            SortColumn::Severity => Severity::from_count(count, thresholds) as u64 + 1,
+            _ => count,
+        })
+    };
+    order.sort_by(|&a, &b| match (key(a), key(b)) {
+        (Some(a), Some(b)) if sort.descending => b.cmp(&a),
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    });
+    order
+}
+
+/// The `mode` hash of each password of a batch, in order, to be checked.
+pub fn hash_all(
+    passwords: &[SecretString],
+    mode: HashMode,
+    normalization: Normalization,
+) -> Vec<String> {
+    passwords
+        .iter()
+        .map(|password| hash_password(password.expose(), mode, normalization))
+        .collect()
+}
+
+/// Checks every `mode` hash, uppercase, as [`hash_all`] or a hash list has
+/// them, fetching each distinct prefix once with at most `concurrency`
+/// requests in flight, each hidden among decoys if the client's settings
+/// say so. Items are yielded as their prefix completes, each with the
+/// totals so far, and a failed prefix only fails the items that share it.
+///
+/// The items in `done`, as resumed from a checkpoint, are yielded first and
+/// not looked up again. Then come those the offline dataset's `filter` says
+/// are certainly not in it, as not found without a request, so a range only
+/// all of whose hashes are ruled out isn't fetched at all.
+pub fn check_all(
+    client: RangeClient,
+    hashes: Vec<String>,
+    mode: HashMode,
+    disk_cache: Option<DiskCache>,
+    concurrency: usize,
+    done: Vec<BatchItem>,
+    filter: Option<Arc<bloom::Filter>>,
+) -> impl Stream<Item = (BatchItem, BatchStats)> + use<> {
+    let started = Instant::now();
+    let mut stats = BatchStats {
+        total: hashes.len(),
+        ..BatchStats::default()
+    };
+    let skipped: HashSet<usize> = done.iter().map(|item| item.index).collect();
+    let mut prefixes: HashMap<String, Vec<(usize, String)>> = HashMap::new();
+    let mut ruled_out = Vec::new();
+    for (index, hash) in hashes.into_iter().enumerate() {
+        if skipped.contains(&index) {
+            continue;
+        }
+        let prefix = hash_prefix_of(&hash).to_ascii_uppercase();
+        if let Some(filter) = &filter
+            && !filter.might_contain(&hash)
+        {
+            ruled_out.push(BatchItem {
+                index,
+                prefix,
+                checked_at: Utc::now(),
+                outcome: Ok(None),
+            });
+            continue;
+        }
+        prefixes.entry(prefix).or_default().push((index, hash));
+    }
+    stats.ranges = prefixes.len();
+
+    let checked =
+        stream::iter(prefixes)
+            .map(move |(prefix, members)| {
+                let client = client.clone();
+                let disk_cache = disk_cache.clone();
+                async move {
+                    let fetch = client
+                        .clone()
+                        .fetch_range((mode, prefix.clone()), disk_cache);
+                    let range = client
+                        .with_decoys(mode, fetch)
+                        .await
+                        .map(|(range, _)| range)
+                        .map_err(|e| e.to_string());
+                    let checked_at = Utc::now();
+                    let last = members.len() - 1;
+                    stream::iter(members.into_iter().enumerate().map(
+                        move |(member, (index, hash))| {
+                            let item = BatchItem {
+                                index,
+                                prefix: prefix.clone(),
+                                checked_at,
+                                outcome: range
+                                    .as_ref()
+                                    .map(|range| range.get(hash_suffix_of(&hash)))
+                                    .map_err(Clone::clone),
+                            };
+                            (item, member == last)
+                        },
+                    ))
+                }
+            })
+            .buffer_unordered(concurrency.max(1))
+            .flatten();
+    // The last item of each range marks it fetched.
+    stream::iter(done.into_iter().chain(ruled_out))
+        .map(|item| (item, false))
+        .chain(checked)
+        .scan(stats, move |stats, (item, last)| {
+            stats.record(&item.outcome, started.elapsed());
+            stats.ranges_done += usize::from(last);
+            future::ready(Some((item, *stats)))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+    use crate::metrics::{Metrics, Snapshot};
+    use crate::pwned::{self, InFlight, ProxySettings, SocksSettings, build_client};
+    use crate::rate_limit::RateLimiter;
+
+    fn client(endpoint: &str) -> RangeClient {
+        client_counting(endpoint, Metrics::default())
+    }
+
+    /// A client for `endpoint` whose lookups are tallied in `metrics`.
+    fn client_counting(endpoint: &str, metrics: Metrics) -> RangeClient {
+        let settings = pwned::Settings {
+            endpoint: pwned::parse_endpoint(endpoint).unwrap(),
+            padding: false,
+            decoys: false,
+            decoy_count: 0,
+            timeout: Duration::from_secs(5),
+            proxy: ProxySettings::default(),
+            socks: SocksSettings::default(),
+            rate_limit: 1000,
+        };
+        RangeClient::new(
+            build_client(&settings).unwrap(),
+            settings,
+            InFlight::default(),
+            metrics,
+            RateLimiter::default(),
+        )
+    }
+
+    /// Answers `password`'s range with it listed `count` times, or with
+    /// `status` and no body.
+    async fn serve(server: &MockServer, password: &str, answer: Result<u64, u16>) {
+        let hash = hash_password(password, HashMode::Sha1, Normalization::Off);
+        let response = match answer {
+            Ok(count) => ResponseTemplate::new(200)
+                .set_body_string(format!("{}:{count}\r\n", hash_suffix_of(&hash))),
+            Err(status) => ResponseTemplate::new(status),
+        };
+        Mock::given(method("GET"))
+            .and(path(format!("/range/{}", hash_prefix_of(&hash))))
+            .respond_with(response)
+            .expect(1)
+            .mount(server)
+            .await;
+    }
+
+    /// A made-up hash in `prefix`'s range.
+    fn hash(prefix: &str, n: u8) -> String {
+        format!("{prefix}{:035X}", n)
+    }
+
+    async fn run(client: RangeClient, passwords: &[&str], concurrency: usize) -> Vec<BatchItem> {
+        let mut items: Vec<_> = progress(client, passwords, concurrency)
+            .await
+            .into_iter()
+            .map(|(item, _)| item)
+            .collect();
+        items.sort_by_key(|item| item.index);
+        items
+    }
+
+    /// Every item with the totals that came with it, in the order they came.
+    async fn progress(
+        client: RangeClient,
+        passwords: &[&str],
+        concurrency: usize,
+    ) -> Vec<(BatchItem, BatchStats)> {
+        let hashes = passwords
+            .iter()
+            .map(|password| hash_password(password, HashMode::Sha1, Normalization::Off))
+            .collect();
+        let items = check_all(
+            client,
+            hashes,
+            HashMode::Sha1,
+            None,
+            concurrency,
+            Vec::new(),
+            None,
+        );
+        items.collect().await
+    }
+
+    #[tokio::test]
+    async fn colliding_prefixes_are_fetched_once_and_reported_per_hash() {
+        let server = MockServer::start().await;
+        for (prefix, body) in [("F3BBB", "1:3\r\n2:5"), ("AAAAA", "1:7")] {
+            let body = body
+                .split("\r\n")
+                .map(|entry| {
+                    let (n, count) = entry.split_once(':').unwrap();
+                    format!("{:035X}:{count}", n.parse::<u8>().unwrap())
+                })
+                .collect::<Vec<_>>()
+                .join("\r\n");
+            Mock::given(method("GET"))
+                .and(path(format!("/range/{prefix}")))
+                .respond_with(ResponseTemplate::new(200).set_body_string(body))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let hashes = vec![
+            hash("F3BBB", 1),
+            hash("AAAAA", 1),
+            hash("F3BBB", 2),
+            hash("F3BBB", 3),
+            hash("AAAAA", 9),
+        ];
+        let mut items: Vec<_> = check_all(
+            client(&server.uri()),
+            hashes,
+            HashMode::Sha1,
+            None,
+            8,
+            Vec::new(),
+            None,
+        )
+        .collect()
+        .await;
+        let stats = items.last().unwrap().1;
+        items.sort_by_key(|(item, _)| item.index);
+        let outcomes: Vec<_> = items
+            .iter()
+            .map(|(item, _)| (item.prefix.as_str(), item.outcome.clone()))
+            .collect();
+        assert_eq!(
+            outcomes,
+            [
+                ("F3BBB", Ok(Some(3))),
+                ("AAAAA", Ok(Some(7))),
+                ("F3BBB", Ok(Some(5))),
+                ("F3BBB", Ok(None)),
+                ("AAAAA", Ok(None)),
+            ]
+        );
+        assert_eq!((stats.ranges, stats.ranges_done), (2, 2));
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn a_failed_range_only_fails_its_own_items() {
+        let server = MockServer::start().await;
+        serve(&server, "hunter2", Ok(7)).await;
+        serve(&server, "password", Ok(3)).await;
+        serve(&server, "letmein", Err(404)).await;
+
+        let passwords = ["hunter2", "password", "letmein", "hunter2"];
+        let items = run(client(&server.uri()), &passwords, 2).await;
+        let outcomes: Vec<_> = items.iter().map(|item| item.outcome.clone()).collect();
+        assert_eq!(outcomes[..2], [Ok(Some(7)), Ok(Some(3))]);
+        assert!(outcomes[2].is_err(), "{outcomes:?}");
+        // The repeat shares its range, fetched once.
+        assert_eq!(outcomes[3], Ok(Some(7)));
+    }
+
+    #[tokio::test]
+    async fn no_more_than_concurrency_ranges_are_fetched_at_once() {
+        let delay = Duration::from_millis(300);
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(format!("{:035X}:1\r\n", 0))
+                    .set_delay(delay),
+            )
+            .expect(4)
+            .mount(&server)
+            .await;
+
+        let started = Instant::now();
+        let passwords = ["one", "two", "three", "four"];
+        let items = run(client(&server.uri()), &passwords, 2).await;
+        assert!(items.iter().all(|item| item.outcome == Ok(None)));
+        // Two at a time, so two rounds.
+        assert!(started.elapsed() >= delay * 2, "{:?}", started.elapsed());
+    }
+
+    /// The range `password` is looked up in.
+    fn range_of(password: &str) -> String {
+        let hash = hash_password(password, HashMode::Sha1, Normalization::Off);
+        format!("/range/{}", hash_prefix_of(&hash))
+    }
+
+    #[tokio::test]
+    async fn a_concurrent_batch_is_tallied_exactly() {
+        let server = MockServer::start().await;
+        // Fails once, then answers.
+        Mock::given(method("GET"))
+            .and(path(range_of("dragon")))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(range_of("letmein")))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(range_of("qwerty")))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "0"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(format!("{:035X}:7\r\n", 1)))
+            .mount(&server)
+            .await;
+
+        let metrics = Metrics::default();
+        // The repeat shares its range with the first, so isn't a request of
+        // its own.
+        let passwords = [
+            "hunter2", "password", "letmein", "qwerty", "dragon", "monkey", "shadow", "hunter2",
+        ];
+        let items = run(
+            client_counting(&server.uri(), metrics.clone()),
+            &passwords,
+            4,
+        )
+        .await;
+        assert_eq!(items.len(), 8);
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(server.received_requests().await.unwrap().len(), 8);
+        assert_eq!(
+            snapshot,
+            Snapshot {
+                // One a range, and dragon's retry.
+                requests: 8,
+                retries: 1,
+                rate_limited: 1,
+                // letmein's 404 and qwerty's 429.
+                failures: 2,
+                cache_hits: 0,
+                cache_misses: 0,
+                average_latency: snapshot.average_latency,
+            }
+        );
+        assert!(snapshot.average_latency.is_some());
+    }
+
+    #[tokio::test]
+    async fn a_rerun_from_the_disk_cache_counts_hits_and_sends_nothing() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(format!("{:035X}:7\r\n", 1)))
+            .expect(3)
+            .mount(&server)
+            .await;
+        let dir = tempfile::tempdir().unwrap();
+        let endpoint = pwned::parse_endpoint(&server.uri()).unwrap();
+        let disk_cache =
+            DiskCache::in_dir(dir.path().to_owned(), &endpoint, Duration::from_secs(60));
+        let passwords: Vec<SecretString> = ["hunter2", "password", "letmein"]
+            .map(|password| password.to_owned().into())
+            .to_vec();
+        let hashes = hash_all(&passwords, HashMode::Sha1, Normalization::Off);
+
+        let metrics = Metrics::default();
+        for (hits, misses) in [(0, 3), (3, 3)] {
+            let items: Vec<_> = check_all(
+                client_counting(&server.uri(), metrics.clone()),
+                hashes.clone(),
+                HashMode::Sha1,
+                Some(disk_cache.clone()),
+                4,
+                Vec::new(),
+                None,
+            )
+            .collect()
+            .await;
+            assert!(items.iter().all(|(item, _)| item.outcome == Ok(None)));
+            let snapshot = metrics.snapshot();
+            assert_eq!((snapshot.cache_hits, snapshot.cache_misses), (hits, misses));
+            assert_eq!(snapshot.requests, 3);
+        }
+        assert_eq!(metrics.snapshot().hit_rate(), Some(0.5));
+    }
+
+    fn exposed(batch: &Lines) -> Vec<&str> {
+        batch.passwords.iter().map(SecretString::expose).collect()
+    }
+
+    #[test]
+    fn a_list_is_split_into_numbered_lines() {
+        let batch = read_list(b"\xEF\xBB\xBFhunter2\r\n\r\npassword\nhunter2\n\n123456").unwrap();
+        assert_eq!(exposed(&batch), ["hunter2", "password", "123456"]);
+        let lines: Vec<_> = batch
+            .lines
+            .iter()
+            .map(|line| (line.number, line.password, line.preview.as_str()))
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                (1, 0, "h******"),
+                (3, 1, "p*******"),
+                (4, 0, "h******"),
+                (6, 2, "1*****"),
+            ]
+        );
+    }
+
+    #[test]
+    fn only_utf8_lists_are_read() {
+        let error = read_list(b"hunter2\n\xFFpassword").unwrap_err();
+        assert_eq!(
+            error,
+            "the file isn't UTF-8 text (invalid byte at offset 8)"
+        );
+    }
+
+    #[test]
+    fn a_list_may_not_be_too_long() {
+        let most = "x\n".repeat(MAX_IMPORT_LINES);
+        assert_eq!(
+            read_list(most.as_bytes()).unwrap().lines.len(),
+            MAX_IMPORT_LINES
+        );
+        let error = read_list(format!("{most}x").as_bytes()).unwrap_err();
+        assert_eq!(
+            error,
+            "the file has 1000001 lines, more than the 1000000 a batch allows"
+        );
+    }
+
+    #[test]
+    fn an_empty_list_has_no_lines() {
+        for bytes in [&b""[..], b"\xEF\xBB\xBF", b"\n\r\n\n"] {
+            let batch = read_list(bytes).unwrap();
+            assert!(batch.lines.is_empty() && batch.passwords.is_empty());
+        }
+    }
+
+    #[test]
+    fn lines_keep_their_spaces() {
+        let batch = split_lines(" hunter2\nhunter2 \n\tpass word");
+        assert_eq!(exposed(&batch), [" hunter2", "hunter2 ", "\tpass word"]);
+    }
+
+    #[test]
+    fn passwords_normalized_alike_are_folded_into_the_first() {
+        let mut batch = split_lines("caf\u{e9}\nhunter2\ncafe\u{301}\nhunter2 ");
+        fold_by_hash(&mut batch, HashMode::Sha1, Normalization::Nfc);
+        assert_eq!(exposed(&batch), ["caf\u{e9}", "hunter2", "hunter2 "]);
+        let passwords: Vec<_> = batch.lines.iter().map(|line| line.password).collect();
+        assert_eq!(passwords, [0, 1, 0, 2]);
+    }
+
+    #[test]
+    fn a_hash_list_is_uppercased_and_keeps_its_labels() {
+        let list = b"  5baa61e4c9b93f3f0682250b6cf8331b7ee68fd8 : work email \n\
+            # exported 2024-05-01\n\
+            \n\
+            7C4A8D09CA3762AF61E59520943DC26494F8941B:db:primary\n\
+            7c4a8d09ca3762af61e59520943dc26494f8941b\n";
+        let batch = read_hashes(list, HashMode::Sha1).unwrap();
+        assert_eq!(batch.hashed, Some(HashMode::Sha1));
+        assert!(batch.malformed.is_empty(), "{:?}", batch.malformed);
+        assert_eq!(
+            exposed(&batch),
+            [
+                "5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8",
+                "7C4A8D09CA3762AF61E59520943DC26494F8941B"
+            ]
+        );
+        let lines: Vec<_> = batch
+            .lines
+            .iter()
+            .map(|line| (line.number, line.label.as_str(), line.password))
+            .collect();
+        assert_eq!(
+            lines,
+            [(1, "work email", 0), (4, "db:primary", 1), (5, "", 1)]
+        );
+        assert_eq!(batch.lines[0].preview, "SHA-1 5BAA6…");
+    }
+
+    #[test]
+    fn a_line_that_isnt_a_hash_of_the_mode_is_malformed() {
+        let ntlm = "8846F7EAEE8FB117AD06BDD830B7586C";
+        let list = format!("{ntlm}:admin\nFFFF\n{}\nhunter2:x\n", hash("F3BBB", 1));
+        let batch = read_hashes(list.as_bytes(), HashMode::Ntlm).unwrap();
+        assert_eq!(exposed(&batch), [ntlm]);
+        assert_eq!(batch.lines[0].label, "admin");
+        let malformed: Vec<_> = batch.malformed.iter().map(|m| m.line).collect();
+        assert_eq!(malformed, [2, 3, 4]);
+        assert_eq!(
+            batch.malformed[0].reason,
+            "an NTLM hash is exactly 32 hexadecimal characters"
+        );
+        let batch = read_hashes(list.as_bytes(), HashMode::Sha1).unwrap();
+        let malformed: Vec<_> = batch.malformed.iter().map(|m| m.line).collect();
+        assert_eq!(malformed, [1, 2, 4]);
+    }
+
+    #[test]
+    fn lines_may_end_in_any_line_ending() {
+        let lines: Vec<_> = text_lines("a\r\nb\rc\nd").collect();
+        assert_eq!(lines, [(1, "a"), (2, "b"), (3, "c"), (4, "d")]);
+    }
+
+    #[test]
+    fn an_empty_hash_list_has_no_lines() {
+        let batch = read_hashes(b"# nothing yet\n\n", HashMode::Sha1).unwrap();
+        assert!(batch.lines.is_empty() && batch.malformed.is_empty());
+        assert!(read_hashes(b"\xFF", HashMode::Sha1).is_err());
+    }
+
+    #[test]
+    fn unique_and_repeated_passwords_are_grouped_by_hash() {
+        let mut batch = split_lines("a\nb\na\nc\nb\na");
+        fold_by_hash(&mut batch, HashMode::Ntlm, Normalization::Off);
+        assert_eq!(exposed(&batch), ["a", "b", "c"]);
+        let passwords: Vec<_> = batch.lines.iter().map(|line| line.password).collect();
+        assert_eq!(passwords, [0, 1, 0, 2, 1, 0]);
+    }
+
+    #[test]
+    fn a_hash_list_is_grouped_by_hash_whatever_its_case() {
+        let upper = hash("F3BBB", 1);
+        let list = format!(
+            "{upper}:work\n{}:home\n{}\n",
+            upper.to_lowercase(),
+            hash("AAAAA", 1)
+        );
+        let mut batch = read_hashes(list.as_bytes(), HashMode::Sha1).unwrap();
+        fold_by_hash(&mut batch, HashMode::Sha1, Normalization::Nfc);
+        assert_eq!(exposed(&batch), [upper.as_str(), &hash("AAAAA", 1)]);
+        let lines: Vec<_> = batch
+            .lines
+            .iter()
+            .map(|line| (line.password, line.label.as_str()))
+            .collect();
+        assert_eq!(lines, [(0, "work"), (0, "home"), (1, "")]);
+    }
+
+    #[tokio::test]
+    async fn a_failed_lookup_is_the_error_of_every_line_with_that_password() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/range/F3BBB"))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(format!("{:035X}:7\r\n", 1)))
+            .mount(&server)
+            .await;
+        let mut batch = split_lines("hunter2\nhunter3\nhunter2\n");
+        fold_by_hash(&mut batch, HashMode::Sha1, Normalization::Nfc);
+        let items = run(client(&server.uri()), &exposed(&batch), 2).await;
+        let outcome = |line: &Line| {
+            let item = items
+                .iter()
+                .find(|item| item.index == line.password)
+                .unwrap();
+            item.outcome.clone()
+        };
+        let outcomes: Vec<_> = batch.lines.iter().map(outcome).collect();
+        assert!(outcomes[0].is_err() && outcomes[2].is_err(), "{outcomes:?}");
+        assert_eq!(outcomes[0], outcomes[2]);
+        assert_eq!(outcomes[1], Ok(None));
+    }
+
+    fn stats(total: usize, ranges: usize) -> BatchStats {
+        BatchStats {
+            total,
+            ranges,
+            ..BatchStats::default()
+        }
+    }
+
+    #[test]
+    fn nothing_done_yet_has_no_eta() {
+        let stats = stats(4, 2);
+        assert_eq!(stats.fraction(), 0.);
+        assert_eq!(stats.eta(), None);
+        // An empty batch is already done.
+        assert_eq!(BatchStats::default().fraction(), 1.);
+    }
+
+    #[test]
+    fn the_eta_follows_the_time_per_range() {
+        let mut stats = stats(4, 3);
+        stats.record(&Ok(Some(3)), Duration::from_secs(2));
+        stats.ranges_done = 1;
+        assert_eq!(stats.fraction(), 0.25);
+        assert_eq!(stats.eta(), Some(Duration::from_secs(4)));
+
+        stats.record(&Ok(None), Duration::from_secs(6));
+        stats.record(&Ok(None), Duration::from_secs(6));
+        stats.ranges_done = 2;
+        assert_eq!((stats.breached, stats.clean), (1, 2));
+        assert_eq!(stats.eta(), Some(Duration::from_secs(3)));
+    }
+
+    #[test]
+    fn an_all_errored_batch_is_finished() {
+        let mut stats = stats(2, 2);
+        for elapsed in [1, 2] {
+            stats.record(&Err("timed out".to_owned()), Duration::from_secs(elapsed));
+            stats.ranges_done += 1;
+        }
+        assert_eq!(
+            (stats.done, stats.errored, stats.breached, stats.clean),
+            (2, 2, 0, 0)
+        );
+        assert_eq!(stats.fraction(), 1.);
+        assert_eq!(stats.eta(), Some(Duration::ZERO));
+    }
+
+    #[tokio::test]
+    async fn progress_comes_after_every_item() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+        let items = progress(client(&server.uri()), &["one", "two", "one"], 1).await;
+        let done: Vec<_> = items.iter().map(|(_, stats)| stats.done).collect();
+        assert_eq!(done, [1, 2, 3]);
+        let last = items.last().unwrap().1;
+        assert_eq!((last.errored, last.ranges, last.ranges_done), (3, 2, 2));
+        assert_eq!(last.fraction(), 1.);
+    }
+
+    /// A line per password, `1, 2, …`, for the sort and filter tests.
+    fn numbered(count: usize) -> Vec<Line> {
+        (0..count)
+            .map(|index| Line {
+                number: index + 1,
+                label: String::new(),
+                username: String::new(),
+                preview: format!("line {}", index + 1),
+                password: index,
+            })
+            .collect()
+    }
+
+    /// Results by password: `Some(n)` found `n` times (0 for not found),
+    /// `None` still being checked; `u64::MAX` stands for a failed lookup.
+    fn results(counts: &[Option<u64>]) -> Vec<Option<BatchItem>> {
+        counts
+            .iter()
+            .enumerate()
+            .map(|(index, count)| {
+                let count = (*count)?;
+                Some(BatchItem {
+                    index,
+                    prefix: "F3BBB".to_owned(),
+                    checked_at: Utc::now(),
+                    outcome: match count {
+                        u64::MAX => Err("503".to_owned()),
+                        0 => Ok(None),
+                        count => Ok(Some(count)),
+                    },
+                })
+            })
+            .collect()
+    }
+
+    const BY_COUNT: Sort = Sort {
+        column: SortColumn::Count,
+        descending: true,
+    };
+
+    fn sorted(lines: &[Line], counts: &[Option<u64>], sort: Sort) -> Vec<usize> {
+        sorted_lines(lines, &results(counts), sort, &Thresholds::default())
+    }
+
+    #[test]
+    fn clicking_a_header_sorts_by_it_then_flips_it() {
+        let sort = Sort::default().toggled(SortColumn::Count);
+        assert_eq!(sort, BY_COUNT);
+        assert!(!sort.toggled(SortColumn::Count).descending);
+        assert!(
+            sort.toggled(SortColumn::Count)
+                .toggled(SortColumn::Count)
+                .descending
+        );
+        // Line order starts the natural way round.
+        assert_eq!(
+            sort.toggled(SortColumn::Line),
+            Sort {
+                column: SortColumn::Line,
+                descending: false
+            }
+        );
+    }
+
+    #[test]
+    fn sorting_by_line_is_input_order_either_way() {
+        let lines = numbered(3);
+        let counts = [Some(5), None, Some(9)];
+        assert_eq!(sorted(&lines, &counts, Sort::default()), [0, 1, 2]);
+        let reversed = Sort::default().toggled(SortColumn::Line);
+        assert_eq!(sorted(&lines, &counts, reversed), [2, 1, 0]);
+    }
+
+    #[test]
+    fn ties_keep_their_input_order() {
+        let lines = numbered(5);
+        let counts = [Some(3), Some(7), Some(3), Some(7), Some(3)];
+        assert_eq!(sorted(&lines, &counts, BY_COUNT), [1, 3, 0, 2, 4]);
+        let ascending = BY_COUNT.toggled(SortColumn::Count);
+        assert_eq!(sorted(&lines, &counts, ascending), [0, 2, 4, 1, 3]);
+
+        // Different counts in the same tier tie for severity.
+        let by_severity = Sort::default().toggled(SortColumn::Severity);
+        let counts = [Some(20), Some(500_000), Some(999), Some(0), Some(50)];
+        assert_eq!(sorted(&lines, &counts, by_severity), [1, 0, 2, 4, 3]);
+    }
+
+    #[test]
+    fn unfinished_and_failed_lines_come_last_both_ways() {
+        let lines = numbered(5);
+        let counts = [None, Some(4), Some(u64::MAX), Some(0), Some(9)];
+        assert_eq!(sorted(&lines, &counts, BY_COUNT), [4, 1, 3, 0, 2]);
+        let ascending = BY_COUNT.toggled(SortColumn::Count);
+        assert_eq!(sorted(&lines, &counts, ascending), [3, 1, 4, 0, 2]);
+    }
+
+    #[test]
+    fn a_row_only_moves_when_its_own_result_arrives() {
+        let lines = numbered(4);
+        let mut counts = [None; 4];
+        assert_eq!(sorted(&lines, &counts, BY_COUNT), [0, 1, 2, 3]);
+        counts[2] = Some(10);
+        assert_eq!(sorted(&lines, &counts, BY_COUNT), [2, 0, 1, 3]);
+        counts[0] = Some(10);
+        assert_eq!(sorted(&lines, &counts, BY_COUNT), [0, 2, 1, 3]);
+        counts[3] = Some(99);
+        assert_eq!(sorted(&lines, &counts, BY_COUNT), [3, 0, 2, 1]);
+        counts[1] = Some(1);
+        assert_eq!(sorted(&lines, &counts, BY_COUNT), [3, 0, 2, 1]);
+    }
+
+    #[test]
+    fn lines_sharing_a_password_sort_together() {
+        let mut lines = numbered(3);
+        lines[2].password = 0;
+        let counts = [Some(8), Some(50)];
+        assert_eq!(sorted(&lines, &counts, BY_COUNT), [1, 0, 2]);
+    }
+
+    #[test]
+    fn sorting_leaves_the_lines_alone() {
+        let lines = numbered(3);
+        let counts = [Some(1), Some(2), Some(3)];
+        assert_eq!(sorted(&lines, &counts, BY_COUNT), [2, 1, 0]);
+        let numbers: Vec<_> = lines.iter().map(|line| line.number).collect();
+        assert_eq!(numbers, [1, 2, 3]);
+    }
+
+    /// Whether `filter` shows each of: an entry labelled "Mail" found 5
+    /// times, "Bank" not found, "Forum" that failed, and "Shop" unchecked.
+    fn shown(filter: &Filter) -> [bool; 4] {
+        let mut lines = numbered(4);
+        for (line, label) in lines.iter_mut().zip(["Mail", "Bank", "Forum", "Shop"]) {
+            line.label = label.to_owned();
+        }
+        let results = results(&[Some(5), Some(0), Some(u64::MAX), None]);
+        [0, 1, 2, 3].map(|index| filter.matches(&lines[index], results[index].as_ref()))
+    }
+
+    #[test]
+    fn no_filter_shows_everything() {
+        let filter = Filter::default();
+        assert!(!filter.is_active());
+        assert_eq!(shown(&filter), [true; 4]);
+        // Spaces alone aren't a filter.
+        let filter = Filter {
+            text: "  ".to_owned(),
+            ..Filter::default()
+        };
+        assert!(!filter.is_active());
+        assert_eq!(shown(&filter), [true; 4]);
+    }
+
+    #[test]
+    fn text_matches_the_label_preview_or_line_number() {
+        let text = |text: &str| Filter {
+            text: text.to_owned(),
+            ..Filter::default()
+        };
+        assert!(text("mail").is_active());
+        assert_eq!(shown(&text(" MAIL ")), [true, false, false, false]);
+        assert_eq!(shown(&text("line")), [true; 4]);
+        assert_eq!(shown(&text("line 3")), [false, false, true, false]);
+        assert_eq!(shown(&text("2")), [false, true, false, false]);
+        assert_eq!(shown(&text("nothing")), [false; 4]);
+    }
+
+    #[test]
+    fn breached_and_errors_only_either_will_do() {
+        let filter = |breached_only, errors_only| Filter {
+            breached_only,
+            errors_only,
+            ..Filter::default()
+        };
+        assert_eq!(shown(&filter(true, false)), [true, false, false, false]);
+        assert_eq!(shown(&filter(false, true)), [false, false, true, false]);
+        assert_eq!(shown(&filter(true, true)), [true, false, true, false]);
+    }
+
+    #[test]
+    fn a_minimum_count_hides_the_less_exposed() {
+        let filter = |min_count| Filter {
+            min_count,
+            ..Filter::default()
+        };
+        assert_eq!(shown(&filter(5)), [true, false, false, false]);
+        assert_eq!(shown(&filter(6)), [false; 4]);
+        assert_eq!(shown(&filter(1)), [true, false, false, false]);
+    }
+
+    #[test]
+    fn every_part_of_a_filter_must_match() {
+        let filter = Filter {
+            text: "a".to_owned(),
+            breached_only: true,
+            errors_only: true,
+            min_count: 0,
+        };
+        // "Forum" failed but has no "a"; "Bank" and "Shop" aren't breached.
+        assert_eq!(shown(&filter), [true, false, false, false]);
+        let filter = Filter {
+            text: "o".to_owned(),
+            errors_only: true,
+            ..Filter::default()
+        };
+        assert_eq!(shown(&filter), [false, false, true, false]);
+        let filter = Filter {
+            min_count: 1,
+            errors_only: true,
+            ..Filter::default()
+        };
+        assert_eq!(shown(&filter), [false; 4]);
+    }
+}
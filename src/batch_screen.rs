@@ -0,0 +1,1243 @@
+use std::collections::BTreeMap;
+use std::convert;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use iced::alignment::Vertical;
+use iced::futures::stream::{self, StreamExt};
+use iced::task;
+use iced::widget::{
+    button, checkbox, column, container, progress_bar, row, scrollable, text, text_editor,
+    text_input, vertical_space,
+};
+use iced::{Element, Length, Task, Theme};
+use zeroize::Zeroizing;
+
+use cybersec_wow::{
+    HashMode, batch, checkpoint, export, hash_password, hash_prefix_of, import, policy, pwdump,
+    report, secret, severity, summary,
+};
+
+use crate::mapping_screen::{self, MappingScreen, Unmapped};
+use crate::ui::{exported_status, report_view};
+use crate::{Shared, high_contrast, save_export, severity_view, virtual_rows};
+use batch::{BatchItem, BatchStats};
+use checkpoint::Checkpoint;
+use policy::Verdict;
+use secret::SecretString;
+use severity::Severity;
+use summary::{Audit, CountStyle, format_count};
+
+/// Masked lines of an imported file shown before it's checked.
+const IMPORT_PREVIEW: usize = 5;
+
+/// How many of the sites sharing the worst finding's password are named.
+const WORST_SITES: usize = 5;
+
+/// How often a running batch saves its checkpoint.
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The results table's line number column.
+const LINE_WIDTH: f32 = 50.;
+
+/// Every row of the results table is this tall, so which ones are in view
+/// follows from the scroll offset alone.
+const ROW_HEIGHT: f32 = 24.;
+
+/// The results table, for the jump to top and bottom shortcuts.
+pub const TABLE: &str = "batch-table";
+
+/// Where the results table is scrolled to, as last reported.
+#[derive(Clone, Copy, Debug)]
+struct Viewport {
+    offset: f32,
+    height: f32,
+}
+
+impl Default for Viewport {
+    /// Until the table first reports its size, enough rows for a tall window.
+    fn default() -> Self {
+        Self {
+            offset: 0.,
+            height: 1200.,
+        }
+    }
+}
+
+/// The Batch tab: a list of passwords or hashes, typed, imported or
+/// dropped, checked together, with the results table and its exports.
+#[derive(Debug)]
+pub struct BatchScreen {
+    pub input: text_editor::Content,
+    pub lines: Vec<batch::Line>,
+    /// The lines each distinct password is on, to tell reused ones apart.
+    reuse: Vec<Vec<usize>>,
+    /// How each line's password meets the policy, by line; empty without
+    /// one, or for a hash list.
+    policy: Vec<Vec<Verdict>>,
+    /// How the results table is ordered. Only the view is sorted.
+    pub sort: batch::Sort,
+    pub filter: batch::Filter,
+    viewport: Viewport,
+    export_filtered: bool,
+    /// A list read from a file, waiting for the go-ahead.
+    pub import: Option<Result<Arc<batch::Lines>, String>>,
+    /// A CSV file whose columns are to be mapped before it's read.
+    mapping: Option<MappingScreen>,
+    /// Files being dragged over the window.
+    pub hovered_files: usize,
+    /// Files still to arrive from a multi-file drop that's being turned away.
+    rejected_drops: usize,
+    /// Outcomes by distinct password, which lines refer to by index.
+    pub results: Vec<Option<BatchItem>>,
+    /// Kept only so an export can include them, never shown. For a hash
+    /// list, the hashes instead.
+    pub passwords: Vec<SecretString>,
+    mode: HashMode,
+    /// Whether the last batch was a hash list.
+    hashed: bool,
+    /// Whether the last batch was a directory dump.
+    accounts: bool,
+    /// What imported directory dumps check besides current passwords.
+    dump_include: pwdump::Include,
+    pub started: DateTime<Utc>,
+    pub finished: Option<DateTime<Utc>>,
+    /// Whether the last batch was stopped before every password was checked.
+    pub cancelled: bool,
+    pub run: Option<task::Handle>,
+    pub stats: Option<BatchStats>,
+    /// Worked out once the batch has finished or been stopped, for the
+    /// summary above its results.
+    audit: Option<Audit>,
+    /// Identifies the batch's input to its checkpoint.
+    fingerprint: String,
+    /// Items from an interrupted run of the same batch, waiting on whether
+    /// to resume from them.
+    resume: Option<Vec<BatchItem>>,
+    checkpoint_saved: Instant,
+    export_plaintext: bool,
+    /// Whether the plaintext warning is up, waiting for an answer.
+    pub confirm_plaintext: bool,
+    export: Option<Result<PathBuf, String>>,
+}
+
+impl Default for BatchScreen {
+    fn default() -> Self {
+        Self {
+            input: text_editor::Content::new(),
+            lines: Vec::new(),
+            reuse: Vec::new(),
+            policy: Vec::new(),
+            sort: batch::Sort::default(),
+            filter: batch::Filter::default(),
+            viewport: Viewport::default(),
+            export_filtered: false,
+            import: None,
+            mapping: None,
+            hovered_files: 0,
+            rejected_drops: 0,
+            results: Vec::new(),
+            passwords: Vec::new(),
+            mode: HashMode::default(),
+            hashed: false,
+            accounts: false,
+            dump_include: pwdump::Include::default(),
+            started: Utc::now(),
+            finished: None,
+            cancelled: false,
+            run: None,
+            stats: None,
+            audit: None,
+            fingerprint: String::new(),
+            resume: None,
+            checkpoint_saved: Instant::now(),
+            export_plaintext: false,
+            confirm_plaintext: false,
+            export: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Edit(text_editor::Action),
+    CheckAll,
+    ImportFile,
+    /// Import a list of hashes of the kind the hash mode is set to.
+    ImportHashes,
+    DumpMachineAccounts(bool),
+    DumpHistory(bool),
+    FileHovered,
+    FilesHoveredLeft,
+    /// Handled by the app too, which shows the tab.
+    FileDropped(PathBuf),
+    FileImported(Option<Result<Imported, String>>),
+    Mapping(mapping_screen::Message),
+    CheckImported,
+    Item(BatchItem),
+    Progress(BatchStats),
+    /// Handled by the app too, which may say so with a notification.
+    Finished,
+    Cancel,
+    /// The items a checkpoint of the batch with this fingerprint had done, if
+    /// there was one.
+    CheckpointLoaded(String, Result<Option<Vec<BatchItem>>, String>),
+    /// Whether to carry on from the checkpoint or start over.
+    Resume(bool),
+    /// A checkpoint saved, or deleted once its batch was done.
+    CheckpointSaved(Result<(), String>),
+    /// A click on a results table header.
+    Sort(batch::SortColumn),
+    FilterText(String),
+    BreachedOnly(bool),
+    ErrorsOnly(bool),
+    MinCount(String),
+    Scrolled(scrollable::Viewport),
+    /// Whether exports leave out the lines the filter hides.
+    ExportFiltered(bool),
+    ExportCsv,
+    ExportJson,
+    Exported(Option<Result<PathBuf, String>>),
+    /// Handled by the app, which has the Check tab's history to report on
+    /// as well.
+    ExportReport(report::Format),
+    ExportPlaintext(bool),
+    ConfirmPlaintext(bool),
+}
+
+impl BatchScreen {
+    /// The Batch tab: the list, imports and dropped files, the run itself
+    /// with its checkpoints, the results table, and its exports.
+    pub fn update(&mut self, message: Message, shared: &mut Shared) -> Task<Message> {
+        match message {
+            Message::Edit(action) => self.input.perform(action),
+            Message::CheckAll => {
+                let batch = batch::split_lines(&self.input.text());
+                return self.start(batch, shared);
+            }
+            Message::ImportFile => {
+                let include = self.dump_include;
+                let mappings = shared.settings.import_mappings.clone();
+                return Task::future(async move {
+                    let file = rfd::AsyncFileDialog::new()
+                        .set_title("Import a password list")
+                        .add_filter("Password list or manager export", &["txt", "csv"])
+                        .add_filter("Text", &["txt"])
+                        .add_filter("Bitwarden or KeePass CSV", &["csv"])
+                        .pick_file()
+                        .await?;
+                    Some(import_list(file.path().to_owned(), include, mappings).await)
+                })
+                .map(Message::FileImported);
+            }
+            Message::ImportHashes => {
+                let (mode, include) = (shared.mode, self.dump_include);
+                return Task::future(async move {
+                    let file = rfd::AsyncFileDialog::new()
+                        .set_title(format!("Import a list of {mode} hashes"))
+                        .add_filter("Hash list", &["txt"])
+                        .pick_file()
+                        .await?;
+                    Some(
+                        import_hashes(file.path().to_owned(), mode, include)
+                            .await
+                            .map(Imported::Lines),
+                    )
+                })
+                .map(Message::FileImported);
+            }
+            Message::DumpMachineAccounts(include) => self.dump_include.machine_accounts = include,
+            Message::DumpHistory(include) => self.dump_include.history = include,
+            Message::FileHovered => self.hovered_files += 1,
+            Message::FilesHoveredLeft => self.hovered_files = 0,
+            Message::FileDropped(path) => {
+                // Each file of a multi-file drop arrives on its own. They're all
+                // turned away rather than imported over one another.
+                if self.hovered_files > 1 {
+                    self.rejected_drops = self.hovered_files;
+                }
+                self.hovered_files = 0;
+                if self.rejected_drops > 0 {
+                    self.rejected_drops -= 1;
+                    self.import = Some(Err("drop one file at a time".to_owned()));
+                    return Task::none();
+                }
+                if !path.extension().is_some_and(|extension| {
+                    extension.eq_ignore_ascii_case("txt") || extension.eq_ignore_ascii_case("csv")
+                }) {
+                    let name = path.file_name().unwrap_or(path.as_os_str());
+                    self.import = Some(Err(format!(
+                        "only .txt lists and .csv exports can be imported, not {}",
+                        name.to_string_lossy()
+                    )));
+                    return Task::none();
+                }
+                let mappings = shared.settings.import_mappings.clone();
+                return Task::perform(import_list(path, self.dump_include, mappings), |lines| {
+                    Message::FileImported(Some(lines))
+                });
+            }
+            Message::FileImported(imported) => match imported {
+                Some(Ok(Imported::Lines(lines))) => {
+                    self.mapping = None;
+                    self.import = Some(Ok(lines));
+                }
+                Some(Ok(Imported::Unmapped(file))) => {
+                    self.import = None;
+                    self.mapping = Some(MappingScreen::new(file));
+                }
+                Some(Err(error)) => {
+                    self.mapping = None;
+                    self.import = Some(Err(error));
+                }
+                None => {}
+            },
+            Message::Mapping(mapping_screen::Message::Import) => {
+                let Some(screen) = self.mapping.take() else {
+                    return Task::none();
+                };
+                let Some(mapping) = screen.mapping() else {
+                    self.mapping = Some(screen);
+                    return Task::none();
+                };
+                if let Some(signature) = screen.signature() {
+                    shared.settings.import_mappings.insert(signature, mapping);
+                    shared.settings_dirty = true;
+                }
+                let file = screen.file().clone();
+                return Task::perform(
+                    async move {
+                        tokio::task::spawn_blocking(move || import::read_mapped(&file.0, &mapping))
+                            .await
+                            .map_err(|e| e.to_string())?
+                            .map(|lines| Imported::Lines(Arc::new(lines)))
+                    },
+                    |imported| Message::FileImported(Some(imported)),
+                );
+            }
+            Message::Mapping(mapping_screen::Message::Cancel) => self.mapping = None,
+            Message::Mapping(message) => {
+                if let Some(screen) = &mut self.mapping {
+                    screen.update(message);
+                }
+            }
+            Message::CheckImported => {
+                // The app holds the only reference once the import has
+                // landed, so it's moved out rather than its passwords copied.
+                if let Some(Ok(batch)) = self.import.take()
+                    && let Some(batch) = Arc::into_inner(batch)
+                {
+                    return self.start(batch, shared);
+                }
+            }
+            // Dropping the run's handle aborts it, but what it had already
+            // sent may still be queued, and mustn't land after a cancel.
+            Message::Item(_) | Message::Progress(_) | Message::Finished if self.run.is_none() => {}
+            Message::Item(item) => {
+                if let Some(slot) = self.results.get_mut(item.index) {
+                    *slot = Some(item);
+                }
+                if self.checkpoint_saved.elapsed() >= CHECKPOINT_INTERVAL {
+                    return self.save_checkpoint();
+                }
+            }
+            Message::CheckpointLoaded(fingerprint, loaded) => {
+                if fingerprint != self.fingerprint {
+                    return Task::none();
+                }
+                match loaded {
+                    // One that saved everything is left from a run that finished
+                    // as it was saved, with nothing to resume.
+                    Ok(Some(done)) if !done.is_empty() && done.len() < self.results.len() => {
+                        self.resume = Some(done)
+                    }
+                    Ok(_) => return self.run(Vec::new(), shared),
+                    Err(error) => {
+                        tracing::warn!("not resuming the batch: {error}");
+                        return self.run(Vec::new(), shared);
+                    }
+                }
+            }
+            Message::Resume(resume) => {
+                let done = self.resume.take().unwrap_or_default();
+                return self.run(if resume { done } else { Vec::new() }, shared);
+            }
+            Message::CheckpointSaved(result) => {
+                if let Err(error) = result {
+                    tracing::warn!("could not update the batch checkpoint: {error}");
+                }
+            }
+            Message::Progress(stats) => self.stats = Some(stats),
+            Message::Finished => {
+                self.run = None;
+                self.finished = Some(Utc::now());
+                self.audit = Some(Audit::new(&self.rows(false, false, shared)));
+                return self.settle_checkpoint();
+            }
+            Message::Sort(column) => self.sort = self.sort.toggled(column),
+            Message::FilterText(text) => self.filter.text = text,
+            Message::BreachedOnly(only) => self.filter.breached_only = only,
+            Message::ErrorsOnly(only) => self.filter.errors_only = only,
+            Message::MinCount(count) => {
+                if let Ok(count) = count.parse() {
+                    self.filter.min_count = count;
+                } else if count.is_empty() {
+                    self.filter.min_count = 0;
+                }
+            }
+            Message::Scrolled(viewport) => {
+                self.viewport = Viewport {
+                    offset: viewport.absolute_offset().y,
+                    height: viewport.bounds().height,
+                };
+            }
+            Message::ExportFiltered(filtered) => self.export_filtered = filtered,
+            Message::Cancel => {
+                // What finished so far stays in the results, and so in exports,
+                // and in the checkpoint, so the batch can be resumed later.
+                self.run = None;
+                self.cancelled = true;
+                self.finished = Some(Utc::now());
+                self.audit = Some(Audit::new(&self.rows(false, false, shared)));
+                return self.save_checkpoint();
+            }
+            Message::ExportCsv => {
+                let rows = self.export_rows(shared);
+                let include_plaintext = self.export_plaintext;
+                let separator = shared.separator;
+                return Task::future(save_export(
+                    "Export batch results",
+                    "batch-results.csv",
+                    "CSV",
+                    move |file| {
+                        export::write_csv(file, &rows, separator, include_plaintext)
+                            .map_err(|e| e.to_string())
+                    },
+                ))
+                .map(Message::Exported);
+            }
+            Message::ExportJson => {
+                let rows = self.export_rows(shared);
+                let summary = export::BatchSummary::new(
+                    &rows,
+                    self.started,
+                    self.finished.unwrap_or_else(Utc::now),
+                    &shared.settings.endpoint,
+                );
+                return Task::future(save_export(
+                    "Export batch results",
+                    "batch-results.json",
+                    "JSON",
+                    move |file| {
+                        export::write_batch_json(file, &summary, &rows).map_err(|e| e.to_string())
+                    },
+                ))
+                .map(Message::Exported);
+            }
+            Message::Exported(exported) => {
+                if exported.is_some() {
+                    self.export = exported;
+                }
+            }
+            Message::ExportPlaintext(include) => {
+                // Turning it on waits for the warning to be acknowledged.
+                self.confirm_plaintext = include;
+                if !include {
+                    self.export_plaintext = false;
+                }
+            }
+            Message::ConfirmPlaintext(include) => {
+                self.export_plaintext = include;
+                self.confirm_plaintext = false;
+            }
+            Message::ExportReport(_) => {}
+        }
+
+        Task::none()
+    }
+
+    fn start(&mut self, mut batch: batch::Lines, shared: &mut Shared) -> Task<Message> {
+        batch::fold_by_hash(&mut batch, shared.mode, shared.settings.normalization);
+        self.reuse = import::reuse(&batch);
+        self.lines = batch.lines;
+        self.results = vec![None; batch.passwords.len()];
+        self.passwords = batch.passwords;
+        self.mode = batch.hashed.unwrap_or(shared.mode);
+        self.hashed = batch.hashed.is_some();
+        self.accounts = batch.accounts;
+        self.policy = match &shared.settings.policy {
+            Some(policy) if !self.hashed => self
+                .lines
+                .iter()
+                .map(|line| {
+                    let password = self.passwords[line.password].expose();
+                    policy.check(password, Some(&line.username))
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        self.started = Utc::now();
+        self.finished = None;
+        self.audit = None;
+        self.cancelled = false;
+        self.stats = None;
+        self.export = None;
+        self.resume = None;
+        let prefixes: Vec<String> = self
+            .passwords
+            .iter()
+            .map(|password| {
+                if self.hashed {
+                    return hash_prefix_of(password.expose()).to_owned();
+                }
+                let hash =
+                    hash_password(password.expose(), self.mode, shared.settings.normalization);
+                hash_prefix_of(&hash).to_ascii_uppercase()
+            })
+            .collect();
+        self.fingerprint = checkpoint::fingerprint(prefixes.iter().map(String::as_str).enumerate());
+
+        // An interrupted run of the same batch is offered to be resumed.
+        let Some(path) = checkpoint::default_path() else {
+            return self.run(Vec::new(), shared);
+        };
+        let fingerprint = self.fingerprint.clone();
+        let total = self.passwords.len();
+        let load = {
+            let fingerprint = fingerprint.clone();
+            move || {
+                Checkpoint::load(&path)?
+                    .map(|checkpoint| checkpoint.resume(&path, &fingerprint, total))
+                    .transpose()
+            }
+        };
+        Task::perform(
+            async move {
+                tokio::task::spawn_blocking(load)
+                    .await
+                    .map_err(|e| e.to_string())?
+                    .map_err(|e| e.to_string())
+            },
+            move |loaded| Message::CheckpointLoaded(fingerprint.clone(), loaded),
+        )
+    }
+
+    /// Starts checking the batch set up by `start`, skipping the items
+    /// in `done`.
+    pub fn run(&mut self, done: Vec<BatchItem>, shared: &mut Shared) -> Task<Message> {
+        for item in &done {
+            if let Some(slot) = self.results.get_mut(item.index) {
+                *slot = Some(item.clone());
+            }
+        }
+        self.checkpoint_saved = Instant::now();
+        let hashes = if self.hashed {
+            self.passwords
+                .iter()
+                .map(|hash| hash.expose().to_owned())
+                .collect()
+        } else {
+            batch::hash_all(&self.passwords, self.mode, shared.settings.normalization)
+        };
+        let updates = batch::check_all(
+            shared.range_client(),
+            hashes,
+            self.mode,
+            shared.disk_cache.clone(),
+            shared.settings.batch_concurrency,
+            done,
+            shared.dataset_filter(),
+        )
+        .flat_map(|(item, stats)| stream::iter([Message::Item(item), Message::Progress(stats)]));
+        let (task, handle) = Task::run(updates, convert::identity)
+            .chain(Task::done(Message::Finished))
+            .abortable();
+        self.run = Some(handle.abort_on_drop());
+        task
+    }
+
+    /// Saves what the batch has done so far, off the UI thread.
+    fn save_checkpoint(&mut self) -> Task<Message> {
+        self.checkpoint_saved = Instant::now();
+        let Some(path) = checkpoint::default_path() else {
+            return Task::none();
+        };
+        let checkpoint = Checkpoint::new(
+            self.fingerprint.clone(),
+            self.results.len(),
+            self.results.iter().flatten().cloned(),
+        );
+        Task::perform(
+            async move {
+                tokio::task::spawn_blocking(move || checkpoint.save(&path))
+                    .await
+                    .map_err(|e| e.to_string())?
+                    .map_err(|e| e.to_string())
+            },
+            Message::CheckpointSaved,
+        )
+    }
+
+    /// Once the batch is done, deletes its checkpoint, or keeps it if any
+    /// lookups failed so that resuming retries just those.
+    fn settle_checkpoint(&mut self) -> Task<Message> {
+        if self
+            .results
+            .iter()
+            .flatten()
+            .any(|item| item.outcome.is_err())
+        {
+            return self.save_checkpoint();
+        }
+        let Some(path) = checkpoint::default_path() else {
+            return Task::none();
+        };
+        Task::perform(
+            async move {
+                tokio::task::spawn_blocking(move || checkpoint::remove(&path))
+                    .await
+                    .map_err(|e| e.to_string())?
+                    .map_err(|e| e.to_string())
+            },
+            Message::CheckpointSaved,
+        )
+    }
+
+    /// A row per line of the last batch whose password has been checked,
+    /// leaving out those the filter hides if the export is filtered.
+    pub fn export_rows(&self, shared: &Shared) -> Vec<export::Row> {
+        self.rows(
+            self.export_filtered && self.filter.is_active(),
+            self.export_plaintext,
+            shared,
+        )
+    }
+
+    pub fn rows(
+        &self,
+        filtered: bool,
+        include_plaintext: bool,
+        shared: &Shared,
+    ) -> Vec<export::Row> {
+        self.lines
+            .iter()
+            .enumerate()
+            .filter_map(|(index, line)| {
+                let item = self.results.get(line.password)?.as_ref()?;
+                if filtered && !self.filter.matches(line, Some(item)) {
+                    return None;
+                }
+                Some(export::Row {
+                    line: line.number,
+                    label: line.label.clone(),
+                    preview: line.preview.clone(),
+                    password: line.password,
+                    length: (!self.hashed)
+                        .then(|| self.passwords.get(line.password))
+                        .flatten()
+                        .map(|password| password.expose().chars().count()),
+                    prefix: item.prefix.clone(),
+                    algorithm: self.mode,
+                    outcome: item.outcome.clone(),
+                    severity: match item.outcome {
+                        Ok(Some(count)) => {
+                            Some(Severity::from_count(count, &shared.settings.severity))
+                        }
+                        _ => None,
+                    },
+                    checked_at: item.checked_at,
+                    policy: self
+                        .policy
+                        .get(index)
+                        .map(|verdicts| policy::failures(verdicts).cloned().collect()),
+                    // A hash list has no plaintext to include.
+                    plaintext: (include_plaintext && !self.hashed)
+                        .then(|| self.passwords.get(line.password).cloned())
+                        .flatten(),
+                })
+            })
+            .collect()
+    }
+
+    pub fn view<'a>(&'a self, shared: &'a Shared) -> Element<'a, Message> {
+        let strings = shared.strings();
+        let items: Vec<_> = self
+            .lines
+            .iter()
+            .map(|line| self.results.get(line.password).and_then(Option::as_ref))
+            .collect();
+        let shown: Vec<bool> = self
+            .lines
+            .iter()
+            .zip(&items)
+            .map(|(line, item)| self.filter.matches(line, *item))
+            .collect();
+        let outcomes: Vec<_> = items
+            .iter()
+            .map(|item| item.map(|item| &item.outcome))
+            .collect();
+        // The counters only count the lines the filter lets through.
+        let total = shown.iter().filter(|&&shown| shown).count();
+        let done: Vec<_> = outcomes
+            .iter()
+            .zip(&shown)
+            .filter_map(|(outcome, &shown)| outcome.filter(|_| shown))
+            .collect();
+        let breached = done
+            .iter()
+            .filter(|outcome| matches!(outcome, Ok(Some(_))))
+            .count();
+        let failed = done.iter().filter(|outcome| outcome.is_err()).count();
+        let reused_groups = self.reuse.iter().filter(|lines| lines.len() > 1);
+        let (reused, reused_lines) = reused_groups.fold((0, 0), |(reused, lines), group| {
+            (reused + 1, lines + group.len())
+        });
+        let worst = import::worst(&self.reuse, |password| {
+            self.results.get(password)?.as_ref()?.outcome.clone().ok()?
+        });
+        let mut order = batch::sorted_lines(
+            &self.lines,
+            &self.results,
+            self.sort,
+            &shared.settings.severity,
+        );
+        order.retain(|&index| shown[index]);
+        // Only the rows in view are built, between spacers as tall as the
+        // rest, so a batch of any size scrolls as fast as a short one.
+        let window = virtual_rows::Window {
+            rows: order.len(),
+            row_height: ROW_HEIGHT,
+            offset: self.viewport.offset,
+            height: self.viewport.height,
+        };
+        let visible = window.visible();
+        let (before, after) = window.spacers(&visible);
+        let rows = order[visible].iter().map(|&index| {
+            let line = &self.lines[index];
+            let reused = self.reuse.get(line.password).map_or(0, Vec::len);
+            let reused = match reused {
+                0 | 1 => String::new(),
+                _ if self.accounts => (strings.batch_shared_by)(reused),
+                _ if line.label.is_empty() || self.hashed => (strings.batch_reused_times)(reused),
+                _ => (strings.batch_reused_sites)(reused),
+            };
+            let (status, count, severity) = match outcomes[index] {
+                None if self.cancelled => (
+                    text(strings.batch_cancelled).style(text::secondary),
+                    text(""),
+                    text(""),
+                ),
+                None => (
+                    text(strings.batch_checking).style(text::secondary),
+                    text(""),
+                    text(""),
+                ),
+                Some(Ok(Some(count))) => {
+                    let severity = Severity::from_count(*count, &shared.settings.severity);
+                    (
+                        text((strings.batch_breached)(&reused)).style(text::danger),
+                        text(format_count(*count, CountStyle::Full, shared.separator)),
+                        text!("{} {}", severity.icon(), severity).style(move |theme: &Theme| {
+                            text::Style {
+                                color: Some(severity_view::color(severity, theme)),
+                            }
+                        }),
+                    )
+                }
+                Some(Ok(None)) => (
+                    text((strings.batch_not_found)(&reused)).style(text::success),
+                    text("0"),
+                    text(""),
+                ),
+                Some(Err(error)) => (
+                    text((strings.batch_error)(error)).style(text::danger),
+                    text(""),
+                    text(""),
+                ),
+            };
+            // Rows must keep to their height for the spacers to add up, so
+            // long errors and previews are cut off rather than wrapped.
+            row![
+                text!("{}", line.number).width(LINE_WIDTH),
+                text(labelled(line))
+                    .style(move |theme: &Theme| text::Style {
+                        color: (worst == Some(line.password)).then(|| theme
+                            .extended_palette()
+                            .danger
+                            .base
+                            .color),
+                    })
+                    .font(iced::Font::MONOSPACE)
+                    .wrapping(text::Wrapping::None)
+                    .width(Length::FillPortion(2)),
+                status
+                    .wrapping(text::Wrapping::None)
+                    .width(Length::FillPortion(2)),
+                count.width(Length::FillPortion(1)),
+                severity.width(Length::FillPortion(1)),
+            ]
+            .push_maybe(self.policy.get(index).map(|verdicts| {
+                let failed: Vec<String> = policy::failures(verdicts)
+                    .map(ToString::to_string)
+                    .collect();
+                if failed.is_empty() {
+                    text(strings.policy_met).style(text::success)
+                } else {
+                    text(failed.join(", ")).style(text::danger)
+                }
+                .wrapping(text::Wrapping::None)
+                .width(Length::FillPortion(2))
+            }))
+            .spacing(10)
+            .height(ROW_HEIGHT)
+            .clip(true)
+            .into()
+        });
+        let lines = column![vertical_space().height(before)]
+            .extend(rows)
+            .push(vertical_space().height(after));
+        let header = |label: &'static str, column: batch::SortColumn| {
+            let arrow = match self.sort {
+                batch::Sort {
+                    column: sorted,
+                    descending,
+                } if sorted == column => {
+                    if descending {
+                        " ▼"
+                    } else {
+                        " ▲"
+                    }
+                }
+                _ => "",
+            };
+            button(text!("{}{}", label, arrow))
+                .style(button::text)
+                .padding(0)
+                .on_press(Message::Sort(column))
+        };
+        let table_header = row![
+            header("#", batch::SortColumn::Line).width(LINE_WIDTH),
+            text(strings.mode_password).width(Length::FillPortion(2)),
+            text(strings.column_status).width(Length::FillPortion(2)),
+            header(strings.column_count, batch::SortColumn::Count).width(Length::FillPortion(1)),
+            header(strings.column_severity, batch::SortColumn::Severity)
+                .width(Length::FillPortion(1)),
+        ]
+        .push_maybe(
+            (!self.policy.is_empty())
+                .then(|| text(strings.column_policy).width(Length::FillPortion(2))),
+        )
+        .spacing(10);
+
+        column![
+            text(strings.batch_prompt),
+            text_editor(&self.input)
+                .on_action(Message::Edit)
+                .height(Length::Fixed(150.)),
+            row![
+                button(strings.check_all)
+                    .on_press_maybe(self.can_start().then_some(Message::CheckAll)),
+                button(strings.import_file)
+                    .on_press_maybe(self.run.is_none().then_some(Message::ImportFile)),
+                button(text((strings.import_hashes)(shared.mode)))
+                    .on_press_maybe(self.run.is_none().then_some(Message::ImportHashes)),
+            ]
+            .push_maybe(
+                self.run
+                    .as_ref()
+                    .map(|_| button(strings.cancel).on_press(Message::Cancel)),
+            )
+            .push(
+                text((strings.batch_counts)(done.len(), total, breached, failed))
+                    .style(text::secondary),
+            )
+            .push_maybe(self.filter.is_active().then(|| {
+                text((strings.batch_showing)(total, self.lines.len())).style(text::secondary)
+            }))
+            .push_maybe((reused_lines > 0).then(|| {
+                if self.accounts {
+                    text((strings.batch_shared_passwords)(reused, reused_lines))
+                } else {
+                    text((strings.batch_reused_passwords)(reused, reused_lines))
+                }
+                .style(text::secondary)
+            }))
+            .spacing(10)
+            .align_y(Vertical::Center),
+            row![
+                text(strings.dump_include).style(text::secondary),
+                checkbox(
+                    strings.dump_machine_accounts,
+                    self.dump_include.machine_accounts
+                )
+                .on_toggle(Message::DumpMachineAccounts),
+                checkbox(strings.dump_history, self.dump_include.history)
+                    .on_toggle(Message::DumpHistory),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+        ]
+        .push_maybe(self.resume.as_ref().map(|done| {
+            row![
+                text((strings.batch_interrupted)(done.len(), self.results.len())),
+                button(strings.resume).on_press(Message::Resume(true)),
+                button(strings.start_over).on_press(Message::Resume(false)),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center)
+        }))
+        .push_maybe(self.shows_progress().then(|| self.progress_view(shared)))
+        .push_maybe(
+            self.import
+                .as_ref()
+                .map(|imported| self.import_preview(imported, shared)),
+        )
+        .push_maybe(
+            self.mapping
+                .as_ref()
+                .map(|screen| screen.view(strings).map(Message::Mapping)),
+        )
+        .push_maybe(
+            self.audit
+                .as_ref()
+                .filter(|_| self.run.is_none())
+                .map(|audit| self.audit_view(audit, shared)),
+        )
+        .push_maybe(
+            (self.run.is_none() && !self.lines.is_empty()).then(|| self.export_view(shared)),
+        )
+        .push_maybe(
+            // Takes whatever height the window has left.
+            (!self.lines.is_empty()).then(|| {
+                column![]
+                    .push_maybe(worst.map(|password| self.worst_view(password, shared)))
+                    .push(self.filter_view(shared))
+                    .push(table_header)
+                    .push(
+                        scrollable(lines)
+                            .id(scrollable::Id::new(TABLE))
+                            .on_scroll(Message::Scrolled)
+                            .height(Length::Fill),
+                    )
+                    .spacing(4)
+            }),
+        )
+        .spacing(5)
+        .into()
+    }
+
+    /// The finished batch's summary: how much was breached and how badly,
+    /// the worst passwords by their labels, reuse and lengths.
+    fn audit_view<'a>(&self, audit: &'a Audit, shared: &Shared) -> Element<'a, Message> {
+        let lines = column![text(shared.strings().batch_summary).size(16)]
+            .extend(
+                audit
+                    .lines(shared.separator)
+                    .into_iter()
+                    .map(|line| text(line).into()),
+            )
+            .spacing(2);
+        container(lines)
+            .padding(5)
+            .style(container::rounded_box)
+            .into()
+    }
+
+    /// The batch's worst finding, the breached `password` the table
+    /// highlights, and every site it's used on.
+    fn worst_view<'a>(&'a self, password: usize, shared: &'a Shared) -> Element<'a, Message> {
+        let strings = shared.strings();
+        let count = self
+            .results
+            .get(password)
+            .and_then(Option::as_ref)
+            .and_then(|item| item.outcome.clone().ok().flatten())
+            .unwrap_or_default();
+        let count = format_count(count, CountStyle::Full, shared.separator);
+        let lines = self.reuse.get(password).map_or(&[][..], Vec::as_slice);
+        let sites: Vec<String> = lines
+            .iter()
+            .take(WORST_SITES)
+            .map(|&index| {
+                let line = &self.lines[index];
+                if line.label.is_empty() {
+                    (strings.worst_line)(line.number)
+                } else {
+                    line.label.clone()
+                }
+            })
+            .collect();
+        let more = match lines.len().saturating_sub(WORST_SITES) {
+            0 => String::new(),
+            more => (strings.worst_more)(more),
+        };
+        let labelled = lines
+            .first()
+            .is_some_and(|&index| !self.lines[index].label.is_empty());
+        let finding = if lines.len() > 1 {
+            (strings.worst_reused)(
+                &count,
+                lines.len(),
+                match labelled {
+                    _ if self.accounts => strings.worst_accounts,
+                    true => strings.worst_sites,
+                    false => strings.worst_lines,
+                },
+                &sites.join(", "),
+                &more,
+            )
+        } else {
+            (strings.worst_single)(&sites.concat(), &count)
+        };
+        text(finding).style(text::danger).into()
+    }
+
+    fn filter_view<'a>(&'a self, shared: &'a Shared) -> Element<'a, Message> {
+        let strings = shared.strings();
+        let filter = &self.filter;
+        let min_count = match filter.min_count {
+            0 => String::new(),
+            count => count.to_string(),
+        };
+        row![
+            text_input(strings.filter_placeholder, &filter.text)
+                .style(high_contrast::text_input)
+                .on_input(Message::FilterText)
+                .width(Length::FillPortion(2)),
+            checkbox(strings.breached_only, filter.breached_only).on_toggle(Message::BreachedOnly),
+            checkbox(strings.errors_only, filter.errors_only).on_toggle(Message::ErrorsOnly),
+            text(strings.seen_at_least),
+            text_input("0", &min_count)
+                .style(high_contrast::text_input)
+                .on_input(Message::MinCount)
+                .width(90),
+            text(strings.seen_at_least_times),
+        ]
+        .spacing(10)
+        .align_y(Vertical::Center)
+        .into()
+    }
+
+    /// Whether there's a list to check and no batch running already.
+    pub fn can_start(&self) -> bool {
+        self.run.is_none() && !self.input.text().trim().is_empty()
+    }
+
+    /// Whether the batch tab shows its progress bar: only while a batch is
+    /// running, not once it's finished or been cancelled.
+    pub fn shows_progress(&self) -> bool {
+        self.run.is_some()
+    }
+
+    fn progress_view<'a>(&'a self, shared: &'a Shared) -> Element<'a, Message> {
+        let stats = self.stats.unwrap_or(BatchStats {
+            total: self.results.len(),
+            ..BatchStats::default()
+        });
+        let strings = shared.strings();
+        let eta = match stats.eta() {
+            Some(eta) => (strings.batch_eta)(eta.as_secs()),
+            None => strings.batch_estimating.to_owned(),
+        };
+        column![
+            progress_bar(0.0..=1.0, stats.fraction()).height(8),
+            text((strings.batch_progress)(&stats, &eta)).style(text::secondary),
+        ]
+        .spacing(2)
+        .into()
+    }
+
+    fn export_view<'a>(&'a self, shared: &'a Shared) -> Element<'a, Message> {
+        let strings = shared.strings();
+        let status = self
+            .export
+            .as_ref()
+            .map(|exported| exported_status(strings, exported));
+        let warning = self.confirm_plaintext.then(|| {
+            row![
+                text(strings.plaintext_warning).style(text::danger),
+                button(strings.include_plaintext)
+                    .style(button::danger)
+                    .on_press(Message::ConfirmPlaintext(true)),
+                button(strings.cancel)
+                    .style(button::secondary)
+                    .on_press(Message::ConfirmPlaintext(false)),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center)
+        });
+        column![
+            row![
+                button(strings.export_csv).on_press(Message::ExportCsv),
+                button(strings.export_json).on_press(Message::ExportJson),
+                checkbox(
+                    strings.include_plaintext_dangerous,
+                    self.export_plaintext || self.confirm_plaintext
+                )
+                .on_toggle(Message::ExportPlaintext),
+            ]
+            .push_maybe(self.filter.is_active().then(|| {
+                checkbox(strings.only_lines_shown, self.export_filtered)
+                    .on_toggle(Message::ExportFiltered)
+            }))
+            .spacing(10)
+            .align_y(Vertical::Center),
+        ]
+        .push_maybe(warning)
+        .push_maybe(status)
+        .push(report_view(
+            strings,
+            shared.report_export.as_ref(),
+            Message::ExportReport,
+        ))
+        .spacing(5)
+        .into()
+    }
+
+    fn import_preview<'a>(
+        &'a self,
+        imported: &'a Result<Arc<batch::Lines>, String>,
+        shared: &Shared,
+    ) -> Element<'a, Message> {
+        let strings = shared.strings();
+        let batch = match imported {
+            Ok(batch) => batch,
+            Err(error) => {
+                return text((strings.import_failed)(error))
+                    .style(text::danger)
+                    .into();
+            }
+        };
+        let preview = column(batch.lines.iter().take(IMPORT_PREVIEW).map(|line| {
+            text!("{}  {}", line.number, labelled(line))
+                .font(iced::Font::MONOSPACE)
+                .into()
+        }));
+        let malformed = (!batch.malformed.is_empty()).then(|| {
+            column![
+                text((strings.import_unreadable)(
+                    batch.malformed.len(),
+                    batch.hashed.is_some()
+                ))
+                .style(text::danger)
+            ]
+            .extend(
+                batch
+                    .malformed
+                    .iter()
+                    .take(IMPORT_PREVIEW)
+                    .map(|malformed| text(malformed.to_string()).style(text::danger).into()),
+            )
+            .spacing(2)
+        });
+        column![
+            text((strings.import_summary)(
+                batch.lines.len(),
+                batch.hashed,
+                batch.passwords.len()
+            )),
+            preview,
+        ]
+        .push_maybe((batch.lines.len() > IMPORT_PREVIEW).then(|| text("…").style(text::secondary)))
+        .push_maybe(malformed)
+        .push(
+            button(text((strings.check_imported)(batch.lines.len()))).on_press_maybe(
+                (self.run.is_none() && !batch.lines.is_empty()).then_some(Message::CheckImported),
+            ),
+        )
+        .spacing(2)
+        .into()
+    }
+}
+
+/// What importing a file came to.
+#[derive(Debug, Clone)]
+pub enum Imported {
+    Lines(Arc<batch::Lines>),
+    /// A CSV file that isn't a known export, with no mapping saved for it.
+    Unmapped(Unmapped),
+}
+
+/// Reads a password list for batch checking, or a `.csv` export from
+/// Bitwarden or KeePass, or another CSV file by the columns mapped for it
+/// in `mappings`, refusing oversized files before reading them. The file's
+/// bytes are wiped once it's been read; one whose columns are still to be
+/// mapped is kept until then.
+async fn import_list(
+    path: PathBuf,
+    include: pwdump::Include,
+    mappings: BTreeMap<String, import::Mapping>,
+) -> Result<Imported, String> {
+    let bytes = read_import(&path).await?;
+    let csv = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("csv"));
+    tokio::task::spawn_blocking(move || {
+        if csv {
+            return match import::read_known(&bytes, None, &mappings)? {
+                Some(lines) => Ok(Imported::Lines(Arc::new(lines))),
+                None => Ok(Imported::Unmapped(Unmapped(Arc::new(bytes)))),
+            };
+        }
+        if pwdump::detect(&bytes) {
+            pwdump::read(&bytes, include)
+        } else {
+            batch::read_list(&bytes)
+        }
+        .map(|lines| Imported::Lines(Arc::new(lines)))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Reads a list of `mode` hashes for batch checking, as `import_list` does
+/// a password list. A directory dump is read as one whatever the mode, as
+/// its hashes are always NTLM.
+async fn import_hashes(
+    path: PathBuf,
+    mode: HashMode,
+    include: pwdump::Include,
+) -> Result<Arc<batch::Lines>, String> {
+    let bytes = read_import(&path).await?;
+    tokio::task::spawn_blocking(move || {
+        if pwdump::detect(&bytes) {
+            pwdump::read(&bytes, include)
+        } else {
+            batch::read_hashes(&bytes, mode)
+        }
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map(Arc::new)
+}
+
+/// The bytes of a file to import, unless it's larger than a batch allows.
+async fn read_import(path: &Path) -> Result<Zeroizing<Vec<u8>>, String> {
+    let size = tokio::fs::metadata(path)
+        .await
+        .map_err(|e| format!("could not read {}: {e}", path.display()))?
+        .len();
+    if size > batch::MAX_IMPORT_BYTES {
+        return Err(format!(
+            "the file is {} MB, more than the {} MB a batch allows",
+            size / 1024 / 1024,
+            batch::MAX_IMPORT_BYTES / 1024 / 1024
+        ));
+    }
+    tokio::fs::read(path)
+        .await
+        .map(Zeroizing::new)
+        .map_err(|e| format!("could not read {}: {e}", path.display()))
+}
+
+/// A batch line's masked password, after its label if it was imported from
+/// a password manager.
+fn labelled(line: &batch::Line) -> String {
+    if line.label.is_empty() {
+        line.preview.clone()
+    } else {
+        format!("{}  {}", line.label, line.preview)
+    }
+}
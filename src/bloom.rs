@@ -0,0 +1,595 @@
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use memmap2::{Mmap, MmapMut};
+use thiserror::Error;
+
+use crate::HashMode;
+
+/// The filter format this build writes and reads. Bumped whenever a change
+/// would make an older filter answer wrongly.
+pub const VERSION: u32 = 1;
+
+/// What `--build-filter` aims for when not told otherwise: one hash in a
+/// hundred that isn't in the dataset still has to be searched for.
+pub const DEFAULT_FALSE_POSITIVE_RATE: f64 = 0.01;
+
+const MAGIC: &[u8; 8] = b"PWBLOOM\0";
+
+/// The header is padded to this, so the bits start on a round offset.
+const HEADER_LEN: usize = 64;
+
+/// How many lines at the start of the dataset its size is estimated from.
+const SAMPLE_LINES: usize = 4096;
+
+/// How many lines are read between calls to the progress callback.
+const PROGRESS_LINES: u64 = 1 << 20;
+
+/// Why a filter can't be built or used.
+#[derive(Debug, Error)]
+pub enum FilterError {
+    #[error("{}: {error}", path.display())]
+    Io { path: PathBuf, error: io::Error },
+    #[error("{} isn't a readable filter: {reason}", path.display())]
+    Corrupt { path: PathBuf, reason: String },
+    #[error("{} is from a newer or older version (format {found}, this one reads {VERSION})", path.display())]
+    Version { path: PathBuf, found: u32 },
+    #[error("{} was built from a different copy of the dataset; build it again", path.display())]
+    Stale { path: PathBuf },
+    #[error("{}: line {line} isn't HASH:COUNT: {text:?}", path.display())]
+    Malformed {
+        path: PathBuf,
+        line: u64,
+        text: String,
+    },
+}
+
+/// What a filter was built from and how, as its header has it:
+///
+/// ```text
+/// offset  size  field
+///      0     8  "PWBLOOM\0"
+///      8     4  format version
+///     12     4  hashes per entry
+///     16     8  seed
+///     24     8  the false positive rate it was sized for
+///     32     8  bits
+///     40     8  entries
+///     48     8  the length of the dataset it was built from
+///     56     1  0 for SHA-1, 1 for NTLM
+/// ```
+///
+/// Every number is little-endian, and the bits follow at byte 64.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Header {
+    pub hashes: u32,
+    pub seed: u64,
+    pub false_positive_rate: f64,
+    pub bits: u64,
+    pub entries: u64,
+    /// So a filter is only trusted for the dataset it was built from: a
+    /// fresh download is all but certain to differ in length.
+    pub dataset_len: u64,
+    pub mode: HashMode,
+}
+
+impl Header {
+    /// The false positive rate the filter actually has for the entries in
+    /// it, which is above the one it was sized for if the dataset turned
+    /// out bigger than estimated.
+    pub fn expected_false_positive_rate(&self) -> f64 {
+        let (k, n, m) = (
+            f64::from(self.hashes),
+            self.entries as f64,
+            self.bits as f64,
+        );
+        (1.0 - (-k * n / m).exp()).powf(k)
+    }
+
+    fn to_bytes(self) -> [u8; HEADER_LEN] {
+        let mut bytes = [0; HEADER_LEN];
+        bytes[..8].copy_from_slice(MAGIC);
+        bytes[8..12].copy_from_slice(&VERSION.to_le_bytes());
+        bytes[12..16].copy_from_slice(&self.hashes.to_le_bytes());
+        bytes[16..24].copy_from_slice(&self.seed.to_le_bytes());
+        bytes[24..32].copy_from_slice(&self.false_positive_rate.to_le_bytes());
+        bytes[32..40].copy_from_slice(&self.bits.to_le_bytes());
+        bytes[40..48].copy_from_slice(&self.entries.to_le_bytes());
+        bytes[48..56].copy_from_slice(&self.dataset_len.to_le_bytes());
+        bytes[56] = match self.mode {
+            HashMode::Sha1 => 0,
+            HashMode::Ntlm => 1,
+        };
+        bytes
+    }
+
+    fn parse(bytes: &[u8], path: &Path) -> Result<Self, FilterError> {
+        let corrupt = |reason: &str| FilterError::Corrupt {
+            path: path.to_owned(),
+            reason: reason.to_owned(),
+        };
+        let header = bytes
+            .get(..HEADER_LEN)
+            .ok_or_else(|| corrupt("it's too short for a header"))?;
+        if &header[..8] != MAGIC {
+            return Err(corrupt("it doesn't start with the filter magic"));
+        }
+        let u32_at = |at: usize| u32::from_le_bytes(header[at..at + 4].try_into().unwrap());
+        let u64_at = |at: usize| u64::from_le_bytes(header[at..at + 8].try_into().unwrap());
+        let found = u32_at(8);
+        if found != VERSION {
+            return Err(FilterError::Version {
+                path: path.to_owned(),
+                found,
+            });
+        }
+        let header = Self {
+            hashes: u32_at(12),
+            seed: u64_at(16),
+            false_positive_rate: f64::from_bits(u64_at(24)),
+            bits: u64_at(32),
+            entries: u64_at(40),
+            dataset_len: u64_at(48),
+            mode: match header[56] {
+                0 => HashMode::Sha1,
+                1 => HashMode::Ntlm,
+                _ => return Err(corrupt("the hash algorithm is unknown")),
+            },
+        };
+        if header.hashes == 0 || header.bits == 0 {
+            return Err(corrupt("it has no hashes or no bits"));
+        }
+        if (bytes.len() - HEADER_LEN) as u64 != header.bits.div_ceil(8) {
+            return Err(corrupt("its length doesn't match its header"));
+        }
+        Ok(header)
+    }
+}
+
+/// A Bloom filter of the hashes in an offline dataset, so a lookup can tell
+/// in a few memory reads that a hash certainly isn't there and skip the
+/// search of the file, or the request, altogether. A hash it might contain
+/// is searched for as before.
+///
+/// The file is mapped rather than read, so opening it costs nothing however
+/// big it is, and only the pages lookups touch are ever loaded.
+#[derive(Debug)]
+pub struct Filter {
+    header: Header,
+    map: Mmap,
+}
+
+/// A false positive rate above 0 and below 1, as in 0.01.
+pub fn parse_false_positive_rate(input: &str) -> Result<f64, String> {
+    match input.trim().parse::<f64>() {
+        Ok(rate) if rate > 0.0 && rate < 1.0 => Ok(rate),
+        _ => Err(format!(
+            "expected a number above 0 and below 1, got \"{input}\""
+        )),
+    }
+}
+
+/// Where the filter of the dataset at `dataset` is kept: beside it, as
+/// `dataset.bloom`.
+pub fn path_for(dataset: &Path) -> PathBuf {
+    let mut path = OsString::from(dataset.as_os_str());
+    path.push(".bloom");
+    PathBuf::from(path)
+}
+
+impl Filter {
+    pub fn open(path: &Path) -> Result<Self, FilterError> {
+        let io_error = |error| FilterError::Io {
+            path: path.to_owned(),
+            error,
+        };
+        let file = File::open(path).map_err(io_error)?;
+        // SAFETY: the map is only read, and a filter is written to a
+        // temporary file and renamed into place, never changed where it is.
+        // One truncated under us by something else would fault rather than
+        // answer wrongly.
+        let map = unsafe { Mmap::map(&file) }.map_err(io_error)?;
+        let header = Header::parse(&map, path)?;
+        Ok(Self { header, map })
+    }
+
+    /// The filter beside the dataset at `dataset`, or `None` if none has
+    /// been built for it.
+    pub fn for_dataset(dataset: &Path) -> Result<Option<Self>, FilterError> {
+        let path = path_for(dataset);
+        let filter = match Self::open(&path) {
+            Ok(filter) => filter,
+            Err(FilterError::Io { error, .. }) if error.kind() == io::ErrorKind::NotFound => {
+                return Ok(None);
+            }
+            Err(error) => return Err(error),
+        };
+        let dataset_len = fs::metadata(dataset)
+            .map_err(|error| FilterError::Io {
+                path: dataset.to_owned(),
+                error,
+            })?
+            .len();
+        if filter.header.dataset_len != dataset_len {
+            return Err(FilterError::Stale { path });
+        }
+        Ok(Some(filter))
+    }
+
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Whether `hash` may be in the dataset. `false` is certain; `true` is
+    /// right except at about the false positive rate, and is also the answer
+    /// for anything that isn't a hash of the filter's kind, such as an NTLM
+    /// hash against a SHA-1 dataset, so that it's searched for rather than
+    /// ruled out.
+    pub fn might_contain(&self, hash: &str) -> bool {
+        let Some(keys) = keys(hash, self.header.mode) else {
+            return true;
+        };
+        let bits = &self.map[HEADER_LEN..];
+        positions(keys, &self.header).all(|bit| bits[(bit / 8) as usize] & mask(bit) != 0)
+    }
+}
+
+/// Builds the filter of the `HASH:COUNT` dataset at `dataset` into
+/// `output`, sized for `false_positive_rate`, calling `progress` with the
+/// bytes read so far and the dataset's length as it goes.
+///
+/// The dataset is streamed, so it's never all in memory, and so is the
+/// filter: it's built in a mapped temporary file that's renamed to
+/// `output` once it's whole. The number of entries is estimated from the
+/// length of the dataset's first lines, which varies little as the hashes
+/// are random.
+pub fn build(
+    dataset: &Path,
+    output: &Path,
+    false_positive_rate: f64,
+    mut progress: impl FnMut(u64, u64),
+) -> Result<Header, FilterError> {
+    let dataset_error = |error| FilterError::Io {
+        path: dataset.to_owned(),
+        error,
+    };
+    let output_error = |error| FilterError::Io {
+        path: output.to_owned(),
+        error,
+    };
+    let file = File::open(dataset).map_err(dataset_error)?;
+    let dataset_len = file.metadata().map_err(dataset_error)?.len();
+    let mut reader = BufReader::new(file);
+    let estimate = estimate_entries(&mut reader, dataset_len).map_err(dataset_error)?;
+    let (bits, hashes) = size_for(estimate, false_positive_rate);
+    let mut header = Header {
+        hashes,
+        seed: rand::random(),
+        false_positive_rate,
+        bits,
+        entries: 0,
+        dataset_len,
+        mode: HashMode::Sha1,
+    };
+
+    let mut temp = OsString::from(output.as_os_str());
+    temp.push(".tmp");
+    let temp = PathBuf::from(temp);
+    let out = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&temp)
+        .map_err(output_error)?;
+    out.set_len(HEADER_LEN as u64 + bits.div_ceil(8))
+        .map_err(output_error)?;
+    // SAFETY: the temporary file was just created by us and is only
+    // renamed into place once the map is flushed and dropped.
+    let mut map = unsafe { MmapMut::map_mut(&out) }.map_err(output_error)?;
+
+    // A dataset that turns out malformed leaves nothing behind.
+    if let Err(error) = fill(
+        &mut reader,
+        dataset,
+        &mut map[HEADER_LEN..],
+        &mut header,
+        &mut progress,
+    ) {
+        drop(map);
+        let _ = fs::remove_file(&temp);
+        return Err(error);
+    }
+    map[..HEADER_LEN].copy_from_slice(&header.to_bytes());
+    map.flush().map_err(output_error)?;
+    drop(map);
+    fs::rename(&temp, output).map_err(output_error)?;
+    progress(dataset_len, dataset_len);
+    Ok(header)
+}
+
+/// Sets the `bits` of every hash `reader` has left, counting them into
+/// `header` along with the kind of hash they are.
+fn fill(
+    reader: &mut BufReader<File>,
+    dataset: &Path,
+    bits: &mut [u8],
+    header: &mut Header,
+    progress: &mut impl FnMut(u64, u64),
+) -> Result<(), FilterError> {
+    let dataset_error = |error| FilterError::Io {
+        path: dataset.to_owned(),
+        error,
+    };
+    let (mut line, mut read) = (Vec::new(), 0);
+    let mut mode = None;
+    loop {
+        line.clear();
+        let n = reader.read_until(b'\n', &mut line).map_err(dataset_error)?;
+        if n == 0 {
+            break;
+        }
+        read += n as u64;
+        let text = line.trim_ascii_end();
+        if text.is_empty() {
+            continue;
+        }
+        header.entries += 1;
+        let malformed = || FilterError::Malformed {
+            path: dataset.to_owned(),
+            line: header.entries,
+            text: String::from_utf8_lossy(text).into_owned(),
+        };
+        let (hash, count) = std::str::from_utf8(text)
+            .ok()
+            .and_then(|text| text.split_once(':'))
+            .ok_or_else(malformed)?;
+        let line_mode = match hash.len() {
+            40 => HashMode::Sha1,
+            32 => HashMode::Ntlm,
+            _ => return Err(malformed()),
+        };
+        if *mode.get_or_insert(line_mode) != line_mode || count.parse::<u64>().is_err() {
+            return Err(malformed());
+        }
+        let keys = keys(hash, line_mode).ok_or_else(malformed)?;
+        for bit in positions(keys, header) {
+            bits[(bit / 8) as usize] |= mask(bit);
+        }
+        if header.entries.is_multiple_of(PROGRESS_LINES) {
+            progress(read, header.dataset_len);
+        }
+    }
+    header.mode = mode.unwrap_or_default();
+    Ok(())
+}
+
+/// Extrapolates the number of lines from the first [`SAMPLE_LINES`], with a
+/// little to spare, and rewinds `reader`.
+fn estimate_entries(reader: &mut BufReader<File>, len: u64) -> io::Result<u64> {
+    let (mut line, mut lines, mut bytes) = (Vec::new(), 0, 0);
+    while lines < SAMPLE_LINES {
+        line.clear();
+        match reader.read_until(b'\n', &mut line)? {
+            0 => break,
+            n => (lines, bytes) = (lines + 1, bytes + n as u64),
+        }
+    }
+    io::Seek::rewind(reader)?;
+    if bytes == 0 {
+        return Ok(1);
+    }
+    let average = bytes as f64 / lines as f64;
+    Ok((len as f64 / average * 1.02).ceil() as u64 + 1)
+}
+
+/// The bits and hashes per entry that give `entries` entries a false
+/// positive rate of `false_positive_rate`.
+fn size_for(entries: u64, false_positive_rate: f64) -> (u64, u32) {
+    let ln2 = std::f64::consts::LN_2;
+    let n = entries.max(1) as f64;
+    let bits = (-n * false_positive_rate.ln() / (ln2 * ln2))
+        .ceil()
+        .max(64.0);
+    let hashes = (bits / n * ln2).round().clamp(1.0, 32.0);
+    (bits as u64, hashes as u32)
+}
+
+/// Two 64-bit keys from the first 32 hex digits of `hash`. The hashes in
+/// the dataset are already uniformly random, so their digits serve as the
+/// filter's hash functions' input with only the seed mixed in.
+fn keys(hash: &str, mode: HashMode) -> Option<(u64, u64)> {
+    let len = match mode {
+        HashMode::Sha1 => 40,
+        HashMode::Ntlm => 32,
+    };
+    if hash.len() != len || !hash.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return None;
+    }
+    let high = u64::from_str_radix(&hash[..16], 16).ok()?;
+    let low = u64::from_str_radix(&hash[16..32], 16).ok()?;
+    Some((high, low))
+}
+
+/// The bits an entry with `keys` sets, by double hashing: each is the first
+/// key plus a multiple of the second, over the seeded, mixed keys.
+fn positions((high, low): (u64, u64), header: &Header) -> impl Iterator<Item = u64> + use<> {
+    let first = mix(high ^ header.seed);
+    // Odd, so the steps never all land on the same bit.
+    let step = mix(low ^ header.seed.rotate_left(32)) | 1;
+    let bits = header.bits;
+    (0..u64::from(header.hashes)).map(move |i| first.wrapping_add(i.wrapping_mul(step)) % bits)
+}
+
+/// The splitmix64 finalizer.
+fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+fn mask(bit: u64) -> u8 {
+    1 << (bit % 8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DATASET: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/dataset-sha1.txt"
+    );
+
+    fn dataset_hashes() -> Vec<String> {
+        fs::read_to_string(DATASET)
+            .unwrap()
+            .lines()
+            .map(|line| line.split(':').next().unwrap().to_owned())
+            .collect()
+    }
+
+    /// A copy of the fixture dataset in a directory of its own, with a
+    /// filter built beside it.
+    fn built() -> (tempfile::TempDir, PathBuf, Header) {
+        let dir = tempfile::tempdir().unwrap();
+        let dataset = dir.path().join("pwned.txt");
+        fs::copy(DATASET, &dataset).unwrap();
+        let header = build(&dataset, &path_for(&dataset), 0.01, |_, _| {}).unwrap();
+        (dir, dataset, header)
+    }
+
+    #[test]
+    fn every_hash_built_in_might_be_contained() {
+        let (_dir, dataset, header) = built();
+        assert_eq!(header.entries, 20);
+        assert_eq!(header.mode, HashMode::Sha1);
+        assert_eq!(header.dataset_len, fs::metadata(&dataset).unwrap().len());
+        let filter = Filter::for_dataset(&dataset).unwrap().unwrap();
+        assert_eq!(filter.header(), &header);
+        for hash in dataset_hashes() {
+            assert!(filter.might_contain(&hash), "{hash}");
+        }
+    }
+
+    #[test]
+    fn most_hashes_not_built_in_are_ruled_out() {
+        let (_dir, dataset, _) = built();
+        let filter = Filter::for_dataset(&dataset).unwrap().unwrap();
+        let absent = (0..1000u32).map(|n| format!("{n:040X}"));
+        let false_positives = absent.filter(|hash| filter.might_contain(hash)).count();
+        // Sized for 1%; a tenth would mean the filter isn't filtering.
+        assert!(false_positives < 100, "{false_positives} of 1000");
+    }
+
+    #[test]
+    fn what_isnt_a_hash_of_its_kind_is_never_ruled_out() {
+        let (_dir, dataset, _) = built();
+        let filter = Filter::for_dataset(&dataset).unwrap().unwrap();
+        assert!(filter.might_contain("8846F7EAEE8FB117AD06BDD830B7586C"));
+        assert!(filter.might_contain("hunter2"));
+    }
+
+    #[test]
+    fn a_header_round_trips() {
+        let (_dir, dataset, header) = built();
+        let bytes = fs::read(path_for(&dataset)).unwrap();
+        assert_eq!(bytes[..HEADER_LEN], header.to_bytes());
+        assert_eq!(Header::parse(&bytes, &dataset).unwrap(), header);
+    }
+
+    #[test]
+    fn a_filter_of_another_version_is_refused() {
+        let (_dir, dataset, _) = built();
+        let path = path_for(&dataset);
+        let mut bytes = fs::read(&path).unwrap();
+        bytes[8..12].copy_from_slice(&(VERSION + 1).to_le_bytes());
+        fs::write(&path, &bytes).unwrap();
+        match Filter::for_dataset(&dataset) {
+            Err(FilterError::Version { found, .. }) => assert_eq!(found, VERSION + 1),
+            other => panic!("{other:?}"),
+        }
+    }
+
+    #[test]
+    fn a_damaged_filter_is_refused_as_corrupt() {
+        let (_dir, dataset, _) = built();
+        let path = path_for(&dataset);
+        let bytes = fs::read(&path).unwrap();
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = b'X';
+        let mut no_hashes = bytes.clone();
+        no_hashes[12..16].fill(0);
+        let mut unknown_mode = bytes.clone();
+        unknown_mode[56] = 7;
+        for damaged in [
+            bad_magic,
+            no_hashes,
+            unknown_mode,
+            bytes[..bytes.len() - 1].to_vec(),
+            bytes[..HEADER_LEN - 1].to_vec(),
+        ] {
+            fs::write(&path, &damaged).unwrap();
+            let error = Filter::open(&path).unwrap_err();
+            assert!(matches!(error, FilterError::Corrupt { .. }), "{error:?}");
+        }
+    }
+
+    #[test]
+    fn a_filter_is_only_used_for_the_dataset_it_was_built_from() {
+        let (_dir, dataset, _) = built();
+        let mut file = OpenOptions::new().append(true).open(&dataset).unwrap();
+        io::Write::write_all(&mut file, b"0000000000000000000000000000000000000000:1\n").unwrap();
+        let error = Filter::for_dataset(&dataset).unwrap_err();
+        assert!(matches!(error, FilterError::Stale { .. }), "{error:?}");
+    }
+
+    #[test]
+    fn a_dataset_without_a_filter_has_none() {
+        assert!(Filter::for_dataset(Path::new(DATASET)).unwrap().is_none());
+    }
+
+    #[test]
+    fn a_malformed_dataset_leaves_no_filter_behind() {
+        let dir = tempfile::tempdir().unwrap();
+        let dataset = dir.path().join("pwned.txt");
+        let hash = "F3BBBD66A63D4BF1747940578EC3D0103530E21D";
+        fs::write(&dataset, format!("{hash}:37\n{hash}\n")).unwrap();
+        let output = path_for(&dataset);
+        match build(&dataset, &output, 0.01, |_, _| {}) {
+            Err(FilterError::Malformed { line, text, .. }) => {
+                assert_eq!((line, text.as_str()), (2, hash))
+            }
+            other => panic!("{other:?}"),
+        }
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn a_false_positive_rate_is_between_0_and_1() {
+        assert_eq!(parse_false_positive_rate(" 0.001 "), Ok(0.001));
+        for input in ["0", "1", "-0.5", "1%", ""] {
+            assert!(parse_false_positive_rate(input).is_err(), "{input}");
+        }
+    }
+
+    #[test]
+    fn a_filter_is_sized_for_its_rate() {
+        let (bits, hashes) = size_for(1_000_000, 0.01);
+        // About 9.6 bits and 7 hashes an entry.
+        assert_eq!(bits.div_ceil(1_000_000), 10);
+        assert_eq!(hashes, 7);
+        let header = Header {
+            hashes,
+            seed: 0,
+            false_positive_rate: 0.01,
+            bits,
+            entries: 1_000_000,
+            dataset_len: 0,
+            mode: HashMode::Sha1,
+        };
+        assert!((header.expected_false_positive_rate() - 0.01).abs() < 0.001);
+    }
+}
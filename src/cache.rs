@@ -0,0 +1,496 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use directories::ProjectDirs;
+use thiserror::Error;
+use url::Url;
+
+use crate::HashMode;
+use crate::range::{Range, parse_range};
+
+pub type CacheKey = (HashMode, String);
+
+/// A disk cache write that failed, sorted by what the user can do about it.
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum CacheError {
+    #[error("permission denied for {}, check the permissions of that directory", .0.display())]
+    PermissionDenied(PathBuf),
+    #[error("the disk holding {} is full, free some space and try again", .0.display())]
+    NoSpace(PathBuf),
+    #[error("{}: {message}", path.display())]
+    Io { path: PathBuf, message: String },
+}
+
+impl CacheError {
+    fn new(path: &Path, error: &io::Error) -> Self {
+        let path = path.to_owned();
+        match error.kind() {
+            io::ErrorKind::PermissionDenied | io::ErrorKind::ReadOnlyFilesystem => {
+                Self::PermissionDenied(path)
+            }
+            io::ErrorKind::StorageFull | io::ErrorKind::QuotaExceeded => Self::NoSpace(path),
+            _ => Self::Io {
+                path,
+                message: error.to_string(),
+            },
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Entry {
+    range: Arc<Range>,
+    fetched_at: Instant,
+    last_used: u64,
+}
+
+/// Least-recently-used cache of parsed ranges, keyed by hash mode and prefix.
+#[derive(Debug)]
+pub struct RangeCache {
+    capacity: usize,
+    ttl: Duration,
+    entries: HashMap<CacheKey, Entry>,
+    clock: u64,
+}
+
+impl RangeCache {
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            capacity,
+            ttl,
+            entries: HashMap::with_capacity(capacity),
+            clock: 0,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    pub fn get(&mut self, key: &CacheKey) -> Option<Arc<Range>> {
+        self.clock += 1;
+        let entry = self.entries.get_mut(key)?;
+        if entry.fetched_at.elapsed() > self.ttl {
+            self.entries.remove(key);
+            return None;
+        }
+        entry.last_used = self.clock;
+        tracing::debug!(mode = %key.0, prefix = key.1, "memory cache hit");
+        Some(entry.range.clone())
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn insert(&mut self, key: CacheKey, range: Arc<Range>) {
+        if !self.is_enabled() {
+            return;
+        }
+        self.clock += 1;
+        if !self.entries.contains_key(&key) && self.entries.len() >= self.capacity {
+            self.evict_least_recent();
+        }
+        self.entries.insert(
+            key,
+            Entry {
+                range,
+                fetched_at: Instant::now(),
+                last_used: self.clock,
+            },
+        );
+    }
+
+    fn evict_least_recent(&mut self) {
+        let oldest = self
+            .entries
+            .iter()
+            .min_by_key(|(_, entry)| entry.last_used)
+            .map(|(key, _)| key.clone());
+        if let Some(key) = oldest {
+            self.entries.remove(&key);
+        }
+    }
+}
+
+/// A range read back from the disk cache, fresh or not.
+#[derive(Debug)]
+pub struct DiskEntry {
+    pub fetched_at: SystemTime,
+    pub etag: Option<String>,
+    pub range: Arc<Range>,
+}
+
+/// Ranges persisted under the platform cache directory, one file per prefix,
+/// in a directory for the endpoint they came from so a mirror's answers are
+/// never taken for the real API's.
+///
+/// Each file starts with a `fetched-at` header, an optional `etag` header and
+/// an `entries` header; a file whose header or entry count doesn't check out
+/// is treated as a miss.
+#[derive(Clone, Debug)]
+
+pub struct DiskCache {
+    /// Holds every endpoint's directory, and is what clearing removes.
+    root: PathBuf,
+    dir: PathBuf,
+    ttl: Duration,
+}
+
+impl DiskCache {
+    pub fn new(endpoint: &Url, ttl: Duration) -> Option<Self> {
+        let dirs = ProjectDirs::from("", "", env!("CARGO_PKG_NAME"))?;
+        Some(Self::in_dir(dirs.cache_dir().join("ranges"), endpoint, ttl))
+    }
+
+    /// A cache under `root` rather than the platform's cache directory.
+    pub fn in_dir(root: PathBuf, endpoint: &Url, ttl: Duration) -> Self {
+        Self {
+            dir: root.join(endpoint_dir(endpoint)),
+            root,
+            ttl,
+        }
+    }
+
+    fn path(&self, (mode, prefix): &CacheKey) -> PathBuf {
+        let mode = match mode {
+            HashMode::Sha1 => "sha1",
+            HashMode::Ntlm => "ntlm",
+        };
+        self.dir.join(mode).join(prefix)
+    }
+
+    /// Reads the entry for `key`, including expired ones so they can be revalidated.
+    pub async fn load(&self, key: &CacheKey) -> Option<DiskEntry> {
+        let contents = tokio::fs::read_to_string(self.path(key)).await.ok()?;
+        parse_entry(&contents)
+    }
+
+    pub fn is_fresh(&self, entry: &DiskEntry) -> bool {
+        SystemTime::now()
+            .duration_since(entry.fetched_at)
+            .is_ok_and(|age| age <= self.ttl)
+    }
+
+    pub async fn store(
+        &self,
+        key: &CacheKey,
+        range: &Range,
+        etag: Option<&str>,
+    ) -> Result<(), CacheError> {
+        let path = self.path(key);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|error| CacheError::new(parent, &error))?;
+        }
+        // Written aside and renamed into place, so an interrupted write never
+        // leaves a half-written entry under the real name.
+        let mut temp = path.clone().into_os_string();
+        temp.push(format!(".{:08x}.tmp", rand::random::<u32>()));
+        tokio::fs::write(&temp, format_entry(SystemTime::now(), etag, range))
+            .await
+            .map_err(|error| CacheError::new(Path::new(&temp), &error))?;
+        if let Err(error) = tokio::fs::rename(&temp, &path).await {
+            let _ = tokio::fs::remove_file(&temp).await;
+            return Err(CacheError::new(&path, &error));
+        }
+        Ok(())
+    }
+
+    /// Removes every endpoint's entries, not just this one's.
+    pub async fn clear(self) -> Result<(), CacheError> {
+        match tokio::fs::remove_dir_all(&self.root).await {
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(error) => {
+                let error = CacheError::new(&self.root, &error);
+                tracing::warn!("could not clear the disk cache: {error}");
+                Err(error)
+            }
+            Ok(()) => {
+                tracing::debug!(dir = %self.root.display(), "disk cache cleared");
+                Ok(())
+            }
+        }
+    }
+}
+
+/// A directory name for `endpoint`: its host, port and path, with anything
+/// that can't go in a file name replaced.
+fn endpoint_dir(endpoint: &Url) -> String {
+    let mut name = endpoint.host_str().unwrap_or_default().to_owned();
+    if let Some(port) = endpoint.port() {
+        let _ = write!(name, "-{port}");
+    }
+    for segment in endpoint
+        .path()
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+    {
+        let _ = write!(name, "-{segment}");
+    }
+    name.chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '.' | '-' => c,
+            _ => '_',
+        })
+        .collect()
+}
+
+fn format_entry(fetched_at: SystemTime, etag: Option<&str>, range: &Range) -> String {
+    let secs = fetched_at
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let mut contents = format!("fetched-at: {secs}\n");
+    if let Some(etag) = etag {
+        let _ = writeln!(contents, "etag: {etag}");
+    }
+    if range.padding() > 0 {
+        let _ = writeln!(contents, "padding: {}", range.padding());
+    }
+    let _ = writeln!(contents, "entries: {}", range.len());
+    for (suffix, count) in range.iter() {
+        let _ = writeln!(contents, "{suffix}:{count}");
+    }
+    contents
+}
+
+fn parse_entry(contents: &str) -> Option<DiskEntry> {
+    // Every line ends in a newline, so one cut short in its last line, which
+    // could still parse with a smaller count, is caught here.
+    if !contents.ends_with('\n') {
+        return None;
+    }
+    let (fetched_at, rest) = contents.split_once('\n')?;
+    let secs = fetched_at.strip_prefix("fetched-at: ")?.parse().ok()?;
+    let (mut header, mut rest) = rest.split_once('\n')?;
+    let etag = match header.strip_prefix("etag: ") {
+        Some(etag) => {
+            let etag = etag.to_owned();
+            (header, rest) = rest.split_once('\n')?;
+            Some(etag)
+        }
+        None => None,
+    };
+    let padding = match header.strip_prefix("padding: ") {
+        Some(padding) => {
+            let padding = padding.parse().ok()?;
+            (header, rest) = rest.split_once('\n')?;
+            padding
+        }
+        None => 0,
+    };
+    let entries: usize = header.strip_prefix("entries: ")?.parse().ok()?;
+    let range = parse_range(rest).ok()?.with_padding(padding);
+
+    (range.len() == entries).then(|| DiskEntry {
+        fetched_at: UNIX_EPOCH + Duration::from_secs(secs),
+        etag,
+        range: Arc::new(range),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(prefix: &str) -> CacheKey {
+        (HashMode::Sha1, prefix.to_owned())
+    }
+
+    fn range(count: u64) -> Arc<Range> {
+        Arc::new(parse_range(&format!("0018A45C4D1DEF81644B54AB7F969B88D65:{count}")).unwrap())
+    }
+
+    #[test]
+    fn hits_and_misses() {
+        let mut cache = RangeCache::new(2, Duration::from_secs(60));
+        assert!(cache.get(&key("F3BBB")).is_none());
+        cache.insert(key("F3BBB"), range(1));
+        assert_eq!(cache.get(&key("F3BBB")), Some(range(1)));
+        assert!(cache.get(&key("00000")).is_none());
+        // The same prefix in the other mode is another range.
+        assert!(cache.get(&(HashMode::Ntlm, "F3BBB".to_owned())).is_none());
+    }
+
+    #[test]
+    fn evicts_the_least_recently_used() {
+        let mut cache = RangeCache::new(2, Duration::from_secs(60));
+        cache.insert(key("AAAAA"), range(1));
+        cache.insert(key("BBBBB"), range(2));
+        cache.get(&key("AAAAA"));
+        cache.insert(key("CCCCC"), range(3));
+        assert!(cache.get(&key("BBBBB")).is_none());
+        assert_eq!(cache.get(&key("AAAAA")), Some(range(1)));
+        assert_eq!(cache.get(&key("CCCCC")), Some(range(3)));
+
+        // Replacing an entry doesn't evict another.
+        cache.insert(key("CCCCC"), range(4));
+        assert_eq!(cache.get(&key("AAAAA")), Some(range(1)));
+        assert_eq!(cache.get(&key("CCCCC")), Some(range(4)));
+    }
+
+    #[test]
+    fn entries_expire() {
+        let mut cache = RangeCache::new(2, Duration::ZERO);
+        cache.insert(key("F3BBB"), range(1));
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(cache.get(&key("F3BBB")).is_none());
+    }
+
+    #[test]
+    fn a_zero_capacity_cache_stores_nothing() {
+        let mut cache = RangeCache::new(0, Duration::from_secs(60));
+        assert!(!cache.is_enabled());
+        cache.insert(key("F3BBB"), range(1));
+        assert!(cache.get(&key("F3BBB")).is_none());
+    }
+
+    fn disk_cache(dir: &tempfile::TempDir, ttl: Duration) -> DiskCache {
+        let endpoint = Url::parse(crate::pwned::DEFAULT_ENDPOINT).unwrap();
+        DiskCache::in_dir(dir.path().join("ranges"), &endpoint, ttl)
+    }
+
+    #[tokio::test]
+    async fn disk_entries_read_back_as_written() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = disk_cache(&dir, Duration::from_secs(60));
+        let stored = parse_range(
+            "0018A45C4D1DEF81644B54AB7F969B88D65:3\n011053FD0102E94D6AE2F8B83D76FAF94F6:0",
+        )
+        .unwrap();
+        cache
+            .store(&key("F3BBB"), &stored, Some("\"abc\""))
+            .await
+            .unwrap();
+
+        let entry = cache.load(&key("F3BBB")).await.unwrap();
+        assert_eq!(*entry.range, stored);
+        assert_eq!(entry.etag.as_deref(), Some("\"abc\""));
+        assert!(cache.is_fresh(&entry));
+        assert!(
+            cache
+                .load(&(HashMode::Ntlm, "F3BBB".to_owned()))
+                .await
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn expired_disk_entries_are_still_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = disk_cache(&dir, Duration::ZERO);
+        cache.store(&key("F3BBB"), &range(1), None).await.unwrap();
+        std::thread::sleep(Duration::from_millis(1100));
+
+        let entry = cache.load(&key("F3BBB")).await.unwrap();
+        assert!(!cache.is_fresh(&entry));
+        assert_eq!(entry.etag, None);
+    }
+
+    #[tokio::test]
+    async fn corrupt_disk_entries_are_misses() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = disk_cache(&dir, Duration::from_secs(60));
+        cache.store(&key("F3BBB"), &range(1), None).await.unwrap();
+        let path = cache.path(&key("F3BBB"));
+        let written = std::fs::read_to_string(&path).unwrap();
+
+        for corrupt in [
+            String::new(),
+            written[..written.len() / 2].to_owned(),
+            written.replace("entries: 1", "entries: 2"),
+            written.replace("fetched-at", "fetched"),
+            format!("{written}not a line\n"),
+            "\u{0}\u{0}\u{0}".to_owned(),
+        ] {
+            std::fs::write(&path, &corrupt).unwrap();
+            assert!(cache.load(&key("F3BBB")).await.is_none(), "{corrupt:?}");
+        }
+        std::fs::write(&path, [0xff, 0xfe, 0x00]).unwrap();
+        assert!(cache.load(&key("F3BBB")).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn clearing_removes_every_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = disk_cache(&dir, Duration::from_secs(60));
+        cache.store(&key("F3BBB"), &range(1), None).await.unwrap();
+        cache.clone().clear().await.unwrap();
+        assert!(cache.load(&key("F3BBB")).await.is_none());
+        // Clearing what's already gone isn't an error.
+        cache.clear().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn each_endpoint_has_its_own_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("ranges");
+        let ttl = Duration::from_secs(60);
+        let api = Url::parse("https://api.pwnedpasswords.com/").unwrap();
+        let api = DiskCache::in_dir(root.clone(), &api, ttl);
+        let mirror = Url::parse("http://127.0.0.1:8080/hibp/v2/").unwrap();
+        let mirror = DiskCache::in_dir(root, &mirror, ttl);
+        assert!(mirror.dir.ends_with("127.0.0.1-8080-hibp-v2"));
+
+        api.store(&key("F3BBB"), &range(1), None).await.unwrap();
+        assert!(mirror.load(&key("F3BBB")).await.is_none());
+        mirror.store(&key("F3BBB"), &range(2), None).await.unwrap();
+        assert_eq!(api.load(&key("F3BBB")).await.unwrap().range, range(1));
+        assert_eq!(mirror.load(&key("F3BBB")).await.unwrap().range, range(2));
+
+        mirror.clear().await.unwrap();
+        assert!(api.load(&key("F3BBB")).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn a_cut_short_entry_is_a_miss() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = disk_cache(&dir, Duration::from_secs(60));
+        let body = include_str!("../tests/fixtures/range-F3BBB.txt");
+        let chunks = futures::stream::iter([Ok::<_, io::Error>(body.as_bytes())]);
+        let range = crate::range::collect_range(chunks).await.unwrap().unwrap();
+        cache
+            .store(&key("F3BBB"), &range, Some("\"abc\""))
+            .await
+            .unwrap();
+        let path = cache.path(&key("F3BBB"));
+        let whole = std::fs::read(&path).unwrap();
+
+        // Every cut in the header and the last lines, and a sample between.
+        let cuts = (0..200)
+            .chain((200..whole.len() - 200).step_by(997))
+            .chain(whole.len() - 200..whole.len());
+        for cut in cuts {
+            std::fs::write(&path, &whole[..cut]).unwrap();
+            assert!(cache.load(&key("F3BBB")).await.is_none(), "cut at {cut}");
+        }
+        std::fs::write(&path, &whole).unwrap();
+        assert_eq!(*cache.load(&key("F3BBB")).await.unwrap().range, range);
+    }
+
+    #[tokio::test]
+    async fn storing_leaves_nothing_but_the_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = disk_cache(&dir, Duration::from_secs(60));
+        cache.store(&key("F3BBB"), &range(1), None).await.unwrap();
+        cache.store(&key("F3BBB"), &range(2), None).await.unwrap();
+        let path = cache.path(&key("F3BBB"));
+        let names: Vec<_> = std::fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(names, [path.file_name().unwrap()]);
+
+        // A write that never got renamed into place is never read.
+        let mut temp = path.clone().into_os_string();
+        temp.push(".0badf00d.tmp");
+        std::fs::write(temp, "fetched-at: 0\n").unwrap();
+        assert_eq!(cache.load(&key("F3BBB")).await.unwrap().range, range(2));
+    }
+}
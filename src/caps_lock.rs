@@ -0,0 +1,146 @@
+use iced::keyboard::{Key, Modifiers, key};
+
+/// What a key press says about Caps Lock. iced 0.13 doesn't report it as a
+/// modifier, so it's worked out from the letters typed: an uppercase letter
+/// without Shift, or a lowercase one with it, means it's on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Hint {
+    On,
+    Off,
+    /// The Caps Lock key itself.
+    Toggled,
+}
+
+/// The hint in a key press, if any. Only letters that have a case count;
+/// digits and symbols look the same either way.
+pub fn hint(key: &Key, text: Option<&str>, modifiers: Modifiers) -> Option<Hint> {
+    if let Key::Named(key::Named::CapsLock) = key {
+        return Some(Hint::Toggled);
+    }
+    let mut chars = text?.chars();
+    let letter = chars.next().filter(|_| chars.next().is_none())?;
+    if letter.is_uppercase() == letter.is_lowercase() {
+        return None;
+    }
+    Some(if letter.is_uppercase() != modifiers.shift() {
+        Hint::On
+    } else {
+        Hint::Off
+    })
+}
+
+/// Caps Lock as last worked out from key presses.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CapsLock {
+    /// `None` until a letter is typed, since its state at startup can't be
+    /// read, and pressing the key then doesn't tell which way it went.
+    on: Option<bool>,
+}
+
+impl CapsLock {
+    pub fn apply(&mut self, hint: Hint) {
+        self.on = match hint {
+            Hint::On => Some(true),
+            Hint::Off => Some(false),
+            Hint::Toggled => self.on.map(|on| !on),
+        };
+    }
+
+    pub fn is_on(self) -> bool {
+        self.on == Some(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NONE: Modifiers = Modifiers::empty();
+    const SHIFT: Modifiers = Modifiers::SHIFT;
+
+    fn typed(text: &str, modifiers: Modifiers) -> Option<Hint> {
+        hint(&Key::Character(text.into()), Some(text), modifiers)
+    }
+
+    fn caps_key() -> Option<Hint> {
+        hint(&Key::Named(key::Named::CapsLock), None, NONE)
+    }
+
+    /// Caps Lock's state after each of `presses`.
+    fn states(presses: impl IntoIterator<Item = Option<Hint>>) -> Vec<bool> {
+        let mut caps_lock = CapsLock::default();
+        presses
+            .into_iter()
+            .map(|press| {
+                if let Some(hint) = press {
+                    caps_lock.apply(hint);
+                }
+                caps_lock.is_on()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn letters_tell_which_way_caps_lock_is() {
+        assert_eq!(typed("a", NONE), Some(Hint::Off));
+        assert_eq!(typed("A", SHIFT), Some(Hint::Off));
+        assert_eq!(typed("A", NONE), Some(Hint::On));
+        assert_eq!(typed("a", SHIFT), Some(Hint::On));
+        assert_eq!(typed("ß", NONE), Some(Hint::Off));
+        assert_eq!(typed("Ä", NONE), Some(Hint::On));
+        assert_eq!(caps_key(), Some(Hint::Toggled));
+    }
+
+    #[test]
+    fn caseless_keys_tell_nothing() {
+        for text in ["1", "!", " ", "€", "字", "ab"] {
+            assert_eq!(typed(text, NONE), None, "{text:?}");
+            assert_eq!(typed(text, SHIFT), None, "{text:?}");
+        }
+        assert_eq!(hint(&Key::Named(key::Named::Enter), None, NONE), None);
+        assert_eq!(hint(&Key::Named(key::Named::Shift), None, SHIFT), None);
+    }
+
+    #[test]
+    fn the_warning_goes_as_soon_as_caps_lock_is_released() {
+        assert_eq!(
+            states([typed("H", NONE), typed("I", NONE), caps_key()]),
+            [true, true, false]
+        );
+        // And comes back when it's pressed again.
+        assert_eq!(
+            states([typed("h", NONE), caps_key(), caps_key()]),
+            [false, true, false]
+        );
+    }
+
+    #[test]
+    fn caps_lock_is_unknown_until_a_letter_is_typed() {
+        assert_eq!(states([caps_key(), caps_key()]), [false, false]);
+        assert_eq!(
+            states([caps_key(), typed("a", SHIFT), typed("1", NONE)]),
+            [false, true, true]
+        );
+    }
+
+    #[test]
+    fn shifted_capitals_are_ordinary_typing() {
+        assert_eq!(
+            states([
+                typed("p", NONE),
+                typed("A", SHIFT),
+                typed("s", NONE),
+                typed("S", SHIFT),
+            ]),
+            [false; 4]
+        );
+    }
+
+    #[test]
+    fn the_latest_letter_wins() {
+        assert_eq!(
+            states([typed("A", NONE), typed("b", NONE), typed("C", NONE)]),
+            [true, false, true]
+        );
+    }
+}
@@ -0,0 +1,102 @@
+use std::cmp::Reverse;
+
+use chrono::NaiveDate;
+use serde::Deserialize;
+
+/// One entry of the public HIBP breach catalog.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Breach {
+    pub name: String,
+    pub title: String,
+    pub domain: String,
+    pub breach_date: NaiveDate,
+    pub pwn_count: u64,
+    pub data_classes: Vec<String>,
+    pub is_verified: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CatalogSort {
+    #[default]
+    Newest,
+    Largest,
+}
+
+/// Breaches whose name, title or domain contain `filter` (case-insensitively),
+/// in `sort` order.
+pub fn filter_breaches<'a>(
+    breaches: &'a [Breach],
+    filter: &str,
+    sort: CatalogSort,
+) -> Vec<&'a Breach> {
+    let filter = filter.trim().to_lowercase();
+    let mut matching: Vec<_> = breaches
+        .iter()
+        .filter(|breach| {
+            [&breach.name, &breach.title, &breach.domain]
+                .iter()
+                .any(|field| field.to_lowercase().contains(&filter))
+        })
+        .collect();
+
+    match sort {
+        CatalogSort::Newest => matching.sort_by_key(|breach| Reverse(breach.breach_date)),
+        CatalogSort::Largest => matching.sort_by_key(|breach| Reverse(breach.pwn_count)),
+    }
+    matching
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn catalog() -> Vec<Breach> {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/hibp/breaches.json"
+        );
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    fn names<'a>(breaches: &[&'a Breach]) -> Vec<&'a str> {
+        breaches.iter().map(|breach| breach.name.as_str()).collect()
+    }
+
+    #[test]
+    fn reads_the_catalog() {
+        let catalog = catalog();
+        assert_eq!(catalog.len(), 4);
+        let adobe = &catalog[0];
+        assert_eq!(adobe.title, "Adobe");
+        assert_eq!(
+            adobe.breach_date,
+            NaiveDate::from_ymd_opt(2013, 10, 4).unwrap()
+        );
+        assert_eq!(adobe.pwn_count, 152445165);
+        assert!(adobe.data_classes.contains(&"Password hints".to_owned()));
+    }
+
+    #[test]
+    fn sorts_newest_or_largest_first() {
+        let catalog = catalog();
+        assert_eq!(
+            names(&filter_breaches(&catalog, "", CatalogSort::Newest)),
+            ["Ashley", "000webhost", "Adobe", "LinkedIn"]
+        );
+        assert_eq!(
+            names(&filter_breaches(&catalog, "", CatalogSort::Largest)),
+            ["LinkedIn", "Adobe", "Ashley", "000webhost"]
+        );
+    }
+
+    #[test]
+    fn filters_on_name_title_or_domain() {
+        let catalog = catalog();
+        let filter = |filter| names(&filter_breaches(&catalog, filter, CatalogSort::Newest));
+        assert_eq!(filter("  ADOBE "), ["Adobe"]);
+        assert_eq!(filter("madison"), ["Ashley"]);
+        assert_eq!(filter("linkedin.com"), ["LinkedIn"]);
+        assert!(filter("nothing like it").is_empty());
+    }
+}
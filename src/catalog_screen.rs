@@ -0,0 +1,132 @@
+use std::sync::Arc;
+
+use iced::alignment::Vertical;
+use iced::futures::TryFutureExt;
+use iced::widget::{column, radio, row, scrollable, text, text_input};
+use iced::{Element, Length, Task};
+use reqwest::Client;
+
+use crate::catalog::{Breach, CatalogSort, filter_breaches};
+use crate::hibp;
+use crate::high_contrast;
+use crate::strings::Strings;
+
+#[derive(Debug, Default)]
+pub enum CatalogState {
+    #[default]
+    NotLoaded,
+    Loading,
+    Loaded(Arc<Vec<Breach>>),
+    Failed(String),
+}
+
+/// The Breaches tab: the public catalog of breaches, fetched the first time
+/// it's opened, and how it's filtered and sorted.
+#[derive(Debug, Default)]
+pub struct CatalogScreen {
+    pub state: CatalogState,
+    filter: String,
+    sort: CatalogSort,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Loaded(Result<Arc<Vec<Breach>>, String>),
+    Filter(String),
+    Sort(CatalogSort),
+}
+
+impl CatalogScreen {
+    pub fn update(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::Loaded(result) => {
+                self.state = match result {
+                    Ok(breaches) => CatalogState::Loaded(breaches),
+                    Err(error) => CatalogState::Failed(error),
+                };
+            }
+            Message::Filter(filter) => self.filter = filter,
+            Message::Sort(sort) => self.sort = sort,
+        }
+
+        Task::none()
+    }
+
+    /// Fetches the catalog with `client` as the tab opens, once per session
+    /// or again after a failure.
+    pub fn open(&mut self, client: &Client) -> Task<Message> {
+        if !matches!(
+            self.state,
+            CatalogState::NotLoaded | CatalogState::Failed(_)
+        ) {
+            return Task::none();
+        }
+        self.state = CatalogState::Loading;
+        Task::future(
+            hibp::breaches(client.clone())
+                .map_ok(Arc::new)
+                .map_err(|e| e.to_string()),
+        )
+        .map(Message::Loaded)
+    }
+
+    pub fn view(&self, strings: &'static Strings) -> Element<'_, Message> {
+        let breaches = match &self.state {
+            CatalogState::NotLoaded | CatalogState::Loading => {
+                return text(strings.catalog_loading).style(text::secondary).into();
+            }
+            CatalogState::Failed(error) => {
+                return text((strings.catalog_failed)(error))
+                    .style(text::danger)
+                    .into();
+            }
+            CatalogState::Loaded(breaches) => breaches,
+        };
+        let matching = filter_breaches(breaches, &self.filter, self.sort);
+        let rows = column(matching.iter().map(|breach| {
+            column![
+                text!(
+                    "{} ({}) — {} — {}{}",
+                    breach.title,
+                    breach.domain,
+                    breach.breach_date,
+                    (strings.catalog_accounts)(breach.pwn_count),
+                    if breach.is_verified {
+                        ""
+                    } else {
+                        strings.catalog_unverified
+                    }
+                ),
+                text(breach.data_classes.join(", ")).style(text::secondary),
+            ]
+            .into()
+        }))
+        .spacing(5);
+
+        column![
+            row![
+                text_input(strings.catalog_filter, &self.filter)
+                    .style(high_contrast::text_input)
+                    .on_input(Message::Filter),
+                radio(
+                    strings.sort_newest,
+                    CatalogSort::Newest,
+                    Some(self.sort),
+                    Message::Sort
+                ),
+                radio(
+                    strings.sort_largest,
+                    CatalogSort::Largest,
+                    Some(self.sort),
+                    Message::Sort
+                ),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            text((strings.catalog_count)(matching.len(), breaches.len())).style(text::secondary),
+            scrollable(rows).height(Length::Fixed(300.)),
+        ]
+        .spacing(5)
+        .into()
+    }
+}
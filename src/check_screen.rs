@@ -0,0 +1,2359 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local, Utc};
+use iced::alignment::Vertical;
+use iced::futures::TryFutureExt;
+use iced::task;
+use iced::widget::{
+    button, checkbox, column, container, radio, row, scrollable, text, text_input, tooltip,
+};
+use iced::{Element, Length, Subscription, Task, Theme};
+use rand::rngs::OsRng;
+
+use cybersec_wow::cache::CacheKey;
+use cybersec_wow::error::{FailureKind, LookupError, ParseError};
+use cybersec_wow::hash::{PREFIX_LEN, normalize_hash};
+use cybersec_wow::history::History;
+use cybersec_wow::pwned::{MAX_RETRY_AFTER, RequestPreview};
+use cybersec_wow::{
+    BreachResult, HashMode, LookupMeta, LookupSource, Normalization, batch, bloom, common,
+    composition, crack_time, download, export, hash_password, hash_prefix_of, hash_suffix_of, hibp,
+    invisible, offline, packed, pin, range, record, report, secret, severity, suggest, summary,
+};
+
+use crate::clipboard_monitor::{self, Monitor};
+use crate::ui::{exported_status, has_edge_whitespace, report_view};
+use crate::{
+    SIZES, Shared, caps_lock, high_contrast, identicon, keypad, save_export, severity_view,
+    strength,
+};
+use composition::Composition;
+use download::Progress;
+use hibp::{AccountBreaches, AccountPastes, DomainBreaches, DomainSort};
+use range::Range;
+use record::CheckRecord;
+use secret::{SecretBuffer, SecretString};
+use severity::{Severity, Thresholds};
+use strength::Strength;
+use summary::{CountStyle, format_count};
+
+/// The password field, for shortcuts to focus.
+pub const PASSWORD_INPUT: &str = "password";
+
+/// The quick-check window's password field, focused as it opens.
+pub const QUICK_INPUT: &str = "quick-password";
+
+/// How often the searching animation moves on.
+const SEARCH_TICK: Duration = Duration::from_millis(250);
+
+/// How long a search runs before its elapsed time is shown.
+const SHOW_SEARCH_TIME: Duration = Duration::from_secs(2);
+
+/// How long "Copied!" stays up after copying the hash.
+const COPY_FEEDBACK: Duration = Duration::from_secs(2);
+
+/// What the Check tab looks up.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InputMode {
+    #[default]
+    Password,
+    Account,
+    Domain,
+    Pin,
+}
+
+#[derive(Default, Debug)]
+pub enum SearchResult {
+    Account(AccountBreaches),
+    Pastes(AccountPastes),
+    Domain(DomainBreaches),
+    Breaches {
+        record: CheckRecord,
+        duration: Duration,
+        strength: Option<Strength>,
+        /// The whole range the answer came from, when it was fetched.
+        range: Option<Arc<Range>>,
+    },
+    Errored(LookupError),
+    Offline,
+    SocksUnreachable(String),
+    Unparsable(ParseError),
+    UnexpectedStatus {
+        status: u16,
+        message: String,
+    },
+    RateLimited(Duration),
+    /// Stopped by the user before an answer came back.
+    Cancelled,
+
+    #[default]
+    NotSubmitted,
+    Searching,
+}
+
+impl SearchResult {
+    fn failed(error: LookupError) -> Self {
+        match error.kind {
+            FailureKind::Offline => SearchResult::Offline,
+            FailureKind::SocksUnreachable => SearchResult::SocksUnreachable(error.message),
+            FailureKind::Unparsable(ref parse) => SearchResult::Unparsable(parse.clone()),
+            FailureKind::UnexpectedStatus(status) => SearchResult::UnexpectedStatus {
+                status,
+                message: error.message,
+            },
+            FailureKind::RateLimited(_) | FailureKind::Network | FailureKind::Other => {
+                SearchResult::Errored(error)
+            }
+        }
+    }
+}
+
+/// The Check tab: the password, account, domain and PIN forms, their
+/// lookups, and the clipboard and dataset they can check against. The
+/// quick-check window is another view of it.
+#[derive(Debug, Default)]
+pub struct CheckScreen {
+    pub input_mode: InputMode,
+    account: String,
+    pub password: SecretBuffer,
+    pub current_hash: SecretString,
+    /// Whether the password field takes an already computed hash.
+    pub hash_input: bool,
+    pub reveal_hash: bool,
+    /// Copies of the hash so far, and whether the last one is still confirmed.
+    pub hash_copies: usize,
+    pub hash_copied: bool,
+    /// A replacement for a breached password, checked not to be breached
+    /// itself, and the search for one while it runs.
+    pub suggestion: Option<Result<Option<SecretString>, String>>,
+    pub suggesting: Option<task::Handle>,
+    /// Whether the last Paste & check found nothing to paste.
+    pub clipboard_empty: bool,
+    /// Whether the clipboard is to be emptied once the pasted password's
+    /// check is over.
+    pub clear_clipboard: bool,
+    /// Watching the clipboard, once the warning has been accepted.
+    pub clipboard_monitor: Option<Monitor>,
+    /// Whether the warning about watching the clipboard is up.
+    clipboard_warning: bool,
+    /// Whether watching ran out, until it's asked for again.
+    pub clipboard_watch_ended: bool,
+    /// The result of the last copied password checked, for a few seconds.
+    /// Kept apart from the check form and never added to the history.
+    clipboard_toast: Option<(String, Result<BreachResult, String>)>,
+    clipboard_toast_generation: usize,
+    /// Rank of the password on the bundled common-password list.
+    common_rank: Option<u32>,
+    strength: Option<Strength>,
+    pub show: bool,
+    offline: bool,
+    pub state: SearchResult,
+    pub search: Option<task::Handle>,
+    rate_limit_retried: bool,
+    record_export: Option<Result<PathBuf, String>>,
+    pub download: Option<task::Handle>,
+    /// `None` while the startup reachability check is running.
+    pub health: Option<Result<Duration, LookupError>>,
+    download_progress: Option<Result<Progress, String>>,
+    /// The last check of the dataset against its manifest; `Some(None)`
+    /// while one runs.
+    dataset_verification: Option<Option<Result<download::Verification, String>>>,
+    /// The panel on what a lookup sends.
+    pub show_explainer: bool,
+    /// The "What gets sent" section under the password field.
+    show_request: bool,
+    /// When the current search started and how far its animation has got;
+    /// only meaningful while the state is `Searching`.
+    searching_since: Option<Instant>,
+    search_frame: usize,
+    /// Bumped on every lookup started, to tell an answer for an earlier
+    /// password, already queued when its search was replaced, from the
+    /// current one's.
+    pub search_generation: u64,
+    /// Bumped on every edit of the password, to tell stale auto-check timers
+    /// from the latest one.
+    input_generation: usize,
+    /// Whether the last thing cleared was cleared by the inactivity timer.
+    pub idle_cleared: bool,
+    pub caps_lock: caps_lock::CapsLock,
+    pub show_details: bool,
+    /// The hash and algorithm of the last password check, for Retry.
+    last_search: Option<(SecretString, HashMode)>,
+    /// Whether the last account search was for pastes rather than breaches.
+    last_search_pastes: bool,
+    /// When the last password lookup finished and how, for the status bar.
+    /// Unlike `state`, it outlives the next edit.
+    pub last_lookup: Option<(DateTime<Utc>, LookupMeta)>,
+    /// The last failed password lookup, kept for its details.
+    pub last_error: Option<LookupError>,
+    show_error_details: bool,
+    pub history: History,
+    /// Optional name given to the next check in the history.
+    history_label: String,
+    /// The on-screen keyboard, while it's shown under the password field.
+    pub keypad: Option<keypad::Keypad>,
+    domain: String,
+    domain_sort: DomainSort,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Input(SecretString),
+    HashInput(bool),
+    RevealHash(bool),
+    CopyHash,
+    CopySummary,
+    HistoryLabel(String),
+    RerunHistory(usize),
+    ClearHistory,
+    /// Holds which copy it's for, so a later copy keeps its own confirmation.
+    CopyFeedbackExpired(usize),
+    TrimPassword,
+    RemoveInvisible,
+    /// Wipes the password and everything derived from it. The app wipes
+    /// what the other tabs hold too.
+    Clear,
+    PasteAndCheck,
+    SuggestAlternative,
+    Suggested(Result<Option<SecretString>, String>),
+    CopySuggestion,
+    UseSuggestion,
+    /// Handled by the app too, which shows the tab.
+    Pasted(Option<SecretString>),
+    /// Asks to watch the clipboard, showing the warning first, or stops.
+    WatchClipboard(bool),
+    StartWatchingClipboard,
+    ClipboardTick,
+    ClipboardPolled(Option<SecretString>),
+    /// A copied password's preview and what its check found.
+    ClipboardChecked(String, Result<BreachResult, String>),
+    ClipboardToastExpired(usize),
+    Normalization(Normalization),
+    Submit,
+    /// Stops the search in flight, or the wait to retry a rate-limited one.
+    CancelSearch,
+    RetryLookup,
+    /// Runs the search that just failed again.
+    Retry,
+    /// A lookup's answer, with the `search_generation` it was started in.
+    BreachResult(u64, Result<(CheckRecord, Duration), LookupError>),
+    RangeFetched(
+        u64,
+        CacheKey,
+        String,
+        Result<(Arc<Range>, LookupMeta), LookupError>,
+    ),
+    InputMode(InputMode),
+    AccountInput(String),
+    AccountResult(Result<AccountBreaches, String>),
+    DomainInput(String),
+    DomainResult(Result<DomainBreaches, String>),
+    DomainSort(DomainSort),
+    ExportRecord,
+    RecordExported(Option<Result<PathBuf, String>>),
+    /// Handled by the app, which has the batch to report on as well.
+    ExportReport(report::Format),
+    SubmitPastes,
+    PastesResult(Result<AccountPastes, String>),
+    ShowPassword(bool),
+    HashMode(HashMode),
+    OfflineMode(bool),
+    DownloadDataset,
+    CancelDownload,
+    Download(Result<Progress, String>),
+    DownloadStopped,
+    VerifyDataset,
+    DatasetVerified(Result<download::Verification, String>),
+    DatasetPath(String),
+    CheckHealth,
+    HealthChecked(Result<Duration, LookupError>),
+    ToggleDetails,
+    ToggleErrorDetails,
+    ToggleKeypad,
+    Keypad(keypad::Message),
+    ToggleExplainer,
+    SearchTick,
+    AutoCheck(usize),
+    PinBreachCheck(bool),
+    /// Handled by the app, which says so in the Settings tab.
+    UseSocks(bool),
+    ToggleRequestPreview,
+    CopyRequestPreview,
+}
+
+impl CheckScreen {
+    pub fn update(&mut self, message: Message, shared: &mut Shared) -> Task<Message> {
+        let mut task = self.handle(message, shared);
+        if !self.is_searching() {
+            self.searching_since = None;
+            // However the check ended, even by being replaced, it's over.
+            if std::mem::take(&mut self.clear_clipboard) {
+                task = Task::batch([task, iced::clipboard::write(String::new())]);
+            }
+        }
+        task
+    }
+
+    fn handle(&mut self, message: Message, shared: &mut Shared) -> Task<Message> {
+        match message {
+            // One lookup at a time; repeated Enters and clicks are dropped.
+            Message::Submit | Message::SubmitPastes if self.is_searching() => {}
+            // The PIN field takes digits only, up to the longest PIN.
+            Message::Input(input)
+                if self.input_mode == InputMode::Pin && !pin::is_typable(input.expose()) => {}
+            Message::Input(input) => {
+                // A lookup still running is for the old input, so it's dropped
+                // (and so aborted) rather than left to show a stale result.
+                self.search = None;
+                self.clipboard_empty = false;
+                self.idle_cleared = false;
+                // A suggestion was for the password being replaced.
+                self.suggestion = None;
+                self.suggesting = None;
+                // Copied into the locked buffer; `input` is wiped as it drops.
+                self.password.set(input.expose());
+                if shared.settings.always_trim {
+                    self.password.trim();
+                }
+                self.reveal_hash = false;
+                // Emptying the field puts the mask back for whatever's typed next.
+                if self.password.is_empty() {
+                    self.show = false;
+                }
+                self.refresh_hash(shared);
+                self.state = SearchResult::NotSubmitted;
+                // Each keystroke makes the previous timer stale, so only the
+                // last one checks.
+                self.input_generation += 1;
+                if shared.settings.auto_check && !self.current_hash.is_empty() {
+                    let generation = self.input_generation;
+                    return Task::future(tokio::time::sleep(shared.settings.auto_check_delay))
+                        .map(move |()| Message::AutoCheck(generation));
+                }
+            }
+            Message::AutoCheck(generation) => {
+                if generation == self.input_generation
+                    && matches!(self.state, SearchResult::NotSubmitted)
+                    && self.input_mode == InputMode::Password
+                    && let Some(submit) = self.submit_message(shared)
+                {
+                    return self.handle(submit, shared);
+                }
+            }
+            Message::RevealHash(reveal) => self.reveal_hash = reveal,
+            Message::Clear => {
+                // Dropping the handle aborts a lookup still in flight.
+                self.search = None;
+                self.password.set("");
+                self.refresh_hash(shared);
+                self.suggestion = None;
+                self.suggesting = None;
+                self.show = false;
+                self.reveal_hash = false;
+                self.hash_copied = false;
+                self.rate_limit_retried = false;
+                self.state = SearchResult::NotSubmitted;
+            }
+            Message::CopyHash => {
+                if self.current_hash.is_empty() {
+                    return Task::none();
+                }
+                self.hash_copies += 1;
+                self.hash_copied = true;
+                let copy = self.hash_copies;
+                return Task::batch([
+                    iced::clipboard::write(self.current_hash.expose().to_owned()),
+                    Task::future(tokio::time::sleep(COPY_FEEDBACK))
+                        .map(move |()| Message::CopyFeedbackExpired(copy)),
+                ]);
+            }
+            Message::CopySummary => {
+                let full_hash = shared
+                    .settings
+                    .summary_full_hash
+                    .then(|| self.current_hash.expose());
+                let separator = shared.separator;
+                let summary = match &self.state {
+                    SearchResult::Breaches { record, .. } => {
+                        summary::summary(record, full_hash, &shared.settings.endpoint, separator)
+                    }
+                    SearchResult::Errored(error) => summary::error_summary(
+                        shared.mode,
+                        hash_prefix_of(self.current_hash.expose()),
+                        full_hash,
+                        &error.message,
+                        Local::now().date_naive(),
+                    ),
+                    _ => return Task::none(),
+                };
+                return iced::clipboard::write(summary);
+            }
+            Message::HistoryLabel(label) => self.history_label = label,
+            Message::RerunHistory(index) => {
+                let Some(entry) = self.history.get(index) else {
+                    return Task::none();
+                };
+                let hash = entry.hash().clone();
+                self.history_label = entry.label.clone();
+                shared.mode = entry.record.algorithm;
+                self.input_mode = InputMode::Password;
+                self.hash_input = true;
+                self.password.set(hash.expose());
+                self.refresh_hash(shared);
+                self.rate_limit_retried = false;
+                return self.check_password(shared);
+            }
+            Message::ClearHistory => self.history.clear(),
+            Message::CopyFeedbackExpired(copy) => {
+                if copy == self.hash_copies {
+                    self.hash_copied = false;
+                }
+            }
+            Message::TrimPassword => {
+                self.password.trim();
+                self.reveal_hash = false;
+                self.refresh_hash(shared);
+                self.rate_limit_retried = false;
+                return self.check_password(shared);
+            }
+            Message::RemoveInvisible => {
+                // The result was for the password as it was.
+                self.search = None;
+                self.password.remove(invisible::is_flagged);
+                self.reveal_hash = false;
+                self.refresh_hash(shared);
+                self.state = SearchResult::NotSubmitted;
+            }
+            Message::PasteAndCheck => {
+                return iced::clipboard::read()
+                    .map(|pasted| Message::Pasted(pasted.map(SecretString::from)));
+            }
+            Message::Pasted(pasted) => {
+                // Nothing, or only whitespace, isn't worth replacing the field with.
+                self.clipboard_empty = pasted
+                    .as_ref()
+                    .is_none_or(|pasted| pasted.expose().trim().is_empty());
+                let Some(pasted) = pasted.filter(|_| !self.clipboard_empty) else {
+                    return Task::none();
+                };
+                self.use_password_form();
+                let input = self.handle(Message::Input(pasted), shared);
+                let submit = self.handle(Message::Submit, shared);
+                self.clear_clipboard =
+                    shared.settings.clear_clipboard_after_paste && self.is_searching();
+                return Task::batch([input, submit]);
+            }
+            Message::SuggestAlternative => return self.suggest_alternative(shared),
+            Message::Suggested(suggestion) => {
+                self.suggesting = None;
+                self.suggestion = Some(suggestion);
+            }
+            Message::CopySuggestion => {
+                if let Some(Ok(Some(suggestion))) = &self.suggestion {
+                    return iced::clipboard::write(suggestion.expose().to_owned());
+                }
+            }
+            Message::UseSuggestion => {
+                if let Some(Ok(Some(suggestion))) = self.suggestion.take() {
+                    let input = self.handle(Message::Input(suggestion), shared);
+                    return Task::batch([input, self.handle(Message::Submit, shared)]);
+                }
+            }
+            Message::WatchClipboard(watch) => {
+                self.clipboard_warning = watch && self.clipboard_monitor.is_none();
+                if !watch {
+                    self.clipboard_monitor = None;
+                }
+                self.clipboard_watch_ended = false;
+            }
+            Message::StartWatchingClipboard => {
+                self.clipboard_warning = false;
+                self.clipboard_watch_ended = false;
+                let minutes = shared.settings.clipboard_watch_minutes;
+                self.clipboard_monitor = Some(Monitor::new(Duration::from_secs(minutes * 60)));
+                // What's there already is noted now, so it isn't taken
+                // for something copied once watching started.
+                return self.handle(Message::ClipboardTick, shared);
+            }
+            Message::ClipboardTick => {
+                let Some(monitor) = &self.clipboard_monitor else {
+                    return Task::none();
+                };
+                if monitor.is_over(Instant::now()) {
+                    self.clipboard_monitor = None;
+                    self.clipboard_watch_ended = true;
+                    return Task::none();
+                }
+                return iced::clipboard::read()
+                    .map(|text| Message::ClipboardPolled(text.map(SecretString::from)));
+            }
+            Message::ClipboardPolled(text) => {
+                let Some(monitor) = &mut self.clipboard_monitor else {
+                    return Task::none();
+                };
+                let text = text.as_ref().map(|text| {
+                    if shared.settings.always_trim {
+                        text.expose().trim()
+                    } else {
+                        text.expose()
+                    }
+                });
+                if monitor.changed(text, shared.settings.normalization)
+                    && let Some(text) =
+                        text.filter(|text| clipboard_monitor::looks_like_password(text))
+                {
+                    return self.check_clipboard(text, shared);
+                }
+            }
+            Message::ClipboardChecked(preview, result) => {
+                // Stopped while it was looked up.
+                if self.clipboard_monitor.is_none() {
+                    return Task::none();
+                }
+                self.clipboard_toast = Some((preview, result));
+                self.clipboard_toast_generation += 1;
+                let generation = self.clipboard_toast_generation;
+                return Task::future(tokio::time::sleep(clipboard_monitor::TOAST))
+                    .map(move |()| Message::ClipboardToastExpired(generation));
+            }
+            Message::ClipboardToastExpired(generation) => {
+                if generation == self.clipboard_toast_generation {
+                    self.clipboard_toast = None;
+                }
+            }
+            Message::Normalization(normalization) => {
+                shared.settings.normalization = normalization;
+                shared.settings_dirty = true;
+                self.refresh_hash(shared);
+                self.state = SearchResult::NotSubmitted;
+            }
+            Message::HashInput(hash_input) => {
+                self.hash_input = hash_input;
+                self.refresh_hash(shared);
+                self.state = SearchResult::NotSubmitted;
+            }
+            Message::HashMode(mode) => {
+                shared.mode = mode;
+                self.refresh_hash(shared);
+                self.state = SearchResult::NotSubmitted;
+            }
+
+            Message::OfflineMode(offline) => {
+                self.offline = offline;
+                self.state = SearchResult::NotSubmitted;
+            }
+            Message::DatasetPath(path) => {
+                shared.settings.dataset_path = path;
+                shared.settings_dirty = true;
+            }
+
+            Message::CancelSearch => {
+                if matches!(
+                    self.state,
+                    SearchResult::Searching | SearchResult::RateLimited(_)
+                ) {
+                    self.search = None;
+                    self.state = SearchResult::Cancelled;
+                }
+            }
+            // Dropping the handle aborts the search, but an answer may already
+            // be queued. It mustn't show against a newer search, or against
+            // a password that was edited or cleared since.
+            Message::BreachResult(generation, _) | Message::RangeFetched(generation, ..)
+                if generation != self.search_generation || self.search.is_none() => {}
+            // A cancelled account or domain lookup's answer is dropped too.
+            Message::AccountResult(_) | Message::PastesResult(_) | Message::DomainResult(_)
+                if matches!(self.state, SearchResult::Cancelled) => {}
+            Message::InputMode(input_mode) => {
+                self.input_mode = input_mode;
+                self.search = None;
+                self.state = SearchResult::NotSubmitted;
+                if input_mode == InputMode::Pin {
+                    // A PIN is checked as itself, the way the API lists it.
+                    self.hash_input = false;
+                    shared.mode = HashMode::Sha1;
+                    if !pin::is_typable(self.password.expose()) {
+                        return self.handle(Message::Input(SecretString::default()), shared);
+                    }
+                    self.refresh_hash(shared);
+                }
+            }
+            Message::AccountInput(account) => {
+                self.search = None;
+                self.account = account;
+                self.state = SearchResult::NotSubmitted;
+            }
+            Message::Submit if self.input_mode == InputMode::Account => {
+                self.last_search_pastes = false;
+                self.start_searching();
+                let (task, handle) = Task::future(shared.api_lookup(hibp::breached_account(
+                    shared.client.clone(),
+                    self.account.trim().to_owned(),
+                    shared.api_key(),
+                )))
+                .map(Message::AccountResult)
+                .abortable();
+                self.search = Some(handle.abort_on_drop());
+                return task;
+            }
+            Message::PinBreachCheck(check) => {
+                shared.settings.pin_breach_check = check;
+                shared.settings_dirty = true;
+            }
+            Message::ExportRecord => {
+                let SearchResult::Breaches { record, .. } = &self.state else {
+                    return Task::none();
+                };
+                let record = record.clone();
+                return Task::future(save_export(
+                    "Export result",
+                    "result.json",
+                    "JSON",
+                    move |file| export::write_record_json(file, &record).map_err(|e| e.to_string()),
+                ))
+                .map(Message::RecordExported);
+            }
+            Message::RecordExported(exported) => {
+                if exported.is_some() {
+                    self.record_export = exported;
+                }
+            }
+            Message::DownloadDataset => {
+                self.dataset_verification = None;
+                let (task, handle) = Task::run(
+                    download::download(
+                        shared.range_client(),
+                        shared.mode,
+                        PathBuf::from(&shared.settings.dataset_path),
+                        shared.settings.download_concurrency,
+                    ),
+                    Message::Download,
+                )
+                .chain(Task::done(Message::DownloadStopped))
+                .abortable();
+                self.download = Some(handle.abort_on_drop());
+                return task;
+            }
+            // Dropping the handle aborts the download; starting again resumes it.
+            Message::CancelDownload | Message::DownloadStopped => self.download = None,
+            Message::Download(progress) => self.download_progress = Some(progress),
+            Message::VerifyDataset => {
+                self.dataset_verification = Some(None);
+                let path = PathBuf::from(&shared.settings.dataset_path);
+                return Task::perform(
+                    async move {
+                        tokio::task::spawn_blocking(move || download::verify(&path))
+                            .await
+                            .map_err(|e| e.to_string())?
+                    },
+                    Message::DatasetVerified,
+                );
+            }
+            Message::DatasetVerified(verification) => {
+                self.dataset_verification = Some(Some(verification));
+            }
+            Message::SubmitPastes => {
+                self.last_search_pastes = true;
+                self.start_searching();
+                let (task, handle) = Task::future(shared.api_lookup(hibp::paste_account(
+                    shared.client.clone(),
+                    self.account.trim().to_owned(),
+                    shared.api_key(),
+                )))
+                .map(Message::PastesResult)
+                .abortable();
+                self.search = Some(handle.abort_on_drop());
+                return task;
+            }
+            Message::PastesResult(result) => {
+                self.search = None;
+                self.state = match result {
+                    Ok(pastes) => SearchResult::Pastes(pastes),
+                    Err(error) => SearchResult::Errored(LookupError::other(error)),
+                };
+            }
+            Message::DomainInput(domain) => {
+                self.search = None;
+                self.domain = domain;
+                self.state = SearchResult::NotSubmitted;
+            }
+            Message::Submit if self.input_mode == InputMode::Domain => {
+                self.start_searching();
+                let (task, handle) = Task::future(shared.api_lookup(hibp::breached_domain(
+                    shared.client.clone(),
+                    self.domain.trim().to_owned(),
+                    shared.api_key(),
+                )))
+                .map(Message::DomainResult)
+                .abortable();
+                self.search = Some(handle.abort_on_drop());
+                return task;
+            }
+            Message::DomainResult(result) => {
+                self.search = None;
+                self.state = match result {
+                    Ok(domain) => SearchResult::Domain(domain),
+                    Err(error) => SearchResult::Errored(LookupError::other(error)),
+                };
+            }
+            Message::DomainSort(sort) => self.domain_sort = sort,
+            Message::AccountResult(result) => {
+                self.search = None;
+                self.state = match result {
+                    Ok(account) => SearchResult::Account(account),
+                    Err(error) => SearchResult::Errored(LookupError::other(error)),
+                };
+            }
+            Message::Retry => {
+                let retry = match self.input_mode {
+                    InputMode::Account if self.last_search_pastes => Message::SubmitPastes,
+                    InputMode::Password | InputMode::Pin => {
+                        // Whatever has happened to the field since, it's the
+                        // failed check that runs again.
+                        let Some((hash, mode)) = self.last_search.clone() else {
+                            return Task::none();
+                        };
+                        self.current_hash = hash;
+                        shared.mode = mode;
+                        Message::Submit
+                    }
+                    _ => Message::Submit,
+                };
+                return self.handle(retry, shared);
+            }
+            Message::Submit => {
+                // A pending auto-check would only repeat this one.
+                self.input_generation += 1;
+                self.rate_limit_retried = false;
+                return self.check_password(shared);
+            }
+            Message::RetryLookup => {
+                // Only if nothing has replaced the rate-limited state in the meantime.
+                if matches!(self.state, SearchResult::RateLimited(_)) {
+                    return self.check_password(shared);
+                }
+            }
+            Message::BreachResult(_, breach_result) => {
+                self.search = None;
+                match breach_result {
+                    Ok((record, duration)) => {
+                        let hash = self.current_hash.clone();
+                        self.show_breaches(record, hash, duration, None);
+                    }
+                    Err(error) => return self.lookup_failed(error),
+                }
+            }
+            Message::RangeFetched(_, key, hash, range) => {
+                self.search = None;
+                match range {
+                    Ok((range, meta)) => {
+                        let breach = BreachResult::from_range(&range, &hash);
+                        let record = CheckRecord::new(
+                            &hash,
+                            key.0,
+                            breach,
+                            meta.source,
+                            &shared.settings.severity,
+                        );
+                        let duration = meta.duration;
+                        self.show_breaches(record, hash.into(), duration, Some(range.clone()));
+                        shared.cache.insert(key, range);
+                    }
+                    Err(error) => return self.lookup_failed(error),
+                }
+            }
+            Message::ShowPassword(show) => self.show = show,
+            Message::ToggleDetails => self.show_details = !self.show_details,
+            Message::ToggleErrorDetails => self.show_error_details = !self.show_error_details,
+            Message::ToggleKeypad => {
+                self.keypad = match self.keypad {
+                    Some(_) => None,
+                    None => Some(keypad::Keypad::default()),
+                };
+            }
+            Message::Keypad(keypad::Message::Close) => self.keypad = None,
+            Message::Keypad(keypad::Message::Key(key)) => {
+                // Sized up front so the copy never reallocates and leaves a
+                // stray one behind; it's wiped as the input drops.
+                let mut input = String::with_capacity(self.password.expose().len() + 4);
+                input.push_str(self.password.expose());
+                input.push(key);
+                return self.handle(Message::Input(input.into()), shared);
+            }
+            Message::Keypad(keypad::Message::Backspace) => {
+                let password = self.password.expose();
+                let end = password
+                    .char_indices()
+                    .next_back()
+                    .map_or(0, |(last, _)| last);
+                let input = password[..end].to_owned().into();
+                return self.handle(Message::Input(input), shared);
+            }
+            Message::Keypad(message) => {
+                if let Some(keypad) = &mut self.keypad {
+                    keypad.update(message);
+                }
+            }
+            Message::ToggleExplainer => self.show_explainer = !self.show_explainer,
+            Message::SearchTick => self.search_frame = self.search_frame.wrapping_add(1),
+            Message::ToggleRequestPreview => self.show_request = !self.show_request,
+            Message::CopyRequestPreview => {
+                if let Some(preview) = self.request_preview(shared) {
+                    return iced::clipboard::write(preview.to_string());
+                }
+            }
+            Message::CheckHealth => {
+                self.health = None;
+                return self.check_health(shared);
+            }
+            Message::HealthChecked(health) => self.health = Some(health),
+            Message::ExportReport(_) | Message::UseSocks(_) => {}
+        }
+
+        Task::none()
+    }
+
+    /// Whether the password field hides what's typed. A hash isn't a live
+    /// secret, and is easier to check unmasked.
+    pub fn is_masked(&self) -> bool {
+        !self.show && !self.hash_input
+    }
+
+    /// Recomputes `current_hash` from the input, which in hash input mode is
+    /// only taken once it's a valid hash. Anything replaced is wiped on drop.
+    fn refresh_hash(&mut self, shared: &Shared) {
+        (self.common_rank, self.strength) = if self.hash_input {
+            (None, None)
+        } else {
+            let password = self.password.expose();
+            (common::rank(password), Strength::estimate(password))
+        };
+        self.current_hash = if self.hash_input {
+            normalize_hash(self.password.expose(), shared.mode)
+                .unwrap_or_default()
+                .into()
+        } else {
+            hash_password(
+                self.password.expose(),
+                shared.mode,
+                shared.settings.normalization,
+            )
+            .into()
+        };
+    }
+
+    fn is_searching(&self) -> bool {
+        matches!(self.state, SearchResult::Searching)
+    }
+
+    fn start_searching(&mut self) {
+        self.last_error = None;
+        self.state = SearchResult::Searching;
+        self.searching_since = Some(Instant::now());
+        self.search_frame = 0;
+    }
+
+    /// What the current form's submit button would send, if it's enabled.
+    pub fn submit_message(&self, shared: &Shared) -> Option<Message> {
+        if self.is_searching() {
+            return None;
+        }
+        let ready = match self.input_mode {
+            InputMode::Password => !self.current_hash.is_empty(),
+            InputMode::Account => !self.account.trim().is_empty(),
+            InputMode::Domain => !self.domain.trim().is_empty(),
+            InputMode::Pin => {
+                shared.settings.pin_breach_check && pin::validate(self.password.expose()).is_ok()
+            }
+        };
+        ready.then_some(Message::Submit)
+    }
+
+    /// The range request a check of the current hash would send, if it
+    /// would send one.
+    fn request_preview(&self, shared: &Shared) -> Option<RequestPreview> {
+        let hash = self.current_hash.expose();
+        (!self.offline && !hash.is_empty()).then(|| {
+            RequestPreview::new(
+                &shared.settings.network(),
+                hash_prefix_of(hash),
+                shared.mode,
+                None,
+            )
+        })
+    }
+
+    pub fn check_health(&self, shared: &Shared) -> Task<Message> {
+        Task::perform(shared.range_client().health_check(), Message::HealthChecked)
+    }
+
+    fn check_password(&mut self, shared: &mut Shared) -> Task<Message> {
+        self.last_search = Some((self.current_hash.clone(), shared.mode));
+        self.search_generation += 1;
+        let generation = self.search_generation;
+        let hash = self.current_hash.expose().to_owned();
+        if self.offline {
+            self.start_searching();
+            let path = PathBuf::from(&shared.settings.dataset_path);
+            let finished = checked(generation, &hash, shared.mode, shared.settings.severity);
+            let (task, handle) = Task::future(timed(lookup_offline(path, hash)))
+                .map(finished)
+                .abortable();
+            self.search = Some(handle.abort_on_drop());
+            return task;
+        }
+
+        let key = (shared.mode, hash_prefix_of(&hash).to_ascii_uppercase());
+        let started = Instant::now();
+        // With a disk cache, the fetch counts the miss if it has to download.
+        let cached = shared.cache.get(&key);
+        if shared.cache.is_enabled() && (cached.is_some() || shared.disk_cache.is_none()) {
+            shared.metrics.cache(cached.is_some());
+        }
+        if let Some(range) = cached {
+            self.search = None;
+            let breach = BreachResult::from_range(&range, &hash);
+            let record = CheckRecord::new(
+                &hash,
+                shared.mode,
+                breach,
+                LookupSource::Memory,
+                &shared.settings.severity,
+            );
+            self.show_breaches(record, hash.into(), started.elapsed(), Some(range));
+            return Task::none();
+        }
+
+        self.start_searching();
+        let client = shared.range_client();
+        let task = if shared.cache.is_enabled() || shared.disk_cache.is_some() {
+            let fetch = client
+                .clone()
+                .fetch_range(key.clone(), shared.disk_cache.clone());
+            Task::future(async move { client.with_decoys(key.0, timed(fetch)).await }).map(
+                move |result| Message::RangeFetched(generation, key.clone(), hash.clone(), result),
+            )
+        } else {
+            let mode = shared.mode;
+            let finished = checked(generation, &hash, mode, shared.settings.severity);
+            let search = client
+                .clone()
+                .search(hash, mode)
+                .map_ok(|breach| (breach, LookupSource::Network));
+            Task::future(async move { client.with_decoys(mode, timed(search)).await }).map(finished)
+        };
+        let (task, handle) = task.abortable();
+        // Replacing the handle drops, and so aborts, any search still in flight.
+        self.search = Some(handle.abort_on_drop());
+        task
+    }
+
+    /// Looks up a password copied while the clipboard is watched, apart from
+    /// the check form: only its preview is kept, for the result.
+    fn check_clipboard(&self, password: &str, shared: &Shared) -> Task<Message> {
+        let hash = hash_password(password, shared.mode, shared.settings.normalization);
+        let preview = batch::mask(password);
+        let task = if self.offline {
+            let path = PathBuf::from(&shared.settings.dataset_path);
+            Task::future(lookup_offline(path, hash)).map(|result| result.map(|(breach, _)| breach))
+        } else {
+            let (client, mode) = (shared.range_client(), shared.mode);
+            Task::future(async move {
+                let search = client.clone().search(hash, mode);
+                client.with_decoys(mode, search).await
+            })
+        };
+        task.map(move |result| {
+            Message::ClipboardChecked(preview.clone(), result.map_err(|error| error.to_string()))
+        })
+    }
+
+    /// Looks for a replacement for the breached password, checking each
+    /// candidate the way the password itself was checked.
+    fn suggest_alternative(&mut self, shared: &Shared) -> Task<Message> {
+        let original = SecretString::from(self.password.expose().to_owned());
+        let client = shared.range_client();
+        let (mode, normalization) = (shared.mode, shared.settings.normalization);
+        let dataset = self
+            .offline
+            .then(|| PathBuf::from(&shared.settings.dataset_path));
+        let is_breached = move |candidate: SecretString| {
+            let hash = hash_password(candidate.expose(), mode, normalization);
+            let client = client.clone();
+            let dataset = dataset.clone();
+            async move {
+                let breach = match dataset {
+                    Some(path) => lookup_offline(path, hash).await.map(|(breach, _)| breach),
+                    None => client.search(hash, mode).await,
+                };
+                breach
+                    .map(|breach| matches!(breach, BreachResult::Found { .. }))
+                    .map_err(|error| error.message)
+            }
+        };
+        let search = async move {
+            suggest::first_unbreached(
+                || suggest::alternative(original.expose(), &mut OsRng),
+                is_breached,
+            )
+            .await
+        };
+        self.suggestion = None;
+        let (task, handle) = Task::future(search).map(Message::Suggested).abortable();
+        self.suggesting = Some(handle.abort_on_drop());
+        task
+    }
+
+    /// Shows a finished check and adds it to the session history.
+    fn show_breaches(
+        &mut self,
+        record: CheckRecord,
+        hash: SecretString,
+        duration: Duration,
+        range: Option<Arc<Range>>,
+    ) {
+        // A lookup started before the password changed is shown but not recorded
+        // against the wrong hash.
+        if hash_prefix_of(hash.expose()).eq_ignore_ascii_case(&record.prefix) {
+            self.history
+                .push(self.history_label.trim().to_owned(), record.clone(), hash);
+        }
+        self.record_export = None;
+        self.last_lookup = Some((
+            record.checked_at,
+            LookupMeta {
+                duration,
+                source: record.source,
+            },
+        ));
+        self.state = SearchResult::Breaches {
+            record,
+            duration,
+            strength: self.strength.clone(),
+            range,
+        };
+    }
+
+    /// Shows a failed lookup, or on a first rate limit waits out the
+    /// `Retry-After` and tries once more.
+    fn lookup_failed(&mut self, error: LookupError) -> Task<Message> {
+        if let FailureKind::RateLimited(wait) = error.kind
+            && !self.rate_limit_retried
+        {
+            self.rate_limit_retried = true;
+            let wait = wait.min(MAX_RETRY_AFTER);
+            self.state = SearchResult::RateLimited(wait);
+            let (task, handle) = Task::future(tokio::time::sleep(wait))
+                .map(|()| Message::RetryLookup)
+                .abortable();
+            self.search = Some(handle.abort_on_drop());
+            return task;
+        }
+        self.last_error = Some(error.clone());
+        self.state = SearchResult::failed(error);
+        Task::none()
+    }
+
+    /// Whether [`Message::Clear`] has anything left to wipe on this tab.
+    pub fn can_clear(&self) -> bool {
+        !self.password.is_empty() || !matches!(self.state, SearchResult::NotSubmitted)
+    }
+
+    /// Switches to the password form, taking a password rather than a hash,
+    /// for one that's pasted or brought over from another tab.
+    pub fn use_password_form(&mut self) {
+        self.input_mode = InputMode::Password;
+        self.hash_input = false;
+    }
+
+    /// Forgets a failure to reach the SOCKS proxy once it's turned off.
+    pub fn socks_disabled(&mut self) {
+        if matches!(self.state, SearchResult::SocksUnreachable(_)) {
+            self.state = SearchResult::NotSubmitted;
+        }
+    }
+
+    /// The Check tab: one password, account or domain at a time.
+    pub fn view<'a>(&'a self, shared: &'a Shared) -> Element<'a, Message> {
+        let strings = shared.strings();
+        let title = row![
+            text(strings.title).size(SIZES.title),
+            tooltip(
+                button(text("ⓘ").size(SIZES.icon))
+                    .style(button::text)
+                    .on_press(Message::ToggleExplainer),
+                text(strings.what_gets_sent),
+                tooltip::Position::Bottom,
+            )
+            .style(container::rounded_box),
+        ]
+        .spacing(5)
+        .align_y(Vertical::Center);
+        let modes = row![
+            radio(
+                strings.mode_password,
+                InputMode::Password,
+                Some(self.input_mode),
+                Message::InputMode
+            ),
+            radio(
+                strings.mode_account,
+                InputMode::Account,
+                Some(self.input_mode),
+                Message::InputMode
+            ),
+            radio(
+                strings.mode_domain,
+                InputMode::Domain,
+                Some(self.input_mode),
+                Message::InputMode
+            ),
+            radio(
+                strings.mode_pin,
+                InputMode::Pin,
+                Some(self.input_mode),
+                Message::InputMode
+            ),
+        ]
+        .spacing(10);
+        let form = match self.input_mode {
+            InputMode::Password => self.password_view(shared),
+            InputMode::Account => self.account_view(shared),
+            InputMode::Domain => self.domain_view(shared),
+            InputMode::Pin => self.pin_view(shared),
+        };
+        column![title]
+            .push_maybe(self.show_explainer.then(|| self.explainer_view(shared)))
+            .push(modes)
+            .push(form)
+            .push(self.result_view(shared))
+            .push_maybe(self.source_note(shared))
+            .push_maybe(self.details_view(shared))
+            .push_maybe(self.error_details_view(shared))
+            .push_maybe((!self.history.is_empty()).then(|| self.history_view(shared)))
+            .push_maybe(
+                matches!(
+                    self.state,
+                    SearchResult::Breaches { .. } | SearchResult::Errored(_)
+                )
+                .then(|| button(strings.copy_summary).on_press(Message::CopySummary)),
+            )
+            .push_maybe(self.record_export_view(shared))
+            .push(self.health_view(shared))
+            .spacing(5)
+            .into()
+    }
+
+    /// The password's length and kinds of character, so a paste that lost
+    /// or gained something shows without revealing it.
+    fn composition_view<'a>(&'a self, shared: &'a Shared) -> Element<'a, Message> {
+        let strings = shared.strings();
+        let composition = Composition::of(self.password.expose());
+        let readout = std::iter::once((strings.characters)(composition.chars, composition.bytes))
+            .chain(
+                composition
+                    .classes()
+                    .map(|class| (strings.class)(class).to_owned()),
+            )
+            .collect::<Vec<_>>()
+            .join(" · ");
+        let found = invisible::found(self.password.expose());
+        column![text(readout).style(text::secondary)]
+            .push_maybe((!found.is_empty()).then(|| {
+                row![
+                    text((strings.invisible_characters)(&found.join(", ")))
+                        .style(|theme: &Theme| text::Style {
+                            color: Some(severity_view::color(Severity::Low, theme)),
+                        })
+                        .width(Length::Fill),
+                    button(strings.remove_invisible).on_press(Message::RemoveInvisible),
+                ]
+                .spacing(10)
+                .align_y(Vertical::Center)
+            }))
+            .into()
+    }
+
+    /// The eye toggle at the end of the password field. There's no mask to
+    /// lift on an empty field or a hash, so it's disabled for those.
+    fn reveal_button<'a>(&'a self, shared: &'a Shared) -> Element<'a, Message> {
+        let enabled = !self.password.is_empty() && !self.hash_input;
+        let strings = shared.strings();
+        let (label, style): (_, fn(&Theme, button::Status) -> button::Style) = if self.show {
+            (strings.hide_password, button::primary)
+        } else {
+            (strings.show_password, button::secondary)
+        };
+        tooltip(
+            button(text("👁").shaping(text::Shaping::Advanced))
+                .style(style)
+                .on_press_maybe(enabled.then_some(Message::ShowPassword(!self.show))),
+            text(label),
+            tooltip::Position::Bottom,
+        )
+        .style(container::rounded_box)
+        .into()
+    }
+
+    fn explainer_view<'a>(&'a self, shared: &'a Shared) -> Element<'a, Message> {
+        let strings = shared.strings();
+        let last_range = match &self.state {
+            SearchResult::Breaches {
+                range: Some(range), ..
+            } => Some(
+                text!("{} {}", strings.explainer_last_range, range.len()).style(text::secondary),
+            ),
+            _ => None,
+        };
+        container(
+            column![text(strings.explainer_title).size(SIZES.subheading)]
+                .extend(
+                    strings
+                        .explainer
+                        .iter()
+                        .map(|&paragraph| text(paragraph).into()),
+                )
+                .push_maybe(last_range)
+                .push(button(strings.explainer_close).on_press(Message::ToggleExplainer))
+                .spacing(8)
+                .max_width(560),
+        )
+        .style(container::rounded_box)
+        .padding(10)
+        .into()
+    }
+
+    fn password_view<'a>(&'a self, shared: &'a Shared) -> Element<'a, Message> {
+        let strings = shared.strings();
+        column![
+            row![
+                radio(
+                    strings.mode_password,
+                    false,
+                    Some(self.hash_input),
+                    Message::HashInput
+                ),
+                radio(
+                    strings.hash,
+                    true,
+                    Some(self.hash_input),
+                    Message::HashInput
+                ),
+            ]
+            .spacing(10),
+            self.hash_line(shared),
+            self.password_input_row(shared),
+        ]
+        .push_maybe(self.clipboard_warning.then(|| {
+            column![
+                text((strings.clipboard_warning)(
+                    shared.settings.clipboard_watch_minutes
+                ))
+                .style(text::danger),
+                row![
+                    button(strings.start_watching)
+                        .style(button::danger)
+                        .on_press(Message::StartWatchingClipboard),
+                    button(strings.cancel)
+                        .style(button::secondary)
+                        .on_press(Message::WatchClipboard(false)),
+                ]
+                .spacing(10),
+            ]
+            .spacing(5)
+        }))
+        .push_maybe(
+            self.keypad
+                .as_ref()
+                .filter(|_| !self.hash_input)
+                .map(|keypad| keypad.view(strings).map(Message::Keypad)),
+        )
+        .push_maybe(
+            (!self.hash_input && !self.password.is_empty()).then(|| self.composition_view(shared)),
+        )
+        // There's no asking iced 0.13 which field has focus, so this shows on
+        // the password form whichever does; it's the only one that's masked.
+        .push_maybe(
+            (self.caps_lock.is_on() && !self.hash_input)
+                .then(|| text(strings.caps_lock_on).style(text::danger)),
+        )
+        .push_maybe(
+            self.clipboard_empty
+                .then(|| text(strings.clipboard_empty).style(text::secondary)),
+        )
+        .push_maybe(
+            self.strength
+                .as_ref()
+                .map(|strength| strength::meter(strength.score)),
+        )
+        .push_maybe(self.strength.as_ref().map(|strength| {
+            text(strength.to_string()).style(if strength.is_weak() {
+                text::danger
+            } else {
+                text::secondary
+            })
+        }))
+        .push_maybe(
+            self.common_rank.map(|rank| {
+                text((strings.common_password)(rank, common::count())).style(text::danger)
+            }),
+        )
+        .push_maybe(
+            (!self.password.is_locked())
+                .then(|| text(strings.memory_unlocked).style(text::secondary)),
+        )
+        .push_maybe(
+            (!self.hash_input && has_edge_whitespace(self.password.expose())).then(|| {
+                row![
+                    text(strings.edge_whitespace).style(text::danger),
+                    button(strings.trim_and_recheck).on_press(Message::TrimPassword),
+                ]
+                .spacing(10)
+                .align_y(Vertical::Center)
+            }),
+        )
+        .push(
+            row![
+                radio(
+                    "SHA-1",
+                    HashMode::Sha1,
+                    Some(shared.mode),
+                    Message::HashMode
+                ),
+                radio("NTLM", HashMode::Ntlm, Some(shared.mode), Message::HashMode),
+            ]
+            .spacing(10),
+        )
+        .push(
+            row![
+                text(strings.normalize),
+                radio(
+                    strings.off,
+                    Normalization::Off,
+                    Some(shared.settings.normalization),
+                    Message::Normalization
+                ),
+                radio(
+                    "NFC",
+                    Normalization::Nfc,
+                    Some(shared.settings.normalization),
+                    Message::Normalization
+                ),
+                radio(
+                    "NFKC",
+                    Normalization::Nfkc,
+                    Some(shared.settings.normalization),
+                    Message::Normalization
+                ),
+            ]
+            .spacing(10),
+        )
+        .push(self.request_view(shared))
+        .push(checkbox(strings.offline_mode, self.offline).on_toggle(Message::OfflineMode))
+        .push_maybe(self.offline.then(|| self.dataset_view(shared)))
+        .spacing(5)
+        .into()
+    }
+
+    /// The hash of what's typed, cut to its prefix unless it's revealed,
+    /// with its identicon and a button to copy it. A typed hash that isn't
+    /// one shows why instead.
+    pub fn hash_line<'a>(&'a self, shared: &'a Shared) -> Element<'a, Message> {
+        let strings = shared.strings();
+        let notice = if self.hash_input {
+            match normalize_hash(self.password.expose(), shared.mode) {
+                Err(error) if !self.password.is_empty() => text(error).style(text::danger),
+                _ => text!("{}: {}", shared.mode, self.displayed_hash()),
+            }
+        } else {
+            text!(
+                "{} ({}): {}",
+                shared.mode,
+                shared.settings.normalization,
+                self.displayed_hash()
+            )
+        };
+        row![
+            identicon::view(self.current_hash.expose(), strings.identicon),
+            notice,
+            checkbox(strings.reveal_full_hash, self.reveal_hash).on_toggle(Message::RevealHash),
+            button(strings.copy_hash)
+                .on_press_maybe((!self.current_hash.is_empty()).then_some(Message::CopyHash)),
+        ]
+        .push_maybe(
+            self.hash_copied
+                .then(|| text(strings.copied).style(text::success)),
+        )
+        .spacing(10)
+        .align_y(Vertical::Center)
+        .into()
+    }
+
+    /// The password field, or the hash field, and the buttons along it.
+    pub fn password_input_row<'a>(&'a self, shared: &'a Shared) -> Element<'a, Message> {
+        let can_submit = !self.current_hash.is_empty() && !self.is_searching();
+        let strings = shared.strings();
+        let placeholder = if self.hash_input {
+            (strings.hash_placeholder)(shared.mode)
+        } else {
+            strings.password_placeholder.to_owned()
+        };
+        row![
+            text_input(&placeholder, self.password.expose())
+                .style(high_contrast::text_input)
+                .id(text_input::Id::new(PASSWORD_INPUT))
+                .secure(self.is_masked())
+                .on_input(|input| Message::Input(input.into()))
+                .on_submit_maybe(can_submit.then_some(Message::Submit)),
+            self.reveal_button(shared),
+            self.keypad_button(shared),
+            text_input(strings.label_placeholder, &self.history_label)
+                .style(high_contrast::text_input)
+                .on_input(Message::HistoryLabel)
+                .on_submit_maybe(can_submit.then_some(Message::Submit))
+                .width(150),
+            button(strings.submit).on_press_maybe(can_submit.then_some(Message::Submit)),
+            button(strings.clear).on_press_maybe(self.can_clear().then_some(Message::Clear)),
+            button(strings.paste_and_check)
+                .on_press_maybe((!self.is_searching()).then_some(Message::PasteAndCheck)),
+            button(strings.watch_clipboard)
+                .style(button::secondary)
+                .on_press_maybe(
+                    self.clipboard_monitor
+                        .is_none()
+                        .then_some(Message::WatchClipboard(true))
+                ),
+        ]
+        .spacing(5)
+        .into()
+    }
+
+    fn request_view<'a>(&'a self, shared: &'a Shared) -> Element<'a, Message> {
+        let strings = shared.strings();
+        let toggle = button(text!(
+            "{} {}",
+            if self.show_request { "▾" } else { "▸" },
+            strings.request_preview
+        ))
+        .style(button::text)
+        .on_press(Message::ToggleRequestPreview);
+        if !self.show_request {
+            return toggle.into();
+        }
+        let Some(preview) = self.request_preview(shared) else {
+            let reason = if self.offline {
+                strings.request_offline
+            } else {
+                strings.request_empty
+            };
+            return column![toggle, text(reason).style(text::secondary)]
+                .spacing(2)
+                .into();
+        };
+        let hash = self.current_hash.expose();
+        let kept = hash.len().saturating_sub(PREFIX_LEN);
+        let kept_local = if self.reveal_hash {
+            hash_suffix_of(hash).to_owned()
+        } else {
+            "•".repeat(kept)
+        };
+        column![
+            toggle,
+            container(
+                text(preview.to_string())
+                    .font(iced::Font::MONOSPACE)
+                    .size(SIZES.code)
+            )
+            .style(container::rounded_box)
+            .padding(8)
+            .width(Length::Fill),
+            row![
+                text((strings.kept_local)(kept, &kept_local))
+                    .font(iced::Font::MONOSPACE)
+                    .size(SIZES.code)
+                    .style(text::success),
+                button(strings.copy_request).on_press(Message::CopyRequestPreview),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+        ]
+        .spacing(4)
+        .into()
+    }
+
+    /// Only the prefix that's sent to the API, unless the full hash was asked for.
+    fn displayed_hash(&self) -> String {
+        let hash = self.current_hash.expose();
+        if self.reveal_hash || hash.len() <= PREFIX_LEN {
+            hash.to_owned()
+        } else {
+            format!("{}…", hash_prefix_of(hash))
+        }
+    }
+
+    fn dataset_view<'a>(&'a self, shared: &'a Shared) -> Element<'a, Message> {
+        let strings = shared.strings();
+        let has_path = !shared.settings.dataset_path.trim().is_empty();
+        let action = match self.download {
+            Some(_) => button(strings.cancel_download).on_press(Message::CancelDownload),
+            None => button(strings.download_dataset)
+                .on_press_maybe(has_path.then_some(Message::DownloadDataset)),
+        };
+        let idle = self.download.is_none() && self.dataset_verification != Some(None);
+        let verify = button(strings.verify_dataset)
+            .on_press_maybe((has_path && idle).then_some(Message::VerifyDataset));
+        let status = match &self.download_progress {
+            None => text(""),
+            Some(Err(error)) => text((strings.download_stopped)(error)).style(text::danger),
+            Some(Ok(progress)) if progress.verifying && self.download.is_some() => {
+                text(strings.verifying_dataset).style(text::secondary)
+            }
+            Some(Ok(progress)) if progress.is_finished() => {
+                text((strings.dataset_downloaded)(progress.bytes / 1_000_000)).style(text::success)
+            }
+            Some(Ok(progress)) => text!(
+                "{}{}",
+                (strings.download_progress)(
+                    progress.done,
+                    download::PREFIX_COUNT,
+                    progress.bytes / 1_000_000,
+                    progress.failures
+                ),
+                match (&self.download, progress.eta()) {
+                    (Some(_), Some(eta)) => (strings.download_eta)(eta.as_secs() / 60),
+                    (Some(_), None) => String::new(),
+                    (None, _) => strings.download_paused.to_owned(),
+                }
+            )
+            .style(text::secondary),
+        };
+        column![
+            row![
+                text_input(strings.dataset_placeholder, &shared.settings.dataset_path)
+                    .style(high_contrast::text_input)
+                    .on_input(Message::DatasetPath),
+                action,
+                verify,
+            ]
+            .spacing(5),
+            status,
+        ]
+        .push_maybe(
+            self.dataset_verification
+                .as_ref()
+                .map(|verification| match verification {
+                    None => text(strings.verifying_dataset).style(text::secondary),
+                    Some(Ok(verification)) => text((strings.dataset_verified)(
+                        &verification.to_string(),
+                    ))
+                    .style(if verification.is_complete() {
+                        text::success
+                    } else {
+                        text::danger
+                    }),
+                    Some(Err(error)) => {
+                        text((strings.dataset_verify_failed)(error)).style(text::danger)
+                    }
+                }),
+        )
+        .spacing(5)
+        .into()
+    }
+
+    fn keypad_button<'a>(&'a self, shared: &'a Shared) -> Element<'a, Message> {
+        tooltip(
+            button("⌨")
+                .style(if self.keypad.is_some() {
+                    button::primary
+                } else {
+                    button::secondary
+                })
+                .on_press_maybe((!self.hash_input).then_some(Message::ToggleKeypad)),
+            text(shared.strings().keypad),
+            tooltip::Position::Bottom,
+        )
+        .style(container::rounded_box)
+        .into()
+    }
+
+    /// The PIN form: ranked against the bundled table as it's typed, all
+    /// offline, and only sent for the breach check if that's on.
+    fn pin_view<'a>(&'a self, shared: &'a Shared) -> Element<'a, Message> {
+        let strings = shared.strings();
+        let pin = self.password.expose();
+        let valid = pin::validate(pin).is_ok();
+        let can_submit = valid && shared.settings.pin_breach_check && !self.is_searching();
+        let ranking = if pin.is_empty() {
+            None
+        } else if !valid {
+            Some(text((strings.pin_length)(pin::MIN_LEN, pin::MAX_LEN)).style(text::secondary))
+        } else {
+            Some(match pin::rank(pin) {
+                Some(ranking) => text((strings.pin_rank)(ranking.rank, &ranking.prevalence()))
+                    .style(text::danger),
+                None => text((strings.pin_not_listed)(pin::count())).style(text::success),
+            })
+        };
+        column![
+            row![
+                text_input(strings.pin_placeholder, pin)
+                    .style(high_contrast::text_input)
+                    .id(text_input::Id::new(PASSWORD_INPUT))
+                    .secure(!self.show)
+                    .on_input(|input| Message::Input(input.into()))
+                    .on_submit_maybe(can_submit.then_some(Message::Submit))
+                    .width(200),
+                self.reveal_button(shared),
+                self.keypad_button(shared),
+                button(strings.submit).on_press_maybe(can_submit.then_some(Message::Submit)),
+                button(strings.clear).on_press_maybe(self.can_clear().then_some(Message::Clear)),
+            ]
+            .spacing(5),
+            checkbox(strings.pin_breach_check, shared.settings.pin_breach_check)
+                .on_toggle(Message::PinBreachCheck),
+        ]
+        .push_maybe(
+            self.keypad
+                .as_ref()
+                .map(|keypad| keypad.view(strings).map(Message::Keypad)),
+        )
+        .push_maybe(ranking)
+        .spacing(5)
+        .into()
+    }
+
+    fn account_view<'a>(&'a self, shared: &'a Shared) -> Element<'a, Message> {
+        let strings = shared.strings();
+        let account_not_empty = !self.account.trim().is_empty() && !self.is_searching();
+        row![
+            text_input(strings.account_placeholder, &self.account)
+                .style(high_contrast::text_input)
+                .on_input(Message::AccountInput)
+                .on_submit_maybe(account_not_empty.then_some(Message::Submit)),
+            button(strings.submit).on_press_maybe(account_not_empty.then_some(Message::Submit)),
+            button(strings.check_pastes)
+                .on_press_maybe(account_not_empty.then_some(Message::SubmitPastes)),
+        ]
+        .spacing(5)
+        .into()
+    }
+
+    fn domain_view<'a>(&'a self, shared: &'a Shared) -> Element<'a, Message> {
+        let strings = shared.strings();
+        let domain_not_empty = !self.domain.trim().is_empty() && !self.is_searching();
+        row![
+            text_input(strings.domain_placeholder, &self.domain)
+                .style(high_contrast::text_input)
+                .on_input(Message::DomainInput)
+                .on_submit_maybe(domain_not_empty.then_some(Message::Submit)),
+            button(strings.search_domain)
+                .on_press_maybe(domain_not_empty.then_some(Message::Submit)),
+        ]
+        .spacing(5)
+        .into()
+    }
+
+    fn record_export_view<'a>(&'a self, shared: &'a Shared) -> Option<Element<'a, Message>> {
+        if !matches!(self.state, SearchResult::Breaches { .. }) {
+            return None;
+        }
+        let strings = shared.strings();
+        let status = self
+            .record_export
+            .as_ref()
+            .map(|exported| exported_status(strings, exported));
+        Some(
+            row![button(strings.export_json).on_press(Message::ExportRecord)]
+                .push_maybe(status)
+                .spacing(10)
+                .align_y(Vertical::Center)
+                .into(),
+        )
+    }
+
+    /// The banner for a finished password check.
+    fn verdict_view(
+        &self,
+        record: &CheckRecord,
+        strength: Option<&Strength>,
+        shared: &Shared,
+    ) -> Element<'_, Message> {
+        let strings = shared.strings();
+        if let (BreachResult::Found { count }, Some(severity)) = (record.result, record.severity) {
+            severity_view::result_banner(
+                Some(severity),
+                (strings.found_headline)(
+                    severity,
+                    &format_count(count, CountStyle::Compact, shared.separator),
+                ),
+                (strings.advice)(severity).to_owned(),
+            )
+        } else if strength.is_some_and(Strength::is_weak) {
+            severity_view::result_banner(
+                None,
+                strings.not_found_headline.to_owned(),
+                strings.not_found_but_weak.to_owned(),
+            )
+        } else {
+            severity_view::result_banner(
+                None,
+                strings.not_found_headline.to_owned(),
+                strings.not_found_safe.to_owned(),
+            )
+        }
+    }
+
+    /// How the password meets each rule of the policy, if the settings file
+    /// sets one. A hash has no characters to judge.
+    fn policy_view<'a>(&'a self, shared: &'a Shared) -> Option<Element<'a, Message>> {
+        let policy = shared
+            .settings
+            .policy
+            .as_ref()
+            .filter(|_| !self.hash_input)?;
+        let verdicts = policy.check(self.password.expose(), None);
+        if verdicts.is_empty() {
+            return None;
+        }
+        let lines = verdicts.into_iter().map(|verdict| {
+            let line = text!(
+                "{} {}",
+                if verdict.passed { "✓" } else { "✗" },
+                verdict.rule
+            );
+            if verdict.passed {
+                line.style(text::success)
+            } else {
+                line.style(text::danger)
+            }
+            .into()
+        });
+        Some(column(lines).spacing(2).into())
+    }
+
+    /// The quick-check window's password field and verdict, with anything
+    /// more left to the main window.
+    pub fn quick_view<'a>(&'a self, shared: &'a Shared) -> Element<'a, Message> {
+        let strings = shared.strings();
+        let can_submit = !self.current_hash.is_empty() && !self.is_searching();
+        let verdict: Element<'_, Message> = match &self.state {
+            SearchResult::Breaches {
+                record, strength, ..
+            } if self.input_mode == InputMode::Password => {
+                self.verdict_view(record, strength.as_ref(), shared)
+            }
+            SearchResult::Searching => text(strings.title_checking).style(text::secondary).into(),
+            SearchResult::Cancelled => text(strings.search_cancelled).style(text::secondary).into(),
+            SearchResult::NotSubmitted => text("").into(),
+            _ => text(strings.title_failed).style(text::danger).into(),
+        };
+        column![
+            row![
+                text_input(strings.password_placeholder, self.password.expose())
+                    .style(high_contrast::text_input)
+                    .id(text_input::Id::new(QUICK_INPUT))
+                    .secure(!self.show)
+                    .on_input(|input| Message::Input(input.into()))
+                    .on_submit_maybe(can_submit.then_some(Message::Submit)),
+                button(strings.submit).on_press_maybe(can_submit.then_some(Message::Submit)),
+            ]
+            .spacing(5),
+            verdict,
+        ]
+        .spacing(8)
+        .into()
+    }
+
+    fn suggestion_view<'a>(&'a self, shared: &'a Shared) -> Element<'a, Message> {
+        let strings = shared.strings();
+        let suggest = button(strings.suggest_alternative).on_press(Message::SuggestAlternative);
+        if self.suggesting.is_some() {
+            return text(strings.suggesting).style(text::secondary).into();
+        }
+        match &self.suggestion {
+            None => suggest.into(),
+            Some(Ok(Some(suggestion))) => row![
+                text(suggestion.expose()).font(iced::Font::MONOSPACE),
+                button(strings.copy).on_press(Message::CopySuggestion),
+                button(strings.use_suggestion).on_press(Message::UseSuggestion),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center)
+            .into(),
+            Some(Ok(None)) => column![
+                text((strings.all_suggestions_breached)(suggest::ATTEMPTS)).style(text::danger),
+                suggest
+            ]
+            .spacing(5)
+            .into(),
+            Some(Err(error)) => column![
+                text((strings.suggestion_failed)(error)).style(text::danger),
+                suggest
+            ]
+            .spacing(5)
+            .into(),
+        }
+    }
+
+    fn result_view<'a>(&'a self, shared: &'a Shared) -> Element<'a, Message> {
+        let strings = shared.strings();
+        match &self.state {
+            SearchResult::Breaches {
+                record, strength, ..
+            } => {
+                let verdict = self.verdict_view(record, strength.as_ref(), shared);
+                let found = matches!(record.result, BreachResult::Found { .. });
+                return column![verdict]
+                    .push_maybe(self.policy_view(shared))
+                    .push_maybe((found && !self.hash_input).then(|| self.suggestion_view(shared)))
+                    .push_maybe(
+                        strength
+                            .as_ref()
+                            .map(|strength| text(strength.to_string()).style(text::secondary)),
+                    )
+                    .push_maybe(
+                        strength
+                            .as_ref()
+                            .map(|strength| self.crack_times_view(strength, shared)),
+                    )
+                    .spacing(2)
+                    .into();
+            }
+            SearchResult::Account(account) if account.breaches.is_empty() => {
+                text((strings.account_clean)(&account.account)).style(text::success)
+            }
+            SearchResult::Account(account) => {
+                let heading = text((strings.account_breached)(
+                    &account.account,
+                    account.breaches.len(),
+                ))
+                .style(text::danger);
+                return column![heading]
+                    .extend(
+                        account
+                            .breaches
+                            .iter()
+                            .map(|name| text!("• {}", name).into()),
+                    )
+                    .spacing(2)
+                    .into();
+            }
+            SearchResult::Domain(domain) if domain.aliases.is_empty() => {
+                text((strings.domain_clean)(&domain.domain)).style(text::success)
+            }
+            SearchResult::Domain(domain) => {
+                let header = row![
+                    text((strings.domain_breached)(
+                        domain.aliases.len(),
+                        &domain.domain
+                    ))
+                    .style(text::danger),
+                    radio(
+                        strings.sort_by_alias,
+                        DomainSort::Alias,
+                        Some(self.domain_sort),
+                        Message::DomainSort
+                    ),
+                    radio(
+                        strings.sort_most_breaches,
+                        DomainSort::MostBreaches,
+                        Some(self.domain_sort),
+                        Message::DomainSort
+                    ),
+                ]
+                .spacing(10);
+                let rows = column(domain.sorted(self.domain_sort).into_iter().map(
+                    |(alias, breaches)| {
+                        row![
+                            text!("{}@{}", alias, domain.domain).width(Length::FillPortion(2)),
+                            text!("{}", breaches.len()).width(Length::FillPortion(1)),
+                            text(breaches.join(", "))
+                                .style(text::secondary)
+                                .width(Length::FillPortion(4)),
+                        ]
+                        .spacing(5)
+                        .into()
+                    },
+                ))
+                .spacing(2);
+                return column![header, scrollable(rows).height(Length::Fixed(200.))]
+                    .spacing(5)
+                    .into();
+            }
+            SearchResult::Pastes(pastes) if pastes.pastes.is_empty() => {
+                text((strings.pastes_clean)(&pastes.account)).style(text::success)
+            }
+            SearchResult::Pastes(pastes) => {
+                let heading = text((strings.pastes_found)(&pastes.account, pastes.pastes.len()))
+                    .style(text::danger);
+                return column![heading]
+                    .extend(pastes.pastes.iter().map(|paste| {
+                        let date = paste.date.map_or_else(
+                            || strings.paste_unknown_date.to_owned(),
+                            |date| date.with_timezone(&Local).format("%x %X").to_string(),
+                        );
+                        text((strings.paste)(
+                            paste.title.as_deref().unwrap_or(strings.paste_untitled),
+                            &paste.source,
+                            &paste.id,
+                            &date,
+                            paste.email_count,
+                        ))
+                        .into()
+                    }))
+                    .spacing(2)
+                    .into();
+            }
+            SearchResult::Errored(error) => {
+                return row![
+                    text((strings.error)(&error.message))
+                        .style(text::danger)
+                        .width(Length::Fill),
+                    button(strings.retry).on_press(Message::Retry),
+                ]
+                .spacing(10)
+                .align_y(Vertical::Center)
+                .into();
+            }
+            SearchResult::SocksUnreachable(error) => {
+                return row![
+                    text((strings.error)(error))
+                        .style(text::danger)
+                        .width(Length::Fill),
+                    button(strings.disable_socks).on_press(Message::UseSocks(false)),
+                ]
+                .spacing(10)
+                .align_y(Vertical::Center)
+                .into();
+            }
+            SearchResult::Unparsable(error) => {
+                return column![
+                    text((strings.unparsable)(&error.to_string())).style(text::danger),
+                    row![
+                        text(strings.captive_portal)
+                            .style(text::secondary)
+                            .width(Length::Fill),
+                        button(strings.retry).on_press(Message::Retry),
+                    ]
+                    .spacing(10)
+                    .align_y(Vertical::Center),
+                ]
+                .spacing(2)
+                .into();
+            }
+            SearchResult::UnexpectedStatus { status, message } => {
+                let hint = if (400..500).contains(status) {
+                    strings.check_endpoint
+                } else {
+                    strings.api_trouble
+                };
+                return column![
+                    text((strings.error)(message)).style(text::danger),
+                    row![
+                        text(hint).style(text::secondary).width(Length::Fill),
+                        button(strings.retry).on_press(Message::Retry),
+                    ]
+                    .spacing(10)
+                    .align_y(Vertical::Center),
+                ]
+                .spacing(2)
+                .into();
+            }
+            SearchResult::Offline => {
+                return row![
+                    text(strings.offline)
+                        .style(text::danger)
+                        .width(Length::Fill),
+                    button(strings.retry).on_press(Message::Retry),
+                ]
+                .spacing(10)
+                .align_y(Vertical::Center)
+                .into();
+            }
+            SearchResult::NotSubmitted => text!(""),
+            SearchResult::RateLimited(wait) => {
+                text((strings.rate_limited)(wait.as_secs())).style(text::secondary)
+            }
+            SearchResult::Searching => {
+                let dots = &"..."[..self.search_frame % 4];
+                let elapsed = self
+                    .searching_since
+                    .map_or(Duration::ZERO, |since| since.elapsed());
+                let progress = if elapsed >= SHOW_SEARCH_TIME {
+                    text!(
+                        "{}{} {:.1}s",
+                        strings.searching,
+                        dots,
+                        elapsed.as_secs_f32()
+                    )
+                } else {
+                    text!("{}{}", strings.searching, dots)
+                };
+                return row![
+                    progress.style(text::secondary),
+                    button(strings.cancel)
+                        .style(button::secondary)
+                        .on_press(Message::CancelSearch),
+                ]
+                .spacing(10)
+                .align_y(Vertical::Center)
+                .into();
+            }
+            SearchResult::Cancelled => text(strings.search_cancelled).style(text::secondary),
+        }
+        .into()
+    }
+
+    fn health_view<'a>(&'a self, shared: &'a Shared) -> Element<'a, Message> {
+        let strings = shared.strings();
+        match &self.health {
+            None => text(strings.checking_api).style(text::secondary).into(),
+            Some(Ok(latency)) => text((strings.api_reachable)(latency.as_millis()))
+                .style(text::success)
+                .into(),
+            Some(Err(error)) => row![
+                text((strings.api_unreachable)(&error.message)).style(text::danger),
+                button(strings.retry).on_press(Message::CheckHealth),
+            ]
+            .spacing(5)
+            .align_y(Vertical::Center)
+            .into(),
+        }
+    }
+
+    fn crack_times_view<'a>(
+        &'a self,
+        strength: &Strength,
+        shared: &'a Shared,
+    ) -> Element<'a, Message> {
+        column(shared.settings.crack_profiles.iter().map(|profile| {
+            let seconds = crack_time::seconds_to_crack(strength.guesses_log10, profile);
+            row![
+                text(profile.name.clone()).width(Length::FillPortion(2)),
+                text(crack_time::humanize(seconds)).width(Length::FillPortion(1)),
+            ]
+            .into()
+        }))
+        .into()
+    }
+
+    fn source_note<'a>(&'a self, shared: &'a Shared) -> Option<Element<'a, Message>> {
+        let SearchResult::Breaches {
+            record, duration, ..
+        } = &self.state
+        else {
+            return None;
+        };
+        let strings = shared.strings();
+        let via = match record.source {
+            LookupSource::Network => strings.source_network.to_owned(),
+            LookupSource::Memory => strings.via_memory.to_owned(),
+            LookupSource::Disk => strings.via_disk.to_owned(),
+            LookupSource::Offline(snapshot) => (strings.via_snapshot)(&snapshot.to_string()),
+        };
+        let note = text((strings.checked_via)(duration.as_millis(), &via));
+        Some(note.style(text::secondary).into())
+    }
+
+    fn history_view<'a>(&'a self, shared: &'a Shared) -> Element<'a, Message> {
+        let strings = shared.strings();
+        let entries = column(self.history.iter().enumerate().map(|(index, entry)| {
+            let record = &entry.record;
+            let outcome = match record.result {
+                BreachResult::Found { count } => (strings.seen_times)(
+                    count,
+                    &format_count(count, CountStyle::Full, shared.separator),
+                ),
+                BreachResult::NotFound => strings.not_found.to_owned(),
+            };
+            let label = if entry.label.is_empty() {
+                format!("{} {}…", record.algorithm, record.prefix)
+            } else {
+                format!("{} ({} {}…)", entry.label, record.algorithm, record.prefix)
+            };
+            let line = text!(
+                "{}  {}: {}",
+                record.checked_at.with_timezone(&Local).format("%H:%M:%S"),
+                label,
+                outcome
+            );
+            let line = match record.severity {
+                Some(severity) => line.style(move |theme: &Theme| text::Style {
+                    color: Some(severity_view::color(severity, theme)),
+                }),
+                None => line.style(text::success),
+            };
+            button(line)
+                .style(button::text)
+                .padding(2)
+                .on_press(Message::RerunHistory(index))
+                .into()
+        }));
+        column![
+            row![
+                text(strings.history_title),
+                button(strings.clear_history).on_press(Message::ClearHistory),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            report_view(
+                shared.strings(),
+                shared.report_export.as_ref(),
+                Message::ExportReport
+            ),
+            scrollable(entries).height(Length::Fixed(150.)),
+        ]
+        .spacing(5)
+        .into()
+    }
+
+    /// The "Details" expander under a failure: what went wrong underneath
+    /// the headline, for a bug report or a closer look.
+    fn error_details_view<'a>(&'a self, shared: &'a Shared) -> Option<Element<'a, Message>> {
+        let details = match &self.state {
+            SearchResult::Errored(error) => &error.details,
+            SearchResult::Offline
+            | SearchResult::SocksUnreachable(_)
+            | SearchResult::Unparsable(_)
+            | SearchResult::UnexpectedStatus { .. } => &self.last_error.as_ref()?.details,
+            _ => return None,
+        };
+        let strings = shared.strings();
+        let toggle = button(if self.show_error_details {
+            strings.hide_details
+        } else {
+            strings.details
+        })
+        .on_press(Message::ToggleErrorDetails);
+        if !self.show_error_details {
+            return Some(toggle.into());
+        }
+
+        let url = details.url.as_ref().map(|url| {
+            let mut url = url.clone();
+            // The last segment is the hash prefix, kept back like the hash is.
+            if !self.reveal_hash
+                && let Ok(mut segments) = url.path_segments_mut()
+            {
+                segments.pop().push("*****");
+            }
+            text!("{}: {}", strings.detail_url, url)
+        });
+        let lines = column![]
+            .push_maybe(
+                details
+                    .status
+                    .map(|status| text!("{}: {}", strings.detail_status, status)),
+            )
+            .push_maybe(
+                details
+                    .kind
+                    .map(|kind| text!("{}: {}", strings.detail_kind, kind)),
+            )
+            .push_maybe(url)
+            .push_maybe(
+                details
+                    .attempts
+                    .map(|attempts| text!("{}: {}", strings.detail_attempts, attempts)),
+            )
+            .extend(details.chain.iter().enumerate().map(|(depth, error)| {
+                text!(
+                    "{}{}",
+                    if depth == 0 { "" } else { strings.caused_by },
+                    error
+                )
+                .into()
+            }))
+            .spacing(2);
+        Some(
+            column![
+                toggle,
+                container(lines).padding(5).style(container::rounded_box)
+            ]
+            .spacing(5)
+            .into(),
+        )
+    }
+
+    fn details_view<'a>(&'a self, shared: &'a Shared) -> Option<Element<'a, Message>> {
+        let SearchResult::Breaches { record, range, .. } = &self.state else {
+            return None;
+        };
+        let strings = shared.strings();
+        let toggle = button(if self.show_details {
+            strings.hide_details
+        } else {
+            strings.details
+        })
+        .on_press(Message::ToggleDetails);
+        if !self.show_details {
+            return Some(toggle.into());
+        }
+
+        let count = match record.result {
+            BreachResult::Found { count } => Some(count),
+            BreachResult::NotFound => None,
+        };
+        let details = match range
+            .as_deref()
+            .and_then(|range| range::stats(range, count))
+        {
+            Some(stats) => column![
+                text((strings.range_suffixes)(stats.suffixes, &record.prefix)),
+                text((strings.range_spread)(
+                    &format_count(stats.max, CountStyle::Full, shared.separator),
+                    &format_count(stats.median, CountStyle::Full, shared.separator)
+                )),
+            ]
+            .push_maybe(
+                stats
+                    .rank
+                    .map(|rank| text((strings.range_rank)(rank, stats.suffixes))),
+            )
+            .push_maybe(
+                range
+                    .as_deref()
+                    .filter(|range| range.padding() > 0)
+                    .map(|range| {
+                        text((strings.range_padding)(range.padding())).style(text::secondary)
+                    }),
+            ),
+            None => column![text(strings.range_missing)],
+        };
+        Some(column![toggle, details.spacing(2)].spacing(5).into())
+    }
+
+    /// The result of the last copied password checked, in the corner.
+    pub fn toast_view<'a>(&'a self, shared: &'a Shared) -> Option<Element<'a, Message>> {
+        let strings = shared.strings();
+        let (preview, result) = self.clipboard_toast.as_ref()?;
+        let (verdict, style): (_, fn(&Theme) -> text::Style) = match result {
+            Ok(BreachResult::Found { count }) => (
+                (strings.seen_times)(
+                    *count,
+                    &format_count(*count, CountStyle::Full, shared.separator),
+                ),
+                text::danger,
+            ),
+            Ok(BreachResult::NotFound) => (strings.not_found.to_owned(), text::success),
+            Err(error) => ((strings.error)(error), text::danger),
+        };
+        let toast = container(text((strings.clipboard_result)(preview, &verdict)).style(style))
+            .padding(10)
+            .style(container::rounded_box);
+        Some(
+            container(toast)
+                .align_right(Length::Fill)
+                .align_bottom(Length::Fill)
+                .padding(30)
+                .into(),
+        )
+    }
+
+    /// Ticks the searching animation while a search runs, and polls the
+    /// clipboard while it's watched.
+    pub fn subscription(&self) -> Subscription<Message> {
+        Subscription::batch([
+            // Only ticks while there's something to animate, so an idle window
+            // isn't redrawn.
+            if self.is_searching() {
+                iced::time::every(SEARCH_TICK).map(|_| Message::SearchTick)
+            } else {
+                Subscription::none()
+            },
+            if self.clipboard_monitor.is_some() {
+                iced::time::every(clipboard_monitor::POLL).map(|_| Message::ClipboardTick)
+            } else {
+                Subscription::none()
+            },
+        ])
+    }
+}
+
+/// Builds the message for a finished lookup of `hash`, started in search
+/// `generation`, keeping only its prefix in the [`CheckRecord`].
+fn checked(
+    generation: u64,
+    hash: &str,
+    mode: HashMode,
+    thresholds: Thresholds,
+) -> impl Fn(Result<(BreachResult, LookupMeta), LookupError>) -> Message + use<> {
+    let prefix = hash_prefix_of(hash).to_owned();
+    move |result| {
+        Message::BreachResult(
+            generation,
+            result.map(|(breach, meta)| {
+                let record = CheckRecord::new(&prefix, mode, breach, meta.source, &thresholds);
+                (record, meta.duration)
+            }),
+        )
+    }
+}
+
+/// Times `lookup`, from when it's first polled until it settles.
+async fn timed<T>(
+    lookup: impl Future<Output = Result<(T, LookupSource), LookupError>>,
+) -> Result<(T, LookupMeta), LookupError> {
+    let started = Instant::now();
+    let (value, source) = lookup.await?;
+    Ok((
+        value,
+        LookupMeta {
+            duration: started.elapsed(),
+            source,
+        },
+    ))
+}
+
+async fn lookup_offline(
+    path: PathBuf,
+    hash: String,
+) -> Result<(BreachResult, LookupSource), LookupError> {
+    if path.as_os_str().is_empty() {
+        return Err(LookupError::other(
+            "choose a dataset file for offline mode first",
+        ));
+    }
+    let (count, snapshot) = tokio::task::spawn_blocking(move || lookup_dataset(&path, &hash))
+        .await
+        .map_err(|e| LookupError::other(e.to_string()))?
+        .map_err(LookupError::other)?;
+
+    Ok((
+        BreachResult::from_count(count),
+        LookupSource::Offline(snapshot),
+    ))
+}
+
+/// [`offline::lookup`], or a [`packed`] dataset's if that's what `path` is,
+/// but with the dataset's Bloom filter consulted first if there is one, so
+/// a hash it rules out is answered without a search. A filter that can't be
+/// used is logged and the file searched as before.
+fn lookup_dataset(path: &Path, hash: &str) -> Result<(Option<u64>, chrono::NaiveDate), String> {
+    let ruled_out = match bloom::Filter::for_dataset(path) {
+        Ok(filter) => filter.is_some_and(|filter| !filter.might_contain(hash)),
+        Err(error) => {
+            tracing::warn!("not using the dataset filter: {error}");
+            false
+        }
+    };
+    // One that can't be read is left for the text dataset's error.
+    if packed::is_packed(path).unwrap_or(false) {
+        let dataset = packed::PackedDataset::open(path).map_err(|e| e.to_string())?;
+        let count = if ruled_out {
+            None
+        } else {
+            dataset.lookup(hash).map_err(|e| e.to_string())?
+        };
+        return Ok((count, dataset.snapshot()));
+    }
+    let mut dataset = offline::Dataset::open(path).map_err(|e| e.to_string())?;
+    let count = if ruled_out {
+        None
+    } else {
+        dataset.lookup(hash).map_err(|e| e.to_string())?
+    };
+    Ok((count, dataset.snapshot()))
+}
@@ -0,0 +1,304 @@
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use thiserror::Error;
+
+use crate::batch::BatchItem;
+
+/// The checkpoint format this build writes and reads. Bumped whenever a
+/// change would make an older checkpoint resume wrongly.
+pub const VERSION: u32 = 1;
+
+/// Why a checkpoint can't be resumed.
+#[derive(Debug, Error)]
+pub enum CheckpointError {
+    #[error("{}: {error}", path.display())]
+    Io { path: PathBuf, error: io::Error },
+    #[error("{} isn't a readable checkpoint: {reason}", path.display())]
+    Corrupt { path: PathBuf, reason: String },
+    #[error("{} is from a newer or older version (format {found}, this one reads {VERSION})", path.display())]
+    Version { path: PathBuf, found: u32 },
+    #[error("{} is for a different input, or the same one checked with other settings", path.display())]
+    Mismatch { path: PathBuf },
+}
+
+/// The progress of an interrupted batch, saved as it runs so it can carry
+/// on from where it stopped.
+///
+/// Nothing in it gives away more than the results already do: the input is
+/// identified by a [`fingerprint`] of the hash prefixes, and each finished
+/// item is its index, prefix and count.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub version: u32,
+    /// The [`fingerprint`] of the input.
+    pub input: String,
+    /// How many items the batch has.
+    pub total: usize,
+    pub items: Vec<BatchItem>,
+}
+
+/// Where the window keeps the checkpoint of its batch, e.g.
+/// `~/.local/share/cybersec-wow/batch-checkpoint.json` on Linux.
+pub fn default_path() -> Option<PathBuf> {
+    let dirs = ProjectDirs::from("", "", env!("CARGO_PKG_NAME"))?;
+    Some(dirs.data_dir().join("batch-checkpoint.json"))
+}
+
+/// Identifies a batch by the index and hash prefix of each item, in order,
+/// so a checkpoint is only resumed for the input it was made from, hashed
+/// the same way.
+///
+/// The digest is only over the prefixes, as the hash of the whole input
+/// would be as good as the hash of the password for a one-line file.
+pub fn fingerprint<'a>(items: impl IntoIterator<Item = (usize, &'a str)>) -> String {
+    let mut hasher = Sha1::new();
+    for (index, prefix) in items {
+        hasher.update(format!("{index}:{prefix}\n"));
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+impl Checkpoint {
+    /// A checkpoint of `items` so far. Failed lookups are left out, so that
+    /// resuming tries them again.
+    pub fn new(input: String, total: usize, items: impl IntoIterator<Item = BatchItem>) -> Self {
+        Self {
+            version: VERSION,
+            input,
+            total,
+            items: items
+                .into_iter()
+                .filter(|item| item.outcome.is_ok())
+                .collect(),
+        }
+    }
+
+    /// The checkpoint saved at `path`, or `None` if there's none.
+    pub fn load(path: &Path) -> Result<Option<Self>, CheckpointError> {
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => {
+                return Err(CheckpointError::Io {
+                    path: path.to_owned(),
+                    error,
+                });
+            }
+        };
+        let corrupt = |reason: String| CheckpointError::Corrupt {
+            path: path.to_owned(),
+            reason,
+        };
+        // The version is read on its own first, so a newer format that no
+        // longer parses is still reported as a version mismatch.
+        #[derive(Deserialize)]
+        struct Versioned {
+            version: u32,
+        }
+        let versioned: Versioned =
+            serde_json::from_slice(&bytes).map_err(|error| corrupt(error.to_string()))?;
+        if versioned.version != VERSION {
+            return Err(CheckpointError::Version {
+                path: path.to_owned(),
+                found: versioned.version,
+            });
+        }
+        let checkpoint: Self =
+            serde_json::from_slice(&bytes).map_err(|error| corrupt(error.to_string()))?;
+        let mut seen = HashSet::new();
+        if let Some(item) = checkpoint
+            .items
+            .iter()
+            .find(|item| item.index >= checkpoint.total || !seen.insert(item.index))
+        {
+            return Err(corrupt(format!(
+                "item {} is out of range or repeated",
+                item.index
+            )));
+        }
+        Ok(Some(checkpoint))
+    }
+
+    /// Writes the checkpoint to `path`, through a temporary file so one
+    /// interrupted mid-write leaves the last one whole.
+    pub fn save(&self, path: &Path) -> Result<(), CheckpointError> {
+        let io_error = |error| CheckpointError::Io {
+            path: path.to_owned(),
+            error,
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(io_error)?;
+        }
+        let json = serde_json::to_vec(self).map_err(|error| io_error(io::Error::other(error)))?;
+        let mut temp = OsString::from(path.as_os_str());
+        temp.push(".tmp");
+        fs::write(&temp, json).map_err(io_error)?;
+        fs::rename(&temp, path).map_err(io_error)
+    }
+
+    /// The items already done, if the checkpoint at `path` is for the batch
+    /// with fingerprint `input` and `total` items.
+    pub fn resume(
+        self,
+        path: &Path,
+        input: &str,
+        total: usize,
+    ) -> Result<Vec<BatchItem>, CheckpointError> {
+        if self.input != input || self.total != total {
+            return Err(CheckpointError::Mismatch {
+                path: path.to_owned(),
+            });
+        }
+        Ok(self.items)
+    }
+}
+
+/// Deletes the checkpoint at `path` once its batch is done, if it's there.
+pub fn remove(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Utc};
+
+    use super::*;
+
+    fn item(index: usize, outcome: Result<Option<u64>, String>) -> BatchItem {
+        BatchItem {
+            index,
+            prefix: "F3BBB".to_owned(),
+            checked_at: DateTime::<Utc>::from_timestamp(1_700_000_000, 0).unwrap(),
+            outcome,
+        }
+    }
+
+    fn input() -> String {
+        fingerprint([(0, "F3BBB"), (1, "ABF7A")])
+    }
+
+    #[test]
+    fn a_saved_checkpoint_resumes_its_finished_items() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested/batch-checkpoint.json");
+        let checkpoint = Checkpoint::new(
+            input(),
+            2,
+            [item(0, Ok(Some(3))), item(1, Err("timed out".to_owned()))],
+        );
+        checkpoint.save(&path).unwrap();
+        assert!(!path.with_extension("json.tmp").exists());
+
+        let loaded = Checkpoint::load(&path).unwrap().unwrap();
+        assert_eq!(loaded.version, VERSION);
+        let items = loaded.resume(&path, &input(), 2).unwrap();
+        // The failed one is left to be tried again.
+        assert_eq!(items.len(), 1);
+        assert_eq!((items[0].index, &items[0].outcome), (0, &Ok(Some(3))));
+    }
+
+    #[test]
+    fn no_checkpoint_is_nothing_to_resume() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("batch-checkpoint.json");
+        assert!(Checkpoint::load(&path).unwrap().is_none());
+        remove(&path).unwrap();
+    }
+
+    #[test]
+    fn a_checkpoint_for_other_input_is_a_mismatch() {
+        let path = Path::new("batch-checkpoint.json");
+        let checkpoint = || Checkpoint::new(input(), 2, [item(0, Ok(None))]);
+        let other = fingerprint([(0, "F3BBB"), (1, "00000")]);
+        for (input, total) in [(other.as_str(), 2), (&input(), 3)] {
+            let error = checkpoint().resume(path, input, total).unwrap_err();
+            assert!(matches!(error, CheckpointError::Mismatch { .. }), "{error}");
+            assert_eq!(
+                error.to_string(),
+                "batch-checkpoint.json is for a different input, or the same one checked with \
+                 other settings"
+            );
+        }
+    }
+
+    #[test]
+    fn the_fingerprint_follows_every_prefix_and_its_place() {
+        let base = fingerprint([(0, "F3BBB"), (1, "ABF7A")]);
+        assert_eq!(base, input());
+        assert_ne!(base, fingerprint([(1, "ABF7A"), (0, "F3BBB")]));
+        assert_ne!(base, fingerprint([(0, "F3BBB"), (2, "ABF7A")]));
+        assert_ne!(base, fingerprint([(0, "F3BBB")]));
+        assert_eq!(base.len(), 40);
+    }
+
+    #[test]
+    fn a_checkpoint_holds_prefixes_and_counts_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("batch-checkpoint.json");
+        Checkpoint::new(input(), 2, [item(0, Ok(Some(17_206_891)))])
+            .save(&path)
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+        let keys: Vec<_> = json.as_object().unwrap().keys().cloned().collect();
+        assert_eq!(keys, ["input", "items", "total", "version"]);
+        let item = json["items"][0].as_object().unwrap();
+        let keys: Vec<_> = item.keys().cloned().collect();
+        assert_eq!(keys, ["checked_at", "index", "outcome", "prefix"]);
+        assert_eq!(item["prefix"], "F3BBB");
+    }
+
+    fn load(contents: &str) -> CheckpointError {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("batch-checkpoint.json");
+        fs::write(&path, contents).unwrap();
+        Checkpoint::load(&path).unwrap_err()
+    }
+
+    #[test]
+    fn a_corrupt_checkpoint_says_why() {
+        for contents in [
+            "",
+            "{\"version\": 1, \"input\": \"ab\"",
+            "{\"version\": 1, \"input\": \"ab\", \"total\": 2}",
+            "[1, 2]",
+        ] {
+            let error = load(contents);
+            assert!(
+                matches!(error, CheckpointError::Corrupt { .. }),
+                "{contents}: {error}"
+            );
+        }
+        let item = |index| {
+            format!(
+                "{{\"index\":{index},\"prefix\":\"F3BBB\",\"checked_at\":\"2023-11-14T22:13:20Z\",\
+                 \"outcome\":{{\"Ok\":3}}}}"
+            )
+        };
+        for items in [item(2), format!("{},{}", item(0), item(0))] {
+            let json =
+                format!("{{\"version\":1,\"input\":\"ab\",\"total\":2,\"items\":[{items}]}}");
+            let error = load(&json).to_string();
+            assert!(error.ends_with("is out of range or repeated"), "{error}");
+        }
+    }
+
+    #[test]
+    fn another_version_is_reported_as_such_whatever_its_shape() {
+        let error = load("{\"version\": 2, \"input\": [\"a new shape\"]}");
+        assert!(
+            matches!(error, CheckpointError::Version { found: 2, .. }),
+            "{error}"
+        );
+        assert!(error.to_string().ends_with("(format 2, this one reads 1)"));
+    }
+}
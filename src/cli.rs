@@ -0,0 +1,2127 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::io::{self, IsTerminal, Write};
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::pin::pin;
+use std::sync::{Arc, LazyLock};
+use std::time::{Duration, Instant};
+
+use chrono::{SecondsFormat, Utc};
+use clap::error::ErrorKind;
+use clap::{ArgGroup, CommandFactory, Parser, Subcommand, ValueEnum, ValueHint};
+use clap_complete::Shell;
+use iced::futures::future::{self, FutureExt, LocalBoxFuture, Shared};
+use iced::futures::stream::{self, StreamExt};
+use rustyline::config::Configurer;
+use rustyline::error::ReadlineError;
+use rustyline::history::DefaultHistory;
+use rustyline::{ColorMode, Editor};
+use serde::Serialize;
+use tokio::io::{AsyncBufRead, BufReader};
+use tokio::runtime::Runtime;
+use url::Url;
+use zeroize::Zeroizing;
+
+use cybersec_wow::batch::{self, BatchItem, mask};
+use cybersec_wow::bloom::{self, Filter};
+use cybersec_wow::checkpoint::{self, Checkpoint, CheckpointError};
+use cybersec_wow::download;
+use cybersec_wow::export::Row;
+use cybersec_wow::hash::normalize_hash;
+use cybersec_wow::import::{self, Manager};
+use cybersec_wow::metrics::Metrics;
+use cybersec_wow::packed;
+use cybersec_wow::policy::{self, Rule};
+use cybersec_wow::pwdump;
+use cybersec_wow::pwned::{InFlight, RangeClient, build_client, parse_endpoint, parse_proxy};
+use cybersec_wow::rate_limit::RateLimiter;
+use cybersec_wow::record::CheckRecord;
+use cybersec_wow::summary::{Audit, CountStyle, format_count};
+use cybersec_wow::{BreachResult, HashMode, LookupSource, hash_password, hash_prefix_of};
+
+use crate::records::{MAX_RECORD, RecordError, Records};
+use crate::repl::{self, Command, Line, Masking, Session};
+use crate::strings::Language;
+use crate::watch::{self, State, Watched};
+use crate::{AppSettings, config, notify};
+
+/// Exit codes of a check from the command line.
+const NOT_FOUND: i32 = 0;
+const FOUND: i32 = 1;
+const FAILED: i32 = 2;
+
+/// How often `--checkpoint` is saved while a file is checked.
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// What this run's lookups have counted, for `--stats`.
+static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::default);
+
+/// What every lookup of this run waits on, for `--rate`.
+static LIMITER: LazyLock<RateLimiter> = LazyLock::new(RateLimiter::default);
+
+/// Checks passwords against Pwned Passwords. Without options the app's
+/// window opens.
+#[derive(Debug, Parser)]
+#[command(
+    version,
+    about,
+    args_conflicts_with_subcommands = true,
+    group(ArgGroup::new("input").args([
+        "stdin",
+        "stdin0",
+        "stdin_raw",
+        "file",
+        "hash_file",
+        "sha1",
+        "ntlm"
+    ])),
+    group(ArgGroup::new("list").args(["stdin0", "file"])),
+    group(ArgGroup::new("changes").args(["watch", "state"]).multiple(true)),
+    group(ArgGroup::new("headless").args([
+        "stdin",
+        "stdin0",
+        "stdin_raw",
+        "file",
+        "hash_file",
+        "sha1",
+        "ntlm",
+        "print_config",
+        "build_filter",
+        "pack_dataset",
+        "verify_dataset"
+    ]))
+)]
+pub struct Args {
+    /// Check one password read from standard input instead, print whether
+    /// it was found and exit with 0 if not, 1 if it was, 2 on an error
+    #[arg(long)]
+    stdin: bool,
+    /// Like --stdin, but check every password up to a NUL byte, as
+    /// `find -print0` and `xargs -0` separate them, the way --file does
+    #[arg(long)]
+    stdin0: bool,
+    /// Like --stdin, but all of standard input is the password, trailing
+    /// line ending and all
+    #[arg(long)]
+    stdin_raw: bool,
+    /// Check every line of a file instead, printing each result as it comes
+    /// in and then the totals. Exits with 1 if any was found, otherwise 2 if
+    /// any failed
+    #[arg(long, value_name = "PATH")]
+    file: Option<PathBuf>,
+    /// How many lines of the file, or records of --stdin0, are checked at
+    /// once [default: the batch concurrency in the settings]
+    #[arg(long, value_name = "N", requires = "list", value_parser = config::parse_concurrency)]
+    jobs: Option<usize>,
+    /// Read the file as a password manager's or browser's CSV export,
+    /// checking each entry's password once however many sites share it,
+    /// and printing each by its name and username
+    #[arg(long, value_enum, value_name = "SOURCE", requires = "file")]
+    import: Option<Import>,
+    /// Print each line of the file, or record of --stdin0, as it is, rather
+    /// than masked
+    #[arg(long, requires = "list")]
+    show_plaintext: bool,
+    /// Check every hash in a file instead, one per line and optionally
+    /// followed by :label, printing each result by its line or label as
+    /// --file does. Lines that aren't hashes are reported and skipped. With
+    /// --watch or --state, only those newly found or seen materially more
+    /// often than last time are printed, and the exit code is 1 if any was
+    #[arg(long, value_name = "PATH")]
+    hash_file: Option<PathBuf>,
+    /// Check one SHA-1 hash instead, 40 hexadecimal characters in either
+    /// case, and print and exit as --stdin does
+    #[arg(long, value_name = "HASH", value_parser = parse_sha1)]
+    sha1: Option<String>,
+    /// Check one NTLM hash instead, 32 hexadecimal characters in either
+    /// case, looked up with mode=ntlm
+    #[arg(long, value_name = "HASH", value_parser = parse_ntlm)]
+    ntlm: Option<String>,
+    /// The kind of hash in the hash file
+    #[arg(
+        long,
+        value_enum,
+        value_name = "ALGORITHM",
+        default_value_t,
+        requires = "hash_file"
+    )]
+    hash_format: HashFormat,
+    /// Check the machine accounts of a pwdump file too, those whose name
+    /// ends with $
+    #[arg(long, requires = "hash_file")]
+    include_machine_accounts: bool,
+    /// Check the password history entries of a pwdump file too, such as
+    /// user_history0
+    #[arg(long, requires = "hash_file")]
+    include_history: bool,
+    /// Keep re-checking the hash file every --interval rather than once.
+    /// Lookups that fail are tried again next time
+    #[arg(long, requires = "hash_file", conflicts_with = "format")]
+    watch: bool,
+    /// How long to wait between checks: a number of seconds, minutes, hours
+    /// or days, as in 30m or 24h
+    #[arg(long, value_name = "DURATION", default_value = "24h", requires = "watch", value_parser = watch::parse_interval)]
+    interval: Duration,
+    /// Check the hash file once against the counts found last time, kept
+    /// at PATH, as --watch does every interval [default with --watch:
+    /// beside the hash file, as PATH.state]
+    #[arg(
+        long,
+        value_name = "PATH",
+        requires = "hash_file",
+        conflicts_with = "format"
+    )]
+    state: Option<PathBuf>,
+    /// Show a desktop notification whenever a check finds something
+    #[arg(long, requires = "changes")]
+    notify: bool,
+    /// How results are printed
+    #[arg(long, value_enum, default_value_t, requires = "input")]
+    format: Format,
+    /// Print nothing, warnings included unless RUST_LOG asks for them; the
+    /// exit code is the only answer
+    #[arg(long, requires = "input")]
+    pub quiet: bool,
+    /// Save progress through --file to PATH as it goes, and on a rerun
+    /// with the same file carry on from it rather than start over. It's
+    /// deleted once the whole file is checked
+    #[arg(
+        long,
+        value_name = "PATH",
+        requires = "file",
+        conflicts_with = "import"
+    )]
+    checkpoint: Option<PathBuf>,
+    /// Print how the lookups went to stderr at the end: requests, retries,
+    /// rate limiting, cache hits and latency. With --watch, after every
+    /// check, counted afresh each time
+    #[arg(long, requires = "input")]
+    stats: bool,
+    /// The range API to ask, overriding the settings and $PWCHECK_ENDPOINT
+    #[arg(long, global = true, value_hint = ValueHint::Url, value_parser = parse_endpoint)]
+    endpoint: Option<Url>,
+    /// Seconds to wait for each request, overriding the settings and
+    /// $PWCHECK_TIMEOUT
+    #[arg(long, value_name = "SECS", global = true, value_parser = config::parse_timeout)]
+    timeout: Option<Duration>,
+    /// The most requests a second, decoys and retries included, overriding
+    /// the settings and $PWCHECK_RATE_LIMIT
+    #[arg(long, value_name = "N", global = true, value_parser = config::parse_rate_limit)]
+    rate: Option<u32>,
+    /// The proxy to send lookups through, overriding the settings and
+    /// $PWCHECK_PROXY. There's no option for the API key, which would show
+    /// up in the process list; set $HIBP_API_KEY instead
+    #[arg(long, value_name = "URL", global = true, value_hint = ValueHint::Url, value_parser = parse_proxy)]
+    proxy: Option<Url>,
+    /// Print the settings in effect and where each came from: the
+    /// defaults, the settings file, the environment or these options
+    #[arg(long, conflicts_with_all = ["format", "quiet"])]
+    print_config: bool,
+    /// Build a Bloom filter of the offline dataset at PATH, saved beside it
+    /// as PATH.bloom. Offline lookups and batches consult it first, so a
+    /// hash that certainly isn't in the dataset is answered without
+    /// searching the file or asking the API
+    #[arg(long, value_name = "PATH")]
+    build_filter: Option<PathBuf>,
+    /// The share of hashes not in the dataset that the filter may still
+    /// pass on to be searched for. Lower takes more space: 0.01 is about
+    /// 1.2 bytes a hash, 0.001 about 1.8
+    #[arg(
+        long,
+        value_name = "RATE",
+        default_value_t = bloom::DEFAULT_FALSE_POSITIVE_RATE,
+        requires = "build_filter",
+        value_parser = bloom::parse_false_positive_rate
+    )]
+    false_positive_rate: f64,
+    /// Convert the offline dataset at PATH into a packed copy beside it, as
+    /// PATH.packed: raw digests in checksummed blocks, about half the size,
+    /// that a lookup searches in microseconds. Choose it as the dataset
+    /// file in the settings to use it
+    #[arg(long, value_name = "PATH")]
+    pack_dataset: Option<PathBuf>,
+    /// Check the dataset downloaded to PATH against the manifest beside it,
+    /// without the network, and print which prefixes don't match. Exits
+    /// with 0 if they all do, 2 if not. Downloading again in the window
+    /// fetches only those
+    #[arg(long, value_name = "PATH")]
+    verify_dataset: Option<PathBuf>,
+    /// Log what each lookup does to stderr. RUST_LOG, if set, takes
+    /// precedence
+    #[arg(short, long, global = true)]
+    pub verbose: bool,
+    #[command(subcommand)]
+    command: Option<Commands>,
+}
+
+/// What can be run instead of a check.
+#[derive(Debug, Subcommand)]
+enum Commands {
+    /// Print a completion script for SHELL to standard output, to be
+    /// sourced from its startup file or saved where it looks for them
+    Completions {
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+    /// Check passwords typed at a prompt one after another, until Ctrl+D.
+    /// What's typed isn't shown, and only commands such as `:mode ntlm`
+    /// are kept in the prompt's history; `:help` lists them. Exits as
+    /// --file does
+    Repl,
+}
+
+/// Whose export `--import` reads.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Import {
+    /// Whichever the header row matches
+    Auto,
+    /// name,login_uri,login_username,login_password,...
+    Bitwarden,
+    /// Account,Login Name,Password,... (or KeePassXC's Title,Username,...)
+    Keepass,
+    /// name,url,username,password,note
+    Chrome,
+    /// url,username,password,httpRealm,...
+    Firefox,
+}
+
+impl Import {
+    fn manager(self) -> Option<Manager> {
+        match self {
+            Import::Auto => None,
+            Import::Bitwarden => Some(Manager::Bitwarden),
+            Import::Keepass => Some(Manager::KeePass),
+            Import::Chrome => Some(Manager::Chrome),
+            Import::Firefox => Some(Manager::Firefox),
+        }
+    }
+}
+
+/// The kind of hash `--hash-file` has.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+enum HashFormat {
+    /// 40 hexadecimal characters, as the range API takes by default
+    #[default]
+    Sha1,
+    /// 32 hexadecimal characters, looked up with mode=ntlm
+    Ntlm,
+    /// An Active Directory dump from secretsdump or pwdump, as
+    /// DOMAIN\user:RID:LMHASH:NTHASH:::, checked by NT hash and printed by
+    /// user
+    Pwdump,
+}
+
+impl HashFormat {
+    fn mode(self) -> HashMode {
+        match self {
+            HashFormat::Sha1 => HashMode::Sha1,
+            HashFormat::Ntlm | HashFormat::Pwdump => HashMode::Ntlm,
+        }
+    }
+}
+
+/// How results are printed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+enum Format {
+    /// A sentence per result, then the totals
+    #[default]
+    Plain,
+    /// A check record per line (JSON Lines), then a summary object
+    Json,
+    /// A header row, then a quoted row per result
+    Csv,
+}
+
+impl Args {
+    /// Whether there's something to check here rather than in the window.
+    pub fn is_headless(&self) -> bool {
+        self.stdin
+            || self.stdin0
+            || self.stdin_raw
+            || self.file.is_some()
+            || self.hash_file.is_some()
+            || self.sha1.is_some()
+            || self.ntlm.is_some()
+            || self.print_config
+            || self.build_filter.is_some()
+            || self.pack_dataset.is_some()
+            || self.verify_dataset.is_some()
+            || self.command.is_some()
+    }
+
+    /// Refuses, as clap would, the options for lookups when nothing run
+    /// from the command line would look anything up. They're global so
+    /// that `repl` takes them too, which leaves clap unable to require it.
+    pub fn check_lookup_options(&self) -> Result<(), clap::Error> {
+        let given = [
+            ("--endpoint", self.endpoint.is_some()),
+            ("--timeout", self.timeout.is_some()),
+            ("--rate", self.rate.is_some()),
+            ("--proxy", self.proxy.is_some()),
+        ];
+        match given.into_iter().find(|&(_, given)| given) {
+            Some((option, _)) if !self.is_headless() => Err(Self::command().error(
+                ErrorKind::MissingRequiredArgument,
+                format!("{option} needs a check to apply to, such as --stdin, --file or repl"),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// The hash given with `--sha1` or `--ntlm`, and its mode.
+    fn single_hash(&self) -> Option<(&str, HashMode)> {
+        match (&self.sha1, &self.ntlm) {
+            (Some(hash), _) => Some((hash, HashMode::Sha1)),
+            (_, Some(hash)) => Some((hash, HashMode::Ntlm)),
+            (None, None) => None,
+        }
+    }
+
+    fn overrides(&self) -> config::Overrides {
+        config::Overrides {
+            endpoint: self.endpoint.clone(),
+            timeout: self.timeout,
+            batch_concurrency: self.jobs,
+            rate_limit: self.rate,
+            proxy: self.proxy.as_ref().map(Url::to_string),
+            api_key: None,
+        }
+    }
+
+    fn print_error(&self, error: impl AsRef<str>) {
+        if !self.quiet {
+            eprintln!("error: {}", error.as_ref());
+        }
+    }
+}
+
+/// Runs the check `args` asks for the way the window would, with the saved
+/// settings, and returns the exit code.
+pub fn run(args: &Args) -> i32 {
+    if let Some(Commands::Completions { shell }) = args.command {
+        // Generated whole first, as writing it straight out panics on a
+        // closed pipe.
+        let mut script = Vec::new();
+        clap_complete::generate(
+            shell,
+            &mut Args::command(),
+            env!("CARGO_BIN_NAME"),
+            &mut script,
+        );
+        return match io::stdout().write_all(&script) {
+            Ok(()) => NOT_FOUND,
+            Err(error) => {
+                args.print_error(error.to_string());
+                FAILED
+            }
+        };
+    }
+    if let Some(dataset) = &args.build_filter {
+        return build_filter(args, dataset).unwrap_or_else(|error| {
+            args.print_error(error);
+            FAILED
+        });
+    }
+    if let Some(dataset) = &args.verify_dataset {
+        return match download::verify(dataset) {
+            Ok(verification) => {
+                println!("{}: {verification}", dataset.display());
+                if verification.is_complete() {
+                    NOT_FOUND
+                } else {
+                    FAILED
+                }
+            }
+            Err(error) => {
+                args.print_error(error);
+                FAILED
+            }
+        };
+    }
+    if let Some(dataset) = &args.pack_dataset {
+        return pack_dataset(dataset).unwrap_or_else(|error| {
+            args.print_error(error);
+            FAILED
+        });
+    }
+    let settings = match settings(args) {
+        Ok(loaded) if args.print_config => {
+            print!("{}", loaded.describe());
+            return NOT_FOUND;
+        }
+        loaded => loaded.map(|loaded| loaded.settings),
+    };
+    let runtime = tokio::runtime::Runtime::new();
+    let code = match (settings, runtime) {
+        (Ok(settings), Ok(runtime)) if matches!(args.command, Some(Commands::Repl)) => {
+            repl(&runtime, &settings)
+        }
+        (Ok(settings), Ok(runtime)) if let Some(path) = &args.hash_file => {
+            if args.watch || args.state.is_some() {
+                runtime.block_on(check_hashes(args, &settings, path))
+            } else {
+                runtime.block_on(check_hash_list(args, &settings, path))
+            }
+        }
+        (Ok(settings), Ok(runtime)) => match &args.file {
+            Some(path) if let Some(import) = args.import => {
+                runtime.block_on(check_import(args, &settings, path, import.manager()))
+            }
+            Some(path) => runtime.block_on(check_file(args, &settings, path)),
+            None if args.stdin0 => runtime.block_on(check_records(
+                args,
+                &settings,
+                BufReader::new(tokio::io::stdin()),
+                b'\0',
+                None,
+            )),
+            None if let Some((hash, mode)) = args.single_hash() => {
+                runtime.block_on(check_hash(args, &settings, hash, mode))
+            }
+            None => runtime.block_on(check_stdin(args, &settings)),
+        },
+        (Err(error), _) => Err(error),
+        (_, Err(error)) => Err(error.to_string()),
+    };
+    // Watching prints them after every check instead.
+    if args.stats && !args.watch {
+        eprintln!("{}", METRICS.snapshot());
+    }
+    code.unwrap_or_else(|error| {
+        args.print_error(error);
+        FAILED
+    })
+}
+
+/// The settings in effect for `args`. An environment variable set to
+/// something unusable is an error here, rather than skipped as in the
+/// window, so a script doesn't check against settings it didn't ask for.
+fn settings(args: &Args) -> Result<config::Layered, String> {
+    let loaded = AppSettings::load(&args.overrides());
+    // Without a config directory, nothing is lost by a check here.
+    if let Some(notice) = &loaded.notice
+        && !matches!(notice, config::ConfigError::NoDir)
+    {
+        tracing::warn!("{notice}");
+    }
+    match loaded.invalid.first() {
+        Some((_, error)) => Err(error.clone()),
+        None => Ok(loaded),
+    }
+}
+
+/// Builds the filter `--build-filter` asks for, showing how far it's got on
+/// a terminal, and prints what's in it.
+fn build_filter(args: &Args, dataset: &Path) -> Result<i32, String> {
+    let output = bloom::path_for(dataset);
+    let terminal = io::stderr().is_terminal();
+    let header = bloom::build(dataset, &output, args.false_positive_rate, |read, len| {
+        if terminal {
+            eprint!("\rbuilding the filter: {}%", read * 100 / len.max(1));
+        }
+    })
+    .map_err(|error| error.to_string())?;
+    if terminal {
+        eprintln!();
+    }
+    println!(
+        "{}: {} {} hashes in {} bytes, {} bits set per hash, {:.3}% false positives",
+        output.display(),
+        header.entries,
+        header.mode,
+        header.bits.div_ceil(8),
+        header.hashes,
+        header.expected_false_positive_rate() * 100.0
+    );
+    Ok(NOT_FOUND)
+}
+
+/// Packs the dataset `--pack-dataset` names, showing how far it's got on a
+/// terminal, and prints how it came out.
+fn pack_dataset(dataset: &Path) -> Result<i32, String> {
+    let output = packed::path_for(dataset);
+    let terminal = io::stderr().is_terminal();
+    let header = packed::pack(dataset, &output, |read, len| {
+        if terminal {
+            eprint!("\rpacking the dataset: {}%", read * 100 / len.max(1));
+        }
+    })
+    .map_err(|error| error.to_string())?;
+    if terminal {
+        eprintln!();
+    }
+    let size = |path: &Path| std::fs::metadata(path).map_or(0, |metadata| metadata.len());
+    println!(
+        "{}: {} {} hashes in {} bytes, {:.0}% of the text",
+        output.display(),
+        header.entries,
+        header.mode,
+        size(&output),
+        size(&output) as f64 * 100.0 / size(dataset).max(1) as f64
+    );
+    Ok(NOT_FOUND)
+}
+
+/// The offline dataset's Bloom filter for a batch to rule hashes out with,
+/// if one has been built. One that can't be used is warned about and the
+/// batch asks the API for everything.
+fn dataset_filter(args: &Args, settings: &AppSettings) -> Option<Arc<Filter>> {
+    settings.dataset_filter().unwrap_or_else(|error| {
+        if !args.quiet {
+            eprintln!("warning: not using the dataset filter: {error}");
+        }
+        None
+    })
+}
+
+/// A `--sha1` hash, uppercased, or what it should have been.
+fn parse_sha1(hash: &str) -> Result<String, String> {
+    normalize_hash(hash, HashMode::Sha1)
+}
+
+/// A `--ntlm` hash, uppercased, or what it should have been.
+fn parse_ntlm(hash: &str) -> Result<String, String> {
+    normalize_hash(hash, HashMode::Ntlm)
+}
+
+fn range_client(settings: &AppSettings) -> Result<RangeClient, String> {
+    let network = settings.network();
+    Ok(RangeClient::new(
+        build_client(&network)?,
+        network,
+        InFlight::default(),
+        METRICS.clone(),
+        LIMITER.clone(),
+    ))
+}
+
+/// Looks up one password, hidden among decoys if the settings say so.
+async fn lookup(
+    client: &RangeClient,
+    password: &str,
+    settings: &AppSettings,
+) -> Result<CheckRecord, String> {
+    let hash = hash_password(password, HashMode::Sha1, settings.normalization);
+    lookup_hash(client, hash, HashMode::Sha1, settings).await
+}
+
+/// Looks up a password by its `mode` hash, as [`lookup`] does.
+async fn lookup_hash(
+    client: &RangeClient,
+    hash: String,
+    mode: HashMode,
+    settings: &AppSettings,
+) -> Result<CheckRecord, String> {
+    let breach = client
+        .with_decoys(mode, client.clone().search(hash.clone(), mode))
+        .await
+        .map_err(|error| error.message)?;
+    Ok(CheckRecord::new(
+        &hash,
+        mode,
+        breach,
+        LookupSource::Network,
+        &settings.severity,
+    ))
+}
+
+/// One checked password, as it's printed.
+struct Checked<'a> {
+    /// The line of the file, or record of standard input, it came from.
+    line: Option<usize>,
+    /// The entry's name and username, for an imported export.
+    label: Option<&'a str>,
+    /// How many sites the password is used on, if more than one.
+    reused: Option<usize>,
+    /// Whether `reused` counts the user accounts of a directory dump.
+    accounts: bool,
+    preview: Option<&'a str>,
+    /// Only with `--show-plaintext`.
+    plaintext: Option<&'a str>,
+    /// The rules of the policy it doesn't meet, if the settings set one and
+    /// there's a password to judge.
+    policy: Option<&'a [Rule]>,
+    result: &'a Result<CheckRecord, String>,
+}
+
+/// Totals over a file, for the last line.
+#[derive(Debug, Default, Serialize)]
+struct Totals {
+    total: usize,
+    breached: usize,
+    clean: usize,
+    errors: usize,
+    /// Lines whose password is the same as an earlier line's.
+    #[serde(skip_serializing_if = "is_zero")]
+    reused: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    worst: Option<Worst>,
+    /// Over the lines that were looked up.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    audit: Option<Audit>,
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
+/// An import's worst finding: the breached password used on the most
+/// sites, or failing that the most seen.
+#[derive(Debug, Serialize)]
+struct Worst {
+    count: u64,
+    sites: Vec<String>,
+}
+
+impl Worst {
+    /// The worst of `groups` of `batch`'s lines, by the count each was
+    /// found, as [`import::worst`] picks it.
+    fn of(batch: &batch::Lines, groups: &[Vec<usize>], counts: &[Option<u64>]) -> Option<Self> {
+        let index = import::worst(groups, |index| counts[index])?;
+        Some(Self {
+            count: counts[index].unwrap_or_default(),
+            sites: groups[index]
+                .iter()
+                .map(|&line| match &batch.lines[line] {
+                    line if line.label.is_empty() => format!("line {}", line.number),
+                    line => line.label.clone(),
+                })
+                .collect(),
+        })
+    }
+}
+
+impl Totals {
+    fn add(&mut self, result: &Result<CheckRecord, String>) {
+        self.total += 1;
+        match result.as_ref().map(|record| record.result) {
+            Ok(BreachResult::Found { .. }) => self.breached += 1,
+            Ok(BreachResult::NotFound) => self.clean += 1,
+            Err(_) => self.errors += 1,
+        }
+    }
+}
+
+/// Standard output in the format asked for, written a result at a time
+/// and flushed after each, so a pipe sees results as they come.
+enum Output {
+    Quiet,
+    Plain(io::Stdout),
+    Json(io::Stdout),
+    /// With a policy column if it's `true`, as a batch export has.
+    Csv(Box<csv::Writer<io::Stdout>>, bool),
+}
+
+impl Output {
+    /// Output whose CSV has a policy column if `policy` is set.
+    fn new(args: &Args, policy: bool) -> io::Result<Self> {
+        if args.quiet {
+            return Ok(Output::Quiet);
+        }
+        Ok(match args.format {
+            Format::Plain => Output::Plain(io::stdout()),
+            Format::Json => Output::Json(io::stdout()),
+            Format::Csv => {
+                let mut csv = csv::WriterBuilder::new()
+                    .quote_style(csv::QuoteStyle::Always)
+                    .from_writer(io::stdout());
+                let (header, plaintext) = CSV_HEADER.split_at(CSV_HEADER.len() - 1);
+                let policy = policy.then_some("policy");
+                csv.write_record(
+                    header
+                        .iter()
+                        .copied()
+                        .chain(policy)
+                        .chain(plaintext.to_vec()),
+                )?;
+                csv.flush()?;
+                Output::Csv(Box::new(csv), policy.is_some())
+            }
+        })
+    }
+
+    fn check(&mut self, checked: &Checked) -> io::Result<()> {
+        match self {
+            Output::Quiet => Ok(()),
+            Output::Plain(stdout) => {
+                let mut verdict = match checked.result {
+                    Ok(record) => match record.result {
+                        BreachResult::Found { count } => {
+                            format!("found {} times", format_count(count, CountStyle::Full, ','))
+                        }
+                        BreachResult::NotFound => "not found".to_owned(),
+                    },
+                    Err(error) => format!("error: {error}"),
+                };
+                match checked.reused {
+                    Some(users) if checked.accounts => {
+                        verdict.push_str(&format!(", shared by {users} users"));
+                    }
+                    Some(sites) => verdict.push_str(&format!(", reused on {sites} sites")),
+                    None => {}
+                }
+                let policy = checked.policy.map(policy::describe);
+                let shown = checked.plaintext.or(checked.preview).unwrap_or_default();
+                let line = match (checked.label, checked.line) {
+                    (Some(label), _) if !label.is_empty() => format!("{label}\t{shown}\t{verdict}"),
+                    (_, Some(line)) => format!("{line}\t{shown}\t{verdict}"),
+                    (_, None) => {
+                        return match policy {
+                            Some(policy) => writeln!(stdout, "{verdict}; {policy}"),
+                            None => writeln!(stdout, "{verdict}"),
+                        };
+                    }
+                };
+                match policy {
+                    Some(policy) => writeln!(stdout, "{line}\t{policy}"),
+                    None => writeln!(stdout, "{line}"),
+                }
+            }
+            Output::Json(stdout) => {
+                write_json_line(&mut *stdout, checked)?;
+                stdout.flush()
+            }
+            Output::Csv(csv, policy) => {
+                write_csv_row(csv, checked, *policy)?;
+                csv.flush()
+            }
+        }
+    }
+
+    fn totals(&mut self, totals: &Totals) -> io::Result<()> {
+        match self {
+            Output::Quiet | Output::Csv(..) => Ok(()),
+            Output::Plain(stdout) => {
+                writeln!(
+                    stdout,
+                    "{} checked: {} breached, {} clean, {} errors",
+                    totals.total, totals.breached, totals.clean, totals.errors
+                )?;
+                if totals.reused > 0 {
+                    writeln!(
+                        stdout,
+                        "{} lines reuse the password of an earlier one",
+                        totals.reused
+                    )?;
+                }
+                if let Some(worst) = &totals.worst {
+                    writeln!(
+                        stdout,
+                        "worst finding: seen {} times, on {}",
+                        format_count(worst.count, CountStyle::Full, ','),
+                        worst.sites.join(", ")
+                    )?;
+                }
+                for line in totals.audit.iter().flat_map(|audit| audit.lines(',')) {
+                    writeln!(stdout, "{line}")?;
+                }
+                Ok(())
+            }
+            Output::Json(stdout) => {
+                #[derive(Serialize)]
+                struct Summary<'a> {
+                    summary: &'a Totals,
+                }
+                serde_json::to_writer(&mut *stdout, &Summary { summary: totals })?;
+                writeln!(stdout)
+            }
+        }
+    }
+}
+
+/// Writes `checked` as one line of JSON: the [`CheckRecord`] that GUI
+/// exports write, with the line and preview ahead of it for a file (and the
+/// label for an import), or just an `error` if the lookup failed. With a
+/// policy, `policy_failures` lists the rules it doesn't meet, as a batch
+/// export does:
+///
+/// ```json
+/// {"line":3,"preview":"p*******","prefix":"5BAA6","algorithm":"sha1","result":{"status":"found","count":52256179},...}
+/// {"line":4,"preview":"h******","error":"request timed out"}
+/// {"line":7,"label":"Mail (ann@example.com)","preview":"h******",...}
+/// {"line":9,"preview":"w*****",...,"policy_failures":[{"min_length":12},"username"]}
+/// ```
+fn write_json_line(mut writer: impl Write, checked: &Checked) -> io::Result<()> {
+    #[derive(Serialize)]
+    struct Line<'a> {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        line: Option<usize>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        label: Option<&'a str>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reused: Option<usize>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        preview: Option<&'a str>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        plaintext: Option<&'a str>,
+        #[serde(flatten)]
+        record: Option<&'a CheckRecord>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<&'a str>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        policy_failures: Option<&'a [Rule]>,
+    }
+
+    let line = Line {
+        line: checked.line,
+        label: checked.label,
+        reused: checked.reused,
+        preview: checked.preview,
+        plaintext: checked.plaintext,
+        record: checked.result.as_ref().ok(),
+        error: checked.result.as_ref().err().map(String::as_str),
+        policy_failures: checked.policy,
+    };
+    serde_json::to_writer(&mut writer, &line)?;
+    writeln!(writer)
+}
+
+const CSV_HEADER: [&str; 14] = [
+    "line",
+    "label",
+    "reused",
+    "preview",
+    "prefix",
+    "algorithm",
+    "found",
+    "count",
+    "severity",
+    "checked_at",
+    "source",
+    "snapshot",
+    "error",
+    "plaintext",
+];
+
+/// Writes `checked` as a CSV row of the [`CheckRecord`] fields, flattened
+/// to the columns of [`CSV_HEADER`], with the policy ahead of the plaintext
+/// if there's a `policy` column. A failed lookup only has its line, label,
+/// reuse, preview and error.
+fn write_csv_row(
+    csv: &mut csv::Writer<impl Write>,
+    checked: &Checked,
+    policy: bool,
+) -> csv::Result<()> {
+    let mut row: [String; CSV_HEADER.len() - 1] = Default::default();
+    row[0] = checked
+        .line
+        .map(|line| line.to_string())
+        .unwrap_or_default();
+    row[1] = checked.label.unwrap_or_default().to_owned();
+    row[2] = checked
+        .reused
+        .map(|sites| sites.to_string())
+        .unwrap_or_default();
+    row[3] = checked.preview.unwrap_or_default().to_owned();
+    match checked.result {
+        Ok(record) => {
+            row[4] = record.prefix.clone();
+            row[5] = record.algorithm.to_string();
+            (row[6], row[7]) = match record.result {
+                BreachResult::Found { count } => ("true".to_owned(), count.to_string()),
+                BreachResult::NotFound => ("false".to_owned(), "0".to_owned()),
+            };
+            row[8] = record.severity.map(|s| s.to_string()).unwrap_or_default();
+            row[9] = record.checked_at.to_rfc3339_opts(SecondsFormat::Secs, true);
+            (row[10], row[11]) = match record.source {
+                LookupSource::Network => ("network".to_owned(), String::new()),
+                LookupSource::Memory => ("memory".to_owned(), String::new()),
+                LookupSource::Disk => ("disk".to_owned(), String::new()),
+                LookupSource::Offline(date) => ("offline".to_owned(), date.to_string()),
+            };
+        }
+        Err(error) => row[12] = error.clone(),
+    }
+    // Borrowed straight from the password rather than copied into `row`.
+    let plaintext = checked.plaintext.unwrap_or_default();
+    let policy = policy.then(|| checked.policy.map(policy::describe).unwrap_or_default());
+    csv.write_record(
+        row.iter()
+            .map(String::as_str)
+            .chain(policy.as_deref())
+            .chain([plaintext]),
+    )
+}
+
+async fn check_stdin(args: &Args, settings: &AppSettings) -> Result<i32, String> {
+    let password = read_password(args.stdin_raw).await?;
+    let client = range_client(settings)?;
+    let result = lookup(&client, &password, settings).await;
+    let policy = policy_failures(settings, &password, None);
+    print_single(args, &result, policy.as_deref())
+}
+
+/// Checks the one hash given with `--sha1` or `--ntlm`, already validated
+/// and uppercased, as [`check_stdin`] does a password.
+async fn check_hash(
+    args: &Args,
+    settings: &AppSettings,
+    hash: &str,
+    mode: HashMode,
+) -> Result<i32, String> {
+    let client = range_client(settings)?;
+    let result = lookup_hash(&client, hash.to_owned(), mode, settings).await;
+    print_single(args, &result, None)
+}
+
+/// Prints the result of a single check, and returns the code to exit with.
+fn print_single(
+    args: &Args,
+    result: &Result<CheckRecord, String>,
+    policy: Option<&[Rule]>,
+) -> Result<i32, String> {
+    let code = match result {
+        Ok(record) if matches!(record.result, BreachResult::Found { .. }) => FOUND,
+        Ok(_) => NOT_FOUND,
+        // In plain text the error goes to standard error, like any other;
+        // the structured formats carry it in their record.
+        Err(error) if args.format == Format::Plain => return Err(error.clone()),
+        Err(_) => FAILED,
+    };
+    Output::new(args, policy.is_some())
+        .and_then(|mut output| {
+            output.check(&Checked {
+                line: None,
+                label: None,
+                reused: None,
+                accounts: false,
+                preview: None,
+                plaintext: None,
+                policy,
+                result,
+            })
+        })
+        .map_err(|error| error.to_string())?;
+    Ok(code)
+}
+
+/// Checks passwords typed at a prompt until Ctrl+D or `:quit`, with the
+/// hash mode and padding the session's commands set.
+fn repl(runtime: &Runtime, settings: &AppSettings) -> Result<i32, String> {
+    let mut settings = settings.clone();
+    let mut session = Session::new(settings.padding);
+    let mut client = range_client(&settings)?;
+    let mut editor = Editor::<Masking, DefaultHistory>::new().map_err(|error| error.to_string())?;
+    editor.set_helper(Some(Masking::default()));
+    // Or a terminal without colour would draw what's typed after all.
+    editor.set_color_mode(ColorMode::Forced);
+    let color = io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+    println!("Type a password to check it, :help for commands, Ctrl+D to leave");
+    loop {
+        let line = match prompt(&mut editor, &session.prompt(), false) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(error) => return Err(error.to_string()),
+        };
+        let _ = repl::remember(editor.history_mut(), &line);
+        let password = match repl::parse(&line) {
+            Ok(Line::Blank) => continue,
+            Ok(Line::Password(password)) => Zeroizing::new(password.to_owned()),
+            Ok(Line::Command(command)) => match command {
+                Command::Check => match prompt(&mut editor, &session.prompt(), true) {
+                    Ok(line) if line.is_empty() => continue,
+                    Ok(line) => line,
+                    Err(ReadlineError::Interrupted) => continue,
+                    Err(ReadlineError::Eof) => break,
+                    Err(error) => return Err(error.to_string()),
+                },
+                Command::Stats => {
+                    println!("{}\n{}", session.tally, METRICS.snapshot());
+                    continue;
+                }
+                Command::Clear => {
+                    print!("{}", repl::CLEAR);
+                    io::stdout().flush().map_err(|error| error.to_string())?;
+                    continue;
+                }
+                Command::Help => {
+                    println!("{}", repl::HELP);
+                    continue;
+                }
+                Command::Quit => break,
+                Command::Mode(_) | Command::Padding(_) => {
+                    if let Some(set) = session.apply(command) {
+                        println!("{set}");
+                    }
+                    if session.padding != settings.padding {
+                        settings.padding = session.padding;
+                        client = range_client(&settings)?;
+                    }
+                    continue;
+                }
+            },
+            Err(error) => {
+                eprintln!("error: {error}");
+                continue;
+            }
+        };
+        let hash = hash_password(&password, session.mode, settings.normalization);
+        let result = runtime.block_on(lookup_hash(&client, hash, session.mode, &settings));
+        session.record(&result);
+        match policy_failures(&settings, &password, None) {
+            Some(failed) => println!(
+                "{}; {}",
+                repl::verdict(&result, color),
+                policy::describe(&failed)
+            ),
+            None => println!("{}", repl::verdict(&result, color)),
+        }
+    }
+    let tally = session.tally;
+    Ok(if tally.found > 0 {
+        FOUND
+    } else if tally.failed > 0 {
+        FAILED
+    } else {
+        NOT_FOUND
+    })
+}
+
+/// The rules of the settings' policy that `password` doesn't meet, or
+/// `None` without one.
+fn policy_failures(
+    settings: &AppSettings,
+    password: &str,
+    username: Option<&str>,
+) -> Option<Vec<Rule>> {
+    let policy = settings.policy.as_ref()?;
+    Some(
+        policy::failures(&policy.check(password, username))
+            .cloned()
+            .collect(),
+    )
+}
+
+/// Reads a line at the prompt without showing it, or only if it's a command
+/// and not `hide_commands`.
+fn prompt(
+    editor: &mut Editor<Masking, DefaultHistory>,
+    prompt: &str,
+    hide_commands: bool,
+) -> rustyline::Result<Zeroizing<String>> {
+    if let Some(masking) = editor.helper_mut() {
+        masking.hide_commands = hide_commands;
+    }
+    let _cursor = editor.set_cursor_visibility(false)?;
+    editor.readline(prompt).map(Zeroizing::new)
+}
+
+/// The password from standard input. Piped in, one trailing line ending is
+/// dropped, as `echo` adds, unless it's `raw`; from a terminal it's asked
+/// for without echo. More than one line is refused unless it's `raw`, as
+/// it's more likely a list than one password.
+async fn read_password(raw: bool) -> Result<Zeroizing<String>, String> {
+    let mut password = if io::stdin().is_terminal() {
+        Zeroizing::new(rpassword::prompt_password("Password: ").map_err(|error| error.to_string())?)
+    } else {
+        let mut records = Records::new(BufReader::new(tokio::io::stdin()), None);
+        let mut bytes = match records.next().await {
+            Some(Ok(bytes)) => bytes,
+            Some(Err(RecordError::TooLong)) => {
+                return Err(format!(
+                    "the password is longer than the {MAX_RECORD} bytes allowed"
+                ));
+            }
+            Some(Err(RecordError::Io(error))) => return Err(error.to_string()),
+            None => Zeroizing::new(Vec::new()),
+        };
+        // Moved rather than copied, and wiped on the way out if it won't do.
+        let text = String::from_utf8(mem::take(&mut *bytes)).map_err(|error| {
+            drop(Zeroizing::new(error.into_bytes()));
+            "the password isn't UTF-8"
+        })?;
+        Zeroizing::new(text)
+    };
+    if !raw && password.ends_with('\n') {
+        password.pop();
+        if password.ends_with('\r') {
+            password.pop();
+        }
+    }
+    if password.is_empty() {
+        return Err("no password on standard input".to_owned());
+    }
+    if !raw && password.contains('\n') {
+        return Err(
+            "standard input has more than one line; use --file or --stdin0 to check a list, \
+             or --stdin-raw if the line breaks are part of the password"
+                .to_owned(),
+        );
+    }
+    Ok(password)
+}
+
+/// Checks each non-blank line of the file at `path`, `--jobs` at a time,
+/// resuming from `--checkpoint` if it's for this file.
+async fn check_file(args: &Args, settings: &AppSettings, path: &Path) -> Result<i32, String> {
+    let open = || async {
+        tokio::fs::File::open(path)
+            .await
+            .map(BufReader::new)
+            .map_err(|error| format!("{}: {error}", path.display()))
+    };
+    let resume = match &args.checkpoint {
+        Some(checkpoint) => Some(Resume::load(checkpoint, open().await?, args, settings).await?),
+        None => None,
+    };
+    check_records(args, settings, open().await?, b'\n', resume).await
+}
+
+/// Where a file's checkpoint is kept, and what it had done.
+struct Resume {
+    path: PathBuf,
+    fingerprint: String,
+    /// How many records the file has.
+    total: usize,
+    /// By record number; the checkpoint counts from 0.
+    done: HashMap<usize, BatchItem>,
+}
+
+impl Resume {
+    /// Reads the file through once for its fingerprint, then the checkpoint
+    /// at `path`. One for another file is an error rather than overwritten;
+    /// one that can't be read is, with a warning, since it's no use anyway.
+    async fn load(
+        path: &Path,
+        reader: impl AsyncBufRead + Unpin,
+        args: &Args,
+        settings: &AppSettings,
+    ) -> Result<Self, String> {
+        let mut records = Records::new(reader, Some(b'\n'));
+        let mut prefixes = Vec::new();
+        let mut total = 0;
+        while let Some(record) = records.next().await {
+            total += 1;
+            if let Some(Ok(password)) = decode(total, record, true) {
+                let hash = hash_password(&password, HashMode::Sha1, settings.normalization);
+                prefixes.push((total, hash_prefix_of(&hash).to_owned()));
+            }
+        }
+        let fingerprint = checkpoint::fingerprint(
+            prefixes
+                .iter()
+                .map(|(number, prefix)| (*number, prefix.as_str())),
+        );
+        let done = match Checkpoint::load(path) {
+            Ok(Some(checkpoint)) => checkpoint.resume(path, &fingerprint, total),
+            Ok(None) => Ok(Vec::new()),
+            Err(error @ (CheckpointError::Corrupt { .. } | CheckpointError::Version { .. })) => {
+                if !args.quiet {
+                    eprintln!("warning: {error}, so the file is checked from the start");
+                }
+                Ok(Vec::new())
+            }
+            Err(error) => Err(error),
+        }
+        .map_err(|error| error.to_string())?;
+        if !done.is_empty() && !args.quiet {
+            eprintln!(
+                "resuming from {}: {} of {total} lines already checked",
+                path.display(),
+                done.len()
+            );
+        }
+        Ok(Self {
+            path: path.to_owned(),
+            fingerprint,
+            total,
+            done: done
+                .into_iter()
+                .map(|item| (item.index + 1, item))
+                .collect(),
+        })
+    }
+
+    fn save(&self, finished: &[BatchItem]) {
+        let checkpoint = Checkpoint::new(
+            self.fingerprint.clone(),
+            self.total,
+            finished.iter().cloned(),
+        );
+        if let Err(error) = checkpoint.save(&self.path) {
+            tracing::warn!("could not save the checkpoint: {error}");
+        }
+    }
+}
+
+/// A record as it's checked, numbered from 1, or `None` for an empty one.
+/// Split at line breaks, its trailing `\r` and the first one's byte order mark
+/// are dropped; at any other delimiter it's taken as it is.
+fn decode(
+    number: usize,
+    record: Result<Zeroizing<Vec<u8>>, RecordError>,
+    lines: bool,
+) -> Option<Result<Zeroizing<String>, String>> {
+    let what = if lines { "line" } else { "record" };
+    let record = match record {
+        Ok(record) => record,
+        Err(RecordError::TooLong) => {
+            return Some(Err(format!(
+                "the {what} is longer than the {MAX_RECORD} bytes a password may be"
+            )));
+        }
+        Err(RecordError::Io(error)) => return Some(Err(error.to_string())),
+    };
+    let mut record = &record[..];
+    if lines {
+        record = record.strip_suffix(b"\r").unwrap_or(record);
+        if number == 1 {
+            record = record.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(record);
+        }
+    }
+    match std::str::from_utf8(record) {
+        Ok("") => None,
+        Ok(record) => Some(Ok(Zeroizing::new(record.to_owned()))),
+        Err(_) => Some(Err(format!("the {what} isn't UTF-8"))),
+    }
+}
+
+/// A result as a checkpoint keeps it, for the record `number`.
+fn checkpoint_item(number: usize, record: &CheckRecord) -> BatchItem {
+    BatchItem {
+        index: number - 1,
+        prefix: record.prefix.clone(),
+        checked_at: record.checked_at,
+        outcome: Ok(match record.result {
+            BreachResult::Found { count } => Some(count),
+            BreachResult::NotFound => None,
+        }),
+    }
+}
+
+/// The result of a batch item for a `mode` hash, or one resumed from a
+/// checkpoint, as it was when checked.
+fn batch_record(
+    item: &BatchItem,
+    mode: HashMode,
+    settings: &AppSettings,
+) -> Result<CheckRecord, String> {
+    let result = BreachResult::from_count(item.outcome.clone()?);
+    Ok(CheckRecord {
+        prefix: item.prefix.clone(),
+        algorithm: mode,
+        result,
+        severity: result.severity(&settings.severity),
+        checked_at: item.checked_at,
+        source: LookupSource::Network,
+    })
+}
+
+/// A looked-up line as a batch export has it, for the [`Audit`] at the
+/// end. It has no preview, as the audit never shows one.
+fn audit_row(
+    line: usize,
+    label: &str,
+    password: usize,
+    length: Option<usize>,
+    mode: HashMode,
+    policy: Option<Vec<Rule>>,
+    result: &Result<CheckRecord, String>,
+) -> Row {
+    let record = result.as_ref().ok();
+    Row {
+        line,
+        label: label.to_owned(),
+        preview: String::new(),
+        password,
+        length,
+        prefix: record
+            .map(|record| record.prefix.clone())
+            .unwrap_or_default(),
+        algorithm: mode,
+        outcome: result
+            .as_ref()
+            .map(|record| match record.result {
+                BreachResult::Found { count } => Some(count),
+                BreachResult::NotFound => None,
+            })
+            .map_err(String::clone),
+        severity: record.and_then(|record| record.severity),
+        checked_at: record.map_or_else(Utc::now, |record| record.checked_at),
+        policy,
+        plaintext: None,
+    }
+}
+
+/// Checks each non-empty record of `reader`, ended by `delimiter`, `--jobs`
+/// at a time. Records are read as they're needed, so the input is never
+/// held in memory, and each result is printed as it arrives, numbered since
+/// they can come back out of order. Records are [`decode`]d as passwords.
+///
+/// With a checkpoint to `resume`, the records it has are printed as they
+/// were without being looked up again, and progress is saved as it goes;
+/// the checkpoint is deleted once every record has been printed.
+async fn check_records(
+    args: &Args,
+    settings: &AppSettings,
+    reader: impl AsyncBufRead + Unpin,
+    delimiter: u8,
+    resume: Option<Resume>,
+) -> Result<i32, String> {
+    let client = range_client(settings)?;
+    let filter = dataset_filter(args, settings);
+    let jobs = settings.batch_concurrency;
+    let mut output =
+        Output::new(args, settings.policy.is_some()).map_err(|error| error.to_string())?;
+    let lines = delimiter == b'\n';
+    let (resume, filter) = (&resume, &filter);
+
+    let records = Records::new(reader, Some(delimiter));
+    let records = stream::unfold((records, 0), |(mut records, number)| async move {
+        let record = records.next().await?;
+        Some(((number + 1, record), (records, number + 1)))
+    });
+    // Lookups by hash, so a password that's on many lines is looked up
+    // once, even when its lines are in flight together, and each gets the
+    // one result, failure included.
+    // Numbered as they're first seen, for the audit to tell them apart.
+    type Lookup<'a> = (
+        usize,
+        Shared<LocalBoxFuture<'a, Result<CheckRecord, String>>>,
+    );
+    let lookups: RefCell<HashMap<String, Lookup>> = RefCell::new(HashMap::new());
+    let results = records
+        .filter_map(|(number, record)| async move {
+            decode(number, record, lines).map(|line| (number, line))
+        })
+        .map(|(number, line)| {
+            let (client, lookups) = (&client, &lookups);
+            async move {
+                let password = match line {
+                    Ok(password) => password,
+                    Err(error) => {
+                        return (number, String::new(), None, Err(error), None, None, false);
+                    }
+                };
+                let preview = mask(&password);
+                let hash = hash_password(&password, HashMode::Sha1, settings.normalization);
+                let distinct = lookups.borrow().len();
+                let ((index, shared), reused) = match lookups.borrow_mut().entry(hash) {
+                    Entry::Occupied(entry) => (entry.get().clone(), true),
+                    Entry::Vacant(entry) => {
+                        let done = resume.as_ref().and_then(|resume| resume.done.get(&number));
+                        let ruled_out = filter
+                            .as_ref()
+                            .is_some_and(|filter| !filter.might_contain(entry.key()));
+                        let result = match done {
+                            Some(item) => {
+                                future::ready(batch_record(item, HashMode::Sha1, settings))
+                                    .boxed_local()
+                            }
+                            None if ruled_out => future::ready(Ok(CheckRecord::new(
+                                entry.key(),
+                                HashMode::Sha1,
+                                BreachResult::NotFound,
+                                LookupSource::Network,
+                                &settings.severity,
+                            )))
+                            .boxed_local(),
+                            None => {
+                                lookup_hash(client, entry.key().clone(), HashMode::Sha1, settings)
+                                    .boxed_local()
+                            }
+                        };
+                        (entry.insert((distinct, result.shared())).clone(), false)
+                    }
+                };
+                let result = shared.await;
+                let audited = (index, password.chars().count());
+                let policy = policy_failures(settings, &password, None);
+                let plaintext = args.show_plaintext.then_some(password);
+                (
+                    number,
+                    preview,
+                    plaintext,
+                    result,
+                    Some(audited),
+                    policy,
+                    reused,
+                )
+            }
+        })
+        .buffer_unordered(jobs);
+    let mut results = pin!(results);
+
+    let mut totals = Totals::default();
+    let mut rows = Vec::new();
+    let mut finished = Vec::new();
+    let mut failed_lookups = false;
+    let mut saved = Instant::now();
+    while let Some((number, preview, plaintext, result, audited, policy, reused)) =
+        results.next().await
+    {
+        totals.add(&result);
+        totals.reused += usize::from(reused);
+        if let Some((password, length)) = audited {
+            rows.push(audit_row(
+                number,
+                "",
+                password,
+                Some(length),
+                HashMode::Sha1,
+                policy.clone(),
+                &result,
+            ));
+        }
+        if let Some(resume) = resume {
+            match &result {
+                Ok(record) => finished.push(checkpoint_item(number, record)),
+                // Records that aren't passwords have no preview, and would
+                // fail again.
+                Err(_) => failed_lookups |= !preview.is_empty(),
+            }
+            if saved.elapsed() >= CHECKPOINT_INTERVAL {
+                resume.save(&finished);
+                saved = Instant::now();
+            }
+        }
+        output
+            .check(&Checked {
+                line: Some(number),
+                label: None,
+                reused: None,
+                accounts: false,
+                preview: Some(&preview),
+                plaintext: plaintext.as_deref().map(String::as_str),
+                policy: policy.as_deref(),
+                result: &result,
+            })
+            .map_err(|error| error.to_string())?;
+    }
+    // In the order of the file, as they came back in any.
+    rows.sort_by_key(|row| row.line);
+    totals.audit = Some(Audit::new(&rows));
+    output.totals(&totals).map_err(|error| error.to_string())?;
+    if let Some(resume) = resume {
+        // Failed lookups are worth a rerun from where they left off.
+        if failed_lookups {
+            resume.save(&finished);
+        } else if let Err(error) = checkpoint::remove(&resume.path) {
+            tracing::warn!("could not remove the checkpoint: {error}");
+        }
+    }
+    Ok(if totals.breached > 0 {
+        FOUND
+    } else if totals.errors > 0 {
+        FAILED
+    } else {
+        NOT_FOUND
+    })
+}
+
+/// Checks each entry with a password in the password manager export at
+/// `path`, `--jobs` at a time, and prints it by its label; another CSV file
+/// is read by the columns mapped for it in the app. Unlike a plain
+/// list the file has to be read whole, since quoted fields can span lines;
+/// it's wiped once parsed, and each password once it's been looked up. Rows
+/// that can't be read are printed as errors, ahead of the results.
+async fn check_import(
+    args: &Args,
+    settings: &AppSettings,
+    path: &Path,
+    manager: Option<Manager>,
+) -> Result<i32, String> {
+    let bytes = read_batch(path).await?;
+    let mut imported = import::read_known(&bytes, manager, &settings.import_mappings)
+        .and_then(|imported| {
+            imported.ok_or_else(|| {
+                "the header isn't that of a Bitwarden, KeePass, Chrome or Firefox export, and \
+                 no columns have been mapped for it; import it in the app to map them"
+                    .to_owned()
+            })
+        })
+        .map_err(|error| format!("{}: {error}", path.display()))?;
+    drop(bytes);
+    batch::fold_by_hash(&mut imported, HashMode::Sha1, settings.normalization);
+    let groups = import::reuse(&imported);
+    let passwords = mem::take(&mut imported.passwords);
+    let client = range_client(settings)?;
+    let mut output =
+        Output::new(args, settings.policy.is_some()).map_err(|error| error.to_string())?;
+    let mut totals = Totals::default();
+
+    for malformed in &imported.malformed {
+        let result = Err(malformed.reason.clone());
+        totals.add(&result);
+        output
+            .check(&Checked {
+                line: Some(malformed.line),
+                label: None,
+                reused: None,
+                accounts: false,
+                preview: None,
+                plaintext: None,
+                policy: None,
+                result: &result,
+            })
+            .map_err(|error| error.to_string())?;
+    }
+
+    // Each password is looked up once, however many sites it's used on,
+    // and each range once, however many passwords share it.
+    let hashes = batch::hash_all(&passwords, HashMode::Sha1, settings.normalization);
+    let results = batch::check_all(
+        client,
+        hashes,
+        HashMode::Sha1,
+        None,
+        settings.batch_concurrency,
+        Vec::new(),
+        dataset_filter(args, settings),
+    );
+    let mut results = pin!(results);
+    let mut counts = vec![None; groups.len()];
+    let mut rows = Vec::new();
+    totals.reused = groups
+        .iter()
+        .map(|lines| lines.len().saturating_sub(1))
+        .sum();
+    while let Some((item, _)) = results.next().await {
+        let (index, lines) = (item.index, &groups[item.index]);
+        counts[index] = item.outcome.clone().ok().map(Option::unwrap_or_default);
+        let result = batch_record(&item, HashMode::Sha1, settings);
+        for line in lines.iter().map(|&line| &imported.lines[line]) {
+            let password = passwords[line.password].expose();
+            let policy = policy_failures(settings, password, Some(&line.username));
+            totals.add(&result);
+            rows.push(audit_row(
+                line.number,
+                &line.label,
+                line.password,
+                Some(password.chars().count()),
+                HashMode::Sha1,
+                policy.clone(),
+                &result,
+            ));
+            output
+                .check(&Checked {
+                    line: Some(line.number),
+                    label: Some(&line.label),
+                    reused: (lines.len() > 1).then_some(lines.len()),
+                    accounts: false,
+                    preview: Some(&line.preview),
+                    plaintext: args.show_plaintext.then_some(password),
+                    policy: policy.as_deref(),
+                    result: &result,
+                })
+                .map_err(|error| error.to_string())?;
+        }
+    }
+    totals.worst = Worst::of(&imported, &groups, &counts);
+    rows.sort_by_key(|row| row.line);
+    totals.audit = Some(Audit::new(&rows));
+    output.totals(&totals).map_err(|error| error.to_string())?;
+    Ok(if totals.breached > 0 {
+        FOUND
+    } else if totals.errors > 0 {
+        FAILED
+    } else {
+        NOT_FOUND
+    })
+}
+
+/// The bytes of a file read whole for a batch, unless it's larger than one
+/// allows.
+async fn read_batch(path: &Path) -> Result<Zeroizing<Vec<u8>>, String> {
+    let size = tokio::fs::metadata(path)
+        .await
+        .map_err(|error| format!("{}: {error}", path.display()))?
+        .len();
+    if size > batch::MAX_IMPORT_BYTES {
+        return Err(format!(
+            "{}: the file is {} MB, more than the {} MB a batch allows",
+            path.display(),
+            size / 1024 / 1024,
+            batch::MAX_IMPORT_BYTES / 1024 / 1024
+        ));
+    }
+    tokio::fs::read(path)
+        .await
+        .map(Zeroizing::new)
+        .map_err(|error| format!("{}: {error}", path.display()))
+}
+
+/// The hashes in the file at `path`, read as `--hash-format` says.
+async fn read_hash_file(args: &Args, path: &Path) -> Result<batch::Lines, String> {
+    let bytes = read_batch(path).await?;
+    match args.hash_format {
+        HashFormat::Pwdump => pwdump::read(
+            &bytes,
+            pwdump::Include {
+                machine_accounts: args.include_machine_accounts,
+                history: args.include_history,
+            },
+        ),
+        format => batch::read_hashes(&bytes, format.mode()),
+    }
+    .map_err(|error| format!("{}: {error}", path.display()))
+}
+
+/// Checks each hash in the file at `path`, `--jobs` at a time, and prints
+/// it by its label or line. Lines that aren't hashes are printed as
+/// errors, ahead of the results, and a hash on many lines is looked up
+/// once.
+async fn check_hash_list(args: &Args, settings: &AppSettings, path: &Path) -> Result<i32, String> {
+    let mode = args.hash_format.mode();
+    let list = read_hash_file(args, path).await?;
+    let groups = import::reuse(&list);
+    let client = range_client(settings)?;
+    let mut output = Output::new(args, false).map_err(|error| error.to_string())?;
+    let mut totals = Totals::default();
+
+    for malformed in &list.malformed {
+        let result = Err(malformed.reason.clone());
+        totals.add(&result);
+        output
+            .check(&Checked {
+                line: Some(malformed.line),
+                label: None,
+                reused: None,
+                accounts: false,
+                preview: None,
+                plaintext: None,
+                policy: None,
+                result: &result,
+            })
+            .map_err(|error| error.to_string())?;
+    }
+
+    let hashes = list
+        .passwords
+        .iter()
+        .map(|hash| hash.expose().to_owned())
+        .collect();
+    let results = batch::check_all(
+        client,
+        hashes,
+        mode,
+        None,
+        settings.batch_concurrency,
+        Vec::new(),
+        dataset_filter(args, settings),
+    );
+    let mut results = pin!(results);
+    let mut counts = vec![None; groups.len()];
+    let mut rows = Vec::new();
+    totals.reused = groups
+        .iter()
+        .map(|lines| lines.len().saturating_sub(1))
+        .sum();
+    while let Some((item, _)) = results.next().await {
+        let (index, lines) = (item.index, &groups[item.index]);
+        counts[index] = item.outcome.clone().ok().map(Option::unwrap_or_default);
+        let result = batch_record(&item, mode, settings);
+        for line in lines.iter().map(|&line| &list.lines[line]) {
+            totals.add(&result);
+            rows.push(audit_row(
+                line.number,
+                &line.label,
+                line.password,
+                None,
+                mode,
+                None,
+                &result,
+            ));
+            output
+                .check(&Checked {
+                    line: Some(line.number),
+                    label: (!line.label.is_empty()).then_some(line.label.as_str()),
+                    reused: (list.accounts && lines.len() > 1).then_some(lines.len()),
+                    accounts: list.accounts,
+                    preview: Some(&line.preview),
+                    plaintext: None,
+                    policy: None,
+                    result: &result,
+                })
+                .map_err(|error| error.to_string())?;
+        }
+    }
+    totals.worst = Worst::of(&list, &groups, &counts);
+    rows.sort_by_key(|row| row.line);
+    totals.audit = Some(Audit::new(&rows));
+    output.totals(&totals).map_err(|error| error.to_string())?;
+    Ok(if totals.breached > 0 {
+        FOUND
+    } else if totals.errors > 0 {
+        FAILED
+    } else {
+        NOT_FOUND
+    })
+}
+
+/// Checks the hashes in the file at `path` against the counts saved last
+/// time, once with `--state` or with `--watch` every `--interval`, and
+/// prints what got worse. Lines that aren't hashes are warned about and
+/// skipped. The counts are saved after every check, and a check is only due
+/// an interval after the last one ended, even across restarts.
+async fn check_hashes(args: &Args, settings: &AppSettings, path: &Path) -> Result<i32, String> {
+    let list = read_hash_file(args, path).await?;
+    if !args.quiet {
+        for malformed in &list.malformed {
+            eprintln!("warning: {malformed}, so it isn't watched");
+        }
+    }
+    let hashes: Vec<Watched> = list
+        .lines
+        .iter()
+        .map(|line| Watched {
+            line: line.number,
+            hash: list.passwords[line.password].expose().to_owned(),
+        })
+        .collect();
+    let state_path = args
+        .state
+        .clone()
+        .unwrap_or_else(|| watch::default_state_path(path));
+    let mut state = State::load(&state_path).map_err(|error| error.to_string())?;
+    let client = range_client(settings)?;
+    loop {
+        if args.watch {
+            tokio::time::sleep(watch::delay(state.checked_at, args.interval, Utc::now())).await;
+        }
+        let counts = lookup_hashes(&client, &hashes, args.hash_format.mode(), settings).await;
+        let failed = counts.iter().filter(|count| count.is_none()).count();
+        let findings = state.update(&hashes, &counts, Utc::now());
+        if let Err(error) = state.save(&state_path) {
+            let error = format!("{}: {error}", state_path.display());
+            if !args.watch {
+                return Err(error);
+            }
+            tracing::warn!("could not save the watch state: {error}");
+        }
+
+        if !args.quiet {
+            for finding in &findings {
+                println!("{}", finding.describe());
+            }
+            if failed > 0 {
+                eprintln!(
+                    "warning: {failed} of {} hashes couldn't be checked{}",
+                    hashes.len(),
+                    if args.watch {
+                        ", they're tried again next time"
+                    } else {
+                        ""
+                    }
+                );
+            }
+        }
+        if args.stats && args.watch {
+            eprintln!("{}", METRICS.snapshot());
+            METRICS.reset();
+        }
+        if args.notify && !findings.is_empty() {
+            let strings = settings.language.resolve(Language::detect()).strings();
+            let content = notify::Content {
+                summary: strings.watch_found.to_owned(),
+                body: (strings.watch_changed)(findings.len(), hashes.len()),
+                open: strings.tray_open,
+            };
+            if let Err(error) = notify::show(content, strings.window_title).await {
+                tracing::warn!("could not show a notification: {error}");
+            }
+        }
+        if !args.watch {
+            return Ok(if !findings.is_empty() {
+                FOUND
+            } else if failed > 0 {
+                FAILED
+            } else {
+                NOT_FOUND
+            });
+        }
+    }
+}
+
+/// The count each of `hashes` is seen now, in order: 0 for not found,
+/// `None` if the lookup failed.
+async fn lookup_hashes(
+    client: &RangeClient,
+    hashes: &[Watched],
+    mode: HashMode,
+    settings: &AppSettings,
+) -> Vec<Option<u64>> {
+    // A hash listed more than once is looked up once.
+    let mut unique = HashMap::new();
+    for watched in hashes {
+        let next = unique.len();
+        unique.entry(watched.hash.as_str()).or_insert(next);
+    }
+    let mut order: Vec<&str> = vec![""; unique.len()];
+    for (&hash, &index) in &unique {
+        order[index] = hash;
+    }
+    let counts: Vec<Option<u64>> = stream::iter(order)
+        .map(|hash| async move {
+            let search = client.clone().search(hash.to_owned(), mode);
+            match client.with_decoys(mode, search).await {
+                Ok(BreachResult::Found { count }) => Some(count),
+                Ok(BreachResult::NotFound) => Some(0),
+                // The range client has logged why.
+                Err(_) => None,
+            }
+        })
+        .buffered(settings.batch_concurrency)
+        .collect()
+        .await;
+    hashes
+        .iter()
+        .map(|watched| counts[unique[watched.hash.as_str()]])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+    use cybersec_wow::severity::Thresholds;
+    use serde_json::Value;
+
+    use super::*;
+
+    const HUNTER2: &str = "F3BBBD66A63D4BF1747940578EC3D0103530E21D";
+
+    fn found() -> CheckRecord {
+        let mut record = CheckRecord::new(
+            HUNTER2,
+            HashMode::Sha1,
+            BreachResult::Found { count: 17206891 },
+            LookupSource::Network,
+            &Thresholds::default(),
+        );
+        record.checked_at = "2025-01-01T12:00:00.123Z".parse().unwrap();
+        record
+    }
+
+    /// `result` as a line of a file, with awkward text in every column that
+    /// takes it.
+    fn checked<'a>(
+        result: &'a Result<CheckRecord, String>,
+        policy: Option<&'a [Rule]>,
+    ) -> Checked<'a> {
+        Checked {
+            line: Some(3),
+            label: Some("Mail, \"old\""),
+            reused: Some(2),
+            accounts: false,
+            preview: Some("h\"*,*\n**"),
+            plaintext: Some("h\",u\r\nter2"),
+            policy,
+            result,
+        }
+    }
+
+    fn json(checked: &Checked) -> Value {
+        let mut line = Vec::new();
+        write_json_line(&mut line, checked).unwrap();
+        let line = String::from_utf8(line).unwrap();
+        // One record a line, however many line breaks its text has.
+        assert_eq!(line.matches('\n').count(), 1);
+        assert!(line.ends_with('\n'));
+        serde_json::from_str(&line).unwrap()
+    }
+
+    fn csv(checked: &Checked, policy: bool) -> Vec<String> {
+        let mut csv = csv::WriterBuilder::new()
+            .quote_style(csv::QuoteStyle::Always)
+            .from_writer(Vec::new());
+        write_csv_row(&mut csv, checked, policy).unwrap();
+        let written = csv.into_inner().unwrap();
+        let mut rows = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .from_reader(&written[..])
+            .into_records();
+        let row = rows.next().unwrap().unwrap();
+        assert!(rows.next().is_none());
+        row.iter().map(str::to_owned).collect()
+    }
+
+    #[test]
+    fn a_json_line_is_the_check_record_with_the_line_ahead() {
+        let result = Ok(found());
+        let line = json(&checked(&result, None));
+        let Value::Object(record) = serde_json::to_value(found()).unwrap() else {
+            unreachable!()
+        };
+        for (key, value) in &record {
+            assert_eq!(&line[key], value, "{key}");
+        }
+        assert_eq!(line["line"], 3);
+        assert_eq!(line["label"], "Mail, \"old\"");
+        assert_eq!(line["reused"], 2);
+        assert_eq!(line["preview"], "h\"*,*\n**");
+        assert_eq!(line["plaintext"], "h\",u\r\nter2");
+        assert!(line.get("error").is_none() && line.get("policy_failures").is_none());
+        let checked_at = line["checked_at"].as_str().unwrap();
+        assert!(
+            DateTime::parse_from_rfc3339(checked_at).is_ok(),
+            "{checked_at}"
+        );
+    }
+
+    #[test]
+    fn a_failed_json_line_has_just_the_error() {
+        let result = Err("request timed out: \"F3BBB\"\n".to_owned());
+        let line = json(&checked(&result, None));
+        assert_eq!(line["error"], "request timed out: \"F3BBB\"\n");
+        for key in [
+            "prefix",
+            "algorithm",
+            "result",
+            "severity",
+            "checked_at",
+            "source",
+        ] {
+            assert!(line.get(key).is_none(), "{key}");
+        }
+        assert_eq!(line["line"], 3);
+    }
+
+    #[test]
+    fn policy_failures_are_listed_in_json() {
+        let result = Ok(found());
+        let failed = [Rule::MinLength(12), Rule::Username];
+        let line = json(&checked(&result, Some(&failed)));
+        assert_eq!(
+            line["policy_failures"],
+            serde_json::to_value(failed).unwrap()
+        );
+    }
+
+    #[test]
+    fn a_csv_row_has_a_column_for_every_header() {
+        let result = Ok(found());
+        let row = csv(&checked(&result, None), false);
+        assert_eq!(
+            row,
+            [
+                "3",
+                "Mail, \"old\"",
+                "2",
+                "h\"*,*\n**",
+                "F3BBB",
+                "SHA-1",
+                "true",
+                "17206891",
+                "critical",
+                "2025-01-01T12:00:00Z",
+                "network",
+                "",
+                "",
+                "h\",u\r\nter2",
+            ]
+        );
+        assert_eq!(row.len(), CSV_HEADER.len());
+        assert!(DateTime::parse_from_rfc3339(&row[9]).is_ok());
+    }
+
+    #[test]
+    fn a_failed_csv_row_has_only_what_isnt_the_record() {
+        let result = Err("the response could not be parsed, \"<html>\"".to_owned());
+        let row = csv(&checked(&result, None), false);
+        assert_eq!(&row[..4], ["3", "Mail, \"old\"", "2", "h\"*,*\n**"]);
+        assert!(row[4..12].iter().all(String::is_empty), "{row:?}");
+        assert_eq!(row[12], "the response could not be parsed, \"<html>\"");
+        assert_eq!(row[13], "h\",u\r\nter2");
+    }
+
+    #[test]
+    fn the_policy_column_comes_before_the_plaintext() {
+        let result = Ok(found());
+        let failed = [Rule::MinLength(12)];
+        let row = csv(&checked(&result, Some(&failed)), true);
+        assert_eq!(row.len(), CSV_HEADER.len() + 1);
+        assert_eq!(row[13], policy::describe(&failed));
+        assert_eq!(row[14], "h\",u\r\nter2");
+        // A failed lookup still has the column, empty.
+        let failed_lookup = Err("offline".to_owned());
+        let row = csv(&checked(&failed_lookup, None), true);
+        assert_eq!(row[13], "");
+    }
+
+    #[test]
+    fn offline_answers_name_their_snapshot() {
+        let mut record = found();
+        record.source = LookupSource::Offline("2024-09-01".parse().unwrap());
+        let result = Ok(record);
+        let row = csv(&checked(&result, None), false);
+        assert_eq!(
+            (row[10].as_str(), row[11].as_str()),
+            ("offline", "2024-09-01")
+        );
+        let line = json(&checked(&result, None));
+        assert_eq!(line["source"]["type"], "offline");
+        assert_eq!(line["source"]["snapshot"], "2024-09-01");
+    }
+
+    fn parse(args: &[&str]) -> Result<Args, clap::Error> {
+        Args::try_parse_from(["cybersec-wow"].iter().chain(args))
+    }
+
+    #[test]
+    fn the_prompt_and_completions_are_subcommands() {
+        let args = parse(&["repl", "--endpoint", "http://127.0.0.1:1", "-v"]).unwrap();
+        assert!(matches!(args.command, Some(Commands::Repl)));
+        assert!(args.is_headless() && args.verbose && args.endpoint.is_some());
+        assert!(args.check_lookup_options().is_ok());
+        let args = parse(&["completions", "zsh"]).unwrap();
+        assert!(matches!(
+            args.command,
+            Some(Commands::Completions { shell: Shell::Zsh })
+        ));
+        for refused in [
+            &["--repl"][..],
+            &["--completions", "bash"],
+            &["--stdin", "repl"],
+            &["--file", "passwords.txt", "completions", "bash"],
+            &["completions"],
+        ] {
+            assert!(parse(refused).is_err(), "{refused:?}");
+        }
+    }
+
+    #[test]
+    fn lookup_options_need_something_to_look_up_with() {
+        for option in [
+            ["--endpoint", "http://127.0.0.1:1"],
+            ["--timeout", "5"],
+            ["--rate", "5"],
+            ["--proxy", "http://127.0.0.1:8080"],
+        ] {
+            let error = parse(&option).unwrap().check_lookup_options().unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::MissingRequiredArgument);
+            assert!(error.to_string().contains(option[0]), "{error}");
+            let args = parse(&[&option[..], &["--stdin"]].concat()).unwrap();
+            assert!(args.check_lookup_options().is_ok());
+        }
+        assert!(parse(&["-v"]).unwrap().check_lookup_options().is_ok());
+    }
+
+    #[test]
+    fn every_completion_script_offers_every_long_option() {
+        let command = Args::command();
+        let longs: Vec<_> = command
+            .get_arguments()
+            .filter_map(|arg| arg.get_long())
+            .collect();
+        assert!(longs.len() > 25, "{longs:?}");
+        for &shell in Shell::value_variants() {
+            let mut script = Vec::new();
+            clap_complete::generate(shell, &mut Args::command(), "cybersec-wow", &mut script);
+            let script = String::from_utf8(script).unwrap();
+            assert!(!script.trim().is_empty(), "{shell}");
+            for long in &longs {
+                let offered = match shell {
+                    Shell::Fish => format!("-l {long}"),
+                    _ => format!("--{long}"),
+                };
+                assert!(script.contains(&offered), "{shell} lacks {offered}");
+            }
+            for subcommand in ["completions", "repl"] {
+                assert!(script.contains(subcommand), "{shell} lacks {subcommand}");
+            }
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use std::time::{Duration, Instant};
+
+use cybersec_wow::hash::normalize_hash;
+use cybersec_wow::{HashMode, Normalization, hash_password};
+
+/// How often the clipboard is read while it's watched.
+pub const POLL: Duration = Duration::from_secs(1);
+
+/// How long a result stays on screen.
+pub const TOAST: Duration = Duration::from_secs(6);
+
+/// The shortest and longest text, in characters, taken for a password.
+pub const MIN_CHARS: usize = 6;
+pub const MAX_CHARS: usize = 64;
+
+/// Whether copied text could be a password: one line of [`MIN_CHARS`] to
+/// [`MAX_CHARS`] characters that isn't a link, or a hash such as this app
+/// copies itself, which would only be checked as the password it isn't.
+pub fn looks_like_password(text: &str) -> bool {
+    let chars = text.chars().count();
+    if !(MIN_CHARS..=MAX_CHARS).contains(&chars) || text.trim().is_empty() {
+        return false;
+    }
+    if text.chars().any(char::is_control) {
+        return false;
+    }
+    let lowercase = text.trim().to_ascii_lowercase();
+    if lowercase.contains("://") || lowercase.starts_with("www.") {
+        return false;
+    }
+    [HashMode::Sha1, HashMode::Ntlm]
+        .into_iter()
+        .all(|mode| normalize_hash(text, mode).is_err())
+}
+
+/// A session of watching the clipboard, which ends by itself once it's run
+/// for as long as the settings allow. It remembers what was last on the
+/// clipboard by hash, never as text, so a value is checked once however
+/// long it stays there.
+#[derive(Debug)]
+pub struct Monitor {
+    until: Instant,
+    /// The hash of the clipboard's text when it was last read, or `None` if
+    /// it had none; unset until the first read.
+    last: Option<Option<String>>,
+}
+
+impl Monitor {
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            until: Instant::now() + duration,
+            last: None,
+        }
+    }
+
+    pub fn is_over(&self, now: Instant) -> bool {
+        now >= self.until
+    }
+
+    /// How long it has left, to the second.
+    pub fn remaining(&self, now: Instant) -> Duration {
+        Duration::from_secs(self.until.saturating_duration_since(now).as_secs())
+    }
+
+    /// Notes what the clipboard holds now and says whether it's new since
+    /// the last read. What's there when watching starts doesn't count, as
+    /// it was copied before.
+    pub fn changed(&mut self, text: Option<&str>, normalization: Normalization) -> bool {
+        let hash = text.map(|text| hash_password(text, HashMode::Sha1, normalization));
+        let first = self.last.is_none();
+        let changed = self.last.as_ref() != Some(&hash);
+        self.last = Some(hash);
+        changed && !first
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn passwords_are_one_line_of_the_right_length() {
+        for password in [
+            "hunter2",
+            "correct horse battery staple",
+            "Tr0ub4dor&3",
+            "pässwörd",
+        ] {
+            assert!(looks_like_password(password), "{password:?}");
+        }
+        assert!(looks_like_password(&"x".repeat(MAX_CHARS)));
+        for not in [
+            "",
+            "short",
+            "      ",
+            "two\nlines",
+            "tab\there",
+            &"x".repeat(MAX_CHARS + 1),
+        ] {
+            assert!(!looks_like_password(not), "{not:?}");
+        }
+    }
+
+    #[test]
+    fn links_and_hashes_arent_taken_for_passwords() {
+        for not in [
+            "https://example.com/login",
+            "ftp://files",
+            "www.example.com",
+            " WWW.Example.com",
+            "F3BBBD66A63D4BF1747940578EC3D0103530E21D",
+            "f3bbbd66a63d4bf1747940578ec3d0103530e21d",
+            "6608E4BC7B2B7A5F77CE3573570775AF",
+        ] {
+            assert!(!looks_like_password(not), "{not:?}");
+        }
+        // One character short of a SHA-1 hash is just text.
+        assert!(looks_like_password(
+            "F3BBBD66A63D4BF1747940578EC3D0103530E21"
+        ));
+    }
+
+    #[test]
+    fn only_a_change_after_the_first_read_counts() {
+        let mut monitor = Monitor::new(Duration::from_secs(60));
+        assert!(!monitor.changed(Some("already there"), Normalization::Off));
+        assert!(!monitor.changed(Some("already there"), Normalization::Off));
+        assert!(monitor.changed(Some("hunter2"), Normalization::Off));
+        assert!(!monitor.changed(Some("hunter2"), Normalization::Off));
+        assert!(monitor.changed(None, Normalization::Off));
+        assert!(!monitor.changed(None, Normalization::Off));
+        // Copying the same password again after something else is new.
+        assert!(monitor.changed(Some("hunter2"), Normalization::Off));
+    }
+
+    #[test]
+    fn an_empty_clipboard_at_the_start_doesnt_count_either() {
+        let mut monitor = Monitor::new(Duration::from_secs(60));
+        assert!(!monitor.changed(None, Normalization::Off));
+        assert!(monitor.changed(Some("hunter2"), Normalization::Off));
+    }
+
+    #[test]
+    fn only_the_hash_is_remembered() {
+        let mut monitor = Monitor::new(Duration::from_secs(60));
+        monitor.changed(Some("hunter2"), Normalization::Off);
+        let remembered = format!("{monitor:?}");
+        assert!(!remembered.contains("hunter2"), "{remembered}");
+        assert_eq!(
+            monitor.last,
+            Some(Some("F3BBBD66A63D4BF1747940578EC3D0103530E21D".to_owned()))
+        );
+    }
+
+    #[test]
+    fn watching_ends_when_its_time_is_up() {
+        let monitor = Monitor::new(Duration::from_secs(60));
+        let start = monitor.until - Duration::from_secs(60);
+        assert!(!monitor.is_over(start));
+        assert_eq!(monitor.remaining(start), Duration::from_secs(60));
+        let later = start + Duration::from_millis(30_500);
+        assert_eq!(monitor.remaining(later), Duration::from_secs(29));
+        assert!(monitor.is_over(monitor.until));
+        assert_eq!(
+            monitor.remaining(monitor.until + Duration::from_secs(5)),
+            Duration::ZERO
+        );
+    }
+}
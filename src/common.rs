@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+use std::io::Read;
+use std::sync::LazyLock;
+
+use flate2::read::GzDecoder;
+
+/// The most common passwords, most common first, one per line.
+static LIST: &[u8] = include_bytes!("../data/common-passwords.txt.gz");
+
+/// Decompressed on first use and kept for the life of the process, so later
+/// lookups only hash the input.
+static RANKS: LazyLock<HashMap<&'static str, u32>> = LazyLock::new(|| {
+    let mut list = String::new();
+    GzDecoder::new(LIST)
+        .read_to_string(&mut list)
+        .expect("the bundled password list is valid gzip");
+    list.leak().lines().zip(1..).collect()
+});
+
+/// How many passwords the bundled list holds.
+pub fn count() -> usize {
+    RANKS.len()
+}
+
+/// Where `password` ranks among the most common passwords, 1 being the most
+/// common. Matching is exact and case-sensitive.
+pub fn rank(password: &str) -> Option<u32> {
+    RANKS.get(password).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_whole_list_is_bundled() {
+        assert_eq!(count(), 100_000);
+    }
+
+    #[test]
+    fn listed_passwords_rank_from_the_top() {
+        assert_eq!(rank("123456"), Some(1));
+        assert_eq!(rank("password"), Some(2));
+        assert_eq!(rank("hunter2"), Some(17083));
+    }
+
+    #[test]
+    fn unlisted_passwords_have_no_rank() {
+        for password in [
+            "",
+            "correct horse battery staple",
+            "8c1f a password nobody uses",
+        ] {
+            assert_eq!(rank(password), None, "{password:?}");
+        }
+    }
+
+    #[test]
+    fn matching_is_exact() {
+        assert_eq!(rank("Hunter2"), None);
+        assert_eq!(rank("hunter2 "), None);
+        assert_eq!(rank(" hunter2"), None);
+        // Case variants that are common are listed, and ranked, on their own.
+        assert!(rank("Password").unwrap() > rank("password").unwrap());
+    }
+}
@@ -0,0 +1,377 @@
+use iced::alignment::Vertical;
+use iced::futures::Stream;
+use iced::widget::{button, checkbox, column, row, text, text_input};
+use iced::{Element, Task, task};
+
+use crate::batch::{BatchItem, BatchStats};
+use crate::high_contrast;
+use crate::secret::{SecretBuffer, SecretString};
+use crate::strings::Strings;
+use crate::summary::{CountStyle, format_count};
+
+/// How a new password gives away the old one it replaces.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Similarity {
+    Identical,
+    /// The same letters, only some in a different case.
+    Case,
+    /// The same apart from digits added, removed or changed at the end.
+    TrailingDigits,
+    /// One character added, removed or replaced.
+    OneCharacter,
+}
+
+/// Whether `new` is too close to `old` to be a real change, by a few rules
+/// of thumb that catch the usual rotations rather than every weak one.
+/// Case is ignored by the last two checks, so `Summer1` and `summer2` count
+/// as a change of digits.
+pub fn similarity(old: &str, new: &str) -> Option<Similarity> {
+    if old == new {
+        return Some(Similarity::Identical);
+    }
+    let old = old.to_lowercase();
+    let new = new.to_lowercase();
+    if old == new {
+        return Some(Similarity::Case);
+    }
+    let stem = |password: &str| {
+        password
+            .trim_end_matches(|c: char| c.is_ascii_digit())
+            .len()
+    };
+    let (old_stem, new_stem) = (&old[..stem(&old)], &new[..stem(&new)]);
+    if !old_stem.is_empty() && old_stem == new_stem {
+        return Some(Similarity::TrailingDigits);
+    }
+    one_edit_apart(&old, &new).then_some(Similarity::OneCharacter)
+}
+
+/// Whether one character added, removed or replaced turns `a` into `b`.
+fn one_edit_apart(a: &str, b: &str) -> bool {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    let (shorter, longer) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    if longer.len() - shorter.len() > 1 {
+        return false;
+    }
+    let prefix = shorter
+        .iter()
+        .zip(&longer)
+        .take_while(|(a, b)| a == b)
+        .count();
+    // Past the first difference, the rest must line up after skipping one
+    // character of the longer, or one of each when they're the same length.
+    let skip = usize::from(shorter.len() == longer.len());
+    shorter[(prefix + skip).min(shorter.len())..] == longer[(prefix + 1).min(longer.len())..]
+}
+
+/// The Compare tab: an old and a new password checked side by side, as when
+/// rotating a credential.
+#[derive(Debug, Default)]
+pub struct CompareScreen {
+    old: SecretBuffer,
+    new: SecretBuffer,
+    show: bool,
+    /// The outcomes for the old and the new password, in that order.
+    results: Option<[Option<BatchItem>; 2]>,
+    run: Option<task::Handle>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Old(SecretString),
+    New(SecretString),
+    Show(bool),
+    /// Handled by the app, which has the client to check them with.
+    Submit,
+    Item(BatchItem),
+    Finished,
+    Clear,
+}
+
+impl CompareScreen {
+    pub fn update(&mut self, message: Message) -> Task<Message> {
+        match message {
+            // Copied into the locked buffers; the messages are wiped as they drop.
+            Message::Old(old) => {
+                self.old.set(old.expose());
+                self.edited();
+            }
+            Message::New(new) => {
+                self.new.set(new.expose());
+                self.edited();
+            }
+            Message::Show(show) => self.show = show,
+            Message::Submit => {}
+            Message::Item(item) => {
+                if let Some(slot) = self
+                    .results
+                    .as_mut()
+                    .and_then(|results| results.get_mut(item.index))
+                {
+                    *slot = Some(item);
+                }
+            }
+            Message::Finished => self.run = None,
+            Message::Clear => self.clear(),
+        }
+        Task::none()
+    }
+
+    /// A result is for the passwords it was run with, so editing either one
+    /// drops it, and stops a run still going.
+    fn edited(&mut self) {
+        self.run = None;
+        self.results = None;
+        if self.old.is_empty() && self.new.is_empty() {
+            self.show = false;
+        }
+    }
+
+    pub fn can_submit(&self) -> bool {
+        self.run.is_none() && !self.old.is_empty() && !self.new.is_empty()
+    }
+
+    pub fn can_clear(&self) -> bool {
+        !self.old.is_empty() || !self.new.is_empty()
+    }
+
+    /// The outcomes for the old and the new password, once a check has
+    /// started.
+    pub fn results(&self) -> Option<&[Option<BatchItem>; 2]> {
+        self.results.as_ref()
+    }
+
+    /// Copies of the old and the new password, for the app to check.
+    pub fn passwords(&self) -> [SecretString; 2] {
+        [&self.old, &self.new].map(|password| password.expose().to_owned().into())
+    }
+
+    /// Runs `updates`, the app's check of [`passwords`](Self::passwords).
+    pub fn start(
+        &mut self,
+        updates: impl Stream<Item = (BatchItem, BatchStats)> + Send + 'static,
+    ) -> Task<Message> {
+        self.results = Some([None, None]);
+        let (task, handle) = Task::run(updates, |(item, _)| Message::Item(item))
+            .chain(Task::done(Message::Finished))
+            .abortable();
+        self.run = Some(handle.abort_on_drop());
+        task
+    }
+
+    /// Wipes both passwords and drops the result.
+    pub fn clear(&mut self) {
+        self.old.set("");
+        self.new.set("");
+        self.edited();
+    }
+
+    pub fn view(&self, strings: &'static Strings, separator: char) -> Element<'_, Message> {
+        let can_submit = self.can_submit();
+        let input =
+            |placeholder, password: &SecretBuffer, on_input: fn(SecretString) -> Message| {
+                text_input(placeholder, password.expose())
+                    .style(high_contrast::text_input)
+                    .secure(!self.show)
+                    .on_input(move |input| on_input(input.into()))
+                    .on_submit_maybe(can_submit.then_some(Message::Submit))
+            };
+        let warning = (!self.old.is_empty() && !self.new.is_empty())
+            .then(|| similarity(self.old.expose(), self.new.expose()))
+            .flatten()
+            .map(|similarity| text((strings.similarity)(similarity)).style(text::danger));
+
+        column![
+            text(strings.compare_intro),
+            input(strings.old_password, &self.old, Message::Old),
+            input(strings.new_password, &self.new, Message::New),
+            row![
+                checkbox(strings.show_passwords, self.show).on_toggle(Message::Show),
+                button(strings.check_both).on_press_maybe(can_submit.then_some(Message::Submit)),
+                button(strings.clear).on_press_maybe(self.can_clear().then_some(Message::Clear)),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+        ]
+        .push_maybe(warning)
+        .push_maybe(self.results().map(|[old, new]| {
+            column![
+                verdict(strings, strings.old, old.as_ref(), separator),
+                verdict(strings, strings.new, new.as_ref(), separator),
+            ]
+            .spacing(2)
+        }))
+        .spacing(5)
+        .into()
+    }
+}
+
+fn verdict<'a>(
+    strings: &'static Strings,
+    label: &str,
+    item: Option<&BatchItem>,
+    separator: char,
+) -> Element<'a, Message> {
+    match item.map(|item| &item.outcome) {
+        None => text((strings.compare_checking)(label)).style(text::secondary),
+        Some(Ok(Some(count))) => text((strings.compare_breached)(
+            label,
+            &format_count(*count, CountStyle::Full, separator),
+        ))
+        .style(text::danger),
+        Some(Ok(None)) => text((strings.compare_not_found)(label)).style(text::success),
+        Some(Err(error)) => text((strings.compare_failed)(label, error)).style(text::danger),
+    }
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::Utc;
+    use iced::futures::stream;
+
+    fn item(index: usize, outcome: Result<Option<u64>, String>) -> BatchItem {
+        BatchItem {
+            index,
+            prefix: "F3BBB".to_owned(),
+            checked_at: Utc::now(),
+            outcome,
+        }
+    }
+
+    fn typed(old: &str, new: &str) -> CompareScreen {
+        let mut screen = CompareScreen::default();
+        let _ = screen.update(Message::Old(old.to_owned().into()));
+        let _ = screen.update(Message::New(new.to_owned().into()));
+        screen
+    }
+
+    #[test]
+    fn the_same_password_is_identical() {
+        assert_eq!(
+            similarity("hunter2", "hunter2"),
+            Some(Similarity::Identical)
+        );
+        assert_eq!(similarity("", ""), Some(Similarity::Identical));
+    }
+
+    #[test]
+    fn a_change_of_case_is_caught() {
+        assert_eq!(similarity("Hunter2", "hunter2"), Some(Similarity::Case));
+        assert_eq!(similarity("straße", "STRASSE"), None);
+        assert_eq!(similarity("Ärger", "äRGER"), Some(Similarity::Case));
+    }
+
+    #[test]
+    fn changed_trailing_digits_are_caught() {
+        assert_eq!(
+            similarity("Summer2023", "Summer2024"),
+            Some(Similarity::TrailingDigits)
+        );
+        assert_eq!(
+            similarity("Summer1", "summer22"),
+            Some(Similarity::TrailingDigits)
+        );
+        assert_eq!(
+            similarity("winter", "winter99"),
+            Some(Similarity::TrailingDigits)
+        );
+        assert_eq!(
+            similarity("winter99", "winter"),
+            Some(Similarity::TrailingDigits)
+        );
+    }
+
+    #[test]
+    fn digits_alone_arent_a_stem() {
+        // Nothing's left once the digits are gone, so only the one-edit rule
+        // can match.
+        assert_eq!(similarity("1234", "1235"), Some(Similarity::OneCharacter));
+        assert_eq!(similarity("1234", "9876"), None);
+    }
+
+    #[test]
+    fn one_character_added_removed_or_replaced_is_caught() {
+        for (old, new) in [
+            ("correct horse", "correct horses"),
+            ("correct horse", "xcorrect horse"),
+            ("correct horse", "correct-horse"),
+            ("correct horse", "corect horse"),
+            ("correct horse", "correct hors"),
+            ("pässwort", "passwort"),
+        ] {
+            assert_eq!(
+                similarity(old, new),
+                Some(Similarity::OneCharacter),
+                "{old} {new}"
+            );
+            assert_eq!(one_edit_apart(new, old), one_edit_apart(old, new));
+        }
+    }
+
+    #[test]
+    fn real_changes_pass() {
+        for (old, new) in [
+            ("correct horse", "battery staple"),
+            ("correct horse", "correct  horse!"),
+            ("abcdef", "badcef"),
+            ("hunter2", "q8#Lm2vR!pTz"),
+        ] {
+            assert_eq!(similarity(old, new), None, "{old} {new}");
+        }
+    }
+
+    #[test]
+    fn both_passwords_are_needed_to_submit() {
+        assert!(!typed("", "").can_submit());
+        assert!(!typed("old", "").can_submit());
+        assert!(!typed("", "new").can_submit());
+        assert!(typed("old", "new").can_submit());
+        assert!(typed("old", "").can_clear());
+        assert!(!typed("", "").can_clear());
+    }
+
+    #[test]
+    fn results_land_in_their_slot() {
+        let mut screen = typed("hunter2", "battery staple");
+        let _ = screen.start(stream::empty::<(BatchItem, BatchStats)>());
+        assert!(!screen.can_submit(), "a run is going");
+        let _ = screen.update(Message::Item(item(1, Ok(None))));
+        let _ = screen.update(Message::Item(item(0, Ok(Some(17206891)))));
+        // An index out of range is dropped rather than panicking.
+        let _ = screen.update(Message::Item(item(2, Ok(None))));
+        let _ = screen.update(Message::Finished);
+
+        let [old, new] = screen.results().unwrap();
+        assert_eq!(old.as_ref().unwrap().outcome, Ok(Some(17206891)));
+        assert_eq!(new.as_ref().unwrap().outcome, Ok(None));
+        assert!(screen.can_submit());
+    }
+
+    #[test]
+    fn editing_either_password_drops_the_result() {
+        let mut screen = typed("hunter2", "battery staple");
+        let _ = screen.start(stream::empty::<(BatchItem, BatchStats)>());
+        let _ = screen.update(Message::New("battery stapler".to_owned().into()));
+        assert!(screen.results().is_none());
+        assert!(screen.can_submit(), "the run was stopped");
+
+        // A result still on its way from the stopped run has nowhere to go.
+        let _ = screen.update(Message::Item(item(0, Ok(Some(1)))));
+        assert!(screen.results().is_none());
+    }
+
+    #[test]
+    fn clearing_wipes_both_and_hides_them_again() {
+        let mut screen = typed("hunter2", "battery staple");
+        let _ = screen.update(Message::Show(true));
+        let _ = screen.start(stream::empty::<(BatchItem, BatchStats)>());
+        let _ = screen.update(Message::Clear);
+        assert_eq!(screen.passwords().map(|p| p.expose().to_owned()), ["", ""]);
+        assert!(screen.results().is_none());
+        assert!(!screen.show);
+        assert!(!screen.can_clear());
+    }
+}
@@ -0,0 +1,177 @@
+use unicode_normalization::char::is_combining_mark;
+
+use crate::invisible;
+
+/// A kind of character a password can contain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Class {
+    Lower,
+    Upper,
+    Digit,
+    Symbol,
+    Space,
+    /// Letters without case, combining marks, control characters and
+    /// anything invisible.
+    Other,
+}
+
+impl Class {
+    pub const ALL: [Class; 6] = [
+        Class::Lower,
+        Class::Upper,
+        Class::Digit,
+        Class::Symbol,
+        Class::Space,
+        Class::Other,
+    ];
+
+    pub fn of(c: char) -> Self {
+        if invisible::is_flagged(c) || c.is_control() || is_combining_mark(c) {
+            Class::Other
+        } else if c.is_whitespace() {
+            Class::Space
+        } else if c.is_lowercase() {
+            Class::Lower
+        } else if c.is_uppercase() {
+            Class::Upper
+        } else if c.is_numeric() {
+            Class::Digit
+        } else if c.is_alphabetic() {
+            Class::Other
+        } else {
+            // Punctuation, currency and emoji alike.
+            Class::Symbol
+        }
+    }
+}
+
+/// What a password is made of, for the readout under the field.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Composition {
+    /// Unicode scalar values, so `é` typed as `e` and a combining accent is two.
+    pub chars: usize,
+    pub bytes: usize,
+    /// Indexed like [`Class::ALL`].
+    classes: [bool; 6],
+}
+
+impl Composition {
+    pub fn of(password: &str) -> Self {
+        let mut composition = Composition {
+            bytes: password.len(),
+            ..Composition::default()
+        };
+        for c in password.chars() {
+            composition.chars += 1;
+            composition.classes[Class::of(c) as usize] = true;
+        }
+        composition
+    }
+
+    /// The classes present, in [`Class::ALL`] order.
+    pub fn classes(&self) -> impl Iterator<Item = Class> + '_ {
+        Class::ALL
+            .into_iter()
+            .filter(|&class| self.classes[class as usize])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classes(password: &str) -> Vec<Class> {
+        Composition::of(password).classes().collect()
+    }
+
+    #[test]
+    fn ascii_falls_into_the_usual_classes() {
+        for (c, class) in [
+            ('a', Class::Lower),
+            ('Z', Class::Upper),
+            ('7', Class::Digit),
+            ('!', Class::Symbol),
+            ('~', Class::Symbol),
+            (' ', Class::Space),
+            ('\t', Class::Other),
+            ('\0', Class::Other),
+        ] {
+            assert_eq!(Class::of(c), class, "{c:?}");
+        }
+    }
+
+    #[test]
+    fn other_scripts_are_classed_by_case() {
+        for (c, class) in [
+            ('é', Class::Lower),
+            ('ß', Class::Lower),
+            ('Ж', Class::Upper),
+            ('Ω', Class::Upper),
+            ('字', Class::Other),
+            ('あ', Class::Other),
+            ('٣', Class::Digit),
+            ('½', Class::Digit),
+            ('\u{3000}', Class::Space),
+            ('€', Class::Symbol),
+        ] {
+            assert_eq!(Class::of(c), class, "{c:?}");
+        }
+    }
+
+    #[test]
+    fn emoji_are_symbols_and_their_joiners_other() {
+        assert_eq!(Class::of('🔑'), Class::Symbol);
+        assert_eq!(Class::of('😀'), Class::Symbol);
+        // The family is three people held together by zero-width joiners.
+        let family = "👩\u{200D}👩\u{200D}👦";
+        assert_eq!(classes(family), [Class::Symbol, Class::Other]);
+        let composition = Composition::of(family);
+        assert_eq!(composition.chars, 5);
+        assert_eq!(composition.bytes, 18);
+        // So is the variation selector asking for the emoji form.
+        assert_eq!(classes("❤\u{FE0F}"), [Class::Symbol, Class::Other]);
+    }
+
+    #[test]
+    fn combining_marks_are_counted_separately() {
+        let decomposed = "e\u{0301}";
+        assert_eq!(Class::of('\u{0301}'), Class::Other);
+        assert_eq!(classes(decomposed), [Class::Lower, Class::Other]);
+        assert_eq!(Composition::of(decomposed).chars, 2);
+        assert_eq!(Composition::of(decomposed).bytes, 3);
+
+        let composed = "é";
+        assert_eq!(classes(composed), [Class::Lower]);
+        assert_eq!(Composition::of(composed).chars, 1);
+        assert_eq!(Composition::of(composed).bytes, 2);
+    }
+
+    #[test]
+    fn counts_and_classes_cover_the_whole_password() {
+        let composition = Composition::of("Hunter 2!");
+        assert_eq!(composition.chars, 9);
+        assert_eq!(composition.bytes, 9);
+        assert_eq!(
+            composition.classes().collect::<Vec<_>>(),
+            [
+                Class::Lower,
+                Class::Upper,
+                Class::Digit,
+                Class::Symbol,
+                Class::Space
+            ]
+        );
+    }
+
+    #[test]
+    fn nothing_is_made_of_nothing() {
+        assert_eq!(Composition::of(""), Composition::default());
+        assert_eq!(classes(""), []);
+    }
+
+    #[test]
+    fn classes_come_in_a_fixed_order() {
+        assert_eq!(classes("!a"), classes("a!"));
+        assert_eq!(classes("1aA"), [Class::Lower, Class::Upper, Class::Digit]);
+    }
+}
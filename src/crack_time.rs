@@ -0,0 +1,136 @@
+/// An attacker's guessing speed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Profile {
+    pub name: String,
+    pub guesses_per_second: f64,
+}
+
+impl Profile {
+    fn new(name: &str, guesses_per_second: f64) -> Self {
+        Self {
+            name: name.to_owned(),
+            guesses_per_second,
+        }
+    }
+}
+
+pub fn default_profiles() -> Vec<Profile> {
+    vec![
+        // What a rate-limited login form lets through.
+        Profile::new("Online, throttled", 100. / 3600.),
+        Profile::new("Offline SHA-1, one GPU", 2.5e10),
+        Profile::new("Offline SHA-1, large GPU rig", 1e12),
+    ]
+}
+
+/// Guesses for an exhaustive search over the character classes `password`
+/// uses, as a base-10 logarithm so long passwords don't overflow.
+pub fn brute_force_guesses_log10(password: &str) -> f64 {
+    let uses = |class: fn(&char) -> bool| password.chars().any(|c| class(&c));
+    let charset = [
+        (uses(char::is_ascii_lowercase), 26),
+        (uses(char::is_ascii_uppercase), 26),
+        (uses(char::is_ascii_digit), 10),
+        (uses(char::is_ascii_punctuation), 33),
+        // Spaces and anything beyond ASCII; a deliberately modest allowance.
+        (password.chars().any(|c| !c.is_ascii_graphic()), 100),
+    ]
+    .into_iter()
+    .filter_map(|(used, size)| used.then_some(size))
+    .sum::<u32>();
+    password.chars().count() as f64 * f64::from(charset.max(1)).log10()
+}
+
+/// Worst-case seconds for `profile` to reach the guess count.
+pub fn seconds_to_crack(guesses_log10: f64, profile: &Profile) -> f64 {
+    10f64.powf(guesses_log10) / profile.guesses_per_second
+}
+
+/// Rounds `seconds` to the largest unit that fits, e.g. "3 weeks".
+pub fn humanize(seconds: f64) -> String {
+    const UNITS: [(&str, f64); 6] = [
+        ("second", 1.),
+        ("minute", 60.),
+        ("hour", 3600.),
+        ("day", 86_400.),
+        ("month", 2_629_746.),
+        ("year", 31_556_952.),
+    ];
+    if seconds < 1. {
+        return "less than a second".into();
+    }
+    if seconds >= 100. * 31_556_952. {
+        return "centuries".into();
+    }
+    let weeks = 604_800.;
+    let (unit, size) = UNITS
+        .iter()
+        .rev()
+        .find(|(_, size)| seconds >= *size)
+        .copied()
+        .unwrap_or(UNITS[0]);
+    // Weeks read better than "1 month" or "20 days" in between.
+    let (unit, size) = if unit == "day" && seconds >= 2. * weeks {
+        ("week", weeks)
+    } else {
+        (unit, size)
+    };
+    let count = (seconds / size).floor() as u64;
+    format!("{count} {unit}{}", if count == 1 { "" } else { "s" })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: f64 = 86_400.;
+    const YEAR: f64 = 31_556_952.;
+
+    #[test]
+    fn humanizes_at_each_units_boundary() {
+        for (seconds, expected) in [
+            (0., "less than a second"),
+            (0.999, "less than a second"),
+            (1., "1 second"),
+            (59.9, "59 seconds"),
+            (60., "1 minute"),
+            (3599., "59 minutes"),
+            (3600., "1 hour"),
+            (DAY - 1., "23 hours"),
+            (DAY, "1 day"),
+            (14. * DAY - 1., "13 days"),
+            (14. * DAY, "2 weeks"),
+            (30. * DAY, "4 weeks"),
+            (2_629_746., "1 month"),
+            (YEAR - 1., "11 months"),
+            (YEAR, "1 year"),
+            (100. * YEAR - 1., "99 years"),
+            (100. * YEAR, "centuries"),
+            (f64::INFINITY, "centuries"),
+        ] {
+            assert_eq!(humanize(seconds), expected, "{seconds} seconds");
+        }
+    }
+
+    #[test]
+    fn brute_force_counts_the_classes_used() {
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+        assert_eq!(brute_force_guesses_log10(""), 0.);
+        assert!(close(brute_force_guesses_log10("aaaa"), 4. * 26f64.log10()));
+        assert!(close(brute_force_guesses_log10("aA1!"), 4. * 95f64.log10()));
+        assert!(close(brute_force_guesses_log10("a b"), 3. * 126f64.log10()));
+        // Characters are counted, not bytes.
+        assert!(close(brute_force_guesses_log10("é"), 100f64.log10()));
+    }
+
+    #[test]
+    fn seconds_scale_with_the_profiles_speed() {
+        let rig = Profile::new("rig", 1e12);
+        assert_eq!(seconds_to_crack(12., &rig), 1.);
+        assert_eq!(seconds_to_crack(15., &rig), 1000.);
+        let [throttled, gpu, big] = default_profiles().try_into().unwrap();
+        assert!(throttled.guesses_per_second < gpu.guesses_per_second);
+        assert!(gpu.guesses_per_second < big.guesses_per_second);
+        assert_eq!(humanize(seconds_to_crack(3., &throttled)), "10 hours");
+    }
+}
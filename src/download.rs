@@ -0,0 +1,866 @@
+use std::fmt;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use flate2::Crc;
+use futures::channel::mpsc;
+use futures::stream::{self, StreamExt};
+use futures::{SinkExt, Stream, future};
+use tokio::fs::{File, OpenOptions};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+
+use crate::HashMode;
+use crate::error::{FailureKind, LookupError};
+use crate::pwned::RangeClient;
+use crate::retry::RetryPolicy;
+
+/// Every 5-hex-digit prefix, `00000` to `FFFFF`.
+pub const PREFIX_COUNT: u32 = 16u32.pow(5);
+
+/// How many prefixes are written between saves of the manifest.
+const CHECKPOINT_EVERY: u32 = 256;
+
+/// Extra rounds a prefix gets once the client has given up on it.
+const PREFIX_ATTEMPTS: u32 = 3;
+
+const REPORT_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Where a download stands, as reported to the UI.
+#[derive(Clone, Debug)]
+pub struct Progress {
+    /// Prefixes whose lines are in the output.
+    pub done: u32,
+    pub bytes: u64,
+    /// Prefix fetches that failed and had to be retried.
+    pub failures: u32,
+    /// Whether what's in the output is being checked against the manifest,
+    /// before carrying on or once every prefix is in.
+    pub verifying: bool,
+    /// Prefixes fetched in this session, and how many it has to fetch.
+    fetched: u32,
+    to_fetch: u32,
+    started: Instant,
+}
+
+impl Progress {
+    fn new(done: u32, bytes: u64, to_fetch: u32) -> Self {
+        Self {
+            done,
+            bytes,
+            failures: 0,
+            verifying: false,
+            fetched: 0,
+            to_fetch,
+            started: Instant::now(),
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.done == PREFIX_COUNT && !self.verifying
+    }
+
+    /// Extrapolated from the prefixes fetched in this session only, so a
+    /// resumed or repaired download doesn't look faster than it is.
+    pub fn eta(&self) -> Option<Duration> {
+        if self.fetched == 0 {
+            return None;
+        }
+        let per_prefix = self.started.elapsed().div_f64(f64::from(self.fetched));
+        Some(per_prefix.mul_f64(f64::from(self.to_fetch - self.fetched)))
+    }
+}
+
+/// The first line of a manifest, before its hash mode.
+const MANIFEST_HEADER: &str = "pwned-passwords-manifest 1";
+
+/// Where one prefix's lines are in the output, and their CRC-32.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Entry {
+    offset: u64,
+    len: u64,
+    crc: u32,
+}
+
+/// Which prefixes are in the output, where and with what checksum,
+/// recorded as each is written so a download can be checked, resumed and
+/// repaired prefix by prefix:
+///
+/// ```text
+/// pwned-passwords-manifest 1 SHA-1
+/// 00000 0 20467 5c1e0b7d
+/// 00001 20467 20339 0e9a44f2
+/// ...
+/// ```
+#[derive(Clone, Debug)]
+struct Manifest {
+    mode: HashMode,
+    /// Indexed by prefix.
+    entries: Vec<Option<Entry>>,
+}
+
+impl Manifest {
+    fn new(mode: HashMode) -> Self {
+        Self {
+            mode,
+            entries: vec![None; PREFIX_COUNT as usize],
+        }
+    }
+
+    /// `None` if it isn't a manifest this version reads. A line that doesn't
+    /// parse is skipped, so only its prefix is fetched again.
+    fn parse(contents: &str) -> Option<Self> {
+        let mut lines = contents.lines();
+        let mode = match lines.next()?.strip_prefix(MANIFEST_HEADER)?.trim() {
+            "SHA-1" => HashMode::Sha1,
+            "NTLM" => HashMode::Ntlm,
+            _ => return None,
+        };
+        let mut manifest = Self::new(mode);
+        for line in lines {
+            if let Some((prefix, entry)) = parse_entry(line) {
+                manifest.entries[prefix as usize] = Some(entry);
+            }
+        }
+        Some(manifest)
+    }
+
+    fn header(mode: HashMode) -> String {
+        format!("{MANIFEST_HEADER} {mode}\n")
+    }
+
+    /// Checks every entry against the bytes of `output`, dropping those
+    /// that don't match or run past its end, and returns their prefixes.
+    fn check(&mut self, output: &Path) -> io::Result<Vec<u32>> {
+        let mut file = match std::fs::File::open(output) {
+            Ok(file) => file,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                let lost = (0..PREFIX_COUNT)
+                    .filter(|&prefix| self.entries[prefix as usize].take().is_some())
+                    .collect();
+                return Ok(lost);
+            }
+            Err(error) => return Err(error),
+        };
+        let len = file.metadata()?.len();
+        let (mut corrupt, mut bytes) = (Vec::new(), Vec::new());
+        for (prefix, slot) in (0..PREFIX_COUNT).zip(&mut self.entries) {
+            let Some(entry) = *slot else {
+                continue;
+            };
+            let matches = if entry.offset.saturating_add(entry.len) <= len {
+                bytes.resize(entry.len as usize, 0);
+                file.seek(SeekFrom::Start(entry.offset))?;
+                file.read_exact(&mut bytes)?;
+                crc32(&bytes) == entry.crc
+            } else {
+                false
+            };
+            if !matches {
+                corrupt.push(prefix);
+                *slot = None;
+            }
+        }
+        Ok(corrupt)
+    }
+}
+
+fn parse_entry(line: &str) -> Option<(u32, Entry)> {
+    let mut fields = line.split(' ');
+    let prefix = fields.next().filter(|prefix| prefix.len() == 5)?;
+    let prefix = u32::from_str_radix(prefix, 16).ok()?;
+    let entry = Entry {
+        offset: fields.next()?.parse().ok()?,
+        len: fields.next()?.parse().ok()?,
+        crc: u32::from_str_radix(fields.next()?, 16).ok()?,
+    };
+    (fields.next().is_none() && prefix < PREFIX_COUNT).then_some((prefix, entry))
+}
+
+fn entry_line(prefix: u32, entry: Entry) -> String {
+    format!(
+        "{prefix:05X} {} {} {:08x}\n",
+        entry.offset, entry.len, entry.crc
+    )
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = Crc::new();
+    crc.update(bytes);
+    crc.sum()
+}
+
+fn manifest_path(output: &Path) -> PathBuf {
+    with_suffix(output, ".manifest")
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(suffix);
+    PathBuf::from(path)
+}
+
+/// How a downloaded dataset compares with its manifest.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Verification {
+    pub mode: HashMode,
+    /// Prefixes whose lines match their checksum.
+    pub verified: u32,
+    /// Prefixes whose lines don't, or aren't all there.
+    pub corrupt: Vec<String>,
+    /// Prefixes the manifest has no entry for.
+    pub missing: u32,
+    /// Bytes of the output that no entry accounts for, as an interrupted
+    /// write leaves behind.
+    pub unaccounted: u64,
+}
+
+impl Verification {
+    pub fn is_complete(&self) -> bool {
+        self.corrupt.is_empty() && self.missing == 0 && self.unaccounted == 0
+    }
+}
+
+impl fmt::Display for Verification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_complete() {
+            return write!(
+                f,
+                "all {} {} prefixes match the manifest",
+                self.verified, self.mode
+            );
+        }
+        write!(
+            f,
+            "{} of {PREFIX_COUNT} {} prefixes match the manifest",
+            self.verified, self.mode
+        )?;
+        match self.corrupt.as_slice() {
+            [] => {}
+            [prefix] => write!(f, ", 1 doesn't ({prefix})")?,
+            [first, ..] => write!(f, ", {} don't (the first is {first})", self.corrupt.len())?,
+        }
+        match self.missing {
+            0 => {}
+            1 => write!(f, ", 1 isn't downloaded")?,
+            missing => write!(f, ", {missing} aren't downloaded")?,
+        }
+        if self.unaccounted > 0 {
+            write!(f, ", {} bytes aren't accounted for", self.unaccounted)?;
+        }
+        if !self.corrupt.is_empty() || self.missing > 0 {
+            write!(f, "; downloading again fetches only those")?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks the dataset downloaded to `output` against its manifest, reading
+/// every byte but with no network. It blocks for as long as that takes.
+pub fn verify(output: &Path) -> Result<Verification, String> {
+    let manifest_path = manifest_path(output);
+    let contents = std::fs::read_to_string(&manifest_path).map_err(|error| match error.kind() {
+        io::ErrorKind::NotFound => format!(
+            "{} has no manifest to check it against; only a download makes one",
+            output.display()
+        ),
+        _ => format!("{}: {error}", manifest_path.display()),
+    })?;
+    let mut manifest = Manifest::parse(&contents).ok_or_else(|| {
+        format!(
+            "{} isn't a manifest this version reads",
+            manifest_path.display()
+        )
+    })?;
+    // Corrupt or not, what the manifest lists is accounted for.
+    let accounted: u64 = manifest
+        .entries
+        .iter()
+        .flatten()
+        .map(|entry| entry.len)
+        .sum();
+    let corrupt = manifest
+        .check(output)
+        .map_err(|error| format!("{}: {error}", output.display()))?;
+    let len = std::fs::metadata(output).map_or(0, |metadata| metadata.len());
+    let verified = manifest.entries.iter().flatten().count() as u32;
+    Ok(Verification {
+        mode: manifest.mode,
+        verified,
+        missing: PREFIX_COUNT - verified - corrupt.len() as u32,
+        corrupt: corrupt
+            .iter()
+            .map(|prefix| format!("{prefix:05X}"))
+            .collect(),
+        unaccounted: len.saturating_sub(accounted),
+    })
+}
+
+/// Downloads every range into `output` as one `HASH:COUNT` file ordered by
+/// hash, ready for offline mode, recording each prefix in a manifest beside
+/// it (`output.manifest`) as it's written.
+///
+/// An interrupted download picks up where it stopped, once what's there
+/// has been checked against the manifest. Prefixes that don't match, or
+/// that the manifest lost, are fetched again on their own: if they're in
+/// the middle, the output is rebuilt beside it (`output.repair`) from the
+/// prefixes that did match and the fetched ones, and renamed into place.
+/// Once every prefix is in, the whole output is checked again.
+pub fn download(
+    client: RangeClient,
+    mode: HashMode,
+    output: PathBuf,
+    concurrency: usize,
+) -> impl Stream<Item = Result<Progress, String>> {
+    let (sender, receiver) = mpsc::channel(1);
+    // The download runs as this stream is polled, ending it with its error
+    // if it fails.
+    let run = stream::once(async move {
+        run(client, mode, &output, concurrency, sender)
+            .await
+            .map_err(|e| e.to_string())
+    })
+    .filter_map(|result| future::ready(result.err().map(Err)));
+    stream::select(receiver.map(Ok), run)
+}
+
+/// Where a prefix's lines come from.
+enum Chunk {
+    /// Already downloaded, at this entry of the old output.
+    Kept(Entry),
+    /// Fetched, with the failed attempts it took.
+    Fetched(String, u32),
+}
+
+async fn run(
+    client: RangeClient,
+    mode: HashMode,
+    output: &Path,
+    concurrency: usize,
+    mut sender: mpsc::Sender<Progress>,
+) -> Result<(), DownloadError> {
+    let manifest_path = manifest_path(output);
+    let manifest = match tokio::fs::read_to_string(&manifest_path).await {
+        Ok(contents) => Manifest::parse(&contents)
+            .filter(|manifest| manifest.mode == mode)
+            .unwrap_or_else(|| Manifest::new(mode)),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Manifest::new(mode),
+        Err(error) => return Err(error.into()),
+    };
+    let mut checking = Progress::new(0, 0, 0);
+    checking.verifying = true;
+    let _ = sender.send(checking).await;
+    let checked = output.to_owned();
+    let (manifest, corrupt) = tokio::task::spawn_blocking(move || {
+        let mut manifest = manifest;
+        let corrupt = manifest.check(&checked)?;
+        io::Result::Ok((manifest, corrupt))
+    })
+    .await
+    .map_err(io::Error::other)??;
+    if !corrupt.is_empty() {
+        tracing::warn!(
+            prefixes = corrupt.len(),
+            "downloaded prefixes don't match the manifest; fetching them again"
+        );
+    }
+
+    // The prefixes one after another at the start of the output can stay
+    // where they are. If any others matched, the output is rebuilt, so
+    // only what's missing is fetched; otherwise the rest is appended.
+    let (mut kept, mut end) = (0, 0);
+    while let Some(Some(entry)) = manifest.entries.get(kept)
+        && entry.offset == end
+    {
+        (kept, end) = (kept + 1, end + entry.len);
+    }
+    let rebuild = manifest.entries[kept..].iter().any(Option::is_some);
+    let (target, target_manifest) = if rebuild {
+        (
+            with_suffix(output, ".repair"),
+            with_suffix(&manifest_path, ".repair"),
+        )
+    } else {
+        (output.to_owned(), manifest_path.clone())
+    };
+    let start = if rebuild { 0 } else { kept as u32 };
+    let mut header = Manifest::header(mode);
+    if !rebuild {
+        for (prefix, entry) in (0..start).zip(manifest.entries.iter().flatten()) {
+            header.push_str(&entry_line(prefix, *entry));
+        }
+    }
+    // Written whole and renamed, so it never loses the entries it had.
+    let temp = with_suffix(&target_manifest, ".tmp");
+    tokio::fs::write(&temp, header).await?;
+    tokio::fs::rename(&temp, &target_manifest).await?;
+    let mut manifest_file = OpenOptions::new()
+        .append(true)
+        .open(&target_manifest)
+        .await?;
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(rebuild)
+        .open(&target)
+        .await?;
+    // Drops whatever was written after the last prefix kept.
+    file.set_len(if rebuild { 0 } else { end }).await?;
+    file.seek(SeekFrom::End(0)).await?;
+    let mut old = if rebuild {
+        Some(File::open(output).await?)
+    } else {
+        None
+    };
+
+    let entries = &manifest.entries;
+    let to_fetch = (start..PREFIX_COUNT)
+        .filter(|&prefix| !rebuild || entries[prefix as usize].is_none())
+        .count() as u32;
+    let mut progress = Progress::new(start, if rebuild { 0 } else { end }, to_fetch);
+    let _ = sender.send(progress.clone()).await;
+
+    // `buffered` keeps prefix order, so the file comes out sorted.
+    let client = &client;
+    let mut chunks = stream::iter(start..PREFIX_COUNT)
+        .map(|prefix| async move {
+            match entries[prefix as usize].filter(|_| rebuild) {
+                Some(entry) => Ok(Chunk::Kept(entry)),
+                None => fetch_prefix(client, mode, prefix)
+                    .await
+                    .map(|(lines, failures)| Chunk::Fetched(lines, failures)),
+            }
+        })
+        .buffered(concurrency.max(1));
+    let mut pending = String::new();
+    let mut last_report = Instant::now();
+    while let Some(chunk) = chunks.next().await {
+        let bytes = match chunk {
+            Ok(Chunk::Kept(entry)) => {
+                let old = old.as_mut().expect("only a rebuild keeps prefixes");
+                let mut bytes = vec![0; entry.len as usize];
+                old.seek(SeekFrom::Start(entry.offset)).await?;
+                old.read_exact(&mut bytes).await?;
+                bytes
+            }
+            Ok(Chunk::Fetched(lines, failures)) => {
+                progress.fetched += 1;
+                progress.failures += failures;
+                lines.into_bytes()
+            }
+            Err(error) => {
+                save(&mut file, &mut manifest_file, &mut pending).await?;
+                return Err(error);
+            }
+        };
+        file.write_all(&bytes).await?;
+        let entry = Entry {
+            offset: progress.bytes,
+            len: bytes.len() as u64,
+            crc: crc32(&bytes),
+        };
+        pending.push_str(&entry_line(progress.done, entry));
+        progress.done += 1;
+        progress.bytes += entry.len;
+
+        if progress.done.is_multiple_of(CHECKPOINT_EVERY) {
+            save(&mut file, &mut manifest_file, &mut pending).await?;
+        }
+        if last_report.elapsed() >= REPORT_INTERVAL {
+            last_report = Instant::now();
+            let _ = sender.send(progress.clone()).await;
+        }
+    }
+    drop(chunks);
+    save(&mut file, &mut manifest_file, &mut pending).await?;
+    file.sync_all().await?;
+    drop((file, manifest_file, old));
+    if rebuild {
+        tokio::fs::rename(&target, output).await?;
+        tokio::fs::rename(&target_manifest, &manifest_path).await?;
+    }
+
+    progress.verifying = true;
+    let _ = sender.send(progress.clone()).await;
+    let checked = output.to_owned();
+    let verification = tokio::task::spawn_blocking(move || verify(&checked))
+        .await
+        .map_err(io::Error::other)?
+        .map_err(DownloadError::Verify)?;
+    if !verification.is_complete() {
+        return Err(DownloadError::Verify(verification.to_string()));
+    }
+    progress.verifying = false;
+    let _ = sender.send(progress).await;
+    Ok(())
+}
+
+/// Makes what's been written durable, then records it in the manifest, so
+/// the manifest never lists lines that could still be lost.
+async fn save(
+    file: &mut File,
+    manifest: &mut File,
+    pending: &mut String,
+) -> Result<(), DownloadError> {
+    file.flush().await?;
+    file.sync_data().await?;
+    manifest.write_all(pending.as_bytes()).await?;
+    manifest.sync_data().await?;
+    pending.clear();
+    Ok(())
+}
+
+/// The sorted lines for one prefix, and how many failed attempts it took.
+async fn fetch_prefix(
+    client: &RangeClient,
+    mode: HashMode,
+    prefix: u32,
+) -> Result<(String, u32), DownloadError> {
+    let prefix = format!("{prefix:05X}");
+    let policy = RetryPolicy::default();
+    let mut failures = 0;
+    loop {
+        match client
+            .clone()
+            .fetch_range((mode, prefix.clone()), None)
+            .await
+        {
+            Ok((range, _)) => {
+                let mut entries: Vec<_> = range.iter().collect();
+                entries.sort_unstable();
+                let mut lines = String::with_capacity(entries.len() * 45);
+                for (suffix, count) in entries {
+                    lines.push_str(&prefix);
+                    lines.push_str(suffix);
+                    lines.push(':');
+                    lines.push_str(&count.to_string());
+                    lines.push('\n');
+                }
+                return Ok((lines, failures));
+            }
+            Err(error) if failures < PREFIX_ATTEMPTS => {
+                failures += 1;
+                let wait = match error.kind {
+                    FailureKind::RateLimited(wait) => wait,
+                    _ => policy.delay(failures, rand::random()),
+                };
+                tokio::time::sleep(wait).await;
+            }
+            Err(error) => return Err(DownloadError::Prefix { prefix, error }),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum DownloadError {
+    Io(io::Error),
+    Prefix { prefix: String, error: LookupError },
+    Verify(String),
+}
+
+impl fmt::Display for DownloadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DownloadError::Io(error) => write!(f, "could not write the dataset: {error}"),
+            DownloadError::Prefix { prefix, error } => {
+                write!(f, "gave up on prefix {prefix}: {error}")
+            }
+            DownloadError::Verify(error) => {
+                write!(f, "the download doesn't check out: {error}")
+            }
+        }
+    }
+}
+
+impl From<io::Error> for DownloadError {
+    fn from(error: io::Error) -> Self {
+        DownloadError::Io(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Write;
+
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+    use crate::metrics::Metrics;
+    use crate::pwned::{self, InFlight, ProxySettings, SocksSettings};
+    use crate::rate_limit::RateLimiter;
+
+    fn client(endpoint: &str) -> RangeClient {
+        let settings = pwned::Settings {
+            endpoint: pwned::parse_endpoint(endpoint).unwrap(),
+            padding: false,
+            decoys: false,
+            decoy_count: 0,
+            timeout: Duration::from_secs(5),
+            proxy: ProxySettings::default(),
+            socks: SocksSettings::default(),
+            rate_limit: 1000,
+        };
+        let http = pwned::build_client(&settings).unwrap();
+        let (in_flight, metrics) = (InFlight::default(), Metrics::default());
+        RangeClient::new(http, settings, in_flight, metrics, RateLimiter::default())
+    }
+
+    /// A manifest listing every prefix but those in `missing` as empty, so
+    /// a download resumed from it fetches only those.
+    fn all_but(missing: &[u32]) -> String {
+        let mut manifest = Manifest::header(HashMode::Sha1);
+        for prefix in (0..PREFIX_COUNT).filter(|prefix| !missing.contains(prefix)) {
+            let _ = writeln!(manifest, "{prefix:05X} 0 0 00000000");
+        }
+        manifest
+    }
+
+    async fn serving_one_line() -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string("0005AD76BD555C1D6D771DE417A4B87E4B4:10\r\n"),
+            )
+            .mount(&server)
+            .await;
+        server
+    }
+
+    async fn requested(server: &MockServer) -> Vec<String> {
+        let mut paths: Vec<_> = server
+            .received_requests()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|request| request.url.path().to_owned())
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn manifest_lines_round_trip() {
+        let entry = Entry {
+            offset: 20467,
+            len: 20339,
+            crc: 0x0e9a44f2,
+        };
+        let line = entry_line(1, entry);
+        assert_eq!(line, "00001 20467 20339 0e9a44f2\n");
+        assert_eq!(parse_entry(line.trim_end()), Some((1, entry)));
+        for garbage in [
+            "",
+            "00001 1 2",
+            "00001 1 2 zz",
+            "0001 1 2 3",
+            "00001 1 2 3 4",
+        ] {
+            assert_eq!(parse_entry(garbage), None, "{garbage:?}");
+        }
+    }
+
+    #[test]
+    fn a_manifest_skips_lines_it_cant_read() {
+        let contents =
+            format!("{MANIFEST_HEADER} NTLM\n00000 0 5 00000001\nnonsense\n00002 5 5 00000002\n");
+        let manifest = Manifest::parse(&contents).unwrap();
+        assert_eq!(manifest.mode, HashMode::Ntlm);
+        assert_eq!(manifest.entries.iter().flatten().count(), 2);
+        assert!(manifest.entries[1].is_none());
+        assert!(Manifest::parse("pwned-passwords-manifest 2 SHA-1\n").is_none());
+        assert!(Manifest::parse(&format!("{MANIFEST_HEADER} MD5\n")).is_none());
+    }
+
+    #[test]
+    fn verification_finds_corrupt_missing_and_stray_bytes() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("pwned.txt");
+        let (first, second) = ("00000AAAA:1\n", "00001BBBB:2\n");
+        std::fs::write(&output, format!("{first}{second}stray")).unwrap();
+        let mut manifest = Manifest::header(HashMode::Sha1);
+        manifest.push_str(&entry_line(
+            0,
+            Entry {
+                offset: 0,
+                len: 12,
+                crc: crc32(first.as_bytes()),
+            },
+        ));
+        manifest.push_str(&entry_line(
+            1,
+            Entry {
+                offset: 12,
+                len: 12,
+                crc: 0xdeadbeef,
+            },
+        ));
+        std::fs::write(manifest_path(&output), manifest).unwrap();
+
+        let verification = verify(&output).unwrap();
+        assert_eq!(verification.verified, 1);
+        assert_eq!(verification.corrupt, ["00001"]);
+        assert_eq!(verification.missing, PREFIX_COUNT - 2);
+        assert_eq!(verification.unaccounted, 5);
+        assert!(!verification.is_complete());
+        assert_eq!(
+            verification.to_string(),
+            "1 of 1048576 SHA-1 prefixes match the manifest, 1 doesn't (00001), \
+             1048574 aren't downloaded, 5 bytes aren't accounted for; \
+             downloading again fetches only those"
+        );
+    }
+
+    #[test]
+    fn verification_needs_a_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let error = verify(&dir.path().join("pwned.txt")).unwrap_err();
+        assert!(error.ends_with("has no manifest to check it against; only a download makes one"));
+    }
+
+    #[test]
+    fn eta_counts_only_this_sessions_fetches() {
+        let mut progress = Progress::new(1000, 0, 200);
+        assert_eq!(progress.eta(), None);
+        progress.fetched = 100;
+        progress.started = Instant::now() - Duration::from_secs(10);
+        let eta = progress.eta().unwrap();
+        assert!(
+            eta >= Duration::from_secs(10) && eta < Duration::from_secs(11),
+            "{eta:?}"
+        );
+        assert!(!progress.is_finished());
+        let done = Progress::new(PREFIX_COUNT, 0, 0);
+        assert!(done.is_finished());
+    }
+
+    #[tokio::test]
+    async fn a_resumed_download_fetches_only_what_it_lacks() {
+        let server = serving_one_line().await;
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("pwned.txt");
+        std::fs::write(&output, "").unwrap();
+        let missing = [PREFIX_COUNT - 2, PREFIX_COUNT - 1];
+        std::fs::write(manifest_path(&output), all_but(&missing)).unwrap();
+
+        let updates: Vec<_> = download(client(&server.uri()), HashMode::Sha1, output.clone(), 2)
+            .collect()
+            .await;
+        let progress: Vec<_> = updates.into_iter().map(Result::unwrap).collect();
+        let first = progress
+            .iter()
+            .find(|progress| !progress.verifying)
+            .unwrap();
+        assert_eq!((first.done, first.to_fetch), (PREFIX_COUNT - 2, 2));
+        let last = progress.last().unwrap();
+        assert!(last.is_finished());
+        assert_eq!((last.fetched, last.failures), (2, 0));
+
+        assert_eq!(requested(&server).await, ["/range/FFFFE", "/range/FFFFF"]);
+        assert_eq!(
+            std::fs::read_to_string(&output).unwrap(),
+            "FFFFE0005AD76BD555C1D6D771DE417A4B87E4B4:10\nFFFFF0005AD76BD555C1D6D771DE417A4B87E4B4:10\n"
+        );
+        assert!(verify(&output).unwrap().is_complete());
+    }
+
+    #[tokio::test]
+    async fn a_corrupt_prefix_is_fetched_again_on_its_own() {
+        let server = serving_one_line().await;
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("pwned.txt");
+        std::fs::write(&output, "").unwrap();
+        let manifest =
+            all_but(&[PREFIX_COUNT - 1]).replace("00005 0 0 00000000", "00005 0 0 12345678");
+        std::fs::write(manifest_path(&output), manifest).unwrap();
+
+        let updates: Vec<_> = download(client(&server.uri()), HashMode::Sha1, output.clone(), 2)
+            .collect()
+            .await;
+        assert!(updates.last().unwrap().as_ref().unwrap().is_finished());
+        assert_eq!(requested(&server).await, ["/range/00005", "/range/FFFFF"]);
+        assert!(!with_suffix(&output, ".repair").exists());
+        assert!(verify(&output).unwrap().is_complete());
+    }
+
+    #[tokio::test]
+    async fn a_prefix_whose_bytes_were_damaged_is_fetched_again_and_the_rest_kept() {
+        let server = serving_one_line().await;
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("pwned.txt");
+        let lines = ["00000AAAA:1\n", "00001BBBB:2\n", "00002CCCC:3\n"];
+        let mut manifest = Manifest::header(HashMode::Sha1);
+        for (prefix, line) in (0..).zip(lines) {
+            let entry = Entry {
+                offset: 12 * u64::from(prefix),
+                len: 12,
+                crc: crc32(line.as_bytes()),
+            };
+            manifest.push_str(&entry_line(prefix, entry));
+        }
+        for prefix in 3..PREFIX_COUNT - 1 {
+            let _ = writeln!(manifest, "{prefix:05X} 36 0 00000000");
+        }
+        std::fs::write(manifest_path(&output), manifest).unwrap();
+        // One bit flipped in the second prefix's count.
+        std::fs::write(&output, lines.concat().replace("BBBB:2", "BBBB:3")).unwrap();
+        assert_eq!(verify(&output).unwrap().corrupt, ["00001"]);
+
+        let updates: Vec<_> = download(client(&server.uri()), HashMode::Sha1, output.clone(), 2)
+            .collect()
+            .await;
+        assert!(updates.last().unwrap().as_ref().unwrap().is_finished());
+        assert_eq!(requested(&server).await, ["/range/00001", "/range/FFFFF"]);
+        let dataset = std::fs::read_to_string(&output).unwrap();
+        assert!(dataset.starts_with(lines[0]), "{dataset}");
+        assert!(dataset.contains(lines[2]), "{dataset}");
+        assert!(dataset.contains("000010005AD76BD555C1D6D771DE417A4B87E4B4:10\n"));
+        assert!(!dataset.contains("BBBB"), "{dataset}");
+        assert!(!with_suffix(&output, ".repair").exists());
+        assert!(verify(&output).unwrap().is_complete());
+    }
+
+    #[tokio::test]
+    async fn a_prefix_the_manifest_lost_is_fetched_again_on_its_own() {
+        let server = serving_one_line().await;
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("pwned.txt");
+        std::fs::write(&output, "").unwrap();
+        let manifest = all_but(&[PREFIX_COUNT - 1]).replace("00007 0 0 00000000", "00007 0 0");
+        std::fs::write(manifest_path(&output), manifest).unwrap();
+        assert_eq!(verify(&output).unwrap().missing, 2);
+
+        let updates: Vec<_> = download(client(&server.uri()), HashMode::Sha1, output.clone(), 2)
+            .collect()
+            .await;
+        assert!(updates.last().unwrap().as_ref().unwrap().is_finished());
+        assert_eq!(requested(&server).await, ["/range/00007", "/range/FFFFF"]);
+        assert!(verify(&output).unwrap().is_complete());
+    }
+
+    #[tokio::test]
+    async fn a_failing_prefix_stops_the_download_and_keeps_what_was_written() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("pwned.txt");
+        std::fs::write(&output, "").unwrap();
+        std::fs::write(manifest_path(&output), all_but(&[PREFIX_COUNT - 1])).unwrap();
+
+        let updates: Vec<_> = download(client(&server.uri()), HashMode::Sha1, output.clone(), 1)
+            .collect()
+            .await;
+        let error = updates.last().unwrap().as_ref().unwrap_err();
+        assert!(error.starts_with("gave up on prefix FFFFF: "), "{error}");
+        let verification = verify(&output).unwrap();
+        assert_eq!(
+            (verification.verified, verification.missing),
+            (PREFIX_COUNT - 1, 1)
+        );
+    }
+}
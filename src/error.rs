@@ -0,0 +1,190 @@
+use std::io;
+use std::time::Duration;
+
+use thiserror::Error;
+use url::Url;
+
+/// A range body that isn't `SUFFIX:COUNT` lines, such as a truncated download
+/// or an HTML error page served with a success status.
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum ParseError {
+    #[error("malformed line {line_no}: {content:?}")]
+    MalformedLine { line_no: usize, content: String },
+    /// The API lists each suffix once, so a repeat means the body is corrupt.
+    #[error("suffix {0} is listed twice")]
+    Duplicate(String),
+    #[error("the response was empty")]
+    EmptyBody,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FailureKind {
+    /// The API couldn't be reached at all: no DNS, no route, or refused.
+    Offline,
+    /// SOCKS5 routing is on but the SOCKS proxy itself couldn't be reached.
+    SocksUnreachable,
+    /// The API answered 429; holds how long it asked us to wait.
+    RateLimited(Duration),
+    /// The API answered with an error status other than 429.
+    UnexpectedStatus(u16),
+    /// The API answered with a success status but the body isn't a range.
+    Unparsable(ParseError),
+    /// A timeout, TLS failure or dropped connection.
+    Network,
+    Other,
+}
+
+/// What went wrong underneath a [`LookupError`], for a "Details" view.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ErrorDetails {
+    pub status: Option<u16>,
+    /// reqwest's category for the error, such as `timeout` or `connect`.
+    pub kind: Option<&'static str>,
+    /// The request URL, hash prefix included.
+    pub url: Option<Url>,
+    pub attempts: Option<u32>,
+    /// The error and each of its sources, outermost first.
+    pub chain: Vec<String>,
+}
+
+/// A failed range lookup, already phrased for the user.
+#[derive(Clone, Debug, Error)]
+#[error("{message}")]
+pub struct LookupError {
+    pub kind: FailureKind,
+    pub message: String,
+    /// Boxed, since it's only read when the user opens it.
+    pub details: Box<ErrorDetails>,
+}
+
+impl LookupError {
+    /// ```
+    /// use cybersec_wow::error::{FailureKind, LookupError};
+    ///
+    /// let error = LookupError::other("the cache is locked");
+    /// assert!(matches!(error.kind, FailureKind::Other));
+    /// assert_eq!(error.to_string(), "the cache is locked");
+    /// ```
+    pub fn other(message: impl Into<String>) -> Self {
+        let message = message.into();
+        Self {
+            kind: FailureKind::Other,
+            details: Box::new(ErrorDetails {
+                chain: vec![message.clone()],
+                ..ErrorDetails::default()
+            }),
+            message,
+        }
+    }
+
+    /// A body that came back with a success status but isn't a range, which
+    /// must not be mistaken for "not found".
+    ///
+    /// ```
+    /// use cybersec_wow::error::{FailureKind, LookupError, ParseError};
+    ///
+    /// let error = LookupError::unparsable(ParseError::EmptyBody);
+    /// assert!(matches!(error.kind, FailureKind::Unparsable(ParseError::EmptyBody)));
+    /// assert!(error.to_string().starts_with("The response could not be parsed"));
+    /// ```
+    pub fn unparsable(error: ParseError) -> Self {
+        Self {
+            message: format!("The response could not be parsed ({error})"),
+            details: Box::new(ErrorDetails {
+                chain: vec![error.to_string()],
+                ..ErrorDetails::default()
+            }),
+            kind: FailureKind::Unparsable(error),
+        }
+    }
+}
+
+/// An I/O error under a lookup, such as a [`Fetch`]'s socket failing.
+/// Unreachable hosts and networks are [`FailureKind::Offline`], timeouts and
+/// dropped connections [`FailureKind::Network`], and the rest
+/// [`FailureKind::Other`].
+///
+/// [`Fetch`]: crate::fetch::Fetch
+impl From<io::Error> for LookupError {
+    fn from(error: io::Error) -> Self {
+        let kind = match error.kind() {
+            kind if is_unreachable(kind) => FailureKind::Offline,
+            io::ErrorKind::TimedOut
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::NotConnected
+            | io::ErrorKind::BrokenPipe
+            | io::ErrorKind::UnexpectedEof => FailureKind::Network,
+            _ => FailureKind::Other,
+        };
+        Self {
+            kind,
+            message: error.to_string(),
+            details: Box::new(ErrorDetails {
+                chain: chain(&error),
+                ..ErrorDetails::default()
+            }),
+        }
+    }
+}
+
+/// Whether an I/O error of `kind` means no connection could be made at all.
+pub(crate) fn is_unreachable(kind: io::ErrorKind) -> bool {
+    matches!(
+        kind,
+        io::ErrorKind::ConnectionRefused
+            | io::ErrorKind::NetworkUnreachable
+            | io::ErrorKind::HostUnreachable
+            | io::ErrorKind::NetworkDown
+            | io::ErrorKind::AddrNotAvailable
+    )
+}
+
+/// `error` and each of its sources, outermost first.
+pub(crate) fn chain(error: &(dyn std::error::Error + 'static)) -> Vec<String> {
+    let mut chain = Vec::new();
+    let mut source = Some(error);
+    while let Some(error) = source {
+        chain.push(error.to_string());
+        source = error.source();
+    }
+    chain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn io_errors_are_classified_by_their_kind() {
+        for (kind, expected) in [
+            (io::ErrorKind::ConnectionRefused, FailureKind::Offline),
+            (io::ErrorKind::NetworkUnreachable, FailureKind::Offline),
+            (io::ErrorKind::HostUnreachable, FailureKind::Offline),
+            (io::ErrorKind::NetworkDown, FailureKind::Offline),
+            (io::ErrorKind::AddrNotAvailable, FailureKind::Offline),
+            (io::ErrorKind::TimedOut, FailureKind::Network),
+            (io::ErrorKind::ConnectionReset, FailureKind::Network),
+            (io::ErrorKind::UnexpectedEof, FailureKind::Network),
+            (io::ErrorKind::BrokenPipe, FailureKind::Network),
+            (io::ErrorKind::PermissionDenied, FailureKind::Other),
+            (io::ErrorKind::InvalidData, FailureKind::Other),
+        ] {
+            assert_eq!(
+                LookupError::from(io::Error::from(kind)).kind,
+                expected,
+                "{kind:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn an_io_error_keeps_its_message_and_sources() {
+        let inner = io::Error::other("certificate has expired");
+        let error = LookupError::from(io::Error::new(io::ErrorKind::ConnectionAborted, inner));
+        assert_eq!(error.kind, FailureKind::Network);
+        assert_eq!(error.message, "certificate has expired");
+        assert_eq!(error.details.chain[0], "certificate has expired");
+        assert_eq!(error.details.status, None);
+    }
+}
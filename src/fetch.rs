@@ -0,0 +1,317 @@
+use std::future::Future;
+
+use reqwest::Client;
+use url::Url;
+
+use crate::error::{FailureKind, LookupError};
+use crate::range::parse_range;
+use crate::record::CheckRecord;
+use crate::severity::Thresholds;
+use crate::{
+    BreachResult, HashMode, LookupSource, Normalization, hash_password, hash_prefix_of,
+    hash_suffix_of,
+};
+
+pub const DEFAULT_ENDPOINT: &str = "https://api.pwnedpasswords.com";
+
+/// Gets a range body over HTTP. The app's [`RangeClient`] does this itself,
+/// with retries, proxies, decoys and caching; this is for where none of that
+/// can run, such as a browser.
+///
+/// ```
+/// use cybersec_wow::error::LookupError;
+/// use cybersec_wow::fetch::Fetch;
+/// use url::Url;
+///
+/// /// Answers every request with the same body.
+/// struct Canned(&'static str);
+///
+/// impl Fetch for Canned {
+///     async fn text(&self, _url: Url) -> Result<String, LookupError> {
+///         Ok(self.0.to_owned())
+///     }
+/// }
+/// ```
+///
+/// [`RangeClient`]: crate::pwned::RangeClient
+pub trait Fetch {
+    /// The body at `url`, which a success status must have come with.
+    fn text(&self, url: Url) -> impl Future<Output = Result<String, LookupError>>;
+}
+
+/// Looks `hash` up in its range at `endpoint`, through `fetch`.
+///
+/// ```
+/// # use cybersec_wow::error::LookupError;
+/// # use url::Url;
+/// use cybersec_wow::fetch::{Fetch, lookup, parse_endpoint};
+/// use cybersec_wow::{BreachResult, HashMode, Normalization, hash_password};
+/// # struct Canned(&'static str);
+/// # impl Fetch for Canned {
+/// #     async fn text(&self, _url: Url) -> Result<String, LookupError> {
+/// #         Ok(self.0.to_owned())
+/// #     }
+/// # }
+///
+/// let endpoint = parse_endpoint("https://api.pwnedpasswords.com").unwrap();
+/// let fetch = Canned("D66A63D4BF1747940578EC3D0103530E21D:17206891");
+/// let hash = hash_password("hunter2", HashMode::Sha1, Normalization::Off);
+/// let result = futures::executor::block_on(lookup(&fetch, &endpoint, &hash, HashMode::Sha1));
+/// assert_eq!(result.unwrap(), BreachResult::Found { count: 17206891 });
+/// ```
+pub async fn lookup(
+    fetch: &impl Fetch,
+    endpoint: &Url,
+    hash: &str,
+    mode: HashMode,
+) -> Result<BreachResult, LookupError> {
+    let body = fetch
+        .text(range_url(endpoint, hash_prefix_of(hash), mode))
+        .await?;
+    let range = parse_range(&body).map_err(LookupError::unparsable)?;
+    Ok(BreachResult::from_count(range.get(hash_suffix_of(hash))))
+}
+
+/// A plain reqwest client asking for padded ranges, with none of the
+/// [`RangeClient`]'s retries or caching. In a browser that's its `fetch`,
+/// through reqwest's wasm backend.
+///
+/// [`RangeClient`]: crate::pwned::RangeClient
+impl Fetch for Client {
+    async fn text(&self, url: Url) -> Result<String, LookupError> {
+        let response = self
+            .get(url)
+            .header("Add-Padding", "true")
+            .send()
+            .await
+            .map_err(|error| LookupError {
+                kind: FailureKind::Network,
+                ..LookupError::other(error.to_string())
+            })?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(LookupError {
+                kind: FailureKind::UnexpectedStatus(status.as_u16()),
+                ..LookupError::other(format!("the API answered {status}"))
+            });
+        }
+        response
+            .text()
+            .await
+            .map_err(|error| LookupError::other(error.to_string()))
+    }
+}
+
+/// Checks `password` as the browser build does: looked up by its SHA-1
+/// hash, normalized the default way, and recorded by the prefix alone.
+pub async fn check_password(
+    fetch: &impl Fetch,
+    endpoint: &Url,
+    password: &str,
+) -> Result<CheckRecord, LookupError> {
+    let mode = HashMode::Sha1;
+    let hash = hash_password(password, mode, Normalization::default());
+    let breach = lookup(fetch, endpoint, &hash, mode).await?;
+    Ok(CheckRecord::new(
+        hash_prefix_of(&hash),
+        mode,
+        breach,
+        LookupSource::Network,
+        &Thresholds::default(),
+    ))
+}
+
+/// Validates an API base URL, which must be `http(s)`, and makes sure it ends
+/// in a slash so ranges join onto it rather than replacing its last segment.
+///
+/// ```
+/// use cybersec_wow::fetch::parse_endpoint;
+///
+/// let mirror = parse_endpoint("https://mirror.example/pwned").unwrap();
+/// assert_eq!(mirror.as_str(), "https://mirror.example/pwned/");
+/// assert!(parse_endpoint("ftp://mirror.example").is_err());
+/// ```
+pub fn parse_endpoint(input: &str) -> Result<Url, String> {
+    let mut url = Url::parse(input.trim()).map_err(|e| e.to_string())?;
+    if !matches!(url.scheme(), "https" | "http") || url.cannot_be_a_base() {
+        return Err(format!("expected an https:// URL, got \"{input}\""));
+    }
+    // `Url::join` replaces the last path segment unless the base ends in a slash.
+    if !url.path().ends_with('/') {
+        let path = format!("{}/", url.path());
+        url.set_path(&path);
+    }
+    Ok(url)
+}
+
+pub(crate) fn range_url(endpoint: &Url, prefix: &str, mode: HashMode) -> Url {
+    let mut url = endpoint
+        .join(&format!("range/{}", prefix.to_ascii_uppercase()))
+        .expect("validated endpoints are base URLs");
+    url.set_query(mode.query());
+    url
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranges_are_under_the_endpoints_path() {
+        for (endpoint, expected) in [
+            (
+                "https://api.pwnedpasswords.com",
+                "https://api.pwnedpasswords.com/range/F3BBB",
+            ),
+            (
+                "https://mirror.example/hibp",
+                "https://mirror.example/hibp/range/F3BBB",
+            ),
+            (
+                "https://mirror.example/hibp/",
+                "https://mirror.example/hibp/range/F3BBB",
+            ),
+            (
+                "http://127.0.0.1:8080/a/b",
+                "http://127.0.0.1:8080/a/b/range/F3BBB",
+            ),
+        ] {
+            let endpoint = parse_endpoint(endpoint).unwrap();
+            assert_eq!(
+                range_url(&endpoint, "f3bbb", HashMode::Sha1).as_str(),
+                expected
+            );
+        }
+        let endpoint = parse_endpoint(DEFAULT_ENDPOINT).unwrap();
+        assert_eq!(
+            range_url(&endpoint, "F3BBB", HashMode::Ntlm).as_str(),
+            "https://api.pwnedpasswords.com/range/F3BBB?mode=ntlm"
+        );
+    }
+
+    /// Answers with `body`, remembering the URL it was asked for.
+    struct Canned {
+        body: Result<&'static str, &'static str>,
+        asked: std::cell::RefCell<Option<Url>>,
+    }
+
+    impl Canned {
+        fn new(body: Result<&'static str, &'static str>) -> Self {
+            Self {
+                body,
+                asked: Default::default(),
+            }
+        }
+    }
+
+    impl Fetch for Canned {
+        async fn text(&self, url: Url) -> Result<String, LookupError> {
+            self.asked.replace(Some(url));
+            self.body.map(str::to_owned).map_err(LookupError::other)
+        }
+    }
+
+    const HUNTER2: &str = "F3BBBD66A63D4BF1747940578EC3D0103530E21D";
+    const RANGE: &str = include_str!("../tests/fixtures/range-F3BBB.txt");
+
+    fn looked_up(fetch: &Canned, mode: HashMode) -> Result<BreachResult, LookupError> {
+        let endpoint = parse_endpoint(DEFAULT_ENDPOINT).unwrap();
+        futures::executor::block_on(lookup(fetch, &endpoint, HUNTER2, mode))
+    }
+
+    #[test]
+    fn lookup_agrees_with_the_parsed_range() {
+        let fetch = Canned::new(Ok(RANGE));
+        let range = parse_range(RANGE).unwrap();
+        assert_eq!(
+            looked_up(&fetch, HashMode::Sha1).unwrap(),
+            BreachResult::from_range(&range, HUNTER2)
+        );
+        assert_eq!(
+            fetch.asked.take().unwrap().as_str(),
+            "https://api.pwnedpasswords.com/range/F3BBB"
+        );
+        looked_up(&fetch, HashMode::Ntlm).unwrap();
+        assert_eq!(fetch.asked.take().unwrap().query(), Some("mode=ntlm"));
+    }
+
+    #[test]
+    fn a_bad_body_or_a_failed_fetch_is_an_error_not_a_miss() {
+        let garbage = looked_up(&Canned::new(Ok("<html>")), HashMode::Sha1).unwrap_err();
+        assert!(matches!(garbage.kind, FailureKind::Unparsable(_)));
+        let failed = looked_up(&Canned::new(Err("unreachable")), HashMode::Sha1).unwrap_err();
+        assert_eq!(failed.message, "unreachable");
+    }
+
+    /// A server answering every range request with `response`, as the
+    /// browser build would be pointed at.
+    async fn serving(response: wiremock::ResponseTemplate) -> (wiremock::MockServer, Url) {
+        let server = wiremock::MockServer::start().await;
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .respond_with(response)
+            .mount(&server)
+            .await;
+        let endpoint = parse_endpoint(&server.uri()).unwrap();
+        (server, endpoint)
+    }
+
+    #[tokio::test]
+    async fn the_browser_check_finds_a_breached_password() {
+        let (server, endpoint) =
+            serving(wiremock::ResponseTemplate::new(200).set_body_string(RANGE)).await;
+        let record = check_password(&Client::new(), &endpoint, "hunter2")
+            .await
+            .unwrap();
+        assert_eq!(record.result, BreachResult::Found { count: 17206891 });
+        assert_eq!(record.prefix, "F3BBB");
+        assert_eq!(record.algorithm, HashMode::Sha1);
+
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].url.path(), "/range/F3BBB");
+        assert_eq!(requests[0].headers.get("add-padding").unwrap(), "true");
+        // What a page gets back has the prefix and nothing more of the hash.
+        let json = serde_json::to_string(&record).unwrap();
+        assert!(!json.contains(&HUNTER2[5..]), "{json}");
+    }
+
+    #[tokio::test]
+    async fn the_browser_check_misses_a_password_not_in_the_range() {
+        let (_server, endpoint) =
+            serving(wiremock::ResponseTemplate::new(200).set_body_string(RANGE)).await;
+        let record = check_password(&Client::new(), &endpoint, "hunter3")
+            .await
+            .unwrap();
+        assert_eq!(record.result, BreachResult::NotFound);
+        assert_eq!(record.severity, None);
+    }
+
+    #[tokio::test]
+    async fn an_error_status_fails_the_browser_check_rather_than_missing() {
+        let (_server, endpoint) = serving(wiremock::ResponseTemplate::new(404)).await;
+        let error = check_password(&Client::new(), &endpoint, "hunter2")
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind, FailureKind::UnexpectedStatus(404));
+        assert_eq!(error.message, "the API answered 404 Not Found");
+    }
+
+    #[tokio::test]
+    async fn an_unreachable_endpoint_fails_the_browser_check() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = parse_endpoint(&format!("http://{}", listener.local_addr().unwrap()));
+        drop(listener);
+        let error = check_password(&Client::new(), &endpoint.unwrap(), "hunter2")
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind, FailureKind::Network);
+    }
+
+    #[test]
+    fn only_http_base_urls_are_endpoints() {
+        assert!(parse_endpoint("  https://mirror.example  ").is_ok());
+        for input in ["", "mirror.example", "ftp://mirror.example", "mailto:a@b.c"] {
+            assert!(parse_endpoint(input).is_err(), "{input}");
+        }
+    }
+}
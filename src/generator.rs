@@ -0,0 +1,423 @@
+use std::sync::LazyLock;
+
+use rand::Rng;
+use rand::seq::SliceRandom;
+use zeroize::Zeroize;
+
+const LOWER: &str = "abcdefghijklmnopqrstuvwxyz";
+const UPPER: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const DIGITS: &str = "0123456789";
+const SYMBOLS: &str = "!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~";
+/// Characters easily misread for one another in common fonts.
+const AMBIGUOUS: &str = "Il1|O0o`'\"";
+
+pub const MIN_LENGTH: u8 = 8;
+pub const MAX_LENGTH: u8 = 64;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CharClass {
+    Lower,
+    Upper,
+    Digits,
+    Symbols,
+}
+
+impl CharClass {
+    pub const ALL: [CharClass; 4] = [
+        CharClass::Lower,
+        CharClass::Upper,
+        CharClass::Digits,
+        CharClass::Symbols,
+    ];
+
+    fn chars(self) -> &'static str {
+        match self {
+            CharClass::Lower => LOWER,
+            CharClass::Upper => UPPER,
+            CharClass::Digits => DIGITS,
+            CharClass::Symbols => SYMBOLS,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            CharClass::Lower => "a-z",
+            CharClass::Upper => "A-Z",
+            CharClass::Digits => "0-9",
+            CharClass::Symbols => "!@#",
+        }
+    }
+}
+
+/// What a generated password may be made of.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Options {
+    pub length: u8,
+    pub lower: bool,
+    pub upper: bool,
+    pub digits: bool,
+    pub symbols: bool,
+    pub avoid_ambiguous: bool,
+    /// Characters never to use, on top of any ambiguous ones.
+    pub exclude: String,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            length: 20,
+            lower: true,
+            upper: true,
+            digits: true,
+            symbols: true,
+            avoid_ambiguous: false,
+            exclude: String::new(),
+        }
+    }
+}
+
+impl Options {
+    pub fn uses(&self, class: CharClass) -> bool {
+        match class {
+            CharClass::Lower => self.lower,
+            CharClass::Upper => self.upper,
+            CharClass::Digits => self.digits,
+            CharClass::Symbols => self.symbols,
+        }
+    }
+
+    pub fn set(&mut self, class: CharClass, enabled: bool) {
+        match class {
+            CharClass::Lower => self.lower = enabled,
+            CharClass::Upper => self.upper = enabled,
+            CharClass::Digits => self.digits = enabled,
+            CharClass::Symbols => self.symbols = enabled,
+        }
+    }
+
+    /// The usable characters of each selected class.
+    fn alphabets(&self) -> Vec<Vec<char>> {
+        CharClass::ALL
+            .into_iter()
+            .filter(|&class| self.uses(class))
+            .map(|class| {
+                class
+                    .chars()
+                    .chars()
+                    .filter(|&c| !(self.avoid_ambiguous && AMBIGUOUS.contains(c)))
+                    .filter(|&c| !self.exclude.contains(c))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Draws a password from `rng`, with at least one character from every
+/// selected class. Fails if nothing is selected or exclusions empty a class.
+pub fn generate(options: &Options, rng: &mut impl Rng) -> Result<String, String> {
+    let alphabets = options.alphabets();
+    if alphabets.is_empty() {
+        return Err("select at least one character class".to_owned());
+    }
+    if alphabets.iter().any(Vec::is_empty) {
+        return Err("the exclusions leave a selected class with no characters".to_owned());
+    }
+    let length = usize::from(options.length.clamp(MIN_LENGTH, MAX_LENGTH));
+    let pool: Vec<char> = alphabets.concat();
+
+    let mut password: Vec<char> = alphabets
+        .iter()
+        .map(|alphabet| alphabet[rng.gen_range(0..alphabet.len())])
+        .collect();
+    password.extend((password.len()..length).map(|_| pool[rng.gen_range(0..pool.len())]));
+    // Otherwise the guaranteed characters would always lead, in class order.
+    password.shuffle(rng);
+
+    let generated = password.iter().collect();
+    password.zeroize();
+    Ok(generated)
+}
+
+/// The EFF large wordlist, one `ROLL<TAB>WORD` line per five-dice roll.
+static WORDLIST: &str = include_str!("../data/eff_large_wordlist.txt");
+
+static WORDS: LazyLock<Vec<&'static str>> = LazyLock::new(|| {
+    WORDLIST
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(_, word)| word)
+        .collect()
+});
+
+pub const MIN_WORDS: u8 = 4;
+pub const MAX_WORDS: u8 = 10;
+
+/// How a generated passphrase is put together.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PassphraseOptions {
+    pub words: u8,
+    pub separator: String,
+    pub capitalize: bool,
+    pub append_digit: bool,
+}
+
+impl Default for PassphraseOptions {
+    fn default() -> Self {
+        Self {
+            words: 6,
+            separator: "-".to_owned(),
+            capitalize: false,
+            append_digit: false,
+        }
+    }
+}
+
+impl PassphraseOptions {
+    fn word_count(&self) -> usize {
+        usize::from(self.words.clamp(MIN_WORDS, MAX_WORDS))
+    }
+
+    /// Bits of entropy against an attacker who knows the list and these
+    /// options. Capitalization is fixed, so it adds nothing.
+    pub fn entropy_bits(&self) -> f64 {
+        let per_word = (WORDS.len() as f64).log2();
+        let digit = if self.append_digit { 10f64.log2() } else { 0. };
+        per_word * self.word_count() as f64 + digit
+    }
+}
+
+/// Picks words uniformly from the EFF large wordlist using `rng`.
+pub fn passphrase(options: &PassphraseOptions, rng: &mut impl Rng) -> String {
+    let mut words: Vec<String> = (0..options.word_count())
+        .map(|_| {
+            let word = WORDS[rng.gen_range(0..WORDS.len())];
+            if options.capitalize {
+                let mut chars = word.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_uppercase().chain(chars).collect())
+                    .unwrap_or_default()
+            } else {
+                word.to_owned()
+            }
+        })
+        .collect();
+    if options.append_digit {
+        words.push(rng.gen_range(0..10).to_string());
+    }
+
+    let passphrase = words.join(&options.separator);
+    words.zeroize();
+    passphrase
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Kind {
+    #[default]
+    Characters,
+    Passphrase,
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    use super::*;
+
+    fn generated(options: &Options, seed: u64) -> String {
+        generate(options, &mut StdRng::seed_from_u64(seed)).unwrap()
+    }
+
+    #[test]
+    fn lengths_are_kept_within_bounds() {
+        for (asked, got) in [
+            (0, MIN_LENGTH),
+            (8, 8),
+            (20, 20),
+            (64, 64),
+            (200, MAX_LENGTH),
+        ] {
+            let options = Options {
+                length: asked,
+                ..Options::default()
+            };
+            assert_eq!(
+                generated(&options, 1).chars().count(),
+                usize::from(got),
+                "{asked}"
+            );
+        }
+    }
+
+    #[test]
+    fn every_selected_class_is_used_and_no_other() {
+        let options = Options {
+            length: MIN_LENGTH,
+            symbols: false,
+            ..Options::default()
+        };
+        for seed in 0..500 {
+            let password = generated(&options, seed);
+            assert!(
+                password.chars().any(|c| c.is_ascii_lowercase()),
+                "{password}"
+            );
+            assert!(
+                password.chars().any(|c| c.is_ascii_uppercase()),
+                "{password}"
+            );
+            assert!(password.chars().any(|c| c.is_ascii_digit()), "{password}");
+            assert!(
+                password.chars().all(|c| c.is_ascii_alphanumeric()),
+                "{password}"
+            );
+        }
+    }
+
+    #[test]
+    fn the_guaranteed_characters_dont_always_lead() {
+        let options = Options {
+            length: MIN_LENGTH,
+            ..Options::default()
+        };
+        let leads_with_lower = (0..200)
+            .filter(|&seed| generated(&options, seed).starts_with(|c: char| c.is_ascii_lowercase()))
+            .count();
+        assert!(leads_with_lower < 150, "{leads_with_lower} of 200");
+    }
+
+    #[test]
+    fn excluded_and_ambiguous_characters_never_appear() {
+        let options = Options {
+            length: MAX_LENGTH,
+            avoid_ambiguous: true,
+            exclude: "abc#".to_owned(),
+            ..Options::default()
+        };
+        for seed in 0..200 {
+            let password = generated(&options, seed);
+            assert!(
+                !password.contains(|c| AMBIGUOUS.contains(c) || options.exclude.contains(c)),
+                "{password}"
+            );
+        }
+    }
+
+    #[test]
+    fn options_that_leave_nothing_to_draw_from_fail() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut none = Options::default();
+        for class in CharClass::ALL {
+            none.set(class, false);
+            assert!(!none.uses(class));
+        }
+        assert_eq!(
+            generate(&none, &mut rng).unwrap_err(),
+            "select at least one character class"
+        );
+        let emptied = Options {
+            lower: false,
+            upper: false,
+            symbols: false,
+            exclude: DIGITS.to_owned(),
+            ..Options::default()
+        };
+        assert_eq!(
+            generate(&emptied, &mut rng).unwrap_err(),
+            "the exclusions leave a selected class with no characters"
+        );
+    }
+
+    #[test]
+    fn the_same_seed_gives_the_same_password() {
+        let options = Options::default();
+        assert_eq!(generated(&options, 7), generated(&options, 7));
+        assert_ne!(generated(&options, 7), generated(&options, 8));
+    }
+
+    #[test]
+    fn the_whole_wordlist_is_embedded() {
+        assert_eq!(WORDS.len(), 7776);
+        assert_eq!(WORDS.first(), Some(&"abacus"));
+        assert_eq!(WORDS.last(), Some(&"zoom"));
+        let unique: std::collections::HashSet<_> = WORDS.iter().collect();
+        assert_eq!(unique.len(), WORDS.len());
+    }
+
+    #[test]
+    fn words_are_drawn_from_across_the_list() {
+        let index: std::collections::HashMap<_, _> = WORDS.iter().zip(0..).collect();
+        let mut rng = StdRng::seed_from_u64(0);
+        let options = PassphraseOptions {
+            words: MAX_WORDS,
+            separator: " ".to_owned(),
+            ..PassphraseOptions::default()
+        };
+        // Which sixth of the list, by the first die, each word came from.
+        let mut sixths = [0; 6];
+        let (mut lowest, mut highest) = (usize::MAX, 0);
+        for _ in 0..6_000 {
+            for word in passphrase(&options, &mut rng).split(' ') {
+                let at = index[&word];
+                sixths[at * 6 / WORDS.len()] += 1;
+                (lowest, highest) = (lowest.min(at), highest.max(at));
+            }
+        }
+        assert!(
+            lowest < 50 && highest > WORDS.len() - 50,
+            "{lowest}..={highest}"
+        );
+        for count in sixths {
+            assert!((9_000..11_000).contains(&count), "{sixths:?}");
+        }
+    }
+
+    #[test]
+    fn passphrases_have_the_words_asked_for() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for (asked, got) in [(0, MIN_WORDS), (4, 4), (7, 7), (10, 10), (99, MAX_WORDS)] {
+            let options = PassphraseOptions {
+                words: asked,
+                separator: " ".to_owned(),
+                ..PassphraseOptions::default()
+            };
+            let words = passphrase(&options, &mut rng).split(' ').count();
+            assert_eq!(words, usize::from(got), "{asked}");
+        }
+    }
+
+    #[test]
+    fn capitals_and_a_digit_are_added_when_asked() {
+        let options = PassphraseOptions {
+            words: 5,
+            separator: ".".to_owned(),
+            capitalize: true,
+            append_digit: true,
+        };
+        let phrase = passphrase(&options, &mut StdRng::seed_from_u64(3));
+        let parts: Vec<_> = phrase.split('.').collect();
+        assert_eq!(parts.len(), 6);
+        assert!(
+            parts[..5]
+                .iter()
+                .all(|word| word.starts_with(|c: char| c.is_uppercase()))
+        );
+        assert!(parts[5].len() == 1 && parts[5].chars().all(|c| c.is_ascii_digit()));
+    }
+
+    #[test]
+    fn entropy_counts_words_and_the_digit() {
+        let bits = |words, append_digit| {
+            PassphraseOptions {
+                words,
+                append_digit,
+                ..PassphraseOptions::default()
+            }
+            .entropy_bits()
+        };
+        assert!((bits(6, false) - 6. * 7776f64.log2()).abs() < 1e-9);
+        assert!((bits(6, true) - bits(6, false) - 10f64.log2()).abs() < 1e-9);
+        assert_eq!(bits(0, false), bits(MIN_WORDS, false));
+    }
+}
@@ -0,0 +1,195 @@
+use iced::alignment::Vertical;
+use iced::widget::{button, checkbox, column, radio, row, slider, text, text_input};
+use iced::{Element, Task};
+use rand::rngs::OsRng;
+
+use crate::generator::{self, CharClass};
+use crate::high_contrast;
+use crate::secret::SecretString;
+use crate::strings::Strings;
+
+/// The Generator tab: its options and what it last generated.
+#[derive(Debug, Default)]
+pub struct GeneratorScreen {
+    kind: generator::Kind,
+    options: generator::Options,
+    passphrase: generator::PassphraseOptions,
+    /// The last generated password, with its entropy when it's a passphrase.
+    generated: Option<Result<(SecretString, Option<f64>), String>>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Length(u8),
+    Class(CharClass, bool),
+    AvoidAmbiguous(bool),
+    Exclude(String),
+    Kind(generator::Kind),
+    Words(u8),
+    Separator(String),
+    Capitalize(bool),
+    AppendDigit(bool),
+    Generate,
+    Copy,
+    /// Handled by the app, which moves the password into the checker.
+    Use,
+}
+
+impl GeneratorScreen {
+    pub fn update(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::Length(length) => self.options.length = length,
+            Message::Class(class, enabled) => self.options.set(class, enabled),
+            Message::AvoidAmbiguous(avoid) => self.options.avoid_ambiguous = avoid,
+            Message::Exclude(exclude) => self.options.exclude = exclude,
+            Message::Kind(kind) => self.kind = kind,
+            Message::Words(words) => self.passphrase.words = words,
+            Message::Separator(separator) => self.passphrase.separator = separator,
+            Message::Capitalize(capitalize) => self.passphrase.capitalize = capitalize,
+            Message::AppendDigit(append) => self.passphrase.append_digit = append,
+            Message::Generate => {
+                self.generated = Some(match self.kind {
+                    generator::Kind::Characters => generator::generate(&self.options, &mut OsRng)
+                        .map(|password| (password.into(), None)),
+                    generator::Kind::Passphrase => Ok((
+                        generator::passphrase(&self.passphrase, &mut OsRng).into(),
+                        Some(self.passphrase.entropy_bits()),
+                    )),
+                });
+            }
+            Message::Copy => {
+                if let Some(generated) = self.generated() {
+                    return iced::clipboard::write(generated.expose().to_owned());
+                }
+            }
+            Message::Use => {}
+        }
+        Task::none()
+    }
+
+    /// The last password generated, if generating it worked.
+    pub fn generated(&self) -> Option<&SecretString> {
+        match &self.generated {
+            Some(Ok((generated, _))) => Some(generated),
+            _ => None,
+        }
+    }
+
+    pub fn has_generated(&self) -> bool {
+        self.generated.is_some()
+    }
+
+    /// Drops the last generated password, as Clear does with everything.
+    pub fn clear(&mut self) {
+        self.generated = None;
+    }
+
+    pub fn view(&self, strings: &'static Strings) -> Element<'_, Message> {
+        let kind = row![
+            radio(
+                strings.kind_characters,
+                generator::Kind::Characters,
+                Some(self.kind),
+                Message::Kind
+            ),
+            radio(
+                strings.kind_passphrase,
+                generator::Kind::Passphrase,
+                Some(self.kind),
+                Message::Kind
+            ),
+        ]
+        .spacing(10);
+        let options = match self.kind {
+            generator::Kind::Characters => self.character_options_view(strings),
+            generator::Kind::Passphrase => self.passphrase_options_view(strings),
+        };
+        let result: Element<'_, Message> = match &self.generated {
+            None => text("").into(),
+            Some(Err(error)) => text(error).style(text::danger).into(),
+            Some(Ok((generated, entropy))) => {
+                row![text(generated.expose()).font(iced::Font::MONOSPACE)]
+                    .push_maybe(
+                        entropy.map(|bits| text((strings.entropy)(bits)).style(text::secondary)),
+                    )
+                    .push(button(strings.copy).on_press(Message::Copy))
+                    .push(button(strings.use_in_checker).on_press(Message::Use))
+                    .spacing(10)
+                    .align_y(Vertical::Center)
+                    .into()
+            }
+        };
+        column![
+            kind,
+            options,
+            button(strings.generate).on_press(Message::Generate),
+            result
+        ]
+        .spacing(5)
+        .into()
+    }
+
+    fn character_options_view(&self, strings: &'static Strings) -> Element<'_, Message> {
+        let options = &self.options;
+        let classes = row(CharClass::ALL.into_iter().map(|class| {
+            checkbox(class.label(), options.uses(class))
+                .on_toggle(move |enabled| Message::Class(class, enabled))
+                .into()
+        }))
+        .spacing(10);
+        column![
+            row![
+                text((strings.length)(options.length)).width(80),
+                slider(
+                    generator::MIN_LENGTH..=generator::MAX_LENGTH,
+                    options.length,
+                    Message::Length
+                ),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            classes,
+            row![
+                checkbox(strings.avoid_ambiguous, options.avoid_ambiguous)
+                    .on_toggle(Message::AvoidAmbiguous),
+                text_input(strings.exclude_placeholder, &options.exclude)
+                    .style(high_contrast::text_input)
+                    .on_input(Message::Exclude),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+        ]
+        .spacing(5)
+        .into()
+    }
+
+    fn passphrase_options_view(&self, strings: &'static Strings) -> Element<'_, Message> {
+        let options = &self.passphrase;
+        column![
+            row![
+                text((strings.words)(options.words)).width(80),
+                slider(
+                    generator::MIN_WORDS..=generator::MAX_WORDS,
+                    options.words,
+                    Message::Words
+                ),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            row![
+                text(strings.separator),
+                text_input("-", &options.separator)
+                    .style(high_contrast::text_input)
+                    .on_input(Message::Separator)
+                    .width(50),
+                checkbox(strings.capitalize, options.capitalize).on_toggle(Message::Capitalize),
+                checkbox(strings.append_digit, options.append_digit)
+                    .on_toggle(Message::AppendDigit),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+        ]
+        .spacing(5)
+        .into()
+    }
+}
@@ -0,0 +1,233 @@
+use md4::Md4;
+use sha1::{Digest, Sha1};
+use unicode_normalization::UnicodeNormalization;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::{HashMode, Normalization};
+
+/// How many leading hex characters of a hash the range API is sent.
+pub const PREFIX_LEN: usize = 5;
+
+/// The uppercase hex hash of `pass`, normalized first, or an empty string
+/// for an empty password. Intermediate copies are wiped.
+///
+/// ```
+/// use cybersec_wow::{HashMode, Normalization, hash_password};
+///
+/// assert_eq!(
+///     hash_password("password", HashMode::Sha1, Normalization::Nfc),
+///     "5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8"
+/// );
+/// assert_eq!(
+///     hash_password("password", HashMode::Ntlm, Normalization::Nfc),
+///     "8846F7EAEE8FB117AD06BDD830B7586C"
+/// );
+/// assert_eq!(hash_password("", HashMode::Sha1, Normalization::Nfc), "");
+/// ```
+pub fn hash_password(pass: &str, mode: HashMode, normalization: Normalization) -> String {
+    if pass.is_empty() {
+        return "".into();
+    }
+    let normalized = Zeroizing::new(match normalization {
+        Normalization::Off => pass.to_owned(),
+        Normalization::Nfc => pass.nfc().collect(),
+        Normalization::Nfkc => pass.nfkc().collect(),
+    });
+    let pass = normalized.as_str();
+
+    match mode {
+        HashMode::Sha1 => {
+            let mut hasher = Sha1::new();
+            hasher.update(pass.as_bytes());
+            let mut digest = hasher.finalize();
+            let hex = format!("{digest:X}");
+            digest.as_mut_slice().zeroize();
+            hex
+        }
+        HashMode::Ntlm => {
+            let mut hasher = Md4::new();
+            for unit in pass.encode_utf16() {
+                let mut bytes = unit.to_le_bytes();
+                hasher.update(bytes);
+                bytes.zeroize();
+            }
+            let mut digest = hasher.finalize();
+            let hex = format!("{digest:X}");
+            digest.as_mut_slice().zeroize();
+            hex
+        }
+    }
+}
+
+/// Validates a hash typed in for `mode` and uppercases it to match what
+/// `hash_password` produces.
+///
+/// ```
+/// use cybersec_wow::HashMode;
+/// use cybersec_wow::hash::normalize_hash;
+///
+/// assert_eq!(
+///     normalize_hash(" 8846f7eaee8fb117ad06bdd830b7586c\n", HashMode::Ntlm),
+///     Ok("8846F7EAEE8FB117AD06BDD830B7586C".to_owned())
+/// );
+/// assert!(normalize_hash("8846f7ea", HashMode::Ntlm).is_err());
+/// ```
+pub fn normalize_hash(input: &str, mode: HashMode) -> Result<String, String> {
+    let input = input.trim();
+    let (article, expected) = match mode {
+        HashMode::Sha1 => ("a", 40),
+        HashMode::Ntlm => ("an", 32),
+    };
+    if input.len() != expected || !input.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!(
+            "{article} {mode} hash is exactly {expected} hexadecimal characters"
+        ));
+    }
+    Ok(input.to_ascii_uppercase())
+}
+
+/// The part of `hash` sent to the range API.
+///
+/// ```
+/// assert_eq!(
+///     cybersec_wow::hash_prefix_of("5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8"),
+///     "5BAA6"
+/// );
+/// ```
+pub fn hash_prefix_of(hash: &str) -> &str {
+    hash.get(..PREFIX_LEN).unwrap_or(hash)
+}
+
+/// The part of `hash` looked for in the range that comes back.
+///
+/// ```
+/// assert_eq!(
+///     cybersec_wow::hash_suffix_of("5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8"),
+///     "1E4C9B93F3F0682250B6CF8331B7EE68FD8"
+/// );
+/// ```
+pub fn hash_suffix_of(hash: &str) -> &str {
+    hash.get(PREFIX_LEN..).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hashes_in_both_modes() {
+        assert_eq!(
+            hash_password("password", HashMode::Sha1, Normalization::Off),
+            "5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8"
+        );
+        assert_eq!(
+            hash_password("password", HashMode::Ntlm, Normalization::Off),
+            "8846F7EAEE8FB117AD06BDD830B7586C"
+        );
+        assert_eq!(hash_password("", HashMode::Ntlm, Normalization::Off), "");
+    }
+
+    #[test]
+    fn a_typed_hash_is_uppercased_and_trimmed() {
+        assert_eq!(
+            normalize_hash(
+                " 5baa61e4c9b93f3f0682250b6cf8331b7ee68fd8\n",
+                HashMode::Sha1
+            )
+            .unwrap(),
+            "5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8"
+        );
+        assert_eq!(
+            normalize_hash("8846f7eaee8fb117ad06bdd830b7586c", HashMode::Ntlm).unwrap(),
+            hash_password("password", HashMode::Ntlm, Normalization::Off)
+        );
+    }
+
+    #[test]
+    fn a_typed_hash_must_fit_its_mode() {
+        let sha1 = "a SHA-1 hash is exactly 40 hexadecimal characters";
+        let ntlm = "an NTLM hash is exactly 32 hexadecimal characters";
+        for (input, mode, error) in [
+            ("", HashMode::Sha1, sha1),
+            ("5BAA6", HashMode::Sha1, sha1),
+            (
+                "5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8A",
+                HashMode::Sha1,
+                sha1,
+            ),
+            (
+                "5BAA61E4C9B93F3F0682250B6CF8331B7EE68FDG",
+                HashMode::Sha1,
+                sha1,
+            ),
+            (
+                "5BAA61E4C9B93F3F 682250B6CF8331B7EE68FD8",
+                HashMode::Sha1,
+                sha1,
+            ),
+            // Right for the other mode isn't enough.
+            (
+                "5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8",
+                HashMode::Ntlm,
+                ntlm,
+            ),
+            ("8846F7EAEE8FB117AD06BDD830B7586C", HashMode::Sha1, sha1),
+            ("８８46F7EAEE8FB117AD06BDD830B7586C", HashMode::Ntlm, ntlm),
+        ] {
+            assert_eq!(normalize_hash(input, mode).unwrap_err(), error, "{input:?}");
+        }
+    }
+
+    #[test]
+    fn canonically_equal_forms_hash_the_same_once_normalized() {
+        // "café" with a precomposed é, and with e and a combining acute.
+        let (composed, decomposed) = ("caf\u{e9}", "cafe\u{301}");
+        for mode in [HashMode::Sha1, HashMode::Ntlm] {
+            let hash = |pass, normalization| hash_password(pass, mode, normalization);
+            assert_ne!(
+                hash(composed, Normalization::Off),
+                hash(decomposed, Normalization::Off)
+            );
+            assert_eq!(
+                hash(composed, Normalization::Nfc),
+                hash(decomposed, Normalization::Nfc)
+            );
+            assert_eq!(
+                hash(composed, Normalization::Nfkc),
+                hash(decomposed, Normalization::Nfkc)
+            );
+            // NFC hashes the precomposed form, which is what most keyboards type.
+            assert_eq!(
+                hash(decomposed, Normalization::Nfc),
+                hash(composed, Normalization::Off)
+            );
+        }
+    }
+
+    #[test]
+    fn compatibility_forms_hash_the_same_only_under_nfkc() {
+        // A "ﬁ" ligature and fullwidth digits against their plain forms.
+        for (fancy, plain) in [("\u{fb01}sh", "fish"), ("pass\u{ff11}\u{ff12}", "pass12")] {
+            let hash = |pass, normalization| hash_password(pass, HashMode::Sha1, normalization);
+            assert_ne!(
+                hash(fancy, Normalization::Off),
+                hash(plain, Normalization::Off)
+            );
+            assert_ne!(
+                hash(fancy, Normalization::Nfc),
+                hash(plain, Normalization::Nfc)
+            );
+            assert_eq!(
+                hash(fancy, Normalization::Nfkc),
+                hash(plain, Normalization::Nfkc)
+            );
+        }
+    }
+
+    #[test]
+    fn ascii_hashes_the_same_however_its_normalized() {
+        let hash = |normalization| hash_password("hunter2", HashMode::Sha1, normalization);
+        assert_eq!(hash(Normalization::Off), hash(Normalization::Nfc));
+        assert_eq!(hash(Normalization::Off), hash(Normalization::Nfkc));
+    }
+}
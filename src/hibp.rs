@@ -0,0 +1,539 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fmt;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use reqwest::{Client, StatusCode};
+use serde::de::{self, DeserializeOwned};
+use serde::{Deserialize, Deserializer};
+use url::Url;
+
+use crate::catalog::Breach;
+use crate::secret::SecretString;
+
+const API_BASE: &str = "https://haveibeenpwned.com/api/v3/";
+
+#[derive(Debug)]
+pub enum ApiError {
+    MissingKey,
+    InvalidKey,
+    RateLimited,
+    DomainNotVerified(String),
+    Status(StatusCode),
+    Request(reqwest::Error),
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::MissingKey => {
+                write!(f, "this lookup needs a HIBP API key, add one in Settings")
+            }
+            ApiError::InvalidKey => {
+                write!(
+                    f,
+                    "the HIBP API key was rejected (401), check it in Settings"
+                )
+            }
+            ApiError::RateLimited => write!(f, "rate limited by the HIBP API, try again shortly"),
+            ApiError::DomainNotVerified(domain) => write!(
+                f,
+                "{domain} isn't verified for this API key (403), verify it on the HIBP dashboard"
+            ),
+            ApiError::Status(status) => {
+                write!(f, "unexpected response from the HIBP API ({status})")
+            }
+            ApiError::Request(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl From<reqwest::Error> for ApiError {
+    fn from(error: reqwest::Error) -> Self {
+        ApiError::Request(error)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct BreachName {
+    name: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct AccountBreaches {
+    pub account: String,
+    pub breaches: Vec<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Paste {
+    pub source: String,
+    pub id: String,
+    pub title: Option<String>,
+    pub date: Option<DateTime<Utc>>,
+    pub email_count: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct AccountPastes {
+    pub account: String,
+    pub pastes: Vec<Paste>,
+}
+
+pub async fn breached_account(
+    client: Client,
+    account: String,
+    api_key: SecretString,
+) -> Result<AccountBreaches, ApiError> {
+    let breaches: Option<Vec<BreachName>> = get_json(
+        &client,
+        &["breachedaccount", &account],
+        Some(api_key.expose()),
+    )
+    .await?;
+
+    Ok(AccountBreaches {
+        account,
+        breaches: breaches
+            .unwrap_or_default()
+            .into_iter()
+            .map(|breach| breach.name)
+            .collect(),
+    })
+}
+
+pub async fn paste_account(
+    client: Client,
+    account: String,
+    api_key: SecretString,
+) -> Result<AccountPastes, ApiError> {
+    let pastes = get_json(&client, &["pasteaccount", &account], Some(api_key.expose())).await?;
+
+    Ok(AccountPastes {
+        account,
+        pastes: pastes.unwrap_or_default(),
+    })
+}
+
+/// Breached aliases on a domain, as alias and the names of its breaches.
+#[derive(Clone, Debug)]
+pub struct DomainBreaches {
+    pub domain: String,
+    pub aliases: Vec<(String, Vec<String>)>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DomainSort {
+    #[default]
+    Alias,
+    MostBreaches,
+}
+
+impl DomainBreaches {
+    pub fn sorted(&self, sort: DomainSort) -> Vec<&(String, Vec<String>)> {
+        let mut aliases: Vec<_> = self.aliases.iter().collect();
+        match sort {
+            DomainSort::Alias => aliases.sort_by(|a, b| a.0.cmp(&b.0)),
+            DomainSort::MostBreaches => {
+                aliases.sort_by_key(|(alias, breaches)| (Reverse(breaches.len()), alias))
+            }
+        }
+        aliases
+    }
+}
+
+/// Every breached alias on `domain`, which must be verified for the key.
+pub async fn breached_domain(
+    client: Client,
+    domain: String,
+    api_key: SecretString,
+) -> Result<DomainBreaches, ApiError> {
+    breached_domain_at(&client, API_BASE, domain, api_key.expose()).await
+}
+
+/// [`breached_domain`] against the API at `base`.
+async fn breached_domain_at(
+    client: &Client,
+    base: &str,
+    domain: String,
+    api_key: &str,
+) -> Result<DomainBreaches, ApiError> {
+    let aliases: Option<HashMap<String, Vec<String>>> =
+        get_json_at(client, base, &["breacheddomain", &domain], Some(api_key))
+            .await
+            .map_err(|e| match e {
+                ApiError::Status(StatusCode::FORBIDDEN) => {
+                    ApiError::DomainNotVerified(domain.clone())
+                }
+                e => e,
+            })?;
+
+    Ok(DomainBreaches {
+        domain,
+        aliases: aliases.unwrap_or_default().into_iter().collect(),
+    })
+}
+
+/// The subscription an API key belongs to, as `subscription/status`
+/// describes it.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SubscriptionStatus {
+    pub subscription_name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Given without an offset, in UTC.
+    #[serde(deserialize_with = "utc_date_time")]
+    pub subscribed_until: DateTime<Utc>,
+    /// How many account, paste and domain lookups a minute it allows.
+    pub rpm: u32,
+    /// The most breached addresses a domain searched can have, if there's
+    /// a limit.
+    #[serde(default)]
+    pub domain_search_max_breached_accounts: Option<u64>,
+}
+
+impl SubscriptionStatus {
+    /// Whole days until it lapses at `now`, below zero once it has.
+    pub fn days_left(&self, now: DateTime<Utc>) -> i64 {
+        (self.subscribed_until - now).num_days()
+    }
+}
+
+/// A date and time with an offset, or without one as the API gives
+/// `SubscribedUntil`, which is then taken as UTC.
+fn utc_date_time<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DateTime<Utc>, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    DateTime::parse_from_rfc3339(&value)
+        .map(|date| date.to_utc())
+        .or_else(|_| value.parse::<NaiveDateTime>().map(|date| date.and_utc()))
+        .map_err(de::Error::custom)
+}
+
+/// Checks `api_key` against the API, returning the subscription it belongs to.
+pub async fn subscription_status(
+    client: Client,
+    api_key: SecretString,
+) -> Result<SubscriptionStatus, ApiError> {
+    get_json(&client, &["subscription", "status"], Some(api_key.expose()))
+        .await?
+        .ok_or(ApiError::Status(StatusCode::NOT_FOUND))
+}
+
+pub async fn breaches(client: Client) -> Result<Vec<Breach>, ApiError> {
+    Ok(get_json(&client, &["breaches"], None)
+        .await?
+        .unwrap_or_default())
+}
+
+/// Builds an API URL from `segments`, percent-encoding each one.
+fn api_url(base: &str, segments: &[&str]) -> Url {
+    let mut url = Url::parse(base).expect("API base is a valid URL");
+    url.path_segments_mut()
+        .expect("API base is a base URL")
+        .pop_if_empty()
+        .extend(segments);
+    url
+}
+
+/// GETs a JSON resource, mapping 404 to `None` since the API uses it for
+/// "nothing found" rather than as an error.
+async fn get_json<T: DeserializeOwned>(
+    client: &Client,
+    segments: &[&str],
+    api_key: Option<&str>,
+) -> Result<Option<T>, ApiError> {
+    get_json_at(client, API_BASE, segments, api_key).await
+}
+
+/// [`get_json`] from the API at `base`.
+async fn get_json_at<T: DeserializeOwned>(
+    client: &Client,
+    base: &str,
+    segments: &[&str],
+    api_key: Option<&str>,
+) -> Result<Option<T>, ApiError> {
+    let mut request = client.get(api_url(base, segments));
+    if let Some(api_key) = api_key {
+        if api_key.is_empty() {
+            return Err(ApiError::MissingKey);
+        }
+        request = request.header("hibp-api-key", api_key);
+    }
+
+    let response = request.send().await?;
+    match response.status() {
+        StatusCode::NOT_FOUND => Ok(None),
+        StatusCode::UNAUTHORIZED => Err(ApiError::InvalidKey),
+        StatusCode::TOO_MANY_REQUESTS => Err(ApiError::RateLimited),
+        status if status.is_success() => Ok(Some(response.json().await?)),
+        status => Err(ApiError::Status(status)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> String {
+        let path = format!("{}/tests/fixtures/hibp/{name}", env!("CARGO_MANIFEST_DIR"));
+        std::fs::read_to_string(path).unwrap()
+    }
+
+    #[test]
+    fn reads_breach_names_from_either_response() {
+        for (name, expected) in [
+            ("breachedaccount.json", &["Adobe", "LinkedIn"][..]),
+            (
+                "breachedaccount-truncated.json",
+                &["Adobe", "LinkedIn", "Dropbox"],
+            ),
+        ] {
+            let breaches: Vec<BreachName> = serde_json::from_str(&fixture(name)).unwrap();
+            let names: Vec<_> = breaches.iter().map(|breach| breach.name.as_str()).collect();
+            assert_eq!(names, expected, "{name}");
+        }
+    }
+
+    #[test]
+    fn accounts_are_one_encoded_segment() {
+        let url = api_url(API_BASE, &["breachedaccount", "a+b/c@example.com"]);
+        assert_eq!(
+            url.as_str(),
+            "https://haveibeenpwned.com/api/v3/breachedaccount/a+b%2Fc@example.com"
+        );
+    }
+
+    #[test]
+    fn pastes_may_have_no_title_or_date() {
+        let pastes: Vec<Paste> = serde_json::from_str(&fixture("pasteaccount.json")).unwrap();
+        assert_eq!(pastes.len(), 3);
+        assert_eq!(pastes[0].title.as_deref(), Some("syslog"));
+        assert_eq!(
+            pastes[0].date,
+            Some("2014-03-04T19:14:54Z".parse().unwrap())
+        );
+        assert_eq!(
+            (pastes[1].title.as_deref(), pastes[1].date.is_some()),
+            (None, true)
+        );
+        assert_eq!((pastes[2].title.as_deref(), pastes[2].date), (None, None));
+        assert_eq!(pastes[2].source, "AdHocUrl");
+        assert_eq!(pastes[2].email_count, 1882);
+    }
+    /// What `breached_domain` makes of a local server answering
+    /// `example.com`'s lookup with `response`.
+    async fn domain(response: wiremock::ResponseTemplate) -> Result<DomainBreaches, ApiError> {
+        use wiremock::matchers::{header, method, path};
+        use wiremock::{Mock, MockServer};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/breacheddomain/example.com"))
+            .and(header("hibp-api-key", "key"))
+            .respond_with(response)
+            .expect(1)
+            .mount(&server)
+            .await;
+        let base = format!("{}/", server.uri());
+        breached_domain_at(&Client::new(), &base, "example.com".to_owned(), "key").await
+    }
+
+    fn aliases<'a>(sorted: &[&'a (String, Vec<String>)]) -> Vec<&'a str> {
+        sorted.iter().map(|(alias, _)| alias.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn reads_a_domains_aliases_and_sorts_them() {
+        use wiremock::ResponseTemplate;
+
+        let json = fixture("breacheddomain.json");
+        let found = domain(ResponseTemplate::new(200).set_body_string(json))
+            .await
+            .unwrap();
+        assert_eq!(found.domain, "example.com");
+        assert_eq!(found.aliases.len(), 5);
+        assert_eq!(
+            aliases(&found.sorted(DomainSort::Alias)),
+            ["alias1", "alias2", "alias3", "bob", "zed"]
+        );
+        // Ties on the count go by alias.
+        assert_eq!(
+            aliases(&found.sorted(DomainSort::MostBreaches)),
+            ["alias2", "bob", "zed", "alias1", "alias3"]
+        );
+        let alias2 = found.sorted(DomainSort::MostBreaches)[0];
+        assert_eq!(alias2.1, ["Adobe", "Gawker", "Stratfor"]);
+    }
+
+    #[tokio::test]
+    async fn a_domain_lookup_maps_each_status() {
+        use wiremock::ResponseTemplate;
+
+        let none = domain(ResponseTemplate::new(404)).await.unwrap();
+        assert!(none.aliases.is_empty());
+        assert!(none.sorted(DomainSort::MostBreaches).is_empty());
+        assert!(matches!(
+            domain(ResponseTemplate::new(401)).await,
+            Err(ApiError::InvalidKey)
+        ));
+        assert!(matches!(
+            domain(ResponseTemplate::new(403)).await,
+            Err(ApiError::DomainNotVerified(domain)) if domain == "example.com"
+        ));
+        assert!(matches!(
+            domain(ResponseTemplate::new(429)).await,
+            Err(ApiError::RateLimited)
+        ));
+    }
+
+    #[test]
+    fn sorting_by_alias_is_by_its_text() {
+        let breaches = DomainBreaches {
+            domain: "example.com".to_owned(),
+            aliases: vec![
+                ("b".to_owned(), vec!["Adobe".to_owned()]),
+                ("B".to_owned(), vec![]),
+                (
+                    "a".to_owned(),
+                    vec!["Adobe".to_owned(), "Gawker".to_owned()],
+                ),
+            ],
+        };
+        assert_eq!(
+            aliases(&breaches.sorted(DomainSort::Alias)),
+            ["B", "a", "b"]
+        );
+        assert_eq!(
+            aliases(&breaches.sorted(DomainSort::MostBreaches)),
+            ["a", "b", "B"]
+        );
+    }
+
+    /// What `get_json_at` makes of `response` from a local server.
+    async fn read(response: wiremock::ResponseTemplate) -> Result<Option<Vec<Paste>>, ApiError> {
+        use wiremock::{Mock, MockServer, matchers::method};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(response)
+            .mount(&server)
+            .await;
+        let base = format!("{}/", server.uri());
+        get_json_at(
+            &Client::new(),
+            &base,
+            &["pasteaccount", "test@example.com"],
+            None,
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn each_status_has_its_own_error() {
+        use wiremock::ResponseTemplate;
+
+        let pastes = ResponseTemplate::new(200).set_body_string(fixture("pasteaccount.json"));
+        assert_eq!(read(pastes).await.unwrap().unwrap().len(), 3);
+        assert!(read(ResponseTemplate::new(404)).await.unwrap().is_none());
+        assert!(matches!(
+            read(ResponseTemplate::new(401)).await,
+            Err(ApiError::InvalidKey)
+        ));
+        assert!(matches!(
+            read(ResponseTemplate::new(429)).await,
+            Err(ApiError::RateLimited)
+        ));
+        assert!(matches!(
+            read(ResponseTemplate::new(503)).await,
+            Err(ApiError::Status(StatusCode::SERVICE_UNAVAILABLE))
+        ));
+        let html = ResponseTemplate::new(200).set_body_string("<html></html>");
+        assert!(matches!(read(html).await, Err(ApiError::Request(error)) if error.is_decode()));
+    }
+
+    #[tokio::test]
+    async fn a_keyed_lookup_without_a_key_isnt_sent() {
+        let error = paste_account(
+            Client::new(),
+            "test@example.com".to_owned(),
+            SecretString::default(),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(error, ApiError::MissingKey));
+    }
+
+    #[test]
+    fn errors_say_what_to_do() {
+        for (error, message) in [
+            (
+                ApiError::MissingKey,
+                "this lookup needs a HIBP API key, add one in Settings",
+            ),
+            (
+                ApiError::InvalidKey,
+                "the HIBP API key was rejected (401), check it in Settings",
+            ),
+            (
+                ApiError::DomainNotVerified("example.com".to_owned()),
+                "example.com isn't verified for this API key (403), verify it on the HIBP dashboard",
+            ),
+            (
+                ApiError::Status(StatusCode::BAD_GATEWAY),
+                "unexpected response from the HIBP API (502 Bad Gateway)",
+            ),
+        ] {
+            assert_eq!(error.to_string(), message);
+        }
+    }
+
+    #[test]
+    fn reads_the_documented_subscription_status() {
+        let status: SubscriptionStatus =
+            serde_json::from_str(&fixture("subscription-status.json")).unwrap();
+        assert_eq!(
+            status,
+            SubscriptionStatus {
+                subscription_name: "Pwned 1".to_owned(),
+                description: "Up to 10 email addresses per minute and searches domains \
+                              with up to 25 breached addresses each."
+                    .to_owned(),
+                subscribed_until: "2026-11-02T08:15:00Z".parse().unwrap(),
+                rpm: 10,
+                domain_search_max_breached_accounts: Some(25),
+            }
+        );
+        let now = "2026-10-14T12:00:00Z".parse().unwrap();
+        assert_eq!(status.days_left(now), 18);
+        assert_eq!(status.days_left(status.subscribed_until), 0);
+        let lapsed = "2026-11-05T08:15:00Z".parse().unwrap();
+        assert_eq!(status.days_left(lapsed), -3);
+    }
+
+    #[test]
+    fn a_subscription_needs_only_its_name_expiry_and_rate() {
+        let status: SubscriptionStatus = serde_json::from_str(
+            r#"{"SubscriptionName":"Pwned 4","SubscribedUntil":"2026-11-02T10:15:00+02:00","Rpm":500}"#,
+        )
+        .unwrap();
+        assert_eq!(status.description, "");
+        assert_eq!(status.domain_search_max_breached_accounts, None);
+        assert_eq!(
+            status.subscribed_until,
+            "2026-11-02T08:15:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+        for broken in [
+            r#"{"SubscriptionName":"Pwned 1","SubscribedUntil":"next year","Rpm":10}"#,
+            r#"{"SubscriptionName":"Pwned 1","SubscribedUntil":"2026-11-02T08:15:00"}"#,
+            r#"{"SubscriptionName":"Pwned 1","SubscribedUntil":"2026-11-02T08:15:00","Rpm":-1}"#,
+        ] {
+            let parsed = serde_json::from_str::<SubscriptionStatus>(broken);
+            assert!(parsed.is_err(), "{broken}");
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use std::sync::LazyLock;
+
+use iced::theme::{Palette, palette};
+use iced::widget::text_input::{self as field, Status};
+use iced::{Color, Theme, color};
+
+/// The theme's name in the picker and the settings file.
+pub const NAME: &str = "High Contrast";
+
+/// White on near-black, with accents bright enough that every colour text
+/// is drawn in keeps a contrast ratio of at least 7:1 against the
+/// background (WCAG AAA). The lowest, danger red, is about 7.4:1.
+const PALETTE: Palette = Palette {
+    background: color!(0x0A0A0A),
+    text: color!(0xFFFFFF),
+    primary: color!(0x00E5FF),
+    success: color!(0x3CFF6E),
+    danger: color!(0xFF7070),
+};
+
+/// Secondary text, about 12.8:1.
+const SECONDARY_TEXT: Color = color!(0xD0D0D0);
+
+/// Borders, placeholders and empty meter segments, about 7.6:1, where the
+/// generated palette would have them barely lighter than the background.
+const STRONG_BACKGROUND: Color = color!(0xA0A0A0);
+
+static THEME: LazyLock<Theme> = LazyLock::new(|| {
+    Theme::custom_with_fn(NAME.to_owned(), PALETTE, |palette| {
+        let mut extended = palette::Extended::generate(palette);
+        extended.secondary.strong.color = SECONDARY_TEXT;
+        extended.background.strong.color = STRONG_BACKGROUND;
+        extended.background.strong.text = PALETTE.background;
+        extended
+    })
+});
+
+pub fn theme() -> Theme {
+    THEME.clone()
+}
+
+pub fn is_active(theme: &Theme) -> bool {
+    *theme == *THEME
+}
+
+/// The stock text field, with borders thick enough to find the focused
+/// field at a glance when the high-contrast theme is on.
+pub fn text_input(theme: &Theme, status: Status) -> field::Style {
+    let mut style = field::default(theme, status);
+    if is_active(theme) {
+        style.border.width = match status {
+            Status::Focused => 3.,
+            _ => 2.,
+        };
+    }
+    style
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// WCAG's relative luminance.
+    fn luminance(color: Color) -> f32 {
+        let channel = |c: f32| {
+            if c <= 0.04045 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        0.2126 * channel(color.r) + 0.7152 * channel(color.g) + 0.0722 * channel(color.b)
+    }
+
+    fn contrast(a: Color, b: Color) -> f32 {
+        let (a, b) = (luminance(a), luminance(b));
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+
+    #[test]
+    fn the_contrast_formula_matches_wcag() {
+        let ratio = contrast(Color::BLACK, Color::WHITE);
+        assert!((ratio - 21.).abs() < 0.01, "{ratio}");
+        assert!((contrast(Color::WHITE, Color::WHITE) - 1.).abs() < 0.01);
+    }
+
+    #[test]
+    fn foregrounds_keep_seven_to_one_against_the_background() {
+        let palette = *theme().extended_palette();
+        let background = palette.background.base.color;
+        for (name, foreground) in [
+            ("text", palette.background.base.text),
+            ("primary", PALETTE.primary),
+            ("success", PALETTE.success),
+            ("danger", PALETTE.danger),
+            ("secondary text", palette.secondary.strong.color),
+            ("borders", palette.background.strong.color),
+        ] {
+            let ratio = contrast(foreground, background);
+            assert!(ratio >= 7., "{name}: {ratio:.2}:1");
+        }
+    }
+
+    #[test]
+    fn text_on_coloured_surfaces_keeps_seven_to_one() {
+        let palette = *theme().extended_palette();
+        for (name, pair) in [
+            ("primary button", palette.primary.base),
+            ("success button", palette.success.base),
+            ("danger button", palette.danger.base),
+            ("strong background", palette.background.strong),
+        ] {
+            let ratio = contrast(pair.text, pair.color);
+            assert!(ratio >= 7., "{name}: {ratio:.2}:1");
+        }
+    }
+
+    #[test]
+    fn only_this_theme_gets_thicker_borders() {
+        let width = |theme: &Theme| text_input(theme, Status::Active).border.width;
+        assert_eq!(width(&theme()), 2.);
+        assert_eq!(text_input(&theme(), Status::Focused).border.width, 3.);
+        assert_eq!(
+            width(&Theme::Dark),
+            field::default(&Theme::Dark, Status::Active).border.width
+        );
+        assert!(is_active(&theme()));
+        assert!(!is_active(&Theme::Dark));
+    }
+}
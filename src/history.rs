@@ -0,0 +1,57 @@
+use std::collections::VecDeque;
+
+use crate::record::CheckRecord;
+use crate::secret::SecretString;
+
+/// Most checks kept; the oldest go first.
+const CAPACITY: usize = 100;
+
+/// One check from this session. The hash is kept so it can be re-run, and
+/// is wiped with the entry; the password itself never is.
+#[derive(Debug)]
+pub struct Entry {
+    pub label: String,
+    pub record: CheckRecord,
+    hash: SecretString,
+}
+
+impl Entry {
+    pub fn hash(&self) -> &SecretString {
+        &self.hash
+    }
+}
+
+/// This session's checks, newest first. Lives in memory only.
+#[derive(Debug, Default)]
+pub struct History {
+    entries: VecDeque<Entry>,
+}
+
+impl History {
+    pub fn push(&mut self, label: String, record: CheckRecord, hash: SecretString) {
+        if self.entries.len() == CAPACITY {
+            self.entries.pop_back();
+        }
+        self.entries.push_front(Entry {
+            label,
+            record,
+            hash,
+        });
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Entry> {
+        self.entries.get(index)
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &Entry> {
+        self.entries.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
@@ -0,0 +1,178 @@
+use iced::widget::canvas::{self, Canvas};
+use iced::widget::{Space, tooltip};
+use iced::{Color, Element, Point, Rectangle, Renderer, Size, Theme, mouse};
+use zeroize::Zeroizing;
+
+/// How many cells across and down.
+const CELLS: usize = 5;
+
+/// The width and height the picture is drawn at.
+const SIZE: f32 = 30.;
+
+/// A 5×5 picture of `hash`, mirrored left to right like a GitHub avatar, for
+/// telling at a glance whether two passwords hash the same without showing
+/// either. The hue comes from the first two bytes; each of the 15 cells in
+/// the left three columns is on or off by a bit of the next two, and light
+/// or dark by a bit of the two after. Cells that are off, and every cell of
+/// an empty hash, are transparent. A hash shorter than six bytes is read as
+/// if padded with zeros.
+pub fn identicon_cells(hash: &[u8]) -> [[Color; CELLS]; CELLS] {
+    let mut cells = [[Color::TRANSPARENT; CELLS]; CELLS];
+    if hash.is_empty() {
+        return cells;
+    }
+    let byte = |i: usize| hash.get(i).copied().unwrap_or(0);
+    let hue = f32::from(u16::from_be_bytes([byte(0), byte(1)]) % 360);
+    let on = u16::from_be_bytes([byte(2), byte(3)]);
+    let dark = u16::from_be_bytes([byte(4), byte(5)]);
+    let light_color = hsl(hue, 0.65, 0.55);
+    let dark_color = hsl(hue, 0.65, 0.35);
+
+    for (row, line) in cells.iter_mut().enumerate() {
+        for column in 0..CELLS.div_ceil(2) {
+            let bit = row * CELLS.div_ceil(2) + column;
+            if on >> bit & 1 == 0 {
+                continue;
+            }
+            let color = if dark >> bit & 1 == 1 {
+                dark_color
+            } else {
+                light_color
+            };
+            line[column] = color;
+            line[CELLS - 1 - column] = color;
+        }
+    }
+    cells
+}
+
+/// The identicon of a hex `hash`, or blank space of the same size while
+/// there's no hash, or it isn't hex, so the line it sits on doesn't shift.
+pub fn view<'a, Message: 'a>(hash: &str, tip: &'a str) -> Element<'a, Message> {
+    let mut bytes = Zeroizing::new([0; 64]);
+    let cells = match base16ct::mixed::decode(hash, bytes.as_mut_slice()) {
+        Ok(decoded) if !decoded.is_empty() => identicon_cells(decoded),
+        _ => return Space::new(SIZE, SIZE).into(),
+    };
+    tooltip(
+        Canvas::new(Identicon { cells }).width(SIZE).height(SIZE),
+        tip,
+        tooltip::Position::Bottom,
+    )
+    .style(iced::widget::container::rounded_box)
+    .into()
+}
+
+struct Identicon {
+    cells: [[Color; CELLS]; CELLS],
+}
+
+impl<Message> canvas::Program<Message> for Identicon {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &(),
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry> {
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+        let side = bounds.width.min(bounds.height) / CELLS as f32;
+        for (row, cells) in self.cells.iter().enumerate() {
+            for (column, &color) in cells.iter().enumerate() {
+                if color != Color::TRANSPARENT {
+                    frame.fill_rectangle(
+                        Point::new(column as f32 * side, row as f32 * side),
+                        Size::new(side, side),
+                        color,
+                    );
+                }
+            }
+        }
+        vec![frame.into_geometry()]
+    }
+}
+
+/// Converts a hue in degrees and saturation and lightness from 0 to 1.
+fn hsl(hue: f32, saturation: f32, lightness: f32) -> Color {
+    let chroma = (1. - (2. * lightness - 1.).abs()) * saturation;
+    let sector = hue / 60.;
+    let x = chroma * (1. - (sector % 2. - 1.).abs());
+    let (r, g, b) = match sector as u8 {
+        0 => (chroma, x, 0.),
+        1 => (x, chroma, 0.),
+        2 => (0., chroma, x),
+        3 => (0., x, chroma),
+        4 => (x, 0., chroma),
+        _ => (chroma, 0., x),
+    };
+    let m = lightness - chroma / 2.;
+    Color::from_rgb(r + m, g + m, b + m)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{HashMode, Normalization};
+    use cybersec_wow::hash_password;
+
+    fn cells_of(password: &str) -> [[Color; CELLS]; CELLS] {
+        let hash = hash_password(password, HashMode::Sha1, Normalization::Off);
+        let mut bytes = [0; 20];
+        identicon_cells(base16ct::mixed::decode(&hash, &mut bytes).unwrap())
+    }
+
+    #[test]
+    fn the_same_hash_always_draws_the_same_picture() {
+        assert_eq!(cells_of("hunter2"), cells_of("hunter2"));
+        assert_ne!(cells_of("hunter2"), cells_of("hunter3"));
+    }
+
+    #[test]
+    fn an_empty_password_draws_nothing() {
+        assert_eq!(cells_of(""), [[Color::TRANSPARENT; CELLS]; CELLS]);
+        assert_eq!(identicon_cells(&[]), [[Color::TRANSPARENT; CELLS]; CELLS]);
+    }
+
+    #[test]
+    fn pictures_are_mirrored_left_to_right() {
+        for password in ["hunter2", "password", "correct horse"] {
+            for row in cells_of(password) {
+                let mut mirrored = row;
+                mirrored.reverse();
+                assert_eq!(row, mirrored, "{password}");
+            }
+        }
+    }
+
+    #[test]
+    fn cells_follow_the_bits_of_the_hash() {
+        // Hue 0, every cell on, the first cell of each row dark.
+        let dark = 0x1249u16.to_be_bytes();
+        let cells = identicon_cells(&[0, 0, 0xFF, 0xFF, dark[0], dark[1]]);
+        let (light, dark) = (hsl(0., 0.65, 0.55), hsl(0., 0.65, 0.35));
+        for row in cells {
+            assert_eq!(row, [dark, light, light, light, dark]);
+        }
+        // No bits on, no cells.
+        assert_eq!(
+            identicon_cells(&[0x12, 0x34, 0, 0, 0xFF, 0xFF]),
+            [[Color::TRANSPARENT; CELLS]; CELLS]
+        );
+    }
+
+    #[test]
+    fn short_hashes_are_padded_with_zeros() {
+        assert_eq!(identicon_cells(&[1]), identicon_cells(&[1, 0, 0, 0, 0, 0]));
+        assert_eq!(identicon_cells(&[1]), [[Color::TRANSPARENT; CELLS]; CELLS]);
+    }
+
+    #[test]
+    fn hues_go_round_the_wheel() {
+        assert_eq!(hsl(0., 1., 0.5), Color::from_rgb(1., 0., 0.));
+        assert_eq!(hsl(120., 1., 0.5), Color::from_rgb(0., 1., 0.));
+        assert_eq!(hsl(240., 1., 0.5), Color::from_rgb(0., 0., 1.));
+    }
+}
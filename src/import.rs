@@ -0,0 +1,949 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::hash::{BuildHasher, RandomState};
+use std::mem;
+use std::ops::ControlFlow;
+
+use csv_core::{ReadRecordResult, ReaderBuilder};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use crate::batch::{Line, Lines, MAX_IMPORT_LINES, mask};
+
+/// A password manager, or a browser's, whose CSV export can be audited.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Manager {
+    /// `name,login_uri,login_username,login_password,...`
+    Bitwarden,
+    /// `Account,Login Name,Password,...` from KeePass, or
+    /// `Title,Username,Password,...` from KeePassXC.
+    KeePass,
+    /// `name,url,username,password,note`, from Chrome and the browsers
+    /// built on it.
+    Chrome,
+    /// `url,username,password,httpRealm,formActionOrigin,guid,...`
+    Firefox,
+}
+
+/// Every manager, in the order [`detect`] tries them: the ones with the
+/// most distinctive headers first.
+const MANAGERS: [Manager; 4] = [
+    Manager::Bitwarden,
+    Manager::Firefox,
+    Manager::Chrome,
+    Manager::KeePass,
+];
+
+impl Manager {
+    /// The headers of the entry's name, its site, the username and the
+    /// password, each with the names it may go by.
+    fn columns(self) -> [&'static [&'static str]; 4] {
+        match self {
+            Manager::Bitwarden => [
+                &["name"],
+                &["login_uri"],
+                &["login_username"],
+                &["login_password"],
+            ],
+            Manager::KeePass => [
+                &["Account", "Title"],
+                &["Web Site", "URL"],
+                &["Login Name", "Username", "User Name"],
+                &["Password"],
+            ],
+            Manager::Chrome => [&["name"], &["url"], &["username"], &["password"]],
+            Manager::Firefox => [&[], &["url"], &["username"], &["password"]],
+        }
+    }
+
+    /// The headers that together tell this manager's export from the others.
+    fn signature(self) -> &'static [&'static str] {
+        match self {
+            Manager::Bitwarden => &["login_password"],
+            Manager::KeePass => &["Password"],
+            Manager::Chrome => &["name", "url", "username", "password"],
+            Manager::Firefox => &["url", "password", "httpRealm"],
+        }
+    }
+}
+
+impl fmt::Display for Manager {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Manager::Bitwarden => "Bitwarden",
+            Manager::KeePass => "KeePass",
+            Manager::Chrome => "Chrome",
+            Manager::Firefox => "Firefox",
+        })
+    }
+}
+
+/// The manager whose export starts with `header`, if any does.
+pub fn detect(header: &[&str]) -> Option<Manager> {
+    MANAGERS.into_iter().find(|manager| {
+        manager
+            .signature()
+            .iter()
+            .all(|name| position(header, &[name]).is_some())
+    })
+}
+
+/// What a CSV file's fields are split at.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Delimiter {
+    #[default]
+    Comma,
+    Semicolon,
+    Tab,
+}
+
+impl Delimiter {
+    pub const ALL: [Delimiter; 3] = [Delimiter::Comma, Delimiter::Semicolon, Delimiter::Tab];
+
+    pub fn byte(self) -> u8 {
+        match self {
+            Delimiter::Comma => b',',
+            Delimiter::Semicolon => b';',
+            Delimiter::Tab => b'\t',
+        }
+    }
+}
+
+impl fmt::Display for Delimiter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Delimiter::Comma => "Comma",
+            Delimiter::Semicolon => "Semicolon",
+            Delimiter::Tab => "Tab",
+        })
+    }
+}
+
+/// How many lines [`sniff`] looks at.
+const SNIFF_LINES: usize = 10;
+
+/// The delimiter a CSV file most likely uses: of those on its first line,
+/// the one found as often on each of its first few, then the one found
+/// most. Delimiters inside quoted fields don't count, and a file with none
+/// is taken to be comma-separated.
+pub fn sniff(bytes: &[u8]) -> Delimiter {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    let mut lines: Vec<[usize; 3]> = Vec::new();
+    let (mut counts, mut blank, mut quoted) = ([0; 3], true, false);
+    for &byte in bytes {
+        match byte {
+            b'"' => quoted = !quoted,
+            b'\n' if !quoted => {
+                if !blank {
+                    lines.push(mem::take(&mut counts));
+                }
+                blank = true;
+                if lines.len() == SNIFF_LINES {
+                    break;
+                }
+                continue;
+            }
+            _ if quoted => {}
+            byte => {
+                if let Some(i) = Delimiter::ALL.iter().position(|d| d.byte() == byte) {
+                    counts[i] += 1;
+                }
+            }
+        }
+        blank &= byte == b'\r';
+    }
+    if !blank && lines.len() < SNIFF_LINES {
+        lines.push(counts);
+    }
+    let Some(first) = lines.first() else {
+        return Delimiter::Comma;
+    };
+    (0..Delimiter::ALL.len())
+        .filter(|&i| first[i] > 0)
+        // The first listed wins a tie.
+        .max_by_key(|&i| {
+            let steady = lines.iter().all(|line| line[i] == first[i]);
+            (steady, first[i], Reverse(i))
+        })
+        .map_or(Delimiter::Comma, |i| Delimiter::ALL[i])
+}
+
+/// Which columns of a CSV file that isn't a known manager's export hold
+/// what, as they're mapped by hand. Saved by the file's [`signature`] when
+/// it has a header row, so the next export like it is read the same way.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Mapping {
+    pub delimiter: Delimiter,
+    /// Whether the first row names the columns, rather than being an entry.
+    pub header: bool,
+    pub password: usize,
+    /// The column an entry is listed by, if not its number.
+    pub label: Option<usize>,
+}
+
+impl Mapping {
+    /// The columns of a file whose rows have `count` fields, as the first
+    /// one does.
+    pub fn columns(&self, count: usize) -> Result<Columns, String> {
+        if let Some(column) = [Some(self.password), self.label]
+            .into_iter()
+            .flatten()
+            .find(|&column| column >= count)
+        {
+            return Err(format!(
+                "the file has {count} {}, so there's no column {}",
+                if count == 1 { "column" } else { "columns" },
+                column + 1
+            ));
+        }
+        Ok(Columns {
+            name: self.label,
+            site: None,
+            username: None,
+            password: self.password,
+            count,
+        })
+    }
+}
+
+/// A header row as the mapping for its file is saved by: its names,
+/// trimmed and lowercased, whatever they're split at. A file without one has
+/// no signature, as its first row is an entry.
+pub fn signature(header: &[&str]) -> String {
+    header
+        .iter()
+        .map(|name| name.trim().to_lowercase())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Where in a row of `manager`'s export each thing about the entry is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Columns {
+    pub name: Option<usize>,
+    pub site: Option<usize>,
+    pub username: Option<usize>,
+    pub password: usize,
+    /// How many fields the header has, and so every row should.
+    pub count: usize,
+}
+
+impl Columns {
+    /// Finds `manager`'s columns in `header`, which needs at least the
+    /// password one.
+    pub fn find(header: &[&str], manager: Manager) -> Result<Self, String> {
+        let [name, site, username, password] =
+            manager.columns().map(|names| position(header, names));
+        let password = password.ok_or_else(|| {
+            format!(
+                "the header has no {} column; is this a {manager} export?",
+                manager.columns()[3][0]
+            )
+        })?;
+        Ok(Self {
+            name,
+            site,
+            username,
+            password,
+            count: header.len(),
+        })
+    }
+
+    /// The label `row` is listed by: `name (username)`, with the site's
+    /// host standing in for a missing name, or whichever of the two it has.
+    pub fn label(&self, row: &[&str]) -> String {
+        let field = |column: Option<usize>| column.map_or("", |i| row[i].trim());
+        let name = match field(self.name) {
+            "" => host(field(self.site)),
+            name => name,
+        };
+        match (name, field(self.username)) {
+            ("", username) => username.to_owned(),
+            (name, "") => name.to_owned(),
+            (name, username) => format!("{name} ({username})"),
+        }
+    }
+}
+
+/// The host of `site`'s URL, or all of it if it isn't one.
+fn host(site: &str) -> &str {
+    let rest = site.split_once("://").map_or(site, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or(rest);
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    match host {
+        "" => site,
+        host => host,
+    }
+}
+
+/// A row of an export that couldn't be read, and so wasn't checked.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Malformed {
+    /// The line of the file the row starts on, 1-based.
+    pub line: usize,
+    pub reason: String,
+}
+
+impl fmt::Display for Malformed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.reason)
+    }
+}
+
+fn position(header: &[&str], names: &[&str]) -> Option<usize> {
+    header.iter().position(|column| {
+        names
+            .iter()
+            .any(|name| column.trim().eq_ignore_ascii_case(name))
+    })
+}
+
+/// Reads a password manager's or browser's CSV export, or with no
+/// `manager` whichever one's the header is [`detect`]ed as. Each row with a
+/// password becomes a line labelled by [`Columns::label`] and numbered by
+/// where it starts in
+/// the file; rows without one, as secure notes and cards are exported, are
+/// skipped, and rows that can't be read are listed in
+/// [`Lines::malformed`].
+pub fn read(bytes: &[u8], manager: Option<Manager>) -> Result<Lines, String> {
+    read_with(bytes, Delimiter::Comma, |header| {
+        let manager = manager
+            .or_else(|| detect(header))
+            .ok_or("the header isn't that of a Bitwarden, KeePass, Chrome or Firefox export")?;
+        Ok((Columns::find(header, manager)?, true))
+    })
+}
+
+/// Reads any other CSV file of passwords, as [`read`] does an export, with
+/// its columns as `mapping` has them.
+pub fn read_mapped(bytes: &[u8], mapping: &Mapping) -> Result<Lines, String> {
+    read_with(bytes, mapping.delimiter, |first| {
+        Ok((mapping.columns(first.len())?, mapping.header))
+    })
+}
+
+/// Reads an export as [`read`] does, or with no `manager` and a header no
+/// manager's is detected as, by the mapping saved for its [`signature`]
+/// split at the delimiter it's [`sniff`]ed as. `None` if there's no such
+/// mapping, and its columns need mapping.
+pub fn read_known(
+    bytes: &[u8],
+    manager: Option<Manager>,
+    mappings: &BTreeMap<String, Mapping>,
+) -> Result<Option<Lines>, String> {
+    let first = |delimiter| -> Result<_, String> {
+        rows(bytes, delimiter, 1)?
+            .pop()
+            .ok_or_else(|| "the file is empty".to_owned())
+    };
+    if manager.is_some() || detect(&fields(&first(Delimiter::Comma)?)).is_some() {
+        return read(bytes, manager).map(Some);
+    }
+    let delimiter = sniff(bytes);
+    match mappings.get(&signature(&fields(&first(delimiter)?))) {
+        Some(mapping) if mapping.header && mapping.delimiter == delimiter => {
+            read_mapped(bytes, mapping).map(Some)
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Up to `limit` rows of a CSV file from the top, split at `delimiter`, as
+/// the columns are mapped from. Rows that aren't UTF-8 text are left out.
+pub fn rows(
+    bytes: &[u8],
+    delimiter: Delimiter,
+    limit: usize,
+) -> Result<Vec<Vec<Zeroizing<String>>>, String> {
+    let mut rows = Vec::new();
+    if limit == 0 {
+        return Ok(rows);
+    }
+    for_each_row(bytes, delimiter, |_, row| {
+        if let Ok(row) = row {
+            rows.push(
+                row.iter()
+                    .map(|&field| Zeroizing::new(field.to_owned()))
+                    .collect(),
+            );
+        }
+        if rows.len() == limit {
+            ControlFlow::Break(Ok(()))
+        } else {
+            ControlFlow::Continue(())
+        }
+    })?;
+    Ok(rows)
+}
+
+fn fields(row: &[Zeroizing<String>]) -> Vec<&str> {
+    row.iter().map(|field| field.as_str()).collect()
+}
+
+/// Reads the rows of a CSV file split at `delimiter` into [`Lines`], with
+/// the columns `layout` finds from the first row, and whether that row is
+/// a header rather than an entry.
+fn read_with(
+    bytes: &[u8],
+    delimiter: Delimiter,
+    layout: impl FnOnce(&[&str]) -> Result<(Columns, bool), String>,
+) -> Result<Lines, String> {
+    let mut layout = Some(layout);
+    let mut header: Option<Columns> = None;
+    let state = RandomState::new();
+    let mut seen: HashMap<u64, Vec<usize>> = HashMap::new();
+    let mut batch = Lines::default();
+    for_each_row(bytes, delimiter, |line, row| {
+        let row = match row {
+            Ok(row) => row,
+            Err(reason) if header.is_none() => {
+                return ControlFlow::Break(Err(format!("the header {reason}")));
+            }
+            Err(reason) => {
+                batch.malformed.push(Malformed {
+                    line,
+                    reason: format!("the row {reason}"),
+                });
+                return ControlFlow::Continue(());
+            }
+        };
+        let columns = match header {
+            Some(columns) => columns,
+            None => {
+                let layout = layout.take().expect("the first row is laid out once");
+                let (columns, is_header) = match layout(row) {
+                    Ok(layout) => layout,
+                    Err(error) => return ControlFlow::Break(Err(error)),
+                };
+                header = Some(columns);
+                if is_header {
+                    return ControlFlow::Continue(());
+                }
+                columns
+            }
+        };
+        if row.len() != columns.count {
+            batch.malformed.push(Malformed {
+                line,
+                reason: format!(
+                    "the row has {} fields, the header {}",
+                    row.len(),
+                    columns.count
+                ),
+            });
+            return ControlFlow::Continue(());
+        }
+        let password = row[columns.password];
+        if password.is_empty() {
+            return ControlFlow::Continue(());
+        }
+        // Keyed by a hash so the map holds no copies of its own.
+        let same = seen.entry(state.hash_one(password)).or_default();
+        let index = match same
+            .iter()
+            .find(|&&i| batch.passwords[i].expose() == password)
+        {
+            Some(&index) => index,
+            None => {
+                batch.passwords.push(password.to_owned().into());
+                same.push(batch.passwords.len() - 1);
+                batch.passwords.len() - 1
+            }
+        };
+        batch.lines.push(Line {
+            number: line,
+            label: columns.label(row),
+            username: columns
+                .username
+                .map_or("", |column| row[column].trim())
+                .to_owned(),
+            preview: mask(password),
+            password: index,
+        });
+        ControlFlow::Continue(())
+    })?;
+    if header.is_none() {
+        return Err("the file is empty".to_owned());
+    }
+    Ok(batch)
+}
+
+/// Calls `each` with every row of a CSV file split at `delimiter`, and the
+/// line of the file it starts on, until it breaks. A row that isn't UTF-8
+/// text is passed as why it can't be read.
+///
+/// Rows are unquoted into a single buffer that's wiped once the file is
+/// read, so the only copies of the passwords left are those `each` makes.
+fn for_each_row(
+    bytes: &[u8],
+    delimiter: Delimiter,
+    mut each: impl FnMut(usize, Result<&[&str], &'static str>) -> ControlFlow<Result<(), String>>,
+) -> Result<(), String> {
+    let mut input = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    let mut reader = ReaderBuilder::new().delimiter(delimiter.byte()).build();
+    // Unquoting only ever shortens a row, so any one of them fits.
+    let mut output = Zeroizing::new(vec![0; input.len()]);
+    let mut ends = vec![0; 16];
+
+    let mut rows = 0;
+    let mut lines_before = 0;
+    loop {
+        let read_from = input;
+        let (mut written, mut fields) = (0, 0);
+        let found = loop {
+            let (result, read, out, ended) =
+                reader.read_record(input, &mut output[written..], &mut ends[fields..]);
+            input = &input[read..];
+            written += out;
+            fields += ended;
+            match result {
+                ReadRecordResult::Record => break true,
+                ReadRecordResult::End => break false,
+                ReadRecordResult::OutputEndsFull => ends.resize(ends.len() * 2, 0),
+                // An empty input tells the reader the file has ended.
+                ReadRecordResult::InputEmpty => {}
+                ReadRecordResult::OutputFull => {
+                    unreachable!("the buffer is as long as the whole file")
+                }
+            }
+        };
+        if !found {
+            break;
+        }
+        // What was read may start with the last row's line ending and any
+        // blank lines after it.
+        let raw = &read_from[..read_from.len() - input.len()];
+        let newline = |&&b: &&u8| b == b'\n';
+        let skipped = raw
+            .iter()
+            .take_while(|b| matches!(b, b'\r' | b'\n'))
+            .filter(newline)
+            .count();
+        let line = lines_before + skipped + 1;
+        lines_before += raw.iter().filter(newline).count();
+        rows += 1;
+        if rows > MAX_IMPORT_LINES {
+            return Err(format!(
+                "the file has more than the {MAX_IMPORT_LINES} rows a batch allows"
+            ));
+        }
+
+        let flow = match std::str::from_utf8(&output[..written]) {
+            Ok(record) => {
+                let starts = std::iter::once(0).chain(ends[..fields].iter().copied());
+                let row: Vec<&str> = starts
+                    .zip(&ends[..fields])
+                    .map(|(start, &end)| &record[start..end])
+                    .collect();
+                each(line, Ok(&row))
+            }
+            Err(_) => each(line, Err("isn't UTF-8 text")),
+        };
+        if let ControlFlow::Break(result) = flow {
+            return result;
+        }
+    }
+    Ok(())
+}
+
+/// The lines each of `batch`'s passwords is used on, in file order, so a
+/// password with more than one is reused across sites.
+pub fn reuse(batch: &Lines) -> Vec<Vec<usize>> {
+    let mut groups = vec![Vec::new(); batch.passwords.len()];
+    for (index, line) in batch.lines.iter().enumerate() {
+        groups[line.password].push(index);
+    }
+    groups
+}
+
+/// The password that's the worst finding among `groups`, as [`reuse`]
+/// returns them, given how many times `count` says each was seen: one
+/// that's breached and reused beats any that's only breached, and then the
+/// most seen, then the most reused, wins. `None` if none was breached.
+pub fn worst(groups: &[Vec<usize>], count: impl Fn(usize) -> Option<u64>) -> Option<usize> {
+    groups
+        .iter()
+        .enumerate()
+        .filter_map(|(password, lines)| {
+            let count = count(password).filter(|&count| count > 0)?;
+            Some(((lines.len() > 1, count, lines.len()), password))
+        })
+        // The first in the file wins a tie.
+        .max_by_key(|&(key, password)| (key, Reverse(password)))
+        .map(|(_, password)| password)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BITWARDEN: &[u8] = include_bytes!("../tests/fixtures/bitwarden.csv");
+    const KEEPASS: &[u8] = include_bytes!("../tests/fixtures/keepass.csv");
+    const CHROME: &[u8] = include_bytes!("../tests/fixtures/chrome.csv");
+    const FIREFOX: &[u8] = include_bytes!("../tests/fixtures/firefox.csv");
+
+    /// Each line's number, label, username and password.
+    fn entries(batch: &Lines) -> Vec<(usize, &str, &str, &str)> {
+        batch
+            .lines
+            .iter()
+            .map(|line| {
+                (
+                    line.number,
+                    line.label.as_str(),
+                    line.username.as_str(),
+                    batch.passwords[line.password].expose(),
+                )
+            })
+            .collect()
+    }
+
+    fn header(bytes: &[u8]) -> Vec<String> {
+        rows(bytes, Delimiter::Comma, 1).unwrap()[0]
+            .iter()
+            .map(|field| field.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn each_export_is_detected_by_its_header() {
+        for (bytes, manager) in [
+            (BITWARDEN, Manager::Bitwarden),
+            (KEEPASS, Manager::KeePass),
+            (CHROME, Manager::Chrome),
+            (FIREFOX, Manager::Firefox),
+        ] {
+            let header = header(bytes);
+            let header: Vec<&str> = header.iter().map(String::as_str).collect();
+            assert_eq!(detect(&header), Some(manager), "{header:?}");
+        }
+        assert_eq!(detect(&["site", "secret"]), None);
+    }
+
+    #[test]
+    fn columns_are_found_whatever_their_order_or_case() {
+        let columns = Columns::find(&["URL", " Password", "name", "username"], Manager::Chrome);
+        assert_eq!(
+            columns,
+            Ok(Columns {
+                name: Some(2),
+                site: Some(0),
+                username: Some(3),
+                password: 1,
+                count: 4,
+            })
+        );
+        let columns = Columns::find(&["url", "password", "httpRealm"], Manager::Firefox).unwrap();
+        assert_eq!((columns.name, columns.username), (None, None));
+    }
+
+    #[test]
+    fn an_entry_without_a_name_is_labelled_by_its_host() {
+        let columns = Columns::find(&["name", "url", "username", "password"], Manager::Chrome);
+        let columns = columns.unwrap();
+        for (row, label) in [
+            (
+                ["Mail", "https://mail.example.com", "ann", "x"],
+                "Mail (ann)",
+            ),
+            (
+                ["", "https://ann:pw@mail.example.com/in?x#y", "", "x"],
+                "mail.example.com",
+            ),
+            (
+                ["", "mail.example.com/in", "ann", "x"],
+                "mail.example.com (ann)",
+            ),
+            (["", "", "ann", "x"], "ann"),
+            (["", "", "", "x"], ""),
+        ] {
+            assert_eq!(columns.label(&row), label, "{row:?}");
+        }
+    }
+
+    #[test]
+    fn a_bitwarden_export_skips_notes_and_reports_broken_rows() {
+        let batch = read(BITWARDEN, None).unwrap();
+        assert_eq!(
+            entries(&batch),
+            [
+                (
+                    2,
+                    "Example Mail (ann@example.com)",
+                    "ann@example.com",
+                    "hunter2"
+                ),
+                (5, "Shop, \"old\"", "", "correct horse battery staple"),
+                (7, "forum.example.com (ann)", "ann", "hunter2"),
+            ]
+        );
+        assert_eq!(
+            batch.malformed,
+            [Malformed {
+                line: 6,
+                reason: "the row has 6 fields, the header 11".to_owned(),
+            }]
+        );
+    }
+
+    #[test]
+    fn a_keepass_export_skips_entries_without_a_password() {
+        let batch = read(KEEPASS, None).unwrap();
+        assert_eq!(
+            entries(&batch),
+            [
+                (
+                    2,
+                    "Example Mail (ann@example.com)",
+                    "ann@example.com",
+                    "hunter2"
+                ),
+                (3, "Bank (ann)", "ann", "Tr0ub4dor&3"),
+            ]
+        );
+        assert!(batch.malformed.is_empty());
+    }
+
+    #[test]
+    fn a_chrome_export_is_labelled_by_name_and_username() {
+        let batch = read(CHROME, None).unwrap();
+        assert_eq!(
+            entries(&batch),
+            [
+                (
+                    2,
+                    "mail.example.com (ann@example.com)",
+                    "ann@example.com",
+                    "hunter2"
+                ),
+                (3, "shop.example.com (ann)", "ann", "hunter2"),
+                (
+                    4,
+                    "forum.example.com (ann)",
+                    "ann",
+                    "correct horse battery staple"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn a_firefox_export_is_labelled_by_host() {
+        let batch = read(FIREFOX, None).unwrap();
+        assert_eq!(
+            entries(&batch),
+            [
+                (
+                    2,
+                    "mail.example.com (ann@example.com)",
+                    "ann@example.com",
+                    "hunter2"
+                ),
+                (3, "wiki.example.com:8443", "", "hunter2"),
+            ]
+        );
+    }
+
+    #[test]
+    fn a_password_used_on_several_entries_is_kept_once() {
+        let batch = read(BITWARDEN, None).unwrap();
+        assert_eq!(batch.passwords.len(), 2);
+        assert_eq!(reuse(&batch), [vec![0, 2], vec![1]]);
+        let batch = read(FIREFOX, None).unwrap();
+        assert_eq!(reuse(&batch), [vec![0, 1]]);
+    }
+
+    #[test]
+    fn the_worst_is_breached_and_reused() {
+        let batch = read(CHROME, None).unwrap();
+        let groups = reuse(&batch);
+        // Seen more, but only on one entry.
+        assert_eq!(
+            worst(&groups, |password| [Some(3), Some(900)][password]),
+            Some(0)
+        );
+        assert_eq!(
+            worst(&groups, |password| [Some(0), Some(900)][password]),
+            Some(1)
+        );
+        assert_eq!(worst(&groups, |_| None), None);
+    }
+
+    #[test]
+    fn the_wrong_manager_or_an_unknown_header_is_an_error() {
+        let error = read(CHROME, Some(Manager::Bitwarden)).unwrap_err();
+        assert_eq!(
+            error,
+            "the header has no login_password column; is this a Bitwarden export?"
+        );
+        let error = read(b"site,secret\nmail,hunter2\n", None).unwrap_err();
+        assert_eq!(
+            error,
+            "the header isn't that of a Bitwarden, KeePass, Chrome or Firefox export"
+        );
+        assert_eq!(read(b"", None).unwrap_err(), "the file is empty");
+    }
+
+    #[test]
+    fn rows_that_arent_utf8_are_reported() {
+        let batch = read(
+            b"name,url,username,password,note\nshop,,ann,\xFFhunter2,\nmail,,ann,hunter2,\n",
+            None,
+        )
+        .unwrap();
+        assert_eq!(entries(&batch), [(3, "mail (ann)", "ann", "hunter2")]);
+        assert_eq!(
+            batch.malformed[0].to_string(),
+            "line 2: the row isn't UTF-8 text"
+        );
+    }
+
+    #[test]
+    fn the_delimiter_is_the_one_on_every_line() {
+        assert_eq!(sniff(b"a;b;c\n1;2;3\n"), Delimiter::Semicolon);
+        assert_eq!(sniff(b"a\tb\n\"1;2\"\t3\n"), Delimiter::Tab);
+        assert_eq!(sniff(b"password\n"), Delimiter::Comma);
+        assert_eq!(sniff(KEEPASS), Delimiter::Comma);
+    }
+
+    #[test]
+    fn a_file_mapped_by_hand_is_read_by_its_mapping() {
+        let mapping = Mapping {
+            delimiter: Delimiter::Semicolon,
+            header: true,
+            password: 1,
+            label: Some(0),
+        };
+        let bytes = b"Site;Secret\nmail;hunter2\nshop;\n";
+        let batch = read_mapped(bytes, &mapping).unwrap();
+        assert_eq!(entries(&batch), [(2, "mail", "", "hunter2")]);
+
+        let mappings = BTreeMap::from([(signature(&["Site ", "SECRET"]), mapping)]);
+        let batch = read_known(bytes, None, &mappings).unwrap().unwrap();
+        assert_eq!(entries(&batch), [(2, "mail", "", "hunter2")]);
+        assert!(read_known(bytes, None, &BTreeMap::new()).unwrap().is_none());
+
+        let error = Mapping {
+            password: 2,
+            ..mapping
+        }
+        .columns(2)
+        .unwrap_err();
+        assert_eq!(error, "the file has 2 columns, so there's no column 3");
+    }
+
+    #[test]
+    fn delimiters_in_quoted_fields_dont_count() {
+        // Two commas a line, but only inside the quotes.
+        let quoted = b"name;password\n\"Smith, J\";\"a,b\"\n\"x\";\"1,2;\"\"3\"\"\"\n";
+        assert_eq!(sniff(quoted), Delimiter::Semicolon);
+        // A quoted line break doesn't end the line it's on.
+        let multiline = b"note\tpassword\n\"one\ntwo, three\"\thunter2\n";
+        assert_eq!(sniff(multiline), Delimiter::Tab);
+    }
+
+    #[test]
+    fn the_steady_delimiter_beats_the_most_frequent() {
+        // Commas on the first line only, which make them the most seen.
+        assert_eq!(sniff(b"a,b,c,d;e\nf;g\nh;i\n"), Delimiter::Semicolon);
+        // Equally steady, the more of them wins; then the first listed.
+        assert_eq!(sniff(b"a;b;c,d\ne;f;g,h\n"), Delimiter::Semicolon);
+        assert_eq!(sniff(b"a;b\tc\nd;e\tf\n"), Delimiter::Semicolon);
+        assert_eq!(sniff(b"a,b;c\nd,e;f\n"), Delimiter::Comma);
+    }
+
+    #[test]
+    fn sniffing_skips_a_bom_blank_lines_and_carriage_returns() {
+        assert_eq!(sniff(b"\xEF\xBB\xBF\r\n\na\tb\r\nc\td\r\n"), Delimiter::Tab);
+        assert_eq!(sniff(b""), Delimiter::Comma);
+        assert_eq!(sniff(b"\n\r\n"), Delimiter::Comma);
+        // A last line without its line ending counts too.
+        assert_eq!(sniff(b"a;b\nc;d"), Delimiter::Semicolon);
+    }
+
+    #[test]
+    fn only_the_first_lines_are_sniffed() {
+        let mut bytes = b"a;b\n".repeat(SNIFF_LINES);
+        bytes.extend_from_slice(b"c,d,e,f,g\n");
+        assert_eq!(sniff(&bytes), Delimiter::Semicolon);
+    }
+
+    #[test]
+    fn a_mapping_reads_quoted_delimiters_quotes_and_line_breaks() {
+        let mapping = Mapping {
+            delimiter: Delimiter::Semicolon,
+            header: false,
+            password: 2,
+            label: Some(0),
+        };
+        let bytes = b"\"Bank; main\";x;\"pa;ss\"\n\
+                      \"say \"\"hi\"\"\";x;\"a \"\"quoted\"\" one\"\n\
+                      mail;x;\"two\nlines\"\n";
+        let batch = read_mapped(bytes, &mapping).unwrap();
+        assert_eq!(
+            entries(&batch),
+            [
+                (1, "Bank; main", "", "pa;ss"),
+                (2, "say \"hi\"", "", "a \"quoted\" one"),
+                (3, "mail", "", "two\nlines"),
+            ]
+        );
+    }
+
+    #[test]
+    fn a_mapping_without_a_label_lists_entries_by_line() {
+        let mapping = Mapping {
+            delimiter: Delimiter::Tab,
+            header: true,
+            password: 0,
+            label: None,
+        };
+        let batch = read_mapped(b"secret\tnote\nhunter2\t\"a\tb\"\n", &mapping).unwrap();
+        assert_eq!(entries(&batch), [(2, "", "", "hunter2")]);
+        let error = read_mapped(
+            b"just one\n",
+            &Mapping {
+                password: 1,
+                ..mapping
+            },
+        )
+        .unwrap_err();
+        assert_eq!(error, "the file has 1 column, so there's no column 2");
+    }
+
+    #[test]
+    fn a_saved_mapping_needs_the_same_delimiter() {
+        let mapping = Mapping {
+            delimiter: Delimiter::Comma,
+            header: true,
+            password: 1,
+            label: Some(0),
+        };
+        let mappings = BTreeMap::from([(signature(&["site", "secret"]), mapping)]);
+        // The same names, but split at semicolons.
+        let bytes = b"site;secret\nmail;hunter2\n";
+        assert!(read_known(bytes, None, &mappings).unwrap().is_none());
+        let no_header = BTreeMap::from([(
+            signature(&["site", "secret"]),
+            Mapping {
+                header: false,
+                ..mapping
+            },
+        )]);
+        let bytes = b"\"site\",\"secret\"\nmail,hunter2\n";
+        assert!(read_known(bytes, None, &no_header).unwrap().is_none());
+        let batch = read_known(bytes, None, &mappings).unwrap().unwrap();
+        assert_eq!(entries(&batch), [(2, "mail", "", "hunter2")]);
+    }
+
+    #[test]
+    fn signatures_ignore_case_and_spacing_but_not_order() {
+        assert_eq!(signature(&[" Site", "PASSWORD "]), "site,password");
+        assert_ne!(signature(&["a", "b"]), signature(&["b", "a"]));
+        assert_eq!(signature(&[]), "");
+    }
+}
@@ -0,0 +1,113 @@
+/// Code points that don't show, or show as an ordinary space, but change
+/// the hash: picked up by copying from chat apps and web pages, they're the
+/// usual cause of a password that won't match its copy.
+const FLAGGED: &[(char, &str)] = &[
+    ('\u{00A0}', "no-break space"),
+    ('\u{00AD}', "soft hyphen"),
+    ('\u{034F}', "combining grapheme joiner"),
+    ('\u{061C}', "Arabic letter mark"),
+    ('\u{115F}', "Hangul choseong filler"),
+    ('\u{1160}', "Hangul jungseong filler"),
+    ('\u{180E}', "Mongolian vowel separator"),
+    ('\u{2007}', "figure space"),
+    ('\u{200B}', "zero-width space"),
+    ('\u{200C}', "zero-width non-joiner"),
+    ('\u{200D}', "zero-width joiner"),
+    ('\u{200E}', "left-to-right mark"),
+    ('\u{200F}', "right-to-left mark"),
+    ('\u{202A}', "left-to-right embedding"),
+    ('\u{202B}', "right-to-left embedding"),
+    ('\u{202C}', "pop directional formatting"),
+    ('\u{202D}', "left-to-right override"),
+    ('\u{202E}', "right-to-left override"),
+    ('\u{202F}', "narrow no-break space"),
+    ('\u{2060}', "word joiner"),
+    ('\u{2061}', "function application"),
+    ('\u{2062}', "invisible times"),
+    ('\u{2063}', "invisible separator"),
+    ('\u{2064}', "invisible plus"),
+    ('\u{2066}', "left-to-right isolate"),
+    ('\u{2067}', "right-to-left isolate"),
+    ('\u{2068}', "first strong isolate"),
+    ('\u{2069}', "pop directional isolate"),
+    ('\u{3164}', "Hangul filler"),
+    ('\u{FEFF}', "zero-width no-break space"),
+];
+
+/// The Unicode name of `c` if it's one of the flagged code points.
+pub fn name(c: char) -> Option<&'static str> {
+    FLAGGED
+        .iter()
+        .find(|&&(flagged, _)| flagged == c)
+        .map(|&(_, name)| name)
+}
+
+pub fn is_flagged(c: char) -> bool {
+    name(c).is_some()
+}
+
+/// The flagged code points in `password`, each once in order of first
+/// appearance, as `zero-width joiner (U+200D)`.
+pub fn found(password: &str) -> Vec<String> {
+    let mut found: Vec<char> = Vec::new();
+    for c in password.chars().filter(|&c| is_flagged(c)) {
+        if !found.contains(&c) {
+            found.push(c);
+        }
+    }
+    found
+        .into_iter()
+        .filter_map(|c| Some(format!("{} (U+{:04X})", name(c)?, u32::from(c))))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_list_is_sorted_without_repeats() {
+        assert!(FLAGGED.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    #[test]
+    fn what_people_type_isnt_flagged() {
+        for c in "hunter2 Hunter-2!é€字🔑\t".chars() {
+            assert!(!is_flagged(c), "{c:?}");
+        }
+        // Combining marks show, on the letter they follow.
+        assert!(!is_flagged('\u{0301}'));
+    }
+
+    #[test]
+    fn invisible_and_lookalike_spaces_are_named() {
+        assert_eq!(name('\u{200B}'), Some("zero-width space"));
+        assert_eq!(name('\u{00A0}'), Some("no-break space"));
+        assert_eq!(name('\u{FEFF}'), Some("zero-width no-break space"));
+        assert_eq!(name('\u{202E}'), Some("right-to-left override"));
+        assert_eq!(name('a'), None);
+    }
+
+    #[test]
+    fn each_code_point_is_listed_once_in_order() {
+        let password = "\u{FEFF}hun\u{200B}ter\u{200B}2\u{00A0}";
+        assert_eq!(
+            found(password),
+            [
+                "zero-width no-break space (U+FEFF)",
+                "zero-width space (U+200B)",
+                "no-break space (U+00A0)",
+            ]
+        );
+        assert!(found("hunter2").is_empty());
+        assert!(found("").is_empty());
+    }
+
+    #[test]
+    fn removing_strips_only_what_was_flagged() {
+        let password = "\u{FEFF}hün\u{200B}ter 2\u{0301}\u{00A0}";
+        let kept: String = password.chars().filter(|&c| !is_flagged(c)).collect();
+        assert_eq!(kept, "hünter 2\u{0301}");
+        assert!(found(&kept).is_empty());
+    }
+}
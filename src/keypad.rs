@@ -0,0 +1,207 @@
+use iced::alignment::Vertical;
+use iced::widget::{button, checkbox, column, row, text};
+use iced::{Element, Length};
+use rand::rngs::{OsRng, StdRng};
+use rand::seq::SliceRandom;
+use rand::{RngCore, SeedableRng};
+
+use crate::strings::Strings;
+
+const LETTERS: [&str; 4] = ["1234567890", "qwertyuiop", "asdfghjkl", "zxcvbnm"];
+const SYMBOLS: [&str; 4] = ["!@#$%^&*()", "-_=+[]{}\\|", ";:'\",.<>/?", "`~"];
+
+/// Which characters the keys type.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Page {
+    #[default]
+    Lower,
+    Upper,
+    Symbols,
+}
+
+/// The keys of `page`, row by row. With a `seed` they're shuffled across
+/// the whole page, keeping the rows' lengths, the same way for the same
+/// seed.
+pub fn layout(page: Page, seed: Option<u64>) -> Vec<Vec<char>> {
+    let rows = match page {
+        Page::Lower | Page::Upper => LETTERS,
+        Page::Symbols => SYMBOLS,
+    };
+    let mut keys: Vec<char> = rows.concat().chars().collect();
+    if page == Page::Upper {
+        keys.iter_mut().for_each(|key| key.make_ascii_uppercase());
+    }
+    if let Some(seed) = seed {
+        keys.shuffle(&mut StdRng::seed_from_u64(seed));
+    }
+    let mut keys = keys.into_iter();
+    rows.iter()
+        .map(|row| keys.by_ref().take(row.len()).collect())
+        .collect()
+}
+
+/// An on-screen keyboard for clicking the password in, so nothing reaches
+/// a keylogger. Every key is a button, closing it included.
+#[derive(Debug, Default)]
+pub struct Keypad {
+    page: Page,
+    /// Set while the layout is shuffled, and drawn afresh each time
+    /// shuffling is turned on.
+    seed: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    /// Handled by the app, which adds it to the password.
+    Key(char),
+    /// Handled by the app too.
+    Backspace,
+    Page(Page),
+    Shuffle(bool),
+    /// Handled by the app, which drops the keypad.
+    Close,
+}
+
+impl Keypad {
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::Page(page) => self.page = page,
+            Message::Shuffle(true) => self.seed = Some(OsRng.next_u64()),
+            Message::Shuffle(false) => self.seed = None,
+            Message::Key(_) | Message::Backspace | Message::Close => {}
+        }
+    }
+
+    pub fn view(&self, strings: &'static Strings) -> Element<'_, Message> {
+        let key = |label: String, message: Message| {
+            button(text(label).center().width(Length::Fill))
+                .on_press(message)
+                .width(36)
+        };
+        let rows = layout(self.page, self.seed).into_iter().map(|keys| {
+            row(keys
+                .into_iter()
+                .map(|c| key(c.to_string(), Message::Key(c)).into()))
+            .spacing(4)
+            .into()
+        });
+        let shift_to = match self.page {
+            Page::Upper => Page::Lower,
+            _ => Page::Upper,
+        };
+        let (symbols, symbols_to) = match self.page {
+            Page::Symbols => (strings.keypad_letters, Page::Lower),
+            _ => (strings.keypad_symbols, Page::Symbols),
+        };
+        column(rows)
+            .push(
+                row![
+                    button(strings.keypad_shift)
+                        .style(if self.page == Page::Upper {
+                            button::primary
+                        } else {
+                            button::secondary
+                        })
+                        .on_press(Message::Page(shift_to)),
+                    button(symbols)
+                        .style(button::secondary)
+                        .on_press(Message::Page(symbols_to)),
+                    button(text(strings.keypad_space).center().width(Length::Fill))
+                        .on_press(Message::Key(' '))
+                        .width(160),
+                    button("⌫")
+                        .style(button::secondary)
+                        .on_press(Message::Backspace),
+                    checkbox(strings.keypad_shuffle, self.seed.is_some())
+                        .on_toggle(Message::Shuffle),
+                    button(strings.keypad_close)
+                        .style(button::secondary)
+                        .on_press(Message::Close),
+                ]
+                .spacing(4)
+                .align_y(Vertical::Center),
+            )
+            .spacing(4)
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(layout: &[Vec<char>]) -> Vec<char> {
+        let mut keys = layout.concat();
+        keys.sort_unstable();
+        keys
+    }
+
+    fn lengths(layout: &[Vec<char>]) -> Vec<usize> {
+        layout.iter().map(Vec::len).collect()
+    }
+
+    #[test]
+    fn unshuffled_pages_are_the_usual_rows() {
+        let lower = layout(Page::Lower, None);
+        assert_eq!(lower[1].iter().collect::<String>(), "qwertyuiop");
+        assert_eq!(lengths(&lower), [10, 10, 9, 7]);
+        let upper = layout(Page::Upper, None);
+        assert_eq!(upper[0].iter().collect::<String>(), "1234567890");
+        assert_eq!(upper[3].iter().collect::<String>(), "ZXCVBNM");
+        let symbols = layout(Page::Symbols, None);
+        assert_eq!(lengths(&symbols), [10, 10, 10, 2]);
+        assert!(symbols.concat().iter().all(char::is_ascii_punctuation));
+    }
+
+    #[test]
+    fn a_seed_always_shuffles_the_same_way() {
+        for page in [Page::Lower, Page::Upper, Page::Symbols] {
+            for seed in [0, 1, 42, u64::MAX] {
+                assert_eq!(layout(page, Some(seed)), layout(page, Some(seed)));
+            }
+        }
+        assert_ne!(layout(Page::Lower, Some(1)), layout(Page::Lower, Some(2)));
+        assert_ne!(layout(Page::Lower, Some(1)), layout(Page::Lower, None));
+    }
+
+    #[test]
+    fn shuffling_moves_keys_but_keeps_them_all() {
+        for page in [Page::Lower, Page::Upper, Page::Symbols] {
+            let plain = layout(page, None);
+            for seed in 0..50 {
+                let shuffled = layout(page, Some(seed));
+                assert_eq!(lengths(&shuffled), lengths(&plain));
+                assert_eq!(keys(&shuffled), keys(&plain), "{page:?} {seed}");
+            }
+        }
+    }
+
+    #[test]
+    fn every_key_is_typable_once() {
+        for page in [Page::Lower, Page::Upper, Page::Symbols] {
+            let mut keys = keys(&layout(page, None));
+            let count = keys.len();
+            keys.dedup();
+            assert_eq!(keys.len(), count, "{page:?}");
+        }
+    }
+
+    #[test]
+    fn shuffling_draws_a_new_layout_each_time_it_is_turned_on() {
+        let mut keypad = Keypad::default();
+        keypad.update(Message::Shuffle(true));
+        let first = keypad.seed;
+        assert!(first.is_some());
+        keypad.update(Message::Shuffle(false));
+        assert_eq!(keypad.seed, None);
+        keypad.update(Message::Shuffle(true));
+        assert_ne!(keypad.seed, first);
+
+        keypad.update(Message::Page(Page::Symbols));
+        assert_eq!(keypad.page, Page::Symbols);
+        // Keys and closing are the app's to handle.
+        keypad.update(Message::Key('a'));
+        keypad.update(Message::Close);
+        assert_eq!(keypad.page, Page::Symbols);
+    }
+}
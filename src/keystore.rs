@@ -0,0 +1,241 @@
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use directories::ProjectDirs;
+use zeroize::Zeroizing;
+
+use cybersec_wow::secret::SecretString;
+
+const SERVICE: &str = env!("CARGO_PKG_NAME");
+const ACCOUNT: &str = "hibp-api-key";
+
+/// Somewhere to keep the HIBP API key between runs.
+pub trait KeyStore: fmt::Debug + Send + Sync {
+    fn load(&self) -> Result<Option<SecretString>, KeyStoreError>;
+    fn save(&self, key: &str) -> Result<(), KeyStoreError>;
+    fn delete(&self) -> Result<(), KeyStoreError>;
+    /// False when the key ends up in a plain file.
+    fn is_secure(&self) -> bool;
+}
+
+#[derive(Debug)]
+pub enum KeyStoreError {
+    Keyring(keyring::Error),
+    Io(PathBuf, io::Error),
+    NoConfigDir,
+}
+
+impl fmt::Display for KeyStoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyStoreError::Keyring(keyring::Error::NoStorageAccess(error)) => write!(
+                f,
+                "the system keyring is locked or refused access, unlock it and try again ({error})"
+            ),
+            KeyStoreError::Keyring(error) => write!(f, "the system keyring failed: {error}"),
+            KeyStoreError::Io(path, error) if error.kind() == io::ErrorKind::PermissionDenied => {
+                write!(
+                    f,
+                    "permission denied for {}, check the permissions of that directory",
+                    path.display()
+                )
+            }
+            KeyStoreError::Io(path, error) => write!(f, "{}: {error}", path.display()),
+            KeyStoreError::NoConfigDir => write!(f, "no configuration directory to store the key"),
+        }
+    }
+}
+
+impl std::error::Error for KeyStoreError {}
+
+/// The platform keyring: Keychain, Credential Manager or the Secret Service.
+#[derive(Debug)]
+pub struct KeyringStore(keyring::Entry);
+
+impl KeyStore for KeyringStore {
+    fn load(&self) -> Result<Option<SecretString>, KeyStoreError> {
+        match self.0.get_password() {
+            Ok(key) => Ok(Some(key.into())),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(error) => Err(KeyStoreError::Keyring(error)),
+        }
+    }
+
+    fn save(&self, key: &str) -> Result<(), KeyStoreError> {
+        self.0.set_password(key).map_err(KeyStoreError::Keyring)
+    }
+
+    fn delete(&self) -> Result<(), KeyStoreError> {
+        match self.0.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(error) => Err(KeyStoreError::Keyring(error)),
+        }
+    }
+
+    fn is_secure(&self) -> bool {
+        true
+    }
+}
+
+/// A file in the config directory, readable only by its owner where the
+/// platform allows. The fallback when there's no usable keyring.
+#[derive(Debug)]
+pub struct FileStore {
+    path: PathBuf,
+}
+
+impl FileStore {
+    pub fn new() -> Option<Self> {
+        let dirs = ProjectDirs::from("", "", SERVICE)?;
+        Some(Self {
+            path: dirs.config_dir().join(ACCOUNT),
+        })
+    }
+
+    fn io_error(&self, error: io::Error) -> KeyStoreError {
+        KeyStoreError::Io(self.path.clone(), error)
+    }
+}
+
+impl KeyStore for FileStore {
+    fn load(&self) -> Result<Option<SecretString>, KeyStoreError> {
+        match fs::read_to_string(&self.path).map(Zeroizing::new) {
+            Ok(key) => Ok(Some(key.trim().to_owned().into())),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(self.io_error(error)),
+        }
+    }
+
+    fn save(&self, key: &str) -> Result<(), KeyStoreError> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|e| self.io_error(e))?;
+        }
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        options
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(key.as_bytes()))
+            .map_err(|e| self.io_error(e))
+    }
+
+    fn delete(&self) -> Result<(), KeyStoreError> {
+        match fs::remove_file(&self.path) {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => Err(self.io_error(error)),
+            _ => Ok(()),
+        }
+    }
+
+    fn is_secure(&self) -> bool {
+        false
+    }
+}
+
+/// The keyring if one answers, otherwise the config file, along with the key
+/// already stored there. Blocks, since keyrings may talk to a daemon.
+pub fn open() -> Result<(Box<dyn KeyStore>, Option<SecretString>), KeyStoreError> {
+    if let Ok(entry) = keyring::Entry::new(SERVICE, ACCOUNT) {
+        let keyring = KeyringStore(entry);
+        match keyring.load() {
+            Ok(key) => return Ok((Box::new(keyring), key)),
+            // Locked rather than missing: the user can fix that, so say so.
+            Err(error @ KeyStoreError::Keyring(keyring::Error::NoStorageAccess(_))) => {
+                return Err(error);
+            }
+            Err(_) => {}
+        }
+    }
+    let file = FileStore::new().ok_or(KeyStoreError::NoConfigDir)?;
+    let key = file.load()?;
+    Ok((Box::new(file), key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file_store(dir: &tempfile::TempDir) -> FileStore {
+        FileStore {
+            path: dir.path().join("config").join(ACCOUNT),
+        }
+    }
+
+    fn loaded(store: &FileStore) -> Option<String> {
+        store.load().unwrap().map(|key| key.expose().to_owned())
+    }
+
+    #[test]
+    fn a_file_store_saves_loads_and_deletes() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = file_store(&dir);
+        assert_eq!(loaded(&store), None);
+        store.save("0123456789abcdef").unwrap();
+        assert_eq!(loaded(&store).as_deref(), Some("0123456789abcdef"));
+        store.save("fedcba").unwrap();
+        assert_eq!(loaded(&store).as_deref(), Some("fedcba"));
+        store.delete().unwrap();
+        assert_eq!(loaded(&store), None);
+        // Deleting what isn't there isn't an error.
+        store.delete().unwrap();
+        assert!(!store.is_secure());
+    }
+
+    #[test]
+    fn a_key_edited_by_hand_is_trimmed() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = file_store(&dir);
+        store.save("").unwrap();
+        fs::write(&store.path, " 0123456789abcdef\n").unwrap();
+        assert_eq!(loaded(&store).as_deref(), Some("0123456789abcdef"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn only_the_owner_can_read_the_file() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = file_store(&dir);
+        store.save("0123456789abcdef").unwrap();
+        let mode = fs::metadata(&store.path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[test]
+    fn errors_say_what_to_do() {
+        let locked = KeyStoreError::Keyring(keyring::Error::NoStorageAccess("locked".into()));
+        assert_eq!(
+            locked.to_string(),
+            "the system keyring is locked or refused access, unlock it and try again (locked)"
+        );
+        let denied = KeyStoreError::Io(
+            PathBuf::from("/etc/api-key"),
+            io::ErrorKind::PermissionDenied.into(),
+        );
+        assert_eq!(
+            denied.to_string(),
+            "permission denied for /etc/api-key, check the permissions of that directory"
+        );
+        assert_eq!(
+            KeyStoreError::NoConfigDir.to_string(),
+            "no configuration directory to store the key"
+        );
+    }
+
+    #[test]
+    fn a_directory_in_the_way_is_reported_with_its_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = file_store(&dir);
+        fs::create_dir_all(&store.path).unwrap();
+        let error = store.load().unwrap_err();
+        assert!(matches!(error, KeyStoreError::Io(ref path, _) if *path == store.path));
+        assert!(
+            error
+                .to_string()
+                .starts_with(&store.path.display().to_string())
+        );
+    }
+}
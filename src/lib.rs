@@ -0,0 +1,54 @@
+#[cfg(not(target_arch = "wasm32"))]
+pub mod batch;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod bloom;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod cache;
+pub mod catalog;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod checkpoint;
+pub mod common;
+pub mod composition;
+pub mod crack_time;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod download;
+pub mod error;
+pub mod export;
+pub mod fetch;
+pub mod generator;
+pub mod hash;
+pub mod hibp;
+pub mod history;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod import;
+pub mod invisible;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod metrics;
+pub mod model;
+pub mod offline;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod packed;
+pub mod pin;
+pub mod policy;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod pwdump;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod pwned;
+pub mod range;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod rate_limit;
+pub mod record;
+#[cfg(all(any(test, feature = "fixtures"), not(target_arch = "wasm32")))]
+pub mod replay;
+pub mod report;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod retry;
+pub mod secret;
+pub mod severity;
+pub mod suggest;
+pub mod summary;
+#[cfg(target_arch = "wasm32")]
+pub mod wasm;
+
+pub use hash::{hash_password, hash_prefix_of, hash_suffix_of};
+pub use model::{BreachResult, HashMode, LookupMeta, LookupSource, Normalization};
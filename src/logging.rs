@@ -0,0 +1,73 @@
+use std::env;
+use std::io::{self, IsTerminal};
+use std::path::PathBuf;
+
+use directories::ProjectDirs;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{self, Rotation};
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{EnvFilter, fmt};
+
+/// How many days of debug logs are kept before the oldest is deleted.
+const KEPT_LOGS: usize = 7;
+
+/// What `--verbose` turns on: everything this app logs, but nothing from
+/// the libraries under it.
+const VERBOSE: &str = "cybersec_wow=debug";
+
+/// Where the debug log is written, e.g. `~/.local/share/cybersec-wow/logs`
+/// on Linux.
+pub fn dir() -> Option<PathBuf> {
+    let dirs = ProjectDirs::from("", "", env!("CARGO_PKG_NAME"))?;
+    Some(dirs.data_dir().join("logs"))
+}
+
+/// The filter `RUST_LOG` asks for, or failing that warnings only, or with
+/// `verbose` debugging.
+fn filter(verbose: bool) -> EnvFilter {
+    EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new(if verbose { VERBOSE } else { "warn" }))
+}
+
+/// Sends events to stderr, filtered by `RUST_LOG` or `verbose`, and with
+/// `to_file` at debug level to a log a day in [`dir`] too. With `quiet`
+/// nothing goes to stderr unless `RUST_LOG` asks for it. The returned guard
+/// flushes the file when dropped, so it has to be held until exit.
+///
+/// Nothing logged anywhere carries a password or a full hash: the lookup
+/// path only ever logs the prefix that's sent to the API anyway.
+pub fn init(verbose: bool, quiet: bool, to_file: bool) -> Option<WorkerGuard> {
+    let stderr = (!quiet || env::var_os(EnvFilter::DEFAULT_ENV).is_some()).then(|| {
+        fmt::layer()
+            .with_writer(io::stderr)
+            .with_ansi(io::stderr().is_terminal())
+            .with_filter(filter(verbose))
+    });
+    let (file, guard) = match to_file.then(dir).flatten().map(|dir| {
+        rolling::Builder::new()
+            .rotation(Rotation::DAILY)
+            .filename_prefix("debug")
+            .filename_suffix("log")
+            .max_log_files(KEPT_LOGS)
+            .build(dir)
+    }) {
+        Some(Ok(appender)) => {
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            let layer = fmt::layer()
+                .with_writer(writer)
+                .with_ansi(false)
+                .with_filter(filter(true));
+            (Some(layer), Some(guard))
+        }
+        Some(Err(error)) => {
+            eprintln!("warning: could not open the debug log: {error}");
+            (None, None)
+        }
+        None => (None, None),
+    };
+    tracing_subscriber::registry()
+        .with(stderr)
+        .with(file)
+        .init();
+    guard
+}
@@ -1,158 +1,1278 @@
-use std::error::Error;
+mod batch_screen;
+mod caps_lock;
+mod catalog_screen;
+mod check_screen;
+mod cli;
+mod clipboard_monitor;
+mod compare_screen;
+mod config;
+mod generator_screen;
+mod high_contrast;
+mod identicon;
+mod keypad;
+mod keystore;
+mod logging;
+mod mapping_screen;
+mod notify;
+mod records;
+mod repl;
+mod screen;
+mod settings_screen;
+mod severity_view;
+mod shortcuts;
+mod strength;
+mod strings;
+#[cfg(test)]
+mod tests;
+mod tray;
+mod ui;
+mod update;
+mod virtual_rows;
+mod watch;
 
-use iced::alignment::Horizontal;
-use iced::futures::TryFutureExt;
-use iced::widget::{button, checkbox, column, container, row, text, text_input};
-use iced::{Element, Size, Task};
+use std::collections::BTreeMap;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
 
+use iced::keyboard;
+use iced::mouse;
+use iced::task;
+use iced::widget::{self as widget, scrollable, text_input};
+use iced::window;
+use iced::{Event, Point, Size, Subscription, Task, Theme, event};
+
+use chrono::{DateTime, Utc};
 use reqwest::Client;
-use sha1::{Digest, Sha1};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use cybersec_wow::metrics::Metrics;
+use cybersec_wow::pwned::{ProxySettings, SocksSettings};
+use cybersec_wow::rate_limit::RateLimiter;
+use cybersec_wow::{
+    BreachResult, HashMode, Normalization, batch, bloom, cache, catalog, composition, crack_time,
+    export, generator, hibp, import, policy, pwned, report, secret, severity, summary,
+};
+
+use batch_screen::BatchScreen;
+use cache::{DiskCache, RangeCache};
+use catalog_screen::CatalogScreen;
+use check_screen::{CheckScreen, InputMode, SearchResult};
+use compare_screen::CompareScreen;
+use config::ThemeChoice;
+use generator_screen::GeneratorScreen;
+use keystore::KeyStore;
+use policy::Policy;
+use pwned::{DEFAULT_ENDPOINT, InFlight, RangeClient, build_client, parse_endpoint};
+use screen::Screen;
+use secret::SecretString;
+use settings_screen::SettingsScreen;
+use severity::Thresholds;
+use shortcuts::Shortcut;
+use strings::Strings;
+use zeroize::Zeroize;
+
+/// The quick-check window at 100% scale: the field and a verdict.
+const QUICK_CHECK_SIZE: Size = Size::new(460., 200.);
+
+/// The window's size until it's been resized, and after its layout is reset.
+const DEFAULT_WINDOW_SIZE: Size = Size::new(640., 480.);
+
+/// How long the window has to stay put before its geometry is saved, so a
+/// drag doesn't write the settings file at every step.
+const WINDOW_SAVE_DELAY: Duration = Duration::from_millis(500);
 
-#[derive(Clone, Copy, Debug)]
-pub struct BreachResult {
-    sites: usize,
-    ocurances: u64,
+/// The geometry saved last session and the minimum size at the UI scale in
+/// use, for [`restored_position`], which can't capture them.
+static SAVED_WINDOW: OnceLock<(config::WindowGeometry, Size)> = OnceLock::new();
+
+/// The saved geometry, fitted to the monitor the window opens on. It's set
+/// while the window is created, from [`restored_position`].
+static FITTED_WINDOW: OnceLock<config::WindowGeometry> = OnceLock::new();
+
+/// Text sizes used across the views, so they're set in one place. The UI
+/// scale enlarges them along with every padding and spacing, through
+/// [`App::scale_factor`].
+pub struct Sizes {
+    pub title: f32,
+    pub heading: f32,
+    pub subheading: f32,
+    pub icon: f32,
+    /// The headline of a result banner.
+    pub banner: f32,
+    /// Monospaced hashes and request lines.
+    pub code: f32,
+    pub status: f32,
 }
 
-impl BreachResult {
-    pub fn new(input: &str, hash: &str) -> Self {
-        let entries: Vec<_> = parse_results(input)
-            .into_iter()
-            .filter(|(hash_suffix, _)| *hash_suffix == &hash[5..])
-            .collect();
+pub const SIZES: Sizes = Sizes {
+    title: 27.,
+    heading: 22.,
+    subheading: 20.,
+    icon: 20.,
+    banner: 18.,
+    code: 13.,
+    status: 12.,
+};
 
-        let ocurances = entries.iter().map(|(_, count)| count).sum();
+/// Below this, at 100% scale, the form's rows of buttons no longer fit.
+const MIN_WINDOW_SIZE: Size = Size::new(520., 420.);
 
+/// The widest the content column grows; wider windows center it.
+const CONTENT_MAX_WIDTH: f32 = 760.;
+
+/// How often the OS colour scheme is checked while the theme follows it.
+const SYSTEM_THEME_POLL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Check(check_screen::Message),
+    Batch(batch_screen::Message),
+    Compare(compare_screen::Message),
+    Generator(generator_screen::Message),
+    Catalog(catalog_screen::Message),
+    Settings(settings_screen::Message),
+    App(AppMessage),
+}
+
+/// What's about the app as a whole rather than one of its tabs.
+#[derive(Debug, Clone)]
+pub enum AppMessage {
+    /// A window's close button, which for the main window quits or, with
+    /// the tray enabled, hides it.
+    Shutdown(window::Id),
+    Quit,
+    OpenQuickCheck,
+    ShowMainWindow,
+    CreateTray,
+    Screen(Screen),
+    WindowOpened(window::Id, Size),
+    WindowMoved(window::Id, Point),
+    WindowResized(window::Id, Size),
+    SaveWindow(usize),
+    DetectSystemTheme,
+    SystemTheme(Result<bool, String>),
+    Shortcut(Shortcut),
+    ToggleShortcuts,
+    /// A key press, click or scroll anywhere in the window, with what a key
+    /// press said about Caps Lock.
+    Activity(Option<caps_lock::Hint>),
+    IdleTimeout(usize),
+    /// Whether a batch notification was clicked, or why it couldn't be shown.
+    Notified(Result<bool, String>),
+    WindowFocused(window::Id, bool),
+    ReportExported(Option<Result<PathBuf, String>>),
+}
+
+/// Everything on the settings screen, saved to the settings file whenever it
+/// changes. The API key lives in the keyring instead.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppSettings {
+    endpoint: Url,
+    padding: bool,
+    decoys: bool,
+    decoy_count: usize,
+    normalization: Normalization,
+    always_trim: bool,
+    /// Whether Paste & check empties the clipboard after checking what it pasted.
+    clear_clipboard_after_paste: bool,
+    /// How the columns of CSV files that aren't a known export were mapped,
+    /// by the signature of their header rows.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    import_mappings: BTreeMap<String, import::Mapping>,
+    /// How long watching the clipboard lasts before it stops by itself.
+    /// Watching is never saved as on, so each session has to be started.
+    clipboard_watch_minutes: u64,
+    /// Whether a password is checked once typing pauses for `auto_check_delay`.
+    auto_check: bool,
+    #[serde(with = "config::millis")]
+    auto_check_delay: Duration,
+    /// Whether the password and its result are cleared once nothing has been
+    /// pressed or scrolled for `idle_clear_after`.
+    idle_clear: bool,
+    #[serde(with = "config::seconds")]
+    idle_clear_after: Duration,
+    #[serde(skip)]
+    crack_profiles: Vec<crack_time::Profile>,
+    severity: Thresholds,
+    /// Whether copied summaries name the full hash rather than its prefix.
+    summary_full_hash: bool,
+    #[serde(with = "config::seconds")]
+    timeout: Duration,
+    proxy: ProxySettings,
+    socks: SocksSettings,
+    cache_capacity: usize,
+    #[serde(with = "config::seconds")]
+    cache_ttl: Duration,
+    disk_cache: bool,
+    #[serde(with = "config::seconds")]
+    disk_cache_ttl: Duration,
+    dataset_path: String,
+    #[serde(skip)]
+    api_key: SecretString,
+    batch_concurrency: usize,
+    /// Most requests a second, over the window's lookups together.
+    rate_limit: u32,
+    /// Whether a batch that ends while no window has focus says so with a
+    /// desktop notification.
+    notify_batch: bool,
+    download_concurrency: usize,
+    appearance: config::Appearance,
+    /// Where the window was last left; `None` for the defaults.
+    window: Option<config::WindowGeometry>,
+    language: strings::LanguageChoice,
+    /// Whether closing the main window leaves the app in the tray.
+    minimize_to_tray: bool,
+    /// Whether a PIN also goes through the breach check, or is only ranked.
+    pin_breach_check: bool,
+    /// Whether the app asks GitHub for a newer release as it starts.
+    check_updates_at_startup: bool,
+    /// Whether a debug log is written to the data directory too. Read as
+    /// the app starts, so a change takes effect on the next run.
+    debug_log: bool,
+    /// The organisation's password rules, checked alongside the breach
+    /// check. Only ever set in the settings file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    policy: Option<Policy>,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
         Self {
-            sites: entries.len(),
-            ocurances,
+            endpoint: parse_endpoint(DEFAULT_ENDPOINT).expect("default endpoint is valid"),
+            padding: true,
+            decoys: false,
+            decoy_count: 3,
+            normalization: Normalization::default(),
+            always_trim: false,
+            clear_clipboard_after_paste: false,
+            clipboard_watch_minutes: 5,
+            import_mappings: BTreeMap::new(),
+            auto_check: false,
+            auto_check_delay: Duration::from_millis(800),
+            idle_clear: true,
+            idle_clear_after: Duration::from_secs(120),
+            crack_profiles: crack_time::default_profiles(),
+            severity: Thresholds::default(),
+            summary_full_hash: false,
+            timeout: Duration::from_secs(10),
+            proxy: ProxySettings::default(),
+            socks: SocksSettings::default(),
+            cache_capacity: 128,
+            cache_ttl: Duration::from_secs(15 * 60),
+            disk_cache: true,
+            disk_cache_ttl: Duration::from_secs(7 * 24 * 60 * 60),
+            dataset_path: String::new(),
+            api_key: SecretString::default(),
+            batch_concurrency: 4,
+            rate_limit: 50,
+            notify_batch: true,
+            download_concurrency: 8,
+            appearance: config::Appearance::default(),
+            window: None,
+            language: strings::LanguageChoice::default(),
+            minimize_to_tray: true,
+            pin_breach_check: true,
+            check_updates_at_startup: false,
+            debug_log: false,
+            policy: None,
         }
     }
 }
 
-#[derive(Default, Debug)]
-enum SearchResult {
-    Breaches(BreachResult),
-    Errored(String),
+impl AppSettings {
+    /// The part that decides how requests go out.
+    fn network(&self) -> pwned::Settings {
+        pwned::Settings {
+            endpoint: self.endpoint.clone(),
+            padding: self.padding,
+            decoys: self.decoys,
+            decoy_count: self.decoy_count,
+            timeout: self.timeout,
+            proxy: self.proxy.clone(),
+            socks: self.socks.clone(),
+            rate_limit: self.rate_limit,
+        }
+    }
 
-    #[default]
-    NotSubmitted,
-    Searching,
+    /// The Bloom filter built for the offline dataset, if there is one, for
+    /// batches to rule hashes out with before asking the API.
+    fn dataset_filter(&self) -> Result<Option<Arc<bloom::Filter>>, bloom::FilterError> {
+        let path = self.dataset_path.trim();
+        if path.is_empty() {
+            return Ok(None);
+        }
+        Ok(bloom::Filter::for_dataset(Path::new(path))?.map(Arc::new))
+    }
 }
 
-#[derive(Debug, Clone)]
-pub enum Message {
-    Input(String),
-    Submit,
-    BreachResult(Result<BreachResult, String>),
-    ShowPassword(bool),
+/// What every tab works with: the settings, and the client, caches and
+/// limits their lookups share.
+#[derive(Debug)]
+pub struct Shared {
+    settings: AppSettings,
+    /// Set by whatever may have changed a setting, so the settings are only
+    /// compared with what was saved after a message that could change them.
+    settings_dirty: bool,
+    client: Client,
+    cache: RangeCache,
+    disk_cache: Option<DiskCache>,
+    in_flight: InFlight,
+    /// What the lookups have counted since start or the last reset, shown
+    /// in the settings.
+    metrics: Metrics,
+    /// Shared by every lookup the window makes, batch or not.
+    rate_limiter: RateLimiter,
+    /// Holds account, paste and domain lookups to the rate the key's
+    /// subscription allows, once it's known.
+    account_limiter: RateLimiter,
+    /// The key's subscription, kept for the session once it's been asked
+    /// for, until the key changes.
+    subscription: Option<Result<hibp::SubscriptionStatus, String>>,
+    mode: HashMode,
+    /// The system locale's thousands separator, for counts.
+    separator: char,
+    /// Whether the OS is in dark mode, as of the last check.
+    system_dark: Result<bool, String>,
+    /// The OS locale's language, read once at startup.
+    system_language: strings::Language,
+    /// How the last session report export went, shown wherever it can be
+    /// exported from.
+    report_export: Option<Result<PathBuf, String>>,
+}
+
+impl Shared {
+    /// The text for the chosen language, or the OS's for "System".
+    fn strings(&self) -> &'static Strings {
+        self.settings
+            .language
+            .resolve(self.system_language)
+            .strings()
+    }
+
+    /// The configured theme, or for "System" the one matching the OS. A failed
+    /// detection counts as dark.
+    fn theme(&self) -> Theme {
+        self.settings
+            .appearance
+            .effective_theme(*self.system_dark.as_ref().unwrap_or(&true))
+    }
+
+    fn range_client(&self) -> RangeClient {
+        RangeClient::new(
+            self.client.clone(),
+            self.settings.network(),
+            self.in_flight.clone(),
+            self.metrics.clone(),
+            self.rate_limiter.clone(),
+        )
+    }
+
+    /// [`AppSettings::dataset_filter`], with a filter that can't be used
+    /// logged and ignored, so the batch asks the API for everything.
+    fn dataset_filter(&self) -> Option<Arc<bloom::Filter>> {
+        self.settings
+            .dataset_filter()
+            .inspect_err(|error| tracing::warn!("not using the dataset filter: {error}"))
+            .ok()
+            .flatten()
+    }
+
+    /// The API key as typed, without the whitespace around it.
+    fn api_key(&self) -> SecretString {
+        self.settings.api_key.expose().trim().to_owned().into()
+    }
+
+    /// Runs an account, paste or domain `lookup` once the subscription's rate
+    /// allows, if it's known; otherwise it goes at once, as the API's 429s
+    /// are all there is to go by.
+    fn api_lookup<T, F>(&self, lookup: F) -> impl Future<Output = Result<T, String>> + use<T, F>
+    where
+        F: Future<Output = Result<T, hibp::ApiError>>,
+    {
+        let limiter = self.account_limiter.clone();
+        let rpm = match &self.subscription {
+            Some(Ok(subscription)) => Some(subscription.rpm),
+            _ => None,
+        };
+        async move {
+            if let Some(rpm) = rpm {
+                limiter.acquire_per_minute(rpm).await;
+            }
+            lookup.await.map_err(|e| e.to_string())
+        }
+    }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct App {
-    password: String,
-    current_hash: String,
-    show: bool,
-    state: SearchResult,
+    screen: Screen,
+    shared: Shared,
+    check: CheckScreen,
+    batch: BatchScreen,
+    compare: CompareScreen,
+    generator: GeneratorScreen,
+    catalog: CatalogScreen,
+    settings_screen: SettingsScreen,
+    /// When the app started, for the session report.
+    session_started: DateTime<Utc>,
+    /// The keyboard shortcut list, shown over everything else.
+    show_shortcuts: bool,
+    /// Bumped on every key press, click and scroll, to tell stale inactivity
+    /// timers from the latest one.
+    idle_generation: usize,
+    /// The running inactivity timer; only set while `idle_clear` is on.
+    idle_timer: Option<task::Handle>,
+    /// The window's geometry as of its last move or resize, saved once it
+    /// settles; `window_generation` tells stale save timers from the latest.
+    window_geometry: config::WindowGeometry,
+    window_generation: usize,
+    /// Set as `main` opens the main window.
+    main_window: Option<window::Id>,
+    /// The compact quick-check window, while it's open. It's another view
+    /// of the Check tab, so it shares its password, cache and settings.
+    quick_window: Option<window::Id>,
+    /// The app window with keyboard focus, if any.
+    focused_window: Option<window::Id>,
+    tray: Option<tray::Tray>,
+    config_dir: Option<PathBuf>,
+    /// The settings as last written, to tell when they need saving again.
+    saved_settings: String,
+    /// A problem reading the settings at startup.
+    config_notice: Option<config::ConfigError>,
+}
+
+impl Default for App {
+    fn default() -> Self {
+        Self::new(AppSettings::default()).expect("the default settings build a client")
+    }
 }
 
 impl App {
+    /// The app as the settings file and the environment set it up, saving
+    /// back to the settings file as they change.
+    fn load() -> Result<Self, String> {
+        let config_dir = config::dir();
+        let loaded = AppSettings::load(&config::Overrides::default());
+        if let Some(notice) = &loaded.notice {
+            tracing::warn!("{notice}");
+        }
+        let mut endpoint_error = None;
+        for (key, error) in loaded.invalid {
+            match key {
+                "endpoint" => endpoint_error = Some(error),
+                _ => tracing::warn!("{error}, so it's ignored"),
+            }
+        }
+        let shadowed = loaded.shadowed;
+        let settings = loaded.settings;
+        let mut saved = settings.clone();
+        shadowed.apply(&mut saved);
+        let saved_settings = toml::to_string(&saved).unwrap_or_default();
+        let mut app = Self::new(settings)?;
+        app.settings_screen.endpoint_error = endpoint_error;
+        app.settings_screen.shadowed = shadowed;
+
+        Ok(Self {
+            config_dir,
+            saved_settings,
+            config_notice: loaded.notice,
+            ..app
+        })
+    }
+
+    /// The app as `settings` set it up, looking everything up where they say.
+    /// Fails if no HTTP client can be built from them, as sending lookups
+    /// past a proxy they ask for would be worse than not starting.
+    fn new(settings: AppSettings) -> Result<Self, String> {
+        let client = build_client(&settings.network())?;
+        Ok(Self {
+            screen: Screen::default(),
+            check: CheckScreen::default(),
+            batch: BatchScreen::default(),
+            compare: CompareScreen::default(),
+            generator: GeneratorScreen::default(),
+            catalog: CatalogScreen::default(),
+            settings_screen: SettingsScreen::new(&settings),
+            session_started: Utc::now(),
+            show_shortcuts: false,
+            idle_generation: 0,
+            idle_timer: None,
+            window_geometry: settings
+                .window
+                .unwrap_or(config::WindowGeometry::new(DEFAULT_WINDOW_SIZE)),
+            window_generation: 0,
+            main_window: None,
+            quick_window: None,
+            focused_window: None,
+            tray: None,
+            // Only what was loaded from the settings file is saved back to it.
+            config_dir: None,
+            saved_settings: String::new(),
+            config_notice: None,
+            shared: Shared {
+                client,
+                cache: RangeCache::new(settings.cache_capacity, settings.cache_ttl),
+                disk_cache: settings
+                    .disk_cache
+                    .then(|| DiskCache::new(&settings.endpoint, settings.disk_cache_ttl))
+                    .flatten(),
+                in_flight: InFlight::default(),
+                metrics: Metrics::default(),
+                rate_limiter: RateLimiter::default(),
+                account_limiter: RateLimiter::default(),
+                subscription: None,
+                mode: HashMode::default(),
+                separator: summary::system_separator(),
+                system_dark: Ok(true),
+                system_language: strings::Language::detect(),
+                report_export: None,
+                settings_dirty: false,
+                settings,
+            },
+        })
+    }
+
+    /// Hands the message to the tab it's for, or to [`Self::update_app`] if
+    /// it's about the app as a whole, then saves the settings if it may have
+    /// changed them.
     pub fn update(&mut self, message: Message) -> Task<Message> {
+        let task = match message {
+            Message::Check(message) => self.update_check(message),
+            Message::Batch(message) => self.update_batch(message),
+            Message::Compare(message) => self.update_compare(message),
+            Message::Generator(message) => self.update_generator(message),
+            Message::Catalog(message) => self.catalog.update(message).map(Message::Catalog),
+            Message::Settings(message) => self.update_settings(message),
+            Message::App(message) => self.update_app(message),
+        };
+        if !mem::take(&mut self.shared.settings_dirty) {
+            return task;
+        }
+        match self.save_settings() {
+            Some(save) => Task::batch([task, save]),
+            None => task,
+        }
+    }
+
+    /// The Check tab, and what its messages ask of the other tabs.
+    fn update_check(&mut self, message: check_screen::Message) -> Task<Message> {
         match message {
-            Message::Input(input) => {
-                self.password = input;
-                self.current_hash = hash_password(&self.password);
-                self.state = SearchResult::NotSubmitted;
+            check_screen::Message::ExportReport(format) => return self.export_report(format),
+            check_screen::Message::UseSocks(enabled) => {
+                return self.update_settings(settings_screen::Message::UseSocks(enabled));
+            }
+            check_screen::Message::Clear => {
+                self.generator.clear();
+                self.compare.clear();
             }
+            _ => {}
+        }
+        let pasted = matches!(message, check_screen::Message::Pasted(_));
+        let task = self
+            .check
+            .update(message, &mut self.shared)
+            .map(Message::Check);
+        // A pasted password is checked where it can be seen.
+        if pasted && !self.check.clipboard_empty {
+            self.screen = Screen::Check;
+        }
+        task
+    }
+
+    /// The Batch tab, which is shown for a dropped file and may say it's
+    /// finished with a notification.
+    fn update_batch(&mut self, message: batch_screen::Message) -> Task<Message> {
+        match message {
+            batch_screen::Message::ExportReport(format) => return self.export_report(format),
+            batch_screen::Message::FileDropped(_) => self.screen = Screen::Batch,
+            _ => {}
+        }
+        let finished =
+            matches!(message, batch_screen::Message::Finished) && self.batch.run.is_some();
+        let task = self
+            .batch
+            .update(message, &mut self.shared)
+            .map(Message::Batch);
+        if finished
+            && self.shared.settings.notify_batch
+            && self.focused_window.is_none()
+            && let Some(stats) = &self.batch.stats
+        {
+            let strings = self.shared.strings();
+            let content = notify::batch_finished(stats, strings);
+            return Task::batch([
+                task,
+                Task::perform(notify::show(content, strings.window_title), |shown| {
+                    Message::App(AppMessage::Notified(shown))
+                }),
+            ]);
+        }
+        task
+    }
 
-            Message::Submit => {
-                self.state = SearchResult::Searching;
-                let hash = hash_password(&self.password);
-                return Task::future(search(hash).map_err(|e| e.to_string()))
-                    .map(Message::BreachResult);
+    /// The Compare tab, whose passwords are checked as a batch of two.
+    fn update_compare(&mut self, message: compare_screen::Message) -> Task<Message> {
+        match message {
+            compare_screen::Message::Submit => {
+                if !self.compare.can_submit() {
+                    return Task::none();
+                }
+                let shared = &self.shared;
+                let hashes = batch::hash_all(
+                    &self.compare.passwords(),
+                    shared.mode,
+                    shared.settings.normalization,
+                );
+                let updates = batch::check_all(
+                    shared.range_client(),
+                    hashes,
+                    shared.mode,
+                    shared.disk_cache.clone(),
+                    shared.settings.batch_concurrency,
+                    Vec::new(),
+                    shared.dataset_filter(),
+                );
+                self.compare.start(updates).map(Message::Compare)
+            }
+            message => self.compare.update(message).map(Message::Compare),
+        }
+    }
+
+    /// The Generator tab, whose password can be taken to the Check tab.
+    fn update_generator(&mut self, message: generator_screen::Message) -> Task<Message> {
+        match message {
+            generator_screen::Message::Use => {
+                let Some(generated) = self.generator.generated().cloned() else {
+                    return Task::none();
+                };
+                self.screen = Screen::Check;
+                self.check.use_password_form();
+                self.update_check(check_screen::Message::Input(generated))
+            }
+            message => self.generator.update(message).map(Message::Generator),
+        }
+    }
+
+    /// The Settings tab, and what its settings change about the window and
+    /// the other tabs.
+    fn update_settings(&mut self, message: settings_screen::Message) -> Task<Message> {
+        match message {
+            settings_screen::Message::ResetWindow => {
+                self.shared.settings.window = None;
+                self.shared.settings_dirty = true;
+                self.window_geometry = self.default_window();
+                self.window_generation += 1;
+                return self.resize_main_window(self.window_geometry.size());
+            }
+            settings_screen::Message::OpenQuickCheck => {
+                return self.update_app(AppMessage::OpenQuickCheck);
             }
-            Message::BreachResult(breach_result) => match breach_result {
-                Ok(breach) => self.state = SearchResult::Breaches(breach),
-                Err(error) => self.state = SearchResult::Errored(error),
+            // Turning it off aborts the timer already running.
+            settings_screen::Message::IdleClearEnabled(false) => self.idle_timer = None,
+            settings_screen::Message::UseSocks(false) => self.check.socks_disabled(),
+            _ => {}
+        }
+        let rescaled = matches!(message, settings_screen::Message::UiScale(_));
+        let task = self
+            .settings_screen
+            .update(message, &mut self.shared)
+            .map(Message::Settings);
+        let size = self.window_geometry.size();
+        let min = self.min_window_size();
+        if rescaled && (size.width < min.width || size.height < min.height) {
+            return Task::batch([task, self.resize_main_window(size.max(min))]);
+        }
+        task
+    }
+
+    /// What's about the app as a whole: its windows and tray, the tabs and
+    /// shortcuts, and clearing once it's been left idle.
+    fn update_app(&mut self, message: AppMessage) -> Task<Message> {
+        match message {
+            AppMessage::Activity(hint) => {
+                if let Some(hint) = hint {
+                    self.check.caps_lock.apply(hint);
+                }
+                // Dropping the handle aborts the timer this activity makes stale.
+                self.idle_timer = None;
+                if !self.shared.settings.idle_clear {
+                    return Task::none();
+                }
+                // A timer runs even with nothing to clear yet, since the key
+                // press that starts typing a password arrives before its input.
+                self.idle_generation += 1;
+                let generation = self.idle_generation;
+                let (task, handle) =
+                    Task::future(tokio::time::sleep(self.shared.settings.idle_clear_after))
+                        .map(move |()| Message::App(AppMessage::IdleTimeout(generation)))
+                        .abortable();
+                self.idle_timer = Some(handle.abort_on_drop());
+                return task;
+            }
+            AppMessage::IdleTimeout(generation) => {
+                // One that fired just before being aborted may still arrive.
+                if generation == self.idle_generation
+                    && self.shared.settings.idle_clear
+                    && self.can_clear()
+                {
+                    let clear = self.update_check(check_screen::Message::Clear);
+                    self.check.idle_cleared = true;
+                    return clear;
+                }
+            }
+            AppMessage::Notified(Ok(true)) => return self.update_app(AppMessage::ShowMainWindow),
+            AppMessage::Notified(Ok(false)) => {}
+            AppMessage::Notified(Err(error)) => {
+                tracing::warn!("could not show the batch notification: {error}");
+            }
+            AppMessage::WindowFocused(id, true) => self.focused_window = Some(id),
+            AppMessage::WindowFocused(id, false) => {
+                if self.focused_window == Some(id) {
+                    self.focused_window = None;
+                }
+            }
+            AppMessage::Shutdown(id) if Some(id) == self.quick_window => {
+                self.quick_window = None;
+                return window::close(id);
+            }
+            AppMessage::Shutdown(id) => {
+                if self.tray.is_some() && self.shared.settings.minimize_to_tray {
+                    return window::change_mode(id, window::Mode::Hidden);
+                }
+                return self.update_app(AppMessage::Quit);
+            }
+            AppMessage::Quit => {
+                // Dropping the handles aborts whatever is still running. Cache
+                // entries are renamed into place whole and the download resumes
+                // from its manifest, so nothing is left half-written.
+                self.check.search = None;
+                self.batch.run = None;
+                self.check.download = None;
+                self.check.password.zeroize();
+                self.check.current_hash.zeroize();
+                self.compare.clear();
+                self.tray = None;
+                return iced::exit();
+            }
+            AppMessage::OpenQuickCheck => {
+                self.check.use_password_form();
+                let focus = || text_input::focus(text_input::Id::new(check_screen::QUICK_INPUT));
+                if let Some(id) = self.quick_window {
+                    return Task::batch([window::gain_focus(id), focus()]);
+                }
+                let (id, open) = window::open(window::Settings {
+                    size: QUICK_CHECK_SIZE * self.shared.settings.appearance.scale.factor(),
+                    resizable: false,
+                    level: window::Level::AlwaysOnTop,
+                    exit_on_close_request: false,
+                    ..window::Settings::default()
+                });
+                self.quick_window = Some(id);
+                return open.then(move |_| focus());
+            }
+            AppMessage::ShowMainWindow => {
+                if let Some(id) = self.main_window {
+                    return Task::batch([
+                        window::change_mode(id, window::Mode::Windowed),
+                        window::gain_focus(id),
+                    ]);
+                }
+            }
+            AppMessage::CreateTray => match tray::Tray::new(self.shared.strings()) {
+                Ok(tray) => self.tray = Some(tray),
+                Err(error) => self.settings_screen.tray_error = Some(error),
             },
-            Message::ShowPassword(show) => self.show = show,
+            AppMessage::Screen(screen) => {
+                self.screen = screen;
+                if screen == Screen::Breaches {
+                    return self.catalog.open(&self.shared.client).map(Message::Catalog);
+                }
+            }
+            AppMessage::WindowOpened(id, size) if Some(id) == self.main_window => {
+                // Window events measure in UI-scaled units, the saved geometry
+                // doesn't.
+                let size = size * self.shared.settings.appearance.scale.factor();
+                // Opening at the saved size could still overflow a smaller monitor.
+                if let Some(fitted) = FITTED_WINDOW.get()
+                    && (fitted.width < size.width || fitted.height < size.height)
+                {
+                    return window::resize(id, fitted.size());
+                }
+            }
+            AppMessage::WindowOpened(..) => {}
+            AppMessage::WindowMoved(id, position) if Some(id) == self.main_window => {
+                let scale = self.shared.settings.appearance.scale.factor();
+                self.window_geometry.x = Some(position.x * scale);
+                self.window_geometry.y = Some(position.y * scale);
+                return self.save_window_later();
+            }
+            AppMessage::WindowResized(id, size) if Some(id) == self.main_window => {
+                let size = size * self.shared.settings.appearance.scale.factor();
+                self.window_geometry.width = size.width;
+                self.window_geometry.height = size.height;
+                return self.save_window_later();
+            }
+            AppMessage::WindowMoved(..) | AppMessage::WindowResized(..) => {}
+            AppMessage::SaveWindow(generation) => {
+                // A window left as it opens by default needn't be remembered,
+                // which also keeps a reset from being saved straight back.
+                if generation == self.window_generation
+                    && self.window_geometry != self.default_window()
+                {
+                    self.shared.settings.window = Some(self.window_geometry);
+                    self.shared.settings_dirty = true;
+                }
+            }
+            AppMessage::Shortcut(shortcut) => return self.shortcut(shortcut),
+            AppMessage::ToggleShortcuts => self.show_shortcuts = !self.show_shortcuts,
+            AppMessage::DetectSystemTheme => {
+                return Task::perform(
+                    async {
+                        tokio::task::spawn_blocking(config::detect_dark)
+                            .await
+                            .map_err(|e| e.to_string())?
+                    },
+                    |detected| Message::App(AppMessage::SystemTheme(detected)),
+                );
+            }
+            AppMessage::SystemTheme(detected) => {
+                if let Err(error) = &detected
+                    && self.shared.system_dark.is_ok()
+                {
+                    tracing::warn!("could not detect the system theme, using dark: {error}");
+                }
+                self.shared.system_dark = detected;
+            }
+            AppMessage::ReportExported(exported) => {
+                if exported.is_some() {
+                    self.shared.report_export = exported;
+                }
+            }
         }
 
         Task::none()
     }
 
-    pub fn view(&self) -> Element<Message> {
-        let password_not_empty = !self.password.is_empty();
-        let title = text("Is this password in a data breach?").size(27);
-        let message = match &self.state {
-            SearchResult::Breaches(breaches) => {
-                if breaches.sites == 0 {
-                    text!("No breaches using this password! It seems this password is safe to use.")
-                        .style(text::success)
-                } else {
-                    text!("This password has been found {} time(s) across {} website(s)\nYou should not use this password!", breaches.ocurances, breaches.sites).style(text::danger)
+    fn shortcut(&mut self, shortcut: Shortcut) -> Task<Message> {
+        // The help overlay and the plaintext warning are modal: only the keys
+        // that close them do anything.
+        if self.show_shortcuts {
+            if matches!(shortcut, Shortcut::Help | Shortcut::Escape) {
+                self.show_shortcuts = false;
+            }
+            return Task::none();
+        }
+        if self.batch.confirm_plaintext {
+            if shortcut == Shortcut::Escape {
+                self.batch.confirm_plaintext = false;
+            }
+            return Task::none();
+        }
+        match shortcut {
+            Shortcut::Help => self.show_shortcuts = true,
+            Shortcut::Escape if self.screen == Screen::Settings => self.screen = Screen::Check,
+            Shortcut::Escape if self.check.show_explainer => self.check.show_explainer = false,
+            Shortcut::Escape => return self.update_check(check_screen::Message::Clear),
+            Shortcut::Submit => {
+                if let Some(submit) = self.submit_message() {
+                    return self.update(submit);
                 }
             }
-            SearchResult::Errored(error) => text!("Error: {}", error).style(text::danger),
-            SearchResult::NotSubmitted => text!(""),
-            SearchResult::Searching => text!("Searching...").style(text::secondary),
+            Shortcut::FocusPassword => {
+                self.screen = Screen::Check;
+                self.check.input_mode = InputMode::Password;
+                let id = text_input::Id::new(check_screen::PASSWORD_INPUT);
+                return Task::batch([text_input::focus(id.clone()), text_input::select_all(id)]);
+            }
+            Shortcut::CopySummary => return self.update_check(check_screen::Message::CopySummary),
+            Shortcut::OpenSettings => return self.update_app(AppMessage::Screen(Screen::Settings)),
+            Shortcut::Screen(screen) => return self.update_app(AppMessage::Screen(screen)),
+            Shortcut::ToggleShowPassword => {
+                let show = !self.check.show;
+                return self.update_check(check_screen::Message::ShowPassword(show));
+            }
+            Shortcut::ScrollToTop if self.screen == Screen::Batch => {
+                return scrollable::snap_to(
+                    scrollable::Id::new(batch_screen::TABLE),
+                    scrollable::RelativeOffset::START,
+                );
+            }
+            Shortcut::ScrollToBottom if self.screen == Screen::Batch => {
+                return scrollable::snap_to(
+                    scrollable::Id::new(batch_screen::TABLE),
+                    scrollable::RelativeOffset::END,
+                );
+            }
+            Shortcut::ScrollToTop | Shortcut::ScrollToBottom => {}
+            Shortcut::FocusNext => return widget::focus_next(),
+            Shortcut::FocusPrevious => return widget::focus_previous(),
+        }
+        Task::none()
+    }
+
+    /// What the current tab's submit button would send, if it's enabled.
+    fn submit_message(&self) -> Option<Message> {
+        match self.screen {
+            Screen::Check => self.check.submit_message(&self.shared).map(Message::Check),
+            Screen::Batch => self
+                .batch
+                .can_start()
+                .then_some(Message::Batch(batch_screen::Message::CheckAll)),
+            Screen::Compare => self
+                .compare
+                .can_submit()
+                .then_some(Message::Compare(compare_screen::Message::Submit)),
+            Screen::Generator | Screen::Breaches | Screen::Settings => None,
+        }
+    }
+
+    /// Writes the settings out if anything changed since they were last saved.
+    fn save_settings(&mut self) -> Option<Task<Message>> {
+        let dir = self.config_dir.clone()?;
+        let mut settings = self.shared.settings.clone();
+        self.settings_screen.shadowed.apply(&mut settings);
+        let serialized = toml::to_string(&settings).ok()?;
+        if serialized == self.saved_settings {
+            return None;
+        }
+        self.saved_settings = serialized;
+        Some(Task::perform(
+            async move {
+                tokio::task::spawn_blocking(move || config::save(&dir, &settings))
+                    .await
+                    .map_err(|e| e.to_string())?
+                    .map_err(|e| e.to_string())
+            },
+            |saved| Message::Settings(settings_screen::Message::ConfigSaved(saved)),
+        ))
+    }
+
+    fn resize_main_window(&self, size: Size) -> Task<Message> {
+        self.main_window
+            .map_or_else(Task::none, |id| window::resize(id, size))
+    }
+
+    fn save_window_later(&mut self) -> Task<Message> {
+        self.window_generation += 1;
+        let generation = self.window_generation;
+        Task::future(tokio::time::sleep(WINDOW_SAVE_DELAY))
+            .map(move |()| Message::App(AppMessage::SaveWindow(generation)))
+    }
+
+    /// Asks where to save the session report, then writes it in `format`.
+    fn export_report(&self, format: report::Format) -> Task<Message> {
+        let report = self.session_report();
+        let separator = self.shared.separator;
+        let export = match format {
+            report::Format::Json => Task::future(save_export(
+                "Export session report",
+                "session-report.json",
+                "JSON",
+                move |file| report::write_json(file, &report).map_err(|e| e.to_string()),
+            )),
+            report::Format::Html => Task::future(save_export(
+                "Export session report",
+                "session-report.html",
+                "HTML",
+                move |file| report::write_html(file, &report, separator).map_err(|e| e.to_string()),
+            )),
         };
-        let content = column![
-            text!("SHA-1: {}", &self.current_hash),
-            row![
-                text_input("input password", &self.password)
-                    .secure(!self.show)
-                    .on_input(Message::Input)
-                    .on_submit_maybe(password_not_empty.then_some(Message::Submit)),
-                button("Submit").on_press_maybe(password_not_empty.then_some(Message::Submit))
-            ]
-            .spacing(5),
-            checkbox("Show Password", self.show).on_toggle(Message::ShowPassword),
-            message,
-        ]
-        .padding(10)
-        .spacing(5);
-        container(column![title, content].align_x(Horizontal::Center)).into()
+        export.map(|exported| Message::App(AppMessage::ReportExported(exported)))
+    }
+
+    /// The session history, which keeps the last 100 checks, and the last
+    /// batch, unfiltered and without plaintext, with the settings they were
+    /// checked under.
+    fn session_report(&self) -> report::SessionReport {
+        let checks = self
+            .check
+            .history
+            .iter()
+            .rev()
+            .map(|entry| report::Check {
+                label: entry.label.clone(),
+                record: entry.record.clone(),
+            })
+            .collect();
+        let shared = &self.shared;
+        let batch = (!self.batch.lines.is_empty()).then(|| {
+            let rows = self.batch.rows(false, false, shared);
+            let summary = export::BatchSummary::new(
+                &rows,
+                self.batch.started,
+                self.batch.finished.unwrap_or_else(Utc::now),
+                &shared.settings.endpoint,
+            );
+            (summary, rows)
+        });
+        report::SessionReport::new(
+            self.session_started,
+            report::Settings {
+                algorithm: shared.mode,
+                normalization: shared.settings.normalization,
+                padding: shared.settings.padding,
+                endpoint: shared.settings.endpoint.to_string(),
+            },
+            checks,
+            batch,
+        )
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        Subscription::batch([
+            window::close_requests().map(|id| Message::App(AppMessage::Shutdown(id))),
+            event::listen_with(|event, _, id| match event {
+                Event::Window(window::Event::Opened { size, .. }) => {
+                    Some(Message::App(AppMessage::WindowOpened(id, size)))
+                }
+                Event::Window(window::Event::Moved(position)) => {
+                    Some(Message::App(AppMessage::WindowMoved(id, position)))
+                }
+                Event::Window(window::Event::Resized(size)) => {
+                    Some(Message::App(AppMessage::WindowResized(id, size)))
+                }
+                Event::Window(window::Event::Focused) => {
+                    Some(Message::App(AppMessage::WindowFocused(id, true)))
+                }
+                Event::Window(window::Event::Unfocused) => {
+                    Some(Message::App(AppMessage::WindowFocused(id, false)))
+                }
+                Event::Window(window::Event::Closed) => {
+                    Some(Message::App(AppMessage::WindowFocused(id, false)))
+                }
+                Event::Window(window::Event::FileHovered(_)) => {
+                    Some(Message::Batch(batch_screen::Message::FileHovered))
+                }
+                Event::Window(window::Event::FilesHoveredLeft) => {
+                    Some(Message::Batch(batch_screen::Message::FilesHoveredLeft))
+                }
+                Event::Window(window::Event::FileDropped(path)) => {
+                    Some(Message::Batch(batch_screen::Message::FileDropped(path)))
+                }
+                Event::Keyboard(keyboard::Event::KeyPressed {
+                    key,
+                    text,
+                    modifiers,
+                    ..
+                }) => Some(Message::App(AppMessage::Activity(caps_lock::hint(
+                    &key,
+                    text.as_deref(),
+                    modifiers,
+                )))),
+                Event::Mouse(
+                    mouse::Event::ButtonPressed(_) | mouse::Event::WheelScrolled { .. },
+                ) => Some(Message::App(AppMessage::Activity(None))),
+                _ => None,
+            }),
+            keyboard::on_key_press(|key, modifiers| {
+                shortcuts::shortcut(&key, modifiers)
+                    .map(|shortcut| Message::App(AppMessage::Shortcut(shortcut)))
+            }),
+            self.check.subscription().map(Message::Check),
+            if self.tray.is_some() {
+                tray::subscription()
+            } else {
+                Subscription::none()
+            },
+            // Polled, since not every platform announces a change of scheme.
+            if self.shared.settings.appearance.theme == ThemeChoice::System {
+                iced::time::every(SYSTEM_THEME_POLL)
+                    .map(|_| Message::App(AppMessage::DetectSystemTheme))
+            } else {
+                Subscription::none()
+            },
+        ])
+    }
+
+    fn theme(&self) -> Theme {
+        self.shared.theme()
     }
-}
 
-pub fn hash_password(pass: &str) -> String {
-    if pass.is_empty() {
-        return "".into();
+    /// The window title, with the state of the last password check in front.
+    ///
+    /// iced 0.13 has no accessibility tree to speak of: widgets can't be given
+    /// accessible names or descriptions, nothing can be announced, and only
+    /// text fields take focus. Screen readers do read out a changed window
+    /// title, so this is what tells them a check started and how it ended.
+    fn title(&self, _window: window::Id) -> String {
+        let strings = self.shared.strings();
+        let status = match &self.check.state {
+            SearchResult::Searching => strings.title_checking,
+            SearchResult::Cancelled => strings.search_cancelled,
+            SearchResult::Breaches { record, .. } => match record.result {
+                BreachResult::Found { .. } => strings.title_breached,
+                BreachResult::NotFound => strings.title_not_found,
+            },
+            SearchResult::NotSubmitted
+            | SearchResult::Account(_)
+            | SearchResult::Pastes(_)
+            | SearchResult::Domain(_) => return strings.window_title.to_owned(),
+            _ => strings.title_failed,
+        };
+        format!("{status} — {}", strings.window_title)
     }
 
-    let mut hasher = Sha1::new();
-    hasher.update(pass.as_bytes());
-    let hash = hasher.finalize();
+    fn scale_factor(&self) -> f64 {
+        f64::from(self.shared.settings.appearance.scale.factor())
+    }
 
-    format!("{:X}", hash)
+    /// [`MIN_WINDOW_SIZE`] at the current UI scale.
+    fn min_window_size(&self) -> Size {
+        MIN_WINDOW_SIZE * self.shared.settings.appearance.scale.factor()
+    }
+
+    /// How the window opens with nothing saved, at the current UI scale.
+    fn default_window(&self) -> config::WindowGeometry {
+        config::WindowGeometry::new(DEFAULT_WINDOW_SIZE.max(self.min_window_size()))
+    }
+
+    /// Whether clearing the Check tab, which wipes what the Generator and
+    /// Compare tabs hold too, has anything left to wipe.
+    fn can_clear(&self) -> bool {
+        self.check.can_clear() || self.generator.has_generated() || self.compare.can_clear()
+    }
 }
 
-fn parse_results(input: &str) -> Vec<(&str, u64)> {
-    input
-        .lines()
-        .filter_map(|line| line.split_once(':'))
-        .filter_map(|(in_hash, count)| Some((in_hash, u64::from_str_radix(count, 10).ok()?)))
-        .collect()
+/// Runs a blocking key store call off the UI thread.
+async fn blocking<T: Send + 'static>(
+    call: impl FnOnce() -> Result<T, keystore::KeyStoreError> + Send + 'static,
+) -> Result<T, String> {
+    tokio::task::spawn_blocking(call)
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
 }
 
-async fn search(hash: String) -> Result<BreachResult, Box<dyn Error>> {
-    let client = Client::new();
-    let response = client
-        .get(format!(
-            "https://api.pwnedpasswords.com/range/{}",
-            &hash[..5]
-        ))
-        .send()
+/// Asks where to save an export, then writes it off the UI thread with
+/// `write`. `None` if the dialog was cancelled.
+async fn save_export(
+    title: &'static str,
+    file_name: &'static str,
+    kind: &'static str,
+    write: impl FnOnce(std::fs::File) -> Result<(), String> + Send + 'static,
+) -> Option<Result<PathBuf, String>> {
+    let extension = file_name
+        .rsplit_once('.')
+        .map_or("", |(_, extension)| extension);
+    let file = rfd::AsyncFileDialog::new()
+        .set_title(title)
+        .set_file_name(file_name)
+        .add_filter(kind, &[extension])
+        .save_file()
         .await?;
-    let body = response.text().await?;
+    let path = file.path().to_owned();
+    let written = tokio::task::spawn_blocking(move || {
+        let file = std::fs::File::create(&path).map_err(|e| e.to_string())?;
+        write(file)?;
+        Ok(path)
+    })
+    .await;
+    Some(written.unwrap_or_else(|e| Err(e.to_string())))
+}
 
-    Ok(BreachResult::new(&body, &hash))
+/// Where the window opens: the saved position, fitted to the `monitor` it
+/// opens on, or centered on it if the window had never been moved.
+fn restored_position(size: Size, monitor: Size) -> Point {
+    let (saved, min) = SAVED_WINDOW
+        .get()
+        .copied()
+        .unwrap_or((config::WindowGeometry::new(size), MIN_WINDOW_SIZE));
+    let fitted = saved.clamp(monitor, min);
+    let _ = FITTED_WINDOW.set(fitted);
+    fitted.position().unwrap_or(Point::new(
+        ((monitor.width - fitted.width) / 2.).max(0.),
+        ((monitor.height - fitted.height) / 2.).max(0.),
+    ))
 }
 
 fn main() -> iced::Result {
-    iced::application("Password databreach checker", App::update, App::view)
-        .theme(|_| iced::Theme::CatppuccinMacchiato)
-        .window_size(Size::new(640., 480.))
-        .run()
+    // Keeps the password out of core dumps and stops other processes of the
+    // same user from attaching to read it.
+    #[cfg(target_os = "linux")]
+    // SAFETY: PR_SET_DUMPABLE takes a plain integer and touches no memory.
+    unsafe {
+        libc::prctl(libc::PR_SET_DUMPABLE, 0);
+    }
+
+    let args = <cli::Args as clap::Parser>::parse();
+    if let Err(error) = args.check_lookup_options() {
+        error.exit();
+    }
+    let to_file = config::dir().is_some_and(|dir| config::debug_log(&dir));
+    let log_guard = logging::init(args.verbose, args.quiet, to_file);
+    if args.is_headless() {
+        let code = cli::run(&args);
+        // Exiting skips destructors, and the guard's is what flushes the log.
+        drop(log_guard);
+        process::exit(code);
+    }
+
+    let app = match App::load() {
+        Ok(app) => app,
+        Err(error) => {
+            eprintln!("error: could not set up the HTTP client: {error}");
+            drop(log_guard);
+            process::exit(2);
+        }
+    };
+    let min_size = app.min_window_size();
+    let (size, position) = match app.shared.settings.window {
+        Some(saved) => {
+            let _ = SAVED_WINDOW.set((saved, min_size));
+            (
+                saved.size(),
+                window::Position::SpecificWith(restored_position),
+            )
+        }
+        None => (DEFAULT_WINDOW_SIZE, window::Position::Default),
+    };
+    // A daemon rather than an application, for the quick-check window and
+    // for the tray, which can outlive every window.
+    iced::daemon(App::title, App::update, App::view)
+        // Every window is styled and scaled alike.
+        .theme(|app: &App, _| app.theme())
+        .scale_factor(|app: &App, _| app.scale_factor())
+        .subscription(App::subscription)
+        .run_with(move || {
+            let mut app = app;
+            let (main_window, open) = window::open(window::Settings {
+                size: size.max(min_size),
+                position,
+                // Fixed for the session; a bigger scale picked later still
+                // grows the window to fit, but can't raise this.
+                min_size: Some(min_size),
+                exit_on_close_request: false,
+                ..window::Settings::default()
+            });
+            app.main_window = Some(main_window);
+            let startup = Task::batch([
+                open.discard(),
+                if tray::SUPPORTED {
+                    Task::done(Message::App(AppMessage::CreateTray))
+                } else {
+                    Task::none()
+                },
+                app.check.check_health(&app.shared).map(Message::Check),
+                if app.shared.settings.check_updates_at_startup {
+                    Task::perform(update::check(app.shared.client.clone()), |result| {
+                        Message::Settings(settings_screen::Message::UpdateChecked(false, result))
+                    })
+                } else {
+                    Task::none()
+                },
+                Task::done(Message::App(AppMessage::DetectSystemTheme)),
+                Task::perform(
+                    blocking(|| {
+                        keystore::open().map(|(store, key)| (Arc::<dyn KeyStore>::from(store), key))
+                    }),
+                    |opened| Message::Settings(settings_screen::Message::KeyStoreOpened(opened)),
+                ),
+            ]);
+            (app, startup)
+        })
 }
@@ -0,0 +1,255 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::Arc;
+
+use iced::alignment::Vertical;
+use iced::widget::{button, checkbox, column, pick_list, row, text};
+use iced::{Element, Length};
+use zeroize::Zeroizing;
+
+use crate::batch::mask;
+use crate::import::{self, Delimiter, Mapping};
+use crate::strings::Strings;
+
+/// How many of the file's rows are shown to map its columns by.
+const PREVIEW_ROWS: usize = 5;
+
+/// A CSV file whose columns are being mapped, kept until it's read. Its
+/// bytes are wiped as the last copy drops.
+#[derive(Clone)]
+pub struct Unmapped(pub Arc<Zeroizing<Vec<u8>>>);
+
+impl fmt::Debug for Unmapped {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unmapped({} bytes)", self.0.len())
+    }
+}
+
+/// A column as it's offered for the password or the label: by its name in
+/// the header row, or by its number without one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Choice {
+    index: Option<usize>,
+    name: String,
+}
+
+impl fmt::Display for Choice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+/// The step between importing a CSV file that isn't a known manager's
+/// export and checking it: a preview of its first rows, masked unless
+/// they're shown, with the delimiter it's split at, whether it has a
+/// header row, and which columns hold the password and the label.
+#[derive(Debug)]
+pub struct MappingScreen {
+    file: Unmapped,
+    delimiter: Delimiter,
+    header: bool,
+    password: Option<usize>,
+    label: Option<usize>,
+    show: bool,
+    /// The header, if it has one, and then the preview rows.
+    rows: Vec<Vec<Zeroizing<String>>>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Delimiter(Delimiter),
+    Header(bool),
+    Password(Choice),
+    Label(Choice),
+    Show(bool),
+    /// Handled by the app, which reads the file by [`MappingScreen::mapping`].
+    Import,
+    Cancel,
+}
+
+impl MappingScreen {
+    /// Starts with the delimiter [`import::sniff`] finds, a header row, and
+    /// the columns whose names look like a password's and an entry's.
+    pub fn new(file: Unmapped) -> Self {
+        let mut screen = Self {
+            delimiter: import::sniff(&file.0),
+            file,
+            header: true,
+            password: None,
+            label: None,
+            show: false,
+            rows: Vec::new(),
+        };
+        screen.read();
+        if let Some(header) = screen.rows.first() {
+            let find = |names: &[&str]| {
+                header.iter().position(|column| {
+                    let column = column.trim().to_lowercase();
+                    names.iter().any(|name| column.contains(name))
+                })
+            };
+            screen.password = find(&["password", "passwort", "pass", "secret"]);
+            screen.label = find(&["name", "title", "account", "site", "url"]);
+        }
+        screen
+    }
+
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::Delimiter(delimiter) => {
+                self.delimiter = delimiter;
+                self.read();
+            }
+            Message::Header(header) => self.header = header,
+            Message::Password(choice) => self.password = choice.index,
+            Message::Label(choice) => self.label = choice.index,
+            Message::Show(show) => self.show = show,
+            Message::Import | Message::Cancel => {}
+        }
+    }
+
+    /// Rereads the preview, for a new delimiter, dropping any column
+    /// chosen that it no longer has.
+    fn read(&mut self) {
+        self.rows =
+            import::rows(&self.file.0, self.delimiter, PREVIEW_ROWS + 1).unwrap_or_default();
+        let count = self.columns();
+        self.password = self.password.filter(|&column| column < count);
+        self.label = self.label.filter(|&column| column < count);
+    }
+
+    /// How many columns the file has, going by its first row.
+    fn columns(&self) -> usize {
+        self.rows.first().map_or(0, Vec::len)
+    }
+
+    pub fn file(&self) -> &Unmapped {
+        &self.file
+    }
+
+    /// The mapping chosen, once there's a password column.
+    pub fn mapping(&self) -> Option<Mapping> {
+        Some(Mapping {
+            delimiter: self.delimiter,
+            header: self.header,
+            password: self.password?,
+            label: self.label,
+        })
+    }
+
+    /// What the mapping is saved by: the header row's [`import::signature`].
+    /// A file without one isn't remembered, as its first row is an entry.
+    pub fn signature(&self) -> Option<String> {
+        let header = self.rows.first().filter(|_| self.header)?;
+        let names: Vec<&str> = header.iter().map(|name| name.as_str()).collect();
+        Some(import::signature(&names))
+    }
+
+    fn choices(&self, strings: &'static Strings) -> Vec<Choice> {
+        (0..self.columns())
+            .map(|index| {
+                let name = self
+                    .rows
+                    .first()
+                    .filter(|_| self.header)
+                    .map(|header| header[index].trim())
+                    .filter(|name| !name.is_empty());
+                Choice {
+                    index: Some(index),
+                    name: match name {
+                        Some(name) => format!("{}: {name}", index + 1),
+                        None => (strings.mapping_column)(index + 1),
+                    },
+                }
+            })
+            .collect()
+    }
+
+    fn choice(&self, strings: &'static Strings, index: Option<usize>) -> Option<Choice> {
+        let index = index?;
+        self.choices(strings).into_iter().nth(index)
+    }
+
+    pub fn view(&self, strings: &'static Strings) -> Element<'_, Message> {
+        let choices = self.choices(strings);
+        let mut labels = vec![Choice {
+            index: None,
+            name: strings.mapping_no_label.to_owned(),
+        }];
+        labels.extend(choices.iter().cloned());
+        let label = self
+            .choice(strings, self.label)
+            .or_else(|| labels.first().cloned());
+        let entries = self.rows.iter().skip(usize::from(self.header));
+        let preview = column(entries.map(|entry| {
+            row(entry.iter().enumerate().map(|(index, field)| {
+                let field = if self.show {
+                    Cow::Borrowed(field.as_str())
+                } else {
+                    Cow::Owned(mask(field))
+                };
+                let cell = text(field)
+                    .font(iced::Font::MONOSPACE)
+                    .wrapping(text::Wrapping::None)
+                    .width(Length::FillPortion(1));
+                if Some(index) == self.password {
+                    cell.style(text::danger)
+                } else {
+                    cell
+                }
+                .into()
+            }))
+            .spacing(10)
+            .into()
+        }))
+        .spacing(2);
+        let header = self.rows.first().filter(|_| self.header).map(|header| {
+            row(header.iter().map(|name| {
+                text(name.trim().to_owned())
+                    .wrapping(text::Wrapping::None)
+                    .width(Length::FillPortion(1))
+                    .into()
+            }))
+            .spacing(10)
+        });
+
+        column![
+            text(strings.mapping_intro),
+            row![
+                text(strings.delimiter),
+                pick_list(Delimiter::ALL, Some(self.delimiter), Message::Delimiter),
+                checkbox(strings.mapping_header, self.header).on_toggle(Message::Header),
+                checkbox(strings.show_values, self.show).on_toggle(Message::Show),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            row![
+                text(strings.mode_password),
+                pick_list(
+                    choices,
+                    self.choice(strings, self.password),
+                    Message::Password
+                )
+                .placeholder(strings.choose_column),
+                text(strings.label),
+                pick_list(labels, label, Message::Label),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+        ]
+        .push_maybe(header)
+        .push(preview)
+        .push(
+            row![
+                button(strings.read_file)
+                    .on_press_maybe(self.mapping().is_some().then_some(Message::Import)),
+                button(strings.cancel)
+                    .style(button::secondary)
+                    .on_press(Message::Cancel),
+            ]
+            .spacing(10),
+        )
+        .spacing(5)
+        .into()
+    }
+}
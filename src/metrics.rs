@@ -0,0 +1,280 @@
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Counters for how lookups have gone since the last reset, shared by every
+/// clone. Each update is a single relaxed atomic add, so concurrent batch
+/// lookups can record freely; a [`Snapshot`] may be a request or so behind
+/// one that's in progress, which is fine for a tally.
+#[derive(Clone, Debug, Default)]
+pub struct Metrics(Arc<Counters>);
+
+#[derive(Debug, Default)]
+struct Counters {
+    requests: AtomicU64,
+    retries: AtomicU64,
+    rate_limited: AtomicU64,
+    failures: AtomicU64,
+    cache_hits: AtomicU64,
+    cache_misses: AtomicU64,
+    responses: AtomicU64,
+    latency_micros: AtomicU64,
+}
+
+impl Metrics {
+    /// A request sent, whatever came of it.
+    pub fn request(&self) {
+        add(&self.0.requests, 1);
+    }
+
+    /// A response received `latency` after its request was sent.
+    pub fn response(&self, latency: Duration) {
+        add(&self.0.responses, 1);
+        let micros = u64::try_from(latency.as_micros()).unwrap_or(u64::MAX);
+        add(&self.0.latency_micros, micros);
+    }
+
+    pub fn retry(&self) {
+        add(&self.0.retries, 1);
+    }
+
+    /// A 429 from the API.
+    pub fn rate_limited(&self) {
+        add(&self.0.rate_limited, 1);
+    }
+
+    /// A request that failed for good, after any retries.
+    pub fn failure(&self) {
+        add(&self.0.failures, 1);
+    }
+
+    /// A lookup a cache could have answered, and whether it did.
+    pub fn cache(&self, hit: bool) {
+        add(
+            if hit {
+                &self.0.cache_hits
+            } else {
+                &self.0.cache_misses
+            },
+            1,
+        );
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+        let responses = load(&self.0.responses);
+        Snapshot {
+            requests: load(&self.0.requests),
+            retries: load(&self.0.retries),
+            rate_limited: load(&self.0.rate_limited),
+            failures: load(&self.0.failures),
+            cache_hits: load(&self.0.cache_hits),
+            cache_misses: load(&self.0.cache_misses),
+            average_latency: (responses > 0)
+                .then(|| Duration::from_micros(load(&self.0.latency_micros) / responses)),
+        }
+    }
+
+    /// Zeroes every counter. Lookups in flight carry on counting into the
+    /// fresh tallies.
+    pub fn reset(&self) {
+        let Counters {
+            requests,
+            retries,
+            rate_limited,
+            failures,
+            cache_hits,
+            cache_misses,
+            responses,
+            latency_micros,
+        } = &*self.0;
+        for counter in [
+            requests,
+            retries,
+            rate_limited,
+            failures,
+            cache_hits,
+            cache_misses,
+            responses,
+            latency_micros,
+        ] {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+fn add(counter: &AtomicU64, n: u64) {
+    counter.fetch_add(n, Ordering::Relaxed);
+}
+
+/// The counters at one moment.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Snapshot {
+    /// Every request sent, retries and decoys included.
+    pub requests: u64,
+    pub retries: u64,
+    pub rate_limited: u64,
+    /// Requests that failed for good, after any retries.
+    pub failures: u64,
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+    /// Over the requests that got a response.
+    pub average_latency: Option<Duration>,
+}
+
+impl Snapshot {
+    /// The share of cacheable lookups a cache answered, if there were any.
+    pub fn hit_rate(&self) -> Option<f64> {
+        let lookups = self.cache_hits + self.cache_misses;
+        (lookups > 0).then(|| self.cache_hits as f64 / lookups as f64)
+    }
+}
+
+/// Three lines: requests, the cache and latency.
+impl fmt::Display for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "requests: {} ({} retries, {} rate limited, {} failed)",
+            self.requests, self.retries, self.rate_limited, self.failures
+        )?;
+        write!(
+            f,
+            "cache: {} hits, {} misses",
+            self.cache_hits, self.cache_misses
+        )?;
+        match self.hit_rate() {
+            Some(rate) => writeln!(f, " ({:.0}% hit rate)", rate * 100.0)?,
+            None => writeln!(f)?,
+        }
+        match self.average_latency {
+            Some(latency) => write!(f, "average latency: {} ms", latency.as_millis()),
+            None => write!(f, "average latency: no responses yet"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn clones_count_into_the_same_tallies() {
+        let metrics = Metrics::default();
+        let clone = metrics.clone();
+        metrics.request();
+        clone.request();
+        clone.retry();
+        metrics.rate_limited();
+        clone.failure();
+        metrics.cache(true);
+        clone.cache(false);
+        clone.cache(false);
+        let snapshot = metrics.snapshot();
+        assert_eq!(
+            snapshot,
+            Snapshot {
+                requests: 2,
+                retries: 1,
+                rate_limited: 1,
+                failures: 1,
+                cache_hits: 1,
+                cache_misses: 2,
+                average_latency: None,
+            }
+        );
+        assert_eq!(snapshot, clone.snapshot());
+    }
+
+    #[test]
+    fn updates_from_many_threads_all_count() {
+        let metrics = Metrics::default();
+        thread::scope(|scope| {
+            for thread in 0..8 {
+                let metrics = metrics.clone();
+                scope.spawn(move || {
+                    for _ in 0..1000 {
+                        metrics.request();
+                        metrics.response(Duration::from_millis(10));
+                        metrics.cache(thread % 2 == 0);
+                    }
+                });
+            }
+        });
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.requests, 8000);
+        assert_eq!((snapshot.cache_hits, snapshot.cache_misses), (4000, 4000));
+        assert_eq!(snapshot.average_latency, Some(Duration::from_millis(10)));
+    }
+
+    #[test]
+    fn latency_is_averaged_over_the_responses() {
+        let metrics = Metrics::default();
+        for millis in [10, 20, 60] {
+            metrics.request();
+            metrics.response(Duration::from_millis(millis));
+        }
+        // A request that never got a response doesn't drag it down.
+        metrics.request();
+        assert_eq!(
+            metrics.snapshot().average_latency,
+            Some(Duration::from_millis(30))
+        );
+    }
+
+    #[test]
+    fn a_reset_zeroes_everything() {
+        let metrics = Metrics::default();
+        metrics.request();
+        metrics.response(Duration::from_millis(5));
+        metrics.retry();
+        metrics.rate_limited();
+        metrics.failure();
+        metrics.cache(true);
+        metrics.reset();
+        assert_eq!(metrics.snapshot(), Snapshot::default());
+        metrics.request();
+        assert_eq!(metrics.snapshot().requests, 1);
+    }
+
+    #[test]
+    fn the_hit_rate_is_over_cacheable_lookups_only() {
+        let snapshot = |cache_hits, cache_misses| Snapshot {
+            requests: 10,
+            cache_hits,
+            cache_misses,
+            ..Snapshot::default()
+        };
+        assert_eq!(snapshot(0, 0).hit_rate(), None);
+        assert_eq!(snapshot(3, 1).hit_rate(), Some(0.75));
+        assert_eq!(snapshot(0, 4).hit_rate(), Some(0.0));
+    }
+
+    #[test]
+    fn a_snapshot_prints_as_three_lines() {
+        let snapshot = Snapshot {
+            requests: 12,
+            retries: 2,
+            rate_limited: 1,
+            failures: 1,
+            cache_hits: 3,
+            cache_misses: 1,
+            average_latency: Some(Duration::from_micros(41_900)),
+        };
+        assert_eq!(
+            snapshot.to_string(),
+            "requests: 12 (2 retries, 1 rate limited, 1 failed)\n\
+             cache: 3 hits, 1 misses (75% hit rate)\n\
+             average latency: 41 ms"
+        );
+        assert_eq!(
+            Snapshot::default().to_string(),
+            "requests: 0 (0 retries, 0 rate limited, 0 failed)\n\
+             cache: 0 hits, 0 misses\n\
+             average latency: no responses yet"
+        );
+    }
+}
@@ -0,0 +1,232 @@
+use std::time::Duration;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::hash::hash_suffix_of;
+use crate::range::Range;
+use crate::severity::{Severity, Thresholds};
+
+/// Whether a hash is in Pwned Passwords. A range lists each suffix once, with
+/// its count already summed over every breach, so there's no per-site figure.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum BreachResult {
+    NotFound,
+    Found { count: u64 },
+}
+
+impl BreachResult {
+    /// ```
+    /// use cybersec_wow::BreachResult;
+    ///
+    /// assert_eq!(BreachResult::from_count(Some(3)), BreachResult::Found { count: 3 });
+    /// assert_eq!(BreachResult::from_count(None), BreachResult::NotFound);
+    /// ```
+    pub fn from_count(count: Option<u64>) -> Self {
+        match count {
+            Some(count) => BreachResult::Found { count },
+            None => BreachResult::NotFound,
+        }
+    }
+
+    /// ```
+    /// use cybersec_wow::BreachResult;
+    /// use cybersec_wow::severity::{Severity, Thresholds};
+    ///
+    /// let thresholds = Thresholds::default();
+    /// assert_eq!(BreachResult::NotFound.severity(&thresholds), None);
+    /// assert_eq!(
+    ///     BreachResult::Found { count: 1_000 }.severity(&thresholds),
+    ///     Some(Severity::High)
+    /// );
+    /// ```
+    pub fn severity(&self, thresholds: &Thresholds) -> Option<Severity> {
+        match *self {
+            BreachResult::Found { count } => Some(Severity::from_count(count, thresholds)),
+            BreachResult::NotFound => None,
+        }
+    }
+
+    /// Whether `hash` is in `range`, which must be the range for its prefix.
+    ///
+    /// ```
+    /// use cybersec_wow::range::parse_range;
+    /// use cybersec_wow::{BreachResult, HashMode, Normalization, hash_password};
+    ///
+    /// let hash = hash_password("hunter2", HashMode::Sha1, Normalization::Off);
+    /// let range = parse_range("D66A63D4BF1747940578EC3D0103530E21D:17206891").unwrap();
+    /// assert_eq!(
+    ///     BreachResult::from_range(&range, &hash),
+    ///     BreachResult::Found { count: 17206891 }
+    /// );
+    /// ```
+    pub fn from_range(range: &Range, hash: &str) -> Self {
+        Self::from_count(range.get(hash_suffix_of(hash)))
+    }
+}
+
+/// Which hash a password is looked up by. Pwned Passwords has a range API
+/// for each.
+///
+/// ```
+/// use cybersec_wow::HashMode;
+///
+/// assert_eq!(HashMode::default(), HashMode::Sha1);
+/// assert_eq!(HashMode::Ntlm.to_string(), "NTLM");
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HashMode {
+    #[default]
+    Sha1,
+    Ntlm,
+}
+
+impl HashMode {
+    pub(crate) fn query(&self) -> Option<&'static str> {
+        match self {
+            HashMode::Sha1 => None,
+            HashMode::Ntlm => Some("mode=ntlm"),
+        }
+    }
+}
+
+/// Unicode normalization applied to a password before it's hashed, so the
+/// same characters typed different ways hash the same.
+///
+/// ```
+/// use cybersec_wow::{HashMode, Normalization, hash_password};
+///
+/// // "é" precomposed, and as "e" with a combining acute accent.
+/// let nfc = |pw| hash_password(pw, HashMode::Sha1, Normalization::Nfc);
+/// assert_eq!(nfc("caf\u{e9}"), nfc("cafe\u{301}"));
+/// assert_eq!(Normalization::Off.to_string(), "not normalized");
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Normalization {
+    Off,
+    #[default]
+    Nfc,
+    Nfkc,
+}
+
+impl std::fmt::Display for Normalization {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Normalization::Off => write!(f, "not normalized"),
+            Normalization::Nfc => write!(f, "NFC"),
+            Normalization::Nfkc => write!(f, "NFKC"),
+        }
+    }
+}
+
+impl std::fmt::Display for HashMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HashMode::Sha1 => write!(f, "SHA-1"),
+            HashMode::Ntlm => write!(f, "NTLM"),
+        }
+    }
+}
+
+/// Where the answer for a lookup came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "snapshot", rename_all = "snake_case")]
+pub enum LookupSource {
+    Network,
+    Memory,
+    Disk,
+    Offline(NaiveDate),
+}
+
+/// How long a lookup took and where its answer came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LookupMeta {
+    pub duration: Duration,
+    pub source: LookupSource,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::hash_password;
+    use crate::range::parse_range;
+
+    const RANGE: &str = include_str!("../tests/fixtures/range-F3BBB.txt");
+
+    #[test]
+    fn a_count_is_found_and_none_isnt() {
+        assert_eq!(
+            BreachResult::from_count(Some(3)),
+            BreachResult::Found { count: 3 }
+        );
+        assert_eq!(BreachResult::from_count(None), BreachResult::NotFound);
+    }
+
+    #[test]
+    fn the_range_gives_the_one_count_for_the_hash() {
+        let range = parse_range(RANGE).unwrap();
+        let hunter2 = hash_password("hunter2", HashMode::Sha1, Normalization::Off);
+        assert_eq!(
+            BreachResult::from_range(&range, &hunter2),
+            BreachResult::Found { count: 17206891 }
+        );
+        // The same prefix with another suffix is a different password.
+        let neighbour = format!("{}0000000000000000000000000000000000", &hunter2[..5]);
+        assert_eq!(
+            BreachResult::from_range(&range, &neighbour),
+            BreachResult::NotFound
+        );
+    }
+
+    #[test]
+    fn every_suffix_in_the_range_gives_the_count_its_line_has() {
+        let range = parse_range(RANGE).unwrap();
+        for line in RANGE.lines() {
+            let (suffix, count) = line.split_once(':').unwrap();
+            let hash = format!("F3BBB{}", suffix.to_ascii_lowercase());
+            assert_eq!(
+                BreachResult::from_range(&range, &hash),
+                BreachResult::from_count(crate::range::find_count(RANGE, suffix).unwrap()),
+                "{suffix}"
+            );
+            assert_eq!(
+                BreachResult::from_range(&range, &hash).severity(&Thresholds::default()),
+                BreachResult::from_count(Some(count.trim().parse().unwrap()))
+                    .severity(&Thresholds::default())
+            );
+        }
+    }
+
+    #[test]
+    fn padding_is_never_found() {
+        let range = parse_range(
+            "00D4F6E8FA6EECAD2A3AA415EEC418D38EC:0\r\n0018A45C4D1DEF81644B54AB7F969B88D65:1",
+        )
+        .unwrap();
+        let padded = "F3BBB00D4F6E8FA6EECAD2A3AA415EEC418D38EC";
+        assert_eq!(
+            BreachResult::from_range(&range, padded),
+            BreachResult::NotFound
+        );
+    }
+
+    #[test]
+    fn only_a_found_password_has_a_severity() {
+        let thresholds = Thresholds::default();
+        assert_eq!(BreachResult::NotFound.severity(&thresholds), None);
+        for (count, severity) in [
+            (1, Severity::Low),
+            (10, Severity::Moderate),
+            (1_000, Severity::High),
+            (100_000, Severity::Critical),
+        ] {
+            assert_eq!(
+                BreachResult::Found { count }.severity(&thresholds),
+                Some(severity)
+            );
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use notify_rust::Notification;
+
+use crate::batch::BatchStats;
+use crate::strings::Strings;
+
+/// The text of a desktop notification.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Content {
+    pub summary: String,
+    pub body: String,
+    /// The label of the action that brings the app forward, where the
+    /// platform shows one.
+    pub open: &'static str,
+}
+
+/// What to say when a batch audit ends. It counts as failed when no
+/// password could be checked at all.
+pub fn batch_finished(stats: &BatchStats, strings: &Strings) -> Content {
+    let (summary, body) = if stats.total > 0 && stats.errored == stats.total {
+        (
+            strings.audit_failed,
+            (strings.audit_none_checked)(stats.total),
+        )
+    } else {
+        let mut body = (strings.audit_found)(stats.breached, stats.total);
+        if stats.errored > 0 {
+            body.push_str(&(strings.audit_unchecked)(stats.errored));
+        }
+        (strings.audit_finished, body)
+    };
+    Content {
+        summary: summary.to_owned(),
+        body,
+        open: strings.tray_open,
+    }
+}
+
+/// Shows `content`, resolving to whether it was clicked.
+///
+/// Only the freedesktop notification servers on Linux and the BSDs report
+/// clicks; on macOS and Windows clicking one brings the app forward by
+/// itself, so it resolves as soon as it's shown.
+pub async fn show(content: Content, app_name: &'static str) -> Result<bool, String> {
+    tokio::task::spawn_blocking(move || {
+        let mut notification = Notification::new();
+        notification
+            .appname(app_name)
+            .summary(&content.summary)
+            .body(&content.body);
+        show_blocking(&mut notification, content.open)
+    })
+    .await
+    .map_err(|error| error.to_string())?
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn show_blocking(notification: &mut Notification, open: &str) -> Result<bool, String> {
+    let handle = notification
+        .action("default", open)
+        .show()
+        .map_err(|error| error.to_string())?;
+    // Returns once the notification is clicked, dismissed or expires.
+    let mut clicked = false;
+    handle.wait_for_action(|action| clicked = action == "default");
+    Ok(clicked)
+}
+
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+fn show_blocking(notification: &mut Notification, _open: &str) -> Result<bool, String> {
+    notification
+        .show()
+        .map(|_| false)
+        .map_err(|error| error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strings::{DE, EN};
+
+    fn stats(total: usize, breached: usize, errored: usize) -> BatchStats {
+        BatchStats {
+            total,
+            done: total,
+            breached,
+            clean: total - breached - errored,
+            errored,
+            ..BatchStats::default()
+        }
+    }
+
+    #[test]
+    fn a_finished_audit_says_how_many_were_found() {
+        assert_eq!(
+            batch_finished(&stats(10, 3, 0), &EN),
+            Content {
+                summary: "Audit finished".to_owned(),
+                body: "3 of 10 passwords found in breaches".to_owned(),
+                open: EN.tray_open,
+            }
+        );
+        assert_eq!(
+            batch_finished(&stats(1, 0, 0), &EN).body,
+            "0 of 1 password found in breaches"
+        );
+    }
+
+    #[test]
+    fn passwords_that_couldnt_be_checked_are_mentioned() {
+        let content = batch_finished(&stats(10, 3, 2), &EN);
+        assert_eq!(content.summary, "Audit finished");
+        assert_eq!(
+            content.body,
+            "3 of 10 passwords found in breaches; 2 couldn't be checked"
+        );
+    }
+
+    #[test]
+    fn an_audit_checking_nothing_failed() {
+        let content = batch_finished(&stats(4, 0, 4), &EN);
+        assert_eq!(content.summary, "Audit failed");
+        assert_eq!(content.body, "None of the 4 passwords could be checked");
+        // An empty batch didn't fail at anything.
+        assert_eq!(
+            batch_finished(&stats(0, 0, 0), &EN).summary,
+            "Audit finished"
+        );
+    }
+
+    #[test]
+    fn notifications_are_in_the_apps_language() {
+        let content = batch_finished(&stats(4, 0, 4), &DE);
+        assert_eq!(content.summary, DE.audit_failed);
+        assert_eq!(content.open, DE.tray_open);
+        assert_eq!(content.body, (DE.audit_none_checked)(4));
+    }
+}
@@ -0,0 +1,197 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom};
+use std::path::Path;
+
+use chrono::{DateTime, Local, NaiveDate};
+
+#[derive(Debug)]
+pub enum OfflineError {
+    NotFound(String),
+    Io(io::Error),
+    Malformed { offset: u64, line: String },
+}
+
+impl fmt::Display for OfflineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OfflineError::NotFound(path) => write!(f, "dataset file not found: {path}"),
+            OfflineError::Io(error) => write!(f, "could not read the dataset: {error}"),
+            OfflineError::Malformed { offset, line } => {
+                write!(f, "malformed dataset line at byte {offset}: {line:?}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for OfflineError {}
+
+impl From<io::Error> for OfflineError {
+    fn from(error: io::Error) -> Self {
+        OfflineError::Io(error)
+    }
+}
+
+/// A local copy of the Pwned Passwords dataset ordered by hash, one
+/// `HASH:COUNT` line per entry.
+pub struct Dataset {
+    reader: BufReader<File>,
+    len: u64,
+    snapshot: NaiveDate,
+}
+
+impl Dataset {
+    pub fn open(path: &Path) -> Result<Self, OfflineError> {
+        let file = File::open(path).map_err(|error| match error.kind() {
+            io::ErrorKind::NotFound => OfflineError::NotFound(path.display().to_string()),
+            _ => OfflineError::Io(error),
+        })?;
+        let metadata = file.metadata()?;
+
+        Ok(Self {
+            reader: BufReader::new(file),
+            len: metadata.len(),
+            snapshot: DateTime::<Local>::from(metadata.modified()?).date_naive(),
+        })
+    }
+
+    /// The file's modification date, which is when the snapshot was taken.
+    pub fn snapshot(&self) -> NaiveDate {
+        self.snapshot
+    }
+
+    /// Binary searches the file by byte offset for `hash`, comparing only
+    /// whole lines so no index is needed.
+    pub fn lookup(&mut self, hash: &str) -> Result<Option<u64>, OfflineError> {
+        let (mut low, mut high) = (0, self.len);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let Some((start, end, line)) = self.line_at_or_after(mid)? else {
+                high = mid;
+                continue;
+            };
+            let (line_hash, count) = line
+                .split_once(':')
+                .and_then(|(line_hash, count)| Some((line_hash, count.parse().ok()?)))
+                .ok_or_else(|| OfflineError::Malformed {
+                    offset: start,
+                    line: line.clone(),
+                })?;
+
+            match compare_hashes(line_hash, hash) {
+                Ordering::Equal => return Ok(Some(count)),
+                Ordering::Less => low = end,
+                Ordering::Greater => high = mid,
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Returns the first full line starting at or after `offset`, with its
+    /// start and end offsets.
+    fn line_at_or_after(&mut self, offset: u64) -> io::Result<Option<(u64, u64, String)>> {
+        let mut start = offset;
+        if offset > 0 {
+            self.reader.seek(SeekFrom::Start(offset - 1))?;
+            let mut partial = Vec::new();
+            start = offset - 1 + self.reader.read_until(b'\n', &mut partial)? as u64;
+        } else {
+            self.reader.seek(SeekFrom::Start(0))?;
+        }
+
+        let mut line = String::new();
+        let read = self.reader.read_line(&mut line)? as u64;
+        if read == 0 {
+            return Ok(None);
+        }
+        let trimmed = line.trim_end_matches(['\r', '\n']).to_owned();
+
+        Ok(Some((start, start + read, trimmed)))
+    }
+}
+
+fn compare_hashes(a: &str, b: &str) -> Ordering {
+    a.bytes()
+        .map(|byte| byte.to_ascii_uppercase())
+        .cmp(b.bytes().map(|byte| byte.to_ascii_uppercase()))
+}
+
+pub fn lookup(path: &Path, hash: &str) -> Result<(Option<u64>, NaiveDate), OfflineError> {
+    let mut dataset = Dataset::open(path)?;
+    let count = dataset.lookup(hash)?;
+
+    Ok((count, dataset.snapshot()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    /// Twenty common passwords' SHA-1 hashes, sorted, CRLF-terminated.
+    const DATASET: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/dataset-sha1.txt"
+    );
+
+    fn lookup_in(contents: &str, hash: &str) -> Result<Option<u64>, OfflineError> {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(contents.as_bytes()).unwrap();
+        Dataset::open(file.path())?.lookup(hash)
+    }
+
+    #[test]
+    fn finds_every_entry_and_nothing_else() {
+        let mut dataset = Dataset::open(Path::new(DATASET)).unwrap();
+        let contents = std::fs::read_to_string(DATASET).unwrap();
+        for line in contents.lines() {
+            let (hash, count) = line.split_once(':').unwrap();
+            assert_eq!(dataset.lookup(hash).unwrap(), count.parse().ok(), "{hash}");
+            assert_eq!(
+                dataset.lookup(&hash.to_ascii_lowercase()).unwrap(),
+                count.parse().ok()
+            );
+        }
+        for missing in [
+            "0000000000000000000000000000000000000000",
+            "5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD9",
+            "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF",
+        ] {
+            assert_eq!(dataset.lookup(missing).unwrap(), None, "{missing}");
+        }
+    }
+
+    #[test]
+    fn a_missing_file_says_so() {
+        let error = lookup(Path::new("/nonexistent/pwned-passwords.txt"), "F3BBB").unwrap_err();
+        assert!(matches!(error, OfflineError::NotFound(_)));
+        assert_eq!(
+            error.to_string(),
+            "dataset file not found: /nonexistent/pwned-passwords.txt"
+        );
+    }
+
+    #[test]
+    fn a_malformed_line_is_an_error_not_a_miss() {
+        let error = lookup_in(
+            "this isn't a dataset\n",
+            "F3BBBD66A63D4BF1747940578EC3D0103530E21D",
+        )
+        .unwrap_err();
+        let OfflineError::Malformed { offset, line } = error else {
+            panic!("expected a malformed line, got {error}");
+        };
+        assert_eq!((offset, line.as_str()), (0, "this isn't a dataset"));
+
+        let error = lookup_in("ABC:many\n", "ABC").unwrap_err();
+        assert!(matches!(error, OfflineError::Malformed { .. }));
+    }
+
+    #[test]
+    fn an_empty_file_has_nothing_in_it() {
+        assert_eq!(lookup_in("", "ABC").unwrap(), None);
+    }
+}
@@ -0,0 +1,736 @@
+use std::cmp::Ordering;
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, Local, NaiveDate};
+use flate2::Crc;
+use memmap2::Mmap;
+use thiserror::Error;
+
+use crate::HashMode;
+
+/// The packed format this build writes and reads. Bumped whenever a change
+/// would make an older file answer wrongly.
+pub const VERSION: u32 = 1;
+
+const MAGIC: &[u8; 8] = b"PWPACKED";
+
+const HEADER_LEN: usize = 64;
+
+/// Entries per block. A lookup reads and checks one block, so this trades
+/// the size of the index against the bytes a lookup touches: about 6 KiB
+/// of SHA-1 entries, against an index a 160th the size of the file.
+const BLOCK_ENTRIES: usize = 256;
+
+/// How many lines are read between calls to the progress callback.
+const PROGRESS_LINES: u64 = 1 << 20;
+
+/// Why a packed dataset can't be written or read.
+#[derive(Debug, Error)]
+pub enum PackedError {
+    #[error("{}: {error}", path.display())]
+    Io { path: PathBuf, error: io::Error },
+    #[error("{} isn't a packed dataset: {reason}", path.display())]
+    Corrupt { path: PathBuf, reason: String },
+    #[error("{} is from a newer or older version (format {found}, this one reads {VERSION})", path.display())]
+    Version { path: PathBuf, found: u32 },
+    #[error("{} is truncated: it should be {expected} bytes but is {len}", path.display())]
+    Truncated {
+        path: PathBuf,
+        expected: u64,
+        len: u64,
+    },
+    #[error("{} is damaged: block {block} doesn't match its checksum", path.display())]
+    Checksum { path: PathBuf, block: u64 },
+    #[error("{}: line {line} isn't HASH:COUNT: {text:?}", path.display())]
+    Malformed {
+        path: PathBuf,
+        line: u64,
+        text: String,
+    },
+    #[error("{}: line {line} is out of order; the dataset has to be sorted by hash", path.display())]
+    Unsorted { path: PathBuf, line: u64 },
+}
+
+/// What a packed dataset holds, as its header has it:
+///
+/// ```text
+/// offset  size  field
+///      0     8  "PWPACKED"
+///      8     4  format version
+///     12     4  digest length: 20 for SHA-1, 16 for NTLM
+///     16     8  entries
+///     24     8  blocks
+///     32     4  entries per block
+///     40     8  where the block index starts
+///     48     8  when the text dataset was last modified, in Unix seconds
+///     60     4  CRC-32 of the first 60 bytes
+/// ```
+///
+/// Every number is little-endian. The blocks follow at byte 64, each up to
+/// [`BLOCK_ENTRIES`] raw digests in order, every one followed by its count
+/// as a LEB128 varint. Then comes the index, a fixed-size record per block:
+/// its first digest, its offset as a u64, its length as a u32 and the
+/// CRC-32 of its bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Header {
+    pub mode: HashMode,
+    pub entries: u64,
+    pub blocks: u64,
+    pub block_entries: u32,
+    pub index_offset: u64,
+    /// The snapshot date is taken from this rather than the packed file's
+    /// own modification time, which is only when it was converted.
+    pub modified: u64,
+}
+
+impl Header {
+    fn digest_len(&self) -> usize {
+        digest_len(self.mode)
+    }
+
+    /// The size of one block's record in the index.
+    fn stride(&self) -> usize {
+        self.digest_len() + 16
+    }
+
+    /// How long a whole file with this header is.
+    fn file_len(&self) -> u64 {
+        self.index_offset + self.blocks * self.stride() as u64
+    }
+
+    fn to_bytes(self) -> [u8; HEADER_LEN] {
+        let mut bytes = [0; HEADER_LEN];
+        bytes[..8].copy_from_slice(MAGIC);
+        bytes[8..12].copy_from_slice(&VERSION.to_le_bytes());
+        bytes[12..16].copy_from_slice(&(self.digest_len() as u32).to_le_bytes());
+        bytes[16..24].copy_from_slice(&self.entries.to_le_bytes());
+        bytes[24..32].copy_from_slice(&self.blocks.to_le_bytes());
+        bytes[32..36].copy_from_slice(&self.block_entries.to_le_bytes());
+        bytes[40..48].copy_from_slice(&self.index_offset.to_le_bytes());
+        bytes[48..56].copy_from_slice(&self.modified.to_le_bytes());
+        let crc = crc32(&bytes[..60]);
+        bytes[60..].copy_from_slice(&crc.to_le_bytes());
+        bytes
+    }
+
+    fn parse(bytes: &[u8], path: &Path) -> Result<Self, PackedError> {
+        let corrupt = |reason: &str| PackedError::Corrupt {
+            path: path.to_owned(),
+            reason: reason.to_owned(),
+        };
+        let header = bytes
+            .get(..HEADER_LEN)
+            .ok_or_else(|| corrupt("it's too short for a header"))?;
+        if &header[..8] != MAGIC {
+            return Err(corrupt("it doesn't start with the packed dataset magic"));
+        }
+        let u32_at = |at: usize| u32::from_le_bytes(header[at..at + 4].try_into().unwrap());
+        let u64_at = |at: usize| u64::from_le_bytes(header[at..at + 8].try_into().unwrap());
+        // Checked first, so a newer format whose header has changed is
+        // still reported as a version mismatch.
+        let found = u32_at(8);
+        if found != VERSION {
+            return Err(PackedError::Version {
+                path: path.to_owned(),
+                found,
+            });
+        }
+        if u32_at(60) != crc32(&header[..60]) {
+            return Err(corrupt("its header doesn't match its checksum"));
+        }
+        let header = Self {
+            mode: match u32_at(12) {
+                20 => HashMode::Sha1,
+                16 => HashMode::Ntlm,
+                _ => return Err(corrupt("the digest length is unknown")),
+            },
+            entries: u64_at(16),
+            blocks: u64_at(24),
+            block_entries: u32_at(32),
+            index_offset: u64_at(40),
+            modified: u64_at(48),
+        };
+        let len = bytes.len() as u64;
+        if len < header.file_len() {
+            return Err(PackedError::Truncated {
+                path: path.to_owned(),
+                expected: header.file_len(),
+                len,
+            });
+        }
+        if len > header.file_len() || header.index_offset < HEADER_LEN as u64 {
+            return Err(corrupt("its length doesn't match its header"));
+        }
+        Ok(header)
+    }
+}
+
+/// An offline dataset packed by [`pack`]: raw digests and varint counts in
+/// checksummed blocks, about half the size of the text, mapped rather than
+/// read. A lookup binary searches the block index for the one block its
+/// hash could be in, checks that block against its checksum and scans it,
+/// so it touches a few pages however big the file is.
+#[derive(Debug)]
+pub struct PackedDataset {
+    path: PathBuf,
+    header: Header,
+    map: Mmap,
+}
+
+/// Where [`pack`] puts the packed copy of the dataset at `dataset`: beside
+/// it, as `dataset.packed`.
+pub fn path_for(dataset: &Path) -> PathBuf {
+    let mut path = OsString::from(dataset.as_os_str());
+    path.push(".packed");
+    PathBuf::from(path)
+}
+
+/// Whether the file at `path` starts like a packed dataset, so an offline
+/// lookup knows which kind it's been pointed at.
+pub fn is_packed(path: &Path) -> io::Result<bool> {
+    let mut magic = [0; MAGIC.len()];
+    match File::open(path)?.read_exact(&mut magic) {
+        Ok(()) => Ok(&magic == MAGIC),
+        Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(error) => Err(error),
+    }
+}
+
+impl PackedDataset {
+    pub fn open(path: &Path) -> Result<Self, PackedError> {
+        let io_error = |error| PackedError::Io {
+            path: path.to_owned(),
+            error,
+        };
+        let file = File::open(path).map_err(io_error)?;
+        // SAFETY: the map is only read, and a packed dataset is written to a
+        // temporary file and renamed into place, never changed where it
+        // is. Every block is checked against its checksum before it's used.
+        let map = unsafe { Mmap::map(&file) }.map_err(io_error)?;
+        let header = Header::parse(&map, path)?;
+        Ok(Self {
+            path: path.to_owned(),
+            header,
+            map,
+        })
+    }
+
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// When the text dataset was last modified, which is when the snapshot
+    /// was taken.
+    pub fn snapshot(&self) -> NaiveDate {
+        let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(self.header.modified);
+        DateTime::<Local>::from(modified).date_naive()
+    }
+
+    /// How many times `hash` is in the dataset, if it is. A hash of the
+    /// wrong kind or length is simply not there.
+    pub fn lookup(&self, hash: &str) -> Result<Option<u64>, PackedError> {
+        let Some(digest) = decode(hash, self.header.mode) else {
+            return Ok(None);
+        };
+        // The last block starting at or before the digest is the only one
+        // it can be in.
+        let (mut low, mut high) = (0, self.header.blocks);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if self.first_digest(mid) <= &digest[..] {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        let Some(block) = low.checked_sub(1) else {
+            return Ok(None);
+        };
+        let mut entries = self.block(block)?;
+        let len = self.header.digest_len();
+        while !entries.is_empty() {
+            let corrupt = || self.corrupt(format!("block {block} ends mid-entry"));
+            let (entry, rest) = entries.split_at_checked(len).ok_or_else(corrupt)?;
+            let (count, rest) = read_varint(rest).ok_or_else(corrupt)?;
+            match entry.cmp(&digest[..]) {
+                Ordering::Equal => return Ok(Some(count)),
+                Ordering::Greater => break,
+                Ordering::Less => entries = rest,
+            }
+        }
+        Ok(None)
+    }
+
+    /// The index record of block `block`.
+    fn record(&self, block: u64) -> &[u8] {
+        let stride = self.header.stride();
+        let start = self.header.index_offset as usize + block as usize * stride;
+        &self.map[start..start + stride]
+    }
+
+    fn first_digest(&self, block: u64) -> &[u8] {
+        &self.record(block)[..self.header.digest_len()]
+    }
+
+    /// The bytes of block `block`, once they've been checked against its
+    /// checksum.
+    fn block(&self, block: u64) -> Result<&[u8], PackedError> {
+        let record = &self.record(block)[self.header.digest_len()..];
+        let offset = u64::from_le_bytes(record[..8].try_into().unwrap());
+        let len = u32::from_le_bytes(record[8..12].try_into().unwrap());
+        let crc = u32::from_le_bytes(record[12..16].try_into().unwrap());
+        let end = offset + u64::from(len);
+        if offset < HEADER_LEN as u64 || end > self.header.index_offset {
+            return Err(self.corrupt(format!("block {block} is out of bounds")));
+        }
+        let bytes = &self.map[offset as usize..end as usize];
+        if crc32(bytes) != crc {
+            return Err(PackedError::Checksum {
+                path: self.path.clone(),
+                block,
+            });
+        }
+        Ok(bytes)
+    }
+
+    fn corrupt(&self, reason: String) -> PackedError {
+        PackedError::Corrupt {
+            path: self.path.clone(),
+            reason,
+        }
+    }
+}
+
+/// Packs the `HASH:COUNT` dataset at `dataset` into `output`, calling
+/// `progress` with the bytes read so far and the dataset's length as it
+/// goes, and returns the header written.
+///
+/// Both files are streamed, so memory use doesn't grow with the dataset:
+/// the blocks go straight to a temporary file and their index to a second
+/// one, which is appended once the blocks are done. The temporary file is
+/// renamed to `output` once it's whole, or removed if the dataset turns out
+/// malformed or out of order.
+pub fn pack(
+    dataset: &Path,
+    output: &Path,
+    mut progress: impl FnMut(u64, u64),
+) -> Result<Header, PackedError> {
+    let dataset_error = |error| PackedError::Io {
+        path: dataset.to_owned(),
+        error,
+    };
+    let file = File::open(dataset).map_err(dataset_error)?;
+    let metadata = file.metadata().map_err(dataset_error)?;
+    let modified = metadata
+        .modified()
+        .map_err(dataset_error)?
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    let temp = with_suffix(output, ".tmp");
+    let index = with_suffix(output, ".index.tmp");
+    let packed = write(
+        BufReader::new(file),
+        dataset,
+        (metadata.len(), modified),
+        (&temp, &index),
+        &mut progress,
+    );
+    let _ = fs::remove_file(&index);
+    let header = packed.inspect_err(|_| {
+        let _ = fs::remove_file(&temp);
+    })?;
+    fs::rename(&temp, output).map_err(|error| PackedError::Io {
+        path: output.to_owned(),
+        error,
+    })?;
+    Ok(header)
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = OsString::from(path.as_os_str());
+    path.push(suffix);
+    PathBuf::from(path)
+}
+
+/// Writes the packed file to `temp`, with its index built up in `index`.
+fn write(
+    mut reader: BufReader<File>,
+    dataset: &Path,
+    (dataset_len, modified): (u64, u64),
+    (temp, index): (&Path, &Path),
+    progress: &mut impl FnMut(u64, u64),
+) -> Result<Header, PackedError> {
+    let dataset_error = |error| PackedError::Io {
+        path: dataset.to_owned(),
+        error,
+    };
+    let output_error = |error| PackedError::Io {
+        path: temp.to_owned(),
+        error,
+    };
+    let index_error = |error| PackedError::Io {
+        path: index.to_owned(),
+        error,
+    };
+    let create = |path: &Path| {
+        OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)
+    };
+    let mut out = BufWriter::new(create(temp).map_err(output_error)?);
+    let mut index_out = BufWriter::new(create(index).map_err(index_error)?);
+    out.write_all(&[0; HEADER_LEN]).map_err(output_error)?;
+
+    let mut header = Header {
+        mode: HashMode::Sha1,
+        entries: 0,
+        blocks: 0,
+        block_entries: BLOCK_ENTRIES as u32,
+        index_offset: 0,
+        modified,
+    };
+    let mut block = Block::default();
+    let mut offset = HEADER_LEN as u64;
+    let (mut line, mut number, mut read) = (Vec::new(), 0, 0);
+    let mut mode = None;
+    let mut previous: Option<Vec<u8>> = None;
+    loop {
+        line.clear();
+        let n = reader.read_until(b'\n', &mut line).map_err(dataset_error)?;
+        if n == 0 {
+            break;
+        }
+        (number, read) = (number + 1, read + n as u64);
+        let text = line.trim_ascii_end();
+        if text.is_empty() {
+            continue;
+        }
+        let malformed = || PackedError::Malformed {
+            path: dataset.to_owned(),
+            line: number,
+            text: String::from_utf8_lossy(text).into_owned(),
+        };
+        let (hash, count) = std::str::from_utf8(text)
+            .ok()
+            .and_then(|text| text.split_once(':'))
+            .ok_or_else(malformed)?;
+        let line_mode = match hash.len() {
+            40 => HashMode::Sha1,
+            32 => HashMode::Ntlm,
+            _ => return Err(malformed()),
+        };
+        if *mode.get_or_insert(line_mode) != line_mode {
+            return Err(malformed());
+        }
+        let digest = decode(hash, line_mode).ok_or_else(malformed)?;
+        let count = count.parse::<u64>().map_err(|_| malformed())?;
+        if previous
+            .as_deref()
+            .is_some_and(|previous| previous >= &digest[..])
+        {
+            return Err(PackedError::Unsorted {
+                path: dataset.to_owned(),
+                line: number,
+            });
+        }
+        block.push(&digest, count);
+        header.entries += 1;
+        if block.entries == BLOCK_ENTRIES {
+            offset += block
+                .flush(&mut out, &mut index_out, offset)
+                .map_err(output_error)?;
+            header.blocks += 1;
+        }
+        match &mut previous {
+            Some(previous) => previous.copy_from_slice(&digest),
+            None => previous = Some(digest),
+        }
+        if number.is_multiple_of(PROGRESS_LINES) {
+            progress(read, dataset_len);
+        }
+    }
+    if block.entries > 0 {
+        offset += block
+            .flush(&mut out, &mut index_out, offset)
+            .map_err(output_error)?;
+        header.blocks += 1;
+    }
+    header.mode = mode.unwrap_or_default();
+    header.index_offset = offset;
+
+    let mut index_file = index_out
+        .into_inner()
+        .map_err(|error| index_error(error.into_error()))?;
+    index_file.rewind().map_err(index_error)?;
+    io::copy(&mut index_file, &mut out).map_err(output_error)?;
+    let mut file = out
+        .into_inner()
+        .map_err(|error| output_error(error.into_error()))?;
+    file.seek(SeekFrom::Start(0)).map_err(output_error)?;
+    file.write_all(&header.to_bytes()).map_err(output_error)?;
+    file.sync_all().map_err(output_error)?;
+    progress(dataset_len, dataset_len);
+    Ok(header)
+}
+
+/// The block being filled, and its index record so far.
+#[derive(Default)]
+struct Block {
+    bytes: Vec<u8>,
+    first: Vec<u8>,
+    entries: usize,
+}
+
+impl Block {
+    fn push(&mut self, digest: &[u8], count: u64) {
+        if self.entries == 0 {
+            self.first = digest.to_owned();
+        }
+        self.bytes.extend_from_slice(digest);
+        write_varint(&mut self.bytes, count);
+        self.entries += 1;
+    }
+
+    /// Writes the block at `offset` to `out` and its record to `index`,
+    /// empties it and returns its length.
+    fn flush(
+        &mut self,
+        out: &mut impl Write,
+        index: &mut impl Write,
+        offset: u64,
+    ) -> io::Result<u64> {
+        out.write_all(&self.bytes)?;
+        index.write_all(&self.first)?;
+        index.write_all(&offset.to_le_bytes())?;
+        index.write_all(&(self.bytes.len() as u32).to_le_bytes())?;
+        index.write_all(&crc32(&self.bytes).to_le_bytes())?;
+        let len = self.bytes.len() as u64;
+        self.bytes.clear();
+        self.entries = 0;
+        Ok(len)
+    }
+}
+
+fn digest_len(mode: HashMode) -> usize {
+    match mode {
+        HashMode::Sha1 => 20,
+        HashMode::Ntlm => 16,
+    }
+}
+
+/// The raw digest of a `mode` hash in hex, either case.
+fn decode(hash: &str, mode: HashMode) -> Option<Vec<u8>> {
+    if hash.len() != digest_len(mode) * 2 || !hash.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return None;
+    }
+    (0..hash.len())
+        .step_by(2)
+        .map(|at| u8::from_str_radix(&hash[at..at + 2], 16).ok())
+        .collect()
+}
+
+fn write_varint(out: &mut Vec<u8>, mut n: u64) {
+    while n >= 0x80 {
+        out.push(n as u8 | 0x80);
+        n >>= 7;
+    }
+    out.push(n as u8);
+}
+
+/// A LEB128 varint from the start of `bytes`, and what follows it.
+fn read_varint(bytes: &[u8]) -> Option<(u64, &[u8])> {
+    let mut n = 0u64;
+    for (i, &byte) in bytes.iter().enumerate().take(10) {
+        n |= u64::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Some((n, &bytes[i + 1..]));
+        }
+    }
+    None
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = Crc::new();
+    crc.update(bytes);
+    crc.sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DATASET: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/dataset-sha1.txt"
+    );
+
+    /// The dataset at `dataset` packed into a directory of its own.
+    fn packed(dataset: &Path) -> (tempfile::TempDir, PathBuf, Header) {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("pwned.packed");
+        let header = pack(dataset, &output, |_, _| {}).unwrap();
+        (dir, output, header)
+    }
+
+    /// A dataset of `entries` sorted SHA-1 hashes, the `n`th of them
+    /// [`nth`] and found `n` times.
+    fn generated(dir: &Path, entries: u64) -> PathBuf {
+        let path = dir.join("pwned.txt");
+        let lines: String = (0..entries)
+            .map(|n| format!("{}:{n}\r\n", nth(n)))
+            .collect();
+        fs::write(&path, lines).unwrap();
+        path
+    }
+
+    fn nth(n: u64) -> String {
+        format!("{:040X}", u128::from(n) * 2 + 1)
+    }
+
+    #[test]
+    fn the_first_the_last_and_absent_digests_of_the_fixture() {
+        let (_dir, output, header) = packed(Path::new(DATASET));
+        assert_eq!(
+            (header.mode, header.entries, header.blocks),
+            (HashMode::Sha1, 20, 1)
+        );
+        let packed = PackedDataset::open(&output).unwrap();
+        for (hash, count) in [
+            ("17B9E1C64588C7FA6419B4D29DC1F4426279BA01", Some(629)),
+            ("f3bbbd66a63d4bf1747940578ec3d0103530e21d", Some(37)),
+            ("0000000000000000000000000000000000000000", None),
+            ("17B9E1C64588C7FA6419B4D29DC1F4426279BA02", None),
+            ("FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF", None),
+            ("8846F7EAEE8FB117AD06BDD830B7586C", None),
+        ] {
+            assert_eq!(packed.lookup(hash).unwrap(), count, "{hash}");
+        }
+    }
+
+    #[test]
+    fn every_block_is_found_by_its_first_and_last_digests() {
+        let dir = tempfile::tempdir().unwrap();
+        let (_out, output, header) = packed(&generated(dir.path(), 600));
+        assert_eq!((header.entries, header.blocks), (600, 3));
+        let packed = PackedDataset::open(&output).unwrap();
+        for n in [0, 255, 256, 511, 512, 599] {
+            assert_eq!(packed.lookup(&nth(n)).unwrap(), Some(n), "{n}");
+        }
+        // Between two entries, and past the last.
+        assert_eq!(packed.lookup(&format!("{:040X}", 512)).unwrap(), None);
+        assert_eq!(packed.lookup(&format!("{:040X}", 1201)).unwrap(), None);
+    }
+
+    #[test]
+    fn a_truncated_file_is_refused_with_its_expected_length() {
+        let (_dir, output, _) = packed(Path::new(DATASET));
+        let bytes = fs::read(&output).unwrap();
+        let len = bytes.len() as u64;
+        fs::write(&output, &bytes[..bytes.len() - 1]).unwrap();
+        match PackedDataset::open(&output) {
+            Err(PackedError::Truncated {
+                expected,
+                len: found,
+                ..
+            }) => {
+                assert_eq!((expected, found), (len, len - 1));
+            }
+            other => panic!("{other:?}"),
+        }
+        fs::write(&output, &bytes[..HEADER_LEN / 2]).unwrap();
+        let error = PackedDataset::open(&output).unwrap_err();
+        assert!(matches!(error, PackedError::Corrupt { .. }), "{error:?}");
+    }
+
+    #[test]
+    fn a_file_of_another_version_is_refused() {
+        let (_dir, output, _) = packed(Path::new(DATASET));
+        let mut bytes = fs::read(&output).unwrap();
+        bytes[8..12].copy_from_slice(&(VERSION + 1).to_le_bytes());
+        fs::write(&output, &bytes).unwrap();
+        match PackedDataset::open(&output) {
+            Err(PackedError::Version { found, .. }) => assert_eq!(found, VERSION + 1),
+            other => panic!("{other:?}"),
+        }
+    }
+
+    #[test]
+    fn a_damaged_header_or_block_fails_its_checksum() {
+        let (_dir, output, _) = packed(Path::new(DATASET));
+        let bytes = fs::read(&output).unwrap();
+        let mut header = bytes.clone();
+        header[16] ^= 1;
+        fs::write(&output, &header).unwrap();
+        let error = PackedDataset::open(&output).unwrap_err();
+        assert!(matches!(error, PackedError::Corrupt { .. }), "{error:?}");
+
+        let mut block = bytes;
+        block[HEADER_LEN + 3] ^= 1;
+        fs::write(&output, &block).unwrap();
+        let packed = PackedDataset::open(&output).unwrap();
+        let error = packed
+            .lookup("F3BBBD66A63D4BF1747940578EC3D0103530E21D")
+            .unwrap_err();
+        assert!(
+            matches!(error, PackedError::Checksum { block: 0, .. }),
+            "{error:?}"
+        );
+    }
+
+    #[test]
+    fn an_unsorted_or_malformed_dataset_leaves_nothing_behind() {
+        let dir = tempfile::tempdir().unwrap();
+        let dataset = dir.path().join("pwned.txt");
+        let output = dir.path().join("pwned.packed");
+        for (text, expected) in [
+            (
+                format!("{}:1\n{}:2\n", nth(1), nth(0)),
+                "line 2 is out of order",
+            ),
+            (
+                format!("{}:1\n{}:2\n", nth(0), nth(0)),
+                "line 2 is out of order",
+            ),
+            (
+                format!("{}:1\n\n{}\n", nth(0), nth(1)),
+                "line 3 isn't HASH:COUNT",
+            ),
+            (
+                format!("{}:1\n8846F7EAEE8FB117AD06BDD830B7586C:2\n", nth(0)),
+                "line 2 isn't",
+            ),
+        ] {
+            fs::write(&dataset, text).unwrap();
+            let error = pack(&dataset, &output, |_, _| {}).unwrap_err();
+            assert!(error.to_string().contains(expected), "{error}");
+            assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+        }
+    }
+
+    #[test]
+    fn a_packed_file_is_told_from_a_text_one() {
+        let (_dir, output, _) = packed(Path::new(DATASET));
+        assert!(is_packed(&output).unwrap());
+        assert!(!is_packed(Path::new(DATASET)).unwrap());
+        let empty = tempfile::NamedTempFile::new().unwrap();
+        assert!(!is_packed(empty.path()).unwrap());
+    }
+
+    #[test]
+    fn counts_round_trip_as_varints() {
+        for n in [0, 1, 127, 128, 300, 17_206_891, u64::MAX] {
+            let mut bytes = Vec::new();
+            write_varint(&mut bytes, n);
+            bytes.push(0xAA);
+            assert_eq!(read_varint(&bytes), Some((n, &[0xAA][..])), "{n}");
+        }
+        assert_eq!(read_varint(&[0x80, 0x80]), None);
+    }
+}
@@ -0,0 +1,156 @@
+use std::sync::LazyLock;
+
+/// The most common 4-digit PINs, most common first, each with the share of
+/// all PINs it made up, in percent.
+static TABLE: &str = include_str!("../data/common-pins.txt");
+
+static RANKS: LazyLock<Vec<(&'static str, f64)>> = LazyLock::new(|| {
+    TABLE
+        .lines()
+        .map(|line| {
+            let (pin, percent) = line
+                .split_once('\t')
+                .expect("the bundled PIN table has two columns");
+            let percent = percent
+                .parse()
+                .expect("the bundled PIN table's percentages are numbers");
+            (pin, percent)
+        })
+        .collect()
+});
+
+pub const MIN_LEN: usize = 4;
+pub const MAX_LEN: usize = 8;
+
+/// How many PINs the bundled table holds.
+pub fn count() -> usize {
+    RANKS.len()
+}
+
+/// Where a PIN stands in the bundled table.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ranking {
+    /// 1 for the most common.
+    pub rank: u32,
+    /// The share of all PINs that were this one.
+    pub percent: f64,
+}
+
+impl Ranking {
+    /// The share rounded to a sensible precision: `10.7%`, `0.52%`.
+    pub fn prevalence(&self) -> String {
+        if self.percent >= 1. {
+            format!("{:.1}%", self.percent)
+        } else {
+            format!("{:.2}%", self.percent)
+        }
+    }
+}
+
+/// Where `pin` ranks among the common PINs, if it's one of them. The table
+/// only holds 4-digit PINs, so longer ones never rank.
+pub fn rank(pin: &str) -> Option<Ranking> {
+    RANKS
+        .iter()
+        .zip(1..)
+        .find(|((listed, _), _)| *listed == pin)
+        .map(|(&(_, percent), rank)| Ranking { rank, percent })
+}
+
+/// Why some input isn't a PIN.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Invalid {
+    NotDigits,
+    TooShort,
+    TooLong,
+}
+
+pub fn validate(pin: &str) -> Result<(), Invalid> {
+    if !pin.bytes().all(|b| b.is_ascii_digit()) {
+        Err(Invalid::NotDigits)
+    } else if pin.len() < MIN_LEN {
+        Err(Invalid::TooShort)
+    } else if pin.len() > MAX_LEN {
+        Err(Invalid::TooLong)
+    } else {
+        Ok(())
+    }
+}
+
+/// Whether `input` could still be typed on the way to a PIN: digits only,
+/// and not too many of them. The field refuses anything else.
+pub fn is_typable(input: &str) -> bool {
+    matches!(validate(input), Ok(()) | Err(Invalid::TooShort))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_bundled_table_is_well_formed() {
+        assert!(count() >= 20);
+        assert!(
+            RANKS
+                .iter()
+                .all(|(pin, _)| pin.len() == 4 && validate(pin).is_ok())
+        );
+        // Most common first, each PIN once.
+        assert!(RANKS.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+        let mut pins: Vec<_> = RANKS.iter().map(|(pin, _)| pin).collect();
+        pins.sort_unstable();
+        pins.dedup();
+        assert_eq!(pins.len(), count());
+        let total: f64 = RANKS.iter().map(|(_, percent)| percent).sum();
+        assert!(total < 100., "{total}");
+    }
+
+    #[test]
+    fn common_pins_are_ranked_from_one() {
+        assert_eq!(
+            rank("1234"),
+            Some(Ranking {
+                rank: 1,
+                percent: 10.713
+            })
+        );
+        assert_eq!(rank("1111").map(|ranking| ranking.rank), Some(2));
+        assert_eq!(rank("0000").map(|ranking| ranking.rank), Some(3));
+        assert_eq!(rank("8068"), None);
+        // Only 4-digit PINs are listed, and only exactly.
+        assert_eq!(rank("12345"), None);
+        assert_eq!(rank("123"), None);
+        assert_eq!(rank(" 1234"), None);
+    }
+
+    #[test]
+    fn prevalence_keeps_two_significant_figures_below_one_percent() {
+        let ranking = |percent| Ranking { rank: 1, percent };
+        assert_eq!(ranking(10.713).prevalence(), "10.7%");
+        assert_eq!(ranking(1.).prevalence(), "1.0%");
+        assert_eq!(ranking(0.745).prevalence(), "0.74%");
+        assert_eq!(ranking(0.005).prevalence(), "0.01%");
+    }
+
+    #[test]
+    fn a_pin_is_four_to_eight_digits() {
+        assert_eq!(validate("1234"), Ok(()));
+        assert_eq!(validate("12345678"), Ok(()));
+        assert_eq!(validate(""), Err(Invalid::TooShort));
+        assert_eq!(validate("123"), Err(Invalid::TooShort));
+        assert_eq!(validate("123456789"), Err(Invalid::TooLong));
+        for pin in ["12a4", "12 34", "١٢٣٤", "-123", "1234\n"] {
+            assert_eq!(validate(pin), Err(Invalid::NotDigits), "{pin:?}");
+        }
+    }
+
+    #[test]
+    fn only_digits_can_be_typed_up_to_the_longest_pin() {
+        for input in ["", "1", "123", "1234", "12345678"] {
+            assert!(is_typable(input), "{input:?}");
+        }
+        for input in ["a", "12a", "123456789", "１２"] {
+            assert!(!is_typable(input), "{input:?}");
+        }
+    }
+}
@@ -0,0 +1,314 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::composition::{Class, Composition};
+
+/// An organisation's rules for passwords, from the `[policy]` section of
+/// the settings file, checked on the device alongside the breach check:
+///
+/// ```toml
+/// [policy]
+/// min_length = 12
+/// min_classes = 3
+/// forbid_username = true
+/// forbidden = ["acme", "winter"]
+/// ```
+///
+/// Each rule is left out unless set. Lengths count characters, as
+/// [`Composition::chars`] does.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Policy {
+    pub min_length: Option<usize>,
+    pub max_length: Option<usize>,
+    /// How many of lowercase, uppercase, digits and symbols it needs.
+    pub min_classes: Option<usize>,
+    /// Kinds of character it needs at least one of each of.
+    pub required_classes: Vec<Required>,
+    /// What it mustn't contain, ignoring case.
+    pub forbidden: Vec<String>,
+    /// Whether it mustn't contain the username it's for, ignoring case.
+    /// Only an imported entry has one.
+    pub forbid_username: bool,
+    pub forbid_whitespace: bool,
+}
+
+/// A kind of character [`Policy::required_classes`] can ask for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Required {
+    Lower,
+    Upper,
+    Digit,
+    Symbol,
+}
+
+impl Required {
+    const ALL: [Required; 4] = [
+        Required::Lower,
+        Required::Upper,
+        Required::Digit,
+        Required::Symbol,
+    ];
+
+    fn class(self) -> Class {
+        match self {
+            Required::Lower => Class::Lower,
+            Required::Upper => Class::Upper,
+            Required::Digit => Class::Digit,
+            Required::Symbol => Class::Symbol,
+        }
+    }
+}
+
+impl fmt::Display for Required {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Required::Lower => "a lowercase letter",
+            Required::Upper => "an uppercase letter",
+            Required::Digit => "a digit",
+            Required::Symbol => "a symbol",
+        })
+    }
+}
+
+/// One rule of a [`Policy`], as it's reported.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rule {
+    MinLength(usize),
+    MaxLength(usize),
+    MinClasses(usize),
+    Requires(Required),
+    Forbidden(String),
+    Username,
+    Whitespace,
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rule::MinLength(length) => write!(f, "at least {length} characters"),
+            Rule::MaxLength(length) => write!(f, "at most {length} characters"),
+            Rule::MinClasses(classes) => {
+                write!(f, "{classes} of lowercase, uppercase, digits and symbols")
+            }
+            Rule::Requires(class) => write!(f, "{class}"),
+            Rule::Forbidden(text) => write!(f, "not containing {text:?}"),
+            Rule::Username => write!(f, "not containing the username"),
+            Rule::Whitespace => write!(f, "no whitespace"),
+        }
+    }
+}
+
+/// Whether a password met one rule.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Verdict {
+    pub rule: Rule,
+    pub passed: bool,
+}
+
+impl Policy {
+    /// The rules it sets, in the order they're reported.
+    pub fn rules(&self) -> Vec<Rule> {
+        let mut rules = Vec::new();
+        rules.extend(self.min_length.map(Rule::MinLength));
+        rules.extend(self.max_length.map(Rule::MaxLength));
+        rules.extend(self.min_classes.map(Rule::MinClasses));
+        rules.extend(self.required_classes.iter().copied().map(Rule::Requires));
+        rules.extend(
+            self.forbidden
+                .iter()
+                .filter(|text| !text.is_empty())
+                .cloned()
+                .map(Rule::Forbidden),
+        );
+        if self.forbid_username {
+            rules.push(Rule::Username);
+        }
+        if self.forbid_whitespace {
+            rules.push(Rule::Whitespace);
+        }
+        rules
+    }
+
+    /// How `password` fares against each rule. The username rule passes
+    /// when there's no `username` to compare it with.
+    pub fn check(&self, password: &str, username: Option<&str>) -> Vec<Verdict> {
+        let composition = Composition::of(password);
+        let lowercase = password.to_lowercase();
+        self.rules()
+            .into_iter()
+            .map(|rule| {
+                let passed = match &rule {
+                    Rule::MinLength(length) => composition.chars >= *length,
+                    Rule::MaxLength(length) => composition.chars <= *length,
+                    Rule::MinClasses(classes) => {
+                        let present = Required::ALL
+                            .iter()
+                            .filter(|required| {
+                                composition.classes().any(|class| class == required.class())
+                            })
+                            .count();
+                        present >= *classes
+                    }
+                    Rule::Requires(required) => {
+                        composition.classes().any(|class| class == required.class())
+                    }
+                    Rule::Forbidden(text) => !lowercase.contains(&text.to_lowercase()),
+                    Rule::Username => username
+                        .map(str::trim)
+                        .filter(|username| !username.is_empty())
+                        .is_none_or(|username| !lowercase.contains(&username.to_lowercase())),
+                    Rule::Whitespace => !password.chars().any(char::is_whitespace),
+                };
+                Verdict { rule, passed }
+            })
+            .collect()
+    }
+}
+
+/// The rules among `verdicts` that weren't met.
+pub fn failures(verdicts: &[Verdict]) -> impl Iterator<Item = &Rule> {
+    verdicts
+        .iter()
+        .filter(|verdict| !verdict.passed)
+        .map(|verdict| &verdict.rule)
+}
+
+/// A password's [`failures`] in a few words: `meets the policy`, or
+/// `fails the policy:` and the rules it doesn't meet.
+pub fn describe<'a>(failed: impl IntoIterator<Item = &'a Rule>) -> String {
+    let failed: Vec<String> = failed.into_iter().map(Rule::to_string).collect();
+    if failed.is_empty() {
+        "meets the policy".to_owned()
+    } else {
+        format!("fails the policy: {}", failed.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The rules `password` fails under `policy`, with no username.
+    fn failed(policy: &Policy, password: &str) -> Vec<Rule> {
+        failures(&policy.check(password, None)).cloned().collect()
+    }
+
+    #[test]
+    fn no_rules_are_set_unless_asked_for() {
+        let policy = Policy::default();
+        assert!(policy.rules().is_empty());
+        assert!(policy.check("", Some("alice")).is_empty());
+        assert_eq!(describe(&failed(&policy, "")), "meets the policy");
+    }
+
+    #[test]
+    fn lengths_count_characters() {
+        let policy = Policy {
+            min_length: Some(4),
+            max_length: Some(6),
+            ..Policy::default()
+        };
+        assert_eq!(failed(&policy, "abc"), [Rule::MinLength(4)]);
+        assert!(failed(&policy, "äöüß").is_empty());
+        assert!(failed(&policy, "abcdef").is_empty());
+        assert_eq!(failed(&policy, "abcdefg"), [Rule::MaxLength(6)]);
+    }
+
+    #[test]
+    fn classes_are_counted_or_required_one_by_one() {
+        let counted = Policy {
+            min_classes: Some(3),
+            ..Policy::default()
+        };
+        assert_eq!(failed(&counted, "hunter2"), [Rule::MinClasses(3)]);
+        assert!(failed(&counted, "Hunter2").is_empty());
+        assert!(failed(&counted, "hunter2!").is_empty());
+        let required = Policy {
+            required_classes: Required::ALL.to_vec(),
+            ..Policy::default()
+        };
+        assert_eq!(
+            failed(&required, "hunter2"),
+            [
+                Rule::Requires(Required::Upper),
+                Rule::Requires(Required::Symbol)
+            ]
+        );
+        assert!(failed(&required, "Hunter2!").is_empty());
+    }
+
+    #[test]
+    fn forbidden_words_ignore_case_and_empty_ones_are_dropped() {
+        let policy = Policy {
+            forbidden: vec!["acme".to_owned(), String::new(), "Winter".to_owned()],
+            ..Policy::default()
+        };
+        assert_eq!(policy.rules().len(), 2);
+        assert_eq!(
+            failed(&policy, "ACMEwinter25"),
+            [
+                Rule::Forbidden("acme".to_owned()),
+                Rule::Forbidden("Winter".to_owned())
+            ]
+        );
+        assert!(failed(&policy, "summer25").is_empty());
+    }
+
+    #[test]
+    fn the_username_rule_needs_a_username_to_fail() {
+        let policy = Policy {
+            forbid_username: true,
+            ..Policy::default()
+        };
+        let fails = |password, username| {
+            failures(&policy.check(password, username))
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(fails("Alice1984", Some(" alice ")), [Rule::Username]);
+        assert!(fails("Alice1984", Some("bob")).is_empty());
+        assert!(fails("Alice1984", Some("  ")).is_empty());
+        assert!(fails("Alice1984", None).is_empty());
+    }
+
+    #[test]
+    fn whitespace_is_any_kind_of_space() {
+        let policy = Policy {
+            forbid_whitespace: true,
+            ..Policy::default()
+        };
+        for spaced in ["correct horse", "tab\there", "nbsp\u{a0}"] {
+            assert_eq!(failed(&policy, spaced), [Rule::Whitespace], "{spaced:?}");
+        }
+        assert!(failed(&policy, "correcthorse").is_empty());
+    }
+
+    #[test]
+    fn combined_rules_are_each_reported_in_order() {
+        let policy = Policy {
+            min_length: Some(12),
+            max_length: None,
+            min_classes: Some(3),
+            required_classes: vec![Required::Digit],
+            forbidden: vec!["acme".to_owned()],
+            forbid_username: true,
+            forbid_whitespace: true,
+        };
+        let verdicts = policy.check("acme alice", Some("Alice"));
+        assert_eq!(verdicts.len(), 6);
+        assert!(verdicts.iter().all(|verdict| !verdict.passed));
+        assert_eq!(
+            describe(failures(&verdicts)),
+            "fails the policy: at least 12 characters, \
+             3 of lowercase, uppercase, digits and symbols, a digit, \
+             not containing \"acme\", not containing the username, no whitespace"
+        );
+        let verdicts = policy.check("Tr0ub4dor&3x!", Some("Alice"));
+        assert!(verdicts.iter().all(|verdict| verdict.passed));
+        assert_eq!(describe(failures(&verdicts)), "meets the policy");
+    }
+}
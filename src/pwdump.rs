@@ -0,0 +1,200 @@
+use crate::HashMode;
+use crate::batch::{self, Lines};
+
+/// Which entries of a dump are checked besides each user's current
+/// password. Neither is by default: machine account passwords are random
+/// and rotated by the domain, and old passwords are no longer in use.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Include {
+    /// Accounts whose name ends with `$`.
+    pub machine_accounts: bool,
+    /// Entries like `user_history0`, for the passwords a user had before.
+    pub history: bool,
+}
+
+/// One line of a dump, split into its fields.
+struct Entry<'a> {
+    user: &'a str,
+    nt: &'a str,
+}
+
+/// Splits `user:RID:LMHASH:NTHASH:::` into its fields, or says why it
+/// can't. The fields after the NT hash, if any, aren't looked at.
+fn parse(line: &str) -> Result<Entry<'_>, String> {
+    let not_a_dump = || "not a user:RID:LM:NT line".to_owned();
+    let mut fields = line.splitn(5, ':');
+    let (Some(user), Some(rid), Some(_lm), Some(nt)) =
+        (fields.next(), fields.next(), fields.next(), fields.next())
+    else {
+        return Err(not_a_dump());
+    };
+    if user.is_empty() || rid.parse::<u32>().is_err() {
+        return Err(not_a_dump());
+    }
+    Ok(Entry { user, nt })
+}
+
+/// Whether `bytes` look like a dump: the first line that isn't blank or a
+/// comment is `user:RID:LM:NT`.
+pub fn detect(bytes: &[u8]) -> bool {
+    let Ok(text) = batch::list_text(bytes) else {
+        return false;
+    };
+    batch::text_lines(text)
+        .map(|(_, line)| line.trim())
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .is_some_and(|line| parse(line).is_ok())
+}
+
+/// The account an entry's name is for, and which of its old passwords it
+/// is, if it's a history entry: `CORP\ann_history2` is `CORP\ann`'s.
+fn history_of(user: &str) -> (&str, Option<&str>) {
+    match user.rsplit_once("_history") {
+        Some((account, index))
+            if !index.is_empty() && index.bytes().all(|b| b.is_ascii_digit()) =>
+        {
+            (account, Some(index))
+        }
+        _ => (user, None),
+    }
+}
+
+/// Reads a `secretsdump` or `pwdump` dump of `DOMAIN\user:RID:LM:NT:::`
+/// lines as a list of NTLM hashes labelled by account. The LM hash is
+/// ignored. Machine accounts and history entries are skipped unless
+/// `include` says otherwise. A line that isn't a dump entry, or whose NT
+/// hash is missing or malformed, is left in [`Lines::malformed`].
+pub fn read(bytes: &[u8], include: Include) -> Result<Lines, String> {
+    let text = batch::list_text(bytes)?;
+    let entries = batch::text_lines(text).filter_map(|(number, line)| {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let entry = match parse(line) {
+            Ok(entry) => entry,
+            Err(reason) => return Some((number, Err(reason))),
+        };
+        let (account, history) = history_of(entry.user);
+        if (account.ends_with('$') && !include.machine_accounts)
+            || (history.is_some() && !include.history)
+        {
+            return None;
+        }
+        if entry.nt.is_empty() {
+            return Some((number, Err(format!("{} has no NT hash", entry.user))));
+        }
+        let label = match history {
+            Some(index) => format!("{account} (history {index})"),
+            None => account.to_owned(),
+        };
+        Some((number, Ok((entry.nt, label))))
+    });
+    Ok(Lines {
+        accounts: true,
+        ..batch::hash_lines(HashMode::Ntlm, entries)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A `secretsdump` dump with an empty NT hash, history entries, a
+    /// machine account, a status line, and `\r\n`, `\n` and lone `\r` line
+    /// endings.
+    const DUMP: &[u8] = include_bytes!("../tests/fixtures/secretsdump.txt");
+
+    const ADMINISTRATOR_NT: &str = "8846F7EAEE8FB117AD06BDD830B7586C";
+
+    fn labels(batch: &Lines) -> Vec<(usize, &str, usize)> {
+        batch
+            .lines
+            .iter()
+            .map(|line| (line.number, line.label.as_str(), line.password))
+            .collect()
+    }
+
+    #[test]
+    fn a_dump_is_read_as_ntlm_hashes_by_account() {
+        let batch = read(DUMP, Include::default()).unwrap();
+        assert_eq!(batch.hashed, Some(HashMode::Ntlm));
+        assert!(batch.accounts);
+        assert_eq!(
+            labels(&batch),
+            [(1, "CORP\\Administrator", 0), (3, "CORP\\ann", 1)]
+        );
+        assert_eq!(batch.passwords[0].expose(), ADMINISTRATOR_NT);
+        assert_eq!(
+            batch.passwords[1].expose(),
+            "32ED87BDB5FDC5E9CBA88547376818D4"
+        );
+    }
+
+    #[test]
+    fn an_empty_nt_hash_and_a_status_line_are_malformed() {
+        let batch = read(DUMP, Include::default()).unwrap();
+        let malformed: Vec<_> = batch
+            .malformed
+            .iter()
+            .map(|malformed| (malformed.line, malformed.reason.as_str()))
+            .collect();
+        assert_eq!(
+            malformed,
+            [
+                (2, "Guest has no NT hash"),
+                (7, "not a user:RID:LM:NT line")
+            ]
+        );
+    }
+
+    #[test]
+    fn history_entries_are_labelled_by_account_when_included() {
+        let include = Include {
+            history: true,
+            ..Include::default()
+        };
+        let batch = read(DUMP, include).unwrap();
+        assert_eq!(
+            labels(&batch),
+            [
+                (1, "CORP\\Administrator", 0),
+                (3, "CORP\\ann", 1),
+                (4, "CORP\\ann (history 0)", 0),
+                (5, "CORP\\ann (history 1)", 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn machine_accounts_are_only_read_when_included() {
+        let include = Include {
+            machine_accounts: true,
+            ..Include::default()
+        };
+        let batch = read(DUMP, include).unwrap();
+        assert_eq!(labels(&batch).last(), Some(&(6, "DC01$", 2)));
+    }
+
+    #[test]
+    fn a_history_entry_is_named_by_its_account_and_index() {
+        assert_eq!(history_of("CORP\\ann_history12"), ("CORP\\ann", Some("12")));
+        assert_eq!(history_of("svc_history"), ("svc_history", None));
+        assert_eq!(history_of("ann_historyx"), ("ann_historyx", None));
+        assert_eq!(history_of("ann"), ("ann", None));
+    }
+
+    #[test]
+    fn a_dump_is_told_from_other_lists() {
+        assert!(detect(DUMP));
+        assert!(detect(
+            b"# dumped\n\nann:1104:::31d6cfe0d16ae931b73c59d7e0c089c0:::"
+        ));
+        assert!(!detect(format!("{ADMINISTRATOR_NT}:admin\n").as_bytes()));
+        assert!(!detect(b"hunter2\ncorrect horse battery staple\n"));
+        assert!(!detect(
+            b"ann:not-a-rid:aad3b435b51404eeaad3b435b51404ee:31d6:::"
+        ));
+        assert!(!detect(b""));
+    }
+}
@@ -0,0 +1,873 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::ops::ControlFlow;
+use std::pin::pin;
+
+use futures::{FutureExt, Stream, StreamExt, stream};
+
+use crate::error::ParseError;
+
+/// The real entries of one hash prefix's range, keyed by uppercase suffix.
+/// Padding entries (count `0`, sent when `Add-Padding` is on) are only
+/// counted, so they can't turn up as a match or skew statistics.
+///
+/// ```
+/// use cybersec_wow::range::parse_range;
+///
+/// let range = parse_range("0018A45C4D1DEF81644B54AB7F969B88D65:10\r\n\
+///                          00D4F6E8FA6EECAD2A3AA415EEC418D38EC:0\r\n").unwrap();
+/// assert_eq!(range.len(), 1);
+/// assert_eq!(range.padding(), 1);
+/// assert_eq!(range.get("0018a45c4d1def81644b54ab7f969b88d65"), Some(10));
+/// assert_eq!(range.get("00D4F6E8FA6EECAD2A3AA415EEC418D38EC"), None);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Range {
+    counts: HashMap<String, u64>,
+    padding: usize,
+}
+
+impl Range {
+    /// The count for `suffix`, in either case.
+    pub fn get(&self, suffix: &str) -> Option<u64> {
+        self.counts.get(&suffix.to_ascii_uppercase()).copied()
+    }
+
+    /// The number of real entries.
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, u64)> {
+        self.counts
+            .iter()
+            .map(|(suffix, &count)| (suffix.as_str(), count))
+    }
+
+    /// How many padding entries the response carried.
+    pub fn padding(&self) -> usize {
+        self.padding
+    }
+
+    pub fn with_padding(self, padding: usize) -> Self {
+        Self { padding, ..self }
+    }
+}
+
+/// Longest line we'll buffer waiting for a newline. Real lines are a 35 or
+/// 27 character suffix and a count, so anything near this isn't a range.
+const MAX_LINE_LEN: usize = 256;
+
+/// Scans a `SUFFIX:COUNT` range body as it arrives and stops at the first line
+/// matching `suffix`. Suffixes are unique within a range, so nothing after the
+/// match can change the answer, and a duplicate past it goes unnoticed.
+/// Padding entries (count `0`) never match.
+///
+/// Each run of whole lines goes to [`find_count`] as soon as it's in, so only
+/// a line cut by a chunk boundary waits for the next chunk.
+///
+/// ```
+/// use std::convert::Infallible;
+///
+/// use cybersec_wow::range::find_suffix;
+/// use futures::executor::block_on;
+/// use futures::stream;
+///
+/// // However the body is split into chunks, and whatever follows the match.
+/// let body = ["0018A45C4D1DEF81644B54AB7F9", "69B88D65:10\r\n<html>"];
+/// let chunks = stream::iter(body.map(Ok::<_, Infallible>));
+/// let found = block_on(find_suffix(chunks, "0018A45C4D1DEF81644B54AB7F969B88D65"));
+/// assert_eq!(found, Ok(Ok(Some(10))));
+/// ```
+pub async fn find_suffix<S, B, E>(
+    stream: S,
+    suffix: &str,
+) -> Result<Result<Option<u64>, ParseError>, E>
+where
+    S: Stream<Item = Result<B, E>>,
+    B: AsRef<[u8]>,
+{
+    let mut stream = pin!(stream);
+    let mut pending = Vec::new();
+    let mut scan = Scan::default();
+
+    while let Some(chunk) = stream.next().await {
+        pending.extend_from_slice(chunk?.as_ref());
+
+        if let Some(end) = pending.iter().rposition(|&byte| byte == b'\n') {
+            match scan.find(&pending[..end], suffix) {
+                Ok(None) => {}
+                found => return Ok(found),
+            }
+            pending.drain(..=end);
+        }
+        if pending.len() > MAX_LINE_LEN {
+            return Ok(Err(malformed(scan.lines + 1, &pending)));
+        }
+    }
+
+    Ok(match scan.find(&pending, suffix) {
+        Ok(None) if !scan.entries => Err(ParseError::EmptyBody),
+        found => found,
+    })
+}
+
+/// The count for `suffix` in a body already in memory, read in one pass that
+/// stops at the match and collects nothing on the way. As with
+/// [`find_suffix`], a line before the match that doesn't parse, or a body with
+/// no entries, is an error rather than a miss.
+///
+/// ```
+/// use cybersec_wow::range::find_count;
+///
+/// let body = "AAA:1\r\nBBB:2\r\nCCC:0\r\n";
+/// assert_eq!(find_count(body, "bbb"), Ok(Some(2)));
+/// assert_eq!(find_count(body, "CCC"), Ok(None));
+/// assert!(find_count("<html>\nBBB:2", "BBB").is_err());
+/// ```
+pub fn find_count(body: &str, suffix: &str) -> Result<Option<u64>, ParseError> {
+    let mut entries = false;
+    for (index, line) in body.split('\n').enumerate() {
+        if line.len() > MAX_LINE_LEN {
+            return Err(malformed(index + 1, line.as_bytes()));
+        }
+        let line = line.trim_end_matches('\r');
+        if line.is_empty() {
+            continue;
+        }
+        let (hash_suffix, count) =
+            parse_line(line).ok_or_else(|| malformed(index + 1, line.as_bytes()))?;
+        if count > 0 && hash_suffix.eq_ignore_ascii_case(suffix) {
+            return Ok(Some(count));
+        }
+        entries = true;
+    }
+    if entries {
+        Ok(None)
+    } else {
+        Err(ParseError::EmptyBody)
+    }
+}
+
+/// Where [`find_suffix`] is in a body it's handing to [`find_count`] a run of
+/// lines at a time.
+#[derive(Default)]
+struct Scan {
+    lines: usize,
+    entries: bool,
+}
+
+impl Scan {
+    /// Looks through `run`, some whole lines without their last newline,
+    /// numbering them on from the runs before.
+    fn find(&mut self, run: &[u8], suffix: &str) -> Result<Option<u64>, ParseError> {
+        let text = match std::str::from_utf8(run) {
+            Ok(text) => text,
+            Err(error) => {
+                // The lines before the one that isn't text may still match.
+                let start = run[..error.valid_up_to()]
+                    .iter()
+                    .rposition(|&byte| byte == b'\n')
+                    .map_or(0, |newline| newline + 1);
+                if start > 0
+                    && let Some(count) = self.find(&run[..start - 1], suffix)?
+                {
+                    return Ok(Some(count));
+                }
+                let line = run[start..].split(|&byte| byte == b'\n').next();
+                return Err(malformed(self.lines + 1, line.unwrap_or_default()));
+            }
+        };
+        let found = match find_count(text, suffix) {
+            Err(ParseError::EmptyBody) => Ok(None),
+            Err(ParseError::MalformedLine { line_no, content }) => Err(ParseError::MalformedLine {
+                line_no: self.lines + line_no,
+                content,
+            }),
+            found => {
+                self.entries = true;
+                found
+            }
+        };
+        self.lines += text.split('\n').count();
+        found
+    }
+}
+
+/// Reads a whole range body into a [`Range`]. A repeated suffix is an error
+/// rather than summed or overwritten.
+///
+/// ```
+/// use std::convert::Infallible;
+///
+/// use cybersec_wow::error::ParseError;
+/// use cybersec_wow::range::collect_range;
+/// use futures::stream;
+///
+/// let body = ["AAA:1\nBBB:2\n", "AAA:3\n"].map(Ok::<_, Infallible>);
+/// let collected = futures::executor::block_on(collect_range(stream::iter(body)));
+/// assert_eq!(collected, Ok(Err(ParseError::Duplicate("AAA".to_owned()))));
+/// ```
+pub async fn collect_range<S, B, E>(stream: S) -> Result<Result<Range, ParseError>, E>
+where
+    S: Stream<Item = Result<B, E>>,
+    B: AsRef<[u8]>,
+{
+    let mut range = Range::default();
+    let mut duplicate = None;
+    let parsed = for_each_line(stream, |hash_suffix, count| {
+        if count == 0 {
+            range.padding += 1;
+        } else if range
+            .counts
+            .insert(hash_suffix.to_ascii_uppercase(), count)
+            .is_some()
+        {
+            duplicate = Some(hash_suffix.to_owned());
+            return ControlFlow::Break(());
+        }
+        ControlFlow::Continue(())
+    })
+    .await?;
+
+    Ok(match (parsed, duplicate) {
+        (Err(error), _) => Err(error),
+        (Ok(()), Some(suffix)) => Err(ParseError::Duplicate(suffix)),
+        (Ok(()), None) => Ok(range),
+    })
+}
+
+/// [`collect_range`] for a body that's already in memory.
+///
+/// ```
+/// use cybersec_wow::error::ParseError;
+/// use cybersec_wow::range::parse_range;
+///
+/// let range = parse_range("AAA:1\nBBB:2\n").unwrap();
+/// assert_eq!(range.get("BBB"), Some(2));
+/// assert_eq!(parse_range(""), Err(ParseError::EmptyBody));
+/// assert!(matches!(
+///     parse_range("AAA:1\n<html>\n"),
+///     Err(ParseError::MalformedLine { line_no: 2, .. })
+/// ));
+/// ```
+pub fn parse_range(body: &str) -> Result<Range, ParseError> {
+    let chunks = stream::iter([Ok::<_, Infallible>(body)]);
+    let Ok(parsed) = collect_range(chunks)
+        .now_or_never()
+        .expect("an in-memory stream is always ready");
+    parsed
+}
+
+/// Feeds each entry to `visit` until it breaks, skipping blank lines. The
+/// outer error is the stream's; the inner one is the first line that doesn't
+/// parse, or a body with no entries at all.
+async fn for_each_line<S, B, E>(
+    stream: S,
+    mut visit: impl FnMut(&str, u64) -> ControlFlow<()>,
+) -> Result<Result<(), ParseError>, E>
+where
+    S: Stream<Item = Result<B, E>>,
+    B: AsRef<[u8]>,
+{
+    let mut stream = pin!(stream);
+    let mut pending = Vec::new();
+    let mut lines = Lines::default();
+
+    while let Some(chunk) = stream.next().await {
+        pending.extend_from_slice(chunk?.as_ref());
+
+        let mut consumed = 0;
+        while let Some(end) = pending[consumed..].iter().position(|&byte| byte == b'\n') {
+            let line = &pending[consumed..consumed + end];
+            consumed += end + 1;
+            // However the body was split, a line that long is refused.
+            if line.len() > MAX_LINE_LEN {
+                lines.line_no += 1;
+                return Ok(Err(malformed(lines.line_no, line)));
+            }
+            match lines.visit(line, &mut visit) {
+                Ok(ControlFlow::Continue(())) => {}
+                Ok(ControlFlow::Break(())) => return Ok(Ok(())),
+                Err(error) => return Ok(Err(error)),
+            }
+        }
+        pending.drain(..consumed);
+        if pending.len() > MAX_LINE_LEN {
+            lines.line_no += 1;
+            return Ok(Err(malformed(lines.line_no, &pending)));
+        }
+    }
+
+    if let Err(error) = lines.visit(&pending, &mut visit) {
+        return Ok(Err(error));
+    }
+    Ok(if lines.entries == 0 {
+        Err(ParseError::EmptyBody)
+    } else {
+        Ok(())
+    })
+}
+
+#[derive(Default)]
+struct Lines {
+    line_no: usize,
+    entries: usize,
+}
+
+impl Lines {
+    fn visit(
+        &mut self,
+        line: &[u8],
+        visit: &mut impl FnMut(&str, u64) -> ControlFlow<()>,
+    ) -> Result<ControlFlow<()>, ParseError> {
+        self.line_no += 1;
+        let text = std::str::from_utf8(line).map(|line| line.trim_end_matches('\r'));
+        if text.is_ok_and(str::is_empty) {
+            return Ok(ControlFlow::Continue(()));
+        }
+        let (hash_suffix, count) = text
+            .ok()
+            .and_then(parse_line)
+            .ok_or_else(|| malformed(self.line_no, line))?;
+        self.entries += 1;
+        Ok(visit(hash_suffix, count))
+    }
+}
+
+fn malformed(line_no: usize, line: &[u8]) -> ParseError {
+    let content = String::from_utf8_lossy(&line[..line.len().min(MAX_LINE_LEN)]);
+    ParseError::MalformedLine {
+        line_no,
+        content: content.trim_end().chars().take(80).collect(),
+    }
+}
+
+fn parse_line(line: &str) -> Option<(&str, u64)> {
+    let (hash_suffix, count) = line.split_once(':')?;
+    Some((hash_suffix, count.parse().ok()?))
+}
+
+/// How one range's counts are spread, for the lookup details panel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RangeStats {
+    pub suffixes: usize,
+    pub max: u64,
+    pub median: u64,
+    /// Where `count` places among the range's counts, 1 being the highest.
+    pub rank: Option<usize>,
+}
+
+/// `None` for an empty range. `rank` is set when `count` is.
+///
+/// ```
+/// use cybersec_wow::range::{RangeStats, parse_range, stats};
+///
+/// let range = parse_range("AAA:1\nBBB:5\nCCC:9\nDDD:2\n").unwrap();
+/// assert_eq!(
+///     stats(&range, Some(5)),
+///     Some(RangeStats { suffixes: 4, max: 9, median: 3, rank: Some(2) })
+/// );
+/// ```
+pub fn stats(range: &Range, count: Option<u64>) -> Option<RangeStats> {
+    let mut counts: Vec<u64> = range.counts.values().copied().collect();
+    counts.sort_unstable();
+    let max = *counts.last()?;
+    let mid = counts.len() / 2;
+    let median = if counts.len().is_multiple_of(2) {
+        counts[mid - 1].midpoint(counts[mid])
+    } else {
+        counts[mid]
+    };
+    let rank = count.map(|count| counts.len() - counts.partition_point(|&c| c <= count) + 1);
+
+    Some(RangeStats {
+        suffixes: counts.len(),
+        max,
+        median,
+        rank,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use futures::{FutureExt, stream};
+    use proptest::collection::{btree_map, vec};
+    use proptest::prelude::*;
+
+    use super::*;
+
+    /// Chunks `body` at the given byte offsets, as a response might arrive.
+    fn chunked<'a>(
+        body: &'a str,
+        splits: &[usize],
+    ) -> impl Stream<Item = Result<&'a [u8], Infallible>> {
+        let mut bounds = vec![0];
+        bounds.extend_from_slice(splits);
+        bounds.push(body.len());
+        let chunks: Vec<_> = bounds
+            .windows(2)
+            .map(|pair| Ok(&body.as_bytes()[pair[0]..pair[1]]))
+            .collect();
+        stream::iter(chunks)
+    }
+
+    fn find(body: &str, splits: &[usize], suffix: &str) -> Option<u64> {
+        let Ok(found) = find_suffix(chunked(body, splits), suffix)
+            .now_or_never()
+            .unwrap();
+        found.unwrap()
+    }
+
+    /// Two real entries among padding, one of which is what's being looked for.
+    const PADDED: &str = "0018A45C4D1DEF81644B54AB7F969B88D65:1\r\n\
+                          00D4F6E8FA6EECAD2A3AA415EEC418D38EC:0\r\n\
+                          011053FD0102E94D6AE2F8B83D76FAF94F6:27\r\n\
+                          012A7CA357541F0AC487871FEEC1891C49C:0\r\n";
+
+    #[test]
+    fn padding_is_never_found() {
+        assert_eq!(
+            find(PADDED, &[], "011053FD0102E94D6AE2F8B83D76FAF94F6"),
+            Some(27)
+        );
+        assert_eq!(
+            find(PADDED, &[], "00D4F6E8FA6EECAD2A3AA415EEC418D38EC"),
+            None
+        );
+        assert_eq!(
+            find(PADDED, &[], "012A7CA357541F0AC487871FEEC1891C49C"),
+            None
+        );
+    }
+
+    #[test]
+    fn finds_a_suffix_however_the_body_is_split() {
+        let body = "0018A45C4D1DEF81644B54AB7F969B88D65:1\r\n\
+                    011053FD0102E94D6AE2F8B83D76FAF94F6:27\r\n\
+                    0120D8D96E4F2C1A1D1E4F8A8EC13D6D41A:3";
+        let suffix = "0120D8D96E4F2C1A1D1E4F8A8EC13D6D41A";
+        // Every single split point, including inside each CRLF.
+        for split in 0..=body.len() {
+            assert_eq!(find(body, &[split], suffix), Some(3), "split at {split}");
+        }
+        // A byte at a time.
+        let every: Vec<_> = (1..body.len()).collect();
+        assert_eq!(find(body, &every, suffix), Some(3));
+        assert_eq!(
+            find(body, &every, "011053FD0102E94D6AE2F8B83D76FAF94F6"),
+            Some(27)
+        );
+    }
+
+    #[test]
+    fn a_crlf_straddling_chunks_isnt_part_of_the_count() {
+        let body =
+            "011053FD0102E94D6AE2F8B83D76FAF94F6:27\r\n0120D8D96E4F2C1A1D1E4F8A8EC13D6D41A:3\r\n";
+        let cr = body.find('\r').unwrap();
+        assert_eq!(
+            find(body, &[cr + 1], "011053FD0102E94D6AE2F8B83D76FAF94F6"),
+            Some(27)
+        );
+        let last_cr = body.rfind('\r').unwrap();
+        assert_eq!(
+            find(body, &[last_cr + 1], "0120D8D96E4F2C1A1D1E4F8A8EC13D6D41A"),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn stops_at_the_match() {
+        // Past the match the body is garbage, which is never read.
+        let body = "011053FD0102E94D6AE2F8B83D76FAF94F6:27\ngarbage";
+        assert_eq!(
+            find(body, &[20], "011053FD0102E94D6AE2F8B83D76FAF94F6"),
+            Some(27)
+        );
+    }
+
+    #[test]
+    fn an_overlong_line_is_refused_however_the_body_arrives() {
+        let body = format!(
+            "{}:1\n011053FD0102E94D6AE2F8B83D76FAF94F6:27\n",
+            "A".repeat(MAX_LINE_LEN)
+        );
+        for splits in [&[][..], &[100], &[MAX_LINE_LEN + 1]] {
+            assert!(
+                matches!(
+                    find_in(
+                        body.as_bytes(),
+                        splits,
+                        "011053FD0102E94D6AE2F8B83D76FAF94F6"
+                    ),
+                    Err(ParseError::MalformedLine { line_no: 1, .. })
+                ),
+                "split at {splits:?}"
+            );
+        }
+        assert!(matches!(
+            parse_range(&body),
+            Err(ParseError::MalformedLine { line_no: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn the_stream_error_comes_first() {
+        let chunks = stream::iter([
+            Ok(&b"011053FD0102E94D6AE2F8B83D76FAF94F6:2"[..]),
+            Err("reset"),
+        ]);
+        let found = find_suffix(chunks, "011053FD0102E94D6AE2F8B83D76FAF94F6").now_or_never();
+        assert_eq!(found, Some(Err("reset")));
+    }
+
+    #[test]
+    fn collecting_drops_padding() {
+        let Ok(range) = collect_range(chunked(PADDED, &[50]))
+            .now_or_never()
+            .unwrap();
+        let range = range.unwrap();
+        assert_eq!(range.len(), 2);
+        assert_eq!(range.get("011053FD0102E94D6AE2F8B83D76FAF94F6"), Some(27));
+        assert_eq!(range.get("00D4F6E8FA6EECAD2A3AA415EEC418D38EC"), None);
+    }
+
+    fn parse(body: &str) -> Result<Range, ParseError> {
+        let Ok(parsed) = collect_range(chunked(body, &[])).now_or_never().unwrap();
+        parsed
+    }
+
+    fn malformed(body: &str) -> (usize, String) {
+        match parse(body) {
+            Err(ParseError::MalformedLine { line_no, content }) => (line_no, content),
+            parsed => panic!("expected a malformed line, got {parsed:?}"),
+        }
+    }
+
+    #[test]
+    fn crlf_lf_and_a_missing_final_newline_read_the_same() {
+        let lf = "0018A45C4D1DEF81644B54AB7F969B88D65:1\n011053FD0102E94D6AE2F8B83D76FAF94F6:27\n";
+        let crlf = lf.replace('\n', "\r\n");
+        let unterminated = lf.trim_end();
+        for body in [lf, &crlf, unterminated] {
+            let range = parse(body).unwrap();
+            assert_eq!(range.len(), 2, "{body:?}");
+            assert_eq!(range.get("011053FD0102E94D6AE2F8B83D76FAF94F6"), Some(27));
+        }
+    }
+
+    #[test]
+    fn blank_lines_are_skipped_but_still_numbered() {
+        let body = "\r\n0018A45C4D1DEF81644B54AB7F969B88D65:1\r\n\r\n\n";
+        assert_eq!(parse(body).unwrap().len(), 1);
+        let (line_no, _) = malformed("\n\r\n0018A45C4D1DEF81644B54AB7F969B88D65\r\n");
+        assert_eq!(line_no, 3);
+    }
+
+    #[test]
+    fn a_line_without_a_colon_is_malformed() {
+        let body =
+            "0018A45C4D1DEF81644B54AB7F969B88D65:1\r\n011053FD0102E94D6AE2F8B83D76FAF94F6 27\r\n";
+        assert_eq!(
+            malformed(body),
+            (2, "011053FD0102E94D6AE2F8B83D76FAF94F6 27".to_owned())
+        );
+    }
+
+    #[test]
+    fn a_count_that_isnt_a_number_is_malformed() {
+        for count in ["", "-1", "27x", "2.5", " 27", "18446744073709551616"] {
+            let body = format!("011053FD0102E94D6AE2F8B83D76FAF94F6:{count}");
+            assert_eq!(malformed(&body).0, 1, "{count:?}");
+        }
+    }
+
+    #[test]
+    fn an_html_error_page_is_malformed_not_empty() {
+        let body = "<!DOCTYPE html>\n<html><head><title>502 Bad Gateway</title></head>\n</html>\n";
+        assert_eq!(malformed(body), (1, "<!DOCTYPE html>".to_owned()));
+        // A lookup can't take it for a miss either.
+        let Ok(found) = find_suffix(chunked(body, &[20]), "ABC")
+            .now_or_never()
+            .unwrap();
+        assert!(matches!(
+            found,
+            Err(ParseError::MalformedLine { line_no: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn a_body_without_entries_is_empty() {
+        for body in ["", "\r\n", "\n\n\n"] {
+            assert_eq!(parse(body).unwrap_err(), ParseError::EmptyBody, "{body:?}");
+        }
+    }
+
+    #[test]
+    fn a_repeated_suffix_is_reported() {
+        let body =
+            "011053FD0102E94D6AE2F8B83D76FAF94F6:27\r\n011053FD0102E94D6AE2F8B83D76FAF94F6:3\r\n";
+        assert_eq!(
+            parse(body).unwrap_err(),
+            ParseError::Duplicate("011053FD0102E94D6AE2F8B83D76FAF94F6".to_owned())
+        );
+    }
+
+    #[test]
+    fn find_count_agrees_with_the_parsed_range() {
+        let body = include_str!("../tests/fixtures/range-F3BBB.txt");
+        let range = parse_range(body).unwrap();
+        for (suffix, count) in range.iter() {
+            assert_eq!(find_count(body, suffix), Ok(Some(count)), "{suffix}");
+            assert_eq!(
+                find_count(body, &suffix.to_ascii_lowercase()),
+                Ok(Some(count))
+            );
+        }
+        assert_eq!(
+            find_count(body, "0000000000000000000000000000000000A"),
+            Ok(None)
+        );
+        assert_eq!(
+            find_count(PADDED, "00D4F6E8FA6EECAD2A3AA415EEC418D38EC"),
+            Ok(None)
+        );
+        assert_eq!(find_count("", "ABC"), Err(ParseError::EmptyBody));
+    }
+
+    #[test]
+    fn find_count_reports_a_body_that_doesnt_parse() {
+        let suffix = "011053FD0102E94D6AE2F8B83D76FAF94F6";
+        for (body, line) in [
+            ("<html>502 Bad Gateway</html>".to_owned(), 1),
+            (
+                format!("0018A45C4D1DEF81644B54AB7F969B88D65\r\n{suffix}:27"),
+                1,
+            ),
+            (format!("\n{}:1\n{suffix}:27", "A".repeat(MAX_LINE_LEN)), 2),
+        ] {
+            assert!(
+                matches!(
+                    find_count(&body, suffix),
+                    Err(ParseError::MalformedLine { line_no, .. }) if line_no == line
+                ),
+                "{body:?}"
+            );
+        }
+        // Nothing past the match is read.
+        assert_eq!(
+            find_count(&format!("{suffix}:27\n<html>"), suffix),
+            Ok(Some(27))
+        );
+    }
+
+    #[test]
+    fn lines_are_numbered_across_chunks() {
+        let body = "0018A45C4D1DEF81644B54AB7F969B88D65:1\n\n\r\n<html>\n";
+        for split in 0..=body.len() {
+            assert!(
+                matches!(
+                    find_in(body.as_bytes(), &[split], "ABC"),
+                    Err(ParseError::MalformedLine { line_no: 4, .. })
+                ),
+                "split at {split}"
+            );
+        }
+        let body = b"011053FD0102E94D6AE2F8B83D76FAF94F6:27\n\xff\xfe:1\n";
+        assert_eq!(
+            find_in(body, &[], "011053FD0102E94D6AE2F8B83D76FAF94F6"),
+            Ok(Some(27))
+        );
+        assert!(matches!(
+            find_in(body, &[], "ABC"),
+            Err(ParseError::MalformedLine { line_no: 2, .. })
+        ));
+    }
+
+    fn stats_of(body: &str, count: Option<u64>) -> Option<RangeStats> {
+        stats(&parse_range(body).unwrap(), count)
+    }
+
+    #[test]
+    fn stats_take_the_middle_count_or_the_two_around_it() {
+        let odd = "A:5\nB:1\nC:9\n";
+        let even = "A:5\nB:1\nC:9\nD:2\n";
+        assert_eq!(
+            stats_of(odd, None),
+            Some(RangeStats {
+                suffixes: 3,
+                max: 9,
+                median: 5,
+                rank: None
+            })
+        );
+        assert_eq!(stats_of(even, None).unwrap().median, 3);
+        assert_eq!(stats_of("A:7", None).unwrap().median, 7);
+    }
+
+    #[test]
+    fn rank_counts_the_entries_above() {
+        let body = "A:5\nB:1\nC:9\nD:5\n";
+        assert_eq!(stats_of(body, Some(9)).unwrap().rank, Some(1));
+        // Ties share the better rank.
+        assert_eq!(stats_of(body, Some(5)).unwrap().rank, Some(2));
+        assert_eq!(stats_of(body, Some(1)).unwrap().rank, Some(4));
+    }
+
+    #[test]
+    fn padding_isnt_in_the_stats() {
+        assert_eq!(stats_of(PADDED, None).unwrap().suffixes, 2);
+        assert_eq!(stats_of(PADDED, None).unwrap().median, 14);
+        assert_eq!(
+            stats_of("00D4F6E8FA6EECAD2A3AA415EEC418D38EC:0\n", None),
+            None
+        );
+    }
+
+    /// `body` cut at `splits`, taken modulo its length.
+    fn pieces<'a>(body: &'a [u8], splits: &[usize]) -> Vec<Result<&'a [u8], Infallible>> {
+        let mut bounds: Vec<_> = splits
+            .iter()
+            .map(|split| split % (body.len() + 1))
+            .collect();
+        bounds.extend([0, body.len()]);
+        bounds.sort_unstable();
+        bounds
+            .windows(2)
+            .map(|pair| Ok(&body[pair[0]..pair[1]]))
+            .collect()
+    }
+
+    fn find_in(body: &[u8], splits: &[usize], suffix: &str) -> Result<Option<u64>, ParseError> {
+        let Ok(found) = find_suffix(stream::iter(pieces(body, splits)), suffix)
+            .now_or_never()
+            .unwrap();
+        found
+    }
+
+    /// One piece of a hostile body.
+    fn hostile() -> impl Strategy<Value = Vec<u8>> {
+        prop_oneof![
+            "[0-9A-F]{35}:[0-9]{1,8}\r?\n".prop_map(String::into_bytes),
+            (1..64usize).prop_map(|len| vec![0; len]),
+            (MAX_LINE_LEN - 8..MAX_LINE_LEN * 3).prop_map(|len| vec![b'A'; len]),
+            "[0-9A-F]{35}:\\PC{0,4}\n?".prop_map(String::into_bytes),
+            vec(any::<u8>(), 0..64),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn a_formatted_range_reads_back(
+            entries in btree_map("[0-9A-F]{35}", 1..u64::MAX, 0..40),
+            padding in vec("[0-9A-F]{35}", 0..10),
+            crlf in any::<bool>(),
+            trailing_newline in any::<bool>(),
+            lowercase in any::<bool>(),
+            splits in vec(any::<usize>(), 0..8),
+        ) {
+            let newline = if crlf { "\r\n" } else { "\n" };
+            let mut lines: Vec<_> = entries.iter().map(|(suffix, count)| format!("{suffix}:{count}")).collect();
+            lines.extend(padding.iter().map(|suffix| format!("{suffix}:0")));
+            let mut body = lines.join(newline);
+            if trailing_newline {
+                body.push_str(newline);
+            }
+            if lowercase {
+                body.make_ascii_lowercase();
+            }
+
+            match parse_range(&body) {
+                Ok(range) => {
+                    prop_assert_eq!(range.len(), entries.len());
+                    prop_assert_eq!(range.padding(), padding.len());
+                    for (suffix, &count) in &entries {
+                        prop_assert_eq!(range.get(suffix), Some(count));
+                    }
+                }
+                Err(error) => {
+                    prop_assert_eq!(error, ParseError::EmptyBody);
+                    prop_assert!(entries.is_empty() && padding.is_empty());
+                }
+            }
+            for (suffix, &count) in entries.iter().take(8) {
+                prop_assert_eq!(find_in(body.as_bytes(), &splits, suffix), Ok(Some(count)));
+                prop_assert_eq!(find_count(&body, suffix), Ok(Some(count)));
+            }
+            for suffix in padding.iter().take(8) {
+                prop_assert_eq!(
+                    find_count(&body, suffix).ok().flatten(),
+                    entries.get(suffix).copied()
+                );
+            }
+            if !entries.is_empty() {
+                prop_assert_eq!(find_in(body.as_bytes(), &splits, &"X".repeat(35)), Ok(None));
+                prop_assert_eq!(find_count(&body, &"X".repeat(35)), Ok(None));
+            }
+        }
+
+        /// Parsing then looking up and one pass agree on every miss, whatever
+        /// the body.
+        #[test]
+        fn find_count_misses_whenever_parse_and_get_does(
+            body in vec(hostile(), 0..12),
+            suffix in "[0-9A-F]{35}",
+        ) {
+            let body = String::from_utf8_lossy(&body.concat()).into_owned();
+            let old = parse_range(&body).ok().and_then(|range| range.get(&suffix));
+            if old.is_none() {
+                prop_assert_eq!(find_count(&body, &suffix).ok().flatten(), None);
+            }
+            let first = body.split('\n').next().unwrap_or_default();
+            if first.len() <= MAX_LINE_LEN
+                && let Some((listed, count)) = parse_line(first.trim_end_matches('\r'))
+                && count > 0
+            {
+                prop_assert_eq!(find_count(&body, listed), Ok(Some(count)));
+            }
+        }
+
+        #[test]
+        fn arbitrary_bytes_never_panic(
+            body in vec(any::<u8>(), 0..4096),
+            splits in vec(any::<usize>(), 0..8),
+        ) {
+            let _ = find_in(&body, &splits, "0018A45C4D1DEF81644B54AB7F969B88D65");
+            let _ = find_count(&String::from_utf8_lossy(&body), "0018A45C4D1DEF81644B54AB7F969B88D65");
+            if let Ok(range) = parse_range(&String::from_utf8_lossy(&body)) {
+                for hash in ["", "F3B", "F3BBB0018A45C4D1DEF81644B54AB7F969B88D65"] {
+                    let _ = crate::BreachResult::from_range(&range, hash);
+                }
+            }
+        }
+
+        #[test]
+        fn an_entry_before_the_garbage_is_still_found(
+            garbage in vec(hostile(), 0..12),
+            splits in vec(any::<usize>(), 0..8),
+        ) {
+            let mut body = b"0018A45C4D1DEF81644B54AB7F969B88D65:42\r\n".to_vec();
+            body.extend(garbage.concat());
+            prop_assert_eq!(
+                find_in(&body, &splits, "0018A45C4D1DEF81644B54AB7F969B88D65"),
+                Ok(Some(42))
+            );
+            // The whole body either parses or names the line that didn't.
+            match parse_range(&String::from_utf8_lossy(&body)) {
+                Ok(range) => prop_assert!(range.get("0018A45C4D1DEF81644B54AB7F969B88D65").is_some()),
+                Err(ParseError::MalformedLine { line_no, content }) => {
+                    prop_assert!(line_no > 1);
+                    prop_assert!(content.chars().count() <= 80);
+                }
+                Err(ParseError::Duplicate(_)) => {}
+                Err(ParseError::EmptyBody) => prop_assert!(false, "the body has an entry"),
+            }
+        }
+    }
+}
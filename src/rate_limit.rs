@@ -0,0 +1,187 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+/// A token bucket every lookup waits on before it sends a request, shared
+/// by every clone, so a batch, a watch and the window's own checks together
+/// stay under one rate however many run at once.
+///
+/// The bucket holds a second's worth of requests, so a short burst goes out
+/// at once and a long run settles at the rate. Below one a second it holds
+/// one, so requests are spaced evenly. Tokens are reserved rather
+/// than polled for: a request that finds the bucket empty takes the next
+/// token due and sleeps until then, so waiters go in the order they came.
+#[derive(Clone, Debug, Default)]
+pub struct RateLimiter(Arc<Mutex<Bucket>>);
+
+#[derive(Debug, Default)]
+struct Bucket {
+    /// Below zero when requests are waiting on tokens not yet refilled.
+    tokens: f64,
+    /// When `tokens` was last brought up to date; `None` for a bucket not
+    /// used yet, which is full. May be ahead of now after a [`pause`].
+    ///
+    /// [`pause`]: RateLimiter::pause
+    updated: Option<Instant>,
+    /// Until when the API asked for no more requests.
+    paused_until: Option<Instant>,
+}
+
+impl Bucket {
+    /// Takes a token at `now`, refilling at `rate` a second, and returns how
+    /// long to wait before it may be used.
+    fn reserve(&mut self, now: Instant, rate: f64) -> Duration {
+        let capacity = rate.max(1.0);
+        let tokens = match self.updated {
+            Some(updated) if updated > now => self.tokens,
+            Some(updated) => (self.tokens + (now - updated).as_secs_f64() * rate).min(capacity),
+            None => capacity,
+        };
+        let updated = self.updated.map_or(now, |updated| updated.max(now));
+        self.tokens = tokens - 1.0;
+        self.updated = Some(updated);
+        let due = updated + Duration::from_secs_f64((-self.tokens).max(0.0) / rate);
+        due.saturating_duration_since(now)
+    }
+
+    /// Empties the bucket and holds off refilling it until `now + wait`.
+    fn pause(&mut self, now: Instant, wait: Duration) {
+        let until = now + wait;
+        self.tokens = self.tokens.min(0.0);
+        self.updated = Some(self.updated.map_or(until, |updated| updated.max(until)));
+        self.paused_until = Some(self.paused_until.map_or(until, |paused| paused.max(until)));
+    }
+}
+
+impl RateLimiter {
+    /// Waits until a request may be sent at no more than `rate` a second.
+    /// A [`pause`](Self::pause) that begins while waiting is waited out too.
+    pub async fn acquire(&self, rate: u32) {
+        self.wait(f64::from(rate.max(1))).await;
+    }
+
+    /// Waits until a request may be sent at no more than `rpm` a minute, as
+    /// the HIBP API's subscriptions are limited.
+    pub async fn acquire_per_minute(&self, rpm: u32) {
+        self.wait(f64::from(rpm.max(1)) / 60.0).await;
+    }
+
+    async fn wait(&self, rate: f64) {
+        let wait = self.0.lock().unwrap().reserve(Instant::now(), rate);
+        tokio::time::sleep(wait).await;
+        loop {
+            let paused_until = self.0.lock().unwrap().paused_until;
+            match paused_until {
+                Some(until) if until > Instant::now() => tokio::time::sleep_until(until).await,
+                _ => break,
+            }
+        }
+    }
+
+    /// Holds every request back for `wait`, as a 429's `Retry-After` asks,
+    /// with the bucket drained so they don't all go at once when it ends.
+    pub fn pause(&self, wait: Duration) {
+        self.0.lock().unwrap().pause(Instant::now(), wait);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    /// The wait of each of `count` reservations at `now`.
+    fn reserve(bucket: &mut Bucket, now: Instant, rate: f64, count: usize) -> Vec<Duration> {
+        (0..count).map(|_| bucket.reserve(now, rate)).collect()
+    }
+
+    #[test]
+    fn a_full_bucket_lets_a_seconds_worth_go_at_once() {
+        let (mut bucket, now) = (Bucket::default(), Instant::now());
+        let waits = reserve(&mut bucket, now, 5.0, 7);
+        assert_eq!(waits[..5], [Duration::ZERO; 5]);
+        // Then each waits for the next token due, in turn.
+        assert_eq!(waits[5..], [ms(200), ms(400)]);
+    }
+
+    #[test]
+    fn requests_at_the_rate_never_wait() {
+        let (mut bucket, start) = (Bucket::default(), Instant::now());
+        reserve(&mut bucket, start, 5.0, 5);
+        for step in 1..=20 {
+            assert_eq!(bucket.reserve(start + ms(200 * step), 5.0), Duration::ZERO);
+        }
+        // Twice as fast, every other one waits half a token.
+        let now = start + ms(4000);
+        assert_eq!(bucket.reserve(now + ms(100), 5.0), ms(100));
+        assert_eq!(bucket.reserve(now + ms(200), 5.0), ms(200));
+    }
+
+    #[test]
+    fn an_idle_bucket_refills_to_no_more_than_a_second_worth() {
+        let (mut bucket, start) = (Bucket::default(), Instant::now());
+        reserve(&mut bucket, start, 5.0, 8);
+        // The three reserved ahead are paid back before anything refills.
+        assert_eq!(bucket.reserve(start + ms(600), 5.0), ms(200));
+        let later = start + Duration::from_secs(60);
+        let waits = reserve(&mut bucket, later, 5.0, 6);
+        assert_eq!(waits[..5], [Duration::ZERO; 5]);
+        assert_eq!(waits[5], ms(200));
+    }
+
+    #[test]
+    fn below_one_a_second_requests_are_spaced_evenly() {
+        let (mut bucket, now) = (Bucket::default(), Instant::now());
+        let rate = 6.0 / 60.0;
+        let waits = reserve(&mut bucket, now, rate, 3);
+        assert_eq!(
+            waits,
+            [
+                Duration::ZERO,
+                Duration::from_secs(10),
+                Duration::from_secs(20)
+            ]
+        );
+    }
+
+    #[test]
+    fn a_pause_drains_the_bucket_and_holds_off_refilling_it() {
+        let (mut bucket, start) = (Bucket::default(), Instant::now());
+        bucket.reserve(start, 5.0);
+        bucket.pause(start, Duration::from_secs(3));
+        assert_eq!(bucket.paused_until, Some(start + Duration::from_secs(3)));
+        assert_eq!(bucket.reserve(start + ms(1000), 5.0), ms(2200));
+        // A shorter pause doesn't cut a longer one short.
+        bucket.pause(start, Duration::from_secs(1));
+        assert_eq!(bucket.paused_until, Some(start + Duration::from_secs(3)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn waiters_are_let_through_at_the_rate_and_after_a_pause() {
+        let limiter = RateLimiter::default();
+        let start = Instant::now();
+        limiter.acquire(2).await;
+        limiter.acquire(2).await;
+        assert_eq!(start.elapsed(), Duration::ZERO);
+        limiter.acquire(2).await;
+        assert_eq!(start.elapsed(), ms(500));
+
+        limiter.pause(Duration::from_secs(10));
+        limiter.acquire(2).await;
+        assert!(start.elapsed() >= ms(10_500), "{:?}", start.elapsed());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn a_per_minute_rate_is_spread_over_the_minute() {
+        let limiter = RateLimiter::default();
+        let start = Instant::now();
+        for _ in 0..3 {
+            limiter.acquire_per_minute(10).await;
+        }
+        assert_eq!(start.elapsed(), Duration::from_secs(12));
+    }
+}
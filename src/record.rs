@@ -0,0 +1,139 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::severity::{Severity, Thresholds};
+use crate::{BreachResult, HashMode, LookupSource, hash_prefix_of};
+
+/// One finished password check, in the shape exports and the CLI write out.
+/// Only the hash prefix is kept, the same five characters a range query sends.
+///
+/// ```json
+/// {
+///   "prefix": "5BAA6",
+///   "algorithm": "sha1",
+///   "result": { "status": "found", "count": 52256179 },
+///   "severity": "critical",
+///   "checked_at": "2025-01-01T12:00:00Z",
+///   "source": { "type": "network" }
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckRecord {
+    pub prefix: String,
+    pub algorithm: HashMode,
+    pub result: BreachResult,
+    /// `None` when the password wasn't found.
+    pub severity: Option<Severity>,
+    pub checked_at: DateTime<Utc>,
+    pub source: LookupSource,
+}
+
+impl CheckRecord {
+    /// A record of `hash` having been checked just now. Only its prefix is
+    /// kept.
+    ///
+    /// ```
+    /// use cybersec_wow::record::CheckRecord;
+    /// use cybersec_wow::severity::{Severity, Thresholds};
+    /// use cybersec_wow::{BreachResult, HashMode, LookupSource};
+    ///
+    /// let record = CheckRecord::new(
+    ///     "F3BBBD66A63D4BF1747940578EC3D0103530E21D",
+    ///     HashMode::Sha1,
+    ///     BreachResult::Found { count: 17 },
+    ///     LookupSource::Network,
+    ///     &Thresholds::default(),
+    /// );
+    /// assert_eq!(record.prefix, "F3BBB");
+    /// assert_eq!(record.severity, Some(Severity::Moderate));
+    /// ```
+    pub fn new(
+        hash: &str,
+        algorithm: HashMode,
+        result: BreachResult,
+        source: LookupSource,
+        thresholds: &Thresholds,
+    ) -> Self {
+        Self {
+            prefix: hash_prefix_of(hash).to_owned(),
+            algorithm,
+            result,
+            severity: result.severity(thresholds),
+            checked_at: Utc::now(),
+            source,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Normalization, hash_password};
+
+    /// `record` as checked at a fixed time, so its JSON is stable.
+    fn at_noon(mut record: CheckRecord) -> CheckRecord {
+        record.checked_at = "2025-01-01T12:00:00Z".parse().unwrap();
+        record
+    }
+
+    fn found() -> CheckRecord {
+        let hash = hash_password("password", HashMode::Sha1, Normalization::Off);
+        let result = BreachResult::Found { count: 52256179 };
+        let record = CheckRecord::new(
+            &hash,
+            HashMode::Sha1,
+            result,
+            LookupSource::Network,
+            &Thresholds::default(),
+        );
+        at_noon(record)
+    }
+
+    fn not_found_offline() -> CheckRecord {
+        let hash = hash_password("password", HashMode::Ntlm, Normalization::Off);
+        let snapshot = LookupSource::Offline("2024-11-30".parse().unwrap());
+        let record = CheckRecord::new(
+            &hash,
+            HashMode::Ntlm,
+            BreachResult::NotFound,
+            snapshot,
+            &Thresholds::default(),
+        );
+        at_noon(record)
+    }
+
+    #[test]
+    fn only_the_prefix_is_kept() {
+        assert_eq!(found().prefix, "5BAA6");
+        assert_eq!(found().severity, Some(Severity::Critical));
+        assert_eq!(not_found_offline().severity, None);
+    }
+
+    #[test]
+    fn records_round_trip_through_json() {
+        for record in [found(), not_found_offline()] {
+            let json = serde_json::to_string(&record).unwrap();
+            assert_eq!(serde_json::from_str::<CheckRecord>(&json).unwrap(), record);
+        }
+    }
+
+    /// The shape external tools read; a change here breaks them.
+    #[test]
+    fn the_json_shape_is_stable() {
+        for (record, snapshot) in [
+            (
+                found(),
+                include_str!("../tests/fixtures/records/found.json"),
+            ),
+            (
+                not_found_offline(),
+                include_str!("../tests/fixtures/records/not-found-offline.json"),
+            ),
+        ] {
+            assert_eq!(
+                serde_json::to_string_pretty(&record).unwrap(),
+                snapshot.trim_end()
+            );
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use std::io;
+
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
+use zeroize::Zeroizing;
+
+/// Longest password read from a file or standard input, in bytes. Anything
+/// longer is much more likely a wrong file than a password.
+pub const MAX_RECORD: usize = 4096;
+
+/// Why a record wasn't read.
+#[derive(Debug)]
+pub enum RecordError {
+    /// It ran past [`MAX_RECORD`]; the rest of it, up to the next
+    /// delimiter, was skipped.
+    TooLong,
+    Io(io::Error),
+}
+
+/// Passwords read from `reader` a record at a time, as they're needed, each
+/// up to [`MAX_RECORD`] bytes. Records end at `delimiter`, which isn't part
+/// of them, or with no delimiter the whole input is one record.
+///
+/// The bytes go straight from the reader's buffer into one allocated at
+/// the most a record may be, so there are no copies left behind by a
+/// growing buffer, and each record is wiped when it's dropped. Bytes are
+/// only split at delimiters, so a character split across two reads comes
+/// out whole.
+pub struct Records<R> {
+    reader: R,
+    delimiter: Option<u8>,
+    done: bool,
+}
+
+impl<R: AsyncBufRead + Unpin> Records<R> {
+    pub fn new(reader: R, delimiter: Option<u8>) -> Self {
+        Self {
+            reader,
+            delimiter,
+            done: false,
+        }
+    }
+
+    /// The next record, or `None` once the input has ended. Input that ends
+    /// with a delimiter has no empty record after it; one between two
+    /// delimiters is returned as it is. After an I/O error there are no
+    /// more records.
+    pub async fn next(&mut self) -> Option<Result<Zeroizing<Vec<u8>>, RecordError>> {
+        if self.done {
+            return None;
+        }
+        let mut record = Zeroizing::new(Vec::with_capacity(MAX_RECORD));
+        let mut too_long = false;
+        let mut read_any = false;
+        loop {
+            let buffer = match self.reader.fill_buf().await {
+                Ok(buffer) => buffer,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => {
+                    self.done = true;
+                    return Some(Err(RecordError::Io(error)));
+                }
+            };
+            if buffer.is_empty() {
+                self.done = true;
+                return match (read_any, too_long) {
+                    (false, _) if self.delimiter.is_some() => None,
+                    (_, true) => Some(Err(RecordError::TooLong)),
+                    _ => Some(Ok(record)),
+                };
+            }
+            read_any = true;
+            let end = self
+                .delimiter
+                .and_then(|delimiter| buffer.iter().position(|&b| b == delimiter));
+            let taken = end.unwrap_or(buffer.len());
+            if !too_long {
+                if record.len() + taken > MAX_RECORD {
+                    too_long = true;
+                } else {
+                    record.extend_from_slice(&buffer[..taken]);
+                }
+            }
+            self.reader.consume(taken + usize::from(end.is_some()));
+            if end.is_some() {
+                return Some(if too_long {
+                    Err(RecordError::TooLong)
+                } else {
+                    Ok(record)
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    use tokio::io::{AsyncRead, BufReader, ReadBuf};
+
+    use super::*;
+
+    /// Every record of `input`, read through a buffer of `capacity` bytes.
+    async fn records(input: &[u8], capacity: usize, delimiter: Option<u8>) -> Vec<String> {
+        let mut records = Records::new(BufReader::with_capacity(capacity, input), delimiter);
+        let mut all = Vec::new();
+        while let Some(record) = records.next().await {
+            all.push(match record {
+                Ok(record) => String::from_utf8(record.to_vec()).unwrap(),
+                Err(RecordError::TooLong) => "<too long>".to_owned(),
+                Err(RecordError::Io(error)) => format!("<{error}>"),
+            });
+        }
+        all
+    }
+
+    #[tokio::test]
+    async fn records_end_at_nul_and_keep_line_endings() {
+        let input = b"hunter2\0pass\nword\0\0two\r\nlines\0caf\xC3\xA9\0";
+        for capacity in [1, 2, 3, 7, 64] {
+            assert_eq!(
+                records(input, capacity, Some(0)).await,
+                ["hunter2", "pass\nword", "", "two\r\nlines", "café"],
+                "capacity {capacity}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn a_character_split_across_reads_comes_out_whole() {
+        // "é" is two bytes and "🔑" four, so most buffer sizes split one.
+        let input = "é🔑é\n🔑é🔑".as_bytes();
+        for capacity in 1..=input.len() {
+            assert_eq!(
+                records(input, capacity, Some(b'\n')).await,
+                ["é🔑é", "🔑é🔑"],
+                "capacity {capacity}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn lines_are_split_at_newlines_leaving_any_carriage_return() {
+        assert_eq!(
+            records(b"hunter2\r\n\r\npassword\n", 4, Some(b'\n')).await,
+            ["hunter2\r", "\r", "password"]
+        );
+        // The last record needn't end with a delimiter.
+        assert_eq!(records(b"a\nb", 4, Some(b'\n')).await, ["a", "b"]);
+    }
+
+    #[tokio::test]
+    async fn empty_input_has_no_records_but_is_one_without_a_delimiter() {
+        assert!(records(b"", 4, Some(0)).await.is_empty());
+        assert_eq!(records(b"", 4, None).await, [""]);
+    }
+
+    #[tokio::test]
+    async fn without_a_delimiter_the_whole_input_is_one_record() {
+        assert_eq!(
+            records(b"one\ntwo\0three\r\n", 2, None).await,
+            ["one\ntwo\0three\r\n"]
+        );
+    }
+
+    #[tokio::test]
+    async fn a_record_too_long_is_skipped_to_the_next_delimiter() {
+        let most = "x".repeat(MAX_RECORD);
+        let input = format!("{most}\n{most}x\nhunter2\n");
+        assert_eq!(
+            records(input.as_bytes(), 1000, Some(b'\n')).await,
+            [most.as_str(), "<too long>", "hunter2"]
+        );
+        let input = format!("{most}y");
+        assert_eq!(records(input.as_bytes(), 1000, None).await, ["<too long>"]);
+        assert_eq!(
+            records(input.as_bytes(), 1000, Some(0)).await,
+            ["<too long>"]
+        );
+    }
+
+    /// A reader that's interrupted once, gives `first`, then fails.
+    struct Failing {
+        first: &'static [u8],
+        reads: usize,
+    }
+
+    impl AsyncRead for Failing {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<io::Result<()>> {
+            self.reads += 1;
+            Poll::Ready(match self.reads {
+                1 => Err(io::ErrorKind::Interrupted.into()),
+                2 => {
+                    buf.put_slice(self.first);
+                    Ok(())
+                }
+                _ => Err(io::Error::other("the pipe broke")),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn an_io_error_ends_the_records_after_reporting_it() {
+        let reader = Failing {
+            first: b"hunter2\npass",
+            reads: 0,
+        };
+        let mut records = Records::new(BufReader::new(reader), Some(b'\n'));
+        assert_eq!(&**records.next().await.unwrap().unwrap(), b"hunter2");
+        let error = records.next().await.unwrap().unwrap_err();
+        assert!(matches!(error, RecordError::Io(error) if error.to_string() == "the pipe broke"));
+        assert!(records.next().await.is_none());
+    }
+}
@@ -0,0 +1,417 @@
+use std::borrow::Cow;
+use std::fmt;
+
+use rustyline::completion::Completer;
+use rustyline::highlight::{CmdKind, Highlighter};
+use rustyline::hint::Hinter;
+use rustyline::history::History;
+use rustyline::validate::Validator;
+use unicode_width::UnicodeWidthStr;
+
+use cybersec_wow::BreachResult;
+use cybersec_wow::HashMode;
+use cybersec_wow::record::CheckRecord;
+use cybersec_wow::summary::{CountStyle, format_count};
+
+/// Clears the screen and the scrollback above it, for `:clear`.
+pub const CLEAR: &str = "\x1b[H\x1b[2J\x1b[3J";
+
+const RED: &str = "\x1b[1;31m";
+const GREEN: &str = "\x1b[32m";
+const YELLOW: &str = "\x1b[33m";
+const RESET: &str = "\x1b[0m";
+
+pub const HELP: &str = "\
+Type a password to check it; what you type isn't shown.
+  :mode sha1|ntlm   check by SHA-1 or by NTLM hash
+  :padding on|off   ask for padded responses or not
+  :check            check a password that begins with a colon
+  :stats            what this session has checked and how the lookups went
+  :clear            clear the screen and its scrollback
+  :help             this list
+  :quit             leave, as Ctrl+D does";
+
+/// A command typed at the prompt, as a colon and its name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Command {
+    Mode(HashMode),
+    Padding(bool),
+    /// Reads the next line as a password whatever it begins with.
+    Check,
+    Stats,
+    Clear,
+    Help,
+    Quit,
+}
+
+/// What a line typed at the prompt is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Line<'a> {
+    Blank,
+    Password(&'a str),
+    Command(Command),
+}
+
+/// Reads a line from the prompt: a command if it begins with a colon,
+/// otherwise a password, taken as typed, spaces and all.
+pub fn parse(line: &str) -> Result<Line<'_>, String> {
+    if line.trim().is_empty() {
+        return Ok(Line::Blank);
+    }
+    let Some(command) = line.strip_prefix(':') else {
+        return Ok(Line::Password(line));
+    };
+    let mut words = command.split_whitespace();
+    let name = words.next().unwrap_or_default();
+    let argument = words.next();
+    if words.next().is_some() {
+        return Err(format!(":{name} takes one word at most"));
+    }
+    let command = match (name, argument) {
+        ("mode", Some(mode)) => Command::Mode(match mode.to_ascii_lowercase().as_str() {
+            "sha1" | "sha-1" => HashMode::Sha1,
+            "ntlm" => HashMode::Ntlm,
+            _ => return Err(format!("{mode:?} isn't a mode: sha1 or ntlm")),
+        }),
+        ("mode", None) => return Err(":mode takes sha1 or ntlm".to_owned()),
+        ("padding", Some(padding)) => Command::Padding(match padding {
+            "on" => true,
+            "off" => false,
+            _ => return Err(format!("{padding:?} isn't on or off")),
+        }),
+        ("padding", None) => return Err(":padding takes on or off".to_owned()),
+        (name, Some(_)) if ["check", "stats", "clear", "help", "quit", "q"].contains(&name) => {
+            return Err(format!(":{name} takes nothing after it"));
+        }
+        ("check", None) => Command::Check,
+        ("stats", None) => Command::Stats,
+        ("clear", None) => Command::Clear,
+        ("help", None) => Command::Help,
+        ("quit" | "q", None) => Command::Quit,
+        ("", _) => return Err("a colon begins a command; :help lists them".to_owned()),
+        (name, _) => return Err(format!("there's no :{name} command; :help lists them")),
+    };
+    Ok(Line::Command(command))
+}
+
+/// Keeps `line` in the prompt's history if it's a command. Nothing else
+/// typed is kept: not a password, nor a line that was meant as a command
+/// but isn't one, which may well be a password too.
+pub fn remember(history: &mut impl History, line: &str) -> rustyline::Result<()> {
+    if let Ok(Line::Command(_)) = parse(line) {
+        history.add(line)?;
+    }
+    Ok(())
+}
+
+/// What the passwords of a session have come to so far.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Tally {
+    pub checked: usize,
+    pub found: usize,
+    pub failed: usize,
+}
+
+impl fmt::Display for Tally {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} checked: {} breached, {} clean, {} errors",
+            self.checked,
+            self.found,
+            self.checked - self.found - self.failed,
+            self.failed
+        )
+    }
+}
+
+/// How passwords are checked at the prompt, starting from the settings and
+/// changed by commands for as long as it's open.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Session {
+    pub mode: HashMode,
+    pub padding: bool,
+    pub tally: Tally,
+}
+
+impl Session {
+    pub fn new(padding: bool) -> Self {
+        Self {
+            mode: HashMode::Sha1,
+            padding,
+            tally: Tally::default(),
+        }
+    }
+
+    /// Applies a command that changes a setting and says what it's set to
+    /// now, or returns `None` for one that doesn't.
+    pub fn apply(&mut self, command: Command) -> Option<String> {
+        match command {
+            Command::Mode(mode) => {
+                self.mode = mode;
+                Some(format!("checking by {mode} hash"))
+            }
+            Command::Padding(padding) => {
+                self.padding = padding;
+                Some(format!("padding {}", if padding { "on" } else { "off" }))
+            }
+            Command::Check | Command::Stats | Command::Clear | Command::Help | Command::Quit => {
+                None
+            }
+        }
+    }
+
+    /// Counts a password's result.
+    pub fn record(&mut self, result: &Result<CheckRecord, String>) {
+        self.tally.checked += 1;
+        match result {
+            Ok(record) if matches!(record.result, BreachResult::Found { .. }) => {
+                self.tally.found += 1;
+            }
+            Ok(_) => {}
+            Err(_) => self.tally.failed += 1,
+        }
+    }
+
+    pub fn prompt(&self) -> String {
+        match self.mode {
+            HashMode::Sha1 => "password> ".to_owned(),
+            HashMode::Ntlm => "password (NTLM)> ".to_owned(),
+        }
+    }
+}
+
+/// A result as a line at the prompt, in red, green or yellow if `color`.
+pub fn verdict(result: &Result<CheckRecord, String>, color: bool) -> String {
+    let (color_code, verdict) = match result {
+        Ok(record) => match record.result {
+            BreachResult::Found { count } => (
+                RED,
+                format!("found {} times", format_count(count, CountStyle::Full, ',')),
+            ),
+            BreachResult::NotFound => (GREEN, "not found".to_owned()),
+        },
+        Err(error) => (YELLOW, format!("error: {error}")),
+    };
+    if color {
+        format!("{color_code}{verdict}{RESET}")
+    } else {
+        verdict
+    }
+}
+
+/// Keeps what's typed at the prompt off the screen, as `read -s` does: it's
+/// drawn as blanks, with the cursor hidden so they don't give away its
+/// length. A command is drawn as typed, unless `hide_commands` is set for
+/// `:check`'s line.
+#[derive(Debug, Default)]
+pub struct Masking {
+    pub hide_commands: bool,
+}
+
+impl Highlighter for Masking {
+    fn highlight<'l>(&self, line: &'l str, _pos: usize) -> Cow<'l, str> {
+        if !self.hide_commands && line.starts_with(':') {
+            Cow::Borrowed(line)
+        } else {
+            Cow::Owned(" ".repeat(line.width()))
+        }
+    }
+
+    fn highlight_char(&self, _line: &str, _pos: usize, kind: CmdKind) -> bool {
+        kind != CmdKind::MoveCursor
+    }
+}
+
+impl Completer for Masking {
+    type Candidate = String;
+}
+
+impl Hinter for Masking {
+    type Hint = String;
+}
+
+impl Validator for Masking {}
+
+impl rustyline::Helper for Masking {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cybersec_wow::LookupSource;
+    use cybersec_wow::severity::Thresholds;
+    use rustyline::history::{DefaultHistory, SearchDirection};
+
+    fn checked(result: BreachResult) -> Result<CheckRecord, String> {
+        Ok(CheckRecord::new(
+            "F3BBBD66A63D4BF1747940578EC3D0103530E21D",
+            HashMode::Sha1,
+            result,
+            LookupSource::Network,
+            &Thresholds::default(),
+        ))
+    }
+
+    #[test]
+    fn parses_passwords_as_typed() {
+        assert_eq!(parse(""), Ok(Line::Blank));
+        assert_eq!(parse("  \t"), Ok(Line::Blank));
+        assert_eq!(parse("hunter2"), Ok(Line::Password("hunter2")));
+        assert_eq!(
+            parse(" correct horse "),
+            Ok(Line::Password(" correct horse "))
+        );
+        assert_eq!(parse("mode: ntlm"), Ok(Line::Password("mode: ntlm")));
+    }
+
+    #[test]
+    fn parses_every_command() {
+        for (line, command) in [
+            (":mode sha1", Command::Mode(HashMode::Sha1)),
+            (":mode SHA-1", Command::Mode(HashMode::Sha1)),
+            (":mode NTLM", Command::Mode(HashMode::Ntlm)),
+            (":padding on", Command::Padding(true)),
+            (":padding off", Command::Padding(false)),
+            (":check", Command::Check),
+            (":stats", Command::Stats),
+            (":clear", Command::Clear),
+            (":help", Command::Help),
+            (":quit", Command::Quit),
+            (":q", Command::Quit),
+            (":  mode   ntlm ", Command::Mode(HashMode::Ntlm)),
+        ] {
+            assert_eq!(parse(line), Ok(Line::Command(command)), "{line}");
+        }
+    }
+
+    #[test]
+    fn says_what_is_wrong_with_a_command() {
+        for (line, error) in [
+            (":", "a colon begins a command; :help lists them"),
+            (":  ", "a colon begins a command; :help lists them"),
+            (":exit", "there's no :exit command; :help lists them"),
+            (":mode", ":mode takes sha1 or ntlm"),
+            (":mode md5", "\"md5\" isn't a mode: sha1 or ntlm"),
+            (":mode sha1 ntlm", ":mode takes one word at most"),
+            (":padding", ":padding takes on or off"),
+            (":padding yes", "\"yes\" isn't on or off"),
+            (":help mode", ":help takes nothing after it"),
+            (":q now", ":q takes nothing after it"),
+        ] {
+            assert_eq!(parse(line), Err(error.to_owned()), "{line}");
+        }
+    }
+
+    #[test]
+    fn history_keeps_commands_and_never_passwords() {
+        let mut history = DefaultHistory::new();
+        for line in [
+            "hunter2",
+            ":mode ntlm",
+            "correct horse battery staple",
+            ":check",
+            ":hunter2",
+            ":padding maybe",
+            "",
+            ":stats",
+        ] {
+            remember(&mut history, line).unwrap();
+        }
+        let kept: Vec<_> = (0..history.len())
+            .map(|index| {
+                let found = history.get(index, SearchDirection::Forward).unwrap();
+                found.unwrap().entry.into_owned()
+            })
+            .collect();
+        assert_eq!(kept, [":mode ntlm", ":check", ":stats"]);
+    }
+
+    #[test]
+    fn commands_change_the_session() {
+        let mut session = Session::new(true);
+        assert_eq!(session.prompt(), "password> ");
+        assert_eq!(
+            session.apply(Command::Mode(HashMode::Ntlm)).as_deref(),
+            Some("checking by NTLM hash")
+        );
+        assert_eq!(session.mode, HashMode::Ntlm);
+        assert_eq!(session.prompt(), "password (NTLM)> ");
+        assert_eq!(
+            session.apply(Command::Padding(false)).as_deref(),
+            Some("padding off")
+        );
+        assert!(!session.padding);
+        let before = session;
+        for command in [
+            Command::Check,
+            Command::Stats,
+            Command::Clear,
+            Command::Help,
+            Command::Quit,
+        ] {
+            assert_eq!(session.apply(command), None);
+        }
+        assert_eq!(session, before);
+        assert_eq!(
+            session.apply(Command::Mode(HashMode::Sha1)).as_deref(),
+            Some("checking by SHA-1 hash")
+        );
+        assert_eq!(session.prompt(), "password> ");
+    }
+
+    #[test]
+    fn tallies_what_was_checked() {
+        let mut session = Session::new(false);
+        assert_eq!(
+            session.tally.to_string(),
+            "0 checked: 0 breached, 0 clean, 0 errors"
+        );
+        session.record(&checked(BreachResult::Found { count: 3 }));
+        session.record(&checked(BreachResult::NotFound));
+        session.record(&checked(BreachResult::NotFound));
+        session.record(&Err("timed out".to_owned()));
+        assert_eq!(
+            session.tally,
+            Tally {
+                checked: 4,
+                found: 1,
+                failed: 1
+            }
+        );
+        assert_eq!(
+            session.tally.to_string(),
+            "4 checked: 1 breached, 2 clean, 1 errors"
+        );
+    }
+
+    #[test]
+    fn verdicts_are_colored_only_when_asked() {
+        let found = checked(BreachResult::Found { count: 17206891 });
+        assert_eq!(verdict(&found, false), "found 17,206,891 times");
+        assert_eq!(
+            verdict(&found, true),
+            "\x1b[1;31mfound 17,206,891 times\x1b[0m"
+        );
+        let clean = checked(BreachResult::NotFound);
+        assert_eq!(verdict(&clean, false), "not found");
+        assert_eq!(verdict(&clean, true), "\x1b[32mnot found\x1b[0m");
+        let failed = Err("timed out".to_owned());
+        assert_eq!(verdict(&failed, false), "error: timed out");
+        assert_eq!(verdict(&failed, true), "\x1b[33merror: timed out\x1b[0m");
+    }
+
+    #[test]
+    fn masking_blanks_passwords_but_not_commands() {
+        let masking = Masking::default();
+        assert_eq!(masking.highlight("hunter2", 7), "       ");
+        assert_eq!(masking.highlight("pässwörd", 8), "        ");
+        assert_eq!(masking.highlight("密码", 2), "    ");
+        assert_eq!(masking.highlight(":mode ntlm", 10), ":mode ntlm");
+        let hiding = Masking {
+            hide_commands: true,
+        };
+        assert_eq!(hiding.highlight(":hunter2", 8), "        ");
+        assert!(masking.highlight_char("hunter2", 7, CmdKind::Other));
+        assert!(!masking.highlight_char("hunter2", 3, CmdKind::MoveCursor));
+    }
+}
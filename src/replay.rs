@@ -0,0 +1,466 @@
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use futures::future::{BoxFuture, FutureExt};
+use reqwest::{Client, Method, Request, Response};
+use url::Url;
+
+use crate::error::{FailureKind, LookupError};
+use crate::fetch::Fetch;
+use crate::pwned::Transport;
+
+/// Set to `1` to record missing cassettes from the real API instead of
+/// failing. Only for refreshing fixtures by hand; tests replay.
+pub const RECORD_VAR: &str = "PWCHECK_RECORD_FIXTURES";
+
+/// Headers that change from one response to the next, say which edge
+/// server answered or only concern the connection, and so are never
+/// recorded.
+const VOLATILE_HEADERS: &[&str] = &[
+    "age",
+    "alt-svc",
+    "cf-cache-status",
+    "cf-ray",
+    "connection",
+    "date",
+    "expires",
+    "keep-alive",
+    "last-modified",
+    "nel",
+    "report-to",
+    "server",
+    "server-timing",
+    "set-cookie",
+    "transfer-encoding",
+    "x-request-id",
+];
+
+/// A recorded response to one range request:
+///
+/// ```text
+/// GET /range/F3BBB
+/// status: 200
+/// content-type: text/plain
+///
+/// 000B535E3AF3369DF880DC26F260530B65C:3
+/// ...
+/// ```
+///
+/// The request line is checked on replay, so a cassette copied or renamed
+/// by hand can't answer for the wrong range.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cassette {
+    /// The path and query that were requested.
+    pub request: String,
+    pub status: u16,
+    /// Lowercase names, in the order they came; never a volatile one.
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Cassette {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let (head, body) = text
+            .split_once("\n\n")
+            .ok_or("no blank line between the headers and the body")?;
+        let mut lines = head.lines();
+        let request = lines
+            .next()
+            .and_then(|line| line.strip_prefix("GET "))
+            .ok_or("the first line isn't a GET request")?
+            .to_owned();
+        let status = lines
+            .next()
+            .and_then(|line| line.strip_prefix("status: "))
+            .and_then(|status| status.parse().ok())
+            .ok_or("the second line isn't a status")?;
+        let headers = lines
+            .map(|line| {
+                line.split_once(": ")
+                    .map(|(name, value)| (name.to_owned(), value.to_owned()))
+                    .ok_or_else(|| format!("{line:?} isn't a header"))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            request,
+            status,
+            headers,
+            body: body.to_owned(),
+        })
+    }
+}
+
+impl std::fmt::Display for Cassette {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "GET {}", self.request)?;
+        writeln!(f, "status: {}", self.status)?;
+        for (name, value) in &self.headers {
+            writeln!(f, "{name}: {value}")?;
+        }
+        write!(f, "\n{}", self.body)
+    }
+}
+
+/// The path and query of `url`, as a cassette's request line has them.
+fn request_of(url: &Url) -> String {
+    let mut request = url.path().to_owned();
+    if let Some(query) = url.query() {
+        let _ = write!(request, "?{query}");
+    }
+    request
+}
+
+/// Serves range requests from cassettes in a directory, one per range
+/// (`F3BBB.cassette`, or `F3BBB-ntlm.cassette` for NTLM), so lookups run
+/// offline and the same every time. A request with no cassette is an
+/// error naming the file it wanted, unless [`RECORD_VAR`] is set, in which
+/// case the real response is fetched and saved.
+///
+/// It answers plain [`Fetch`] lookups and, as a [`Transport`], a
+/// [`RangeClient`](crate::pwned::RangeClient)'s requests, so batches and
+/// the disk cache replay too.
+#[derive(Clone, Debug)]
+pub struct Replay {
+    dir: PathBuf,
+    /// Set when recording.
+    http: Option<Client>,
+}
+
+impl Replay {
+    /// Replays from `dir`, or records into it if [`RECORD_VAR`] is `1`.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        let record = std::env::var(RECORD_VAR).is_ok_and(|value| value == "1");
+        Self {
+            dir: dir.into(),
+            http: record.then(Client::new),
+        }
+    }
+
+    /// Records every request into `dir` that has no cassette there yet,
+    /// whatever [`RECORD_VAR`] says.
+    pub fn recording(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            http: Some(Client::new()),
+        }
+    }
+
+    /// The cassette file for a request to `url`.
+    pub fn path(&self, url: &Url) -> PathBuf {
+        let range = url.path().rsplit('/').next().unwrap_or_default();
+        let name = match url.query_pairs().find(|(key, _)| key == "mode") {
+            Some((_, mode)) => format!("{range}-{mode}.cassette"),
+            None => format!("{range}.cassette"),
+        };
+        self.dir.join(name)
+    }
+
+    fn load(&self, path: &Path, url: &Url) -> Result<Option<Cassette>, LookupError> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => {
+                return Err(LookupError::other(format!("{}: {error}", path.display())));
+            }
+        };
+        let cassette = Cassette::parse(&text)
+            .map_err(|error| LookupError::other(format!("{}: {error}", path.display())))?;
+        let request = request_of(url);
+        if cassette.request != request {
+            return Err(LookupError::other(format!(
+                "{} was recorded for GET {}, not GET {request}",
+                path.display(),
+                cassette.request
+            )));
+        }
+        Ok(Some(cassette))
+    }
+
+    async fn record(
+        &self,
+        http: &Client,
+        path: &Path,
+        request: Request,
+    ) -> Result<Cassette, LookupError> {
+        let network = |error: reqwest::Error| LookupError {
+            kind: FailureKind::Network,
+            ..LookupError::other(error.to_string())
+        };
+        let sent = request_of(request.url());
+        let response = http.execute(request).await.map_err(network)?;
+        let status = response.status().as_u16();
+        let headers = response
+            .headers()
+            .iter()
+            .map(|(name, value)| (name.as_str(), value))
+            .filter(|(name, _)| !VOLATILE_HEADERS.contains(name))
+            .filter_map(|(name, value)| Some((name.to_owned(), value.to_str().ok()?.to_owned())))
+            .collect();
+        let body = response.text().await.map_err(network)?;
+        let cassette = Cassette {
+            request: sent,
+            status,
+            headers,
+            body,
+        };
+        let saved =
+            fs::create_dir_all(&self.dir).and_then(|()| fs::write(path, cassette.to_string()));
+        saved.map_err(|error| LookupError::other(format!("{}: {error}", path.display())))?;
+        tracing::debug!(path = %path.display(), "recorded cassette");
+        Ok(cassette)
+    }
+
+    /// The cassette answering `request`, recorded now if it's missing and
+    /// recording is on.
+    async fn cassette(&self, request: Request) -> Result<Cassette, LookupError> {
+        let url = request.url().clone();
+        let path = self.path(&url);
+        match (self.load(&path, &url)?, &self.http) {
+            (Some(cassette), _) => Ok(cassette),
+            (None, Some(http)) => self.record(http, &path, request).await,
+            (None, None) => Err(LookupError::other(format!(
+                "no cassette for GET {} at {}; set {RECORD_VAR}=1 to record one",
+                request_of(&url),
+                path.display()
+            ))),
+        }
+    }
+}
+
+impl Fetch for Replay {
+    async fn text(&self, url: Url) -> Result<String, LookupError> {
+        let cassette = self.cassette(Request::new(Method::GET, url)).await?;
+        if !(200..300).contains(&cassette.status) {
+            return Err(LookupError {
+                kind: FailureKind::UnexpectedStatus(cassette.status),
+                ..LookupError::other(format!("the API answered {}", cassette.status))
+            });
+        }
+        Ok(cassette.body)
+    }
+}
+
+/// The recorded response, whatever its status: telling a 429 or a 503 from
+/// a range is the [`RangeClient`](crate::pwned::RangeClient)'s job, as it
+/// is over the network.
+impl Transport for Replay {
+    fn send(&self, request: Request) -> BoxFuture<'_, Result<Response, LookupError>> {
+        async move {
+            let cassette = self.cassette(request).await?;
+            let mut response = http::Response::builder().status(cassette.status);
+            for (name, value) in &cassette.headers {
+                response = response.header(name, value);
+            }
+            let response = response
+                .body(cassette.body)
+                .map_err(|error| LookupError::other(format!("unreplayable cassette: {error}")))?;
+            Ok(Response::from(response))
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures::StreamExt;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+    use crate::batch::check_all;
+    use crate::cache::DiskCache;
+    use crate::metrics::Metrics;
+    use crate::pwned::{self, InFlight, ProxySettings, RangeClient, SocksSettings};
+    use crate::rate_limit::RateLimiter;
+    use crate::{BreachResult, HashMode, Normalization, hash_password};
+
+    const ENDPOINT: &str = "https://api.pwnedpasswords.com";
+
+    fn cassettes() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/cassettes")
+    }
+
+    /// A client for `endpoint` that sends nothing over the network, only
+    /// through `replay`.
+    fn client(endpoint: &str, replay: Replay, metrics: Metrics) -> RangeClient {
+        let settings = pwned::Settings {
+            endpoint: pwned::parse_endpoint(endpoint).unwrap(),
+            padding: false,
+            decoys: false,
+            decoy_count: 0,
+            timeout: Duration::from_secs(5),
+            proxy: ProxySettings::default(),
+            socks: SocksSettings::default(),
+            rate_limit: 1000,
+        };
+        let http = pwned::build_client(&settings).unwrap();
+        RangeClient::new(
+            http,
+            settings,
+            InFlight::default(),
+            metrics,
+            RateLimiter::default(),
+        )
+        .with_transport(replay)
+    }
+
+    fn hunter2() -> String {
+        hash_password("hunter2", HashMode::Sha1, Normalization::Off)
+    }
+
+    #[test]
+    fn a_cassette_round_trips() {
+        let text = fs::read_to_string(cassettes().join("F3BBB.cassette")).unwrap();
+        let cassette = Cassette::parse(&text).unwrap();
+        assert_eq!(cassette.request, "/range/F3BBB");
+        assert_eq!(cassette.status, 200);
+        assert_eq!(cassette.to_string(), text);
+    }
+
+    #[test]
+    fn cassettes_are_named_by_range_and_mode() {
+        let replay = Replay::new("cassettes");
+        let url = Url::parse("https://example.com/range/F3BBB?mode=ntlm").unwrap();
+        assert_eq!(
+            replay.path(&url),
+            Path::new("cassettes/F3BBB-ntlm.cassette")
+        );
+        let url = Url::parse("https://example.com/range/F3BBB").unwrap();
+        assert_eq!(replay.path(&url), Path::new("cassettes/F3BBB.cassette"));
+    }
+
+    #[tokio::test]
+    async fn a_lookup_is_answered_from_its_cassette() {
+        let url = Url::parse(&format!("{ENDPOINT}/range/F3BBB")).unwrap();
+        let body = Replay::new(cassettes()).text(url).await.unwrap();
+        assert!(body.contains("D66A63D4BF1747940578EC3D0103530E21D:17206891"));
+    }
+
+    #[tokio::test]
+    async fn a_search_is_replayed_through_the_range_client() {
+        let client = client(ENDPOINT, Replay::new(cassettes()), Metrics::default());
+        let result = client.search(hunter2(), HashMode::Sha1).await.unwrap();
+        assert_eq!(result, BreachResult::Found { count: 17206891 });
+    }
+
+    #[tokio::test]
+    async fn a_batch_and_its_disk_cache_are_replayed() {
+        let dir = tempfile::tempdir().unwrap();
+        let endpoint = pwned::parse_endpoint(ENDPOINT).unwrap();
+        let disk_cache =
+            DiskCache::in_dir(dir.path().to_owned(), &endpoint, Duration::from_secs(60));
+        let not_found = format!("F3BBB{:035X}", 0);
+        let hashes = vec![hunter2(), not_found];
+        let metrics = Metrics::default();
+        // The second run has no cassettes at all, so anything it sent
+        // would fail.
+        let empty = tempfile::tempdir().unwrap();
+        for replay in [Replay::new(cassettes()), Replay::new(empty.path())] {
+            let items: Vec<_> = check_all(
+                client(ENDPOINT, replay, metrics.clone()),
+                hashes.clone(),
+                HashMode::Sha1,
+                Some(disk_cache.clone()),
+                4,
+                Vec::new(),
+                None,
+            )
+            .collect()
+            .await;
+            let mut outcomes: Vec<_> = items.into_iter().map(|(item, _)| item.outcome).collect();
+            outcomes.sort();
+            assert_eq!(outcomes, [Ok(None), Ok(Some(17206891))]);
+        }
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.requests, 1);
+        assert_eq!(snapshot.cache_hits, 1);
+    }
+
+    #[tokio::test]
+    async fn a_missing_cassette_names_the_file_and_how_to_record_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let client = client(ENDPOINT, Replay::new(dir.path()), Metrics::default());
+        let error = client.search(hunter2(), HashMode::Sha1).await.unwrap_err();
+        assert_eq!(error.kind, FailureKind::Other);
+        let wanted = dir.path().join("F3BBB.cassette");
+        assert!(error.message.contains(&wanted.display().to_string()));
+        assert!(error.message.contains(RECORD_VAR));
+        assert!(!wanted.exists());
+    }
+
+    #[tokio::test]
+    async fn a_cassette_recorded_for_another_range_is_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::copy(
+            cassettes().join("F3BBB.cassette"),
+            dir.path().join("AAAAA.cassette"),
+        )
+        .unwrap();
+        let url = Url::parse(&format!("{ENDPOINT}/range/AAAAA")).unwrap();
+        let error = Replay::new(dir.path()).text(url).await.unwrap_err();
+        assert!(
+            error
+                .message
+                .ends_with("was recorded for GET /range/F3BBB, not GET /range/AAAAA"),
+            "{}",
+            error.message
+        );
+    }
+
+    #[tokio::test]
+    async fn a_recorded_error_status_is_classified_by_the_range_client() {
+        let dir = tempfile::tempdir().unwrap();
+        let cassette = Cassette {
+            request: "/range/F3BBB".to_owned(),
+            status: 404,
+            headers: Vec::new(),
+            body: String::new(),
+        };
+        fs::write(dir.path().join("F3BBB.cassette"), cassette.to_string()).unwrap();
+        let client = client(ENDPOINT, Replay::new(dir.path()), Metrics::default());
+        let error = client.search(hunter2(), HashMode::Sha1).await.unwrap_err();
+        assert_eq!(error.kind, FailureKind::UnexpectedStatus(404));
+        assert_eq!(error.details.status, Some(404));
+    }
+
+    #[tokio::test]
+    async fn a_recorded_response_replays_without_the_server() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/range/F3BBB"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("server", "mock")
+                    .set_body_string("D66A63D4BF1747940578EC3D0103530E21D:5\r\n"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        let dir = tempfile::tempdir().unwrap();
+        let recording = client(
+            &server.uri(),
+            Replay::recording(dir.path()),
+            Metrics::default(),
+        );
+        let recorded = recording.search(hunter2(), HashMode::Sha1).await.unwrap();
+        let endpoint = server.uri();
+        drop(server);
+
+        let text = fs::read_to_string(dir.path().join("F3BBB.cassette")).unwrap();
+        let cassette = Cassette::parse(&text).unwrap();
+        assert!(cassette.headers.iter().all(|(name, _)| name != "server"));
+        let replaying = client(&endpoint, Replay::new(dir.path()), Metrics::default());
+        let replayed = replaying.search(hunter2(), HashMode::Sha1).await.unwrap();
+        assert_eq!(
+            (recorded, replayed),
+            (
+                BreachResult::Found { count: 5 },
+                BreachResult::Found { count: 5 }
+            )
+        );
+    }
+}
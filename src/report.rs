@@ -0,0 +1,447 @@
+use std::fmt::Write as _;
+use std::io;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Serialize;
+
+use crate::export::{self, BatchSummary, Row};
+use crate::record::CheckRecord;
+use crate::summary::{Audit, CountStyle, format_count};
+use crate::{BreachResult, HashMode, Normalization};
+
+/// Which file the session report is written as.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Html,
+}
+
+/// The settings that change what a check finds, or where it asks.
+#[derive(Debug, Serialize)]
+pub struct Settings {
+    pub algorithm: HashMode,
+    pub normalization: Normalization,
+    pub padding: bool,
+    pub endpoint: String,
+}
+
+/// A check from the session history, with its label if it was given one.
+#[derive(Debug, Serialize)]
+pub struct Check {
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub label: String,
+    #[serde(flatten)]
+    pub record: CheckRecord,
+}
+
+/// Totals over the single checks and the batch together.
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+pub struct Totals {
+    pub checked: usize,
+    pub breached: usize,
+    pub clean: usize,
+    pub failed: usize,
+    pub worst_count: Option<u64>,
+}
+
+impl Totals {
+    fn add(&mut self, outcome: Result<Option<u64>, ()>) {
+        self.checked += 1;
+        match outcome {
+            Ok(Some(count)) => {
+                self.breached += 1;
+                self.worst_count = self.worst_count.max(Some(count));
+            }
+            Ok(None) => self.clean += 1,
+            Err(()) => self.failed += 1,
+        }
+    }
+}
+
+/// Everything checked this run, for one file at the end of an audit.
+/// Passwords only ever appear masked: a batch's plaintext is dropped here
+/// even if the batch export would include it.
+#[derive(Debug)]
+pub struct SessionReport {
+    pub started_at: DateTime<Utc>,
+    pub exported_at: DateTime<Utc>,
+    pub settings: Settings,
+    pub totals: Totals,
+    /// Oldest first.
+    pub checks: Vec<Check>,
+    pub batch: Option<(BatchSummary, Vec<Row>)>,
+}
+
+impl SessionReport {
+    pub fn new(
+        started_at: DateTime<Utc>,
+        settings: Settings,
+        checks: Vec<Check>,
+        mut batch: Option<(BatchSummary, Vec<Row>)>,
+    ) -> Self {
+        let mut totals = Totals::default();
+        for check in &checks {
+            totals.add(Ok(match check.record.result {
+                BreachResult::Found { count } => Some(count),
+                BreachResult::NotFound => None,
+            }));
+        }
+        if let Some((_, rows)) = &mut batch {
+            for row in rows {
+                row.plaintext = None;
+                totals.add(row.outcome.clone().map_err(drop));
+            }
+        }
+        Self {
+            started_at,
+            exported_at: Utc::now(),
+            settings,
+            totals,
+            checks,
+            batch,
+        }
+    }
+}
+
+/// Writes the report as pretty-printed JSON:
+///
+/// ```json
+/// {
+///   "started_at": "2025-01-01T12:00:00Z",
+///   "exported_at": "2025-01-01T12:30:00Z",
+///   "app_version": "0.1.0",
+///   "settings": { "algorithm": "sha1", "normalization": "none", "padding": true, "endpoint": "..." },
+///   "totals": { "checked": 3, "breached": 1, "clean": 2, "failed": 0, "worst_count": 52256179 },
+///   "checks": [{ "label": "work", "prefix": "5BAA6", ... }],
+///   "batch": { "summary": { ... }, "audit": { ... }, "results": [{ "line": 1, "preview": "p*******", ... }] }
+/// }
+/// ```
+pub fn write_json(writer: impl io::Write, report: &SessionReport) -> serde_json::Result<()> {
+    #[derive(Serialize)]
+    struct Batch<'a> {
+        summary: &'a BatchSummary,
+        audit: Audit,
+        results: Vec<export::Entry<'a>>,
+    }
+
+    #[derive(Serialize)]
+    struct Report<'a> {
+        started_at: DateTime<Utc>,
+        exported_at: DateTime<Utc>,
+        app_version: &'static str,
+        settings: &'a Settings,
+        totals: &'a Totals,
+        checks: &'a [Check],
+        #[serde(skip_serializing_if = "Option::is_none")]
+        batch: Option<Batch<'a>>,
+    }
+
+    let report = Report {
+        started_at: report.started_at,
+        exported_at: report.exported_at,
+        app_version: env!("CARGO_PKG_VERSION"),
+        settings: &report.settings,
+        totals: &report.totals,
+        checks: &report.checks,
+        batch: report.batch.as_ref().map(|(summary, rows)| Batch {
+            summary,
+            audit: Audit::new(rows),
+            results: rows.iter().map(export::Entry::from).collect(),
+        }),
+    };
+    serde_json::to_writer_pretty(writer, &report)
+}
+
+/// Writes the report as a standalone HTML page, with counts grouped by
+/// `separator`.
+pub fn write_html(
+    mut writer: impl io::Write,
+    report: &SessionReport,
+    separator: char,
+) -> io::Result<()> {
+    writer.write_all(html(report, separator).as_bytes())
+}
+
+fn html(report: &SessionReport, separator: char) -> String {
+    let time = |at: DateTime<Utc>| at.to_rfc3339_opts(SecondsFormat::Secs, true);
+    let count = |count: u64| format_count(count, CountStyle::Full, separator);
+    let settings = &report.settings;
+    let totals = &report.totals;
+
+    let mut page = String::new();
+    // Writing to a String can't fail.
+    let _ = write!(
+        page,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>Password check session report</title>\n\
+         <style>body{{font-family:sans-serif}}table{{border-collapse:collapse}}\
+         td,th{{border:1px solid #999;padding:2px 8px;text-align:left}}</style>\n\
+         </head>\n<body>\n<h1>Password check session report</h1>\n\
+         <p>Session started {}, exported {}, version {}.</p>\n\
+         <p>{} hashes, normalization {}, padding {}, endpoint {}.</p>\n\
+         <p>{} checked: {} breached, {} not found, {} failed{}.</p>\n",
+        time(report.started_at),
+        time(report.exported_at),
+        env!("CARGO_PKG_VERSION"),
+        settings.algorithm,
+        settings.normalization,
+        if settings.padding { "on" } else { "off" },
+        escape(&settings.endpoint),
+        totals.checked,
+        totals.breached,
+        totals.clean,
+        totals.failed,
+        totals
+            .worst_count
+            .map(|worst| format!("; the most exposed was seen {} times", count(worst)))
+            .unwrap_or_default(),
+    );
+
+    if !report.checks.is_empty() {
+        page.push_str(
+            "<h2>Single checks</h2>\n<table>\n<tr><th>Checked at</th><th>Label</th>\
+             <th>Prefix</th><th>Result</th><th>Severity</th></tr>\n",
+        );
+        for check in &report.checks {
+            let record = &check.record;
+            let _ = writeln!(
+                page,
+                "<tr><td>{}</td><td>{}</td><td>{} {}</td><td>{}</td><td>{}</td></tr>",
+                time(record.checked_at),
+                escape(&check.label),
+                record.algorithm,
+                escape(&record.prefix),
+                match record.result {
+                    BreachResult::Found { count: seen } => format!("seen {} times", count(seen)),
+                    BreachResult::NotFound => "not found".to_owned(),
+                },
+                record.severity.map(|s| s.to_string()).unwrap_or_default(),
+            );
+        }
+        page.push_str("</table>\n");
+    }
+
+    if let Some((_, rows)) = &report.batch {
+        page.push_str("<h2>Batch summary</h2>\n<ul>\n");
+        for line in Audit::new(rows).lines(separator) {
+            let _ = writeln!(page, "<li>{}</li>", escape(&line));
+        }
+        page.push_str("</ul>\n");
+        page.push_str(
+            "<h2>Batch</h2>\n<table>\n<tr><th>Line</th><th>Label</th><th>Password</th>\
+             <th>Prefix</th><th>Result</th><th>Severity</th></tr>\n",
+        );
+        for row in rows {
+            let _ = writeln!(
+                page,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{} {}</td><td>{}</td><td>{}</td></tr>",
+                row.line,
+                escape(&row.label),
+                escape(&row.preview),
+                row.algorithm,
+                escape(&row.prefix),
+                match &row.outcome {
+                    Ok(Some(seen)) => format!("seen {} times", count(*seen)),
+                    Ok(None) => "not found".to_owned(),
+                    Err(error) => format!("error: {}", escape(error)),
+                },
+                row.severity.map(|s| s.to_string()).unwrap_or_default(),
+            );
+        }
+        page.push_str("</table>\n");
+    }
+    page.push_str("</body>\n</html>\n");
+    page
+}
+
+/// `text` with the characters HTML treats specially replaced by entities.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LookupSource;
+    use crate::severity::{Severity, Thresholds};
+
+    fn at(time: &str) -> DateTime<Utc> {
+        time.parse().unwrap()
+    }
+
+    fn check(label: &str, result: BreachResult) -> Check {
+        let mut record = CheckRecord::new(
+            "F3BBB6A0B3C2D4E5F60718293A4B5C6D7E8F9012",
+            HashMode::Sha1,
+            result,
+            LookupSource::Network,
+            &Thresholds::default(),
+        );
+        record.checked_at = at("2025-01-01T12:01:00Z");
+        Check {
+            label: label.to_owned(),
+            record,
+        }
+    }
+
+    fn row(line: usize, outcome: Result<Option<u64>, String>) -> Row {
+        Row {
+            line,
+            label: if line == 1 {
+                "<Mail>".to_owned()
+            } else {
+                String::new()
+            },
+            preview: "h*****2".to_owned(),
+            password: line,
+            length: Some(7),
+            prefix: "F3BBB".to_owned(),
+            algorithm: HashMode::Sha1,
+            severity: match outcome {
+                Ok(Some(count)) => Some(Severity::from_count(count, &Thresholds::default())),
+                _ => None,
+            },
+            outcome,
+            checked_at: at("2025-01-01T12:02:00Z"),
+            policy: None,
+            plaintext: Some("hunter2".to_owned().into()),
+        }
+    }
+
+    /// Two single checks and a three-line batch whose rows hold plaintext,
+    /// as when the batch export was asked to include it.
+    fn report() -> SessionReport {
+        let rows = vec![
+            row(1, Ok(Some(17206891))),
+            row(2, Ok(None)),
+            row(3, Err("request timed out".to_owned())),
+        ];
+        let summary = BatchSummary::new(
+            &rows,
+            at("2025-01-01T12:02:00Z"),
+            at("2025-01-01T12:02:05Z"),
+            &"https://api.pwnedpasswords.com/".parse().unwrap(),
+        );
+        let mut report = SessionReport::new(
+            at("2025-01-01T12:00:00Z"),
+            Settings {
+                algorithm: HashMode::Sha1,
+                normalization: Normalization::Off,
+                padding: true,
+                endpoint: "https://api.pwnedpasswords.com/".to_owned(),
+            },
+            vec![
+                check("work", BreachResult::Found { count: 42 }),
+                check("", BreachResult::NotFound),
+            ],
+            Some((summary, rows)),
+        );
+        report.exported_at = at("2025-01-01T12:30:00Z");
+        report
+    }
+
+    #[test]
+    fn totals_cover_single_checks_and_the_batch() {
+        assert_eq!(
+            report().totals,
+            Totals {
+                checked: 5,
+                breached: 2,
+                clean: 2,
+                failed: 1,
+                worst_count: Some(17206891),
+            }
+        );
+        let empty = SessionReport::new(
+            at("2025-01-01T12:00:00Z"),
+            report().settings,
+            Vec::new(),
+            None,
+        );
+        assert_eq!(empty.totals, Totals::default());
+    }
+
+    #[test]
+    fn no_plaintext_survives_into_the_report() {
+        let report = report();
+        let (_, rows) = report.batch.as_ref().unwrap();
+        assert!(rows.iter().all(|row| row.plaintext.is_none()));
+
+        let mut json = Vec::new();
+        write_json(&mut json, &report).unwrap();
+        let mut html = Vec::new();
+        write_html(&mut html, &report, ',').unwrap();
+        for written in [json, html] {
+            let written = String::from_utf8(written).unwrap();
+            assert!(!written.contains("hunter2"), "{written}");
+            assert!(!written.contains("plaintext"), "{written}");
+        }
+    }
+
+    /// The shape scripts read; a change here breaks them. The version is
+    /// left out of the snapshot so a release doesn't.
+    #[test]
+    fn the_session_json_shape_is_stable() {
+        let mut json = Vec::new();
+        write_json(&mut json, &report()).unwrap();
+        let json = String::from_utf8(json)
+            .unwrap()
+            .replace(&format!("\"{}\"", env!("CARGO_PKG_VERSION")), "\"VERSION\"");
+        assert_eq!(
+            json,
+            include_str!("../tests/fixtures/records/session-report.json").trim_end()
+        );
+    }
+
+    #[test]
+    fn the_html_fills_in_every_part() {
+        let html = html(&report(), '.');
+        assert!(html.starts_with("<!DOCTYPE html>\n"));
+        assert!(html.ends_with("</body>\n</html>\n"));
+        for expected in [
+            "<p>Session started 2025-01-01T12:00:00Z, exported 2025-01-01T12:30:00Z, version ",
+            "<p>SHA-1 hashes, normalization not normalized, padding on, endpoint https://api.pwnedpasswords.com/.</p>",
+            "<p>5 checked: 2 breached, 2 not found, 1 failed; the most exposed was seen 17.206.891 times.</p>",
+            "<tr><td>2025-01-01T12:01:00Z</td><td>work</td><td>SHA-1 F3BBB</td><td>seen 42 times</td><td>moderate</td></tr>",
+            "<td>SHA-1 F3BBB</td><td>not found</td><td></td></tr>",
+            "<tr><td>1</td><td>&lt;Mail&gt;</td><td>h*****2</td><td>SHA-1 F3BBB</td><td>seen 17.206.891 times</td><td>critical</td></tr>",
+            "<td>error: request timed out</td>",
+            "<h2>Batch</h2>",
+        ] {
+            assert!(html.contains(expected), "{expected}\n{html}");
+        }
+    }
+
+    #[test]
+    fn sections_with_nothing_in_them_are_left_out() {
+        let report = SessionReport::new(
+            at("2025-01-01T12:00:00Z"),
+            report().settings,
+            Vec::new(),
+            None,
+        );
+        let html = html(&report, ',');
+        assert!(html.contains("<p>0 checked: 0 breached, 0 not found, 0 failed.</p>"));
+        assert!(!html.contains("<h2>"), "{html}");
+        assert!(!html.contains("the most exposed"), "{html}");
+    }
+
+    #[test]
+    fn markup_is_escaped() {
+        assert_eq!(
+            escape("<a href=\"x\">Tom & Jerry's</a>"),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;"
+        );
+    }
+}
@@ -0,0 +1,162 @@
+use std::fmt;
+use std::future::Future;
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(4),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Backoff before the attempt following `attempt` (1-based), scaled by a
+    /// `jitter` factor in `0.0..=1.0` so concurrent clients spread out.
+    pub fn delay(&self, attempt: u32, jitter: f64) -> Duration {
+        let exponential = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_delay);
+        exponential.mul_f64(0.5 + jitter.clamp(0.0, 1.0) / 2.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct RetryError<E> {
+    pub attempts: u32,
+    pub error: E,
+}
+
+impl<E> RetryError<E> {
+    pub fn map<U>(self, f: impl FnOnce(E) -> U) -> RetryError<U> {
+        RetryError {
+            attempts: self.attempts,
+            error: f(self.error),
+        }
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for RetryError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (after {} attempt(s))", self.error, self.attempts)
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> std::error::Error for RetryError<E> {}
+
+pub async fn retry<T, E, F, Fut>(
+    policy: &RetryPolicy,
+    mut operation: F,
+    is_transient: impl Fn(&E) -> bool,
+) -> Result<T, RetryError<E>>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        match operation().await {
+            Ok(value) => return Ok(value),
+            Err(error) if attempts < policy.max_attempts && is_transient(&error) => {
+                let delay = policy.delay(attempts, rand::random());
+                tracing::debug!(attempt = attempts, ?delay, "transient failure, retrying");
+                tokio::time::sleep(delay).await;
+            }
+            Err(error) => return Err(RetryError { attempts, error }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    const IMMEDIATE: RetryPolicy = RetryPolicy {
+        max_attempts: 3,
+        base_delay: Duration::ZERO,
+        max_delay: Duration::ZERO,
+    };
+
+    #[test]
+    fn delay_doubles_up_to_the_cap() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.delay(1, 1.0), Duration::from_millis(250));
+        assert_eq!(policy.delay(2, 1.0), Duration::from_millis(500));
+        assert_eq!(policy.delay(3, 1.0), Duration::from_secs(1));
+        assert_eq!(policy.delay(10, 1.0), Duration::from_secs(4));
+        assert_eq!(policy.delay(u32::MAX, 1.0), Duration::from_secs(4));
+    }
+
+    #[test]
+    fn jitter_takes_off_at_most_half() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.delay(2, 0.0), Duration::from_millis(250));
+        assert_eq!(policy.delay(2, -3.0), Duration::from_millis(250));
+        assert_eq!(policy.delay(2, 7.0), Duration::from_millis(500));
+    }
+
+    #[tokio::test]
+    async fn retries_transient_failures_until_one_succeeds() {
+        let calls = Cell::new(0);
+        let result = retry(
+            &IMMEDIATE,
+            || async {
+                calls.set(calls.get() + 1);
+                if calls.get() < 3 {
+                    Err("reset")
+                } else {
+                    Ok(calls.get())
+                }
+            },
+            |_| true,
+        )
+        .await;
+        assert_eq!(result.unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_the_last_attempt() {
+        let calls = Cell::new(0);
+        let result: Result<(), _> = retry(
+            &IMMEDIATE,
+            || async {
+                calls.set(calls.get() + 1);
+                Err("reset")
+            },
+            |_| true,
+        )
+        .await;
+        let error = result.unwrap_err();
+        assert_eq!((error.attempts, error.error), (3, "reset"));
+        assert_eq!(calls.get(), 3);
+    }
+
+    #[tokio::test]
+    async fn permanent_failures_arent_retried() {
+        let calls = Cell::new(0);
+        let result: Result<(), _> = retry(
+            &IMMEDIATE,
+            || async {
+                calls.set(calls.get() + 1);
+                Err(404)
+            },
+            |status| *status >= 500,
+        )
+        .await;
+        assert_eq!(result.unwrap_err().attempts, 1);
+        assert_eq!(calls.get(), 1);
+    }
+}
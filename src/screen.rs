@@ -0,0 +1,30 @@
+/// The tabs along the top of the window. Each keeps its state while another
+/// is shown, so a batch keeps running behind the Generator tab.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Screen {
+    #[default]
+    Check,
+    Batch,
+    Compare,
+    Generator,
+    Breaches,
+    Settings,
+}
+
+impl Screen {
+    /// In tab order, which is also the order of Ctrl+1 to Ctrl+6.
+    pub const ALL: [Screen; 6] = [
+        Screen::Check,
+        Screen::Batch,
+        Screen::Compare,
+        Screen::Generator,
+        Screen::Breaches,
+        Screen::Settings,
+    ];
+
+    /// The tab Ctrl plus `digit` switches to.
+    pub fn from_digit(digit: char) -> Option<Self> {
+        let index = digit.to_digit(10)?.checked_sub(1)?;
+        Screen::ALL.get(usize::try_from(index).ok()?).copied()
+    }
+}
@@ -0,0 +1,245 @@
+use std::fmt;
+
+use zeroize::{Zeroize, Zeroizing};
+
+/// A string that's wiped from memory when it's replaced or dropped, and that
+/// `Debug` never prints.
+#[derive(Clone, Default)]
+pub struct SecretString(Zeroizing<String>);
+
+impl SecretString {
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl From<String> for SecretString {
+    fn from(secret: String) -> Self {
+        Self(Zeroizing::new(secret))
+    }
+}
+
+impl Zeroize for SecretString {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretString(<redacted>)")
+    }
+}
+
+/// The smallest allocation a `SecretBuffer` makes, so typing a password
+/// doesn't reallocate (and leave copies behind) on every keystroke.
+const MIN_CAPACITY: usize = 64;
+
+/// UTF-8 text held in memory that's locked against being swapped to disk and
+/// excluded from core dumps, and wiped when it's overwritten or dropped.
+///
+/// If the OS refuses the lock (as it may under a low `RLIMIT_MEMLOCK`), the
+/// buffer still works, just unlocked, and `is_locked` says so.
+pub struct SecretBuffer {
+    bytes: Box<[u8]>,
+    len: usize,
+    locked: bool,
+}
+
+impl SecretBuffer {
+    pub fn new(secret: &str) -> Self {
+        let capacity = secret.len().max(MIN_CAPACITY).next_power_of_two();
+        let mut bytes = vec![0; capacity].into_boxed_slice();
+        // SAFETY: the range is exactly the allocation, which outlives the lock
+        // since `Drop` unlocks it before the box is freed.
+        #[cfg(not(target_arch = "wasm32"))]
+        let locked = unsafe { memsec::mlock(bytes.as_mut_ptr(), bytes.len()) };
+        // A browser never swaps a page's memory out, nor lets it be locked.
+        #[cfg(target_arch = "wasm32")]
+        let locked = false;
+        bytes[..secret.len()].copy_from_slice(secret.as_bytes());
+
+        Self {
+            bytes,
+            len: secret.len(),
+            locked,
+        }
+    }
+
+    pub fn expose(&self) -> &str {
+        // Only ever filled from `&str`s and trimmed on char boundaries.
+        std::str::from_utf8(&self.bytes[..self.len]).unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+
+    /// Replaces the contents in place, moving to a bigger locked allocation
+    /// only when `secret` doesn't fit. The old contents are wiped either way.
+    pub fn set(&mut self, secret: &str) {
+        if secret.len() > self.bytes.len() {
+            *self = Self::new(secret);
+            return;
+        }
+        self.bytes[..secret.len()].copy_from_slice(secret.as_bytes());
+        if secret.len() < self.len {
+            self.bytes[secret.len()..self.len].zeroize();
+        }
+        self.len = secret.len();
+    }
+
+    /// Drops every character `discard` picks, in place, wiping the bytes
+    /// left past the new end.
+    pub fn remove(&mut self, discard: impl Fn(char) -> bool) {
+        let keep: Vec<_> = self
+            .expose()
+            .char_indices()
+            .filter(|&(_, c)| !discard(c))
+            .map(|(start, c)| start..start + c.len_utf8())
+            .collect();
+        let mut len = 0;
+        for range in keep {
+            let width = range.len();
+            self.bytes.copy_within(range, len);
+            len += width;
+        }
+        self.bytes[len..self.len].zeroize();
+        self.len = len;
+    }
+
+    /// Strips leading and trailing whitespace in place.
+    pub fn trim(&mut self) {
+        let text = self.expose();
+        let end = text.trim_end().len();
+        let start = end - text[..end].trim_start().len();
+        self.bytes.copy_within(start..end, 0);
+        self.bytes[end - start..self.len].zeroize();
+        self.len = end - start;
+    }
+}
+
+impl Default for SecretBuffer {
+    fn default() -> Self {
+        Self::new("")
+    }
+}
+
+impl Zeroize for SecretBuffer {
+    fn zeroize(&mut self) {
+        self.set("");
+    }
+}
+
+impl Drop for SecretBuffer {
+    fn drop(&mut self) {
+        self.bytes.zeroize();
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.locked {
+            // SAFETY: the same range `new` locked, still allocated.
+            unsafe { memsec::munlock(self.bytes.as_mut_ptr(), self.bytes.len()) };
+        }
+    }
+}
+
+impl fmt::Debug for SecretBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SecretBuffer")
+            .field("locked", &self.locked)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Kilobytes this process has locked, as Linux reports them.
+    #[cfg(target_os = "linux")]
+    fn locked_kb() -> usize {
+        let status = std::fs::read_to_string("/proc/self/status").unwrap();
+        status
+            .lines()
+            .find_map(|line| line.strip_prefix("VmLck:"))
+            .and_then(|kb| kb.trim().trim_end_matches("kB").trim().parse().ok())
+            .unwrap()
+    }
+
+    #[test]
+    fn allocates_in_powers_of_two_from_a_floor() {
+        assert_eq!(SecretBuffer::new("").bytes.len(), MIN_CAPACITY);
+        assert_eq!(SecretBuffer::new("hunter2").bytes.len(), MIN_CAPACITY);
+        assert_eq!(SecretBuffer::new(&"x".repeat(65)).bytes.len(), 128);
+        assert_eq!(SecretBuffer::new(&"x".repeat(128)).bytes.len(), 128);
+    }
+
+    #[test]
+    fn setting_wipes_what_it_no_longer_holds() {
+        let mut buffer = SecretBuffer::new("hunter2hunter2");
+        let allocation = buffer.bytes.as_ptr();
+        buffer.set("abc");
+        assert_eq!(buffer.expose(), "abc");
+        assert!(buffer.bytes[3..].iter().all(|&byte| byte == 0));
+        // Typing within the capacity never moves the password.
+        buffer.set(&"y".repeat(MIN_CAPACITY));
+        assert_eq!(buffer.bytes.as_ptr(), allocation);
+
+        buffer.set(&"z".repeat(MIN_CAPACITY + 1));
+        assert_eq!(buffer.expose(), "z".repeat(MIN_CAPACITY + 1));
+        assert_eq!(buffer.bytes.len(), 2 * MIN_CAPACITY);
+        buffer.zeroize();
+        assert!(buffer.is_empty());
+        assert!(buffer.bytes.iter().all(|&byte| byte == 0));
+    }
+
+    #[test]
+    fn removing_and_trimming_work_in_place() {
+        let mut buffer = SecretBuffer::new("\u{a0}\thun\u{200b}ter2 \r\n");
+        buffer.remove(|c| c == '\u{200b}');
+        assert_eq!(buffer.expose(), "\u{a0}\thunter2 \r\n");
+        buffer.trim();
+        assert_eq!(buffer.expose(), "hunter2");
+        assert!(buffer.bytes[buffer.len..].iter().all(|&byte| byte == 0));
+
+        let mut blank = SecretBuffer::new(" \n ");
+        blank.trim();
+        assert!(blank.is_empty());
+    }
+
+    #[test]
+    fn debug_never_shows_the_secret() {
+        let buffer = SecretBuffer::new("hunter2");
+        assert!(!format!("{buffer:?}").contains("hunter2"));
+        let string = SecretString::from("hunter2".to_owned());
+        assert_eq!(format!("{string:?}"), "SecretString(<redacted>)");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn dropping_unlocks_the_memory() {
+        let before = locked_kb();
+        let buffers: Vec<_> = (0..64)
+            .map(|_| SecretBuffer::new(&"x".repeat(64 * 1024)))
+            .collect();
+        assert!(buffers.iter().all(SecretBuffer::is_locked));
+        assert!(
+            locked_kb() >= before + 4 * 1024,
+            "{} kB locked",
+            locked_kb()
+        );
+        drop(buffers);
+        assert!(
+            locked_kb() < before + 1024,
+            "{} kB still locked",
+            locked_kb()
+        );
+    }
+}
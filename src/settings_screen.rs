@@ -0,0 +1,813 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{Local, Utc};
+use iced::alignment::Vertical;
+use iced::futures::TryFutureExt;
+use iced::widget::{
+    button, checkbox, column, container, pick_list, row, scrollable, text, text_input, tooltip,
+};
+use iced::{Element, Length, Task, Theme};
+
+use cybersec_wow::cache::{CacheError, DiskCache};
+use cybersec_wow::pwned::{
+    DEFAULT_ENDPOINT, ENDPOINT_VAR, InFlight, USER_AGENT, build_client, parse_endpoint,
+};
+use cybersec_wow::{hibp, secret, severity};
+
+use crate::config::{self, ThemeChoice};
+use crate::keystore::KeyStore;
+use crate::{AppSettings, SIZES, Shared, blocking, high_contrast, logging, strings, tray, update};
+use secret::SecretString;
+use severity::Severity;
+
+/// How many days before the API key's subscription lapses the settings
+/// start warning about it.
+const SUBSCRIPTION_WARNING_DAYS: i64 = 14;
+
+/// The Settings tab: the API key and its subscription, where lookups go,
+/// how checks behave, the look of the app and its upkeep. The settings
+/// themselves are shared with the other tabs.
+#[derive(Debug, Default)]
+pub struct SettingsScreen {
+    /// Why the proxy settings couldn't build a client, until they can.
+    pub proxy_error: Option<String>,
+    /// The settings file's values for what the environment overrides, so
+    /// the overrides aren't saved.
+    pub shadowed: config::Overrides,
+    pub config_error: Option<String>,
+    pub tray_error: Option<String>,
+    /// `None` until the keyring (or its fallback) has been opened.
+    key_store: Option<Arc<dyn KeyStore>>,
+    pub api_key_status: Option<Result<String, String>>,
+    checking_subscription: bool,
+    pub endpoint_input: String,
+    pub endpoint_error: Option<String>,
+    cache_status: Option<Result<(), CacheError>>,
+    /// A newer release, until the banner for it is dismissed.
+    pub update: Option<update::Update>,
+    /// How the last check asked for in Settings went; `None` while it runs
+    /// or before there's been one.
+    pub update_status: Option<Result<(), String>>,
+    pub checking_update: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    SummaryFullHash(bool),
+    AlwaysTrim(bool),
+    ClearClipboardAfterPaste(bool),
+    ClipboardWatchMinutes(String),
+    ApiKey(SecretString),
+    KeyStoreOpened(Result<(Arc<dyn KeyStore>, Option<SecretString>), String>),
+    SaveApiKey,
+    DeleteApiKey,
+    /// Asks the API which subscription the key belongs to.
+    CheckSubscription,
+    /// The subscription of the key it was asked about.
+    SubscriptionChecked(SecretString, Result<hibp::SubscriptionStatus, String>),
+    ApiKeyStatus(Result<String, String>),
+    MinimizeToTray(bool),
+    /// Handled by the app, which owns the window.
+    ResetWindow,
+    /// Handled by the app, which opens the window.
+    OpenQuickCheck,
+    ProxyUrl(String),
+    ProxyUsername(String),
+    ProxyPassword(SecretString),
+    /// Handled by the app too, which takes back the Check tab's SOCKS error.
+    UseSocks(bool),
+    Decoys(bool),
+    DecoyCount(String),
+    SeverityThreshold(Severity, String),
+    SocksAddress(String),
+    Endpoint(String),
+    Theme(ThemeChoice),
+    LightTheme(Theme),
+    DarkTheme(Theme),
+    /// Handled by the app too, which grows the window to fit.
+    UiScale(config::UiScale),
+    Language(strings::LanguageChoice),
+    ConfigSaved(Result<(), String>),
+    AutoCheckEnabled(bool),
+    AutoCheckDelay(String),
+    /// Handled by the app too, which stops the timer running.
+    IdleClearEnabled(bool),
+    IdleClearAfter(String),
+    NotifyBatch(bool),
+    CheckForUpdates,
+    /// Whether the check was asked for in Settings, and what it found.
+    UpdateChecked(bool, Result<Option<update::Update>, String>),
+    OpenUpdate,
+    DismissUpdate,
+    CheckUpdatesAtStartup(bool),
+    DebugLog(bool),
+    ClearCache,
+    CacheCleared(Result<(), CacheError>),
+    ResetMetrics,
+}
+
+impl SettingsScreen {
+    /// The tab with its inputs filled in from `settings`.
+    pub fn new(settings: &AppSettings) -> Self {
+        Self {
+            endpoint_input: settings.endpoint.to_string(),
+            ..Self::default()
+        }
+    }
+
+    pub fn update(&mut self, message: Message, shared: &mut Shared) -> Task<Message> {
+        // Most of what's here changes a setting; saving skips what didn't.
+        // Saving them and checking for updates change none.
+        shared.settings_dirty = !matches!(
+            message,
+            Message::ConfigSaved(_)
+                | Message::CheckForUpdates
+                | Message::UpdateChecked(..)
+                | Message::OpenUpdate
+                | Message::DismissUpdate
+        );
+        match message {
+            Message::AutoCheckEnabled(enabled) => shared.settings.auto_check = enabled,
+            Message::AutoCheckDelay(delay) => {
+                if let Ok(millis) = delay.parse() {
+                    shared.settings.auto_check_delay = Duration::from_millis(millis);
+                }
+            }
+            Message::IdleClearEnabled(enabled) => shared.settings.idle_clear = enabled,
+            Message::IdleClearAfter(after) => {
+                if let Ok(seconds) = after.parse::<u64>()
+                    && seconds > 0
+                {
+                    shared.settings.idle_clear_after = Duration::from_secs(seconds);
+                }
+            }
+            Message::SummaryFullHash(include) => shared.settings.summary_full_hash = include,
+            Message::AlwaysTrim(always_trim) => shared.settings.always_trim = always_trim,
+            Message::ClearClipboardAfterPaste(clear) => {
+                shared.settings.clear_clipboard_after_paste = clear;
+            }
+            Message::ClipboardWatchMinutes(minutes) => {
+                if let Ok(minutes) = minutes.parse::<u64>()
+                    && minutes > 0
+                {
+                    shared.settings.clipboard_watch_minutes = minutes;
+                }
+            }
+            Message::ApiKey(api_key) => {
+                shared.settings.api_key = api_key;
+                shared.subscription = None;
+                self.checking_subscription = false;
+            }
+            Message::KeyStoreOpened(opened) => match opened {
+                Ok((key_store, api_key)) => {
+                    // A key from the environment wins over the saved one.
+                    if let Some(api_key) = api_key
+                        && self.shadowed.api_key.is_none()
+                    {
+                        shared.settings.api_key = api_key;
+                    }
+                    self.key_store = Some(key_store);
+                }
+                Err(error) => self.api_key_status = Some(Err(error)),
+            },
+            Message::SaveApiKey => {
+                if let Some(key_store) = self.key_store.clone() {
+                    let api_key = shared.api_key();
+                    let save = Task::perform(
+                        blocking(move || {
+                            key_store.save(api_key.expose())?;
+                            Ok("API key saved".to_owned())
+                        }),
+                        Message::ApiKeyStatus,
+                    );
+                    return Task::batch([save, self.check_subscription(shared)]);
+                }
+            }
+            Message::DeleteApiKey => {
+                shared.settings.api_key = SecretString::default();
+                shared.subscription = None;
+                self.checking_subscription = false;
+                if let Some(key_store) = self.key_store.clone() {
+                    return Task::perform(
+                        blocking(move || {
+                            key_store.delete()?;
+                            Ok("API key deleted".to_owned())
+                        }),
+                        Message::ApiKeyStatus,
+                    );
+                }
+            }
+            Message::CheckSubscription => return self.check_subscription(shared),
+            // A check of a key that's since been changed says nothing about this one.
+            Message::SubscriptionChecked(api_key, _)
+                if api_key.expose() != shared.settings.api_key.expose().trim() => {}
+            Message::SubscriptionChecked(_, subscription) => {
+                self.checking_subscription = false;
+                shared.subscription = Some(subscription);
+            }
+            Message::ApiKeyStatus(status) => self.api_key_status = Some(status),
+            Message::NotifyBatch(notify) => shared.settings.notify_batch = notify,
+            Message::CheckUpdatesAtStartup(check) => {
+                shared.settings.check_updates_at_startup = check
+            }
+            Message::DebugLog(enabled) => shared.settings.debug_log = enabled,
+            Message::MinimizeToTray(minimize) => shared.settings.minimize_to_tray = minimize,
+            Message::ProxyUrl(url) => {
+                shared.settings.proxy.url = url;
+                self.rebuild_client(shared);
+            }
+            Message::ProxyUsername(username) => {
+                shared.settings.proxy.username = username;
+                self.rebuild_client(shared);
+            }
+            Message::ProxyPassword(password) => {
+                shared.settings.proxy.password = password;
+                self.rebuild_client(shared);
+            }
+            Message::Decoys(enabled) => shared.settings.decoys = enabled,
+            Message::DecoyCount(count) => {
+                if let Ok(count) = count.parse() {
+                    shared.settings.decoy_count = count;
+                } else if count.is_empty() {
+                    shared.settings.decoy_count = 0;
+                }
+            }
+            Message::SeverityThreshold(tier, count) => {
+                let thresholds = &mut shared.settings.severity;
+                let threshold = match tier {
+                    Severity::Low => return Task::none(),
+                    Severity::Moderate => &mut thresholds.moderate,
+                    Severity::High => &mut thresholds.high,
+                    Severity::Critical => &mut thresholds.critical,
+                };
+                if let Ok(count) = count.parse() {
+                    *threshold = count;
+                } else if count.is_empty() {
+                    *threshold = 0;
+                }
+            }
+            Message::UseSocks(enabled) => {
+                shared.settings.socks.enabled = enabled;
+                self.rebuild_client(shared);
+            }
+            Message::SocksAddress(address) => {
+                shared.settings.socks.address = address;
+                self.rebuild_client(shared);
+            }
+            Message::Endpoint(input) => {
+                match parse_endpoint(&input) {
+                    Ok(url) => {
+                        if url != shared.settings.endpoint {
+                            // What's cached or fetching is the old endpoint's answer.
+                            shared.cache.clear();
+                            shared.in_flight = InFlight::default();
+                            if shared.disk_cache.is_some() {
+                                shared.disk_cache =
+                                    DiskCache::new(&url, shared.settings.disk_cache_ttl);
+                            }
+                        }
+                        shared.settings.endpoint = url;
+                        self.shadowed.endpoint = None;
+                        self.endpoint_error = None;
+                    }
+                    Err(error) => self.endpoint_error = Some(error),
+                }
+                self.endpoint_input = input;
+            }
+            Message::Theme(theme) => shared.settings.appearance.theme = theme,
+            Message::LightTheme(theme) => shared.settings.appearance.light_theme = theme,
+            Message::DarkTheme(theme) => shared.settings.appearance.dark_theme = theme,
+            Message::Language(language) => shared.settings.language = language,
+            Message::UiScale(scale) => shared.settings.appearance.scale = scale,
+            Message::ResetWindow | Message::OpenQuickCheck => {}
+            Message::ClearCache => {
+                shared.cache.clear();
+                self.cache_status = None;
+                if let Some(disk_cache) = shared.disk_cache.clone() {
+                    return Task::future(disk_cache.clear()).map(Message::CacheCleared);
+                }
+                self.cache_status = Some(Ok(()));
+            }
+            Message::CacheCleared(result) => self.cache_status = Some(result),
+            Message::ResetMetrics => shared.metrics.reset(),
+            Message::ConfigSaved(saved) => {
+                if let Err(error) = &saved {
+                    tracing::warn!("could not save the settings: {error}");
+                }
+                self.config_error = saved.err();
+            }
+            Message::CheckForUpdates => {
+                self.checking_update = true;
+                self.update_status = None;
+                return Task::perform(update::check(shared.client.clone()), |result| {
+                    Message::UpdateChecked(true, result)
+                });
+            }
+            Message::UpdateChecked(manual, result) => {
+                if manual {
+                    self.checking_update = false;
+                }
+                match result {
+                    Ok(update) => {
+                        self.update = update;
+                        if manual {
+                            self.update_status = Some(Ok(()));
+                        }
+                    }
+                    // The check at startup wasn't asked for, so it fails
+                    // quietly.
+                    Err(error) if manual => self.update_status = Some(Err(error)),
+                    Err(_) => {}
+                }
+            }
+            Message::OpenUpdate => {
+                if let Some(update) = &self.update
+                    && let Err(error) = open::that_detached(&update.url)
+                {
+                    tracing::warn!("could not open {}: {error}", update.url);
+                }
+            }
+            Message::DismissUpdate => self.update = None,
+        }
+
+        Task::none()
+    }
+
+    /// Asks the API about the key's subscription. A rejected key is said to
+    /// be invalid rather than described by its status code.
+    fn check_subscription(&mut self, shared: &mut Shared) -> Task<Message> {
+        self.checking_subscription = true;
+        let api_key = shared.api_key();
+        Task::perform(
+            hibp::subscription_status(shared.client.clone(), api_key.clone()).map_err(|error| {
+                match error {
+                    hibp::ApiError::InvalidKey => "invalid key".to_owned(),
+                    error => error.to_string(),
+                }
+            }),
+            move |subscription| Message::SubscriptionChecked(api_key.clone(), subscription),
+        )
+    }
+
+    fn rebuild_client(&mut self, shared: &mut Shared) {
+        match build_client(&shared.settings.network()) {
+            Ok(client) => {
+                shared.client = client;
+                self.proxy_error = None;
+            }
+            Err(error) => self.proxy_error = Some(error),
+        }
+    }
+
+    pub fn view<'a>(&'a self, shared: &'a Shared) -> Element<'a, Message> {
+        column![
+            text(shared.strings().settings).size(SIZES.title),
+            scrollable(self.form(shared)).height(Length::Fill),
+        ]
+        .spacing(10)
+        .into()
+    }
+
+    /// Everything on the Settings tab, a section at a time.
+    pub fn form<'a>(&'a self, shared: &'a Shared) -> Element<'a, Message> {
+        column![
+            self.appearance_settings(shared),
+            self.api_key_settings(shared),
+            self.connection_settings(shared),
+            self.checking_settings(shared),
+            self.lookup_settings(shared),
+            self.maintenance_settings(shared),
+        ]
+        .spacing(5)
+        .into()
+    }
+
+    /// The theme, language and scale, the window, and the tray.
+    fn appearance_settings<'a>(&'a self, shared: &'a Shared) -> Element<'a, Message> {
+        let strings = shared.strings();
+        let theme_status = match &shared.system_dark {
+            _ if shared.settings.appearance.theme != ThemeChoice::System => text(""),
+            Ok(dark) => text((strings.system_theme)(*dark, &shared.theme().to_string()))
+                .style(text::secondary),
+            Err(error) => text((strings.system_theme_failed)(
+                error,
+                &shared.theme().to_string(),
+            ))
+            .style(text::danger),
+        };
+        column![
+            row![
+                text(strings.theme),
+                pick_list(
+                    ThemeChoice::all(),
+                    Some(&shared.settings.appearance.theme),
+                    Message::Theme
+                ),
+                text(strings.language),
+                pick_list(
+                    strings::LanguageChoice::all(),
+                    Some(shared.settings.language),
+                    Message::Language
+                ),
+                text(strings.ui_scale),
+                pick_list(
+                    config::UiScale::PRESETS,
+                    Some(shared.settings.appearance.scale),
+                    Message::UiScale
+                ),
+            ]
+            .push_maybe(
+                (shared.settings.appearance.theme == ThemeChoice::System).then(|| {
+                    row![
+                        text(strings.light),
+                        pick_list(
+                            config::themes(),
+                            Some(&shared.settings.appearance.light_theme),
+                            Message::LightTheme
+                        ),
+                        text(strings.dark),
+                        pick_list(
+                            config::themes(),
+                            Some(&shared.settings.appearance.dark_theme),
+                            Message::DarkTheme
+                        ),
+                    ]
+                    .spacing(5)
+                    .align_y(Vertical::Center)
+                }),
+            )
+            .push_maybe(
+                self.config_error
+                    .as_ref()
+                    .map(|error| text((strings.save_failed)(error)).style(text::danger)),
+            )
+            .spacing(10)
+            .align_y(Vertical::Center),
+            theme_status,
+            row![
+                text(strings.window_remembered).style(text::secondary),
+                button(strings.reset_window).on_press_maybe(
+                    shared
+                        .settings
+                        .window
+                        .is_some()
+                        .then_some(Message::ResetWindow)
+                ),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            row![
+                tooltip(
+                    button(strings.tray_quick_check).on_press(Message::OpenQuickCheck),
+                    text(strings.quick_check_tooltip),
+                    tooltip::Position::Bottom,
+                )
+                .style(container::rounded_box),
+            ]
+            .push_maybe(tray::SUPPORTED.then(|| {
+                checkbox(strings.minimize_to_tray, shared.settings.minimize_to_tray)
+                    .on_toggle(Message::MinimizeToTray)
+            }))
+            .push_maybe(
+                self.tray_error
+                    .as_ref()
+                    .map(|error| text((strings.tray_failed)(error)).style(text::danger)),
+            )
+            .spacing(10)
+            .align_y(Vertical::Center),
+        ]
+        .spacing(5)
+        .into()
+    }
+
+    /// The HIBP API key the account and domain lookups use.
+    fn api_key_settings<'a>(&'a self, shared: &'a Shared) -> Element<'a, Message> {
+        let strings = shared.strings();
+        // Values the environment sets are shown masked, and can't be edited
+        // here since the next start would set them again.
+        let api_key_input = match &self.shadowed.api_key {
+            Some(_) => text_input(
+                "hibp-api-key",
+                &config::mask_secret(shared.settings.api_key.expose()),
+            ),
+            None => text_input("hibp-api-key", shared.settings.api_key.expose())
+                .secure(true)
+                .on_input(|key| Message::ApiKey(key.into())),
+        };
+        let can_save_key = self.key_store.is_some() && self.shadowed.api_key.is_none();
+        column![
+            text(strings.api_key_heading),
+            row![
+                api_key_input.style(high_contrast::text_input),
+                button(strings.save).on_press_maybe(can_save_key.then_some(Message::SaveApiKey)),
+                button(if shared.subscription.is_some() {
+                    strings.recheck
+                } else {
+                    strings.check
+                })
+                .on_press_maybe(
+                    (!self.checking_subscription).then_some(Message::CheckSubscription)
+                ),
+                button(strings.delete)
+                    .on_press_maybe(can_save_key.then_some(Message::DeleteApiKey)),
+            ]
+            .spacing(5),
+            match &self.shadowed.api_key {
+                Some(_) => {
+                    text((strings.api_key_from_env)(config::API_KEY_VAR)).style(text::secondary)
+                }
+                None => text(""),
+            },
+            match &self.api_key_status {
+                Some(Ok(status)) => text(status).style(text::success),
+                Some(Err(error)) => text((strings.api_key_error)(error)).style(text::danger),
+                None => text(""),
+            },
+        ]
+        .push_maybe(self.subscription_view(shared))
+        .push(match &self.key_store {
+            Some(key_store) if !key_store.is_secure() => {
+                text(strings.plain_keystore).style(text::danger)
+            }
+            _ => text(""),
+        })
+        .spacing(5)
+        .into()
+    }
+
+    /// The key's plan, its rate and when it runs out, warning once that's
+    /// close.
+    fn subscription_view<'a>(&'a self, shared: &'a Shared) -> Option<Element<'a, Message>> {
+        let strings = shared.strings();
+        if self.checking_subscription {
+            return Some(
+                text(strings.checking_subscription)
+                    .style(text::secondary)
+                    .into(),
+            );
+        }
+        let subscription = match shared.subscription.as_ref()? {
+            Ok(subscription) => subscription,
+            Err(error) => {
+                return Some(
+                    text((strings.api_key_error)(error))
+                        .style(text::danger)
+                        .into(),
+                );
+            }
+        };
+        let until = subscription
+            .subscribed_until
+            .with_timezone(&Local)
+            .format("%x")
+            .to_string();
+        let days_left = subscription.days_left(Utc::now());
+        let expiry = if days_left < 0 {
+            Some(text((strings.subscription_expired)(&until)))
+        } else if days_left <= SUBSCRIPTION_WARNING_DAYS {
+            Some(text((strings.subscription_expiring)(days_left)))
+        } else {
+            None
+        };
+        Some(
+            column![
+                text((strings.subscription_plan)(
+                    &subscription.subscription_name,
+                    subscription.rpm,
+                    &until
+                ))
+                .style(text::success)
+            ]
+            .push_maybe(expiry.map(|expiry| expiry.style(text::danger)))
+            .spacing(5)
+            .into(),
+        )
+    }
+
+    /// Where lookups go, and through which proxy.
+    fn connection_settings<'a>(&'a self, shared: &'a Shared) -> Element<'a, Message> {
+        let strings = shared.strings();
+        let proxy = &shared.settings.proxy;
+        let error = match &self.proxy_error {
+            Some(error) => text((strings.invalid_proxy)(error)).style(text::danger),
+            None => text!(""),
+        };
+        let endpoint = shared.settings.endpoint.as_str();
+        let endpoint_notice = match &self.endpoint_error {
+            Some(error) => text((strings.invalid_endpoint)(error)).style(text::danger),
+            None if shared.settings.endpoint.scheme() == "http" => {
+                text(strings.plain_http).style(text::danger)
+            }
+            None if self.shadowed.endpoint.is_some() => {
+                text((strings.using_endpoint_from_env)(endpoint, ENDPOINT_VAR))
+                    .style(text::secondary)
+            }
+            None => text((strings.using_endpoint)(endpoint)).style(text::secondary),
+        };
+        // Like the API key, a proxy the environment sets is shown masked.
+        let proxy_input = match &self.shadowed.proxy {
+            Some(_) => text_input("", &config::mask_proxy(&proxy.url)),
+            None => text_input(strings.proxy_placeholder, &proxy.url).on_input(Message::ProxyUrl),
+        };
+        column![
+            text((strings.endpoint_heading)(ENDPOINT_VAR)),
+            text_input(DEFAULT_ENDPOINT, &self.endpoint_input)
+                .style(high_contrast::text_input)
+                .on_input(Message::Endpoint),
+            endpoint_notice,
+            text(strings.proxy_heading),
+            proxy_input.style(high_contrast::text_input),
+            match &self.shadowed.proxy {
+                Some(_) => text((strings.set_by)(config::PROXY_VAR)).style(text::secondary),
+                None => text(""),
+            },
+            row![
+                text_input(strings.proxy_username, &proxy.username)
+                    .style(high_contrast::text_input)
+                    .on_input(Message::ProxyUsername),
+                text_input(strings.proxy_password, proxy.password.expose())
+                    .style(high_contrast::text_input)
+                    .secure(true)
+                    .on_input(|password| Message::ProxyPassword(password.into())),
+            ]
+            .spacing(5),
+            checkbox(strings.use_socks, shared.settings.socks.enabled).on_toggle(Message::UseSocks),
+            text_input("127.0.0.1:9050", &shared.settings.socks.address)
+                .style(high_contrast::text_input)
+                .on_input(Message::SocksAddress),
+            error,
+        ]
+        .spacing(5)
+        .into()
+    }
+
+    /// How the Check tab takes and checks a password.
+    fn checking_settings<'a>(&'a self, shared: &'a Shared) -> Element<'a, Message> {
+        let strings = shared.strings();
+        column![
+            checkbox(strings.summary_full_hash, shared.settings.summary_full_hash)
+                .on_toggle(Message::SummaryFullHash),
+            checkbox(strings.always_trim, shared.settings.always_trim)
+                .on_toggle(Message::AlwaysTrim),
+            checkbox(
+                strings.clear_clipboard_after_paste,
+                shared.settings.clear_clipboard_after_paste
+            )
+            .on_toggle(Message::ClearClipboardAfterPaste),
+            row![
+                text(strings.clipboard_watch_limit),
+                text_input("5", &shared.settings.clipboard_watch_minutes.to_string())
+                    .style(high_contrast::text_input)
+                    .on_input(Message::ClipboardWatchMinutes)
+                    .width(70),
+                text("min"),
+            ]
+            .spacing(5)
+            .align_y(Vertical::Center),
+            row![
+                checkbox(strings.auto_check, shared.settings.auto_check)
+                    .on_toggle(Message::AutoCheckEnabled),
+                text_input(
+                    "800",
+                    &shared.settings.auto_check_delay.as_millis().to_string()
+                )
+                .style(high_contrast::text_input)
+                .on_input(Message::AutoCheckDelay)
+                .width(70),
+                text("ms"),
+            ]
+            .spacing(5)
+            .align_y(Vertical::Center),
+            row![
+                checkbox(strings.idle_clear, shared.settings.idle_clear)
+                    .on_toggle(Message::IdleClearEnabled),
+                text_input(
+                    "120",
+                    &shared.settings.idle_clear_after.as_secs().to_string()
+                )
+                .style(high_contrast::text_input)
+                .on_input(Message::IdleClearAfter)
+                .width(70),
+                text("s"),
+            ]
+            .spacing(5)
+            .align_y(Vertical::Center),
+            checkbox(strings.notify_batch, shared.settings.notify_batch)
+                .on_toggle(Message::NotifyBatch),
+        ]
+        .spacing(5)
+        .into()
+    }
+
+    /// Decoy lookups, and the counts each severity starts at.
+    fn lookup_settings<'a>(&'a self, shared: &'a Shared) -> Element<'a, Message> {
+        let strings = shared.strings();
+        column![
+            row![
+                checkbox(strings.decoys, shared.settings.decoys).on_toggle(Message::Decoys),
+                text_input("3", &shared.settings.decoy_count.to_string())
+                    .style(high_contrast::text_input)
+                    .on_input(Message::DecoyCount)
+                    .width(50),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            text((strings.decoy_cost)(
+                shared.settings.decoy_count,
+                shared.settings.decoy_count * 40
+            ))
+            .style(text::secondary),
+            row![
+                text(strings.severity_from),
+                text((strings.threshold)(Severity::Moderate)),
+                text_input("10", &shared.settings.severity.moderate.to_string())
+                    .style(high_contrast::text_input)
+                    .on_input(|count| Message::SeverityThreshold(Severity::Moderate, count))
+                    .width(80),
+                text((strings.threshold)(Severity::High)),
+                text_input("1000", &shared.settings.severity.high.to_string())
+                    .style(high_contrast::text_input)
+                    .on_input(|count| Message::SeverityThreshold(Severity::High, count))
+                    .width(80),
+                text((strings.threshold)(Severity::Critical)),
+                text_input("100000", &shared.settings.severity.critical.to_string())
+                    .style(high_contrast::text_input)
+                    .on_input(|count| Message::SeverityThreshold(Severity::Critical, count))
+                    .width(80),
+                text(strings.occurrences).style(text::secondary),
+            ]
+            .spacing(5)
+            .align_y(Vertical::Center),
+        ]
+        .spacing(5)
+        .into()
+    }
+
+    /// The cache, diagnostics, updates and the debug log.
+    fn maintenance_settings<'a>(&'a self, shared: &'a Shared) -> Element<'a, Message> {
+        let strings = shared.strings();
+        column![
+            row![
+                button(strings.clear_cache).on_press(Message::ClearCache),
+                match &self.cache_status {
+                    Some(Ok(())) => text(strings.cache_cleared).style(text::success),
+                    Some(Err(error)) =>
+                        text((strings.clear_cache_failed)(&error.to_string())).style(text::danger),
+                    None => text(""),
+                },
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            row![
+                text(strings.diagnostics),
+                button(strings.reset).on_press(Message::ResetMetrics),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            text(shared.metrics.snapshot().to_string()).style(text::secondary),
+            row![
+                checkbox(
+                    strings.check_updates,
+                    shared.settings.check_updates_at_startup
+                )
+                .on_toggle(Message::CheckUpdatesAtStartup),
+                button(strings.check_now)
+                    .on_press_maybe((!self.checking_update).then_some(Message::CheckForUpdates)),
+                if self.checking_update {
+                    text(strings.checking_updates).style(text::secondary)
+                } else {
+                    match (&self.update_status, &self.update) {
+                        (Some(Ok(())), Some(update)) => {
+                            text((strings.update_available)(&update.version.to_string()))
+                                .style(text::success)
+                        }
+                        (Some(Ok(())), None) => {
+                            text((strings.up_to_date)(env!("CARGO_PKG_VERSION")))
+                                .style(text::secondary)
+                        }
+                        (Some(Err(error)), _) => {
+                            text((strings.update_failed)(error)).style(text::danger)
+                        }
+                        (None, _) => text(""),
+                    }
+                },
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            row![
+                checkbox(strings.debug_log, shared.settings.debug_log).on_toggle(Message::DebugLog),
+                text(match logging::dir() {
+                    Some(dir) => (strings.debug_log_dir)(&dir.display().to_string()),
+                    None => strings.no_data_dir.to_owned(),
+                })
+                .style(text::secondary),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            text!("User-Agent: {}", USER_AGENT).style(text::secondary),
+        ]
+        .spacing(5)
+        .into()
+    }
+}
@@ -0,0 +1,133 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// How widely a breached password is known, by its occurrence count.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Low,
+    Moderate,
+    High,
+    Critical,
+}
+
+/// The lowest count in each tier above [`Severity::Low`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Thresholds {
+    pub moderate: u64,
+    pub high: u64,
+    pub critical: u64,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Self {
+            moderate: 10,
+            high: 1_000,
+            critical: 100_000,
+        }
+    }
+}
+
+impl Severity {
+    /// The tier `count` falls in, each threshold being the first count in
+    /// its tier.
+    ///
+    /// ```
+    /// use cybersec_wow::severity::{Severity, Thresholds};
+    ///
+    /// let thresholds = Thresholds::default();
+    /// assert_eq!(Severity::from_count(9, &thresholds), Severity::Low);
+    /// assert_eq!(Severity::from_count(10, &thresholds), Severity::Moderate);
+    /// assert_eq!(Severity::from_count(100_000, &thresholds), Severity::Critical);
+    /// ```
+    pub fn from_count(count: u64, thresholds: &Thresholds) -> Self {
+        if count >= thresholds.critical {
+            Severity::Critical
+        } else if count >= thresholds.high {
+            Severity::High
+        } else if count >= thresholds.moderate {
+            Severity::Moderate
+        } else {
+            Severity::Low
+        }
+    }
+
+    pub fn icon(self) -> &'static str {
+        match self {
+            Severity::Low => "●",
+            Severity::Moderate => "▲",
+            Severity::High => "⚠",
+            Severity::Critical => "⛔",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Low => "low",
+            Severity::Moderate => "moderate",
+            Severity::High => "high",
+            Severity::Critical => "critical",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_tier_starts_at_its_threshold() {
+        let thresholds = Thresholds::default();
+        for (count, severity) in [
+            (0, Severity::Low),
+            (9, Severity::Low),
+            (10, Severity::Moderate),
+            (999, Severity::Moderate),
+            (1_000, Severity::High),
+            (99_999, Severity::High),
+            (100_000, Severity::Critical),
+            (u64::MAX, Severity::Critical),
+        ] {
+            assert_eq!(
+                Severity::from_count(count, &thresholds),
+                severity,
+                "{count}"
+            );
+        }
+    }
+
+    #[test]
+    fn thresholds_can_be_moved() {
+        let thresholds = Thresholds {
+            moderate: 2,
+            high: 3,
+            critical: 3,
+        };
+        assert_eq!(Severity::from_count(1, &thresholds), Severity::Low);
+        assert_eq!(Severity::from_count(2, &thresholds), Severity::Moderate);
+        // Equal thresholds skip the lower tier.
+        assert_eq!(Severity::from_count(3, &thresholds), Severity::Critical);
+    }
+
+    #[test]
+    fn tiers_are_named_the_same_everywhere() {
+        for (severity, name) in [
+            (Severity::Low, "low"),
+            (Severity::Moderate, "moderate"),
+            (Severity::High, "high"),
+            (Severity::Critical, "critical"),
+        ] {
+            assert_eq!(severity.to_string(), name);
+            assert_eq!(
+                serde_json::to_string(&severity).unwrap(),
+                format!("\"{name}\"")
+            );
+        }
+        assert!(Severity::Low < Severity::Critical);
+    }
+}
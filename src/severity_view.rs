@@ -0,0 +1,166 @@
+use iced::widget::{column, container, text};
+use iced::{Color, Element, Length, Theme, border};
+
+use crate::severity::Severity;
+use crate::{SIZES, high_contrast};
+
+/// The tier's colour: darker shades on light themes, where the bright ones
+/// wash out, and brighter ones on the high-contrast theme, all at least 7:1
+/// against its background.
+pub fn color(severity: Severity, theme: &Theme) -> Color {
+    if high_contrast::is_active(theme) {
+        return match severity {
+            Severity::Low => Color::from_rgb8(0xFF, 0xF0, 0x4D),
+            Severity::Moderate => Color::from_rgb8(0xFF, 0xB3, 0x40),
+            Severity::High => Color::from_rgb8(0xFF, 0x8A, 0x5C),
+            Severity::Critical => theme.palette().danger,
+        };
+    }
+    let dark = theme.extended_palette().is_dark;
+    match (severity, dark) {
+        (Severity::Low, true) => Color::from_rgb8(0xD4, 0xA0, 0x17),
+        (Severity::Low, false) => Color::from_rgb8(0x8A, 0x65, 0x00),
+        (Severity::Moderate, true) => Color::from_rgb8(0xE0, 0x7B, 0x20),
+        (Severity::Moderate, false) => Color::from_rgb8(0xA3, 0x50, 0x00),
+        (Severity::High, true) => Color::from_rgb8(0xD9, 0x48, 0x2B),
+        (Severity::High, false) => Color::from_rgb8(0xB0, 0x2E, 0x14),
+        (Severity::Critical, _) => Color::from_rgb8(0xB0, 0x10, 0x10),
+    }
+}
+
+/// The accent for a result: the theme's success colour when nothing was
+/// found, otherwise the tier's own colour, which [`color`] already
+/// adapts to light and dark themes.
+pub fn accent(severity: Option<Severity>, theme: &Theme) -> Color {
+    match severity {
+        None => theme.extended_palette().success.base.color,
+        Some(Severity::Critical) => theme.extended_palette().danger.base.color,
+        Some(severity) => color(severity, theme),
+    }
+}
+
+/// A full-width verdict banner: `headline` after the tier's icon, then
+/// `detail` below. The background is only a tint of the accent over the
+/// theme's background, so the theme's own text colour stays readable; the
+/// high-contrast theme skips the tint and thickens the border instead.
+pub fn result_banner<'a, Message: 'a>(
+    severity: Option<Severity>,
+    headline: String,
+    detail: String,
+) -> Element<'a, Message> {
+    let icon = severity.map_or("✔", Severity::icon);
+    container(
+        column![
+            text!("{} {}", icon, headline)
+                .size(SIZES.banner)
+                .shaping(text::Shaping::Advanced),
+            text(detail),
+        ]
+        .spacing(4),
+    )
+    .width(Length::Fill)
+    .padding(10)
+    .style(move |theme: &Theme| banner_style(severity, theme))
+    .into()
+}
+
+/// How [`result_banner`] looks for `severity` on `theme`.
+fn banner_style(severity: Option<Severity>, theme: &Theme) -> container::Style {
+    let accent = accent(severity, theme);
+    let palette = theme.extended_palette();
+    let base = palette.background.base.color;
+    let (tint, border_width) = if high_contrast::is_active(theme) {
+        (0., 3.)
+    } else {
+        (0.18, 2.)
+    };
+    container::Style {
+        background: Some(mix(base, accent, tint).into()),
+        border: border::rounded(6).color(accent).width(border_width),
+        text_color: Some(palette.background.base.text),
+        ..container::Style::default()
+    }
+}
+
+/// `from` moved `amount` of the way towards `to`.
+fn mix(from: Color, to: Color, amount: f32) -> Color {
+    Color {
+        r: from.r + (to.r - from.r) * amount,
+        g: from.g + (to.g - from.g) * amount,
+        b: from.b + (to.b - from.b) * amount,
+        a: 1.,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIERS: [Severity; 4] = [
+        Severity::Low,
+        Severity::Moderate,
+        Severity::High,
+        Severity::Critical,
+    ];
+
+    #[test]
+    fn each_outcome_has_its_own_accent() {
+        for theme in [Theme::Dark, Theme::Light, high_contrast::theme()] {
+            let palette = theme.extended_palette();
+            assert_eq!(accent(None, &theme), palette.success.base.color);
+            assert_eq!(
+                accent(Some(Severity::Critical), &theme),
+                palette.danger.base.color
+            );
+            let mut accents: Vec<_> = std::iter::once(None)
+                .chain(TIERS.map(Some))
+                .map(|severity| accent(severity, &theme))
+                .collect();
+            accents.dedup();
+            assert_eq!(accents.len(), 5, "{theme}");
+        }
+    }
+
+    #[test]
+    fn light_themes_get_the_darker_shades() {
+        for severity in [Severity::Low, Severity::Moderate, Severity::High] {
+            let (light, dark) = (
+                color(severity, &Theme::Light),
+                color(severity, &Theme::Dark),
+            );
+            let luminance = |c: Color| c.r + c.g + c.b;
+            assert!(luminance(light) < luminance(dark), "{severity:?}");
+        }
+    }
+
+    #[test]
+    fn the_banner_is_a_tint_bordered_in_the_accent() {
+        let theme = Theme::Dark;
+        let base = theme.extended_palette().background.base;
+        for severity in std::iter::once(None).chain(TIERS.map(Some)) {
+            let style = banner_style(severity, &theme);
+            let accent = accent(severity, &theme);
+            assert_eq!(style.border.color, accent);
+            assert_eq!(style.border.width, 2.);
+            assert_eq!(style.text_color, Some(base.text));
+            let Some(iced::Background::Color(background)) = style.background else {
+                panic!("{severity:?} has no background");
+            };
+            assert_ne!(background, base.color);
+            assert_ne!(background, accent);
+        }
+    }
+
+    #[test]
+    fn high_contrast_thickens_the_border_instead_of_tinting() {
+        let theme = high_contrast::theme();
+        let style = banner_style(Some(Severity::High), &theme);
+        assert_eq!(style.border.width, 3.);
+        assert_eq!(
+            style.background,
+            Some(iced::Background::Color(
+                theme.extended_palette().background.base.color
+            ))
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use iced::keyboard::{Key, Modifiers, key};
+
+use crate::screen::Screen;
+
+/// Something a key combination asks for. Whether it does anything depends on
+/// the state it arrives in, so the app decides that, not the key handler.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shortcut {
+    Submit,
+    FocusPassword,
+    CopySummary,
+    OpenSettings,
+    ToggleShowPassword,
+    Help,
+    Escape,
+    Screen(Screen),
+    /// Tab and Shift+Tab, which only reach the text fields: iced 0.13 can't
+    /// focus buttons or checkboxes.
+    FocusNext,
+    FocusPrevious,
+    ScrollToTop,
+    ScrollToBottom,
+}
+
+/// The combinations [`shortcut`] knows, as listed in the help overlay with
+/// what they do in the UI's language. Ctrl is Cmd on macOS.
+pub const BINDINGS: &[(&str, Shortcut)] = &[
+    ("Ctrl+Enter", Shortcut::Submit),
+    ("Ctrl+L", Shortcut::FocusPassword),
+    ("Ctrl+Shift+C", Shortcut::CopySummary),
+    ("Ctrl+,", Shortcut::OpenSettings),
+    ("Ctrl+H", Shortcut::ToggleShowPassword),
+    ("Ctrl+1…6", Shortcut::Screen(Screen::Check)),
+    ("Tab / Shift+Tab", Shortcut::FocusNext),
+    ("Ctrl+Home / Ctrl+End", Shortcut::ScrollToTop),
+    ("Esc", Shortcut::Escape),
+    ("F1", Shortcut::Help),
+];
+
+pub fn shortcut(key: &Key, modifiers: Modifiers) -> Option<Shortcut> {
+    match key.as_ref() {
+        Key::Named(key::Named::F1) => return Some(Shortcut::Help),
+        Key::Named(key::Named::Escape) => return Some(Shortcut::Escape),
+        Key::Named(key::Named::Tab) if modifiers.shift() => return Some(Shortcut::FocusPrevious),
+        Key::Named(key::Named::Tab) => return Some(Shortcut::FocusNext),
+        _ => {}
+    }
+    if !modifiers.command() {
+        return None;
+    }
+    match key.as_ref() {
+        Key::Named(key::Named::Enter) => Some(Shortcut::Submit),
+        Key::Named(key::Named::Home) => Some(Shortcut::ScrollToTop),
+        Key::Named(key::Named::End) => Some(Shortcut::ScrollToBottom),
+        // Shift may or may not have uppercased the character already.
+        Key::Character(c) if c.eq_ignore_ascii_case("c") && modifiers.shift() => {
+            Some(Shortcut::CopySummary)
+        }
+        _ if modifiers.shift() => None,
+        Key::Character("l" | "L") => Some(Shortcut::FocusPassword),
+        Key::Character(",") => Some(Shortcut::OpenSettings),
+        Key::Character("h" | "H") => Some(Shortcut::ToggleShowPassword),
+        Key::Character(c) => {
+            let mut chars = c.chars();
+            let digit = chars.next().filter(|_| chars.next().is_none())?;
+            Screen::from_digit(digit).map(Shortcut::Screen)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn char(c: &str) -> Key {
+        Key::Character(c.into())
+    }
+
+    fn named(named: key::Named) -> Key {
+        Key::Named(named)
+    }
+
+    const CTRL: Modifiers = Modifiers::COMMAND;
+
+    #[test]
+    fn command_combinations_map_to_their_shortcuts() {
+        for (key, modifiers, expected) in [
+            (named(key::Named::Enter), CTRL, Shortcut::Submit),
+            (char("l"), CTRL, Shortcut::FocusPassword),
+            (char("L"), CTRL, Shortcut::FocusPassword),
+            (char(","), CTRL, Shortcut::OpenSettings),
+            (char("h"), CTRL, Shortcut::ToggleShowPassword),
+            (char("c"), CTRL | Modifiers::SHIFT, Shortcut::CopySummary),
+            (char("C"), CTRL | Modifiers::SHIFT, Shortcut::CopySummary),
+            (named(key::Named::Home), CTRL, Shortcut::ScrollToTop),
+            (named(key::Named::End), CTRL, Shortcut::ScrollToBottom),
+        ] {
+            assert_eq!(shortcut(&key, modifiers), Some(expected), "{key:?}");
+        }
+    }
+
+    #[test]
+    fn digits_switch_tabs_in_order() {
+        for (digit, screen) in ('1'..='6').zip(Screen::ALL) {
+            let key = char(&digit.to_string());
+            assert_eq!(shortcut(&key, CTRL), Some(Shortcut::Screen(screen)));
+        }
+        for key in ["0", "7", "12"] {
+            assert_eq!(shortcut(&char(key), CTRL), None, "{key}");
+        }
+    }
+
+    #[test]
+    fn some_keys_need_no_modifier() {
+        let none = Modifiers::empty();
+        assert_eq!(shortcut(&named(key::Named::F1), none), Some(Shortcut::Help));
+        assert_eq!(
+            shortcut(&named(key::Named::Escape), CTRL),
+            Some(Shortcut::Escape)
+        );
+        assert_eq!(
+            shortcut(&named(key::Named::Tab), none),
+            Some(Shortcut::FocusNext)
+        );
+        assert_eq!(
+            shortcut(&named(key::Named::Tab), Modifiers::SHIFT),
+            Some(Shortcut::FocusPrevious)
+        );
+    }
+
+    #[test]
+    fn typing_isnt_a_shortcut() {
+        for (key, modifiers) in [
+            (char("l"), Modifiers::empty()),
+            (named(key::Named::Enter), Modifiers::empty()),
+            (char("c"), CTRL),
+            (char("l"), CTRL | Modifiers::SHIFT),
+            (char("1"), CTRL | Modifiers::SHIFT),
+            (char("h"), Modifiers::ALT),
+        ] {
+            assert_eq!(shortcut(&key, modifiers), None, "{key:?} {modifiers:?}");
+        }
+    }
+}
@@ -0,0 +1,198 @@
+use std::fmt;
+
+use iced::widget::{Space, container, row};
+use iced::{Color, Element, Length, Theme, border};
+use zxcvbn::zxcvbn;
+
+use crate::crack_time::brute_force_guesses_log10;
+
+/// zxcvbn only looks at this many characters of a password.
+const ANALYSED_CHARS: usize = 100;
+
+/// zxcvbn's verdict on a password, kept apart from the password itself.
+#[derive(Clone, Debug)]
+pub struct Strength {
+    /// 0 (guessable in about a thousand tries) to 4 (beyond ten billion).
+    pub score: u8,
+    pub guesses_log10: f64,
+    pub warning: Option<String>,
+    pub suggestions: Vec<String>,
+}
+
+impl Strength {
+    pub fn estimate(password: &str) -> Option<Self> {
+        if password.is_empty() {
+            return None;
+        }
+        let entropy = zxcvbn(password, &[]);
+        let feedback = entropy.feedback();
+
+        Some(Self {
+            score: entropy.score().into(),
+            // Beyond what zxcvbn reads, its count would understate the search.
+            guesses_log10: if password.chars().count() > ANALYSED_CHARS {
+                brute_force_guesses_log10(password)
+            } else {
+                entropy.guesses_log10()
+            },
+            warning: feedback
+                .and_then(|feedback| feedback.warning())
+                .map(|warning| warning.to_string()),
+            suggestions: feedback
+                .map(|feedback| {
+                    feedback
+                        .suggestions()
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect()
+                })
+                .unwrap_or_default(),
+        })
+    }
+
+    pub fn is_weak(&self) -> bool {
+        self.score < 3
+    }
+}
+
+impl fmt::Display for Strength {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Score {}/4", self.score)?;
+        if let Some(warning) = &self.warning {
+            write!(f, " — {warning}")?;
+        }
+        for suggestion in &self.suggestions {
+            write!(f, " {suggestion}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Segments in [`meter`], one per score above zero.
+const SEGMENTS: u8 = 4;
+
+/// How one segment of the [`meter`] is drawn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Shade {
+    Unlit,
+    Weak,
+    Fair,
+    Good,
+    Strong,
+}
+
+/// The shade of `segment` (1 to [`SEGMENTS`]) for `score`. Score 0 still
+/// lights the first segment so the bar never looks idle.
+fn shade(score: u8, segment: u8) -> Shade {
+    if segment > score.clamp(1, SEGMENTS) {
+        return Shade::Unlit;
+    }
+    match score {
+        0 | 1 => Shade::Weak,
+        2 => Shade::Fair,
+        3 => Shade::Good,
+        _ => Shade::Strong,
+    }
+}
+
+/// A bar of [`SEGMENTS`] segments filled up to `score`, going from red to
+/// green.
+pub fn meter<'a, Message: 'a>(score: u8) -> Element<'a, Message> {
+    row((1..=SEGMENTS).map(|segment| {
+        container(Space::new(Length::Fill, 6))
+            .style(move |theme: &Theme| {
+                let palette = theme.extended_palette();
+                let color = match shade(score, segment) {
+                    Shade::Unlit => palette.background.strong.color,
+                    Shade::Weak => palette.danger.base.color,
+                    Shade::Fair => Color::from_rgb8(0xF5, 0xA9, 0x7F),
+                    Shade::Good => Color::from_rgb8(0xEE, 0xD4, 0x9F),
+                    Shade::Strong => palette.success.base.color,
+                };
+                container::Style {
+                    background: Some(color.into()),
+                    border: border::rounded(3),
+                    ..container::Style::default()
+                }
+            })
+            .into()
+    }))
+    .spacing(4)
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(password: &str) -> u8 {
+        Strength::estimate(password).unwrap().score
+    }
+
+    #[test]
+    fn known_passwords_score_where_expected() {
+        for (password, scores) in [
+            ("password", 0..=0),
+            ("123456", 0..=0),
+            ("qwerty", 0..=0),
+            ("hunter2", 0..=1),
+            ("Tr0ub4dor&3", 3..=4),
+            ("P@ssw0rd1", 0..=1),
+            ("correct horse battery staple", 3..=4),
+            ("8c1f!Gv#q29Lz@pX", 4..=4),
+        ] {
+            let score = score(password);
+            assert!(scores.contains(&score), "{password:?} scored {score}");
+        }
+    }
+
+    #[test]
+    fn weak_is_below_three() {
+        assert!(Strength::estimate("password").unwrap().is_weak());
+        assert!(
+            !Strength::estimate("correct horse battery staple")
+                .unwrap()
+                .is_weak()
+        );
+    }
+
+    #[test]
+    fn an_empty_password_has_no_estimate() {
+        assert!(Strength::estimate("").is_none());
+    }
+
+    #[test]
+    fn a_common_password_comes_with_a_warning() {
+        let strength = Strength::estimate("password").unwrap();
+        assert!(strength.warning.is_some());
+        assert!(strength.to_string().starts_with("Score 0/4 — "));
+    }
+
+    #[test]
+    fn past_what_zxcvbn_reads_the_guesses_keep_growing() {
+        let long = "correct horse battery staple ".repeat(4);
+        let longer = "correct horse battery staple ".repeat(8);
+        let guesses = |password: &str| Strength::estimate(password).unwrap().guesses_log10;
+        assert!(long.chars().count() > ANALYSED_CHARS);
+        assert!(guesses(&longer) > guesses(&long));
+    }
+
+    fn shades(score: u8) -> Vec<Shade> {
+        (1..=SEGMENTS)
+            .map(|segment| shade(score, segment))
+            .collect()
+    }
+
+    #[test]
+    fn each_score_lights_its_segments_in_its_shade() {
+        use Shade::*;
+
+        assert_eq!(shades(0), [Weak, Unlit, Unlit, Unlit]);
+        assert_eq!(shades(1), [Weak, Unlit, Unlit, Unlit]);
+        assert_eq!(shades(2), [Fair, Fair, Unlit, Unlit]);
+        assert_eq!(shades(3), [Good, Good, Good, Unlit]);
+        assert_eq!(shades(4), [Strong; 4]);
+        // zxcvbn never scores above 4, but the bar can't overflow if it did.
+        assert_eq!(shades(9), [Strong; 4]);
+    }
+}
@@ -0,0 +1,1732 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::HashMode;
+use crate::batch::BatchStats;
+use crate::compare_screen::Similarity;
+use crate::composition::Class;
+use crate::screen::Screen;
+use crate::severity::Severity;
+use crate::shortcuts::Shortcut;
+
+/// A language the UI is translated into.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Language {
+    English,
+    German,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::German];
+
+    /// The tag it's stored under in the settings file.
+    fn code(self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::German => "de",
+        }
+    }
+
+    fn from_code(code: &str) -> Option<Self> {
+        Language::ALL.into_iter().find(|language| {
+            code.get(..2)
+                .is_some_and(|prefix| prefix.eq_ignore_ascii_case(language.code()))
+        })
+    }
+
+    /// The OS locale's language, if it's one of ours, else English.
+    pub fn detect() -> Self {
+        sys_locale::get_locale()
+            .and_then(|locale| Language::from_code(&locale))
+            .unwrap_or(Language::English)
+    }
+
+    pub fn strings(self) -> &'static Strings {
+        match self {
+            Language::English => &EN,
+            Language::German => &DE,
+        }
+    }
+}
+
+impl fmt::Display for Language {
+    /// Each language by its own name, so it can be found by someone who
+    /// can't read the current one.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Language::English => "English",
+            Language::German => "Deutsch",
+        })
+    }
+}
+
+/// The language setting, stored as `"system"` or a language's tag. Anything
+/// else is taken as `"system"`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum LanguageChoice {
+    #[default]
+    System,
+    Fixed(Language),
+}
+
+impl LanguageChoice {
+    /// "System" followed by every language, for the picker.
+    pub fn all() -> Vec<Self> {
+        std::iter::once(LanguageChoice::System)
+            .chain(Language::ALL.map(LanguageChoice::Fixed))
+            .collect()
+    }
+
+    pub fn resolve(self, system: Language) -> Language {
+        match self {
+            LanguageChoice::System => system,
+            LanguageChoice::Fixed(language) => language,
+        }
+    }
+}
+
+impl From<String> for LanguageChoice {
+    fn from(code: String) -> Self {
+        Language::from_code(&code).map_or(LanguageChoice::System, LanguageChoice::Fixed)
+    }
+}
+
+impl From<LanguageChoice> for String {
+    fn from(choice: LanguageChoice) -> Self {
+        match choice {
+            LanguageChoice::System => "system".to_owned(),
+            LanguageChoice::Fixed(language) => language.code().to_owned(),
+        }
+    }
+}
+
+impl fmt::Display for LanguageChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LanguageChoice::System => f.write_str("System"),
+            LanguageChoice::Fixed(language) => language.fmt(f),
+        }
+    }
+}
+
+/// User-facing text for one language, kept apart from the views so it can be
+/// translated. Text with numbers or names in it is a function, so each
+/// language can order and pluralize it as its grammar needs.
+///
+/// A translation spells out only what it has translated and takes the rest
+/// from [`EN`] with `..EN`, so a new string shows in English until it's
+/// translated rather than going missing.
+pub struct Strings {
+    pub title: &'static str,
+    /// The window title, which screen readers announce when it changes.
+    pub window_title: &'static str,
+    pub title_checking: &'static str,
+    pub title_breached: &'static str,
+    pub title_not_found: &'static str,
+    pub title_failed: &'static str,
+    /// The tray menu, whose first item also opens the quick-check window
+    /// from the main one.
+    pub tray_quick_check: &'static str,
+    pub tray_open: &'static str,
+    pub tray_quit: &'static str,
+    pub tab: fn(Screen) -> &'static str,
+    pub what_gets_sent: &'static str,
+    pub mode_password: &'static str,
+    pub mode_account: &'static str,
+    pub mode_domain: &'static str,
+    pub mode_pin: &'static str,
+    pub settings: &'static str,
+    pub shortcuts_button: &'static str,
+    pub copy_summary: &'static str,
+    pub drop_one_file: &'static str,
+    pub drop_password_list: &'static str,
+
+    pub explainer_title: &'static str,
+    /// Paragraphs of the k-anonymity explainer.
+    pub explainer: &'static [&'static str],
+    /// Followed by the number of suffixes in the last range fetched.
+    pub explainer_last_range: &'static str,
+    pub explainer_close: &'static str,
+
+    pub shortcuts_title: &'static str,
+    pub shortcut: fn(Shortcut) -> &'static str,
+    pub close: &'static str,
+
+    pub hash: &'static str,
+    pub password_placeholder: &'static str,
+    pub hash_placeholder: fn(HashMode) -> String,
+    pub label_placeholder: &'static str,
+    pub show_password: &'static str,
+    pub hide_password: &'static str,
+    pub reveal_full_hash: &'static str,
+    pub identicon: &'static str,
+    pub copy_hash: &'static str,
+    pub copied: &'static str,
+    pub submit: &'static str,
+    pub clear: &'static str,
+    pub paste_and_check: &'static str,
+    pub clipboard_empty: &'static str,
+    pub watch_clipboard: &'static str,
+    /// Shown before watching starts, with how many minutes it lasts.
+    pub clipboard_warning: fn(u64) -> String,
+    pub start_watching: &'static str,
+    /// The minutes and seconds left.
+    pub clipboard_watching: fn(u64, u64) -> String,
+    pub stop_watching: &'static str,
+    pub clipboard_watch_ended: &'static str,
+    /// The copied password's preview, then its verdict.
+    pub clipboard_result: fn(&str, &str) -> String,
+    /// The rank, then how many common passwords are ranked.
+    pub common_password: fn(u32, usize) -> String,
+    pub pin_placeholder: &'static str,
+    /// The shortest and longest PIN.
+    pub pin_length: fn(usize, usize) -> String,
+    /// The PIN's rank and its share of all PINs, already formatted.
+    pub pin_rank: fn(u32, &str) -> String,
+    /// How many PINs the table holds.
+    pub pin_not_listed: fn(usize) -> String,
+    pub pin_breach_check: &'static str,
+    pub memory_unlocked: &'static str,
+    pub idle_cleared: &'static str,
+    /// The newer version's number.
+    pub update_available: fn(&str) -> String,
+    pub update_open: &'static str,
+    pub dismiss: &'static str,
+    pub caps_lock_on: &'static str,
+    /// The on-screen keyboard and the button in the password row that
+    /// shows it.
+    pub keypad: &'static str,
+    pub keypad_shift: &'static str,
+    pub keypad_symbols: &'static str,
+    pub keypad_letters: &'static str,
+    pub keypad_space: &'static str,
+    pub keypad_shuffle: &'static str,
+    pub keypad_close: &'static str,
+    /// Characters, then bytes when that's different.
+    pub characters: fn(usize, usize) -> String,
+    pub class: fn(Class) -> &'static str,
+    /// The flagged characters by name, comma-separated.
+    pub invisible_characters: fn(&str) -> String,
+    pub remove_invisible: &'static str,
+    pub edge_whitespace: &'static str,
+    pub trim_and_recheck: &'static str,
+    pub normalize: &'static str,
+    pub off: &'static str,
+    pub offline_mode: &'static str,
+
+    pub severity: fn(Severity) -> &'static str,
+    pub advice: fn(Severity) -> &'static str,
+    /// The tier, then the count already formatted.
+    pub found_headline: fn(Severity, &str) -> String,
+    /// The count, then the same formatted.
+    pub seen_times: fn(u64, &str) -> String,
+    pub not_found: &'static str,
+    pub not_found_headline: &'static str,
+    pub not_found_but_weak: &'static str,
+    pub not_found_safe: &'static str,
+    pub suggest_alternative: &'static str,
+    pub suggesting: &'static str,
+    pub copy: &'static str,
+    pub use_suggestion: &'static str,
+    /// How many candidates were tried.
+    pub all_suggestions_breached: fn(usize) -> String,
+    pub suggestion_failed: fn(&str) -> String,
+    /// The notification when a batch ends while the app is in the background.
+    pub audit_finished: &'static str,
+    pub audit_failed: &'static str,
+    pub audit_found: fn(usize, usize) -> String,
+    pub audit_unchecked: fn(usize) -> String,
+    pub audit_none_checked: fn(usize) -> String,
+    /// The notification when `--watch` finds a hash newly breached, or seen
+    /// more often, given how many of how many watched.
+    pub watch_found: &'static str,
+    pub watch_changed: fn(usize, usize) -> String,
+    pub error: fn(&str) -> String,
+    pub retry: &'static str,
+    pub disable_socks: &'static str,
+    pub unparsable: fn(&str) -> String,
+    pub captive_portal: &'static str,
+    pub check_endpoint: &'static str,
+    pub api_trouble: &'static str,
+    pub offline: &'static str,
+    pub rate_limited: fn(u64) -> String,
+    pub searching: &'static str,
+    pub cancel: &'static str,
+    pub search_cancelled: &'static str,
+    pub details: &'static str,
+    pub hide_details: &'static str,
+
+    /// The time, milliseconds taken, and where the answer came from.
+    pub checked: fn(&str, u128, &str) -> String,
+    pub source_network: &'static str,
+    pub source_cache: &'static str,
+    pub source_offline: &'static str,
+    pub checking_api: &'static str,
+    pub api_reachable: fn(u128) -> String,
+    pub api_unreachable: fn(&str) -> String,
+    pub padding_on: &'static str,
+    pub padding_off: &'static str,
+
+    pub theme: &'static str,
+    pub language: &'static str,
+    pub ui_scale: &'static str,
+
+    /// Where a report or export was written, or why it wasn't.
+    pub exported_to: fn(&str) -> String,
+    pub export_failed: fn(&str) -> String,
+    pub session_report: &'static str,
+    pub export_json: &'static str,
+    pub export_csv: &'static str,
+    pub plaintext_warning: &'static str,
+    pub include_plaintext: &'static str,
+    pub include_plaintext_dangerous: &'static str,
+    pub only_lines_shown: &'static str,
+
+    /// The request preview's toggle, after its arrow.
+    pub request_preview: &'static str,
+    pub request_offline: &'static str,
+    pub request_empty: &'static str,
+    /// How many characters of the hash stay here, then them or their mask.
+    pub kept_local: fn(usize, &str) -> String,
+    pub copy_request: &'static str,
+
+    pub cancel_download: &'static str,
+    pub download_dataset: &'static str,
+    pub verify_dataset: &'static str,
+    pub download_stopped: fn(&str) -> String,
+    pub verifying_dataset: &'static str,
+    /// The megabytes downloaded.
+    pub dataset_downloaded: fn(u64) -> String,
+    /// Prefixes done and in all, megabytes written, and failed attempts.
+    pub download_progress: fn(u32, u32, u64, u32) -> String,
+    /// Minutes left, after the progress.
+    pub download_eta: fn(u64) -> String,
+    pub download_paused: &'static str,
+    pub dataset_placeholder: &'static str,
+    pub dataset_verified: fn(&str) -> String,
+    pub dataset_verify_failed: fn(&str) -> String,
+
+    pub account_placeholder: &'static str,
+    pub check_pastes: &'static str,
+    pub domain_placeholder: &'static str,
+    pub search_domain: &'static str,
+
+    /// How often a batch line's password is reused, after its status.
+    pub batch_shared_by: fn(usize) -> String,
+    pub batch_reused_times: fn(usize) -> String,
+    pub batch_reused_sites: fn(usize) -> String,
+    pub batch_cancelled: &'static str,
+    pub batch_checking: &'static str,
+    /// Each is followed by the line's reuse, if any.
+    pub batch_breached: fn(&str) -> String,
+    pub batch_not_found: fn(&str) -> String,
+    pub batch_error: fn(&str) -> String,
+    pub policy_met: &'static str,
+    pub column_status: &'static str,
+    pub column_count: &'static str,
+    pub column_severity: &'static str,
+    pub column_policy: &'static str,
+    pub batch_prompt: &'static str,
+    pub check_all: &'static str,
+    pub import_file: &'static str,
+    pub import_hashes: fn(HashMode) -> String,
+    /// Lines done and shown, then how many were breached and failed.
+    pub batch_counts: fn(usize, usize, usize, usize) -> String,
+    pub batch_showing: fn(usize, usize) -> String,
+    /// How many passwords are reused, then on how many lines.
+    pub batch_shared_passwords: fn(usize, usize) -> String,
+    pub batch_reused_passwords: fn(usize, usize) -> String,
+    pub dump_include: &'static str,
+    pub dump_machine_accounts: &'static str,
+    pub dump_history: &'static str,
+    /// How many of how many the interrupted run had checked.
+    pub batch_interrupted: fn(usize, usize) -> String,
+    pub resume: &'static str,
+    pub start_over: &'static str,
+    pub batch_summary: &'static str,
+    /// A line without a label, by its number.
+    pub worst_line: fn(usize) -> String,
+    pub worst_more: fn(usize) -> String,
+    /// The count, how many lines share the password and what they are,
+    /// the lines themselves, and how many more there are.
+    pub worst_reused: fn(&str, usize, &str, &str, &str) -> String,
+    pub worst_accounts: &'static str,
+    pub worst_sites: &'static str,
+    pub worst_lines: &'static str,
+    /// The line, then the count.
+    pub worst_single: fn(&str, &str) -> String,
+    pub filter_placeholder: &'static str,
+    pub breached_only: &'static str,
+    pub errors_only: &'static str,
+    /// Either side of the minimum count.
+    pub seen_at_least: &'static str,
+    pub seen_at_least_times: &'static str,
+    /// The seconds left.
+    pub batch_eta: fn(u64) -> String,
+    pub batch_estimating: &'static str,
+    /// The run's progress, then the time left.
+    pub batch_progress: fn(&BatchStats, &str) -> String,
+    pub import_failed: fn(&str) -> String,
+    /// Lines in the file, the mode of the hashes if it held hashes, and
+    /// how many distinct.
+    pub import_summary: fn(usize, Option<HashMode>, usize) -> String,
+    /// How many couldn't be read, and whether the file held hashes.
+    pub import_unreadable: fn(usize, bool) -> String,
+    pub check_imported: fn(usize) -> String,
+
+    pub account_clean: fn(&str) -> String,
+    /// The account, then how many breaches it's in.
+    pub account_breached: fn(&str, usize) -> String,
+    pub domain_clean: fn(&str) -> String,
+    /// How many aliases are breached, then the domain.
+    pub domain_breached: fn(usize, &str) -> String,
+    pub sort_by_alias: &'static str,
+    pub sort_most_breaches: &'static str,
+    pub pastes_clean: fn(&str) -> String,
+    pub pastes_found: fn(&str, usize) -> String,
+    pub paste_unknown_date: &'static str,
+    pub paste_untitled: &'static str,
+    /// The title, source, id and date, then how many addresses it held.
+    pub paste: fn(&str, &str, &str, &str, u64) -> String,
+    /// The milliseconds taken, then where the answer came from.
+    pub checked_via: fn(u128, &str) -> String,
+    pub via_memory: &'static str,
+    pub via_disk: &'static str,
+    pub via_snapshot: fn(&str) -> String,
+    pub history_title: &'static str,
+    pub clear_history: &'static str,
+    pub detail_url: &'static str,
+    pub detail_status: &'static str,
+    pub detail_kind: &'static str,
+    pub detail_attempts: &'static str,
+    pub caused_by: &'static str,
+    /// How many suffixes, then the prefix they share.
+    pub range_suffixes: fn(usize, &str) -> String,
+    /// The highest and the median count, already formatted.
+    pub range_spread: fn(&str, &str) -> String,
+    pub range_rank: fn(usize, usize) -> String,
+    pub range_padding: fn(usize) -> String,
+    pub range_missing: &'static str,
+
+    pub catalog_loading: &'static str,
+    pub catalog_failed: fn(&str) -> String,
+    pub catalog_accounts: fn(u64) -> String,
+    pub catalog_unverified: &'static str,
+    pub catalog_filter: &'static str,
+    pub sort_newest: &'static str,
+    pub sort_largest: &'static str,
+    /// How many match, of how many.
+    pub catalog_count: fn(usize, usize) -> String,
+
+    /// Whether the system is dark, then the theme in use.
+    pub system_theme: fn(bool, &str) -> String,
+    pub system_theme_failed: fn(&str, &str) -> String,
+    pub light: &'static str,
+    pub dark: &'static str,
+    pub save_failed: fn(&str) -> String,
+    pub window_remembered: &'static str,
+    pub reset_window: &'static str,
+    pub quick_check_tooltip: &'static str,
+    pub minimize_to_tray: &'static str,
+    pub tray_failed: fn(&str) -> String,
+    pub api_key_heading: &'static str,
+    pub save: &'static str,
+    pub recheck: &'static str,
+    pub check: &'static str,
+    pub delete: &'static str,
+    /// The variable that sets it.
+    pub api_key_from_env: fn(&str) -> String,
+    pub api_key_error: fn(&str) -> String,
+    pub plain_keystore: &'static str,
+    pub checking_subscription: &'static str,
+    /// The day it ran out.
+    pub subscription_expired: fn(&str) -> String,
+    pub subscription_expiring: fn(i64) -> String,
+    /// The plan, its lookups a minute, and the day it runs out.
+    pub subscription_plan: fn(&str, u32, &str) -> String,
+    pub invalid_proxy: fn(&str) -> String,
+    pub invalid_endpoint: fn(&str) -> String,
+    pub plain_http: &'static str,
+    /// The endpoint, then the variable that set it.
+    pub using_endpoint_from_env: fn(&str, &str) -> String,
+    pub using_endpoint: fn(&str) -> String,
+    pub endpoint_heading: fn(&str) -> String,
+    pub proxy_heading: &'static str,
+    pub proxy_placeholder: &'static str,
+    pub set_by: fn(&str) -> String,
+    pub proxy_username: &'static str,
+    pub proxy_password: &'static str,
+    pub use_socks: &'static str,
+    pub summary_full_hash: &'static str,
+    pub always_trim: &'static str,
+    pub clear_clipboard_after_paste: &'static str,
+    pub clipboard_watch_limit: &'static str,
+    pub auto_check: &'static str,
+    pub idle_clear: &'static str,
+    pub notify_batch: &'static str,
+    pub decoys: &'static str,
+    /// How many decoys, then the kilobytes they cost.
+    pub decoy_cost: fn(usize, usize) -> String,
+    pub severity_from: &'static str,
+    /// A tier, as the threshold it starts at.
+    pub threshold: fn(Severity) -> &'static str,
+    pub occurrences: &'static str,
+    pub clear_cache: &'static str,
+    pub cache_cleared: &'static str,
+    pub clear_cache_failed: fn(&str) -> String,
+    pub diagnostics: &'static str,
+    pub reset: &'static str,
+    pub check_updates: &'static str,
+    pub check_now: &'static str,
+    pub checking_updates: &'static str,
+    pub up_to_date: fn(&str) -> String,
+    pub update_failed: fn(&str) -> String,
+    pub debug_log: &'static str,
+    pub debug_log_dir: fn(&str) -> String,
+    pub no_data_dir: &'static str,
+    pub no_host: &'static str,
+
+    pub compare_intro: &'static str,
+    pub old_password: &'static str,
+    pub new_password: &'static str,
+    pub show_passwords: &'static str,
+    pub check_both: &'static str,
+    pub old: &'static str,
+    pub new: &'static str,
+    /// Each follows the password's label.
+    pub compare_checking: fn(&str) -> String,
+    pub compare_breached: fn(&str, &str) -> String,
+    pub compare_not_found: fn(&str) -> String,
+    pub compare_failed: fn(&str, &str) -> String,
+    pub similarity: fn(Similarity) -> &'static str,
+
+    pub kind_characters: &'static str,
+    pub kind_passphrase: &'static str,
+    pub entropy: fn(f64) -> String,
+    pub use_in_checker: &'static str,
+    pub generate: &'static str,
+    pub length: fn(u8) -> String,
+    pub avoid_ambiguous: &'static str,
+    pub exclude_placeholder: &'static str,
+    pub words: fn(u8) -> String,
+    pub separator: &'static str,
+    pub capitalize: &'static str,
+    pub append_digit: &'static str,
+
+    /// A column without a name, by its number.
+    pub mapping_column: fn(usize) -> String,
+    pub mapping_no_label: &'static str,
+    pub mapping_intro: &'static str,
+    pub delimiter: &'static str,
+    pub mapping_header: &'static str,
+    pub show_values: &'static str,
+    pub choose_column: &'static str,
+    pub label: &'static str,
+    pub read_file: &'static str,
+}
+
+pub const EN: Strings = Strings {
+    title: "Is this password in a data breach?",
+    window_title: "Password databreach checker",
+    tray_quick_check: "Quick check…",
+    tray_open: "Open main window",
+    tray_quit: "Quit",
+    title_checking: "Checking…",
+    title_breached: "Breached",
+    title_not_found: "Not found",
+    title_failed: "Check failed",
+    tab: |screen| match screen {
+        Screen::Check => "Check",
+        Screen::Batch => "Batch audit",
+        Screen::Compare => "Compare",
+        Screen::Generator => "Generator",
+        Screen::Breaches => "Breach catalog",
+        Screen::Settings => "Settings",
+    },
+    what_gets_sent: "What gets sent?",
+    mode_password: "Password",
+    mode_account: "Email account",
+    mode_domain: "Domain",
+    mode_pin: "PIN",
+    settings: "Settings",
+    shortcuts_button: "Shortcuts (F1)",
+    copy_summary: "Copy summary",
+    drop_one_file: "Drop one file at a time",
+    drop_password_list: "Drop a .txt password list to check it",
+
+    explainer_title: "What leaves this computer?",
+    explainer: &[
+        "Your password is turned into a hash, a fingerprint that can't be reversed, \
+         on this computer.",
+        "Only the first five characters of that hash are sent. The service replies with \
+         every known breached hash starting with them, and the app looks for yours in the \
+         reply here.",
+        "Your password and its full hash never leave the machine. Each five-character \
+         prefix is shared by around 800 to 1,000 known hashes, so the service can't tell \
+         which one, if any, is yours.",
+    ],
+    explainer_last_range: "The reply for your last check held this many hashes:",
+    explainer_close: "Got it",
+
+    shortcuts_title: "Keyboard shortcuts",
+    shortcut: |shortcut| match shortcut {
+        Shortcut::Submit => "Check, from anywhere in the window",
+        Shortcut::FocusPassword => "Go to the password field",
+        Shortcut::CopySummary => "Copy the result summary",
+        Shortcut::OpenSettings => "Open the settings",
+        Shortcut::ToggleShowPassword => "Show or hide the password",
+        Shortcut::Escape => "Clear, or close what's open",
+        Shortcut::Help => "Show or hide this list",
+        Shortcut::Screen(_) => "Switch to the first to sixth tab",
+        Shortcut::FocusNext | Shortcut::FocusPrevious => "Move to the next or previous field",
+        Shortcut::ScrollToTop | Shortcut::ScrollToBottom => {
+            "Jump to the top or bottom of the batch audit results"
+        }
+    },
+    close: "Close",
+
+    hash: "Hash",
+    password_placeholder: "input password",
+    hash_placeholder: |mode| format!("{mode} hash"),
+    label_placeholder: "label (optional)",
+    show_password: "Show password (Ctrl+H)",
+    hide_password: "Hide password (Ctrl+H)",
+    reveal_full_hash: "Reveal full hash",
+    identicon: "The same password always draws the same picture",
+    copy_hash: "Copy hash",
+    copied: "Copied!",
+    submit: "Submit",
+    clear: "Clear",
+    paste_and_check: "Paste & check",
+    clipboard_empty: "There's no text on the clipboard to check.",
+    watch_clipboard: "Watch clipboard…",
+    clipboard_warning: |minutes| {
+        format!(
+            "While the clipboard is watched, the app reads everything you copy, from any app, \
+             every second. Anything that looks like a password is hashed and checked, just as \
+             if you'd typed it. Watching stops by itself after {minutes} {}.",
+            if minutes == 1 { "minute" } else { "minutes" }
+        )
+    },
+    start_watching: "Start watching",
+    clipboard_watching: |minutes, seconds| {
+        format!(
+            "Watching the clipboard: everything you copy is read and may be checked. \
+             Stops in {minutes}:{seconds:02}."
+        )
+    },
+    stop_watching: "Stop watching",
+    clipboard_watch_ended: "Stopped watching the clipboard.",
+    clipboard_result: |preview, verdict| format!("Copied {preview}: {verdict}"),
+    common_password: |rank, total| {
+        format!("Extremely common password: #{rank} of the {total} most used")
+    },
+    pin_placeholder: "4 to 8 digits",
+    pin_length: |min, max| format!("A PIN has {min} to {max} digits"),
+    pin_rank: |rank, share| {
+        format!("This is the #{rank} most common PIN, about {share} of all PINs")
+    },
+    pin_not_listed: |total| format!("Not among the {total} most common 4-digit PINs"),
+    pin_breach_check: "Also check it against Pwned Passwords",
+    memory_unlocked: "Memory locking unavailable, the password could be swapped to disk",
+    idle_cleared: "Cleared for your security after a period of inactivity.",
+    update_available: |version| format!("Version {version} is available"),
+    update_open: "Release page",
+    dismiss: "Dismiss",
+    caps_lock_on: "Caps Lock is on",
+    keypad: "On-screen keyboard",
+    keypad_shift: "Shift",
+    keypad_symbols: "#+=",
+    keypad_letters: "ABC",
+    keypad_space: "Space",
+    keypad_shuffle: "Shuffle keys",
+    keypad_close: "Close",
+    characters: |chars, bytes| match (chars, bytes) {
+        (1, 1) => "1 character".to_owned(),
+        (chars, bytes) if chars == bytes => format!("{chars} characters"),
+        (1, bytes) => format!("1 character ({bytes} bytes)"),
+        (chars, bytes) => format!("{chars} characters ({bytes} bytes)"),
+    },
+    class: |class| match class {
+        Class::Lower => "lowercase",
+        Class::Upper => "uppercase",
+        Class::Digit => "digits",
+        Class::Symbol => "symbols",
+        Class::Space => "spaces",
+        Class::Other => "other",
+    },
+    invisible_characters: |names| format!("Contains invisible characters: {names}"),
+    remove_invisible: "Remove them",
+    edge_whitespace: "The password starts or ends with whitespace, which is easy to paste by \
+                      accident",
+    trim_and_recheck: "Trim and re-check",
+    normalize: "Normalize:",
+    off: "Off",
+    offline_mode: "Offline mode (local dataset file)",
+
+    severity: |severity| match severity {
+        Severity::Low => "Low",
+        Severity::Moderate => "Moderate",
+        Severity::High => "High",
+        Severity::Critical => "Critical",
+    },
+    advice: |severity| match severity {
+        Severity::Low => "It's rare, but attackers replay every leaked password.",
+        Severity::Moderate => "It's in the lists attackers try. Change it.",
+        Severity::High => "It's among the first guesses in any attack. Change it now.",
+        Severity::Critical => {
+            "It's one of the most common leaked passwords. Change it everywhere you use it."
+        }
+    },
+    found_headline: |severity, count| {
+        format!(
+            "{} risk: seen {count} times in known data breaches",
+            (EN.severity)(severity)
+        )
+    },
+    seen_times: |count, formatted| match count {
+        1 => "seen once".to_owned(),
+        _ => format!("seen {formatted} times"),
+    },
+    not_found: "not found",
+    not_found_headline: "Not found in known data breaches",
+    not_found_but_weak: "But it's easy to guess, so it's not a good choice.",
+    not_found_safe: "It seems this password is safe to use.",
+    suggest_alternative: "Suggest a safe alternative",
+    suggesting: "Generating an alternative and checking it isn't breached…",
+    copy: "Copy",
+    use_suggestion: "Check this instead",
+    all_suggestions_breached: |attempts| {
+        format!(
+            "All {attempts} alternatives tried were breached, which is very unlikely. Try again."
+        )
+    },
+    suggestion_failed: |error| format!("The alternative couldn't be checked: {error}"),
+    audit_finished: "Audit finished",
+    audit_failed: "Audit failed",
+    audit_found: |breached, total| {
+        format!(
+            "{breached} of {total} {} found in breaches",
+            if total == 1 { "password" } else { "passwords" }
+        )
+    },
+    audit_unchecked: |errored| format!("; {errored} couldn't be checked"),
+    audit_none_checked: |total| {
+        format!(
+            "None of the {total} {} could be checked",
+            if total == 1 { "password" } else { "passwords" }
+        )
+    },
+    watch_found: "New breach findings",
+    watch_changed: |changed, total| {
+        format!(
+            "{changed} of {total} watched {} newly found or seen more often",
+            if total == 1 { "password" } else { "passwords" }
+        )
+    },
+    error: |error| format!("Error: {error}"),
+    retry: "Retry",
+    disable_socks: "Disable SOCKS5 routing",
+    unparsable: |error| format!("The response could not be parsed ({error})."),
+    captive_portal: "A proxy or captive portal may be answering in place of the API.",
+    check_endpoint: "Check the API endpoint in the settings.",
+    api_trouble: "The Pwned Passwords API is having trouble, try again later.",
+    offline: "You appear to be offline. Check your connection and try again.",
+    rate_limited: |seconds| format!("Rate limited, retrying in {seconds}s..."),
+    searching: "Searching",
+    cancel: "Cancel",
+    search_cancelled: "Check cancelled",
+    details: "Details",
+    hide_details: "Hide details",
+
+    checked: |time, millis, source| format!("Checked {time} · {millis} ms · {source}"),
+    source_network: "network",
+    source_cache: "cache",
+    source_offline: "offline file",
+    checking_api: "Checking API...",
+    api_reachable: |millis| format!("API reachable ({millis} ms)"),
+    api_unreachable: |error| format!("API unreachable: {error}"),
+    padding_on: "padding on",
+    padding_off: "padding off",
+
+    theme: "Theme",
+    language: "Language",
+    ui_scale: "UI scale",
+
+    exported_to: |path| format!("Exported to {path}"),
+    export_failed: |error| format!("Could not export: {error}"),
+    session_report: "Session report:",
+    export_json: "Export JSON…",
+    export_csv: "Export CSV…",
+    plaintext_warning: "The file will hold every password in the clear. Anyone who can read it \
+                        can use them.",
+    include_plaintext: "Include plaintext",
+    include_plaintext_dangerous: "Include plaintext (dangerous)",
+    only_lines_shown: "Only the lines shown",
+
+    request_preview: "What gets sent",
+    request_offline: "Nothing: offline mode looks the hash up in the local dataset.",
+    request_empty: "Nothing yet. Type a password to see the request.",
+    kept_local: |kept, hash| format!("Kept local, {kept} characters: {hash}"),
+    copy_request: "Copy request",
+
+    cancel_download: "Cancel download",
+    download_dataset: "Download dataset",
+    verify_dataset: "Verify",
+    download_stopped: |error| format!("Download stopped: {error}"),
+    verifying_dataset: "Checking the dataset against its manifest…",
+    dataset_downloaded: |megabytes| format!("Dataset downloaded ({megabytes} MB)"),
+    download_progress: |done, total, megabytes, failures| {
+        format!("{done} / {total} prefixes, {megabytes} MB written, {failures} failed attempt(s)")
+    },
+    download_eta: |minutes| format!(", about {minutes} min left"),
+    download_paused: " (paused, download again to resume)",
+    dataset_placeholder: "path to the ordered-by-hash Pwned Passwords file",
+    dataset_verified: |verification| format!("Verified: {verification}"),
+    dataset_verify_failed: |error| format!("Couldn't verify: {error}"),
+
+    account_placeholder: "email address",
+    check_pastes: "Check pastes",
+    domain_placeholder: "verified domain, e.g. example.com",
+    search_domain: "Search domain",
+
+    batch_shared_by: |users| format!(", shared by {users} users"),
+    batch_reused_times: |times| format!(", reused {times} times"),
+    batch_reused_sites: |sites| format!(", reused on {sites} sites"),
+    batch_cancelled: "not checked (cancelled)",
+    batch_checking: "checking…",
+    batch_breached: |reused| format!("breached{reused}"),
+    batch_not_found: |reused| format!("not found{reused}"),
+    batch_error: |error| format!("error: {error}"),
+    policy_met: "meets",
+    column_status: "Status",
+    column_count: "Count",
+    column_severity: "Severity",
+    column_policy: "Policy",
+    batch_prompt: "One password per line:",
+    check_all: "Check all",
+    import_file: "Import file…",
+    import_hashes: |mode| format!("Import {mode} hashes…"),
+    batch_counts: |done, total, breached, failed| {
+        format!("{done}/{total} checked, {breached} breached, {failed} failed")
+    },
+    batch_showing: |shown, total| format!("showing {shown} of {total}"),
+    batch_shared_passwords: |passwords, users| {
+        format!("{passwords} passwords shared by {users} users")
+    },
+    batch_reused_passwords: |passwords, lines| {
+        format!("{passwords} passwords reused on {lines} lines")
+    },
+    dump_include: "From Active Directory dumps, also import",
+    dump_machine_accounts: "machine accounts",
+    dump_history: "password history",
+    batch_interrupted: |done, total| {
+        format!("An interrupted run of this batch had checked {done} of {total}.")
+    },
+    resume: "Resume",
+    start_over: "Start over",
+    batch_summary: "Batch summary",
+    worst_line: |number| format!("line {number}"),
+    worst_more: |more| format!(" and {more} more"),
+    worst_reused: |count, used, what, lines, more| {
+        format!(
+            "Worst finding: one password, seen {count} times, is used on {used} {what}: \
+             {lines}{more}"
+        )
+    },
+    worst_accounts: "accounts",
+    worst_sites: "sites",
+    worst_lines: "lines",
+    worst_single: |line, count| format!("Worst finding: {line}, seen {count} times"),
+    filter_placeholder: "Filter by line, label or preview",
+    breached_only: "Breached only",
+    errors_only: "Errors only",
+    seen_at_least: "Seen at least",
+    seen_at_least_times: "times",
+    batch_eta: |seconds| format!("about {seconds}s left"),
+    batch_estimating: "estimating time left",
+    batch_progress: |stats, eta| {
+        format!(
+            "{}/{} distinct passwords: {} breached, {} clean, {} failed. \
+             {}/{} ranges fetched, {}s elapsed, {eta}",
+            stats.done,
+            stats.total,
+            stats.breached,
+            stats.clean,
+            stats.errored,
+            stats.ranges_done,
+            stats.ranges,
+            stats.elapsed.as_secs(),
+        )
+    },
+    import_failed: |error| format!("Could not import the file: {error}"),
+    import_summary: |lines, hashed, distinct| match hashed {
+        Some(mode) => format!("{lines} {mode} hashes ({distinct} distinct) in the file:"),
+        None => format!("{lines} passwords ({distinct} distinct) in the file:"),
+    },
+    import_unreadable: |unread, hashed| {
+        format!(
+            "{unread} {} could not be read and will be skipped:",
+            if hashed { "lines" } else { "rows" }
+        )
+    },
+    check_imported: |lines| format!("Check {lines} passwords"),
+
+    account_clean: |account| format!("No breaches found for {account}."),
+    account_breached: |account, breaches| format!("{account} appears in {breaches} breach(es):"),
+    domain_clean: |domain| format!("No breached aliases on {domain}."),
+    domain_breached: |aliases, domain| format!("{aliases} breached alias(es) on {domain}:"),
+    sort_by_alias: "By alias",
+    sort_most_breaches: "Most breaches",
+    pastes_clean: |account| format!("No pastes found for {account}."),
+    pastes_found: |account, pastes| format!("{account} appears in {pastes} paste(s):"),
+    paste_unknown_date: "unknown date",
+    paste_untitled: "untitled",
+    paste: |title, source, id, date, emails| {
+        format!("• {title} — {source} {id} ({date}, {emails} email(s))")
+    },
+    checked_via: |millis, via| format!("checked in {millis} ms via {via}"),
+    via_memory: "memory cache",
+    via_disk: "disk cache",
+    via_snapshot: |date| format!("the local snapshot from {date}"),
+    history_title: "This session's checks, click one to check it again",
+    clear_history: "Clear history",
+    detail_url: "URL",
+    detail_status: "Status",
+    detail_kind: "Kind",
+    detail_attempts: "Attempts",
+    caused_by: "caused by: ",
+    range_suffixes: |suffixes, prefix| format!("{suffixes} suffixes share the prefix {prefix}"),
+    range_spread: |max, median| format!("Most seen {max} time(s), median {median}"),
+    range_rank: |rank, suffixes| format!("This password ranks {rank} of {suffixes} in its range"),
+    range_padding: |padding| {
+        format!("The response included {padding} padding entries, left out of these figures")
+    },
+    range_missing: "Range statistics need the range itself, which this lookup didn't fetch.",
+
+    catalog_loading: "Loading breaches...",
+    catalog_failed: |error| format!("Could not load breaches: {error}"),
+    catalog_accounts: |accounts| format!("{accounts} accounts"),
+    catalog_unverified: " (unverified)",
+    catalog_filter: "filter breaches",
+    sort_newest: "Newest",
+    sort_largest: "Largest",
+    catalog_count: |matching, total| format!("{matching} of {total} breaches"),
+
+    system_theme: |dark, theme| {
+        format!(
+            "The system is in {} mode, so {theme} is in use",
+            if dark { "dark" } else { "light" }
+        )
+    },
+    system_theme_failed: |error, theme| {
+        format!("Could not detect the system mode ({error}), so {theme} is in use")
+    },
+    light: "light",
+    dark: "dark",
+    save_failed: |error| format!("Could not save settings: {error}"),
+    window_remembered: "Window size and position are remembered between sessions.",
+    reset_window: "Reset window layout",
+    quick_check_tooltip: "A small window with just the password field, kept on top",
+    minimize_to_tray: "Keep running in the tray when the window is closed",
+    tray_failed: |error| format!("No tray icon: {error}"),
+    api_key_heading: "HIBP API key (needed for email lookups)",
+    save: "Save",
+    recheck: "Re-check",
+    check: "Check",
+    delete: "Delete",
+    api_key_from_env: |var| format!("Set by {var}; unset it to use the saved key"),
+    api_key_error: |error| format!("API key: {error}"),
+    plain_keystore: "No system keyring available, the key is saved in a plain file",
+    checking_subscription: "Checking the subscription...",
+    subscription_expired: |until| {
+        format!("The subscription ran out on {until}; renew it to keep using these lookups")
+    },
+    subscription_expiring: |days| {
+        format!("The subscription runs out in {days} day(s); renew it before then")
+    },
+    subscription_plan: |name, rpm, until| format!("{name}: {rpm} lookups a minute, until {until}"),
+    invalid_proxy: |error| format!("Invalid proxy: {error}"),
+    invalid_endpoint: |error| format!("Invalid endpoint: {error}"),
+    plain_http: "Warning: plain HTTP endpoints send hash prefixes unencrypted",
+    using_endpoint_from_env: |endpoint, var| format!("Using {endpoint}, set by {var}"),
+    using_endpoint: |endpoint| format!("Using {endpoint}"),
+    endpoint_heading: |var| format!("Pwned Passwords endpoint (or set {var})"),
+    proxy_heading: "Proxy (leave empty to use HTTPS_PROXY / HTTP_PROXY / ALL_PROXY)",
+    proxy_placeholder: "http://, https:// or socks5:// proxy URL",
+    set_by: |var| format!("Set by {var}"),
+    proxy_username: "proxy username",
+    proxy_password: "proxy password",
+    use_socks: "Route lookups through a SOCKS5 proxy such as Tor",
+    summary_full_hash: "Include the full hash in copied summaries",
+    always_trim: "Always trim whitespace around passwords",
+    clear_clipboard_after_paste: "Empty the clipboard after Paste & check",
+    clipboard_watch_limit: "Stop watching the clipboard after",
+    auto_check: "Check automatically after typing stops for",
+    idle_clear: "Clear the password after no activity for",
+    notify_batch: "Notify me when a batch finishes while the app is in the background",
+    decoys: "Send decoy queries for random prefixes",
+    decoy_cost: |decoys, kilobytes| {
+        format!("Each lookup then downloads {decoys} extra ranges, about {kilobytes} KB more")
+    },
+    severity_from: "Severity from",
+    threshold: |severity| match severity {
+        Severity::Low => "low",
+        Severity::Moderate => "moderate",
+        Severity::High => "high",
+        Severity::Critical => "critical",
+    },
+    occurrences: "occurrences",
+    clear_cache: "Clear cache",
+    cache_cleared: "Cache cleared",
+    clear_cache_failed: |error| format!("Could not clear cache: {error}"),
+    diagnostics: "Diagnostics",
+    reset: "Reset",
+    check_updates: "Check for updates at startup",
+    check_now: "Check now",
+    checking_updates: "Checking...",
+    up_to_date: |version| format!("{version} is the latest version"),
+    update_failed: |error| format!("Could not check for updates: {error}"),
+    debug_log: "Write a debug log to file",
+    debug_log_dir: |dir| format!("In {dir}, from the next start"),
+    no_data_dir: "There's no data directory to write it to",
+    no_host: "no host",
+
+    compare_intro: "Check an old password and the one replacing it together.",
+    old_password: "Old password",
+    new_password: "New password",
+    show_passwords: "Show passwords",
+    check_both: "Check both",
+    old: "Old",
+    new: "New",
+    compare_checking: |label| format!("{label}: checking…"),
+    compare_breached: |label, count| format!("{label}: breached {count} time(s)"),
+    compare_not_found: |label| format!("{label}: not found"),
+    compare_failed: |label, error| format!("{label}: lookup failed ({error})"),
+    similarity: |similarity| match similarity {
+        Similarity::Identical => "The new password is the same as the old one.",
+        Similarity::Case => {
+            "The new password only changes the case of the old one, which is among the first \
+             things tried with a leaked password."
+        }
+        Similarity::TrailingDigits => {
+            "The new password only changes the digits at the end of the old one, which is among \
+             the first things tried with a leaked password."
+        }
+        Similarity::OneCharacter => "The new password differs from the old one by one character.",
+    },
+
+    kind_characters: "Characters",
+    kind_passphrase: "Passphrase",
+    entropy: |bits| format!("≈ {bits:.1} bits of entropy"),
+    use_in_checker: "Use this in the checker",
+    generate: "Generate",
+    length: |length| format!("Length {length}"),
+    avoid_ambiguous: "Avoid ambiguous characters",
+    exclude_placeholder: "exclude characters",
+    words: |words| format!("{words} words"),
+    separator: "Separator",
+    capitalize: "Capitalize words",
+    append_digit: "Append a digit",
+
+    mapping_column: |number| format!("Column {number}"),
+    mapping_no_label: "None (by line number)",
+    mapping_intro: "The file's columns aren't those of a known export. Say which holds the \
+                    password, and the file is read that way; with a header row, exports like it \
+                    are read the same way next time.",
+    delimiter: "Delimiter",
+    mapping_header: "The first row names the columns",
+    show_values: "Show values",
+    choose_column: "Choose a column",
+    label: "Label",
+    read_file: "Read the file",
+};
+
+pub const DE: Strings = Strings {
+    title: "Ist dieses Passwort in einem Datenleck?",
+    window_title: "Datenleck-Prüfung für Passwörter",
+    tray_quick_check: "Schnellprüfung …",
+    tray_open: "Hauptfenster öffnen",
+    tray_quit: "Beenden",
+    title_checking: "Wird geprüft …",
+    title_breached: "In Datenleck",
+    title_not_found: "Nicht gefunden",
+    title_failed: "Prüfung fehlgeschlagen",
+    tab: |screen| match screen {
+        Screen::Check => "Prüfen",
+        Screen::Batch => "Listenprüfung",
+        Screen::Compare => "Vergleichen",
+        Screen::Generator => "Generator",
+        Screen::Breaches => "Datenleck-Katalog",
+        Screen::Settings => "Einstellungen",
+    },
+    what_gets_sent: "Was wird gesendet?",
+    mode_password: "Passwort",
+    mode_account: "E-Mail-Konto",
+    mode_domain: "Domain",
+    mode_pin: "PIN",
+    settings: "Einstellungen",
+    shortcuts_button: "Tastenkürzel (F1)",
+    copy_summary: "Zusammenfassung kopieren",
+    drop_one_file: "Bitte nur eine Datei auf einmal ablegen",
+    drop_password_list: "Eine .txt-Passwortliste ablegen, um sie zu prüfen",
+
+    explainer_title: "Was verlässt diesen Computer?",
+    explainer: &[
+        "Dein Passwort wird auf diesem Computer in einen Hash umgewandelt, einen \
+         Fingerabdruck, der sich nicht umkehren lässt.",
+        "Nur die ersten fünf Zeichen dieses Hashes werden gesendet. Der Dienst antwortet \
+         mit allen bekannten geleakten Hashes, die damit beginnen, und die App sucht \
+         deinen hier in der Antwort.",
+        "Dein Passwort und sein vollständiger Hash verlassen den Rechner nie. Jedes \
+         fünfstellige Präfix teilen sich etwa 800 bis 1.000 bekannte Hashes, daher kann \
+         der Dienst nicht erkennen, welcher davon deiner ist, falls überhaupt.",
+    ],
+    explainer_last_range: "So viele Hashes enthielt die Antwort auf deine letzte Prüfung:",
+    explainer_close: "Verstanden",
+
+    shortcuts_title: "Tastenkürzel",
+    shortcut: |shortcut| match shortcut {
+        Shortcut::Submit => "Prüfen, von überall im Fenster",
+        Shortcut::FocusPassword => "Zum Passwortfeld springen",
+        Shortcut::CopySummary => "Zusammenfassung des Ergebnisses kopieren",
+        Shortcut::OpenSettings => "Einstellungen öffnen",
+        Shortcut::ToggleShowPassword => "Passwort zeigen oder verbergen",
+        Shortcut::Escape => "Leeren oder Geöffnetes schließen",
+        Shortcut::Help => "Diese Liste zeigen oder verbergen",
+        Shortcut::Screen(_) => "Zum ersten bis sechsten Tab wechseln",
+        Shortcut::FocusNext | Shortcut::FocusPrevious => "Zum nächsten oder vorigen Feld",
+        Shortcut::ScrollToTop | Shortcut::ScrollToBottom => {
+            "Zum Anfang oder Ende der Listenprüfung springen"
+        }
+    },
+    close: "Schließen",
+
+    hash: "Hash",
+    password_placeholder: "Passwort eingeben",
+    hash_placeholder: |mode| format!("{mode}-Hash"),
+    label_placeholder: "Bezeichnung (optional)",
+    show_password: "Passwort zeigen (Strg+H)",
+    hide_password: "Passwort verbergen (Strg+H)",
+    reveal_full_hash: "Ganzen Hash zeigen",
+    identicon: "Dasselbe Passwort ergibt immer dasselbe Bild",
+    copy_hash: "Hash kopieren",
+    copied: "Kopiert!",
+    submit: "Prüfen",
+    clear: "Leeren",
+    paste_and_check: "Einfügen & prüfen",
+    clipboard_empty: "In der Zwischenablage ist kein Text zum Prüfen.",
+    watch_clipboard: "Zwischenablage überwachen …",
+    clipboard_warning: |minutes| {
+        format!(
+            "Solange die Zwischenablage überwacht wird, liest die App jede Sekunde alles, was du \
+             kopierst, aus jeder App. Was wie ein Passwort aussieht, wird gehasht und geprüft, \
+             als hättest du es eingegeben. Die Überwachung endet von selbst nach {minutes} {}.",
+            if minutes == 1 { "Minute" } else { "Minuten" }
+        )
+    },
+    start_watching: "Überwachung starten",
+    clipboard_watching: |minutes, seconds| {
+        format!(
+            "Die Zwischenablage wird überwacht: Alles, was du kopierst, wird gelesen und \
+             eventuell geprüft. Endet in {minutes}:{seconds:02}."
+        )
+    },
+    stop_watching: "Überwachung beenden",
+    clipboard_watch_ended: "Die Zwischenablage wird nicht mehr überwacht.",
+    clipboard_result: |preview, verdict| format!("Kopiert {preview}: {verdict}"),
+    common_password: |rank, total| {
+        format!("Extrem häufiges Passwort: Platz {rank} der {total} meistgenutzten")
+    },
+    pin_placeholder: "4 bis 8 Ziffern",
+    pin_length: |min, max| format!("Eine PIN hat {min} bis {max} Ziffern"),
+    pin_rank: |rank, share| format!("Platz {rank} der häufigsten PINs, etwa {share} aller PINs"),
+    pin_not_listed: |total| format!("Nicht unter den {total} häufigsten vierstelligen PINs"),
+    pin_breach_check: "Auch mit Pwned Passwords abgleichen",
+    memory_unlocked: "Speicher lässt sich nicht sperren, das Passwort könnte auf die Festplatte \
+                      ausgelagert werden",
+    idle_cleared: "Zu deiner Sicherheit nach längerer Inaktivität geleert.",
+    update_available: |version| format!("Version {version} ist verfügbar"),
+    update_open: "Zur Release-Seite",
+    dismiss: "Ausblenden",
+    caps_lock_on: "Die Feststelltaste ist aktiv",
+    keypad: "Bildschirmtastatur",
+    keypad_shift: "Umschalt",
+    keypad_symbols: "#+=",
+    keypad_letters: "ABC",
+    keypad_space: "Leerzeichen",
+    keypad_shuffle: "Tasten mischen",
+    keypad_close: "Schließen",
+    characters: |chars, bytes| match (chars, bytes) {
+        (1, 1) => "1 Zeichen".to_owned(),
+        (chars, bytes) if chars == bytes => format!("{chars} Zeichen"),
+        (chars, bytes) => format!("{chars} Zeichen ({bytes} Byte)"),
+    },
+    class: |class| match class {
+        Class::Lower => "Kleinbuchstaben",
+        Class::Upper => "Großbuchstaben",
+        Class::Digit => "Ziffern",
+        Class::Symbol => "Sonderzeichen",
+        Class::Space => "Leerzeichen",
+        Class::Other => "Sonstige",
+    },
+    invisible_characters: |names| format!("Enthält unsichtbare Zeichen: {names}"),
+    remove_invisible: "Entfernen",
+    edge_whitespace: "Das Passwort beginnt oder endet mit Leerraum, der leicht versehentlich \
+                      mitkopiert wird",
+    trim_and_recheck: "Kürzen und neu prüfen",
+    normalize: "Normalisieren:",
+    off: "Aus",
+    offline_mode: "Offline-Modus (lokale Datensatzdatei)",
+
+    severity: |severity| match severity {
+        Severity::Low => "Geringes",
+        Severity::Moderate => "Mittleres",
+        Severity::High => "Hohes",
+        Severity::Critical => "Kritisches",
+    },
+    advice: |severity| match severity {
+        Severity::Low => "Es ist selten, aber Angreifer probieren jedes geleakte Passwort aus.",
+        Severity::Moderate => "Es steht in den Listen, die Angreifer durchprobieren. Ändere es.",
+        Severity::High => "Es gehört bei jedem Angriff zu den ersten Versuchen. Ändere es sofort.",
+        Severity::Critical => {
+            "Es ist eines der häufigsten geleakten Passwörter. Ändere es überall, wo du es \
+             verwendest."
+        }
+    },
+    found_headline: |severity, count| {
+        format!(
+            "{} Risiko: {count}-mal in bekannten Datenlecks gefunden",
+            (DE.severity)(severity)
+        )
+    },
+    seen_times: |count, formatted| match count {
+        1 => "einmal gefunden".to_owned(),
+        _ => format!("{formatted}-mal gefunden"),
+    },
+    not_found: "nicht gefunden",
+    not_found_headline: "In keinem bekannten Datenleck gefunden",
+    not_found_but_weak: "Aber es ist leicht zu erraten und daher keine gute Wahl.",
+    not_found_safe: "Dieses Passwort scheint sicher zu sein.",
+    suggest_alternative: "Sichere Alternative vorschlagen",
+    suggesting: "Alternative wird erzeugt und auf Datenlecks geprüft …",
+    copy: "Kopieren",
+    use_suggestion: "Stattdessen prüfen",
+    all_suggestions_breached: |attempts| {
+        format!(
+            "Alle {attempts} versuchten Alternativen waren in Datenlecks, was sehr unwahrscheinlich ist. Bitte erneut versuchen."
+        )
+    },
+    suggestion_failed: |error| format!("Die Alternative konnte nicht geprüft werden: {error}"),
+    audit_finished: "Prüfung abgeschlossen",
+    audit_failed: "Prüfung fehlgeschlagen",
+    audit_found: |breached, total| {
+        format!(
+            "{breached} von {total} {} in Datenlecks gefunden",
+            if total == 1 {
+                "Passwort"
+            } else {
+                "Passwörtern"
+            }
+        )
+    },
+    audit_unchecked: |errored| format!("; {errored} konnten nicht geprüft werden"),
+    audit_none_checked: |total| {
+        format!(
+            "Keines der {total} {} konnte geprüft werden",
+            if total == 1 {
+                "Passwort"
+            } else {
+                "Passwörter"
+            }
+        )
+    },
+    watch_found: "Neue Funde in Datenlecks",
+    watch_changed: |changed, total| {
+        format!(
+            "{changed} von {total} beobachteten {} neu gefunden oder häufiger gesehen",
+            if total == 1 {
+                "Passwort"
+            } else {
+                "Passwörtern"
+            }
+        )
+    },
+    error: |error| format!("Fehler: {error}"),
+    retry: "Erneut versuchen",
+    disable_socks: "SOCKS5-Weiterleitung ausschalten",
+    unparsable: |error| format!("Die Antwort konnte nicht gelesen werden ({error})."),
+    captive_portal: "Vielleicht antwortet ein Proxy oder ein Captive Portal anstelle der API.",
+    check_endpoint: "Prüfe den API-Endpunkt in den Einstellungen.",
+    api_trouble: "Die Pwned-Passwords-API hat Probleme, versuche es später erneut.",
+    offline: "Du scheinst offline zu sein. Prüfe deine Verbindung und versuche es erneut.",
+    rate_limited: |seconds| format!("Anfragelimit erreicht, neuer Versuch in {seconds} s..."),
+    searching: "Suche läuft",
+    cancel: "Abbrechen",
+    search_cancelled: "Prüfung abgebrochen",
+    details: "Details",
+    hide_details: "Details verbergen",
+
+    checked: |time, millis, source| format!("Geprüft {time} · {millis} ms · {source}"),
+    source_network: "Netzwerk",
+    source_cache: "Cache",
+    source_offline: "Offline-Datei",
+    checking_api: "API wird geprüft...",
+    api_reachable: |millis| format!("API erreichbar ({millis} ms)"),
+    api_unreachable: |error| format!("API nicht erreichbar: {error}"),
+    padding_on: "Padding an",
+    padding_off: "Padding aus",
+
+    theme: "Design",
+    language: "Sprache",
+    ui_scale: "Skalierung",
+
+    exported_to: |path| format!("Exportiert nach {path}"),
+    export_failed: |error| format!("Export fehlgeschlagen: {error}"),
+    session_report: "Sitzungsbericht:",
+    export_json: "Als JSON exportieren …",
+    export_csv: "Als CSV exportieren …",
+    plaintext_warning: "Die Datei enthält jedes Passwort im Klartext. Wer sie lesen kann, kann \
+                        sie verwenden.",
+    include_plaintext: "Klartext einschließen",
+    include_plaintext_dangerous: "Klartext einschließen (gefährlich)",
+    only_lines_shown: "Nur die angezeigten Zeilen",
+
+    request_preview: "Was gesendet wird",
+    request_offline: "Nichts: Im Offline-Modus wird der Hash im lokalen Datensatz nachgeschlagen.",
+    request_empty: "Noch nichts. Gib ein Passwort ein, um die Anfrage zu sehen.",
+    kept_local: |kept, hash| format!("Bleibt hier, {kept} Zeichen: {hash}"),
+    copy_request: "Anfrage kopieren",
+
+    cancel_download: "Download abbrechen",
+    download_dataset: "Datensatz herunterladen",
+    verify_dataset: "Überprüfen",
+    download_stopped: |error| format!("Download angehalten: {error}"),
+    verifying_dataset: "Der Datensatz wird mit seinem Manifest abgeglichen …",
+    dataset_downloaded: |megabytes| format!("Datensatz heruntergeladen ({megabytes} MB)"),
+    download_progress: |done, total, megabytes, failures| {
+        format!(
+            "{done} / {total} Präfixe, {megabytes} MB geschrieben, {failures} fehlgeschlagene \
+             Versuche"
+        )
+    },
+    download_eta: |minutes| format!(", noch etwa {minutes} min"),
+    download_paused: " (angehalten, lade erneut herunter, um fortzusetzen)",
+    dataset_placeholder: "Pfad zur nach Hash sortierten Pwned-Passwords-Datei",
+    dataset_verified: |verification| format!("Überprüft: {verification}"),
+    dataset_verify_failed: |error| format!("Überprüfung nicht möglich: {error}"),
+
+    account_placeholder: "E-Mail-Adresse",
+    check_pastes: "Pastes prüfen",
+    domain_placeholder: "verifizierte Domain, z. B. example.com",
+    search_domain: "Domain durchsuchen",
+
+    batch_shared_by: |users| format!(", von {users} Nutzern geteilt"),
+    batch_reused_times: |times| format!(", {times}-mal wiederverwendet"),
+    batch_reused_sites: |sites| format!(", auf {sites} Seiten wiederverwendet"),
+    batch_cancelled: "nicht geprüft (abgebrochen)",
+    batch_checking: "wird geprüft …",
+    batch_breached: |reused| format!("in Datenlecks{reused}"),
+    batch_not_found: |reused| format!("nicht gefunden{reused}"),
+    batch_error: |error| format!("Fehler: {error}"),
+    policy_met: "erfüllt",
+    column_status: "Status",
+    column_count: "Anzahl",
+    column_severity: "Schweregrad",
+    column_policy: "Richtlinie",
+    batch_prompt: "Ein Passwort pro Zeile:",
+    check_all: "Alle prüfen",
+    import_file: "Datei importieren …",
+    import_hashes: |mode| format!("{mode}-Hashes importieren …"),
+    batch_counts: |done, total, breached, failed| {
+        format!("{done}/{total} geprüft, {breached} in Datenlecks, {failed} fehlgeschlagen")
+    },
+    batch_showing: |shown, total| format!("{shown} von {total} angezeigt"),
+    batch_shared_passwords: |passwords, users| {
+        format!("{passwords} Passwörter von {users} Nutzern geteilt")
+    },
+    batch_reused_passwords: |passwords, lines| {
+        format!("{passwords} Passwörter in {lines} Zeilen wiederverwendet")
+    },
+    dump_include: "Aus Active-Directory-Dumps auch importieren:",
+    dump_machine_accounts: "Computerkonten",
+    dump_history: "Passwortverlauf",
+    batch_interrupted: |done, total| {
+        format!("Ein unterbrochener Lauf dieser Liste hatte {done} von {total} geprüft.")
+    },
+    resume: "Fortsetzen",
+    start_over: "Neu beginnen",
+    batch_summary: "Zusammenfassung der Liste",
+    worst_line: |number| format!("Zeile {number}"),
+    worst_more: |more| format!(" und {more} weitere"),
+    worst_reused: |count, used, what, lines, more| {
+        format!(
+            "Schlimmster Fund: Ein Passwort, {count}-mal gefunden, wird in {used} {what} \
+             verwendet: {lines}{more}"
+        )
+    },
+    worst_accounts: "Konten",
+    worst_sites: "Seiten",
+    worst_lines: "Zeilen",
+    worst_single: |line, count| format!("Schlimmster Fund: {line}, {count}-mal gefunden"),
+    filter_placeholder: "Nach Zeile, Bezeichnung oder Vorschau filtern",
+    breached_only: "Nur in Datenlecks",
+    errors_only: "Nur Fehler",
+    seen_at_least: "Mindestens",
+    seen_at_least_times: "-mal gefunden",
+    batch_eta: |seconds| format!("noch etwa {seconds} s"),
+    batch_estimating: "Restzeit wird geschätzt",
+    batch_progress: |stats, eta| {
+        format!(
+            "{}/{} verschiedene Passwörter: {} in Datenlecks, {} sauber, {} fehlgeschlagen. \
+             {}/{} Bereiche geladen, {} s vergangen, {eta}",
+            stats.done,
+            stats.total,
+            stats.breached,
+            stats.clean,
+            stats.errored,
+            stats.ranges_done,
+            stats.ranges,
+            stats.elapsed.as_secs(),
+        )
+    },
+    import_failed: |error| format!("Die Datei konnte nicht importiert werden: {error}"),
+    import_summary: |lines, hashed, distinct| match hashed {
+        Some(mode) => {
+            format!("{lines} {mode}-Hashes ({distinct} verschiedene) in der Datei:")
+        }
+        None => format!("{lines} Passwörter ({distinct} verschiedene) in der Datei:"),
+    },
+    import_unreadable: |unread, _| {
+        format!("{unread} Zeilen konnten nicht gelesen werden und werden übersprungen:")
+    },
+    check_imported: |lines| format!("{lines} Passwörter prüfen"),
+
+    account_clean: |account| format!("Keine Datenlecks für {account} gefunden."),
+    account_breached: |account, breaches| {
+        format!("{account} ist in {breaches} Datenleck(s) enthalten:")
+    },
+    domain_clean: |domain| format!("Keine geleakten Adressen unter {domain}."),
+    domain_breached: |aliases, domain| format!("{aliases} geleakte Adresse(n) unter {domain}:"),
+    sort_by_alias: "Nach Adresse",
+    sort_most_breaches: "Meiste Datenlecks",
+    pastes_clean: |account| format!("Keine Pastes für {account} gefunden."),
+    pastes_found: |account, pastes| format!("{account} ist in {pastes} Paste(s) enthalten:"),
+    paste_unknown_date: "Datum unbekannt",
+    paste_untitled: "ohne Titel",
+    paste: |title, source, id, date, emails| {
+        format!("• {title} — {source} {id} ({date}, {emails} E-Mail-Adresse(n))")
+    },
+    checked_via: |millis, via| format!("in {millis} ms geprüft, über {via}"),
+    via_memory: "den Arbeitsspeicher-Cache",
+    via_disk: "den Festplatten-Cache",
+    via_snapshot: |date| format!("den lokalen Stand vom {date}"),
+    history_title: "Prüfungen dieser Sitzung, klicke eine an, um sie zu wiederholen",
+    clear_history: "Verlauf leeren",
+    detail_url: "URL",
+    detail_status: "Status",
+    detail_kind: "Art",
+    detail_attempts: "Versuche",
+    caused_by: "verursacht durch: ",
+    range_suffixes: |suffixes, prefix| {
+        format!("{suffixes} Suffixe teilen sich das Präfix {prefix}")
+    },
+    range_spread: |max, median| format!("Höchstens {max}-mal gefunden, Median {median}"),
+    range_rank: |rank, suffixes| {
+        format!("Dieses Passwort steht auf Platz {rank} von {suffixes} in seinem Bereich")
+    },
+    range_padding: |padding| {
+        format!("Die Antwort enthielt {padding} Padding-Einträge, die hier nicht mitzählen")
+    },
+    range_missing: "Die Statistik braucht den Bereich selbst, den diese Abfrage nicht geladen hat.",
+
+    catalog_loading: "Datenlecks werden geladen ...",
+    catalog_failed: |error| format!("Datenlecks konnten nicht geladen werden: {error}"),
+    catalog_accounts: |accounts| format!("{accounts} Konten"),
+    catalog_unverified: " (nicht bestätigt)",
+    catalog_filter: "Datenlecks filtern",
+    sort_newest: "Neueste",
+    sort_largest: "Größte",
+    catalog_count: |matching, total| format!("{matching} von {total} Datenlecks"),
+
+    system_theme: |dark, theme| {
+        format!(
+            "Das System ist im {} Modus, daher wird {theme} verwendet",
+            if dark { "dunklen" } else { "hellen" }
+        )
+    },
+    system_theme_failed: |error, theme| {
+        format!("Der Systemmodus ließ sich nicht erkennen ({error}), daher wird {theme} verwendet")
+    },
+    light: "hell",
+    dark: "dunkel",
+    save_failed: |error| format!("Einstellungen konnten nicht gespeichert werden: {error}"),
+    window_remembered: "Fenstergröße und -position werden zwischen Sitzungen gespeichert.",
+    reset_window: "Fensteranordnung zurücksetzen",
+    quick_check_tooltip: "Ein kleines Fenster nur mit dem Passwortfeld, immer im Vordergrund",
+    minimize_to_tray: "Beim Schließen des Fensters im Infobereich weiterlaufen",
+    tray_failed: |error| format!("Kein Symbol im Infobereich: {error}"),
+    api_key_heading: "HIBP-API-Schlüssel (für E-Mail-Abfragen nötig)",
+    save: "Speichern",
+    recheck: "Erneut prüfen",
+    check: "Prüfen",
+    delete: "Löschen",
+    api_key_from_env: |var| {
+        format!(
+            "Von {var} gesetzt; entferne die Variable, um den gespeicherten Schlüssel zu nutzen"
+        )
+    },
+    api_key_error: |error| format!("API-Schlüssel: {error}"),
+    plain_keystore: "Kein System-Schlüsselbund verfügbar, der Schlüssel wird in einer einfachen \
+                     Datei gespeichert",
+    checking_subscription: "Das Abo wird geprüft ...",
+    subscription_expired: |until| {
+        format!("Dein Abo ist am {until} abgelaufen; verlängere es, um diese Abfragen zu nutzen")
+    },
+    subscription_expiring: |days| {
+        format!("Dein Abo läuft in {days} Tag(en) ab; verlängere es vorher")
+    },
+    subscription_plan: |name, rpm, until| format!("{name}: {rpm} Abfragen pro Minute, bis {until}"),
+    invalid_proxy: |error| format!("Ungültiger Proxy: {error}"),
+    invalid_endpoint: |error| format!("Ungültiger Endpunkt: {error}"),
+    plain_http: "Achtung: Reine HTTP-Endpunkte senden Hash-Präfixe unverschlüsselt",
+    using_endpoint_from_env: |endpoint, var| {
+        format!("Verwendet wird {endpoint}, gesetzt von {var}")
+    },
+    using_endpoint: |endpoint| format!("Verwendet wird {endpoint}"),
+    endpoint_heading: |var| format!("Pwned-Passwords-Endpunkt (oder setze {var})"),
+    proxy_heading: "Proxy (leer lassen, um HTTPS_PROXY / HTTP_PROXY / ALL_PROXY zu verwenden)",
+    proxy_placeholder: "Proxy-URL mit http://, https:// oder socks5://",
+    set_by: |var| format!("Gesetzt von {var}"),
+    proxy_username: "Proxy-Benutzername",
+    proxy_password: "Proxy-Passwort",
+    use_socks: "Abfragen über einen SOCKS5-Proxy wie Tor leiten",
+    summary_full_hash: "Den ganzen Hash in kopierte Zusammenfassungen aufnehmen",
+    always_trim: "Leerraum um Passwörter immer entfernen",
+    clear_clipboard_after_paste: "Zwischenablage nach „Einfügen & prüfen“ leeren",
+    clipboard_watch_limit: "Zwischenablage nicht mehr überwachen nach",
+    auto_check: "Automatisch prüfen, wenn du beim Tippen eine Pause machst von",
+    idle_clear: "Das Passwort leeren nach einer Inaktivität von",
+    notify_batch: "Benachrichtige mich, wenn eine Listenprüfung im Hintergrund fertig wird",
+    decoys: "Täuschanfragen für zufällige Präfixe senden",
+    decoy_cost: |decoys, kilobytes| {
+        format!("Jede Abfrage lädt dann {decoys} zusätzliche Bereiche, etwa {kilobytes} KB mehr")
+    },
+    severity_from: "Schweregrad ab",
+    threshold: |severity| match severity {
+        Severity::Low => "gering",
+        Severity::Moderate => "mittel",
+        Severity::High => "hoch",
+        Severity::Critical => "kritisch",
+    },
+    occurrences: "Funden",
+    clear_cache: "Cache leeren",
+    cache_cleared: "Cache geleert",
+    clear_cache_failed: |error| format!("Der Cache konnte nicht geleert werden: {error}"),
+    diagnostics: "Diagnose",
+    reset: "Zurücksetzen",
+    check_updates: "Beim Start nach Updates suchen",
+    check_now: "Jetzt suchen",
+    checking_updates: "Wird gesucht ...",
+    up_to_date: |version| format!("{version} ist die neueste Version"),
+    update_failed: |error| format!("Die Suche nach Updates ist fehlgeschlagen: {error}"),
+    debug_log: "Ein Debug-Protokoll in eine Datei schreiben",
+    debug_log_dir: |dir| format!("In {dir}, ab dem nächsten Start"),
+    no_data_dir: "Es gibt kein Datenverzeichnis, in das es geschrieben werden kann",
+    no_host: "kein Host",
+
+    compare_intro: "Prüfe ein altes Passwort und das, das es ersetzt, zusammen.",
+    old_password: "Altes Passwort",
+    new_password: "Neues Passwort",
+    show_passwords: "Passwörter zeigen",
+    check_both: "Beide prüfen",
+    old: "Alt",
+    new: "Neu",
+    compare_checking: |label| format!("{label}: wird geprüft …"),
+    compare_breached: |label, count| format!("{label}: {count}-mal in Datenlecks"),
+    compare_not_found: |label| format!("{label}: nicht gefunden"),
+    compare_failed: |label, error| format!("{label}: Abfrage fehlgeschlagen ({error})"),
+    similarity: |similarity| match similarity {
+        Similarity::Identical => "Das neue Passwort ist dasselbe wie das alte.",
+        Similarity::Case => {
+            "Das neue Passwort ändert nur die Groß- und Kleinschreibung des alten, und genau das \
+             wird mit einem geleakten Passwort als Erstes probiert."
+        }
+        Similarity::TrailingDigits => {
+            "Das neue Passwort ändert nur die Ziffern am Ende des alten, und genau das wird mit \
+             einem geleakten Passwort als Erstes probiert."
+        }
+        Similarity::OneCharacter => {
+            "Das neue Passwort unterscheidet sich nur in einem Zeichen \
+                                     vom alten."
+        }
+    },
+
+    kind_characters: "Zeichen",
+    kind_passphrase: "Passphrase",
+    entropy: |bits| format!("≈ {bits:.1} Bit Entropie"),
+    use_in_checker: "Im Prüfer verwenden",
+    generate: "Erzeugen",
+    length: |length| format!("Länge {length}"),
+    avoid_ambiguous: "Verwechselbare Zeichen vermeiden",
+    exclude_placeholder: "Zeichen ausschließen",
+    words: |words| format!("{words} Wörter"),
+    separator: "Trennzeichen",
+    capitalize: "Wörter großschreiben",
+    append_digit: "Eine Ziffer anhängen",
+
+    mapping_column: |number| format!("Spalte {number}"),
+    mapping_no_label: "Keine (nach Zeilennummer)",
+    mapping_intro: "Die Spalten der Datei passen zu keinem bekannten Export. Wähle, welche das \
+                    Passwort enthält, dann wird die Datei so gelesen; mit einer Kopfzeile werden \
+                    solche Exporte beim nächsten Mal genauso gelesen.",
+    delimiter: "Trennzeichen",
+    mapping_header: "Die erste Zeile benennt die Spalten",
+    show_values: "Werte zeigen",
+    choose_column: "Spalte wählen",
+    label: "Bezeichnung",
+    read_file: "Datei einlesen",
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = include_str!("strings.rs");
+
+    /// The name each language's strings are defined under.
+    fn constant(language: Language) -> &'static str {
+        match language {
+            Language::English => "EN",
+            Language::German => "DE",
+        }
+    }
+
+    /// The source between `start` and the first `end` after it.
+    fn block(start: &str, end: &str) -> &'static str {
+        let from = SOURCE.find(start).expect(start) + start.len();
+        let len = SOURCE[from..].find(end).expect(end);
+        &SOURCE[from..from + len]
+    }
+
+    /// The fields of [`Strings`], in order.
+    fn keys() -> Vec<&'static str> {
+        block("pub struct Strings {", "\n}")
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("pub "))
+            .filter_map(|field| field.split_once(':'))
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    /// The keys `language`'s literal spells out, one per line at its top
+    /// level.
+    fn entries(language: Language) -> Vec<&'static str> {
+        let start = format!("pub const {}: Strings = Strings {{", constant(language));
+        block(&start, "\n};")
+            .lines()
+            .filter(|line| line.starts_with("    ") && !line.starts_with("     "))
+            .filter_map(|line| line.trim().split_once(':'))
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    #[test]
+    fn every_key_exists_in_every_locale() {
+        let keys = keys();
+        assert!(keys.len() > 300, "{}", keys.len());
+        for language in Language::ALL {
+            let entries = entries(language);
+            let missing: Vec<_> = keys.iter().filter(|key| !entries.contains(key)).collect();
+            assert!(missing.is_empty(), "{language} is missing {missing:?}");
+            assert_eq!(entries.len(), keys.len(), "{language} sets a key twice");
+        }
+    }
+
+    #[test]
+    fn no_locale_falls_back_to_english() {
+        for language in Language::ALL
+            .into_iter()
+            .filter(|&l| l != Language::English)
+        {
+            let start = format!("pub const {}: Strings = Strings {{", constant(language));
+            assert!(!block(&start, "\n};").contains("..EN"), "{language}");
+        }
+    }
+
+    #[test]
+    fn german_says_du() {
+        let german = block("pub const DE: Strings = Strings {", "\n};");
+        for formal in [" Sie ", " Sie.", "Ihr", "Ihnen", "Ihre"] {
+            assert!(!german.contains(formal), "{formal}");
+        }
+        assert!((DE.clipboard_warning)(5).contains(" du "));
+        assert_eq!(
+            DE.idle_cleared,
+            "Zu deiner Sicherheit nach längerer Inaktivität geleert."
+        );
+    }
+
+    #[test]
+    fn text_is_translated_rather_than_copied() {
+        // Words that are the same in both.
+        let same = [
+            "hash",
+            "generator",
+            "mode_domain",
+            "mode_pin",
+            "keypad_symbols",
+            "keypad_letters",
+            "details",
+            "column_status",
+            "detail_url",
+            "detail_status",
+            "kind_passphrase",
+            "source_cache",
+        ];
+        let english = block("pub const EN: Strings = Strings {", "\n};");
+        let german = block("pub const DE: Strings = Strings {", "\n};");
+        let value = |block: &str, key: &str| {
+            block
+                .lines()
+                .find_map(|line| line.strip_prefix(&format!("    {key}: \"")))
+                .map(|rest| rest.trim_end_matches(['"', ','].as_slice()).to_owned())
+        };
+        for key in keys().into_iter().filter(|key| !same.contains(key)) {
+            if let (Some(en), Some(de)) = (value(english, key), value(german, key)) {
+                assert_ne!(en, de, "{key} isn't translated");
+            }
+        }
+    }
+
+    #[test]
+    fn views_get_each_languages_text() {
+        for (strings, language) in [(&EN, "English"), (&DE, "German")] {
+            assert!(!strings.batch_prompt.is_empty(), "{language}");
+            assert!(
+                (strings.batch_counts)(1, 2, 3, 4).contains('3'),
+                "{language}"
+            );
+            assert!(
+                (strings.kept_local)(35, "•••").ends_with("•••"),
+                "{language}"
+            );
+        }
+        assert_eq!(
+            (EN.similarity)(Similarity::Identical),
+            "The new password is the same as the old one."
+        );
+        assert_eq!(
+            (DE.similarity)(Similarity::Identical),
+            "Das neue Passwort ist dasselbe wie das alte."
+        );
+        assert_eq!(
+            (DE.import_summary)(3, Some(HashMode::Ntlm), 2),
+            "3 NTLM-Hashes (2 verschiedene) in der Datei:"
+        );
+        assert_eq!((EN.threshold)(Severity::High), "high");
+        assert_eq!((DE.threshold)(Severity::High), "hoch");
+    }
+}
@@ -0,0 +1,207 @@
+use std::future::Future;
+
+use rand::Rng;
+
+use crate::generator::{self, CharClass};
+use crate::secret::SecretString;
+
+/// How many candidates are checked before giving up on finding one that
+/// isn't breached.
+pub const ATTEMPTS: usize = 5;
+
+/// The shortest replacement suggested, however short the original was.
+const MIN_LENGTH: u8 = 16;
+
+/// The longest run of the original's characters, in order and ignoring
+/// case, that a suggestion may share with it.
+const MAX_SHARED_RUN: usize = 3;
+
+/// Generator options for replacing `original`: at least as long and at least
+/// [`MIN_LENGTH`], with the classes it used plus lowercase and digits.
+pub fn options_like(original: &str) -> generator::Options {
+    let mut options = generator::Options {
+        length: u8::try_from(original.chars().count())
+            .unwrap_or(u8::MAX)
+            .clamp(MIN_LENGTH, generator::MAX_LENGTH),
+        lower: true,
+        upper: false,
+        digits: true,
+        symbols: false,
+        ..generator::Options::default()
+    };
+    for class in [CharClass::Upper, CharClass::Symbols] {
+        if original.chars().any(|c| class_of(c) == Some(class)) {
+            options.set(class, true);
+        }
+    }
+    options
+}
+
+fn class_of(c: char) -> Option<CharClass> {
+    match c {
+        'a'..='z' => Some(CharClass::Lower),
+        'A'..='Z' => Some(CharClass::Upper),
+        '0'..='9' => Some(CharClass::Digits),
+        _ if c.is_ascii_punctuation() => Some(CharClass::Symbols),
+        _ => None,
+    }
+}
+
+/// Whether `candidate` carries over more than [`MAX_SHARED_RUN`] characters
+/// of `original` in a row, ignoring case.
+pub fn reuses(original: &str, candidate: &str) -> bool {
+    let original: Vec<char> = original.to_lowercase().chars().collect();
+    let candidate = candidate.to_lowercase();
+    original
+        .windows(MAX_SHARED_RUN + 1)
+        .any(|run| candidate.contains(&run.iter().collect::<String>()))
+}
+
+/// A random replacement for `original`, drawn until one doesn't reuse it.
+pub fn alternative(original: &str, rng: &mut impl Rng) -> SecretString {
+    let options = options_like(original);
+    loop {
+        // Lowercase and digits are always selected and nothing is excluded,
+        // so this can't fail.
+        let candidate: SecretString = generator::generate(&options, rng)
+            .unwrap_or_default()
+            .into();
+        if !reuses(original, candidate.expose()) {
+            return candidate;
+        }
+    }
+}
+
+/// Checks candidates from `generate` with `is_breached` until one isn't, up
+/// to [`ATTEMPTS`] of them. `None` means every one was breached; a failed
+/// lookup stops the search.
+pub async fn first_unbreached<F, E>(
+    mut generate: impl FnMut() -> SecretString,
+    mut is_breached: impl FnMut(SecretString) -> F,
+) -> Result<Option<SecretString>, E>
+where
+    F: Future<Output = Result<bool, E>>,
+{
+    for _ in 0..ATTEMPTS {
+        let candidate = generate();
+        if !is_breached(candidate.clone()).await? {
+            return Ok(Some(candidate));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::convert::Infallible;
+
+    use futures::executor::block_on;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    use super::*;
+
+    #[test]
+    fn suggestions_are_long_and_keep_the_originals_classes() {
+        let options = options_like("hunter2");
+        assert_eq!(options.length, MIN_LENGTH);
+        assert!(options.lower && options.digits);
+        assert!(!options.upper && !options.symbols);
+
+        let options = options_like("Correct-Horse-Battery-Staple!");
+        assert_eq!(options.length, 29);
+        assert!(options.upper && options.symbols);
+
+        assert_eq!(options_like(&"a".repeat(500)).length, generator::MAX_LENGTH);
+    }
+
+    #[test]
+    fn runs_longer_than_three_count_as_reuse() {
+        assert!(reuses("hunter2", "xxHUNTxx"));
+        assert!(reuses("hunter2", "ter2"));
+        assert!(!reuses("hunter2", "hun-ter-2"));
+        assert!(!reuses("hunter2", "2retnuh"));
+        // Too short to share a run with anything.
+        assert!(!reuses("abc", "abc"));
+    }
+
+    #[test]
+    fn alternatives_never_reuse_the_original() {
+        for original in ["hunter2", "password", "aaaaaaaaaaaaaaaa", "0123456789"] {
+            for seed in 0..200 {
+                let candidate = alternative(original, &mut StdRng::seed_from_u64(seed));
+                let candidate = candidate.expose();
+                assert!(!reuses(original, candidate), "{original} → {candidate}");
+                assert!(candidate.chars().count() >= usize::from(MIN_LENGTH));
+            }
+        }
+    }
+
+    /// Runs [`first_unbreached`] over numbered candidates, answering each
+    /// lookup from `answers`, and returns the result with what was looked up.
+    fn search(
+        answers: &[Result<bool, &'static str>],
+    ) -> (Result<Option<String>, &'static str>, Vec<String>) {
+        let generated = RefCell::new(0);
+        let looked_up = RefCell::new(Vec::new());
+        let result = block_on(first_unbreached(
+            || {
+                *generated.borrow_mut() += 1;
+                SecretString::from(format!("candidate {}", generated.borrow()))
+            },
+            |candidate| {
+                looked_up.borrow_mut().push(candidate.expose().to_owned());
+                let answer = answers[looked_up.borrow().len() - 1];
+                async move { answer }
+            },
+        ));
+        (
+            result.map(|found| found.map(|found| found.expose().to_owned())),
+            looked_up.into_inner(),
+        )
+    }
+
+    #[test]
+    fn a_breached_candidate_is_replaced_by_the_next() {
+        let (result, looked_up) = search(&[Ok(true), Ok(false)]);
+        assert_eq!(result, Ok(Some("candidate 2".to_owned())));
+        assert_eq!(looked_up, ["candidate 1", "candidate 2"]);
+    }
+
+    #[test]
+    fn the_first_unbreached_candidate_is_kept() {
+        let (result, looked_up) = search(&[Ok(false)]);
+        assert_eq!(result, Ok(Some("candidate 1".to_owned())));
+        assert_eq!(looked_up.len(), 1);
+    }
+
+    #[test]
+    fn giving_up_after_every_attempt_is_breached() {
+        let (result, looked_up) = search(&[Ok(true); ATTEMPTS]);
+        assert_eq!(result, Ok(None));
+        assert_eq!(looked_up.len(), ATTEMPTS);
+    }
+
+    #[test]
+    fn a_failed_lookup_stops_the_search() {
+        let (result, looked_up) = search(&[Ok(true), Err("offline"), Ok(false)]);
+        assert_eq!(result, Err("offline"));
+        assert_eq!(looked_up.len(), 2);
+    }
+
+    #[test]
+    fn the_real_generator_feeds_the_loop() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut breached = true;
+        let result = block_on(first_unbreached(
+            || alternative("hunter2", &mut rng),
+            |_| {
+                let answer = std::mem::replace(&mut breached, false);
+                async move { Ok::<_, Infallible>(answer) }
+            },
+        ));
+        let suggestion = result.unwrap().unwrap();
+        assert!(!reuses("hunter2", suggestion.expose()));
+    }
+}
@@ -0,0 +1,681 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use chrono::{Local, NaiveDate};
+use serde::Serialize;
+use url::Url;
+
+use crate::export::Row;
+use crate::policy::Rule;
+use crate::record::CheckRecord;
+use crate::severity::Severity;
+use crate::{BreachResult, HashMode, LookupSource};
+
+/// A one-line account of a check, safe to paste into a ticket. It names the
+/// hash prefix only, unless `full_hash` is given.
+pub fn summary(
+    record: &CheckRecord,
+    full_hash: Option<&str>,
+    endpoint: &Url,
+    separator: char,
+) -> String {
+    let outcome = match record.result {
+        BreachResult::Found { count } => format!(
+            "found {} times in known breaches",
+            format_count(count, CountStyle::Full, separator)
+        ),
+        BreachResult::NotFound => "not found in known breaches".to_owned(),
+    };
+    let via = match record.source {
+        LookupSource::Network => host(endpoint),
+        LookupSource::Memory | LookupSource::Disk => {
+            format!("a cached copy from {}", host(endpoint))
+        }
+        LookupSource::Offline(snapshot) => format!("the offline snapshot from {snapshot}"),
+    };
+    format!(
+        "{}: {} (checked {} via {})",
+        subject(record.algorithm, &record.prefix, full_hash),
+        outcome,
+        record.checked_at.with_timezone(&Local).date_naive(),
+        via
+    )
+}
+
+/// [`summary`] for a lookup that failed with `message`.
+pub fn error_summary(
+    mode: HashMode,
+    prefix: &str,
+    full_hash: Option<&str>,
+    message: &str,
+    date: NaiveDate,
+) -> String {
+    format!(
+        "{}: lookup failed, {} (checked {})",
+        subject(mode, prefix, full_hash),
+        message,
+        date
+    )
+}
+
+fn subject(mode: HashMode, prefix: &str, full_hash: Option<&str>) -> String {
+    match full_hash {
+        Some(hash) => format!("{mode} hash {hash}"),
+        None => format!("{mode} prefix {prefix}…"),
+    }
+}
+
+fn host(endpoint: &Url) -> String {
+    endpoint.host_str().unwrap_or("the API").to_owned()
+}
+
+/// How [`format_count`] writes a number.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CountStyle {
+    /// Every digit, grouped: `9,545,824`.
+    Full,
+    /// Rounded to the largest unit that fits: `9.5M`.
+    Compact,
+}
+
+/// Units for [`CountStyle::Compact`], each a thousand times the last. `Qi`
+/// (quintillion) covers the whole `u64` range.
+const UNITS: [&str; 6] = ["K", "M", "B", "T", "Qa", "Qi"];
+
+/// `count` for people to read. `separator` groups thousands, and a compact
+/// count's decimal mark is whichever of `.` and `,` it isn't.
+pub fn format_count(count: u64, style: CountStyle, separator: char) -> String {
+    if style == CountStyle::Full || count < 1000 {
+        return group_digits(count, separator);
+    }
+    let mut scaled = count as f64 / 1000.;
+    let mut unit = 0;
+    while scaled >= 1000. && unit + 1 < UNITS.len() {
+        scaled /= 1000.;
+        unit += 1;
+    }
+    let number = loop {
+        let number = if scaled < 99.95 {
+            format!("{scaled:.1}")
+        } else {
+            format!("{scaled:.0}")
+        };
+        // 999,950 rounds up to "1000K", which is better said as "1M".
+        if number != "1000" || unit + 1 == UNITS.len() {
+            break number;
+        }
+        scaled /= 1000.;
+        unit += 1;
+    };
+    let number = number.strip_suffix(".0").unwrap_or(&number);
+    let decimal = if separator == '.' { ',' } else { '.' };
+    format!(
+        "{}{}",
+        number.replace('.', &decimal.to_string()),
+        UNITS[unit]
+    )
+}
+
+/// `count` with its digits grouped in threes by `separator`.
+fn group_digits(count: u64, separator: char) -> String {
+    let digits = count.to_string();
+    let mut grouped = String::with_capacity(digits.len() * 4 / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(separator);
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+/// The thousands separator for a BCP 47 locale such as `de-DE`, by its
+/// language. Unknown languages get a comma.
+pub fn grouping_separator(locale: &str) -> char {
+    let language = locale.split(['-', '_']).next().unwrap_or_default();
+    match language.to_ascii_lowercase().as_str() {
+        "de" | "es" | "it" | "nl" | "pt" | "da" | "id" | "tr" | "el" | "ro" | "hr" | "sl" => '.',
+        "fr" | "ru" | "pl" | "sv" | "nb" | "no" | "fi" | "cs" | "sk" | "uk" | "hu" | "bg" => {
+            '\u{a0}'
+        }
+        _ => ',',
+    }
+}
+
+/// [`grouping_separator`] for the system locale.
+pub fn system_separator() -> char {
+    sys_locale::get_locale()
+        .as_deref()
+        .map_or(',', grouping_separator)
+}
+
+/// How many of the most exposed passwords an [`Audit`] lists.
+pub const TOP_FINDINGS: usize = 10;
+
+/// How many of a finding's labels [`Audit::lines`] names before "and N more".
+const FINDING_LABELS: usize = 3;
+
+/// An account of a whole batch for the end of an audit: how much of it was
+/// breached and how badly, its most exposed passwords, how often passwords
+/// are shared, and how long the breached ones are against the rest.
+///
+/// The counts and tiers are over lines, as each is an account that would
+/// need its password changed. The findings, reuse and lengths are over
+/// distinct passwords, so one shared by many lines isn't counted many
+/// times. Lines whose lookup failed are left out of everything but
+/// `failed`. No password appears in it, only the labels of its lines.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Audit {
+    pub total: usize,
+    pub breached: usize,
+    pub clean: usize,
+    pub failed: usize,
+    /// The breached share of the lines that were checked, out of 100;
+    /// `None` if none was.
+    pub breached_percent: Option<f64>,
+    /// Breached lines in each tier.
+    pub severity: Tiers,
+    /// The [`TOP_FINDINGS`] passwords seen most often, most seen first.
+    pub worst: Vec<Finding>,
+    pub reuse: Reuse,
+    /// Average lengths in characters, over distinct passwords.
+    pub average_length: Lengths,
+    /// Over every line, looked up or not; `None` without a policy.
+    pub policy: Option<PolicyFailures>,
+}
+
+/// How many lines fell in each [`Severity`] tier.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct Tiers {
+    pub critical: usize,
+    pub high: usize,
+    pub moderate: usize,
+    pub low: usize,
+}
+
+/// A breached password among an audit's worst.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Finding {
+    pub count: u64,
+    pub severity: Option<Severity>,
+    /// The label of each line it's on, or `line N` for one with none.
+    pub labels: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct Reuse {
+    /// Distinct passwords that were checked.
+    pub passwords: usize,
+    /// Those on more than one line.
+    pub reused: usize,
+    /// The lines those are on, together.
+    pub reused_lines: usize,
+    /// The most lines any one password is on.
+    pub most_lines: usize,
+}
+
+/// How many lines fail a policy, altogether and by rule.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct PolicyFailures {
+    pub lines: usize,
+    /// Each rule some line fails, the first to be failed first.
+    pub by_rule: Vec<RuleFailures>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct RuleFailures {
+    pub rule: Rule,
+    pub lines: usize,
+}
+
+/// `None` where there's no password of that kind, or none of known length.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct Lengths {
+    pub breached: Option<f64>,
+    pub clean: Option<f64>,
+}
+
+impl Audit {
+    pub fn new(rows: &[Row]) -> Self {
+        let mut audit = Audit {
+            total: rows.len(),
+            ..Audit::default()
+        };
+        // By password, in the order each first appears.
+        let mut passwords: Vec<(usize, Vec<&Row>)> = Vec::new();
+        let mut first_row = HashMap::new();
+        for row in rows {
+            if let Some(failed) = &row.policy {
+                let policy = audit.policy.get_or_insert_default();
+                policy.lines += usize::from(!failed.is_empty());
+                for rule in failed {
+                    match policy.by_rule.iter_mut().find(|count| count.rule == *rule) {
+                        Some(count) => count.lines += 1,
+                        None => policy.by_rule.push(RuleFailures {
+                            rule: rule.clone(),
+                            lines: 1,
+                        }),
+                    }
+                }
+            }
+            match row.outcome {
+                Ok(Some(_)) => audit.breached += 1,
+                Ok(None) => audit.clean += 1,
+                Err(_) => {
+                    audit.failed += 1;
+                    continue;
+                }
+            }
+            match row.severity {
+                Some(Severity::Critical) => audit.severity.critical += 1,
+                Some(Severity::High) => audit.severity.high += 1,
+                Some(Severity::Moderate) => audit.severity.moderate += 1,
+                Some(Severity::Low) => audit.severity.low += 1,
+                None => {}
+            }
+            let index = *first_row.entry(row.password).or_insert_with(|| {
+                passwords.push((row.password, Vec::new()));
+                passwords.len() - 1
+            });
+            passwords[index].1.push(row);
+        }
+        let checked = audit.breached + audit.clean;
+        audit.breached_percent =
+            (checked > 0).then(|| audit.breached as f64 * 100. / checked as f64);
+
+        let mut findings = Vec::new();
+        let (mut breached_lengths, mut clean_lengths) = (Vec::new(), Vec::new());
+        for (_, lines) in &passwords {
+            let first = lines[0];
+            let count = first.outcome.clone().ok().flatten();
+            if let Some(length) = first.length {
+                match count {
+                    Some(_) => breached_lengths.push(length),
+                    None => clean_lengths.push(length),
+                }
+            }
+            if lines.len() > 1 {
+                audit.reuse.reused += 1;
+                audit.reuse.reused_lines += lines.len();
+            }
+            audit.reuse.most_lines = audit.reuse.most_lines.max(lines.len());
+            if let Some(count) = count {
+                findings.push(Finding {
+                    count,
+                    severity: first.severity,
+                    labels: lines.iter().map(|row| label(row)).collect(),
+                });
+            }
+        }
+        audit.reuse.passwords = passwords.len();
+        // Stable, so the first in the batch wins a tie.
+        findings.sort_by_key(|finding| Reverse(finding.count));
+        findings.truncate(TOP_FINDINGS);
+        audit.worst = findings;
+        audit.average_length = Lengths {
+            breached: average(&breached_lengths),
+            clean: average(&clean_lengths),
+        };
+        audit
+    }
+
+    /// The audit as sentences, one a line, with counts grouped by
+    /// `separator`: the CLI's footer and the report's summary.
+    pub fn lines(&self, separator: char) -> Vec<String> {
+        let count = |count: u64| format_count(count, CountStyle::Full, separator);
+        let mut lines = Vec::new();
+        let checked = self.breached + self.clean;
+        let mut breached = match self.breached_percent {
+            Some(percent) => format!(
+                "{} of {} checked breached ({percent:.1}%)",
+                self.breached, checked
+            ),
+            None if self.failed > 0 => "no lookup succeeded".to_owned(),
+            None => "nothing was checked".to_owned(),
+        };
+        if self.failed > 0 {
+            breached.push_str(&format!(", {} failed", self.failed));
+        }
+        lines.push(breached);
+        if self.breached > 0 {
+            let tiers = &self.severity;
+            lines.push(format!(
+                "by severity: {} critical, {} high, {} moderate, {} low",
+                tiers.critical, tiers.high, tiers.moderate, tiers.low
+            ));
+        }
+        for (rank, finding) in self.worst.iter().enumerate() {
+            let mut labels = finding.labels[..finding.labels.len().min(FINDING_LABELS)].join(", ");
+            if let more @ 1.. = finding.labels.len().saturating_sub(FINDING_LABELS) {
+                labels.push_str(&format!(" and {more} more"));
+            }
+            lines.push(format!(
+                "{}. seen {} times{}: {labels}",
+                rank + 1,
+                count(finding.count),
+                finding
+                    .severity
+                    .map(|severity| format!(" ({severity})"))
+                    .unwrap_or_default(),
+            ));
+        }
+        let reuse = &self.reuse;
+        if reuse.reused > 0 {
+            lines.push(format!(
+                "{} of {} passwords are on more than one line, {} lines in all, \
+                 the most on {}",
+                reuse.reused, reuse.passwords, reuse.reused_lines, reuse.most_lines
+            ));
+        } else if reuse.passwords > 1 {
+            lines.push("no password is on more than one line".to_owned());
+        }
+        match &self.policy {
+            Some(policy) if policy.lines > 0 => {
+                let rules: Vec<String> = policy
+                    .by_rule
+                    .iter()
+                    .map(|count| format!("{} ({})", count.rule, count.lines))
+                    .collect();
+                lines.push(format!(
+                    "{} of {} fail the policy: {}",
+                    policy.lines,
+                    self.total,
+                    rules.join(", ")
+                ));
+            }
+            Some(_) => lines.push(format!("all {} meet the policy", self.total)),
+            None => {}
+        }
+        let lengths = [
+            ("breached", self.average_length.breached),
+            ("clean", self.average_length.clean),
+        ];
+        let lengths: Vec<String> = lengths
+            .iter()
+            .filter_map(|(kind, length)| Some(format!("{:.1} {kind}", (*length)?)))
+            .collect();
+        if !lengths.is_empty() {
+            lines.push(format!(
+                "average length in characters: {}",
+                lengths.join(", ")
+            ));
+        }
+        lines
+    }
+}
+
+/// What a row is called among the findings.
+fn label(row: &Row) -> String {
+    if row.label.is_empty() {
+        format!("line {}", row.line)
+    } else {
+        row.label.clone()
+    }
+}
+
+fn average(lengths: &[usize]) -> Option<f64> {
+    (!lengths.is_empty()).then(|| lengths.iter().sum::<usize>() as f64 / lengths.len() as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::severity::Thresholds;
+    use crate::{Normalization, hash_password};
+
+    fn endpoint() -> Url {
+        "https://api.pwnedpasswords.com/".parse().unwrap()
+    }
+
+    fn record(result: BreachResult, source: LookupSource) -> (String, CheckRecord) {
+        let hash = hash_password("password", HashMode::Sha1, Normalization::Off);
+        let record = CheckRecord::new(
+            &hash,
+            HashMode::Sha1,
+            result,
+            source,
+            &Thresholds::default(),
+        );
+        (hash, record)
+    }
+
+    /// The day `record` was checked, as the summary writes it.
+    fn day(record: &CheckRecord) -> NaiveDate {
+        record.checked_at.with_timezone(&Local).date_naive()
+    }
+
+    #[test]
+    fn a_found_summary_groups_the_count_for_the_locale() {
+        let found = BreachResult::Found { count: 52256179 };
+        let (_, record) = record(found, LookupSource::Network);
+        assert_eq!(
+            summary(&record, None, &endpoint(), ','),
+            format!(
+                "SHA-1 prefix 5BAA6…: found 52,256,179 times in known breaches \
+                 (checked {} via api.pwnedpasswords.com)",
+                day(&record)
+            )
+        );
+        let german = summary(&record, None, &endpoint(), '.');
+        assert!(german.contains("found 52.256.179 times"), "{german}");
+    }
+
+    #[test]
+    fn a_not_found_summary_says_where_the_answer_came_from() {
+        let (_, cached) = record(BreachResult::NotFound, LookupSource::Disk);
+        assert_eq!(
+            summary(&cached, None, &endpoint(), ','),
+            format!(
+                "SHA-1 prefix 5BAA6…: not found in known breaches \
+                 (checked {} via a cached copy from api.pwnedpasswords.com)",
+                day(&cached)
+            )
+        );
+        let snapshot = LookupSource::Offline("2024-11-30".parse().unwrap());
+        let (_, offline) = record(BreachResult::NotFound, snapshot);
+        let line = summary(&offline, None, &endpoint(), ',');
+        assert!(
+            line.ends_with("via the offline snapshot from 2024-11-30)"),
+            "{line}"
+        );
+    }
+
+    #[test]
+    fn the_full_hash_is_named_only_when_asked() {
+        let (hash, record) = record(BreachResult::NotFound, LookupSource::Network);
+        let line = summary(&record, Some(&hash), &endpoint(), ',');
+        assert!(line.starts_with(&format!("SHA-1 hash {hash}: ")), "{line}");
+        assert!(!summary(&record, None, &endpoint(), ',').contains(&hash));
+    }
+
+    #[test]
+    fn an_error_summary_carries_the_message() {
+        let date = "2025-03-04".parse().unwrap();
+        assert_eq!(
+            error_summary(HashMode::Ntlm, "8846F", None, "timed out", date),
+            "NTLM prefix 8846F…: lookup failed, timed out (checked 2025-03-04)"
+        );
+        assert_eq!(
+            error_summary(HashMode::Sha1, "5BAA6", Some("5BAA61E4"), "offline", date),
+            "SHA-1 hash 5BAA61E4: lookup failed, offline (checked 2025-03-04)"
+        );
+    }
+
+    #[test]
+    fn counts_group_and_compact() {
+        assert_eq!(format_count(999, CountStyle::Full, ','), "999");
+        assert_eq!(format_count(1000, CountStyle::Full, '\u{a0}'), "1\u{a0}000");
+        assert_eq!(format_count(52256179, CountStyle::Full, '.'), "52.256.179");
+        assert_eq!(format_count(9545824, CountStyle::Compact, ','), "9.5M");
+        assert_eq!(format_count(9545824, CountStyle::Compact, '.'), "9,5M");
+        assert_eq!(format_count(999_950, CountStyle::Compact, ','), "1M");
+        assert_eq!(format_count(120_000, CountStyle::Compact, ','), "120K");
+        assert_eq!(format_count(u64::MAX, CountStyle::Compact, ','), "18.4Qi");
+    }
+
+    #[test]
+    fn separators_follow_the_language() {
+        assert_eq!(grouping_separator("de-DE"), '.');
+        assert_eq!(grouping_separator("fr_FR"), '\u{a0}');
+        assert_eq!(grouping_separator("en-GB"), ',');
+        assert_eq!(grouping_separator(""), ',');
+    }
+
+    #[test]
+    fn counts_at_the_boundaries() {
+        for (count, full, compact) in [
+            (0, "0", "0"),
+            (999, "999", "999"),
+            (1_000, "1,000", "1K"),
+            (1_049, "1,049", "1K"),
+            (1_050, "1,050", "1.1K"),
+            (99_949, "99,949", "99.9K"),
+            (99_950, "99,950", "100K"),
+            (1_500_000, "1,500,000", "1.5M"),
+            (999_999_999, "999,999,999", "1B"),
+            (u64::MAX, "18,446,744,073,709,551,615", "18.4Qi"),
+        ] {
+            assert_eq!(format_count(count, CountStyle::Full, ','), full);
+            assert_eq!(
+                format_count(count, CountStyle::Compact, ','),
+                compact,
+                "{count}"
+            );
+        }
+    }
+
+    /// Line `line` of a batch, the `password`th distinct password in it.
+    fn row(line: usize, password: usize, outcome: Result<Option<u64>, String>) -> Row {
+        let severity = match outcome {
+            Ok(Some(count)) => BreachResult::Found { count }.severity(&Thresholds::default()),
+            _ => None,
+        };
+        Row {
+            line,
+            label: String::new(),
+            preview: "h*****2".to_owned(),
+            password,
+            length: Some(5 + password),
+            prefix: "F3BBB".to_owned(),
+            algorithm: HashMode::Sha1,
+            outcome,
+            severity,
+            checked_at: "2025-01-01T12:00:00Z".parse().unwrap(),
+            policy: None,
+            plaintext: None,
+        }
+    }
+
+    #[test]
+    fn an_empty_batch_audits_as_nothing_checked() {
+        let audit = Audit::new(&[]);
+        assert_eq!(audit, Audit::default());
+        assert_eq!(audit.breached_percent, None);
+        assert_eq!(audit.lines(','), ["nothing was checked"]);
+    }
+
+    #[test]
+    fn an_all_clean_batch_has_no_findings_or_tiers() {
+        let rows: Vec<_> = (1..=3).map(|line| row(line, line, Ok(None))).collect();
+        let audit = Audit::new(&rows);
+        assert_eq!((audit.total, audit.clean, audit.breached), (3, 3, 0));
+        assert_eq!(audit.breached_percent, Some(0.));
+        assert_eq!(audit.severity, Tiers::default());
+        assert!(audit.worst.is_empty());
+        assert_eq!(audit.reuse.passwords, 3);
+        assert_eq!(audit.reuse.most_lines, 1);
+        assert_eq!(
+            audit.average_length,
+            Lengths {
+                breached: None,
+                clean: Some(7.)
+            }
+        );
+        assert_eq!(
+            audit.lines(','),
+            [
+                "0 of 3 checked breached (0.0%)",
+                "no password is on more than one line",
+                "average length in characters: 7.0 clean",
+            ]
+        );
+    }
+
+    #[test]
+    fn an_all_error_batch_counts_only_the_failures() {
+        let rows: Vec<_> = (1..=2)
+            .map(|line| row(line, line, Err("timed out".to_owned())))
+            .collect();
+        let audit = Audit::new(&rows);
+        assert_eq!(
+            audit,
+            Audit {
+                total: 2,
+                failed: 2,
+                ..Audit::default()
+            }
+        );
+        assert_eq!(audit.lines(','), ["no lookup succeeded, 2 failed"]);
+    }
+
+    #[test]
+    fn a_mixed_batch_ranks_findings_and_counts_reuse_by_password() {
+        let mut rows = vec![
+            row(1, 0, Ok(Some(3))),
+            row(2, 1, Ok(Some(52256179))),
+            row(3, 0, Ok(Some(3))),
+            row(4, 2, Ok(None)),
+            row(5, 3, Err("timed out".to_owned())),
+            row(6, 0, Ok(Some(3))),
+            row(7, 0, Ok(Some(3))),
+        ];
+        rows[0].label = "alice".to_owned();
+        let audit = Audit::new(&rows);
+        assert_eq!(
+            (audit.total, audit.breached, audit.clean, audit.failed),
+            (7, 5, 1, 1)
+        );
+        assert_eq!(audit.breached_percent, Some(500. / 6.));
+        assert_eq!(
+            audit.severity,
+            Tiers {
+                critical: 1,
+                low: 4,
+                ..Tiers::default()
+            }
+        );
+        let counts: Vec<_> = audit.worst.iter().map(|finding| finding.count).collect();
+        assert_eq!(counts, [52256179, 3]);
+        assert_eq!(
+            audit.worst[1].labels,
+            ["alice", "line 3", "line 6", "line 7"]
+        );
+        assert_eq!(
+            audit.reuse,
+            Reuse {
+                passwords: 3,
+                reused: 1,
+                reused_lines: 4,
+                most_lines: 4
+            }
+        );
+        assert_eq!(
+            audit.average_length,
+            Lengths {
+                breached: Some(5.5),
+                clean: Some(7.)
+            }
+        );
+        let lines = audit.lines(',');
+        assert_eq!(lines[0], "5 of 6 checked breached (83.3%), 1 failed");
+        assert_eq!(
+            lines[1],
+            "by severity: 1 critical, 0 high, 0 moderate, 4 low"
+        );
+        assert_eq!(lines[2], "1. seen 52,256,179 times (critical): line 2");
+        assert_eq!(
+            lines[3],
+            "2. seen 3 times (low): alice, line 3, line 6 and 1 more"
+        );
+    }
+}
@@ -0,0 +1,2630 @@
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use iced::futures::{FutureExt, StreamExt, stream};
+use iced_runtime::Action;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use super::*;
+use crate::check_screen::{self, SearchResult};
+use crate::ui::has_edge_whitespace;
+use cybersec_wow::batch::{BatchItem, BatchStats};
+use cybersec_wow::error::{FailureKind, ParseError};
+use cybersec_wow::hibp::AccountBreaches;
+use cybersec_wow::range::{self, Range, find_suffix};
+use cybersec_wow::record::CheckRecord;
+use cybersec_wow::{LookupSource, hash_password, hash_suffix_of, suggest};
+
+/// The real range for `F3BBB`, which lists `hunter2` 17,206,891 times.
+const RANGE: &str = include_str!("../tests/fixtures/range-F3BBB.txt");
+const SUBSCRIPTION: &str = include_str!("../tests/fixtures/hibp/subscription-status.json");
+
+/// How long a flow may take before the test gives up on it.
+const DEADLINE: Duration = Duration::from_secs(20);
+
+/// Looks `hash` up in a range body that arrives all at once.
+fn find(body: &str, hash: &str) -> BreachResult {
+    let chunks = stream::iter([Ok::<_, Infallible>(body.as_bytes())]);
+    let Ok(count) = find_suffix(chunks, hash_suffix_of(hash))
+        .now_or_never()
+        .unwrap();
+    BreachResult::from_count(count.unwrap())
+}
+
+#[test]
+fn finds_both_modes_in_their_own_ranges() {
+    let sha1 = hash_password("hunter2", HashMode::Sha1, Normalization::Off);
+    let body = format!("{}:17043\r\n", hash_suffix_of(&sha1));
+    assert_eq!(find(&body, &sha1), BreachResult::Found { count: 17043 });
+
+    let ntlm = hash_password("hunter2", HashMode::Ntlm, Normalization::Off);
+    let body = format!("{}:912\r\n", hash_suffix_of(&ntlm));
+    assert_eq!(find(&body, &ntlm), BreachResult::Found { count: 912 });
+    assert_eq!(find(&body, &sha1), BreachResult::NotFound);
+}
+
+#[test]
+fn another_endpoint_doesnt_answer_from_the_old_ones_cache() {
+    let mut app = App::default();
+    let key = (HashMode::Sha1, "F3BBB".to_owned());
+    app.shared
+        .cache
+        .insert(key.clone(), Arc::new(Range::default()));
+
+    let _ = app.update(Message::Settings(settings_screen::Message::Endpoint(
+        DEFAULT_ENDPOINT.to_owned(),
+    )));
+    assert!(app.shared.cache.get(&key).is_some());
+    let _ = app.update(Message::Settings(settings_screen::Message::Endpoint(
+        "https://mirror.example/hibp".to_owned(),
+    )));
+    assert!(app.shared.cache.get(&key).is_none());
+    assert_eq!(
+        app.shared.settings.endpoint.as_str(),
+        "https://mirror.example/hibp/"
+    );
+}
+
+#[test]
+fn a_new_search_aborts_the_one_in_flight() {
+    let mut app = App::default();
+    let _ = app.update(Message::Check(check_screen::Message::Input(
+        "hunter2".to_owned().into(),
+    )));
+    let _first = app.update(Message::Check(check_screen::Message::Submit));
+    // A clone of a handle that aborts on drop would abort it too.
+    let first = std::mem::ManuallyDrop::new(app.check.search.clone().unwrap());
+    assert!(!first.is_aborted());
+
+    // Submitting again changes nothing while it runs; new input drops it.
+    let _ = app.update(Message::Check(check_screen::Message::Submit));
+    assert!(!first.is_aborted());
+    let _ = app.update(Message::Check(check_screen::Message::Input(
+        "hunter3".to_owned().into(),
+    )));
+    assert!(first.is_aborted());
+    assert!(app.check.search.is_none());
+
+    let _second = app.update(Message::Check(check_screen::Message::Submit));
+    assert!(!app.check.search.as_ref().unwrap().is_aborted());
+
+    let record = CheckRecord::new(
+        &hash_password("hunter2", HashMode::Sha1, Normalization::Off),
+        HashMode::Sha1,
+        BreachResult::NotFound,
+        LookupSource::Network,
+        &Thresholds::default(),
+    );
+    let generation = app.check.search_generation;
+    let _ = app.update(Message::Check(check_screen::Message::BreachResult(
+        generation,
+        Ok((record, Duration::ZERO)),
+    )));
+    assert!(app.check.search.is_none());
+    assert!(matches!(app.check.state, SearchResult::Breaches { .. }));
+}
+
+#[test]
+fn an_account_lookup_shows_its_breaches_or_its_error() {
+    let mut app = App::default();
+    let breaches = AccountBreaches {
+        account: "test@example.com".to_owned(),
+        breaches: vec!["Adobe".to_owned()],
+    };
+    let _ = app.update(Message::Check(check_screen::Message::AccountResult(Ok(
+        breaches,
+    ))));
+    match &app.check.state {
+        SearchResult::Account(account) => assert_eq!(account.breaches, ["Adobe"]),
+        state => panic!("expected the account's breaches, got {state:?}"),
+    }
+
+    let error = "the HIBP API key was rejected (401), check it in Settings";
+    let _ = app.update(Message::Check(check_screen::Message::AccountResult(Err(
+        error.to_owned(),
+    ))));
+    match &app.check.state {
+        SearchResult::Errored(shown) => assert_eq!(shown.message, error),
+        state => panic!("expected the error, got {state:?}"),
+    }
+}
+
+#[tokio::test]
+async fn an_account_lookup_needs_an_api_key() {
+    let error = hibp::breached_account(
+        build_client(&AppSettings::default().network()).unwrap(),
+        "test@example.com".to_owned(),
+        SecretString::default(),
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(error, hibp::ApiError::MissingKey));
+    assert!(error.to_string().contains("API key"), "{error}");
+}
+
+/// Settings that look everything up at `endpoint`, with nothing kept on
+/// disk.
+fn settings(endpoint: &str) -> AppSettings {
+    AppSettings {
+        endpoint: parse_endpoint(endpoint).unwrap(),
+        disk_cache: false,
+        ..AppSettings::default()
+    }
+}
+
+/// An app set up with `settings`.
+fn app(settings: AppSettings) -> App {
+    App::new(settings).unwrap()
+}
+
+/// Every message `task` produces, in the order they come.
+async fn outputs(task: Task<Message>) -> Vec<Message> {
+    let Some(stream) = iced_runtime::task::into_stream(task) else {
+        return Vec::new();
+    };
+    stream
+        .filter_map(|action| async move {
+            match action {
+                Action::Output(message) => Some(message),
+                _ => None,
+            }
+        })
+        .collect()
+        .await
+}
+
+/// Hands `message` to `update`, then everything its tasks send back, until
+/// nothing's left.
+async fn drive(app: &mut App, message: Message) {
+    let run = async {
+        let mut pending = VecDeque::from([message]);
+        while let Some(message) = pending.pop_front() {
+            pending.extend(outputs(app.update(message)).await);
+        }
+    };
+    tokio::time::timeout(DEADLINE, run)
+        .await
+        .expect("the flow didn't settle");
+}
+
+/// Types `password` into the check form and submits it.
+async fn check(app: &mut App, password: &str) {
+    drive(
+        app,
+        Message::Check(check_screen::Message::Input(password.to_owned().into())),
+    )
+    .await;
+    drive(app, Message::Check(check_screen::Message::Submit)).await;
+}
+
+fn hunter2() -> String {
+    hash_password("hunter2", HashMode::Sha1, Normalization::Off)
+}
+
+/// A server answering every range request with `response`.
+async fn serving(response: ResponseTemplate) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(response)
+        .mount(&server)
+        .await;
+    server
+}
+
+/// What the finished lookup found, if it finished.
+fn found(state: &SearchResult) -> Option<BreachResult> {
+    match state {
+        SearchResult::Breaches { record, .. } => Some(record.result),
+        _ => None,
+    }
+}
+
+#[tokio::test]
+async fn a_listed_password_is_found_with_its_count() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/range/F3BBB"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(RANGE))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let mut app = app(settings(&server.uri()));
+
+    check(&mut app, "hunter2").await;
+    assert_eq!(
+        found(&app.check.state),
+        Some(BreachResult::Found { count: 17206891 })
+    );
+}
+
+#[tokio::test]
+async fn an_unlisted_password_isnt_found() {
+    let server = serving(ResponseTemplate::new(200).set_body_string(RANGE)).await;
+    let mut app = app(settings(&server.uri()));
+
+    // Not in the fixture, whatever its prefix.
+    check(&mut app, "a password nobody has ever used 8c1f").await;
+    assert_eq!(found(&app.check.state), Some(BreachResult::NotFound));
+}
+
+#[tokio::test]
+async fn either_lookup_path_gives_the_same_answer() {
+    let server = serving(ResponseTemplate::new(200).set_body_string(RANGE)).await;
+    for cache_capacity in [0, 128] {
+        let mut app = app(AppSettings {
+            cache_capacity,
+            ..settings(&server.uri())
+        });
+        check(&mut app, "hunter2").await;
+        assert_eq!(
+            found(&app.check.state),
+            Some(BreachResult::Found { count: 17206891 }),
+            "cache capacity {cache_capacity}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn a_server_error_is_retried() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(RANGE))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let mut app = app(settings(&server.uri()));
+
+    check(&mut app, "hunter2").await;
+    assert_eq!(
+        found(&app.check.state),
+        Some(BreachResult::Found { count: 17206891 })
+    );
+}
+
+#[tokio::test]
+async fn rate_limiting_is_retried_once_then_reported() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "0"))
+        .expect(2)
+        .mount(&server)
+        .await;
+    let mut app = app(settings(&server.uri()));
+
+    check(&mut app, "hunter2").await;
+    match &app.check.state {
+        SearchResult::Errored(error) => assert!(error.message.contains("rate limited"), "{error}"),
+        state => panic!("expected a rate limit error, got {state:?}"),
+    }
+}
+
+#[tokio::test]
+async fn a_malformed_body_isnt_taken_for_not_found() {
+    let server = serving(ResponseTemplate::new(200).set_body_string("<html>hello</html>")).await;
+    let mut app = app(settings(&server.uri()));
+
+    check(&mut app, "hunter2").await;
+    assert!(
+        matches!(app.check.state, SearchResult::Unparsable(_)),
+        "{:?}",
+        app.check.state
+    );
+}
+
+#[tokio::test]
+async fn a_reset_connection_is_reported() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let _ = stream.read(&mut [0; 1024]).await;
+            // Dropped with no linger, the socket is reset rather than closed.
+            let _ = stream.set_zero_linger();
+        }
+    });
+    let mut app = app(settings(&format!("http://{address}")));
+
+    check(&mut app, "hunter2").await;
+    assert!(
+        matches!(app.check.state, SearchResult::Errored(_)),
+        "{:?}",
+        app.check.state
+    );
+}
+
+#[tokio::test]
+async fn the_hash_is_never_sent_whole() {
+    let server = serving(ResponseTemplate::new(200).set_body_string(RANGE)).await;
+    let mut app = app(settings(&server.uri()));
+
+    check(&mut app, "hunter2").await;
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 1);
+    let url = requests[0].url.to_string();
+    assert!(url.ends_with("/range/F3BBB"), "{url}");
+    assert!(!url.contains(&hash_password("hunter2", HashMode::Sha1, Normalization::Off)[5..]));
+}
+
+#[tokio::test]
+async fn the_health_check_reports_a_reachable_api_without_blocking() {
+    let server = serving(ResponseTemplate::new(200).set_body_string(RANGE)).await;
+    let mirror = format!("{}/mirror/v2", server.uri());
+    let mut app = app(settings(&mirror));
+
+    // Asking for a check only hands back its task; the answer comes later.
+    let task = app.update(Message::Check(check_screen::Message::CheckHealth));
+    assert!(app.check.health.is_none());
+    assert!(server.received_requests().await.unwrap().is_empty());
+
+    for message in outputs(task).await {
+        drive(&mut app, message).await;
+    }
+    assert!(
+        matches!(app.check.health, Some(Ok(_))),
+        "{:?}",
+        app.check.health
+    );
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].url.path(), "/mirror/v2/range/00000");
+}
+
+#[tokio::test]
+async fn the_health_check_reports_an_unreachable_api() {
+    let server = serving(ResponseTemplate::new(404)).await;
+    let mut app = app(settings(&server.uri()));
+
+    drive(&mut app, Message::Check(check_screen::Message::CheckHealth)).await;
+    match &app.check.health {
+        Some(Err(error)) => assert!(error.message.contains("404"), "{error}"),
+        health => panic!("expected the check to fail, got {health:?}"),
+    }
+}
+
+#[tokio::test]
+async fn the_health_check_goes_through_the_proxy() {
+    // A proxy is sent the whole URL, so the API's host needn't resolve.
+    let proxy = serving(ResponseTemplate::new(200).set_body_string(RANGE)).await;
+    let mut settings = settings("http://api.invalid");
+    settings.proxy.url = proxy.uri();
+    let mut app = app(settings);
+
+    drive(&mut app, Message::Check(check_screen::Message::CheckHealth)).await;
+    assert!(
+        matches!(app.check.health, Some(Ok(_))),
+        "{:?}",
+        app.check.health
+    );
+    let requests = proxy.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].url.path(), "/range/00000");
+}
+
+fn source(state: &SearchResult) -> Option<LookupSource> {
+    match state {
+        SearchResult::Breaches { record, .. } => Some(record.source),
+        _ => None,
+    }
+}
+
+#[tokio::test]
+async fn each_answer_is_labeled_with_where_it_came_from() {
+    let server = serving(ResponseTemplate::new(200).set_body_string(RANGE)).await;
+    let dir = tempfile::tempdir().unwrap();
+    let disk = |settings: &AppSettings| {
+        DiskCache::in_dir(
+            dir.path().to_owned(),
+            &settings.endpoint,
+            settings.disk_cache_ttl,
+        )
+    };
+    let cached = AppSettings {
+        cache_capacity: 128,
+        ..settings(&server.uri())
+    };
+
+    let mut first = app(cached.clone());
+    first.shared.disk_cache = Some(disk(&cached));
+    check(&mut first, "hunter2").await;
+    assert_eq!(source(&first.check.state), Some(LookupSource::Network));
+    check(&mut first, "hunter2").await;
+    assert_eq!(source(&first.check.state), Some(LookupSource::Memory));
+
+    // A new run has nothing in memory, but the range is still on disk.
+    let mut second = app(cached.clone());
+    second.shared.disk_cache = Some(disk(&cached));
+    check(&mut second, "hunter2").await;
+    assert_eq!(source(&second.check.state), Some(LookupSource::Disk));
+    assert_eq!(
+        found(&second.check.state),
+        Some(BreachResult::Found { count: 17206891 })
+    );
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+
+    // Without a cache, every answer is fetched.
+    let mut uncached = app(AppSettings {
+        cache_capacity: 0,
+        ..settings(&server.uri())
+    });
+    check(&mut uncached, "hunter2").await;
+    check(&mut uncached, "hunter2").await;
+    assert_eq!(source(&uncached.check.state), Some(LookupSource::Network));
+    assert_eq!(server.received_requests().await.unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn an_answer_carries_how_long_it_took() {
+    let server = serving(
+        ResponseTemplate::new(200)
+            .set_body_string(RANGE)
+            .set_delay(Duration::from_millis(200)),
+    )
+    .await;
+    let mut app = app(settings(&server.uri()));
+
+    check(&mut app, "hunter2").await;
+    match &app.check.state {
+        SearchResult::Breaches { duration, .. } => {
+            assert!(*duration >= Duration::from_millis(200), "{duration:?}")
+        }
+        state => panic!("expected an answer, got {state:?}"),
+    }
+}
+
+#[tokio::test]
+async fn a_typed_hash_is_looked_up_as_given() {
+    let server = serving(ResponseTemplate::new(200).set_body_string(RANGE)).await;
+    let mut app = app(settings(&server.uri()));
+    assert!(app.check.is_masked());
+
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::HashInput(true)),
+    )
+    .await;
+    assert!(!app.check.is_masked());
+    let hunter2 = hash_password("hunter2", HashMode::Sha1, Normalization::Off);
+    check(&mut app, &format!(" {} ", hunter2.to_ascii_lowercase())).await;
+    assert_eq!(app.check.current_hash.expose(), hunter2);
+    assert_eq!(
+        found(&app.check.state),
+        Some(BreachResult::Found { count: 17206891 })
+    );
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].url.path(), "/range/F3BBB");
+}
+
+#[tokio::test]
+async fn a_typed_hash_that_isnt_one_cant_be_checked() {
+    let mut app = app(AppSettings::default());
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::HashInput(true)),
+    )
+    .await;
+    let hunter2 = hash_password("hunter2", HashMode::Sha1, Normalization::Off);
+    for input in ["F3BBB", "hunter2", &format!("{hunter2}0")] {
+        drive(
+            &mut app,
+            Message::Check(check_screen::Message::Input(input.to_owned().into())),
+        )
+        .await;
+        assert!(app.check.current_hash.is_empty(), "{input}");
+    }
+}
+
+#[tokio::test]
+async fn edge_whitespace_is_flagged_and_trimmed_before_checking() {
+    let server = serving(ResponseTemplate::new(200).set_body_string(RANGE)).await;
+    let hunter2 = hash_password("hunter2", HashMode::Sha1, Normalization::Off);
+    for typed in [
+        "hunter2\n",
+        "hunter2\r\n",
+        "\thunter2",
+        "\u{a0}hunter2\u{a0}",
+    ] {
+        let mut app = app(settings(&server.uri()));
+        assert!(has_edge_whitespace(typed), "{typed:?}");
+        drive(
+            &mut app,
+            Message::Check(check_screen::Message::Input(typed.to_owned().into())),
+        )
+        .await;
+        assert_ne!(app.check.current_hash.expose(), hunter2, "{typed:?}");
+
+        drive(
+            &mut app,
+            Message::Check(check_screen::Message::TrimPassword),
+        )
+        .await;
+        assert_eq!(app.check.password.expose(), "hunter2");
+        assert_eq!(app.check.current_hash.expose(), hunter2, "{typed:?}");
+        assert_eq!(
+            found(&app.check.state),
+            Some(BreachResult::Found { count: 17206891 }),
+            "{typed:?}"
+        );
+    }
+    // Spaces inside are part of the password.
+    assert!(!has_edge_whitespace("hunter 2"));
+    assert!(!has_edge_whitespace(""));
+}
+
+/// A key store in memory, which fails every call once told to.
+#[derive(Debug, Default)]
+struct MemoryStore {
+    key: std::sync::Mutex<Option<String>>,
+    locked: std::sync::atomic::AtomicBool,
+}
+
+impl MemoryStore {
+    fn check(&self) -> Result<(), keystore::KeyStoreError> {
+        if self.locked.load(std::sync::atomic::Ordering::Relaxed) {
+            return Err(keystore::KeyStoreError::Keyring(
+                keyring::Error::NoStorageAccess("locked".into()),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl KeyStore for MemoryStore {
+    fn load(&self) -> Result<Option<SecretString>, keystore::KeyStoreError> {
+        self.check()?;
+        Ok(self.key.lock().unwrap().clone().map(Into::into))
+    }
+
+    fn save(&self, key: &str) -> Result<(), keystore::KeyStoreError> {
+        self.check()?;
+        *self.key.lock().unwrap() = Some(key.to_owned());
+        Ok(())
+    }
+
+    fn delete(&self) -> Result<(), keystore::KeyStoreError> {
+        self.check()?;
+        *self.key.lock().unwrap() = None;
+        Ok(())
+    }
+
+    fn is_secure(&self) -> bool {
+        true
+    }
+}
+
+#[tokio::test]
+async fn the_stored_api_key_is_loaded_saved_and_deleted() {
+    let store = Arc::new(MemoryStore::default());
+    *store.key.lock().unwrap() = Some("stored".to_owned());
+    let mut app = app(settings("http://127.0.0.1:1/"));
+    let opened = (store.clone() as Arc<dyn KeyStore>, store.load().unwrap());
+    drive(
+        &mut app,
+        Message::Settings(settings_screen::Message::KeyStoreOpened(Ok(opened))),
+    )
+    .await;
+    assert_eq!(app.shared.settings.api_key.expose(), "stored");
+
+    drive(
+        &mut app,
+        Message::Settings(settings_screen::Message::ApiKey(
+            " edited ".to_owned().into(),
+        )),
+    )
+    .await;
+    drive(
+        &mut app,
+        Message::Settings(settings_screen::Message::SaveApiKey),
+    )
+    .await;
+    assert_eq!(store.key.lock().unwrap().as_deref(), Some("edited"));
+    assert_eq!(
+        app.settings_screen.api_key_status,
+        Some(Ok("API key saved".to_owned()))
+    );
+
+    drive(
+        &mut app,
+        Message::Settings(settings_screen::Message::DeleteApiKey),
+    )
+    .await;
+    assert_eq!(*store.key.lock().unwrap(), None);
+    assert!(app.shared.settings.api_key.is_empty());
+    assert_eq!(
+        app.settings_screen.api_key_status,
+        Some(Ok("API key deleted".to_owned()))
+    );
+}
+
+#[test]
+fn the_api_key_is_never_printed_with_the_settings() {
+    let key = "0123456789abcdef0123456789abcdef";
+    let settings = AppSettings {
+        api_key: key.to_owned().into(),
+        ..AppSettings::default()
+    };
+    assert!(!format!("{settings:?}").contains(key));
+}
+
+#[tokio::test]
+async fn the_settings_are_saved_only_after_a_change() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join(config::FILE_NAME);
+    let mut app = app(AppSettings::default());
+    app.config_dir = Some(dir.path().to_owned());
+
+    drive(&mut app, Message::App(AppMessage::ToggleShortcuts)).await;
+    assert!(!file.exists(), "saved without a setting changing");
+
+    drive(
+        &mut app,
+        Message::Settings(settings_screen::Message::Decoys(true)),
+    )
+    .await;
+    assert!(config::load(dir.path()).settings.decoys);
+    assert!(!app.shared.settings_dirty);
+}
+
+#[test]
+fn the_proxy_password_is_never_printed_with_the_settings() {
+    let settings = AppSettings {
+        proxy: ProxySettings {
+            url: "http://proxy.internal:3128".to_owned(),
+            username: "user".to_owned(),
+            password: "hunter2".to_owned().into(),
+        },
+        ..AppSettings::default()
+    };
+    assert!(!format!("{settings:?}").contains("hunter2"));
+}
+
+#[tokio::test]
+async fn a_locked_keyring_says_how_to_fix_it() {
+    let store = Arc::new(MemoryStore::default());
+    let mut app = app(settings("http://127.0.0.1:1/"));
+    let opened = (store.clone() as Arc<dyn KeyStore>, None);
+    drive(
+        &mut app,
+        Message::Settings(settings_screen::Message::KeyStoreOpened(Ok(opened))),
+    )
+    .await;
+    store
+        .locked
+        .store(true, std::sync::atomic::Ordering::Relaxed);
+
+    drive(
+        &mut app,
+        Message::Settings(settings_screen::Message::ApiKey("key".to_owned().into())),
+    )
+    .await;
+    drive(
+        &mut app,
+        Message::Settings(settings_screen::Message::SaveApiKey),
+    )
+    .await;
+    let error = "the system keyring is locked or refused access, unlock it and try again (locked)";
+    assert_eq!(
+        app.settings_screen.api_key_status,
+        Some(Err(error.to_owned()))
+    );
+    drive(
+        &mut app,
+        Message::Settings(settings_screen::Message::DeleteApiKey),
+    )
+    .await;
+    assert_eq!(
+        app.settings_screen.api_key_status,
+        Some(Err(error.to_owned()))
+    );
+}
+
+#[tokio::test]
+async fn the_details_panel_has_the_whole_range_to_show() {
+    let server = serving(ResponseTemplate::new(200).set_body_string(RANGE)).await;
+    let mut app = app(AppSettings {
+        cache_capacity: 128,
+        ..settings(&server.uri())
+    });
+    check(&mut app, "hunter2").await;
+    let SearchResult::Breaches {
+        range: Some(range),
+        record,
+        ..
+    } = &app.check.state
+    else {
+        panic!(
+            "expected the range with the answer, got {:?}",
+            app.check.state
+        );
+    };
+    let count = match record.result {
+        BreachResult::Found { count } => count,
+        BreachResult::NotFound => panic!("hunter2 is in the fixture"),
+    };
+    let stats = range::stats(range, Some(count)).unwrap();
+    assert_eq!(stats.suffixes, range.len());
+    assert_eq!(stats.rank, Some(1));
+
+    assert!(!app.check.show_details);
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::ToggleDetails),
+    )
+    .await;
+    assert!(app.check.show_details);
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::ToggleDetails),
+    )
+    .await;
+    assert!(!app.check.show_details);
+}
+
+#[tokio::test]
+async fn a_padding_entry_for_the_hash_is_not_found() {
+    let body = format!(
+        "0018A45C4D1DEF81644B54AB7F969B88D65:1\r\n{}:0\r\n",
+        hash_suffix_of(&hash_password(
+            "hunter2",
+            HashMode::Sha1,
+            Normalization::Off
+        ))
+    );
+    let server = serving(ResponseTemplate::new(200).set_body_string(body)).await;
+    for cache_capacity in [0, 128] {
+        let mut app = app(AppSettings {
+            cache_capacity,
+            ..settings(&server.uri())
+        });
+        check(&mut app, "hunter2").await;
+        assert_eq!(
+            found(&app.check.state),
+            Some(BreachResult::NotFound),
+            "cache capacity {cache_capacity}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn a_generated_passphrase_can_be_taken_to_the_checker() {
+    use crate::generator_screen::Message as Generator;
+
+    let mut app = app(AppSettings::default());
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::HashInput(true)),
+    )
+    .await;
+    for message in [
+        Generator::Kind(generator::Kind::Passphrase),
+        Generator::Words(5),
+        Generator::Separator(" ".to_owned()),
+        Generator::Generate,
+    ] {
+        drive(&mut app, Message::Generator(message)).await;
+    }
+    let generated = app.generator.generated().unwrap().expose().to_owned();
+    assert_eq!(generated.split(' ').count(), 5);
+
+    drive(&mut app, Message::Generator(Generator::Use)).await;
+    assert_eq!(app.screen, Screen::Check);
+    assert!(!app.check.hash_input);
+    assert_eq!(app.check.password.expose(), generated);
+    assert_eq!(
+        app.check.current_hash.expose(),
+        hash_password(&generated, HashMode::Sha1, Normalization::Nfc)
+    );
+}
+
+#[tokio::test]
+async fn the_copied_confirmation_lasts_until_the_latest_copy_expires() {
+    let mut app = app(AppSettings::default());
+
+    // There's nothing to copy yet.
+    let _ = app.update(Message::Check(check_screen::Message::CopyHash));
+    assert!(!app.check.hash_copied);
+    assert_eq!(app.check.hash_copies, 0);
+
+    let _ = app.update(Message::Check(check_screen::Message::Input(
+        "hunter2".to_owned().into(),
+    )));
+    let _ = app.update(Message::Check(check_screen::Message::CopyHash));
+    assert!(app.check.hash_copied);
+    let _ = app.update(Message::Check(check_screen::Message::CopyHash));
+    assert_eq!(app.check.hash_copies, 2);
+
+    // The first copy's timer mustn't cut the second one's short.
+    let _ = app.update(Message::Check(check_screen::Message::CopyFeedbackExpired(
+        1,
+    )));
+    assert!(app.check.hash_copied);
+    let _ = app.update(Message::Check(check_screen::Message::CopyFeedbackExpired(
+        2,
+    )));
+    assert!(!app.check.hash_copied);
+
+    // Clearing drops the confirmation early.
+    let _ = app.update(Message::Check(check_screen::Message::CopyHash));
+    let _ = app.update(Message::Check(check_screen::Message::Clear));
+    assert!(!app.check.hash_copied);
+}
+
+#[tokio::test]
+async fn escape_clears_everything_and_nothing_is_left_to_clear() {
+    let server = serving(ResponseTemplate::new(200).set_body_string(RANGE)).await;
+    let mut app = app(settings(&server.uri()));
+    assert!(!app.can_clear());
+
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::ShowPassword(true)),
+    )
+    .await;
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::RevealHash(true)),
+    )
+    .await;
+    check(&mut app, "hunter2").await;
+    assert!(found(&app.check.state).is_some());
+    assert!(app.can_clear());
+
+    drive(
+        &mut app,
+        Message::App(AppMessage::Shortcut(Shortcut::Escape)),
+    )
+    .await;
+    assert!(app.check.password.is_empty());
+    assert!(app.check.current_hash.is_empty());
+    assert!(!app.check.show && !app.check.reveal_hash && !app.check.hash_copied);
+    assert!(app.check.search.is_none());
+    assert!(matches!(app.check.state, SearchResult::NotSubmitted));
+    assert!(!app.can_clear());
+}
+
+#[tokio::test]
+async fn an_answer_arriving_after_a_clear_is_dropped() {
+    let server = serving(ResponseTemplate::new(200).set_body_string(RANGE)).await;
+    let mut app = app(settings(&server.uri()));
+
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::Input("hunter2".to_owned().into())),
+    )
+    .await;
+    let lookup = app.update(Message::Check(check_screen::Message::Submit));
+    assert!(matches!(app.check.state, SearchResult::Searching));
+    let answers = outputs(lookup).await;
+    assert!(!answers.is_empty());
+
+    drive(&mut app, Message::Check(check_screen::Message::Clear)).await;
+    for answer in answers {
+        drive(&mut app, answer).await;
+    }
+    assert!(matches!(app.check.state, SearchResult::NotSubmitted));
+    assert!(app.check.password.is_empty());
+}
+
+/// The error the batch screen shows for the last import, if it failed.
+fn import_error(app: &App) -> Option<&str> {
+    match &app.batch.import {
+        Some(Err(error)) => Some(error),
+        _ => None,
+    }
+}
+
+#[tokio::test]
+async fn every_file_of_a_multi_file_drop_is_turned_away() {
+    let dir = tempfile::tempdir().unwrap();
+    let list = dir.path().join("list.txt");
+    std::fs::write(&list, "hunter2\npassword\n").unwrap();
+    let mut app = app(AppSettings::default());
+
+    drive(&mut app, Message::Batch(batch_screen::Message::FileHovered)).await;
+    drive(&mut app, Message::Batch(batch_screen::Message::FileHovered)).await;
+    for _ in 0..2 {
+        drive(
+            &mut app,
+            Message::Batch(batch_screen::Message::FileDropped(list.clone())),
+        )
+        .await;
+        assert_eq!(app.screen, Screen::Batch);
+        assert_eq!(import_error(&app), Some("drop one file at a time"));
+    }
+
+    // A drop of one file on its own after that is imported.
+    drive(&mut app, Message::Batch(batch_screen::Message::FileHovered)).await;
+    drive(
+        &mut app,
+        Message::Batch(batch_screen::Message::FileDropped(list)),
+    )
+    .await;
+    match &app.batch.import {
+        Some(Ok(batch)) => assert_eq!(batch.lines.len(), 2),
+        _ => panic!("the list wasn't imported"),
+    }
+}
+
+#[tokio::test]
+async fn only_lists_and_exports_can_be_dropped() {
+    let mut app = app(AppSettings::default());
+    drive(&mut app, Message::Batch(batch_screen::Message::FileHovered)).await;
+    drive(
+        &mut app,
+        Message::Batch(batch_screen::Message::FileDropped("/tmp/photo.JPG".into())),
+    )
+    .await;
+    assert_eq!(
+        import_error(&app),
+        Some("only .txt lists and .csv exports can be imported, not photo.JPG")
+    );
+}
+
+#[tokio::test]
+async fn the_progress_bar_shows_only_while_a_batch_runs() {
+    let mut app = app(AppSettings::default());
+    assert!(!app.batch.shows_progress());
+
+    // Nothing's polled, so the run stays in flight.
+    let _run = app.batch.run(Vec::new(), &mut app.shared);
+    assert!(app.batch.shows_progress());
+    let stats = BatchStats {
+        total: 3,
+        done: 1,
+        ..Default::default()
+    };
+    drive(
+        &mut app,
+        Message::Batch(batch_screen::Message::Progress(stats)),
+    )
+    .await;
+    assert_eq!(app.batch.stats, Some(stats));
+}
+
+#[tokio::test]
+async fn a_failed_theme_detection_falls_back_to_dark() {
+    let mut settings = AppSettings::default();
+    settings.appearance.theme = config::ThemeChoice::System;
+    let mut app = app(settings);
+    let (light, dark) = (
+        app.shared.settings.appearance.light_theme.clone(),
+        app.shared.settings.appearance.dark_theme.clone(),
+    );
+
+    drive(&mut app, Message::App(AppMessage::SystemTheme(Ok(false)))).await;
+    assert_eq!(app.theme(), light);
+    drive(
+        &mut app,
+        Message::App(AppMessage::SystemTheme(Err("no portal".to_owned()))),
+    )
+    .await;
+    assert_eq!(app.theme(), dark);
+    drive(&mut app, Message::App(AppMessage::SystemTheme(Ok(false)))).await;
+    assert_eq!(app.theme(), light);
+}
+
+#[tokio::test]
+async fn repeated_submits_start_one_lookup() {
+    let server = serving(ResponseTemplate::new(200).set_body_string(RANGE)).await;
+    let mut app = app(settings(&server.uri()));
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::Input("hunter2".to_owned().into())),
+    )
+    .await;
+
+    let tasks: Vec<_> = (0..3)
+        .map(|_| app.update(Message::Check(check_screen::Message::Submit)))
+        .collect();
+    let ran: Vec<_> = tasks
+        .into_iter()
+        .filter_map(iced_runtime::task::into_stream)
+        .collect();
+    assert_eq!(ran.len(), 1);
+    assert_eq!(app.check.search_generation, 1);
+    assert!(app.submit_message().is_none());
+
+    for message in ran.into_iter().next().unwrap().collect::<Vec<_>>().await {
+        if let Action::Output(message) = message {
+            drive(&mut app, message).await;
+        }
+    }
+    assert_eq!(
+        found(&app.check.state),
+        Some(BreachResult::Found { count: 17206891 })
+    );
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn a_fetch_from_the_old_endpoint_isnt_shared_after_a_change() {
+    let slow = ResponseTemplate::new(200)
+        .set_body_string(RANGE)
+        .set_delay(Duration::from_secs(5));
+    let old = serving(slow).await;
+    let mirror = serving(ResponseTemplate::new(200).set_body_string("0000:1")).await;
+    let mut app = app(settings(&old.uri()));
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::Input("hunter2".to_owned().into())),
+    )
+    .await;
+    let mut first =
+        iced_runtime::task::into_stream(app.update(Message::Check(check_screen::Message::Submit)))
+            .unwrap();
+    // Long enough for the old endpoint's fetch to start, not to finish.
+    let _ = tokio::time::timeout(Duration::from_millis(500), first.next()).await;
+
+    drive(
+        &mut app,
+        Message::Settings(settings_screen::Message::Endpoint(mirror.uri())),
+    )
+    .await;
+    check(&mut app, "hunter2").await;
+    assert_eq!(found(&app.check.state), Some(BreachResult::NotFound));
+    assert_eq!(mirror.received_requests().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn only_the_latest_submission_shows() {
+    let server = serving(ResponseTemplate::new(200).set_body_string(RANGE)).await;
+    let mut app = app(settings(&server.uri()));
+
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::Input("hunter2".to_owned().into())),
+    )
+    .await;
+    let first = outputs(app.update(Message::Check(check_screen::Message::Submit))).await;
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::Input("password".to_owned().into())),
+    )
+    .await;
+    let second = outputs(app.update(Message::Check(check_screen::Message::Submit))).await;
+    assert_eq!(app.check.search_generation, 2);
+
+    // hunter2's answer, arriving late, isn't shown for "password".
+    for message in first {
+        drive(&mut app, message).await;
+    }
+    assert!(matches!(app.check.state, SearchResult::Searching));
+    for message in second {
+        drive(&mut app, message).await;
+    }
+    assert_eq!(found(&app.check.state), Some(BreachResult::NotFound));
+}
+
+#[tokio::test]
+async fn an_older_answer_arriving_first_leaves_the_new_search_running() {
+    let server = serving(ResponseTemplate::new(200).set_body_string(RANGE)).await;
+    let mut app = app(AppSettings {
+        cache_capacity: 0,
+        ..settings(&server.uri())
+    });
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::Input("hunter2".to_owned().into())),
+    )
+    .await;
+    let _stale = app.update(Message::Check(check_screen::Message::Submit));
+    let stale_generation = app.check.search_generation;
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::Input(
+            "not hunter2".to_owned().into(),
+        )),
+    )
+    .await;
+    let fresh = app.update(Message::Check(check_screen::Message::Submit));
+    assert_eq!(app.check.search_generation, stale_generation + 1);
+
+    // hunter2's answer, and its range, land before the new lookup's.
+    let record = CheckRecord::new(
+        &hunter2(),
+        HashMode::Sha1,
+        BreachResult::Found { count: 17206891 },
+        LookupSource::Network,
+        &app.shared.settings.severity,
+    );
+    let late = Message::Check(check_screen::Message::BreachResult(
+        stale_generation,
+        Ok((record, Duration::ZERO)),
+    ));
+    drive(&mut app, late).await;
+    assert!(matches!(app.check.state, SearchResult::Searching));
+    assert!(app.check.search.is_some());
+    let error = cybersec_wow::error::LookupError::other("an old failure");
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::BreachResult(
+            stale_generation,
+            Err(error),
+        )),
+    )
+    .await;
+    assert!(matches!(app.check.state, SearchResult::Searching));
+    assert!(app.check.search.is_some());
+
+    for message in outputs(fresh).await {
+        drive(&mut app, message).await;
+    }
+    assert_eq!(found(&app.check.state), Some(BreachResult::NotFound));
+}
+
+/// An app that checks what's typed once typing pauses for 50ms.
+fn auto_checking(endpoint: &str) -> App {
+    app(AppSettings {
+        auto_check: true,
+        auto_check_delay: Duration::from_millis(50),
+        ..settings(endpoint)
+    })
+}
+
+#[tokio::test]
+async fn typing_fast_checks_once_with_the_final_value() {
+    let server = serving(ResponseTemplate::new(200).set_body_string(RANGE)).await;
+    let mut app = auto_checking(&server.uri());
+
+    let timers: Vec<_> = (1..="hunter2".len())
+        .map(|typed| {
+            app.update(Message::Check(check_screen::Message::Input(
+                "hunter2"[..typed].to_owned().into(),
+            )))
+        })
+        .collect();
+    // Every keystroke's timer goes off, but only the last one checks.
+    let fired = iced::futures::future::join_all(timers.into_iter().map(outputs)).await;
+    let fired: Vec<_> = fired.into_iter().flatten().collect();
+    assert_eq!(fired.len(), 7);
+    for message in fired {
+        drive(&mut app, message).await;
+    }
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].url.path(), "/range/F3BBB");
+    assert_eq!(
+        found(&app.check.state),
+        Some(BreachResult::Found { count: 17206891 })
+    );
+}
+
+#[tokio::test]
+async fn submitting_before_the_pause_checks_once() {
+    let server = serving(ResponseTemplate::new(200).set_body_string(RANGE)).await;
+    let mut app = auto_checking(&server.uri());
+
+    let timer = app.update(Message::Check(check_screen::Message::Input(
+        "hunter2".to_owned().into(),
+    )));
+    drive(&mut app, Message::Check(check_screen::Message::Submit)).await;
+    for message in outputs(timer).await {
+        drive(&mut app, message).await;
+    }
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn a_garbled_range_can_be_retried() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("<html>maintenance</html>"))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(RANGE))
+        .mount(&server)
+        .await;
+    let mut app = app(settings(&server.uri()));
+
+    check(&mut app, "hunter2").await;
+    assert!(
+        matches!(
+            app.check.state,
+            SearchResult::Unparsable(ParseError::MalformedLine { line_no: 1, .. })
+        ),
+        "{:?}",
+        app.check.state
+    );
+    // The details say what didn't parse.
+    let error = app.check.last_error.as_ref().unwrap();
+    assert!(
+        error.details.chain[0].contains("<html>maintenance</html>"),
+        "{error:?}"
+    );
+
+    // It's the failed check that's retried, not what the field holds now.
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::Input("password".to_owned().into())),
+    )
+    .await;
+    drive(&mut app, Message::Check(check_screen::Message::Retry)).await;
+    assert_eq!(
+        found(&app.check.state),
+        Some(BreachResult::Found { count: 17206891 })
+    );
+}
+
+#[tokio::test]
+async fn a_dropped_connection_can_be_retried() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let broken = Arc::new(AtomicBool::new(true));
+    let serving = broken.clone();
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let _ = stream.read(&mut [0; 1024]).await;
+            if serving.load(Ordering::Relaxed) {
+                let _ = stream.set_zero_linger();
+                continue;
+            }
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{RANGE}",
+                RANGE.len()
+            );
+            let _ = stream.write_all(response.as_bytes()).await;
+        }
+    });
+    let mut app = app(settings(&format!("http://{address}")));
+
+    check(&mut app, "hunter2").await;
+    match &app.check.state {
+        SearchResult::Errored(error) => {
+            assert_eq!(error.kind, FailureKind::Network);
+            assert_eq!(error.details.url.as_ref().unwrap().path(), "/range/F3BBB");
+        }
+        state => panic!("expected a network error, got {state:?}"),
+    }
+
+    broken.store(false, Ordering::Relaxed);
+    drive(&mut app, Message::Check(check_screen::Message::Retry)).await;
+    assert_eq!(
+        found(&app.check.state),
+        Some(BreachResult::Found { count: 17206891 })
+    );
+}
+
+/// The bounds of every container, row, column and button an operation
+/// passes, in tree order.
+struct Bounds(Vec<iced::Rectangle>);
+
+impl iced_runtime::core::widget::Operation for Bounds {
+    fn container(
+        &mut self,
+        _id: Option<&iced_runtime::core::widget::Id>,
+        bounds: iced::Rectangle,
+        operate_on_children: &mut dyn FnMut(&mut dyn iced_runtime::core::widget::Operation),
+    ) {
+        self.0.push(bounds);
+        operate_on_children(self);
+    }
+}
+
+/// The software renderer, so no GPU is needed.
+fn renderer() -> iced::Renderer {
+    iced::Renderer::Secondary(iced_tiny_skia::Renderer::new(
+        iced::Font::default(),
+        iced::Pixels(16.),
+    ))
+}
+
+/// The bounds of every container, row, column and button in `element`
+/// laid out at `size`.
+fn laid_out(element: iced::Element<'_, Message>, size: iced::Size) -> Vec<iced::Rectangle> {
+    use iced_runtime::user_interface::{Cache, UserInterface};
+
+    let mut renderer = renderer();
+    let mut ui = UserInterface::build(element, size, Cache::default(), &mut renderer);
+    let mut bounds = Bounds(Vec::new());
+    ui.operate(&renderer, &mut bounds);
+    bounds.0
+}
+
+/// The messages `element` gives when the middle of each of its
+/// containers, rows, columns and buttons is clicked in turn, laid out at
+/// the default size. A disabled button gives none.
+fn clicks(element: iced::Element<'_, Message>) -> Vec<Message> {
+    use iced::mouse;
+    use iced_runtime::core::clipboard;
+    use iced_runtime::user_interface::{Cache, UserInterface};
+
+    let mut renderer = renderer();
+    let mut ui = UserInterface::build(element, BREAKPOINTS[1], Cache::default(), &mut renderer);
+    let mut bounds = Bounds(Vec::new());
+    ui.operate(&renderer, &mut bounds);
+    let mut messages = Vec::new();
+    for target in bounds.0 {
+        let position = target.center();
+        let events = [
+            mouse::Event::CursorMoved { position },
+            mouse::Event::ButtonPressed(mouse::Button::Left),
+            mouse::Event::ButtonReleased(mouse::Button::Left),
+        ]
+        .map(iced::Event::Mouse);
+        ui.update(
+            &events,
+            mouse::Cursor::Available(position),
+            &mut renderer,
+            &mut clipboard::Null,
+            &mut messages,
+        );
+    }
+    messages
+}
+
+/// The window at its narrowest, at the default size, and wide.
+const BREAKPOINTS: [iced::Size; 3] = [
+    MIN_WINDOW_SIZE,
+    iced::Size::new(640., 480.),
+    iced::Size::new(1400., 900.),
+];
+
+#[tokio::test]
+async fn tabs_that_dont_fit_move_down_rather_than_squeeze() {
+    use crate::strings::{Language, LanguageChoice};
+
+    for language in Language::ALL {
+        let mut app = app(AppSettings {
+            language: LanguageChoice::Fixed(language),
+            ..AppSettings::default()
+        });
+        for screen in Screen::ALL {
+            drive(&mut app, Message::App(AppMessage::Screen(screen))).await;
+            // The bar, the tabs' box and row, then each tab and the shortcuts
+            // button.
+            let natural = laid_out(app.tab_bar(), iced::Size::new(10_000., 100.));
+            assert_eq!(natural.len(), 3 + Screen::ALL.len() + 1);
+            for size in BREAKPOINTS {
+                let fitted = laid_out(app.tab_bar(), size);
+                for (fitted, natural) in fitted[3..].iter().zip(&natural[3..]) {
+                    assert_eq!(fitted.size(), natural.size(), "{language:?} at {size:?}");
+                    assert!(
+                        fitted.x + fitted.width <= size.width,
+                        "{fitted:?} at {size:?}"
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn the_content_column_fills_narrow_windows_and_centers_in_wide_ones() {
+    assert!(BREAKPOINTS[2].width > CONTENT_MAX_WIDTH);
+    let mut app = app(AppSettings::default());
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::Input("hunter2".to_owned().into())),
+    )
+    .await;
+    let id = window::Id::unique();
+    for size in BREAKPOINTS {
+        let column = CONTENT_MAX_WIDTH.min(size.width);
+        let nodes = laid_out(app.view(id), size);
+        let content = nodes
+            .iter()
+            .find(|bounds| (bounds.width - column).abs() < 0.5 && bounds.y > 0.)
+            .unwrap_or_else(|| panic!("no {column}px column at {size:?}"));
+        assert!(
+            (content.x - (size.width - column) / 2.).abs() < 0.5,
+            "{content:?}"
+        );
+        for bounds in &nodes {
+            assert!(
+                bounds.x + bounds.width <= size.width + 0.5,
+                "{bounds:?} at {size:?}"
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn comparing_checks_both_passwords_side_by_side() {
+    use crate::compare_screen::Message as Compare;
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/range/F3BBB"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(RANGE))
+        .expect(1)
+        .mount(&server)
+        .await;
+    // Any other range, which lists nothing else of ours.
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(RANGE))
+        .mount(&server)
+        .await;
+    let mut app = app(settings(&server.uri()));
+    for message in [
+        Compare::Old("hunter2".to_owned().into()),
+        Compare::New("battery staple horse".to_owned().into()),
+        Compare::Submit,
+    ] {
+        drive(&mut app, Message::Compare(message)).await;
+    }
+
+    let outcomes = app
+        .compare
+        .results()
+        .expect("the check ran")
+        .each_ref()
+        .map(|item| item.as_ref().map(|item| item.outcome.clone()));
+    assert_eq!(outcomes, [Some(Ok(Some(17206891))), Some(Ok(None))]);
+    assert!(app.compare.can_submit(), "the run finished");
+    assert_eq!(server.received_requests().await.unwrap().len(), 2);
+}
+
+/// Like [`drive`], with `clipboard` standing in for the system's: reads get
+/// what it holds, which is `None` for nothing or something other than text,
+/// and writes replace it. Returns everything written, in order.
+async fn drive_with_clipboard(
+    app: &mut App,
+    message: Message,
+    clipboard: &mut Option<String>,
+) -> Vec<String> {
+    let run = async {
+        let mut writes = Vec::new();
+        let mut pending = VecDeque::from([message]);
+        while let Some(message) = pending.pop_front() {
+            let Some(mut stream) = iced_runtime::task::into_stream(app.update(message)) else {
+                continue;
+            };
+            while let Some(action) = stream.next().await {
+                match action {
+                    Action::Output(message) => pending.push_back(message),
+                    Action::Clipboard(iced_runtime::clipboard::Action::Read {
+                        channel, ..
+                    }) => {
+                        let _ = channel.send(clipboard.clone());
+                    }
+                    Action::Clipboard(iced_runtime::clipboard::Action::Write {
+                        contents, ..
+                    }) => {
+                        *clipboard = Some(contents.clone());
+                        writes.push(contents);
+                    }
+                    _ => {}
+                }
+            }
+        }
+        writes
+    };
+    tokio::time::timeout(DEADLINE, run)
+        .await
+        .expect("the flow didn't settle")
+}
+
+#[tokio::test]
+async fn paste_and_check_checks_what_was_copied() {
+    let server = serving(ResponseTemplate::new(200).set_body_string(RANGE)).await;
+    let mut app = app(settings(&server.uri()));
+    app.screen = crate::screen::Screen::Batch;
+    let mut clipboard = Some("hunter2".to_owned());
+
+    let writes = drive_with_clipboard(
+        &mut app,
+        Message::Check(check_screen::Message::PasteAndCheck),
+        &mut clipboard,
+    )
+    .await;
+    assert_eq!(app.screen, crate::screen::Screen::Check);
+    assert_eq!(app.check.password.expose(), "hunter2");
+    assert_eq!(
+        found(&app.check.state),
+        Some(BreachResult::Found { count: 17206891 })
+    );
+    assert!(writes.is_empty(), "clearing is off by default");
+    assert_eq!(clipboard.as_deref(), Some("hunter2"));
+}
+
+#[tokio::test]
+async fn an_empty_or_non_text_clipboard_gets_a_gentle_note() {
+    let server = serving(ResponseTemplate::new(200).set_body_string(RANGE)).await;
+    let mut app = app(settings(&server.uri()));
+    check(&mut app, "hunter2").await;
+
+    // Non-text content reads as nothing, the same as an empty clipboard.
+    for contents in [None, Some(String::new()), Some(" \n\t".to_owned())] {
+        let mut clipboard = contents.clone();
+        drive_with_clipboard(
+            &mut app,
+            Message::Check(check_screen::Message::PasteAndCheck),
+            &mut clipboard,
+        )
+        .await;
+        assert!(app.check.clipboard_empty, "{contents:?}");
+        // What was there is left alone.
+        assert_eq!(app.check.password.expose(), "hunter2");
+        assert!(found(&app.check.state).is_some());
+        assert_eq!(clipboard, contents);
+    }
+
+    // Typing again takes the note away.
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::Input("hunter3".to_owned().into())),
+    )
+    .await;
+    assert!(!app.check.clipboard_empty);
+}
+
+#[tokio::test]
+async fn the_clipboard_is_emptied_once_the_pasted_check_is_over() {
+    let server = serving(
+        ResponseTemplate::new(200)
+            .set_body_string(RANGE)
+            .set_delay(Duration::from_millis(200)),
+    )
+    .await;
+    let mut app = app(AppSettings {
+        clear_clipboard_after_paste: true,
+        ..settings(&server.uri())
+    });
+
+    // Not while the check is still going: the paste alone clears nothing.
+    let paste = app.update(Message::Check(check_screen::Message::Pasted(Some(
+        "hunter2".to_owned().into(),
+    ))));
+    assert!(matches!(app.check.state, SearchResult::Searching));
+    assert!(app.check.clear_clipboard);
+    let mut clipboard = Some("hunter2".to_owned());
+    let mut writes = Vec::new();
+    for message in outputs(paste).await {
+        writes.extend(drive_with_clipboard(&mut app, message, &mut clipboard).await);
+    }
+    assert_eq!(writes, [String::new()], "emptied once, after the answer");
+    assert_eq!(clipboard.as_deref(), Some(""));
+    assert!(found(&app.check.state).is_some());
+    assert!(!app.check.clear_clipboard);
+
+    // A check that's cancelled is over too. (hunter2 is cached by now.)
+    let mut clipboard = Some("hunter3".to_owned());
+    let _ = app.update(Message::Check(check_screen::Message::Pasted(
+        clipboard.clone().map(Into::into),
+    )));
+    assert!(app.check.clear_clipboard);
+    let writes = drive_with_clipboard(
+        &mut app,
+        Message::Check(check_screen::Message::CancelSearch),
+        &mut clipboard,
+    )
+    .await;
+    assert_eq!(writes, [String::new()]);
+}
+
+#[tokio::test]
+async fn an_empty_paste_doesnt_empty_the_clipboard() {
+    let mut app = app(AppSettings {
+        clear_clipboard_after_paste: true,
+        ..AppSettings::default()
+    });
+    let mut clipboard = Some("  ".to_owned());
+    let writes = drive_with_clipboard(
+        &mut app,
+        Message::Check(check_screen::Message::PasteAndCheck),
+        &mut clipboard,
+    )
+    .await;
+    assert!(writes.is_empty());
+    assert!(!app.check.clear_clipboard);
+}
+
+#[tokio::test]
+async fn a_breached_password_gets_a_checked_replacement() {
+    let server = serving(ResponseTemplate::new(200).set_body_string(RANGE)).await;
+    let mut app = app(settings(&server.uri()));
+    check(&mut app, "hunter2").await;
+    let requests = server.received_requests().await.unwrap().len();
+
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::SuggestAlternative),
+    )
+    .await;
+    let Some(Ok(Some(suggestion))) = &app.check.suggestion else {
+        panic!("no suggestion: {:?}", app.check.suggestion);
+    };
+    let suggestion = suggestion.expose().to_owned();
+    assert!(!suggest::reuses("hunter2", &suggestion));
+    // The fixture doesn't list it, so the first candidate was kept.
+    assert_eq!(
+        server.received_requests().await.unwrap().len(),
+        requests + 1
+    );
+    assert!(app.check.suggesting.is_none());
+
+    let mut clipboard = None;
+    let writes = drive_with_clipboard(
+        &mut app,
+        Message::Check(check_screen::Message::CopySuggestion),
+        &mut clipboard,
+    )
+    .await;
+    assert_eq!(writes, [suggestion.as_str()]);
+
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::UseSuggestion),
+    )
+    .await;
+    assert_eq!(app.check.password.expose(), suggestion);
+    assert_eq!(found(&app.check.state), Some(BreachResult::NotFound));
+    assert!(app.check.suggestion.is_none());
+}
+
+#[tokio::test]
+async fn a_failed_lookup_is_reported_instead_of_a_suggestion() {
+    let server = serving(ResponseTemplate::new(200).set_body_string(RANGE)).await;
+    let mut app = app(settings(&server.uri()));
+    check(&mut app, "hunter2").await;
+    server.reset().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&server)
+        .await;
+
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::SuggestAlternative),
+    )
+    .await;
+    assert!(
+        matches!(app.check.suggestion, Some(Err(_))),
+        "{:?}",
+        app.check.suggestion
+    );
+    // Nothing to copy or use.
+    let mut clipboard = None;
+    assert!(
+        drive_with_clipboard(
+            &mut app,
+            Message::Check(check_screen::Message::CopySuggestion),
+            &mut clipboard
+        )
+        .await
+        .is_empty()
+    );
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::UseSuggestion),
+    )
+    .await;
+    assert_eq!(app.check.password.expose(), "hunter2");
+}
+
+#[tokio::test]
+async fn typing_drops_a_suggestion_for_the_old_password() {
+    let server = serving(ResponseTemplate::new(200).set_body_string(RANGE)).await;
+    let mut app = app(settings(&server.uri()));
+    check(&mut app, "hunter2").await;
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::SuggestAlternative),
+    )
+    .await;
+    assert!(app.check.suggestion.is_some());
+
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::Input("hunter3".to_owned().into())),
+    )
+    .await;
+    assert!(app.check.suggestion.is_none());
+}
+
+/// A checked hunter2 in an app that clears it after `after` of inactivity.
+async fn idle_app(server: &MockServer, after: Duration) -> App {
+    let mut app = app(AppSettings {
+        idle_clear: true,
+        idle_clear_after: after,
+        ..settings(&server.uri())
+    });
+    check(&mut app, "hunter2").await;
+    app
+}
+
+#[tokio::test]
+async fn inactivity_clears_the_password_with_a_notice() {
+    let server = serving(ResponseTemplate::new(200).set_body_string(RANGE)).await;
+    let mut app = idle_app(&server, Duration::from_millis(10)).await;
+
+    drive(&mut app, Message::App(AppMessage::Activity(None))).await;
+    assert!(app.check.password.expose().is_empty());
+    assert!(found(&app.check.state).is_none());
+    assert!(app.check.idle_cleared, "no notice");
+
+    // The notice goes once there's something new.
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::Input("h".to_owned().into())),
+    )
+    .await;
+    assert!(!app.check.idle_cleared);
+}
+
+#[tokio::test]
+async fn activity_since_a_timer_started_makes_it_stale() {
+    let server = serving(ResponseTemplate::new(200).set_body_string(RANGE)).await;
+    let mut app = idle_app(&server, Duration::from_millis(10)).await;
+
+    let first = app.update(Message::App(AppMessage::Activity(None)));
+    let second = app.update(Message::App(AppMessage::Activity(None)));
+    // The first timer was aborted, but one that had already fired is
+    // dropped too.
+    let stale = Message::App(AppMessage::IdleTimeout(app.idle_generation - 1));
+    drive(&mut app, stale).await;
+    for timeout in outputs(first).await {
+        drive(&mut app, timeout).await;
+    }
+    assert_eq!(
+        app.check.password.expose(),
+        "hunter2",
+        "an older timer cleared it"
+    );
+    assert!(!app.check.idle_cleared);
+
+    for timeout in outputs(second).await {
+        drive(&mut app, timeout).await;
+    }
+    assert!(app.check.password.expose().is_empty());
+    assert!(app.check.idle_cleared);
+}
+
+#[tokio::test]
+async fn inactivity_clears_nothing_when_turned_off_or_already_clear() {
+    let server = serving(ResponseTemplate::new(200).set_body_string(RANGE)).await;
+    let mut app = idle_app(&server, Duration::from_millis(10)).await;
+    let _ = app.update(Message::App(AppMessage::Activity(None)));
+    assert!(app.idle_timer.is_some());
+    drive(
+        &mut app,
+        Message::Settings(settings_screen::Message::IdleClearEnabled(false)),
+    )
+    .await;
+    assert!(app.idle_timer.is_none(), "the running timer wasn't aborted");
+    drive(&mut app, Message::App(AppMessage::Activity(None))).await;
+    assert!(app.idle_timer.is_none(), "a timer started while turned off");
+    assert_eq!(app.check.password.expose(), "hunter2");
+    assert!(!app.check.idle_cleared);
+
+    let mut app = self::app(AppSettings {
+        idle_clear_after: Duration::from_millis(10),
+        ..settings(&server.uri())
+    });
+    drive(&mut app, Message::App(AppMessage::Activity(None))).await;
+    assert!(!app.check.idle_cleared, "announced clearing an empty form");
+}
+
+#[tokio::test]
+async fn the_input_row_submits_and_clears_only_once_theres_a_password() {
+    let mut app = app(AppSettings::default());
+    let offered = clicks(
+        app.check
+            .password_input_row(&app.shared)
+            .map(Message::Check),
+    );
+    assert!(
+        matches!(
+            offered[..],
+            [
+                Message::Check(check_screen::Message::PasteAndCheck),
+                Message::Check(check_screen::Message::WatchClipboard(true))
+            ]
+        ),
+        "{offered:?}"
+    );
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::Input("hunter2".to_owned().into())),
+    )
+    .await;
+    let offered = clicks(
+        app.check
+            .password_input_row(&app.shared)
+            .map(Message::Check),
+    );
+    assert!(
+        matches!(
+            offered[..],
+            [
+                Message::Check(check_screen::Message::Submit),
+                Message::Check(check_screen::Message::Clear),
+                Message::Check(check_screen::Message::PasteAndCheck),
+                Message::Check(check_screen::Message::WatchClipboard(true))
+            ]
+        ),
+        "{offered:?}"
+    );
+}
+
+#[tokio::test]
+async fn the_hash_line_copies_only_once_theres_a_hash() {
+    let mut app = app(AppSettings::default());
+    let offered = clicks(app.check.hash_line(&app.shared).map(Message::Check));
+    assert!(
+        matches!(
+            offered[..],
+            [Message::Check(check_screen::Message::RevealHash(true))]
+        ),
+        "{offered:?}"
+    );
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::Input("hunter2".to_owned().into())),
+    )
+    .await;
+    let offered = clicks(app.check.hash_line(&app.shared).map(Message::Check));
+    assert!(
+        matches!(
+            offered[..],
+            [
+                Message::Check(check_screen::Message::RevealHash(true)),
+                Message::Check(check_screen::Message::CopyHash)
+            ]
+        ),
+        "{offered:?}"
+    );
+}
+
+#[test]
+fn the_result_banner_spans_the_column_and_wraps_its_detail() {
+    use crate::severity::Severity;
+    use crate::severity_view::result_banner;
+
+    let short = || result_banner(Some(Severity::Critical), "Found".to_owned(), "x".to_owned());
+    for size in BREAKPOINTS {
+        assert_eq!(laid_out(short(), size)[0].width, size.width);
+    }
+    let long = result_banner::<Message>(None, "Not found".to_owned(), "x ".repeat(200));
+    let banner = laid_out(long, BREAKPOINTS[0])[0];
+    assert_eq!(banner.width, BREAKPOINTS[0].width);
+    assert!(
+        banner.height > laid_out(short(), BREAKPOINTS[0])[0].height * 2.,
+        "{banner:?}"
+    );
+}
+
+#[test]
+fn the_settings_form_fits_the_narrowest_window() {
+    let app = app(AppSettings::default());
+    let size = BREAKPOINTS[0];
+    let nodes = laid_out(
+        app.settings_screen.form(&app.shared).map(Message::Settings),
+        size,
+    );
+    assert!(nodes.len() > 10, "{nodes:?}");
+    for bounds in &nodes {
+        assert!(bounds.x + bounds.width <= size.width + 0.5, "{bounds:?}");
+    }
+    let offered = clicks(app.settings_screen.form(&app.shared).map(Message::Settings));
+    assert!(
+        offered.iter().any(|message| matches!(
+            message,
+            Message::Settings(settings_screen::Message::CheckUpdatesAtStartup(true))
+        )),
+        "{offered:?}"
+    );
+}
+
+/// Where focus lands in `element` after each of `presses` Tabs (or
+/// Shift+Tabs when `backwards`), as an index into its focusable widgets in
+/// tree order, which is the order they're laid out in. The first entry is
+/// how many there are.
+fn tab_through(
+    element: iced::Element<'_, Message>,
+    presses: usize,
+    backwards: bool,
+) -> (usize, Vec<Option<usize>>) {
+    use iced_runtime::core::widget::operation::{Focusable, Outcome, focusable};
+    use iced_runtime::core::widget::{Id, Operation};
+    use iced_runtime::user_interface::{Cache, UserInterface};
+
+    /// Each focusable widget's focus, in tree order.
+    struct Focus(Vec<bool>);
+
+    impl Operation for Focus {
+        fn container(
+            &mut self,
+            _id: Option<&Id>,
+            _bounds: iced::Rectangle,
+            operate_on_children: &mut dyn FnMut(&mut dyn Operation),
+        ) {
+            operate_on_children(self);
+        }
+
+        fn focusable(&mut self, state: &mut dyn Focusable, _id: Option<&Id>) {
+            self.0.push(state.is_focused());
+        }
+    }
+
+    let mut renderer = renderer();
+    let mut ui = UserInterface::build(element, BREAKPOINTS[1], Cache::default(), &mut renderer);
+    let focus = |ui: &mut UserInterface<'_, _, _, _>| {
+        let mut focus = Focus(Vec::new());
+        ui.operate(&renderer, &mut focus);
+        focus.0
+    };
+    let count = focus(&mut ui).len();
+    let mut landed = Vec::new();
+    for _ in 0..presses {
+        let mut operation: Box<dyn Operation> = if backwards {
+            Box::new(focusable::focus_previous())
+        } else {
+            Box::new(focusable::focus_next())
+        };
+        loop {
+            ui.operate(&renderer, operation.as_mut());
+            match operation.finish() {
+                Outcome::Chain(next) => operation = next,
+                _ => break,
+            }
+        }
+        landed.push(focus(&mut ui).iter().position(|&focused| focused));
+    }
+    (count, landed)
+}
+
+#[test]
+fn tab_walks_the_check_forms_fields_in_order_and_wraps() {
+    let app = app(AppSettings::default());
+    let (count, forwards) = tab_through(app.view(window::Id::unique()), 4, false);
+    assert!(count >= 2, "{count} focusable widgets");
+    // Past the last field nothing has focus, as in iced's own focus cycle,
+    // and the next Tab starts over.
+    let mut expected: Vec<_> = (0..count).map(Some).chain([None, Some(0)]).collect();
+    assert_eq!(forwards[..], expected[..4]);
+
+    // Shift+Tab goes the other way round.
+    let app = self::app(AppSettings::default());
+    let (_, backwards) = tab_through(app.view(window::Id::unique()), count + 1, true);
+    expected = (0..count).rev().map(Some).chain([None]).collect();
+    assert_eq!(backwards, expected);
+}
+
+#[test]
+fn every_tab_is_walked_in_layout_order() {
+    for screen in crate::screen::Screen::ALL {
+        let mut app = app(AppSettings::default());
+        app.screen = screen;
+        let (count, _) = tab_through(app.view(window::Id::unique()), 0, false);
+        let (_, landed) = tab_through(app.view(window::Id::unique()), count + 1, false);
+        let expected: Vec<_> = (0..count).map(Some).chain([None]).collect();
+        assert_eq!(landed, expected, "{screen:?}");
+    }
+}
+
+#[tokio::test]
+async fn the_window_title_announces_how_a_check_went() {
+    let strings = &crate::strings::EN;
+    let server = serving(ResponseTemplate::new(200).set_body_string(RANGE)).await;
+    let mut app = app(settings(&server.uri()));
+    assert_eq!(app.title(window::Id::unique()), strings.window_title);
+
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::Input("hunter2".to_owned().into())),
+    )
+    .await;
+    let submit = app.update(Message::Check(check_screen::Message::Submit));
+    assert!(
+        app.title(window::Id::unique())
+            .starts_with(strings.title_checking),
+        "{}",
+        app.title(window::Id::unique())
+    );
+    for message in outputs(submit).await {
+        drive(&mut app, message).await;
+    }
+    assert_eq!(
+        app.title(window::Id::unique()),
+        format!("{} — {}", strings.title_breached, strings.window_title)
+    );
+
+    check(&mut app, "not hunter2").await;
+    assert!(
+        app.title(window::Id::unique())
+            .starts_with(strings.title_not_found)
+    );
+
+    server.reset().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&server)
+        .await;
+    check(&mut app, "hunter3").await;
+    assert!(
+        app.title(window::Id::unique())
+            .starts_with(strings.title_failed),
+        "{}",
+        app.title(window::Id::unique())
+    );
+}
+
+#[tokio::test]
+async fn the_caps_lock_warning_doesnt_get_in_the_way_of_typing() {
+    let server = serving(ResponseTemplate::new(200).set_body_string(RANGE)).await;
+    let mut app = app(settings(&server.uri()));
+    let caps_on = Some(crate::caps_lock::Hint::On);
+
+    for (hint, typed) in [(caps_on, "H"), (caps_on, "HU")] {
+        let _ = app.update(Message::App(AppMessage::Activity(hint)));
+        drive(
+            &mut app,
+            Message::Check(check_screen::Message::Input(typed.to_owned().into())),
+        )
+        .await;
+        assert!(app.check.caps_lock.is_on());
+        assert_eq!(app.check.password.expose(), typed);
+    }
+    drive(&mut app, Message::Check(check_screen::Message::Submit)).await;
+    assert_eq!(found(&app.check.state), Some(BreachResult::NotFound));
+
+    let _ = app.update(Message::App(AppMessage::Activity(Some(
+        crate::caps_lock::Hint::Toggled,
+    ))));
+    assert!(!app.check.caps_lock.is_on());
+    assert_eq!(app.check.password.expose(), "HU");
+}
+
+#[tokio::test]
+async fn removing_invisible_characters_rehashes_at_once() {
+    let server = serving(ResponseTemplate::new(200).set_body_string(RANGE)).await;
+    let mut app = app(settings(&server.uri()));
+    check(&mut app, "\u{200B}hunter2\u{FEFF}").await;
+    assert_eq!(found(&app.check.state), Some(BreachResult::NotFound));
+    assert_ne!(app.check.current_hash.expose(), hunter2());
+
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::RemoveInvisible),
+    )
+    .await;
+    assert_eq!(app.check.password.expose(), "hunter2");
+    assert_eq!(app.check.current_hash.expose(), hunter2());
+    // The old result was for the old password.
+    assert!(matches!(app.check.state, SearchResult::NotSubmitted));
+    let requests = server.received_requests().await.unwrap().len();
+
+    drive(&mut app, Message::Check(check_screen::Message::Submit)).await;
+    assert_eq!(
+        found(&app.check.state),
+        Some(BreachResult::Found { count: 17206891 })
+    );
+    assert_eq!(
+        server.received_requests().await.unwrap().len(),
+        requests + 1
+    );
+}
+
+#[tokio::test]
+async fn a_removal_drops_a_lookup_still_running() {
+    let server = serving(
+        ResponseTemplate::new(200)
+            .set_body_string(RANGE)
+            .set_delay(Duration::from_millis(200)),
+    )
+    .await;
+    let mut app = app(settings(&server.uri()));
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::Input(
+            "hun\u{200B}ter2".to_owned().into(),
+        )),
+    )
+    .await;
+    let submit = app.update(Message::Check(check_screen::Message::Submit));
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::RemoveInvisible),
+    )
+    .await;
+    for late in outputs(submit).await {
+        drive(&mut app, late).await;
+    }
+    assert!(matches!(app.check.state, SearchResult::NotSubmitted));
+    assert_eq!(app.check.current_hash.expose(), hunter2());
+}
+
+/// What's logged at any level, as the log file's formatter writes it.
+#[derive(Clone, Default)]
+pub(crate) struct Capture(Arc<std::sync::Mutex<Vec<u8>>>);
+
+impl std::io::Write for Capture {
+    fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(bytes);
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Capture {
+    /// Captures what's logged on this thread until the guard is dropped,
+    /// which on a current-thread runtime is every task of the test.
+    pub(crate) fn start() -> (Self, tracing::subscriber::DefaultGuard) {
+        let capture = Capture::default();
+        let writer = capture.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::TRACE)
+            .with_ansi(false)
+            .with_writer(move || writer.clone())
+            .finish();
+        (capture, tracing::subscriber::set_default(subscriber))
+    }
+
+    pub(crate) fn text(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+}
+
+/// What `run` logs.
+fn logged(run: impl FnOnce()) -> String {
+    let (capture, _guard) = Capture::start();
+    run();
+    capture.text()
+}
+
+#[tokio::test]
+async fn nothing_a_check_logs_gives_away_the_password_or_its_hash() {
+    let hash = hunter2();
+    for response in [
+        ResponseTemplate::new(200).set_body_string(RANGE),
+        ResponseTemplate::new(200).set_body_string("<html>maintenance</html>"),
+        ResponseTemplate::new(404),
+    ] {
+        let server = serving(response).await;
+        let mut app = app(settings(&server.uri()));
+        let (capture, guard) = Capture::start();
+        check(&mut app, "hunter2").await;
+        drop(guard);
+        let logs = capture.text();
+        assert!(logs.contains("F3BBB"), "{logs}");
+        for secret in ["hunter2", &hash[5..], &hash[5..].to_ascii_lowercase()] {
+            assert!(!logs.contains(secret), "logged {secret}: {logs}");
+        }
+    }
+}
+
+#[test]
+fn a_notification_that_cant_be_shown_is_logged_and_shrugged_off() {
+    let mut app = app(AppSettings::default());
+    let screen = app.screen;
+    let logs = logged(|| {
+        let _ = app.update(Message::App(AppMessage::Notified(Err(
+            "no notification server".to_owned(),
+        ))));
+    });
+    assert!(logs.contains("WARN"), "{logs}");
+    assert!(logs.contains("no notification server"), "{logs}");
+    assert_eq!(app.screen, screen);
+
+    // Dismissing one is nothing to log.
+    let logs = logged(|| {
+        let _ = app.update(Message::App(AppMessage::Notified(Ok(false))));
+    });
+    assert!(logs.is_empty(), "{logs}");
+}
+
+#[tokio::test]
+async fn no_late_answer_brings_a_cancelled_search_back() {
+    let server = serving(
+        ResponseTemplate::new(200)
+            .set_body_string(RANGE)
+            .set_delay(Duration::from_millis(200)),
+    )
+    .await;
+    let mut app = app(settings(&server.uri()));
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::Input("hunter2".to_owned().into())),
+    )
+    .await;
+    let submit = app.update(Message::Check(check_screen::Message::Submit));
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::CancelSearch),
+    )
+    .await;
+    assert!(matches!(app.check.state, SearchResult::Cancelled));
+
+    // The lookup itself was aborted, so it answers nothing.
+    assert!(outputs(submit).await.is_empty());
+    // And an answer that was already queued is dropped.
+    let record = CheckRecord::new(
+        &hunter2(),
+        HashMode::Sha1,
+        BreachResult::Found { count: 17206891 },
+        LookupSource::Network,
+        &app.shared.settings.severity,
+    );
+    let generation = app.check.search_generation;
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::BreachResult(
+            generation,
+            Ok((record, Duration::ZERO)),
+        )),
+    )
+    .await;
+    assert!(matches!(app.check.state, SearchResult::Cancelled));
+
+    // Checking again afterwards works as ever.
+    drive(&mut app, Message::Check(check_screen::Message::Submit)).await;
+    assert_eq!(
+        found(&app.check.state),
+        Some(BreachResult::Found { count: 17206891 })
+    );
+}
+
+#[tokio::test]
+async fn a_search_cancelled_while_rate_limited_isnt_retried() {
+    let server = serving(ResponseTemplate::new(429).insert_header("Retry-After", "0")).await;
+    let mut app = app(settings(&server.uri()));
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::Input("hunter2".to_owned().into())),
+    )
+    .await;
+    let submit = app.update(Message::Check(check_screen::Message::Submit));
+    // Run the lookup up to the rate limit, leaving the retry pending.
+    let mut retry = Task::none();
+    for message in outputs(submit).await {
+        retry = app.update(message);
+    }
+    assert!(matches!(app.check.state, SearchResult::RateLimited(_)));
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::CancelSearch),
+    )
+    .await;
+
+    assert!(outputs(retry).await.is_empty());
+    drive(&mut app, Message::Check(check_screen::Message::RetryLookup)).await;
+    assert!(matches!(app.check.state, SearchResult::Cancelled));
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn nothing_a_cancelled_batch_had_sent_lands() {
+    let mut app = app(AppSettings::default());
+    app.batch.results = vec![None; 2];
+    // The run is left unstarted, so only what's fed in below arrives.
+    let _run = app.batch.run(Vec::new(), &mut app.shared);
+    let stats = BatchStats {
+        total: 2,
+        done: 1,
+        ..Default::default()
+    };
+    let _ = app.update(Message::Batch(batch_screen::Message::Progress(stats)));
+    // Saving the checkpoint is left undone, so nothing is written to disk.
+    let _save = app.update(Message::Batch(batch_screen::Message::Cancel));
+    let finished = app.batch.finished;
+
+    let item = BatchItem {
+        index: 1,
+        prefix: "F3BBB".to_owned(),
+        checked_at: chrono::Utc::now(),
+        outcome: Ok(Some(17206891)),
+    };
+    let done = BatchStats { done: 2, ..stats };
+    for late in [
+        Message::Batch(batch_screen::Message::Item(item)),
+        Message::Batch(batch_screen::Message::Progress(done)),
+        Message::Batch(batch_screen::Message::Finished),
+    ] {
+        let _ = app.update(late);
+    }
+    assert!(app.batch.results.iter().all(Option::is_none));
+    assert_eq!(app.batch.stats, Some(stats));
+    assert_eq!(app.batch.finished, finished);
+    assert!(app.batch.cancelled);
+}
+
+#[test]
+fn exports_keep_input_order_however_the_table_is_sorted() {
+    let mut app = app(AppSettings::default());
+    app.batch.lines = (0..3)
+        .map(|index| batch::Line {
+            number: index + 1,
+            label: String::new(),
+            username: String::new(),
+            preview: "•••".to_owned(),
+            password: index,
+        })
+        .collect();
+    app.batch.passwords = vec![String::new().into(); 3];
+    app.batch.results = [1, 300, 20]
+        .into_iter()
+        .enumerate()
+        .map(|(index, count)| {
+            Some(batch::BatchItem {
+                index,
+                prefix: "F3BBB".to_owned(),
+                checked_at: chrono::Utc::now(),
+                outcome: Ok(Some(count)),
+            })
+        })
+        .collect();
+
+    let _ = app.update(Message::Batch(batch_screen::Message::Sort(
+        batch::SortColumn::Count,
+    )));
+    let shown = batch::sorted_lines(
+        &app.batch.lines,
+        &app.batch.results,
+        app.batch.sort,
+        &app.shared.settings.severity,
+    );
+    assert_eq!(shown, [1, 2, 0]);
+    let exported: Vec<_> = app
+        .batch
+        .export_rows(&app.shared)
+        .iter()
+        .map(|row| row.line)
+        .collect();
+    assert_eq!(exported, [1, 2, 3]);
+}
+
+#[test]
+fn jumping_to_the_top_or_bottom_only_scrolls_the_batch_table() {
+    use crate::shortcuts::Shortcut;
+
+    for shortcut in [Shortcut::ScrollToTop, Shortcut::ScrollToBottom] {
+        let mut app = app(AppSettings::default());
+        let task = app.update(Message::App(AppMessage::Shortcut(shortcut)));
+        assert!(
+            iced_runtime::task::into_stream(task).is_none(),
+            "{shortcut:?}"
+        );
+
+        app.screen = crate::screen::Screen::Batch;
+        let task = app.update(Message::App(AppMessage::Shortcut(shortcut)));
+        assert!(
+            iced_runtime::task::into_stream(task).is_some(),
+            "{shortcut:?}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn a_password_can_be_clicked_in_and_checked_on_the_keypad() {
+    use crate::keypad::{Message as Key, Page};
+
+    let server = serving(ResponseTemplate::new(200).set_body_string(RANGE)).await;
+    let mut app = app(settings(&server.uri()));
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::ToggleKeypad),
+    )
+    .await;
+    assert!(app.check.keypad.is_some());
+
+    for key in "hunter22".chars() {
+        drive(
+            &mut app,
+            Message::Check(check_screen::Message::Keypad(Key::Key(key))),
+        )
+        .await;
+    }
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::Keypad(Key::Backspace)),
+    )
+    .await;
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::Keypad(Key::Page(Page::Upper))),
+    )
+    .await;
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::Keypad(Key::Shuffle(true))),
+    )
+    .await;
+    assert_eq!(app.check.password.expose(), "hunter2");
+    assert_eq!(app.check.current_hash.expose(), hunter2());
+
+    drive(&mut app, Message::Check(check_screen::Message::Submit)).await;
+    assert_eq!(
+        found(&app.check.state),
+        Some(BreachResult::Found { count: 17206891 })
+    );
+
+    // Dismissed from its own button, or the one that opened it.
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::Keypad(Key::Close)),
+    )
+    .await;
+    assert!(app.check.keypad.is_none());
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::ToggleKeypad),
+    )
+    .await;
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::ToggleKeypad),
+    )
+    .await;
+    assert!(app.check.keypad.is_none());
+    // Backspace on nothing is nothing.
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::Input(String::new().into())),
+    )
+    .await;
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::Keypad(Key::Backspace)),
+    )
+    .await;
+    assert!(app.check.password.expose().is_empty());
+}
+
+#[test]
+fn only_a_manual_update_check_reports_failing() {
+    let mut app = app(AppSettings::default());
+    let _ = app.update(Message::Settings(settings_screen::Message::UpdateChecked(
+        false,
+        Err("offline".to_owned()),
+    )));
+    assert_eq!(app.settings_screen.update_status, None);
+    assert_eq!(app.settings_screen.update, None);
+
+    let _ = app.update(Message::Settings(settings_screen::Message::CheckForUpdates));
+    assert!(app.settings_screen.checking_update);
+    let _ = app.update(Message::Settings(settings_screen::Message::UpdateChecked(
+        true,
+        Err("offline".to_owned()),
+    )));
+    assert!(!app.settings_screen.checking_update);
+    assert_eq!(
+        app.settings_screen.update_status,
+        Some(Err("offline".to_owned()))
+    );
+
+    let found = crate::update::Update {
+        version: "9.0.0".parse().unwrap(),
+        url: "https://example.com".to_owned(),
+    };
+    let _ = app.update(Message::Settings(settings_screen::Message::UpdateChecked(
+        false,
+        Ok(Some(found.clone())),
+    )));
+    assert_eq!(app.settings_screen.update, Some(found));
+}
+
+#[tokio::test]
+async fn pin_mode_takes_digits_only_and_checks_a_whole_pin() {
+    let server = serving(ResponseTemplate::new(200).set_body_string(RANGE)).await;
+    let mut app = app(settings(&server.uri()));
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::Input("hunter2".to_owned().into())),
+    )
+    .await;
+
+    // What was typed isn't a PIN, so switching drops it.
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::InputMode(crate::InputMode::Pin)),
+    )
+    .await;
+    assert!(app.check.password.expose().is_empty());
+    assert!(!app.check.hash_input);
+    assert_eq!(app.shared.mode, HashMode::Sha1);
+
+    for typed in ["1", "12", "12a", "123", "1234"] {
+        drive(
+            &mut app,
+            Message::Check(check_screen::Message::Input(typed.to_owned().into())),
+        )
+        .await;
+    }
+    assert_eq!(app.check.password.expose(), "1234");
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::Input("123456789".to_owned().into())),
+    )
+    .await;
+    assert_eq!(app.check.password.expose(), "1234", "longer than any PIN");
+    assert!(matches!(
+        app.submit_message(),
+        Some(Message::Check(check_screen::Message::Submit))
+    ));
+
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::Input("123".to_owned().into())),
+    )
+    .await;
+    assert!(app.submit_message().is_none(), "too short to check");
+
+    // Checking against the API can be turned off, leaving the table only.
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::Input("1234".to_owned().into())),
+    )
+    .await;
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::PinBreachCheck(false)),
+    )
+    .await;
+    assert!(app.submit_message().is_none());
+
+    // A PIN that fits carries over to password mode.
+    drive(
+        &mut app,
+        Message::Check(check_screen::Message::InputMode(crate::InputMode::Password)),
+    )
+    .await;
+    assert_eq!(app.check.password.expose(), "1234");
+}
+
+#[tokio::test(start_paused = true)]
+async fn account_lookups_go_at_the_subscriptions_rate_once_its_known() {
+    let mut app = app(AppSettings {
+        api_key: "0123456789abcdef0123456789abcdef".to_owned().into(),
+        ..AppSettings::default()
+    });
+    let lookups = |app: &App| {
+        let lookups: Vec<_> = (0..3)
+            .map(|_| app.shared.api_lookup(async { Ok::<_, hibp::ApiError>(()) }))
+            .collect();
+        async move {
+            let start = tokio::time::Instant::now();
+            for lookup in lookups {
+                lookup.await.unwrap();
+            }
+            start.elapsed()
+        }
+    };
+    // Unknown, so only the API's 429s hold them back.
+    assert_eq!(lookups(&app).await, Duration::ZERO);
+
+    let status: hibp::SubscriptionStatus = serde_json::from_str(SUBSCRIPTION).unwrap();
+    let api_key = app.shared.settings.api_key.clone();
+    drive(
+        &mut app,
+        Message::Settings(settings_screen::Message::SubscriptionChecked(
+            api_key.clone(),
+            Ok(status),
+        )),
+    )
+    .await;
+    // Ten a minute: the first at once, then one every six seconds.
+    assert_eq!(lookups(&app).await, Duration::from_secs(12));
+
+    // A status for a key that's since been replaced doesn't set the rate.
+    let mut replaced = self::app(AppSettings {
+        api_key: "fedcba9876543210fedcba9876543210".to_owned().into(),
+        ..AppSettings::default()
+    });
+    let status = serde_json::from_str(SUBSCRIPTION).unwrap();
+    drive(
+        &mut replaced,
+        Message::Settings(settings_screen::Message::SubscriptionChecked(
+            api_key,
+            Ok(status),
+        )),
+    )
+    .await;
+    assert_eq!(lookups(&replaced).await, Duration::ZERO);
+}
@@ -0,0 +1,124 @@
+use iced::Subscription;
+
+use crate::Message;
+use crate::strings::Strings;
+
+/// Whether this build has a tray icon, which needs the `tray` feature.
+pub const SUPPORTED: bool = cfg!(feature = "tray");
+
+pub use imp::{Tray, subscription};
+
+#[cfg(feature = "tray")]
+mod imp {
+    use std::fmt;
+
+    use iced::futures::{SinkExt, Stream};
+    use tray_icon::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem};
+    use tray_icon::{Icon, TrayIcon, TrayIconBuilder};
+
+    use super::*;
+    use crate::AppMessage;
+
+    /// Menu item IDs, fixed so the event stream can map them without the tray.
+    const QUICK_CHECK: &str = "quick-check";
+    const OPEN: &str = "open";
+    const QUIT: &str = "quit";
+
+    fn message_for(id: &str) -> Option<Message> {
+        match id {
+            QUICK_CHECK => Some(AppMessage::OpenQuickCheck),
+            OPEN => Some(AppMessage::ShowMainWindow),
+            QUIT => Some(AppMessage::Quit),
+            _ => None,
+        }
+        .map(Message::App)
+    }
+
+    /// The tray icon and its menu, removed when dropped.
+    ///
+    /// On Linux it's served over D-Bus (the KSNI protocol) from a worker
+    /// thread, so it needs no GTK. On macOS it must be made once the event
+    /// loop runs, which is why the app asks for it in a startup message.
+    pub struct Tray {
+        _icon: TrayIcon,
+    }
+
+    impl fmt::Debug for Tray {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("Tray").finish_non_exhaustive()
+        }
+    }
+
+    impl Tray {
+        pub fn new(strings: &Strings) -> Result<Self, String> {
+            let menu = Menu::with_items(&[
+                &MenuItem::with_id(QUICK_CHECK, strings.tray_quick_check, true, None),
+                &MenuItem::with_id(OPEN, strings.tray_open, true, None),
+                &PredefinedMenuItem::separator(),
+                &MenuItem::with_id(QUIT, strings.tray_quit, true, None),
+            ])
+            .map_err(|error| error.to_string())?;
+            let icon = TrayIconBuilder::new()
+                .with_menu(Box::new(menu))
+                .with_tooltip(strings.window_title)
+                .with_icon(icon()?)
+                .build()
+                .map_err(|error| error.to_string())?;
+            Ok(Self { _icon: icon })
+        }
+    }
+
+    /// A filled circle, since the app has no icon of its own yet.
+    fn icon() -> Result<Icon, String> {
+        const SIZE: u32 = 32;
+        let center = (SIZE as f32 - 1.) / 2.;
+        let rgba = (0..SIZE * SIZE)
+            .flat_map(|i| {
+                let (x, y) = ((i % SIZE) as f32, (i / SIZE) as f32);
+                let inside = (x - center).hypot(y - center) <= center;
+                [0xD9, 0x48, 0x2B, if inside { 0xFF } else { 0 }]
+            })
+            .collect();
+        Icon::from_rgba(rgba, SIZE, SIZE).map_err(|error| error.to_string())
+    }
+
+    /// Picks from the tray menu, as messages.
+    pub fn subscription() -> Subscription<Message> {
+        Subscription::run(menu_events)
+    }
+
+    fn menu_events() -> impl Stream<Item = Message> {
+        iced::stream::channel(4, |mut output| async move {
+            let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+            // Called on whichever thread the platform delivers menu events on.
+            MenuEvent::set_event_handler(Some(move |event: MenuEvent| {
+                let _ = sender.send(event.id);
+            }));
+            while let Some(id) = receiver.recv().await {
+                if let Some(message) = message_for(id.as_ref()) {
+                    let _ = output.send(message).await;
+                }
+            }
+        })
+    }
+}
+
+#[cfg(not(feature = "tray"))]
+mod imp {
+    use super::*;
+
+    /// Stands in for the tray when the `tray` feature is off. The app checks
+    /// [`SUPPORTED`](super::SUPPORTED) before making one.
+    #[derive(Debug)]
+    pub struct Tray;
+
+    impl Tray {
+        pub fn new(_strings: &Strings) -> Result<Self, String> {
+            Err("built without tray support".to_owned())
+        }
+    }
+
+    pub fn subscription() -> Subscription<Message> {
+        Subscription::none()
+    }
+}
@@ -0,0 +1,274 @@
+use std::path::PathBuf;
+use std::time::Instant;
+
+use chrono::Local;
+use iced::alignment::Vertical;
+use iced::widget::{Text, button, column, container, row, stack, text};
+use iced::window;
+use iced::{Element, Length, Theme};
+
+use cybersec_wow::{LookupSource, report};
+
+use crate::screen::Screen;
+use crate::{
+    App, AppMessage, CONTENT_MAX_WIDTH, Message, SIZES, check_screen, settings_screen, shortcuts,
+    strings,
+};
+use strings::Strings;
+
+impl App {
+    pub fn view(&self, window: window::Id) -> Element<'_, Message> {
+        let (shared, strings) = (&self.shared, self.shared.strings());
+        if Some(window) == self.quick_window {
+            return column![
+                self.check.quick_view(shared).map(Message::Check),
+                button(strings.tray_open)
+                    .style(button::text)
+                    .on_press(Message::App(AppMessage::ShowMainWindow)),
+            ]
+            .padding(10)
+            .spacing(8)
+            .into();
+        }
+        let tabs = self.tab_bar();
+        let notice = self
+            .config_notice
+            .as_ref()
+            .map(|notice| text(notice.to_string()).style(text::danger));
+        let idle_cleared = self
+            .check
+            .idle_cleared
+            .then(|| text(strings.idle_cleared).style(text::secondary));
+        let update = self.settings_screen.update.as_ref().map(|update| {
+            row![
+                text((strings.update_available)(&update.version.to_string())),
+                button(strings.update_open)
+                    .style(button::secondary)
+                    .on_press(Message::Settings(settings_screen::Message::OpenUpdate)),
+                button(strings.dismiss)
+                    .style(button::text)
+                    .on_press(Message::Settings(settings_screen::Message::DismissUpdate)),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center)
+        });
+        let watching = self.check.clipboard_monitor.as_ref().map(|monitor| {
+            let left = monitor.remaining(Instant::now()).as_secs();
+            row![
+                text((strings.clipboard_watching)(left / 60, left % 60)).style(text::danger),
+                button(strings.stop_watching)
+                    .style(button::danger)
+                    .on_press(Message::Check(check_screen::Message::WatchClipboard(false))),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center)
+        });
+        let watch_ended = self
+            .check
+            .clipboard_watch_ended
+            .then(|| text(strings.clipboard_watch_ended).style(text::secondary));
+        let body = match self.screen {
+            Screen::Check => self.check.view(shared).map(Message::Check),
+            Screen::Batch => self.batch.view(shared).map(Message::Batch),
+            Screen::Compare => self
+                .compare
+                .view(strings, shared.separator)
+                .map(Message::Compare),
+            Screen::Generator => self.generator.view(strings).map(Message::Generator),
+            Screen::Breaches => self.catalog.view(strings).map(Message::Catalog),
+            Screen::Settings => self.settings_screen.view(shared).map(Message::Settings),
+        };
+        let page = container(
+            column![]
+                .push_maybe(notice)
+                .push_maybe(idle_cleared)
+                .push_maybe(update)
+                .push_maybe(watching)
+                .push_maybe(watch_ended)
+                .push(body)
+                .padding(10)
+                .spacing(5)
+                .max_width(CONTENT_MAX_WIDTH),
+        )
+        .center_x(Length::Fill)
+        .height(Length::Fill);
+        let page = column![tabs, page, self.status_bar()];
+        let page: Element<'_, Message> = match self.check.toast_view(shared) {
+            Some(toast) => stack![page, toast.map(Message::Check)].into(),
+            None => page.into(),
+        };
+        if self.show_shortcuts {
+            return stack![page, self.shortcuts_overlay()].into();
+        }
+        if self.batch.hovered_files == 0 {
+            return page;
+        }
+        let hint = if self.batch.hovered_files > 1 {
+            strings.drop_one_file
+        } else {
+            strings.drop_password_list
+        };
+        let overlay = container(text(hint).size(SIZES.heading))
+            .center(Length::Fill)
+            .style(overlay_style);
+        stack![page, overlay].into()
+    }
+
+    /// The tabs, and the shortcuts button at the far end. Tabs that don't
+    /// fit on one line carry on onto the next rather than squeezing their
+    /// labels.
+    pub(crate) fn tab_bar(&self) -> Element<'_, Message> {
+        let strings = self.shared.strings();
+        let tabs = row(Screen::ALL.into_iter().map(|screen| {
+            button(text((strings.tab)(screen)))
+                .style(if screen == self.screen {
+                    button::primary
+                } else {
+                    button::text
+                })
+                .on_press(Message::App(AppMessage::Screen(screen)))
+                .into()
+        }))
+        .spacing(5)
+        .wrap();
+        row![
+            container(tabs).width(Length::Fill),
+            button(strings.shortcuts_button).on_press(Message::App(AppMessage::ToggleShortcuts)),
+        ]
+        .spacing(5)
+        .padding([5, 10])
+        .align_y(Vertical::Center)
+        .into()
+    }
+
+    fn shortcuts_overlay(&self) -> Element<'_, Message> {
+        let strings = self.shared.strings();
+        let bindings = column(shortcuts::BINDINGS.iter().map(|&(keys, shortcut)| {
+            row![
+                text(keys).font(iced::Font::MONOSPACE).width(130),
+                text((strings.shortcut)(shortcut))
+            ]
+            .into()
+        }))
+        .spacing(4);
+        container(
+            column![
+                text(strings.shortcuts_title).size(SIZES.heading),
+                bindings,
+                button(strings.close).on_press(Message::App(AppMessage::ToggleShortcuts)),
+            ]
+            .spacing(10),
+        )
+        .center(Length::Fill)
+        .style(overlay_style)
+        .into()
+    }
+
+    /// The line along the bottom of the window: how the last lookup went, or
+    /// the API's health before there's been one, then where lookups go. It
+    /// keeps one line's height and clips rather than wrapping when narrow.
+    fn status_bar(&self) -> Element<'_, Message> {
+        let strings = self.shared.strings();
+        let last = match self.check.last_lookup {
+            Some((checked_at, meta)) => {
+                let source = match meta.source {
+                    LookupSource::Network => strings.source_network,
+                    LookupSource::Memory | LookupSource::Disk => strings.source_cache,
+                    LookupSource::Offline(_) => strings.source_offline,
+                };
+                (strings.checked)(
+                    &checked_at.with_timezone(&Local).format("%X").to_string(),
+                    meta.duration.as_millis(),
+                    source,
+                )
+            }
+            None => match &self.check.health {
+                None => strings.checking_api.to_owned(),
+                Some(Ok(latency)) => (strings.api_reachable)(latency.as_millis()),
+                Some(Err(error)) => (strings.api_unreachable)(&error.message),
+            },
+        };
+        let host = self
+            .shared
+            .settings
+            .endpoint
+            .host_str()
+            .unwrap_or(strings.no_host);
+        let padding = if self.shared.settings.padding {
+            strings.padding_on
+        } else {
+            strings.padding_off
+        };
+        let line = |content: String| {
+            text(content)
+                .size(SIZES.status)
+                .wrapping(text::Wrapping::None)
+                .style(text::secondary)
+        };
+        container(
+            row![
+                line(last).width(Length::Fill),
+                line(format!("{host} · {padding}")),
+            ]
+            .spacing(10),
+        )
+        .width(Length::Fill)
+        .height(Length::Fixed(22.))
+        .padding([3, 10])
+        .clip(true)
+        .style(container::bordered_box)
+        .into()
+    }
+}
+
+/// The buttons that export the session report, with how the last export
+/// went, for the tabs it can be exported from.
+pub(crate) fn report_view<'a, M: Clone + 'a>(
+    strings: &'static Strings,
+    exported: Option<&Result<PathBuf, String>>,
+    on_export: fn(report::Format) -> M,
+) -> Element<'a, M> {
+    let status = exported.map(|exported| exported_status(strings, exported));
+    row![
+        text(strings.session_report),
+        button("JSON…").on_press(on_export(report::Format::Json)),
+        button("HTML…").on_press(on_export(report::Format::Html)),
+    ]
+    .push_maybe(status)
+    .spacing(10)
+    .align_y(Vertical::Center)
+    .into()
+}
+
+/// Where an export was written, or why it couldn't be.
+pub(crate) fn exported_status<'a>(
+    strings: &'static Strings,
+    exported: &Result<PathBuf, String>,
+) -> Text<'a> {
+    match exported {
+        Ok(path) => text((strings.exported_to)(&path.display().to_string())).style(text::success),
+        Err(error) => text((strings.export_failed)(error)).style(text::danger),
+    }
+}
+
+/// Whether `password` starts or ends with whitespace, non-breaking spaces
+/// included. Interior spaces are deliberate and don't count.
+pub(crate) fn has_edge_whitespace(password: &str) -> bool {
+    password.trim().len() != password.len()
+}
+
+/// A translucent cover over the page, for overlays.
+fn overlay_style(theme: &Theme) -> container::Style {
+    container::Style {
+        background: Some(
+            theme
+                .extended_palette()
+                .background
+                .base
+                .color
+                .scale_alpha(0.85)
+                .into(),
+        ),
+        ..container::Style::default()
+    }
+}
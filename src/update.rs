@@ -0,0 +1,190 @@
+use reqwest::Client;
+use semver::Version;
+use serde::Deserialize;
+
+/// The repository's releases, from the `repository` in Cargo.toml.
+fn releases_url() -> String {
+    let repository = env!("CARGO_PKG_REPOSITORY");
+    let path = repository
+        .strip_prefix("https://github.com/")
+        .unwrap_or(repository)
+        .trim_end_matches('/');
+    format!("https://api.github.com/repos/{path}/releases?per_page=30")
+}
+
+/// A release newer than the running version.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Update {
+    pub version: Version,
+    /// The release's page on GitHub.
+    pub url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Release {
+    tag_name: String,
+    html_url: String,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    prerelease: bool,
+}
+
+/// The version a release tag names, as in `v1.2.0` or `1.2.0`. `None` for
+/// tags that aren't semver.
+pub fn parse_tag(tag: &str) -> Option<Version> {
+    let tag = tag.trim();
+    let tag = tag
+        .strip_prefix('v')
+        .or_else(|| tag.strip_prefix('V'))
+        .unwrap_or(tag);
+    Version::parse(tag).ok()
+}
+
+/// The newest release in `json`, a releases API response, if it's newer
+/// than `current`. Drafts, pre-releases (flagged or by a `-rc.1` style
+/// suffix) and tags that aren't versions are skipped.
+pub fn newest(json: &str, current: &Version) -> Result<Option<Update>, String> {
+    let releases: Vec<Release> =
+        serde_json::from_str(json).map_err(|error| format!("unexpected reply: {error}"))?;
+    Ok(releases
+        .into_iter()
+        .filter(|release| !release.draft && !release.prerelease)
+        .filter_map(|release| {
+            let version = parse_tag(&release.tag_name)?;
+            version.pre.is_empty().then_some(Update {
+                version,
+                url: release.html_url,
+            })
+        })
+        .max_by(|a, b| a.version.cmp(&b.version))
+        .filter(|update| update.version > *current))
+}
+
+/// Asks GitHub for a release newer than this build.
+pub async fn check(client: Client) -> Result<Option<Update>, String> {
+    let response = client
+        .get(releases_url())
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|error| error.to_string())?;
+    let json = response.text().await.map_err(|error| error.to_string())?;
+    let current = Version::parse(env!("CARGO_PKG_VERSION")).map_err(|error| error.to_string())?;
+    newest(&json, &current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(version: &str) -> Version {
+        Version::parse(version).unwrap()
+    }
+
+    fn release(tag: &str, draft: bool, prerelease: bool) -> String {
+        format!(
+            r#"{{"tag_name": "{tag}", "html_url": "https://github.com/o/r/releases/tag/{tag}", "draft": {draft}, "prerelease": {prerelease}}}"#
+        )
+    }
+
+    fn releases(releases: &[String]) -> String {
+        format!("[{}]", releases.join(","))
+    }
+
+    #[test]
+    fn tags_may_start_with_a_v() {
+        assert_eq!(parse_tag("v1.2.0"), Some(version("1.2.0")));
+        assert_eq!(parse_tag("V1.2.0"), Some(version("1.2.0")));
+        assert_eq!(parse_tag(" 1.2.0\n"), Some(version("1.2.0")));
+        assert_eq!(parse_tag("v2.0.0-rc.1"), Some(version("2.0.0-rc.1")));
+    }
+
+    #[test]
+    fn malformed_tags_arent_versions() {
+        for tag in [
+            "",
+            "v",
+            "latest",
+            "1.2",
+            "v1",
+            "vv1.2.0",
+            "1.2.0.0",
+            "release-1.2.0",
+        ] {
+            assert_eq!(parse_tag(tag), None, "{tag:?}");
+        }
+    }
+
+    #[test]
+    fn the_newest_release_is_offered_when_newer() {
+        let json = releases(&[
+            release("v0.9.0", false, false),
+            release("v1.10.0", false, false),
+            release("v1.9.3", false, false),
+        ]);
+        let update = newest(&json, &version("1.2.0")).unwrap().unwrap();
+        assert_eq!(update.version, version("1.10.0"));
+        assert_eq!(update.url, "https://github.com/o/r/releases/tag/v1.10.0");
+        assert_eq!(newest(&json, &version("1.10.0")), Ok(None));
+        assert_eq!(newest(&json, &version("2.0.0")), Ok(None));
+    }
+
+    #[test]
+    fn drafts_and_pre_releases_are_skipped() {
+        let json = releases(&[
+            release("v1.0.0", false, false),
+            release("v3.0.0", true, false),
+            release("v2.0.0", false, true),
+            release("v2.1.0-rc.1", false, false),
+            release("v2.1.0-beta", false, false),
+        ]);
+        let update = newest(&json, &version("0.1.0")).unwrap().unwrap();
+        assert_eq!(update.version, version("1.0.0"));
+        // A pre-release build is offered the release it led up to.
+        let json = releases(&[release("v2.1.0", false, false)]);
+        let update = newest(&json, &version("2.1.0-rc.1")).unwrap().unwrap();
+        assert_eq!(update.version, version("2.1.0"));
+    }
+
+    #[test]
+    fn malformed_tags_are_passed_over() {
+        let json = releases(&[
+            release("nightly", false, false),
+            release("v9", false, false),
+            release("v1.1.0", false, false),
+        ]);
+        let update = newest(&json, &version("1.0.0")).unwrap().unwrap();
+        assert_eq!(update.version, version("1.1.0"));
+        assert_eq!(newest("[]", &version("1.0.0")), Ok(None));
+    }
+
+    #[test]
+    fn missing_flags_mean_a_plain_release() {
+        let json = r#"[{"tag_name": "v1.1.0", "html_url": "https://example.com", "assets": []}]"#;
+        let update = newest(json, &version("1.0.0")).unwrap().unwrap();
+        assert_eq!(update.version, version("1.1.0"));
+    }
+
+    #[test]
+    fn a_reply_that_isnt_a_release_list_is_an_error() {
+        for json in [
+            "",
+            "{\"message\": \"API rate limit exceeded\"}",
+            "[{\"name\": \"v1.0.0\"}]",
+            "<html>",
+        ] {
+            let error = newest(json, &version("1.0.0")).unwrap_err();
+            assert!(error.starts_with("unexpected reply: "), "{json:?}: {error}");
+        }
+    }
+
+    #[test]
+    fn releases_are_asked_for_from_the_repository() {
+        let url = releases_url();
+        assert!(url.starts_with("https://api.github.com/repos/"), "{url}");
+        assert!(url.ends_with("/releases?per_page=30"), "{url}");
+        assert!(!url.contains("github.com/repos/https"), "{url}");
+    }
+}
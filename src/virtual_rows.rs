@@ -0,0 +1,133 @@
+use std::ops::Range;
+
+/// Rows built beyond each edge of the viewport, so a fast scroll doesn't
+/// show blank space before the next frame catches up.
+pub const OVERSCAN: usize = 10;
+
+/// A list of `rows` rows of `row_height` each, scrolled `offset` down in a
+/// viewport `height` tall.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Window {
+    pub rows: usize,
+    pub row_height: f32,
+    pub offset: f32,
+    pub height: f32,
+}
+
+impl Window {
+    /// The rows at least partly in view, plus [`OVERSCAN`] either side,
+    /// clamped to the list. An offset past the end, as the list shrinks
+    /// under a filter, still shows the last screenful.
+    pub fn visible(&self) -> Range<usize> {
+        if self.rows == 0 || self.row_height <= 0. {
+            return 0..0;
+        }
+        let in_view = (self.height.max(0.) / self.row_height).ceil() as usize + 1;
+        let first = ((self.offset.max(0.) / self.row_height) as usize)
+            .min(self.rows.saturating_sub(in_view));
+        let start = first.saturating_sub(OVERSCAN);
+        let end = (first + in_view + OVERSCAN).min(self.rows);
+        start..end
+    }
+
+    /// The height of the rows before and after `range`, for the spacers
+    /// that stand in for them.
+    pub fn spacers(&self, range: &Range<usize>) -> (f32, f32) {
+        let before = range.start as f32 * self.row_height;
+        let after = self.rows.saturating_sub(range.end) as f32 * self.row_height;
+        (before, after)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 100 rows of 24 with ten and a bit in view, scrolled `offset` down.
+    fn window(offset: f32) -> Window {
+        Window {
+            rows: 100,
+            row_height: 24.,
+            offset,
+            height: 250.,
+        }
+    }
+
+    #[test]
+    fn the_top_shows_the_first_screenful_and_the_overscan_below() {
+        // 250 / 24 rounds up to 11, plus one for a row cut at each edge.
+        assert_eq!(window(0.).visible(), 0..12 + OVERSCAN);
+        assert_eq!(window(-40.).visible(), window(0.).visible());
+    }
+
+    #[test]
+    fn rows_are_counted_from_the_one_cut_by_the_top_edge() {
+        assert_eq!(window(24. * 50.).visible(), 40..72);
+        assert_eq!(window(24. * 50. - 1.).visible(), 39..71);
+        assert_eq!(window(24. * 50. + 23.).visible(), 40..72);
+    }
+
+    #[test]
+    fn the_bottom_shows_the_last_screenful() {
+        let bottom = 100 - 12;
+        assert_eq!(window(24. * 88.).visible(), bottom - OVERSCAN..100);
+        // Past the end, as when a filter shrinks the list under the scroll.
+        assert_eq!(window(1e9).visible(), bottom - OVERSCAN..100);
+    }
+
+    #[test]
+    fn short_lists_are_shown_whole() {
+        for rows in [1, 5, 12] {
+            let window = Window {
+                rows,
+                ..window(500.)
+            };
+            assert_eq!(window.visible(), 0..rows, "{rows}");
+        }
+    }
+
+    #[test]
+    fn empty_or_degenerate_lists_show_nothing() {
+        let empty = Window {
+            rows: 0,
+            ..window(0.)
+        };
+        assert_eq!(empty.visible(), 0..0);
+        let flat = Window {
+            row_height: 0.,
+            ..window(0.)
+        };
+        assert_eq!(flat.visible(), 0..0);
+        // A viewport not laid out yet still shows the top.
+        let not_laid_out = Window {
+            height: 0.,
+            ..window(0.)
+        };
+        assert_eq!(not_laid_out.visible(), 0..1 + OVERSCAN);
+    }
+
+    #[test]
+    fn spacers_make_up_the_rest_of_the_height() {
+        for offset in [0., 700., 24. * 50., 1e9] {
+            let window = window(offset);
+            let visible = window.visible();
+            let (before, after) = window.spacers(&visible);
+            assert_eq!(before, visible.start as f32 * 24.);
+            let shown = visible.len() as f32 * 24.;
+            assert_eq!(before + shown + after, 100. * 24., "{offset}");
+        }
+    }
+
+    #[test]
+    fn rows_arriving_below_dont_move_whats_in_view() {
+        let mut window = Window {
+            rows: 60,
+            ..window(24. * 30.)
+        };
+        let before = window.visible();
+        for rows in [61, 80, 1_000] {
+            window.rows = rows;
+            assert_eq!(window.visible(), before, "{rows}");
+        }
+    }
+}
@@ -0,0 +1,20 @@
+use reqwest::Client;
+use wasm_bindgen::prelude::*;
+
+use crate::fetch::{self, DEFAULT_ENDPOINT, parse_endpoint};
+
+/// Checks `password` against Pwned Passwords, or the range API at
+/// `endpoint`, from a browser. Resolves to the check as a plain object, the
+/// same fields as a JSON export, with only the hash prefix in it.
+#[wasm_bindgen(js_name = checkPassword)]
+pub async fn check_password(
+    password: String,
+    endpoint: Option<String>,
+) -> Result<JsValue, JsError> {
+    let endpoint = parse_endpoint(endpoint.as_deref().unwrap_or(DEFAULT_ENDPOINT))
+        .map_err(|error| JsError::new(&error))?;
+    let record = fetch::check_password(&Client::new(), &endpoint, &password)
+        .await
+        .map_err(|error| JsError::new(&error.message))?;
+    serde_wasm_bindgen::to_value(&record).map_err(|error| JsError::new(&error.to_string()))
+}
@@ -0,0 +1,352 @@
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use cybersec_wow::hash::hash_prefix_of;
+use cybersec_wow::summary::{CountStyle, format_count};
+
+/// How much a count has to grow, as a share of the last one, to be
+/// reported: breach corpora are re-counted now and then, so small drifts
+/// aren't news.
+const MATERIAL_GROWTH: f64 = 0.1;
+
+/// The shortest `--interval`, so a typo can't hammer the API.
+const MIN_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Parses an interval as a whole number of seconds, minutes, hours or
+/// days: `90s`, `30m`, `24h`, `7d`.
+pub fn parse_interval(input: &str) -> Result<Duration, String> {
+    let input = input.trim();
+    let split = input
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(input.len());
+    let (number, unit) = input.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("{input:?} isn't a number followed by s, m, h or d"))?;
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => return Err(format!("{input:?} needs a unit: s, m, h or d")),
+    };
+    let interval = Duration::from_secs(number.saturating_mul(seconds));
+    if interval < MIN_INTERVAL {
+        return Err(format!(
+            "the interval is at least {} seconds",
+            MIN_INTERVAL.as_secs()
+        ));
+    }
+    Ok(interval)
+}
+
+/// A watched hash, with the line of the hash file it's on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Watched {
+    pub line: usize,
+    /// Uppercase, as [`cybersec_wow::batch::read_hashes`] leaves it.
+    pub hash: String,
+}
+
+/// Where the last-known counts for `hash_file` are kept by default: beside
+/// it, as `hashes.txt.state`.
+pub fn default_state_path(hash_file: &Path) -> PathBuf {
+    let mut name = OsString::from(hash_file.as_os_str());
+    name.push(".state");
+    name.into()
+}
+
+/// The counts each hash had when last checked, saved between cycles and
+/// runs. A hash that was never checked successfully isn't in it.
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct State {
+    /// When the last cycle ended.
+    pub checked_at: Option<DateTime<Utc>>,
+    /// By hash; 0 for not found.
+    pub counts: BTreeMap<String, u64>,
+}
+
+impl State {
+    /// The state saved at `path`, or an empty one if there's none yet.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read(path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).map_err(|error| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}: {error}", path.display()),
+                )
+            }),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Writes the state to `path`, through a temporary file so a crash
+    /// mid-write can't lose the counts.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let json = serde_json::to_vec_pretty(self).map_err(io::Error::other)?;
+        let mut temp = OsString::from(path.as_os_str());
+        temp.push(".tmp");
+        fs::write(&temp, json)?;
+        fs::rename(&temp, path)
+    }
+
+    /// Takes in a cycle's counts for `hashes`, returning what changed. A
+    /// hash whose lookup failed keeps its last count, to be tried again
+    /// next cycle, and hashes no longer watched are forgotten.
+    pub fn update(
+        &mut self,
+        hashes: &[Watched],
+        counts: &[Option<u64>],
+        now: DateTime<Utc>,
+    ) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        let mut next = BTreeMap::new();
+        for (watched, count) in hashes.iter().zip(counts) {
+            let last = self.counts.get(&watched.hash).copied();
+            let Some(count) = count.or(last) else {
+                continue;
+            };
+            if let Some(change) = diff(last, count) {
+                findings.push(Finding {
+                    line: watched.line,
+                    prefix: hash_prefix_of(&watched.hash).to_owned(),
+                    change,
+                });
+            }
+            next.insert(watched.hash.clone(), count);
+        }
+        self.counts = next;
+        self.checked_at = Some(now);
+        findings
+    }
+}
+
+/// How a watched hash got worse since it was last checked.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Change {
+    /// Clean before, or never checked, and now found.
+    Found { count: u64 },
+    /// Found before, and now materially more often.
+    Grew { from: u64, to: u64 },
+}
+
+/// What changed for a hash whose last-known count was `last` and is now
+/// `count`, if it's worth reporting.
+pub fn diff(last: Option<u64>, count: u64) -> Option<Change> {
+    match last.unwrap_or(0) {
+        _ if count == 0 => None,
+        0 => Some(Change::Found { count }),
+        from if count > from && (count - from) as f64 >= from as f64 * MATERIAL_GROWTH => {
+            Some(Change::Grew { from, to: count })
+        }
+        _ => None,
+    }
+}
+
+/// A reported change, by the line and prefix of its hash.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Finding {
+    pub line: usize,
+    pub prefix: String,
+    pub change: Change,
+}
+
+impl Finding {
+    /// A line of output: `3  5BAA6…  now found 52,256,179 times`.
+    pub fn describe(&self) -> String {
+        let count = |count| format_count(count, CountStyle::Full, ',');
+        let change = match self.change {
+            Change::Found { count: now } => format!("now found {} times", count(now)),
+            Change::Grew { from, to } => {
+                format!("seen {} times, up from {}", count(to), count(from))
+            }
+        };
+        format!("{}\t{}…\t{change}", self.line, self.prefix)
+    }
+}
+
+/// How long to wait before the next cycle, when the last ended at `last`.
+/// With no last cycle, or one longer ago than `interval`, the next is due
+/// now; one apparently in the future, as after the clock is set back,
+/// waits no longer than `interval`.
+pub fn delay(last: Option<DateTime<Utc>>, interval: Duration, now: DateTime<Utc>) -> Duration {
+    let Some(last) = last else {
+        return Duration::ZERO;
+    };
+    let elapsed = (now - last).to_std().unwrap_or(Duration::ZERO);
+    interval.saturating_sub(elapsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HUNTER2: &str = "F3BBBD66A63D4BF1747940578EC3D0103530E21D";
+    const PASSWORD: &str = "5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8";
+
+    fn at(seconds: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000 + seconds, 0).unwrap()
+    }
+
+    fn watched(hashes: &[&str]) -> Vec<Watched> {
+        (1..)
+            .zip(hashes)
+            .map(|(line, hash)| Watched {
+                line,
+                hash: (*hash).to_owned(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn intervals_take_a_unit_and_a_floor() {
+        assert_eq!(parse_interval("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_interval(" 30m "), Ok(Duration::from_secs(30 * 60)));
+        assert_eq!(parse_interval("24h"), Ok(Duration::from_secs(24 * 60 * 60)));
+        assert_eq!(
+            parse_interval("7d"),
+            Ok(Duration::from_secs(7 * 24 * 60 * 60))
+        );
+        assert_eq!(parse_interval("1m"), Ok(MIN_INTERVAL));
+        assert_eq!(
+            parse_interval("59s").unwrap_err(),
+            "the interval is at least 60 seconds"
+        );
+        assert_eq!(
+            parse_interval("10").unwrap_err(),
+            "\"10\" needs a unit: s, m, h or d"
+        );
+        assert_eq!(
+            parse_interval("h").unwrap_err(),
+            "\"h\" isn't a number followed by s, m, h or d"
+        );
+        // Too long to count is as long as can be, not a wrap to nothing.
+        assert_eq!(
+            parse_interval("18446744073709551615d"),
+            Ok(Duration::from_secs(u64::MAX))
+        );
+    }
+
+    #[test]
+    fn the_first_cycle_is_due_at_once_and_the_next_an_interval_after_it() {
+        let interval = Duration::from_secs(3600);
+        assert_eq!(delay(None, interval, at(0)), Duration::ZERO);
+        assert_eq!(
+            delay(Some(at(0)), interval, at(600)),
+            Duration::from_secs(3000)
+        );
+        assert_eq!(delay(Some(at(0)), interval, at(3600)), Duration::ZERO);
+        assert_eq!(delay(Some(at(0)), interval, at(86_400)), Duration::ZERO);
+        // After the clock is set back.
+        assert_eq!(delay(Some(at(600)), interval, at(0)), interval);
+    }
+
+    #[test]
+    fn only_new_findings_and_material_growth_are_changes() {
+        assert_eq!(diff(None, 0), None);
+        assert_eq!(diff(Some(0), 0), None);
+        assert_eq!(diff(None, 5), Some(Change::Found { count: 5 }));
+        assert_eq!(diff(Some(0), 5), Some(Change::Found { count: 5 }));
+        assert_eq!(diff(Some(100), 109), None);
+        assert_eq!(
+            diff(Some(100), 110),
+            Some(Change::Grew { from: 100, to: 110 })
+        );
+        // Counts that drop, as when a corpus is cleaned up, aren't news.
+        assert_eq!(diff(Some(100), 50), None);
+        assert_eq!(diff(Some(100), 0), None);
+    }
+
+    #[test]
+    fn a_failed_lookup_keeps_its_last_count_for_the_next_cycle() {
+        let hashes = watched(&[HUNTER2, PASSWORD]);
+        let mut state = State::default();
+        let findings = state.update(&hashes, &[Some(17), None], at(0));
+        assert_eq!(
+            findings,
+            [Finding {
+                line: 1,
+                prefix: "F3BBB".to_owned(),
+                change: Change::Found { count: 17 },
+            }]
+        );
+        assert_eq!(state.counts, BTreeMap::from([(HUNTER2.to_owned(), 17)]));
+        assert_eq!(state.checked_at, Some(at(0)));
+
+        // Failing again changes nothing; succeeding is news then.
+        assert!(state.update(&hashes, &[None, None], at(60)).is_empty());
+        assert_eq!(state.counts[HUNTER2], 17);
+        let findings = state.update(&hashes, &[Some(17), Some(3)], at(120));
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].line, 2);
+        assert_eq!(state.checked_at, Some(at(120)));
+    }
+
+    #[test]
+    fn hashes_no_longer_watched_are_forgotten() {
+        let mut state = State::default();
+        state.update(&watched(&[HUNTER2, PASSWORD]), &[Some(1), Some(0)], at(0));
+        state.update(&watched(&[PASSWORD]), &[Some(0)], at(60));
+        assert_eq!(state.counts, BTreeMap::from([(PASSWORD.to_owned(), 0)]));
+    }
+
+    #[test]
+    fn the_state_is_saved_and_loaded_as_it_was() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("hashes.txt.state");
+        assert_eq!(State::load(&path).unwrap(), State::default());
+
+        let mut state = State::default();
+        state.update(&watched(&[HUNTER2]), &[Some(17)], at(0));
+        state.save(&path).unwrap();
+        assert_eq!(State::load(&path).unwrap(), state);
+        assert!(!dir.path().join("hashes.txt.state.tmp").exists());
+    }
+
+    #[test]
+    fn a_corrupt_state_is_an_error_naming_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("hashes.txt.state");
+        fs::write(&path, "{\"counts\":").unwrap();
+        let error = State::load(&path).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(error.to_string().starts_with(&path.display().to_string()));
+    }
+
+    #[test]
+    fn the_state_is_kept_beside_the_hash_file() {
+        assert_eq!(
+            default_state_path(Path::new("audit/hashes.txt")),
+            Path::new("audit/hashes.txt.state")
+        );
+    }
+
+    #[test]
+    fn a_finding_is_described_by_line_and_prefix() {
+        let finding = |change| Finding {
+            line: 3,
+            prefix: "5BAA6".to_owned(),
+            change,
+        };
+        assert_eq!(
+            finding(Change::Found { count: 52_256_179 }).describe(),
+            "3\t5BAA6…\tnow found 52,256,179 times"
+        );
+        assert_eq!(
+            finding(Change::Grew {
+                from: 100,
+                to: 1_000
+            })
+            .describe(),
+            "3\t5BAA6…\tseen 1,000 times, up from 100"
+        );
+    }
+}